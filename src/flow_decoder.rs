@@ -0,0 +1,529 @@
+use super::*;
+
+use std::collections::{BTreeSet, HashMap};
+use std::net::SocketAddr;
+
+/// Decoded value of a field in a NetFlow v9 or IPFIX data record.
+///
+/// Fields with a known type & a valid length (including reduced size
+/// encodings of unsigned values) are decoded into typed values, all other
+/// fields are returned as [`FlowField::Unknown`] containing the raw bytes.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum FlowField<'a> {
+    OctetDeltaCount(u64),
+    PacketDeltaCount(u64),
+    ProtocolIdentifier(u8),
+    IpClassOfService(u8),
+    TcpControlBits(u16),
+    SourceTransportPort(u16),
+    SourceIpv4Address([u8;4]),
+    SourceIpv4PrefixLength(u8),
+    IngressInterface(u32),
+    DestinationTransportPort(u16),
+    DestinationIpv4Address([u8;4]),
+    DestinationIpv4PrefixLength(u8),
+    EgressInterface(u32),
+    IpNextHopIpv4Address([u8;4]),
+    BgpSourceAsNumber(u32),
+    BgpDestinationAsNumber(u32),
+    FlowEndSysUpTime(u32),
+    FlowStartSysUpTime(u32),
+    SourceIpv6Address([u8;16]),
+    DestinationIpv6Address([u8;16]),
+    SourceIpv6PrefixLength(u8),
+    DestinationIpv6PrefixLength(u8),
+    SourceMacAddress([u8;6]),
+    VlanId(u16),
+    DestinationMacAddress([u8;6]),
+    /// Reason why the flow ended (see [`FlowEndReason`] for the known values).
+    FlowEndReason(u8),
+    FlowStartSeconds(u32),
+    FlowEndSeconds(u32),
+    FlowStartMilliseconds(u64),
+    FlowEndMilliseconds(u64),
+    /// Field of an unknown type, an enterprise specific field or a field
+    /// with an unexpected length.
+    Unknown{ field_type: u16, enterprise_number: Option<u32>, data: &'a [u8] },
+}
+
+impl<'a> FlowField<'a> {
+    /// Decodes the value of a field described by the given template field.
+    pub fn decode(field: &FlowTemplateField, data: &'a [u8]) -> FlowField<'a> {
+        use flow_field_type::*;
+        use FlowField::*;
+
+        // unsigned values can be encoded with less bytes then their
+        // nominal size (reduced size encoding, rfc7011 section 6.2)
+        let unsigned = |max_len: usize| -> Option<u64> {
+            if data.is_empty() || data.len() > max_len {
+                None
+            } else {
+                Some(data.iter().fold(0u64, |acc, b| (acc << 8) | u64::from(*b)))
+            }
+        };
+        let u8_value = || unsigned(1).map(|v| v as u8);
+        let u16_value = || unsigned(2).map(|v| v as u16);
+        let u32_value = || unsigned(4).map(|v| v as u32);
+        let u64_value = || unsigned(8);
+        let ipv4 = || if 4 == data.len() {
+            Some([data[0], data[1], data[2], data[3]])
+        } else {
+            None
+        };
+        let ipv6 = || if 16 == data.len() {
+            let mut result = [0u8;16];
+            result.copy_from_slice(data);
+            Some(result)
+        } else {
+            None
+        };
+        let mac = || if 6 == data.len() {
+            Some([data[0], data[1], data[2], data[3], data[4], data[5]])
+        } else {
+            None
+        };
+
+        let result = if field.enterprise_number.is_some() {
+            None
+        } else {
+            match field.field_type {
+                OCTET_DELTA_COUNT => u64_value().map(OctetDeltaCount),
+                PACKET_DELTA_COUNT => u64_value().map(PacketDeltaCount),
+                PROTOCOL_IDENTIFIER => u8_value().map(ProtocolIdentifier),
+                IP_CLASS_OF_SERVICE => u8_value().map(IpClassOfService),
+                TCP_CONTROL_BITS => u16_value().map(TcpControlBits),
+                SOURCE_TRANSPORT_PORT => u16_value().map(SourceTransportPort),
+                SOURCE_IPV4_ADDRESS => ipv4().map(SourceIpv4Address),
+                SOURCE_IPV4_PREFIX_LENGTH => u8_value().map(SourceIpv4PrefixLength),
+                INGRESS_INTERFACE => u32_value().map(IngressInterface),
+                DESTINATION_TRANSPORT_PORT => u16_value().map(DestinationTransportPort),
+                DESTINATION_IPV4_ADDRESS => ipv4().map(DestinationIpv4Address),
+                DESTINATION_IPV4_PREFIX_LENGTH => u8_value().map(DestinationIpv4PrefixLength),
+                EGRESS_INTERFACE => u32_value().map(EgressInterface),
+                IP_NEXT_HOP_IPV4_ADDRESS => ipv4().map(IpNextHopIpv4Address),
+                BGP_SOURCE_AS_NUMBER => u32_value().map(BgpSourceAsNumber),
+                BGP_DESTINATION_AS_NUMBER => u32_value().map(BgpDestinationAsNumber),
+                FLOW_END_SYS_UP_TIME => u32_value().map(FlowEndSysUpTime),
+                FLOW_START_SYS_UP_TIME => u32_value().map(FlowStartSysUpTime),
+                SOURCE_IPV6_ADDRESS => ipv6().map(SourceIpv6Address),
+                DESTINATION_IPV6_ADDRESS => ipv6().map(DestinationIpv6Address),
+                SOURCE_IPV6_PREFIX_LENGTH => u8_value().map(SourceIpv6PrefixLength),
+                DESTINATION_IPV6_PREFIX_LENGTH => u8_value().map(DestinationIpv6PrefixLength),
+                SOURCE_MAC_ADDRESS => mac().map(SourceMacAddress),
+                VLAN_ID => u16_value().map(VlanId),
+                DESTINATION_MAC_ADDRESS => mac().map(DestinationMacAddress),
+                FLOW_END_REASON => u8_value().map(FlowEndReason),
+                FLOW_START_SECONDS => u32_value().map(FlowStartSeconds),
+                FLOW_END_SECONDS => u32_value().map(FlowEndSeconds),
+                FLOW_START_MILLISECONDS => u64_value().map(FlowStartMilliseconds),
+                FLOW_END_MILLISECONDS => u64_value().map(FlowEndMilliseconds),
+                _ => None,
+            }
+        };
+        result.unwrap_or(Unknown{
+            field_type: field.field_type,
+            enterprise_number: field.enterprise_number,
+            data,
+        })
+    }
+}
+
+/// Template (or options template) of a NetFlow v9 or IPFIX message
+/// describing the fields of the data records in the data sets with the
+/// same id.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FlowTemplate {
+    pub template_id: u16,
+    /// Number of scope fields at the start of `fields` (0 for templates
+    /// that are not options templates).
+    pub scope_field_count: u16,
+    /// Fields of the data records (an IPFIX template without fields
+    /// withdraws a previously sent template).
+    pub fields: Vec<FlowTemplateField>,
+}
+
+/// Set of a NetFlow v9 or IPFIX message.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum FlowSet<'a> {
+    /// Template or options template set.
+    Templates(Vec<FlowTemplate>),
+    /// Data set decoded with a template from the [`FlowTemplateCache`].
+    Data{ template_id: u16, records: Vec<Vec<FlowField<'a>>> },
+    /// Data set for which no template is known (contains the content of the
+    /// set after the set header).
+    UnknownTemplate{ template_id: u16, data: &'a [u8] },
+}
+
+/// Decoded NetFlow v5, NetFlow v9 or IPFIX message.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum FlowExportMessage<'a> {
+    NetflowV5{ header: NetflowV5Header, records: Vec<NetflowV5Record> },
+    NetflowV9{ header: NetflowV9Header, sets: Vec<FlowSet<'a>> },
+    Ipfix{ header: IpfixHeader, sets: Vec<FlowSet<'a>> },
+}
+
+/// Identifies the templates of one exporter observation domain.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct FlowTemplateScope {
+    /// Address & port the messages were sent from.
+    pub exporter: SocketAddr,
+    /// Message version (9 for NetFlow v9 & 10 for IPFIX).
+    pub version: u16,
+    /// NetFlow v9 source id or IPFIX observation domain id.
+    pub domain_id: u32,
+}
+
+/// Decodes NetFlow v5, NetFlow v9 & IPFIX messages & caches the templates
+/// received from the exporters (needed to decode the data sets of NetFlow
+/// v9 & IPFIX messages).
+///
+/// Templates are cached per exporter address, version & source id
+/// (observation domain). Data sets received before their template are
+/// returned as [`FlowSet::UnknownTemplate`].
+///
+/// The number of cached templates is limited (see
+/// [`FlowTemplateCache::with_max_templates`]), as the messages are usually
+/// received via unauthenticated UDP. If a new template exceeds the limit,
+/// the least recently received or used template gets removed.
+///
+/// # Example
+///
+/// ```
+/// # use etherparse::*;
+/// # let mut exporter = FlowExporter::new(FlowExporterConfig::default(), 0);
+/// # let mut table = FlowTable::new(FlowTableConfig::default());
+/// # let flow_builder = PacketBuilder::ipv4([192,168,1,1], [192,168,1,2], 20).udp(1234, 53);
+/// # let mut flow_packet = Vec::<u8>::with_capacity(flow_builder.size(0));
+/// # flow_builder.write(&mut flow_packet, &[]).unwrap();
+/// # table.process(&SlicedPacket::from_ip(&flow_packet).unwrap(), 1000);
+/// # let message = exporter.export(&table.flush(), 2000).remove(0);
+/// # let builder = PacketBuilder::ipv4([10,0,0,1], [10,0,0,2], 64).udp(4739, 4739);
+/// # let mut packet = Vec::<u8>::with_capacity(builder.size(message.len()));
+/// # builder.write(&mut packet, &message).unwrap();
+/// use std::net::{IpAddr, SocketAddr};
+///
+/// let mut cache = FlowTemplateCache::new();
+///
+/// let sliced = SlicedPacket::from_ip(&packet).unwrap();
+/// if let (Some(InternetSlice::Ipv4(ip, _)), Some(TransportSlice::Udp(udp))) = (&sliced.ip, &sliced.transport) {
+///     let exporter = SocketAddr::new(IpAddr::V4(ip.source_addr()), udp.source_port());
+///     let message = cache.decode(exporter, sliced.payload).unwrap();
+///     if let FlowExportMessage::Ipfix{ sets, .. } = message {
+///         for set in sets {
+///             if let FlowSet::Data{ records, .. } = set {
+///                 assert!(records[0].contains(&FlowField::SourceTransportPort(1234)));
+///             }
+///         }
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct FlowTemplateCache {
+    max_templates: usize,
+    /// Templates together with the value of `clock` at which they were
+    /// last received or used.
+    templates: HashMap<(FlowTemplateScope, u16), (u64, FlowTemplate)>,
+    /// Templates ordered by the time they were last received or used (used
+    /// to find the least recently used template without iterating over all
+    /// templates).
+    by_last: BTreeSet<(u64, (FlowTemplateScope, u16))>,
+    /// Counter incremented each time a template is received or used.
+    clock: u64,
+}
+
+impl Default for FlowTemplateCache {
+    fn default() -> FlowTemplateCache {
+        FlowTemplateCache::new()
+    }
+}
+
+impl FlowTemplateCache {
+    /// Default maximum number of cached templates.
+    pub const DEFAULT_MAX_TEMPLATES: usize = 65536;
+
+    /// Creates a cache without any templates (limited to
+    /// [`FlowTemplateCache::DEFAULT_MAX_TEMPLATES`] templates).
+    pub fn new() -> FlowTemplateCache {
+        FlowTemplateCache::with_max_templates(FlowTemplateCache::DEFAULT_MAX_TEMPLATES)
+    }
+
+    /// Creates a cache without any templates that holds at most
+    /// `max_templates` templates (the least recently received or used
+    /// template gets removed if a new template exceeds the limit).
+    ///
+    /// A value of 0 is treated like 1, as a received template is always
+    /// added to the cache.
+    pub fn with_max_templates(max_templates: usize) -> FlowTemplateCache {
+        FlowTemplateCache {
+            max_templates,
+            templates: HashMap::new(),
+            by_last: BTreeSet::new(),
+            clock: 0,
+        }
+    }
+
+    /// Maximum number of cached templates.
+    pub fn max_templates(&self) -> usize {
+        self.max_templates
+    }
+
+    /// Number of cached templates.
+    pub fn len(&self) -> usize {
+        self.templates.len()
+    }
+
+    /// Returns true if no templates are cached.
+    pub fn is_empty(&self) -> bool {
+        self.templates.is_empty()
+    }
+
+    /// Returns the cached template with the given id.
+    pub fn template(&self, scope: &FlowTemplateScope, template_id: u16) -> Option<&FlowTemplate> {
+        self.templates.get(&(*scope, template_id)).map(|(_, template)| template)
+    }
+
+    /// Removes all templates of the given exporter (e.g. after it restarted).
+    pub fn remove_exporter(&mut self, exporter: SocketAddr) {
+        let by_last = &mut self.by_last;
+        self.templates.retain(|key, (last, _)| {
+            let keep = key.0.exporter != exporter;
+            if !keep {
+                by_last.remove(&(*last, *key));
+            }
+            keep
+        });
+    }
+
+    /// Decodes a NetFlow v5, NetFlow v9 or IPFIX message (e.g. the payload
+    /// of a UDP packet) sent by the given exporter.
+    ///
+    /// Templates contained in the message are added to the cache before
+    /// the following data sets get decoded.
+    pub fn decode<'a>(&mut self, exporter: SocketAddr, slice: &'a [u8]) -> Result<FlowExportMessage<'a>, ReadError> {
+        use crate::ReadError::*;
+        if slice.len() < 2 {
+            return Err(UnexpectedEndOfSlice(2));
+        }
+        match read_u16(slice, 0) {
+            5 => {
+                let (header, mut rest) = NetflowV5Header::from_slice(slice)?;
+                // the count is only trusted as far as the data is present
+                let mut records = Vec::with_capacity(std::cmp::min(
+                    usize::from(header.count),
+                    rest.len() / NetflowV5Record::SERIALIZED_SIZE
+                ));
+                for i in 0..usize::from(header.count) {
+                    let (record, next) = NetflowV5Record::from_slice(rest).map_err(
+                        |err| err.add_slice_offset(NetflowV5Header::SERIALIZED_SIZE + i*NetflowV5Record::SERIALIZED_SIZE)
+                    )?;
+                    records.push(record);
+                    rest = next;
+                }
+                Ok(FlowExportMessage::NetflowV5{ header, records })
+            },
+            9 => {
+                let (header, rest) = NetflowV9Header::from_slice(slice)?;
+                let scope = FlowTemplateScope {
+                    exporter,
+                    version: 9,
+                    domain_id: header.source_id,
+                };
+                let sets = self.decode_sets(scope, rest)
+                    .map_err(|err| err.add_slice_offset(NetflowV9Header::SERIALIZED_SIZE))?;
+                Ok(FlowExportMessage::NetflowV9{ header, sets })
+            },
+            10 => {
+                let (header, rest) = IpfixHeader::from_slice(slice)?;
+                let scope = FlowTemplateScope {
+                    exporter,
+                    version: 10,
+                    domain_id: header.observation_domain_id,
+                };
+                let sets = self.decode_sets(scope, rest)
+                    .map_err(|err| err.add_slice_offset(IpfixHeader::SERIALIZED_SIZE))?;
+                Ok(FlowExportMessage::Ipfix{ header, sets })
+            },
+            version => Err(FlowExportUnsupportedVersion(version)),
+        }
+    }
+
+    fn decode_sets<'a>(&mut self, scope: FlowTemplateScope, slice: &'a [u8]) -> Result<Vec<FlowSet<'a>>, ReadError> {
+        use crate::ReadError::*;
+
+        let ipfix = 10 == scope.version;
+        let (template_set_id, options_template_set_id) = if ipfix { (2, 3) } else { (0, 1) };
+
+        let mut sets = Vec::new();
+        let mut offset = 0;
+        // NetFlow v9 messages can be padded after the last set
+        while slice.len() - offset >= 4 {
+            let set_id = read_u16(slice, offset);
+            let set_len = read_u16(slice, offset + 2);
+            if set_len < 4 {
+                return Err(FlowExportLengthTooSmall(set_len));
+            }
+            if slice.len() - offset < usize::from(set_len) {
+                return Err(UnexpectedEndOfSlice(offset + usize::from(set_len)));
+            }
+            let content = &slice[offset + 4..offset + usize::from(set_len)];
+
+            if set_id == template_set_id || set_id == options_template_set_id {
+                let templates = decode_templates(content, ipfix, set_id == options_template_set_id)
+                    .map_err(|err| err.add_slice_offset(offset + 4))?;
+                for template in &templates {
+                    let key = (scope, template.template_id);
+                    if let Some((last, _)) = self.templates.remove(&key) {
+                        self.by_last.remove(&(last, key));
+                    }
+                    if !template.fields.is_empty() {
+                        self.insert(key, template.clone());
+                    }
+                }
+                sets.push(FlowSet::Templates(templates));
+            } else if set_id >= 256 {
+                sets.push(match self.use_template(&(scope, set_id)) {
+                    Some(template) => FlowSet::Data {
+                        template_id: set_id,
+                        records: decode_records(template, ipfix, content),
+                    },
+                    None => FlowSet::UnknownTemplate {
+                        template_id: set_id,
+                        data: content,
+                    },
+                });
+            }
+            // sets with reserved ids are skipped
+
+            offset += usize::from(set_len);
+        }
+        Ok(sets)
+    }
+
+    /// Adds a template (not yet present in the cache) & removes the least
+    /// recently used template if the cache is full.
+    fn insert(&mut self, key: (FlowTemplateScope, u16), template: FlowTemplate) {
+        if self.templates.len() >= self.max_templates {
+            let oldest = self.by_last.iter().next().copied();
+            if let Some((last, oldest)) = oldest {
+                self.by_last.remove(&(last, oldest));
+                self.templates.remove(&oldest);
+            }
+        }
+        self.clock += 1;
+        self.by_last.insert((self.clock, key));
+        self.templates.insert(key, (self.clock, template));
+    }
+
+    /// Returns a template & marks it as used.
+    fn use_template(&mut self, key: &(FlowTemplateScope, u16)) -> Option<&FlowTemplate> {
+        let (last, template) = self.templates.get_mut(key)?;
+        self.clock += 1;
+        self.by_last.remove(&(*last, *key));
+        self.by_last.insert((self.clock, *key));
+        *last = self.clock;
+        Some(template)
+    }
+}
+
+/// Decodes the template records of a (options) template set.
+fn decode_templates(slice: &[u8], ipfix: bool, options: bool) -> Result<Vec<FlowTemplate>, ReadError> {
+    use crate::ReadError::*;
+
+    let header_len = if options { 6 } else { 4 };
+    let mut templates = Vec::new();
+    let mut offset = 0;
+    // the rest is padding if it is smaller then a template header
+    while slice.len() - offset >= header_len {
+        let template_id = read_u16(slice, offset);
+        let (field_count, scope_field_count) = if !options {
+            (read_u16(slice, offset + 2), 0)
+        } else if ipfix {
+            (read_u16(slice, offset + 2), read_u16(slice, offset + 4))
+        } else {
+            // NetFlow v9 options templates contain the lengths in bytes
+            let scope_len = read_u16(slice, offset + 2) / 4;
+            let options_len = read_u16(slice, offset + 4) / 4;
+            (scope_len + options_len, scope_len)
+        };
+        offset += header_len;
+
+        // the count is only trusted as far as the data is present
+        let mut fields = Vec::with_capacity(std::cmp::min(
+            usize::from(field_count),
+            (slice.len() - offset) / 4
+        ));
+        for _ in 0..field_count {
+            if slice.len() - offset < 4 {
+                return Err(UnexpectedEndOfSlice(offset + 4));
+            }
+            let field_type = read_u16(slice, offset);
+            let length = read_u16(slice, offset + 2);
+            offset += 4;
+            // ipfix enterprise specific field
+            if ipfix && 0 != field_type & 0x8000 {
+                if slice.len() - offset < 4 {
+                    return Err(UnexpectedEndOfSlice(offset + 4));
+                }
+                fields.push(FlowTemplateField {
+                    field_type: field_type & 0x7fff,
+                    length,
+                    enterprise_number: Some(read_u32(slice, offset)),
+                });
+                offset += 4;
+            } else {
+                fields.push(FlowTemplateField {
+                    field_type,
+                    length,
+                    enterprise_number: None,
+                });
+            }
+        }
+        templates.push(FlowTemplate {
+            template_id,
+            scope_field_count,
+            fields,
+        });
+    }
+    Ok(templates)
+}
+
+/// Decodes the data records of a data set (the rest of the set that is
+/// too small to contain a record is treated as padding).
+fn decode_records<'a>(template: &FlowTemplate, ipfix: bool, slice: &'a [u8]) -> Vec<Vec<FlowField<'a>>> {
+    let mut records = Vec::new();
+    let mut rest = slice;
+    'records: while !rest.is_empty() {
+        let mut record = Vec::with_capacity(template.fields.len());
+        let mut offset = 0;
+        for field in &template.fields {
+            let len = if ipfix && FLOW_FIELD_VARIABLE_LENGTH == field.length {
+                // variable length (rfc7011 section 7, not defined for NetFlow v9)
+                match rest.get(offset) {
+                    Some(255) if rest.len() >= offset + 3 => {
+                        offset += 3;
+                        usize::from(read_u16(rest, offset - 2))
+                    },
+                    Some(len) if 255 != *len => {
+                        offset += 1;
+                        usize::from(*len)
+                    },
+                    _ => break 'records,
+                }
+            } else {
+                usize::from(field.length)
+            };
+            if rest.len() < offset + len {
+                break 'records;
+            }
+            record.push(FlowField::decode(field, &rest[offset..offset + len]));
+            offset += len;
+        }
+        if 0 == offset {
+            // records without content can not be separated from padding
+            break;
+        }
+        records.push(record);
+        rest = &rest[offset..];
+    }
+    records
+}
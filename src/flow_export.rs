@@ -0,0 +1,363 @@
+use super::*;
+
+/// Message format generated by a [`FlowExporter`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum FlowExportFormat {
+    /// NetFlow v5 (only IPv4 flows can be exported, other flows are skipped).
+    NetflowV5,
+    /// NetFlow v9 (rfc3954).
+    NetflowV9,
+    /// IPFIX (rfc7011).
+    Ipfix,
+}
+
+/// Settings of a [`FlowExporter`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FlowExporterConfig {
+    pub format: FlowExportFormat,
+    /// Source id (NetFlow v9) or observation domain id (IPFIX) written to
+    /// the message headers.
+    pub source_id: u32,
+    /// Engine type written to NetFlow v5 headers.
+    pub engine_type: u8,
+    /// Engine id written to NetFlow v5 headers.
+    pub engine_id: u8,
+    /// Maximum length of a generated message in bytes (a message always
+    /// contains at least one flow record, even if it exceeds the limit).
+    pub max_message_len: usize,
+    /// Number of messages after which the templates are resent (NetFlow v9
+    /// & IPFIX). The templates are always part of the first message.
+    pub template_refresh_interval: u32,
+}
+
+impl Default for FlowExporterConfig {
+    fn default() -> FlowExporterConfig {
+        FlowExporterConfig {
+            format: FlowExportFormat::Ipfix,
+            source_id: 0,
+            engine_type: 0,
+            engine_id: 0,
+            max_message_len: 1400,
+            template_refresh_interval: 20,
+        }
+    }
+}
+
+/// Id of the template used for IPv4 flows by a [`FlowExporter`].
+pub const FLOW_EXPORT_IPV4_TEMPLATE_ID: u16 = 256;
+
+/// Id of the template used for IPv6 flows by a [`FlowExporter`].
+pub const FLOW_EXPORT_IPV6_TEMPLATE_ID: u16 = 257;
+
+/// Serializes [`FlowRecord`]s as NetFlow v5, NetFlow v9 or IPFIX messages
+/// that can be sent as UDP payloads.
+///
+/// All timestamps (the `now` values & the timestamps in the flow records)
+/// are expected to be unix timestamps in milliseconds. The NetFlow v5 & v9
+/// uptime values are calculated relative to the boot time passed to
+/// [`FlowExporter::new`].
+///
+/// NetFlow v9 & IPFIX messages use one template for IPv4 & one for IPv6
+/// flows ([`FLOW_EXPORT_IPV4_TEMPLATE_ID`] & [`FLOW_EXPORT_IPV6_TEMPLATE_ID`]),
+/// containing the addresses, ports, protocol, ToS, TCP flags, packet & byte
+/// counters, start & end time and VLAN id (0 if not present). IPFIX
+/// templates additionally contain the flow end reason.
+///
+/// # Example
+///
+/// ```
+/// # use etherparse::*;
+/// # let builder = PacketBuilder::ipv4([192,168,1,1], [192,168,1,2], 20).udp(1234, 53);
+/// # let mut packet = Vec::<u8>::with_capacity(builder.size(0));
+/// # builder.write(&mut packet, &[]).unwrap();
+/// let mut table = FlowTable::new(FlowTableConfig::default());
+/// let mut exporter = FlowExporter::new(FlowExporterConfig::default(), 1_600_000_000_000);
+///
+/// table.process(&SlicedPacket::from_ip(&packet).unwrap(), 1_600_000_001_000);
+/// let records = table.flush();
+///
+/// // send the messages to a collector via UDP
+/// for message in exporter.export(&records, 1_600_000_002_000) {
+///     let builder = PacketBuilder::ipv4([10,0,0,1], [10,0,0,2], 64)
+///         .udp(4739, 4739);
+///     let mut packet = Vec::<u8>::with_capacity(builder.size(message.len()));
+///     builder.write(&mut packet, &message).unwrap();
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct FlowExporter {
+    config: FlowExporterConfig,
+    boot_time: u64,
+    sequence_number: u32,
+    /// Number of messages sent since the templates were last sent (`None`
+    /// if they were never sent).
+    messages_since_templates: Option<u32>,
+}
+
+impl FlowExporter {
+    /// Creates an exporter with the given boot time (unix timestamp in
+    /// milliseconds used as the reference for NetFlow uptime values).
+    pub fn new(config: FlowExporterConfig, boot_time: u64) -> FlowExporter {
+        FlowExporter {
+            config,
+            boot_time,
+            sequence_number: 0,
+            messages_since_templates: None,
+        }
+    }
+
+    /// Settings of the exporter.
+    pub fn config(&self) -> &FlowExporterConfig {
+        &self.config
+    }
+
+    /// Sequence number that will be written to the next message header.
+    pub fn sequence_number(&self) -> u32 {
+        self.sequence_number
+    }
+
+    /// Returns the templates used for the IPv4 & IPv6 flows in the current
+    /// export format (empty for NetFlow v5).
+    pub fn templates(&self) -> Vec<(u16, Vec<FlowTemplateField>)> {
+        use flow_field_type::*;
+        let field = |field_type, length| FlowTemplateField{ field_type, length, enterprise_number: None };
+        let times = match self.config.format {
+            FlowExportFormat::NetflowV5 => return Vec::new(),
+            FlowExportFormat::NetflowV9 => [
+                field(FLOW_START_SYS_UP_TIME, 4),
+                field(FLOW_END_SYS_UP_TIME, 4),
+            ],
+            FlowExportFormat::Ipfix => [
+                field(FLOW_START_MILLISECONDS, 8),
+                field(FLOW_END_MILLISECONDS, 8),
+            ],
+        };
+        let template = |source, destination, address_len| {
+            let mut result = vec![
+                field(source, address_len),
+                field(destination, address_len),
+                field(SOURCE_TRANSPORT_PORT, 2),
+                field(DESTINATION_TRANSPORT_PORT, 2),
+                field(PROTOCOL_IDENTIFIER, 1),
+                field(IP_CLASS_OF_SERVICE, 1),
+                field(TCP_CONTROL_BITS, 1),
+                field(PACKET_DELTA_COUNT, 8),
+                field(OCTET_DELTA_COUNT, 8),
+                times[0],
+                times[1],
+                field(VLAN_ID, 2),
+            ];
+            if FlowExportFormat::Ipfix == self.config.format {
+                result.push(field(FLOW_END_REASON, 1));
+            }
+            result
+        };
+        vec![
+            (FLOW_EXPORT_IPV4_TEMPLATE_ID, template(SOURCE_IPV4_ADDRESS, DESTINATION_IPV4_ADDRESS, 4)),
+            (FLOW_EXPORT_IPV6_TEMPLATE_ID, template(SOURCE_IPV6_ADDRESS, DESTINATION_IPV6_ADDRESS, 16)),
+        ]
+    }
+
+    /// Serializes the given flow records into one or more messages (no
+    /// message is generated if no record can be exported).
+    pub fn export(&mut self, records: &[FlowRecord], now: u64) -> Vec<Vec<u8>> {
+        match self.config.format {
+            FlowExportFormat::NetflowV5 => self.export_v5(records, now),
+            _ => self.export_templated(records, now),
+        }
+    }
+
+    fn uptime(&self, timestamp: u64) -> u32 {
+        timestamp.saturating_sub(self.boot_time) as u32
+    }
+
+    fn export_v5(&mut self, records: &[FlowRecord], now: u64) -> Vec<Vec<u8>> {
+        let records: Vec<NetflowV5Record> = records.iter()
+            .filter_map(|record| match record.key.addresses {
+                FlowAddresses::Ipv4{ source, destination } => Some(NetflowV5Record {
+                    source,
+                    destination,
+                    packets: record.packets.min(u64::from(u32::MAX)) as u32,
+                    octets: record.bytes.min(u64::from(u32::MAX)) as u32,
+                    first: self.uptime(record.first),
+                    last: self.uptime(record.last),
+                    source_port: record.key.source_port,
+                    destination_port: record.key.destination_port,
+                    tcp_flags: record.tcp_flags,
+                    protocol: record.key.protocol,
+                    tos: record.tos,
+                    ..Default::default()
+                }),
+                FlowAddresses::Ipv6{ .. } => None,
+            })
+            .collect();
+
+        let max_records = (self.config.max_message_len.saturating_sub(NetflowV5Header::SERIALIZED_SIZE)
+            / NetflowV5Record::SERIALIZED_SIZE)
+            .clamp(1, NETFLOW_V5_MAX_RECORDS);
+
+        let mut messages = Vec::new();
+        for chunk in records.chunks(max_records) {
+            let header = NetflowV5Header {
+                count: chunk.len() as u16,
+                sys_uptime: self.uptime(now),
+                unix_secs: (now / 1000) as u32,
+                unix_nsecs: ((now % 1000) * 1_000_000) as u32,
+                flow_sequence: self.sequence_number,
+                engine_type: self.config.engine_type,
+                engine_id: self.config.engine_id,
+                sampling_interval: 0,
+            };
+            let mut message = Vec::with_capacity(
+                NetflowV5Header::SERIALIZED_SIZE + chunk.len()*NetflowV5Record::SERIALIZED_SIZE
+            );
+            message.extend_from_slice(&header.to_bytes());
+            for record in chunk {
+                message.extend_from_slice(&record.to_bytes());
+            }
+            messages.push(message);
+            self.sequence_number = self.sequence_number.wrapping_add(chunk.len() as u32);
+        }
+        messages
+    }
+
+    fn export_templated(&mut self, records: &[FlowRecord], now: u64) -> Vec<Vec<u8>> {
+        let ipfix = FlowExportFormat::Ipfix == self.config.format;
+        let header_len = if ipfix {
+            IpfixHeader::SERIALIZED_SIZE
+        } else {
+            NetflowV9Header::SERIALIZED_SIZE
+        };
+        let templates = self.templates();
+
+        let mut messages = Vec::new();
+        let mut remaining = records.iter().peekable();
+        while remaining.peek().is_some() {
+            let mut message = vec![0u8;header_len];
+            // number of template & data records
+            let mut count = 0u16;
+            let mut data_records = 0u32;
+
+            let templates_due = match self.messages_since_templates {
+                None => true,
+                Some(sent) => sent >= self.config.template_refresh_interval,
+            };
+            if templates_due {
+                let set_start = message.len();
+                message.extend_from_slice(&[0, if ipfix { 2 } else { 0 }, 0, 0]);
+                for (id, fields) in &templates {
+                    message.extend_from_slice(&id.to_be_bytes());
+                    message.extend_from_slice(&(fields.len() as u16).to_be_bytes());
+                    for field in fields {
+                        message.extend_from_slice(&field.field_type.to_be_bytes());
+                        message.extend_from_slice(&field.length.to_be_bytes());
+                    }
+                    count += 1;
+                }
+                finish_set(&mut message, set_start);
+                self.messages_since_templates = Some(0);
+            }
+
+            // start offset & template id of the currently open data set
+            let mut open_set: Option<(usize, u16)> = None;
+            while let Some(record) = remaining.peek() {
+                let template_id = match record.key.addresses {
+                    FlowAddresses::Ipv4{ .. } => FLOW_EXPORT_IPV4_TEMPLATE_ID,
+                    FlowAddresses::Ipv6{ .. } => FLOW_EXPORT_IPV6_TEMPLATE_ID,
+                };
+                let data = self.data_record(record);
+                let new_set = open_set.map(|(_, id)| id) != Some(template_id);
+                // worst case length including a new set header & padding
+                let needed = data.len() + if new_set { 4 + 3 } else { 3 };
+                if data_records > 0 && message.len() + needed > self.config.max_message_len {
+                    break;
+                }
+                if new_set {
+                    if let Some((start, _)) = open_set {
+                        finish_set(&mut message, start);
+                    }
+                    open_set = Some((message.len(), template_id));
+                    message.extend_from_slice(&template_id.to_be_bytes());
+                    message.extend_from_slice(&[0, 0]);
+                }
+                message.extend_from_slice(&data);
+                count += 1;
+                data_records += 1;
+                remaining.next();
+            }
+            if let Some((start, _)) = open_set {
+                finish_set(&mut message, start);
+            }
+
+            if ipfix {
+                let header = IpfixHeader {
+                    length: message.len() as u16,
+                    export_time: (now / 1000) as u32,
+                    sequence_number: self.sequence_number,
+                    observation_domain_id: self.config.source_id,
+                };
+                message[..header_len].copy_from_slice(&header.to_bytes());
+                self.sequence_number = self.sequence_number.wrapping_add(data_records);
+            } else {
+                let header = NetflowV9Header {
+                    count,
+                    sys_uptime: self.uptime(now),
+                    unix_secs: (now / 1000) as u32,
+                    sequence_number: self.sequence_number,
+                    source_id: self.config.source_id,
+                };
+                message[..header_len].copy_from_slice(&header.to_bytes());
+                self.sequence_number = self.sequence_number.wrapping_add(1);
+            }
+            if let Some(sent) = self.messages_since_templates.as_mut() {
+                *sent += 1;
+            }
+            messages.push(message);
+        }
+        messages
+    }
+
+    /// Serializes a flow record based on the template of the record.
+    fn data_record(&self, record: &FlowRecord) -> Vec<u8> {
+        let mut result = Vec::with_capacity(80);
+        match &record.key.addresses {
+            FlowAddresses::Ipv4{ source, destination } => {
+                result.extend_from_slice(source);
+                result.extend_from_slice(destination);
+            },
+            FlowAddresses::Ipv6{ source, destination } => {
+                result.extend_from_slice(source);
+                result.extend_from_slice(destination);
+            },
+        }
+        result.extend_from_slice(&record.key.source_port.to_be_bytes());
+        result.extend_from_slice(&record.key.destination_port.to_be_bytes());
+        result.push(record.key.protocol);
+        result.push(record.tos);
+        result.push(record.tcp_flags);
+        result.extend_from_slice(&record.packets.to_be_bytes());
+        result.extend_from_slice(&record.bytes.to_be_bytes());
+        if FlowExportFormat::Ipfix == self.config.format {
+            result.extend_from_slice(&record.first.to_be_bytes());
+            result.extend_from_slice(&record.last.to_be_bytes());
+        } else {
+            result.extend_from_slice(&self.uptime(record.first).to_be_bytes());
+            result.extend_from_slice(&self.uptime(record.last).to_be_bytes());
+        }
+        result.extend_from_slice(&record.key.vlan_id.unwrap_or(0).to_be_bytes());
+        if FlowExportFormat::Ipfix == self.config.format {
+            result.push(record.end_reason as u8);
+        }
+        result
+    }
+}
+
+/// Pads the set starting at the given offset to a multiple of 4 bytes &
+/// writes its length.
+fn finish_set(message: &mut Vec<u8>, start: usize) {
+    let padding = (4 - (message.len() - start) % 4) % 4;
+    message.resize(message.len() + padding, 0);
+    let len = ((message.len() - start) as u16).to_be_bytes();
+    message[start + 2] = len[0];
+    message[start + 3] = len[1];
+}
@@ -0,0 +1,265 @@
+use super::*;
+
+use arrayvec::ArrayVec;
+
+/// Source & destination IP addresses of a [`FlowKey`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub enum FlowAddresses {
+    Ipv4{ source: [u8;4], destination: [u8;4] },
+    Ipv6{ source: [u8;16], destination: [u8;16] },
+}
+
+impl FlowAddresses {
+    /// Returns the addresses with source & destination swapped.
+    pub fn reversed(&self) -> FlowAddresses {
+        use FlowAddresses::*;
+        match *self {
+            Ipv4{ source, destination } => Ipv4{ source: destination, destination: source },
+            Ipv6{ source, destination } => Ipv6{ source: destination, destination: source },
+        }
+    }
+}
+
+/// Maximum length of the input passed to the Toeplitz hash by
+/// [`FlowKey::rss_hash`] (two IPv6 addresses & two ports).
+pub const FLOW_KEY_MAX_RSS_INPUT_LEN: usize = 16 + 16 + 2 + 2;
+
+/// Identifies the flow (one direction of a "connection") a packet belongs to.
+///
+/// The key consists of the IP addresses, the IP protocol number following
+/// the IP header & extension headers, the transport ports and optionally
+/// the identifier of the outer VLAN header. The ports are set as follows:
+///
+/// * TCP & UDP: the source & destination port.
+/// * ICMPv4 & ICMPv6 echo request/reply: source & destination port are both
+///   set to the echo identifier (so requests & replies end up in the same
+///   bidirectional flow).
+/// * Everything else (including fragmented packets for which no transport
+///   header could be decoded): both ports are set to 0.
+///
+/// The [`std::hash::Hash`] implementation is direction dependent. Use
+/// [`FlowKey::canonical`] to get a key that is identical for both
+/// directions of a flow, or [`FlowKey::rss_hash`] with a symmetric key
+/// ([`rss::SYMMETRIC_KEY`]) to calculate a direction independent hash.
+///
+/// # Example
+///
+/// ```
+/// use etherparse::{FlowKey, PacketBuilder, SlicedPacket, ip_number};
+///
+/// let builder = PacketBuilder::ethernet2([2,3,4,5,6,7], [8,9,10,11,12,13])
+///     .ipv4([192,168,1,1], [192,168,1,2], 20)
+///     .udp(53, 1234);
+/// let mut packet = Vec::<u8>::with_capacity(builder.size(0));
+/// builder.write(&mut packet, &[]).unwrap();
+///
+/// let key = FlowKey::from_sliced_packet(
+///     &SlicedPacket::from_ethernet(&packet).unwrap()
+/// ).unwrap();
+/// assert_eq!(ip_number::UDP, key.protocol);
+/// assert_eq!((53, 1234), (key.source_port, key.destination_port));
+///
+/// // the canonical key is the same for both directions
+/// assert_eq!(key.canonical(), key.reversed().canonical());
+/// ```
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct FlowKey {
+    /// VLAN identifier of the outer VLAN header (if present).
+    pub vlan_id: Option<u16>,
+    /// Source & destination IP addresses.
+    pub addresses: FlowAddresses,
+    /// IP protocol number of the payload after the IP header & extension headers.
+    pub protocol: u8,
+    pub source_port: u16,
+    pub destination_port: u16,
+}
+
+impl FlowKey {
+    /// Extracts the flow key of a sliced packet (`None` if the packet
+    /// does not contain an IP header).
+    pub fn from_sliced_packet(packet: &SlicedPacket) -> Option<FlowKey> {
+        let vlan_id = packet.vlan.as_ref().map(|vlan| match vlan {
+            VlanSlice::SingleVlan(single) => single.vlan_identifier(),
+            VlanSlice::DoubleVlan(double) => double.outer().vlan_identifier(),
+        });
+
+        let (addresses, ip_protocol) = match &packet.ip {
+            Some(InternetSlice::Ipv4(header, extensions)) => (
+                FlowAddresses::Ipv4{
+                    source: header.source(),
+                    destination: header.destination(),
+                },
+                if let Some(trailer) = &extensions.esp_trailer {
+                    trailer.next_header()
+                } else if extensions.esp.is_some() {
+                    ip_number::ENCAP_SEC
+                } else if let Some(auth) = &extensions.auth {
+                    auth.next_header()
+                } else {
+                    header.protocol()
+                }
+            ),
+            Some(InternetSlice::Ipv6(header, extensions)) => (
+                FlowAddresses::Ipv6{
+                    source: header.source(),
+                    destination: header.destination(),
+                },
+                if let Some(trailer) = extensions.esp_trailer() {
+                    trailer.next_header()
+                } else if extensions.esp().is_some() {
+                    ip_number::ENCAP_SEC
+                } else {
+                    extensions.clone().into_iter().last()
+                        .map(|ext| ext.next_header())
+                        .unwrap_or_else(|| header.next_header())
+                }
+            ),
+            None => return None,
+        };
+
+        use TransportSlice::*;
+        let (protocol, source_port, destination_port) = match &packet.transport {
+            Some(Udp(udp)) => (ip_number::UDP, udp.source_port(), udp.destination_port()),
+            Some(Tcp(tcp)) => (ip_number::TCP, tcp.source_port(), tcp.destination_port()),
+            Some(Icmpv4(icmp)) => match icmp.icmp_type() {
+                Icmpv4Type::EchoRequest(echo) | Icmpv4Type::EchoReply(echo) => (ip_number::ICMP, echo.id, echo.id),
+                _ => (ip_number::ICMP, 0, 0),
+            },
+            Some(Icmpv6(icmp)) => match icmp.icmp_type() {
+                Icmpv6Type::EchoRequest(echo) | Icmpv6Type::EchoReply(echo) => (ip_number::IPV6_ICMP, echo.id, echo.id),
+                _ => (ip_number::IPV6_ICMP, 0, 0),
+            },
+            Some(Igmp(_)) => (ip_number::IGMP, 0, 0),
+            Some(Unknown(value)) => (*value, 0, 0),
+            None => (ip_protocol, 0, 0),
+        };
+
+        Some(FlowKey{
+            vlan_id,
+            addresses,
+            protocol,
+            source_port,
+            destination_port,
+        })
+    }
+
+    /// Extracts the flow key of decoded packet headers (`None` if the
+    /// packet does not contain an IP header or the IP extension headers
+    /// are inconsistent).
+    pub fn from_packet_headers(headers: &PacketHeaders) -> Option<FlowKey> {
+        let vlan_id = headers.vlan.as_ref().map(|vlan| match vlan {
+            VlanHeader::Single(single) => single.vlan_identifier,
+            VlanHeader::Double(double) => double.outer.vlan_identifier,
+        });
+
+        let ip = headers.ip.as_ref()?;
+        let addresses = match ip {
+            IpHeader::Version4(header, _) => FlowAddresses::Ipv4{
+                source: header.source,
+                destination: header.destination,
+            },
+            IpHeader::Version6(header, _) => FlowAddresses::Ipv6{
+                source: header.source,
+                destination: header.destination,
+            },
+        };
+
+        use TransportHeader::*;
+        let (protocol, source_port, destination_port) = match &headers.transport {
+            Some(Udp(udp)) => (ip_number::UDP, udp.source_port, udp.destination_port),
+            Some(Tcp(tcp)) => (ip_number::TCP, tcp.source_port, tcp.destination_port),
+            Some(Icmpv4(icmp)) => match icmp.icmp_type {
+                Icmpv4Type::EchoRequest(echo) | Icmpv4Type::EchoReply(echo) => (ip_number::ICMP, echo.id, echo.id),
+                _ => (ip_number::ICMP, 0, 0),
+            },
+            Some(Icmpv6(icmp)) => match icmp.icmp_type {
+                Icmpv6Type::EchoRequest(echo) | Icmpv6Type::EchoReply(echo) => (ip_number::IPV6_ICMP, echo.id, echo.id),
+                _ => (ip_number::IPV6_ICMP, 0, 0),
+            },
+            Some(Igmp(_)) => (ip_number::IGMP, 0, 0),
+            None => (ip.next_header().ok()?, 0, 0),
+        };
+
+        Some(FlowKey{
+            vlan_id,
+            addresses,
+            protocol,
+            source_port,
+            destination_port,
+        })
+    }
+
+    /// Returns the key of the opposite direction of the flow.
+    pub fn reversed(&self) -> FlowKey {
+        FlowKey{
+            vlan_id: self.vlan_id,
+            addresses: self.addresses.reversed(),
+            protocol: self.protocol,
+            source_port: self.destination_port,
+            destination_port: self.source_port,
+        }
+    }
+
+    /// Returns true if the source (address & port) is smaller or equal
+    /// to the destination (address & port).
+    pub fn is_canonical(&self) -> bool {
+        use FlowAddresses::*;
+        match &self.addresses {
+            Ipv4{ source, destination } => (source, self.source_port) <= (destination, self.destination_port),
+            Ipv6{ source, destination } => (source, self.source_port) <= (destination, self.destination_port),
+        }
+    }
+
+    /// Returns the key in a direction independent form (the direction
+    /// in which the source is smaller or equal to the destination).
+    ///
+    /// Both directions of a flow result in the same canonical key.
+    pub fn canonical(&self) -> FlowKey {
+        if self.is_canonical() {
+            *self
+        } else {
+            self.reversed()
+        }
+    }
+
+    /// Returns the key without the VLAN identifier.
+    pub fn without_vlan(&self) -> FlowKey {
+        FlowKey{
+            vlan_id: None,
+            ..*self
+        }
+    }
+
+    /// Returns the input used by network cards to calculate the RSS hash:
+    /// The source address, destination address and for TCP & UDP the
+    /// source & destination port (all in network byte order).
+    pub fn rss_input(&self) -> ArrayVec<u8, FLOW_KEY_MAX_RSS_INPUT_LEN> {
+        let mut result = ArrayVec::new();
+        match &self.addresses {
+            FlowAddresses::Ipv4{ source, destination } => {
+                result.extend(source.iter().copied());
+                result.extend(destination.iter().copied());
+            },
+            FlowAddresses::Ipv6{ source, destination } => {
+                result.extend(source.iter().copied());
+                result.extend(destination.iter().copied());
+            },
+        }
+        if ip_number::TCP == self.protocol || ip_number::UDP == self.protocol {
+            result.extend(self.source_port.to_be_bytes().iter().copied());
+            result.extend(self.destination_port.to_be_bytes().iter().copied());
+        }
+        result
+    }
+
+    /// Calculates the Toeplitz RSS hash of the key with the given RSS key
+    /// (see [`FlowKey::rss_input`] for the hashed fields).
+    ///
+    /// The result matches the hash calculated by network cards using the
+    /// same key (e.g. [`rss::MICROSOFT_KEY`]). When using
+    /// [`rss::SYMMETRIC_KEY`] both directions of a flow result in the
+    /// same hash.
+    pub fn rss_hash(&self, key: &[u8]) -> u32 {
+        rss::toeplitz_hash(key, &self.rss_input())
+    }
+}
@@ -0,0 +1,247 @@
+use super::*;
+
+use std::collections::{BTreeSet, HashMap};
+
+/// Reason why a [`FlowTable`] emitted a flow record (the values match the
+/// IPFIX `flowEndReason` information element).
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub enum FlowEndReason {
+    /// No packet was seen for the configured idle timeout.
+    IdleTimeout = 1,
+    /// The flow was active for longer then the configured active timeout
+    /// (packets following afterwards are accounted in a new record).
+    ActiveTimeout = 2,
+    /// A TCP segment with the FIN or RST flag set was seen.
+    EndOfFlow = 3,
+    /// The flow was emitted via [`FlowTable::flush`].
+    ForcedEnd = 4,
+    /// The flow was evicted as the maximum number of flows was reached.
+    LackOfResources = 5,
+}
+
+/// Statistics of a unidirectional flow accumulated by a [`FlowTable`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct FlowRecord {
+    pub key: FlowKey,
+    /// Number of packets.
+    pub packets: u64,
+    /// Number of bytes on the IP layer (IP header, extension headers & payload).
+    pub bytes: u64,
+    /// Timestamp of the first packet.
+    pub first: u64,
+    /// Timestamp of the last packet.
+    pub last: u64,
+    /// Union of the TCP flags (byte 13 of the TCP header, CWR to FIN)
+    /// of all packets (0 for non TCP flows).
+    pub tcp_flags: u8,
+    /// IPv4 type of service byte or IPv6 traffic class of the first packet.
+    pub tos: u8,
+    /// Reason why the record was emitted (set to
+    /// [`FlowEndReason::ForcedEnd`] while the flow is still active).
+    pub end_reason: FlowEndReason,
+}
+
+/// Limits & timeouts of a [`FlowTable`] (the timeouts are in the same unit as
+/// the `now` values passed to the table).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FlowTableConfig {
+    /// Maximum number of flows (the least recently active flow gets
+    /// emitted if a new flow exceeds the limit).
+    ///
+    /// A value of 0 is treated like 1, as the flow of a processed packet
+    /// is always added to the table.
+    pub max_flows: usize,
+    /// Time after the first packet after which a flow gets emitted even if
+    /// it is still active.
+    pub active_timeout: u64,
+    /// Time without packets after which a flow gets emitted.
+    pub idle_timeout: u64,
+}
+
+impl Default for FlowTableConfig {
+    fn default() -> FlowTableConfig {
+        FlowTableConfig {
+            max_flows: 65536,
+            active_timeout: 1_800_000,
+            idle_timeout: 15_000,
+        }
+    }
+}
+
+/// Bounded table accumulating per flow statistics (packets, bytes,
+/// timestamps, TCP flags & ToS) of unidirectional flows identified by
+/// their [`FlowKey`].
+///
+/// Flows are emitted as [`FlowRecord`]s when they expire, which can be
+/// exported as NetFlow or IPFIX messages via a [`FlowExporter`].
+///
+/// # Example
+///
+/// ```
+/// # use etherparse::*;
+/// # let builder = PacketBuilder::ipv4([192,168,1,1], [192,168,1,2], 20).udp(1234, 53);
+/// # let mut packet = Vec::<u8>::with_capacity(builder.size(4));
+/// # builder.write(&mut packet, &[1,2,3,4]).unwrap();
+/// let mut table = FlowTable::new(FlowTableConfig::default());
+///
+/// let packet = SlicedPacket::from_ip(&packet).unwrap();
+/// table.process(&packet, 1000);
+/// table.process(&packet, 2000);
+///
+/// // flows get emitted once they are idle for longer then the idle timeout
+/// let expired = table.expire(20_000);
+/// assert_eq!(1, expired.len());
+/// assert_eq!(2, expired[0].packets);
+/// assert_eq!(2*(20 + 8 + 4), expired[0].bytes);
+/// assert_eq!(FlowEndReason::IdleTimeout, expired[0].end_reason);
+/// ```
+#[derive(Clone, Debug)]
+pub struct FlowTable {
+    config: FlowTableConfig,
+    flows: HashMap<FlowKey, FlowRecord>,
+    /// Flows ordered by the time of their last packet (used to find the
+    /// least recently active flow without iterating over all flows).
+    by_last: BTreeSet<(u64, FlowKey)>,
+}
+
+impl FlowTable {
+    /// Creates a table without any flows.
+    pub fn new(config: FlowTableConfig) -> FlowTable {
+        FlowTable {
+            config,
+            flows: HashMap::new(),
+            by_last: BTreeSet::new(),
+        }
+    }
+
+    /// Limits & timeouts of the table.
+    pub fn config(&self) -> &FlowTableConfig {
+        &self.config
+    }
+
+    /// Number of currently active flows.
+    pub fn flow_count(&self) -> usize {
+        self.flows.len()
+    }
+
+    /// Returns the statistics of an active flow.
+    pub fn flow(&self, key: &FlowKey) -> Option<&FlowRecord> {
+        self.flows.get(key)
+    }
+
+    /// Accounts the packet to its flow & returns the flow records emitted
+    /// as a result of it (flows that reached a timeout, a flow evicted to
+    /// make room for a new flow or the flow of the packet itself if it
+    /// ended the flow).
+    ///
+    /// Packets without an IP header are ignored.
+    pub fn process(&mut self, packet: &SlicedPacket, now: u64) -> Vec<FlowRecord> {
+        let mut emitted = Vec::new();
+
+        let key = match FlowKey::from_sliced_packet(packet) {
+            Some(key) => key,
+            None => return emitted,
+        };
+        let (bytes, tos) = match &packet.ip {
+            Some(InternetSlice::Ipv4(header, _)) => (
+                u64::from(header.total_len()),
+                header.slice()[1]
+            ),
+            Some(InternetSlice::Ipv6(header, _)) => (
+                (Ipv6Header::SERIALIZED_SIZE as u64) + u64::from(header.payload_length()),
+                header.traffic_class()
+            ),
+            None => return emitted,
+        };
+        let tcp_flags = match &packet.transport {
+            Some(TransportSlice::Tcp(tcp)) => tcp.slice()[13],
+            _ => 0,
+        };
+
+        // emit the existing flow in case it timed out
+        if let Some(flow) = self.flows.get(&key) {
+            let reason = if now.saturating_sub(flow.last) >= self.config.idle_timeout {
+                Some(FlowEndReason::IdleTimeout)
+            } else if now.saturating_sub(flow.first) >= self.config.active_timeout {
+                Some(FlowEndReason::ActiveTimeout)
+            } else {
+                None
+            };
+            if let Some(reason) = reason {
+                emitted.push(self.remove(&key, reason));
+            }
+        }
+
+        if !self.flows.contains_key(&key) {
+            if self.flows.len() >= self.config.max_flows {
+                let oldest = self.by_last.iter().next().map(|(_, key)| *key);
+                if let Some(oldest) = oldest {
+                    emitted.push(self.remove(&oldest, FlowEndReason::LackOfResources));
+                }
+            }
+            self.flows.insert(key, FlowRecord {
+                key,
+                packets: 0,
+                bytes: 0,
+                first: now,
+                last: now,
+                tcp_flags: 0,
+                tos,
+                end_reason: FlowEndReason::ForcedEnd,
+            });
+            self.by_last.insert((now, key));
+        }
+
+        let flow = self.flows.get_mut(&key).unwrap();
+        flow.packets += 1;
+        flow.bytes += bytes;
+        self.by_last.remove(&(flow.last, key));
+        self.by_last.insert((now, key));
+        flow.last = now;
+        flow.tcp_flags |= tcp_flags;
+
+        // fin or rst
+        if 0 != tcp_flags & 0b101 {
+            emitted.push(self.remove(&key, FlowEndReason::EndOfFlow));
+        }
+        emitted
+    }
+
+    /// Removes all flows that reached the idle or active timeout &
+    /// returns their records.
+    pub fn expire(&mut self, now: u64) -> Vec<FlowRecord> {
+        let config = &self.config;
+        let mut expired: Vec<(FlowKey, FlowEndReason)> = self.flows.values()
+            .filter_map(|flow| {
+                if now.saturating_sub(flow.last) >= config.idle_timeout {
+                    Some((flow.key, FlowEndReason::IdleTimeout))
+                } else if now.saturating_sub(flow.first) >= config.active_timeout {
+                    Some((flow.key, FlowEndReason::ActiveTimeout))
+                } else {
+                    None
+                }
+            })
+            .collect();
+        expired.sort();
+        expired.into_iter()
+            .map(|(key, reason)| self.remove(&key, reason))
+            .collect()
+    }
+
+    /// Removes all flows & returns their records.
+    pub fn flush(&mut self) -> Vec<FlowRecord> {
+        self.by_last.clear();
+        let mut result: Vec<FlowRecord> = self.flows.drain()
+            .map(|(_, flow)| flow)
+            .collect();
+        result.sort_by_key(|flow| flow.key);
+        result
+    }
+
+    fn remove(&mut self, key: &FlowKey, reason: FlowEndReason) -> FlowRecord {
+        let mut flow = self.flows.remove(key).unwrap();
+        self.by_last.remove(&(flow.last, *key));
+        flow.end_reason = reason;
+        flow
+    }
+}
@@ -0,0 +1,657 @@
+use super::*;
+
+use arrayvec::ArrayVec;
+use std::io;
+
+/// Maximum length of an IPv4 packet containing an ICMPv4 error message (rfc1812 4.3.2.3).
+pub const ICMPV4_ERROR_MAX_PACKET_LEN: usize = 576;
+
+/// Maximum length of an IPv6 packet containing an ICMPv6 error message (rfc4443 2.4 (c)).
+pub const ICMPV6_ERROR_MAX_PACKET_LEN: usize = 1280;
+
+/// Maximum length of the original datagram quoted in an ICMPv4 error message.
+const ICMPV4_MAX_QUOTE_LEN: usize = ICMPV4_ERROR_MAX_PACKET_LEN - Ipv4Header::SERIALIZED_SIZE - Icmpv4Header::MIN_SERIALIZED_SIZE;
+
+/// Maximum length of the original datagram quoted in an ICMPv6 error message.
+const ICMPV6_MAX_QUOTE_LEN: usize = ICMPV6_ERROR_MAX_PACKET_LEN - Ipv6Header::SERIALIZED_SIZE - Icmpv6Header::MIN_SERIALIZED_SIZE;
+
+/// Errors that can occur when generating an ICMP error reply.
+#[derive(Debug)]
+pub enum IcmpErrorReplyError {
+    /// Error if the received packet has no IP header.
+    NoIpHeader,
+    /// Error if an ICMPv4 error was requested for an IPv6 packet or an
+    /// ICMPv6 error for an IPv4 packet.
+    IpVersionMismatch,
+    /// Error if the given ICMP type is not an error message type.
+    NotAnErrorType,
+    /// Error if the received packet itself is an ICMP error message
+    /// (rfc1122 3.2.2 & rfc4443 2.4 (e.1)).
+    ReplyToIcmpError,
+    /// Error if the received packet is a fragment that is not the first
+    /// fragment of a packet (rfc1122 3.2.2 & rfc4443 2.4 (e.4)).
+    NonFirstFragment,
+    /// Error if the received packet was sent to a multicast or broadcast address
+    /// or the source address does not identify a single host (rfc1122 3.2.2 &
+    /// rfc4443 2.4 (e.2), (e.3) & (e.5)).
+    MulticastOrBroadcast,
+    /// Error while writing the reply.
+    WriteError(WriteError),
+}
+
+impl fmt::Display for IcmpErrorReplyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use IcmpErrorReplyError::*;
+        match self {
+            NoIpHeader => {
+                write!(f, "IcmpErrorReplyError: The received packet has no IP header.")
+            },
+            IpVersionMismatch => {
+                write!(f, "IcmpErrorReplyError: The IP version of the received packet does not match the ICMP version of the error message (ICMPv4 is required for IPv4 & ICMPv6 for IPv6).")
+            },
+            NotAnErrorType => {
+                write!(f, "IcmpErrorReplyError: The given ICMP type is not an error message type.")
+            },
+            ReplyToIcmpError => {
+                write!(f, "IcmpErrorReplyError: ICMP error messages must not be sent in reply to ICMP error messages.")
+            },
+            NonFirstFragment => {
+                write!(f, "IcmpErrorReplyError: ICMP error messages must not be sent in reply to fragments other then the first fragment.")
+            },
+            MulticastOrBroadcast => {
+                write!(f, "IcmpErrorReplyError: ICMP error messages must not be sent in reply to packets sent to a multicast or broadcast address or from an address not identifying a single host.")
+            },
+            WriteError(err) => err.fmt(f),
+        }
+    }
+}
+
+impl Error for IcmpErrorReplyError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            IcmpErrorReplyError::WriteError(ref err) => Some(err),
+            _ => None
+        }
+    }
+}
+
+impl From<WriteError> for IcmpErrorReplyError {
+    fn from(err: WriteError) -> IcmpErrorReplyError {
+        IcmpErrorReplyError::WriteError(err)
+    }
+}
+
+impl From<io::Error> for IcmpErrorReplyError {
+    fn from(err: io::Error) -> IcmpErrorReplyError {
+        IcmpErrorReplyError::WriteError(WriteError::IoError(err))
+    }
+}
+
+/// Generates ICMPv4 & ICMPv6 error messages in reply to received packets.
+///
+/// The reply contains the headers of the received packet with swapped addresses
+/// (ethernet II & vlan headers are only present if they were present in the
+/// received packet), followed by the ICMP error message quoting as much of the
+/// received IP packet as possible without exceeding 576 bytes (IPv4) or 1280
+/// bytes (IPv6). All lengths & checksums are calculated.
+///
+/// As the reply is written to a given writer, a buffer can be reused for multiple
+/// replies (e.g. when rate limiting the number of error messages).
+///
+/// No reply is generated (an error is returned instead) if the rfcs forbid sending
+/// an ICMP error for the received packet (e.g. if the received packet is an ICMP
+/// error message itself or was sent to a multicast address).
+///
+/// # Example
+///
+/// ```
+/// # use etherparse::*;
+/// # let builder = PacketBuilder::
+/// #    ethernet2([2,3,4,5,6,7], [8,9,10,11,12,13])
+/// #    .ipv4([192,168,1,1], [192,168,1,2], 20)
+/// #    .udp(21, 1234);
+/// # let mut packet = Vec::<u8>::with_capacity(builder.size(4));
+/// # builder.write(&mut packet, &[1,2,3,4]).unwrap();
+/// let received = SlicedPacket::from_ethernet(&packet).unwrap();
+///
+/// // the buffer can be reused for multiple replies
+/// let mut buffer = Vec::with_capacity(1500);
+/// IcmpErrorReplyBuilder::new()
+///     .write_icmpv4(
+///         &mut buffer,
+///         &received,
+///         Icmpv4Type::DestinationUnreachable(icmpv4::DestUnreachableHeader::Port)
+///     )
+///     .unwrap();
+///
+/// let reply = SlicedPacket::from_ethernet(&buffer).unwrap();
+/// assert!(reply.transport.is_some());
+/// # buffer.clear();
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct IcmpErrorReplyBuilder {
+    time_to_live: u8,
+    ipv4_source: Option<[u8;4]>,
+    ipv6_source: Option<[u8;16]>,
+}
+
+impl Default for IcmpErrorReplyBuilder {
+    fn default() -> IcmpErrorReplyBuilder {
+        IcmpErrorReplyBuilder::new()
+    }
+}
+
+impl IcmpErrorReplyBuilder {
+    /// Creates a builder with a time to live (hop limit) of 64 that uses
+    /// the destination address of the received packet as source address.
+    pub fn new() -> IcmpErrorReplyBuilder {
+        IcmpErrorReplyBuilder {
+            time_to_live: 64,
+            ipv4_source: None,
+            ipv6_source: None,
+        }
+    }
+
+    /// Set the time to live (IPv4) or hop limit (IPv6) of the replies.
+    pub fn time_to_live(mut self, value: u8) -> IcmpErrorReplyBuilder {
+        self.time_to_live = value;
+        self
+    }
+
+    /// Set the source address of IPv4 replies (e.g. the address of the
+    /// interface of a router the received packet arrived on).
+    pub fn ipv4_source(mut self, value: [u8;4]) -> IcmpErrorReplyBuilder {
+        self.ipv4_source = Some(value);
+        self
+    }
+
+    /// Set the source address of IPv6 replies (e.g. the address of the
+    /// interface of a router the received packet arrived on).
+    pub fn ipv6_source(mut self, value: [u8;16]) -> IcmpErrorReplyBuilder {
+        self.ipv6_source = Some(value);
+        self
+    }
+
+    /// Writes an ICMPv4 error message in reply to the received IPv4 packet.
+    ///
+    /// The `icmp_type` has to be an error message type (`DestinationUnreachable`,
+    /// `Redirect`, `TimeExceeded` or `ParameterProblem`).
+    pub fn write_icmpv4<T: io::Write + Sized>(&self, writer: &mut T, received: &SlicedPacket, icmp_type: Icmpv4Type) -> Result<(), IcmpErrorReplyError> {
+        use IcmpErrorReplyError::*;
+
+        // check the icmp type
+        match icmp_type {
+            Icmpv4Type::DestinationUnreachable(_) |
+            Icmpv4Type::Redirect(_) |
+            Icmpv4Type::TimeExceeded(_) |
+            Icmpv4Type::ParameterProblem(_) => {},
+            _ => return Err(NotAnErrorType),
+        }
+
+        let (ip, ext) = match &received.ip {
+            Some(InternetSlice::Ipv4(ip, ext)) => (ip, ext),
+            Some(InternetSlice::Ipv6(_, _)) => return Err(IpVersionMismatch),
+            None => return Err(NoIpHeader),
+        };
+
+        // check that a reply is allowed
+        if 0 != ip.fragments_offset() {
+            return Err(NonFirstFragment);
+        }
+        if ip.destination()[0] >= 224 || ip.source()[0] >= 224 || [0;4] == ip.source() || is_link_multicast(received) {
+            return Err(MulticastOrBroadcast);
+        }
+        if is_icmp_error(received) {
+            return Err(ReplyToIcmpError);
+        }
+
+        // collect the original datagram
+        let mut quoted = ArrayVec::<u8, ICMPV4_MAX_QUOTE_LEN>::new();
+        {
+            let max_len = usize::from(ip.total_len());
+            extend_limited(&mut quoted, max_len, ip.slice());
+            if let Some(auth) = &ext.auth {
+                extend_limited(&mut quoted, max_len, auth.slice());
+            }
+            if let (Some(esp), Some(_)) = (&ext.esp, &ext.esp_trailer) {
+                extend_limited(&mut quoted, max_len, esp.slice());
+            }
+            extend_transport_and_payload(&mut quoted, max_len, received);
+        }
+
+        // write the reply
+        write_link_headers(writer, received)?;
+        Ipv4Header::new(
+            (Icmpv4Header::MIN_SERIALIZED_SIZE + quoted.len()) as u16,
+            self.time_to_live,
+            ip_number::ICMP,
+            self.ipv4_source.unwrap_or_else(|| ip.destination()),
+            ip.source()
+        ).write(writer)?;
+        Icmpv4Header::with_checksum(icmp_type, &quoted).write(writer)?;
+        writer.write_all(&quoted)?;
+        Ok(())
+    }
+
+    /// Writes an ICMPv6 error message in reply to the received IPv6 packet.
+    ///
+    /// The `icmp_type` has to be an error message type (`DestinationUnreachable`,
+    /// `PacketTooBig`, `TimeExceeded` or `ParameterProblem`).
+    pub fn write_icmpv6<T: io::Write + Sized>(&self, writer: &mut T, received: &SlicedPacket, icmp_type: Icmpv6Type) -> Result<(), IcmpErrorReplyError> {
+        use IcmpErrorReplyError::*;
+
+        // check the icmp type & if replies to multicast packets are allowed
+        // (rfc4443 2.4 (e.3))
+        let multicast_allowed = match icmp_type {
+            Icmpv6Type::DestinationUnreachable(_) |
+            Icmpv6Type::TimeExceeded(_) => false,
+            Icmpv6Type::PacketTooBig{ .. } => true,
+            Icmpv6Type::ParameterProblem(header) => {
+                icmpv6::ParameterProblemCode::UnrecognizedIpv6Option == header.code
+            },
+            _ => return Err(NotAnErrorType),
+        };
+
+        let (ip, ext) = match &received.ip {
+            Some(InternetSlice::Ipv6(ip, ext)) => (ip, ext),
+            Some(InternetSlice::Ipv4(_, _)) => return Err(IpVersionMismatch),
+            None => return Err(NoIpHeader),
+        };
+
+        // check that a reply is allowed
+        let non_first_fragment = Ipv6ExtensionsChainIter::new(ip.next_header(), ext.slice())
+            .filter_map(|header| header.ok())
+            .any(|header| matches!(header, Ipv6ExtensionSlice::Fragment(fragment) if 0 != fragment.fragment_offset()));
+        if non_first_fragment {
+            return Err(NonFirstFragment);
+        }
+        if 0xff == ip.source()[0] || [0;16] == ip.source() {
+            return Err(MulticastOrBroadcast);
+        }
+        if !multicast_allowed && (0xff == ip.destination()[0] || is_link_multicast(received)) {
+            return Err(MulticastOrBroadcast);
+        }
+        if is_icmp_error(received) {
+            return Err(ReplyToIcmpError);
+        }
+
+        // collect the original datagram
+        let mut quoted = ArrayVec::<u8, ICMPV6_MAX_QUOTE_LEN>::new();
+        {
+            let max_len = match ip.payload_length() {
+                // jumbogram
+                0 => usize::MAX,
+                value => Ipv6Header::SERIALIZED_SIZE + usize::from(value),
+            };
+            extend_limited(&mut quoted, max_len, ip.slice());
+            extend_limited(&mut quoted, max_len, ext.slice());
+            if let (Some(esp), Some(_)) = (ext.esp(), ext.esp_trailer()) {
+                extend_limited(&mut quoted, max_len, esp.slice());
+            }
+            extend_transport_and_payload(&mut quoted, max_len, received);
+        }
+
+        // write the reply
+        let source = self.ipv6_source.unwrap_or_else(|| ip.destination());
+        let destination = ip.source();
+        write_link_headers(writer, received)?;
+        Ipv6Header {
+            traffic_class: 0,
+            flow_label: 0,
+            payload_length: (Icmpv6Header::MIN_SERIALIZED_SIZE + quoted.len()) as u16,
+            next_header: ip_number::IPV6_ICMP,
+            hop_limit: self.time_to_live,
+            source,
+            destination,
+        }.write(writer)?;
+        Icmpv6Header::with_checksum(icmp_type, source, destination, &quoted)
+            .map_err(crate::WriteError::ValueError)?
+            .write(writer)?;
+        writer.write_all(&quoted)?;
+        Ok(())
+    }
+}
+
+/// Errors that can occur when generating an ICMP echo reply.
+#[derive(Debug)]
+pub enum IcmpEchoReplyError {
+    /// Error if the packet is not an ICMPv4 echo request in an IPv4 packet
+    /// or an ICMPv6 echo request in an IPv6 packet.
+    NotAnEchoRequest,
+    /// Error while slicing the packet (only returned when replying in place).
+    ReadError(ReadError),
+    /// Error while writing the reply.
+    WriteError(WriteError),
+}
+
+impl fmt::Display for IcmpEchoReplyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use IcmpEchoReplyError::*;
+        match self {
+            NotAnEchoRequest => {
+                write!(f, "IcmpEchoReplyError: The packet is not an ICMPv4 echo request in an IPv4 packet or an ICMPv6 echo request in an IPv6 packet.")
+            },
+            ReadError(err) => err.fmt(f),
+            WriteError(err) => err.fmt(f),
+        }
+    }
+}
+
+impl Error for IcmpEchoReplyError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            IcmpEchoReplyError::NotAnEchoRequest => None,
+            IcmpEchoReplyError::ReadError(ref err) => Some(err),
+            IcmpEchoReplyError::WriteError(ref err) => Some(err),
+        }
+    }
+}
+
+impl From<ReadError> for IcmpEchoReplyError {
+    fn from(err: ReadError) -> IcmpEchoReplyError {
+        IcmpEchoReplyError::ReadError(err)
+    }
+}
+
+impl From<WriteError> for IcmpEchoReplyError {
+    fn from(err: WriteError) -> IcmpEchoReplyError {
+        IcmpEchoReplyError::WriteError(err)
+    }
+}
+
+impl From<io::Error> for IcmpEchoReplyError {
+    fn from(err: io::Error) -> IcmpEchoReplyError {
+        IcmpEchoReplyError::WriteError(WriteError::IoError(err))
+    }
+}
+
+/// Writes the ICMPv4 or ICMPv6 echo reply to a received echo request.
+///
+/// The reply contains the ethernet II & vlan headers of the request with swapped
+/// addresses (if present), a new IP header with swapped addresses & the given
+/// time to live (hop limit) and an echo reply with the identifier, sequence number
+/// and payload of the request. IPv4 options & IPv6 extension headers of the request
+/// are not copied. All lengths & checksums are calculated.
+///
+/// # Example
+///
+/// ```
+/// # use etherparse::*;
+/// # let builder = PacketBuilder::
+/// #    ethernet2([2,3,4,5,6,7], [8,9,10,11,12,13])
+/// #    .ipv4([192,168,1,1], [192,168,1,2], 20)
+/// #    .icmpv4_echo_request(1, 2);
+/// # let mut packet = Vec::<u8>::with_capacity(builder.size(4));
+/// # builder.write(&mut packet, &[1,2,3,4]).unwrap();
+/// let request = SlicedPacket::from_ethernet(&packet).unwrap();
+///
+/// let mut buffer = Vec::with_capacity(1500);
+/// write_icmp_echo_reply(&mut buffer, &request, 64).unwrap();
+///
+/// let reply = SlicedPacket::from_ethernet(&buffer).unwrap();
+/// assert!(reply.transport.is_some());
+/// ```
+pub fn write_icmp_echo_reply<T: io::Write + Sized>(writer: &mut T, request: &SlicedPacket, time_to_live: u8) -> Result<(), IcmpEchoReplyError> {
+    use std::convert::TryFrom;
+    use IcmpEchoReplyError::*;
+    match (&request.ip, &request.transport) {
+        (Some(InternetSlice::Ipv4(ip, _)), Some(TransportSlice::Icmpv4(icmp))) => {
+            // cut off data following the ip payload (e.g. ethernet padding)
+            let icmp = Icmpv4Slice::from_slice(request.limit_to_ip_payload(icmp.slice()))?;
+            let echo = match icmp.icmp_type() {
+                Icmpv4Type::EchoRequest(echo) => echo,
+                _ => return Err(NotAnEchoRequest),
+            };
+            let icmp_header = Icmpv4Header::with_checksum(Icmpv4Type::EchoReply(echo), icmp.payload());
+            write_link_headers(writer, request)?;
+            // the length always fits as it was read from an ipv4 header
+            Ipv4Header::new(
+                (icmp_header.header_len() + icmp.payload().len()) as u16,
+                time_to_live,
+                ip_number::ICMP,
+                ip.destination(),
+                ip.source()
+            ).write(writer)?;
+            icmp_header.write(writer)?;
+            writer.write_all(icmp.payload())?;
+            Ok(())
+        },
+        (Some(InternetSlice::Ipv6(ip, _)), Some(TransportSlice::Icmpv6(icmp))) => {
+            // cut off data following the ip payload (e.g. ethernet padding)
+            let icmp = Icmpv6Slice::from_slice(request.limit_to_ip_payload(icmp.slice()))?;
+            let echo = match icmp.icmp_type() {
+                Icmpv6Type::EchoRequest(echo) => echo,
+                _ => return Err(NotAnEchoRequest),
+            };
+            let source = ip.destination();
+            let destination = ip.source();
+            let icmp_header = Icmpv6Header::with_checksum(Icmpv6Type::EchoReply(echo), source, destination, icmp.payload())
+                .map_err(crate::WriteError::ValueError)?;
+            let payload_len = icmp_header.header_len() + icmp.payload().len();
+            let payload_length = u16::try_from(payload_len)
+                .map_err(|_| crate::WriteError::ValueError(ValueError::Ipv6PayloadLengthTooLarge(payload_len)))?;
+            write_link_headers(writer, request)?;
+            Ipv6Header {
+                traffic_class: 0,
+                flow_label: 0,
+                payload_length,
+                next_header: ip_number::IPV6_ICMP,
+                hop_limit: time_to_live,
+                source,
+                destination,
+            }.write(writer)?;
+            icmp_header.write(writer)?;
+            writer.write_all(icmp.payload())?;
+            Ok(())
+        },
+        _ => Err(NotAnEchoRequest),
+    }
+}
+
+/// Turns the ICMPv4 or ICMPv6 echo request in the given ethernet II frame into
+/// the matching echo reply (without copying the packet).
+///
+/// The ethernet & IP addresses are swapped, the time to live (hop limit) is set
+/// to the given value, the ICMP type is changed to echo reply and all checksums
+/// are recalculated. All other fields (including IPv4 options, IPv6 extension
+/// headers, the identifier, sequence number & payload) are kept unchanged.
+///
+/// # Example
+///
+/// ```
+/// # use etherparse::*;
+/// # let builder = PacketBuilder::
+/// #    ethernet2([2,3,4,5,6,7], [8,9,10,11,12,13])
+/// #    .ipv6([1;16], [2;16], 20)
+/// #    .icmpv6_echo_request(1, 2);
+/// # let mut packet = Vec::<u8>::with_capacity(builder.size(4));
+/// # builder.write(&mut packet, &[1,2,3,4]).unwrap();
+/// icmp_echo_reply_in_place_ethernet(&mut packet, 64).unwrap();
+///
+/// let reply = SlicedPacket::from_ethernet(&packet).unwrap();
+/// assert!(reply.transport.is_some());
+/// ```
+pub fn icmp_echo_reply_in_place_ethernet(packet: &mut [u8], time_to_live: u8) -> Result<(), IcmpEchoReplyError> {
+    echo_reply_in_place(packet, time_to_live, |slice| SlicedPacket::from_ethernet(slice))
+}
+
+/// Turns the ICMPv4 or ICMPv6 echo request in the given IP packet into the matching
+/// echo reply (without copying the packet).
+///
+/// Behaves like [`icmp_echo_reply_in_place_ethernet`] for packets starting with an
+/// IPv4 or IPv6 header (e.g. packets read from a tun interface).
+pub fn icmp_echo_reply_in_place_ip(packet: &mut [u8], time_to_live: u8) -> Result<(), IcmpEchoReplyError> {
+    echo_reply_in_place(packet, time_to_live, |slice| SlicedPacket::from_ip(slice))
+}
+
+/// Offsets of the headers that have to be modified to turn an echo request into
+/// an echo reply.
+struct EchoReplyOffsets {
+    ethernet: Option<usize>,
+    ip: usize,
+    ipv4_header_len: Option<usize>,
+    icmp: usize,
+    icmp_len: usize,
+}
+
+fn echo_reply_in_place(
+    packet: &mut [u8],
+    time_to_live: u8,
+    slice_packet: fn(&[u8]) -> Result<SlicedPacket, ReadError>
+) -> Result<(), IcmpEchoReplyError> {
+    use IcmpEchoReplyError::*;
+
+    // determine the offsets of the headers
+    let offsets = {
+        let base = packet.as_ptr() as usize;
+        let offset = |slice: &[u8]| slice.as_ptr() as usize - base;
+        let request = slice_packet(packet)?;
+        let ethernet = request.link.as_ref().map(|link| match link {
+            LinkSlice::Ethernet2(eth) => offset(eth.slice()),
+        });
+        match (&request.ip, &request.transport) {
+            (Some(InternetSlice::Ipv4(ip, _)), Some(TransportSlice::Icmpv4(icmp)))
+                if icmpv4::TYPE_ECHO_REQUEST == icmp.type_u8() && 0 == icmp.code_u8() =>
+            {
+                EchoReplyOffsets {
+                    ethernet,
+                    ip: offset(ip.slice()),
+                    ipv4_header_len: Some(ip.slice().len()),
+                    icmp: offset(icmp.slice()),
+                    // cut off data following the ip payload (e.g. ethernet padding)
+                    icmp_len: Icmpv4Slice::from_slice(request.limit_to_ip_payload(icmp.slice()))?.slice().len(),
+                }
+            },
+            (Some(InternetSlice::Ipv6(ip, _)), Some(TransportSlice::Icmpv6(icmp)))
+                if icmpv6::TYPE_ECHO_REQUEST == icmp.type_u8() && 0 == icmp.code_u8() =>
+            {
+                EchoReplyOffsets {
+                    ethernet,
+                    ip: offset(ip.slice()),
+                    ipv4_header_len: None,
+                    icmp: offset(icmp.slice()),
+                    // cut off data following the ip payload (e.g. ethernet padding)
+                    icmp_len: Icmpv6Slice::from_slice(request.limit_to_ip_payload(icmp.slice()))?.slice().len(),
+                }
+            },
+            _ => return Err(NotAnEchoRequest),
+        }
+    };
+
+    // swap the ethernet addresses
+    if let Some(start) = offsets.ethernet {
+        let (destination, source) = packet[start..start + 12].split_at_mut(6);
+        destination.swap_with_slice(source);
+    }
+
+    // update the ip header & icmp message
+    let icmp = &mut packet[offsets.icmp..offsets.icmp + offsets.icmp_len];
+    icmp[2] = 0;
+    icmp[3] = 0;
+    let icmp_checksum = if let Some(header_len) = offsets.ipv4_header_len {
+        let ip = &mut packet[offsets.ip..offsets.ip + header_len];
+        let (source, destination) = ip[12..20].split_at_mut(4);
+        source.swap_with_slice(destination);
+        ip[8] = time_to_live;
+        ip[10] = 0;
+        ip[11] = 0;
+        let ip_checksum = checksum::Sum16BitWords::new()
+            .add_slice(ip)
+            .ones_complement()
+            .to_be();
+        ip[10..12].copy_from_slice(&ip_checksum.to_be_bytes());
+
+        let icmp = &mut packet[offsets.icmp..offsets.icmp + offsets.icmp_len];
+        icmp[0] = icmpv4::TYPE_ECHO_REPLY;
+        checksum::Sum16BitWords::new()
+            .add_slice(icmp)
+            .ones_complement()
+            .to_be()
+    } else {
+        let ip = &mut packet[offsets.ip..offsets.ip + Ipv6Header::SERIALIZED_SIZE];
+        let (source, destination) = ip[8..40].split_at_mut(16);
+        source.swap_with_slice(destination);
+        ip[7] = time_to_live;
+        let mut source = [0u8;16];
+        source.copy_from_slice(&ip[8..24]);
+        let mut destination = [0u8;16];
+        destination.copy_from_slice(&ip[24..40]);
+
+        let icmp = &mut packet[offsets.icmp..offsets.icmp + offsets.icmp_len];
+        icmp[0] = icmpv6::TYPE_ECHO_REPLY;
+        // the length always fits into 32 bits as it was read from the packet
+        checksum::Sum16BitWords::new()
+            .add_16bytes(source)
+            .add_16bytes(destination)
+            .add_2bytes([0, ip_number::IPV6_ICMP])
+            .add_4bytes((icmp.len() as u32).to_be_bytes())
+            .add_slice(icmp)
+            .ones_complement()
+            .to_be()
+    };
+    packet[offsets.icmp + 2..offsets.icmp + 4].copy_from_slice(&icmp_checksum.to_be_bytes());
+    Ok(())
+}
+
+/// Returns true if the received packet was sent to an ethernet multicast
+/// or broadcast address.
+fn is_link_multicast(received: &SlicedPacket) -> bool {
+    match &received.link {
+        Some(LinkSlice::Ethernet2(eth)) => 0 != eth.destination()[0] & 1,
+        None => false,
+    }
+}
+
+/// Returns true if the received packet is an ICMP error message.
+fn is_icmp_error(received: &SlicedPacket) -> bool {
+    use icmpv4::*;
+    match &received.transport {
+        Some(TransportSlice::Icmpv4(icmp)) => matches!(
+            icmp.type_u8(),
+            TYPE_DEST_UNREACH | TYPE_SOURCE_QUENCH | TYPE_REDIRECT | TYPE_TIME_EXCEEDED | TYPE_PARAMETER_PROBLEM
+        ),
+        // all ICMPv6 types smaller then 128 are error messages (rfc4443 2.1)
+        Some(TransportSlice::Icmpv6(icmp)) => icmp.type_u8() < 128,
+        _ => false,
+    }
+}
+
+/// Appends as much of `data` to `target` as possible without exceeding the
+/// capacity of `target` or `max_len`.
+fn extend_limited<const CAP: usize>(target: &mut ArrayVec<u8, CAP>, max_len: usize, data: &[u8]) {
+    let len = std::cmp::min(
+        data.len(),
+        std::cmp::min(CAP, max_len).saturating_sub(target.len())
+    );
+    // unwrap is safe as the length was limited to the remaining capacity above
+    target.try_extend_from_slice(&data[..len]).unwrap();
+}
+
+/// Appends the transport header & payload of the received packet.
+fn extend_transport_and_payload<const CAP: usize>(target: &mut ArrayVec<u8, CAP>, max_len: usize, received: &SlicedPacket) {
+    use TransportSlice::*;
+    match &received.transport {
+        Some(Icmpv4(value)) => extend_limited(target, max_len, value.slice()),
+        Some(Icmpv6(value)) => extend_limited(target, max_len, value.slice()),
+        Some(Igmp(value)) => extend_limited(target, max_len, value.slice()),
+        Some(Udp(value)) => extend_limited(target, max_len, value.slice()),
+        Some(Tcp(value)) => extend_limited(target, max_len, value.slice()),
+        Some(Unknown(_)) | None => {},
+    }
+    extend_limited(target, max_len, received.payload);
+}
+
+/// Writes the ethernet II (with swapped addresses) & vlan headers of the received packet.
+fn write_link_headers<T: io::Write + Sized>(writer: &mut T, received: &SlicedPacket) -> Result<(), WriteError> {
+    if let Some(LinkSlice::Ethernet2(eth)) = &received.link {
+        Ethernet2Header {
+            source: eth.destination(),
+            destination: eth.source(),
+            ether_type: eth.ether_type(),
+        }.write(writer)?;
+    }
+    if let Some(vlan) = &received.vlan {
+        vlan.to_header().write(writer)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,215 @@
+use super::super::*;
+
+use std::slice::from_raw_parts;
+
+/// Unencrypted start of an IP Encapsulating Security Payload (rfc4303)
+/// containing the security parameters index & sequence number.
+///
+/// Everything following these two fields (IV, payload, padding, trailer
+/// & integrity check value) is protected by the security association and
+/// can in general not be decoded without knowing the used algorithms.
+#[derive(Clone, Debug, Eq, PartialEq, Default)]
+pub struct EspHeader {
+    /// Security Parameters Index
+    pub spi: u32,
+    /// This unsigned 32-bit field contains a counter value that
+    /// increases by one for each packet sent.
+    pub sequence_number: u32,
+}
+
+impl EspHeader {
+    /// Serialized size of the unencrypted ESP header part in bytes/octets.
+    pub const SERIALIZED_SIZE: usize = 8;
+
+    /// Read an ESP header from a slice and return the header & unused parts of the slice.
+    pub fn from_slice(slice: &[u8]) -> Result<(EspHeader, &[u8]), ReadError> {
+        let s = EspHeaderSlice::from_slice(slice)?;
+        let rest = &slice[EspHeader::SERIALIZED_SIZE..];
+        Ok((s.to_header(), rest))
+    }
+
+    /// Read an ESP header from the current reader position.
+    pub fn read<T: io::Read + Sized>(reader: &mut T) -> Result<EspHeader, ReadError> {
+        let mut buffer = [0; EspHeader::SERIALIZED_SIZE];
+        reader.read_exact(&mut buffer)?;
+        Ok(EspHeader {
+            spi: u32::from_be_bytes([buffer[0], buffer[1], buffer[2], buffer[3]]),
+            sequence_number: u32::from_be_bytes([buffer[4], buffer[5], buffer[6], buffer[7]]),
+        })
+    }
+
+    /// Writes the ESP header to the current position.
+    pub fn write<T: io::Write + Sized>(&self, writer: &mut T) -> Result<(), WriteError> {
+        writer.write_all(&self.to_bytes())?;
+        Ok(())
+    }
+
+    /// Returns the serialized form of the header.
+    pub fn to_bytes(&self) -> [u8; EspHeader::SERIALIZED_SIZE] {
+        let spi_be = self.spi.to_be_bytes();
+        let sequence_number_be = self.sequence_number.to_be_bytes();
+        [
+            spi_be[0],
+            spi_be[1],
+            spi_be[2],
+            spi_be[3],
+            sequence_number_be[0],
+            sequence_number_be[1],
+            sequence_number_be[2],
+            sequence_number_be[3],
+        ]
+    }
+
+    /// Length of the header in bytes.
+    #[inline]
+    pub fn header_len(&self) -> usize {
+        EspHeader::SERIALIZED_SIZE
+    }
+}
+
+/// A slice containing the unencrypted start of an IP Encapsulating
+/// Security Payload (rfc4303).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EspHeaderSlice<'a> {
+    slice: &'a [u8],
+}
+
+impl<'a> EspHeaderSlice<'a> {
+    /// Creates a ESP header slice from a slice.
+    pub fn from_slice(slice: &'a [u8]) -> Result<EspHeaderSlice<'a>, ReadError> {
+        use crate::ReadError::*;
+        if slice.len() < EspHeader::SERIALIZED_SIZE {
+            return Err(UnexpectedEndOfSlice(EspHeader::SERIALIZED_SIZE));
+        }
+        Ok(EspHeaderSlice {
+            // SAFETY:
+            // Safe as slice len is checked to be at last 8 above.
+            slice: unsafe { from_raw_parts(slice.as_ptr(), EspHeader::SERIALIZED_SIZE) },
+        })
+    }
+
+    /// Returns the slice containing the ESP header.
+    #[inline]
+    pub fn slice(&self) -> &'a [u8] {
+        self.slice
+    }
+
+    /// Read the security parameters index from the slice
+    #[inline]
+    pub fn spi(&self) -> u32 {
+        // SAFETY:
+        // Safe as slice length is checked in the constructor
+        // to be at least 8.
+        unsafe { get_unchecked_be_u32(self.slice.as_ptr()) }
+    }
+
+    /// This unsigned 32-bit field contains a counter value that
+    /// increases by one for each packet sent.
+    #[inline]
+    pub fn sequence_number(&self) -> u32 {
+        // SAFETY:
+        // Safe as slice length is checked in the constructor
+        // to be at least 8.
+        unsafe { get_unchecked_be_u32(self.slice.as_ptr().add(4)) }
+    }
+
+    /// Decode all the fields and copy the results to a EspHeader struct
+    pub fn to_header(&self) -> EspHeader {
+        EspHeader {
+            spi: self.spi(),
+            sequence_number: self.sequence_number(),
+        }
+    }
+}
+
+/// A slice containing the padding, trailer (pad length & next header)
+/// and integrity check value at the end of an ESP packet that uses NULL
+/// encryption (rfc2410).
+///
+/// ```text
+/// +---------------+---------------+---------------+---------------+
+/// |                  payload (decoded separately)                 |
+/// ~                               +---------------+---------------+
+/// |                               |    padding (0-255 bytes)      |
+/// +---------------+---------------+---------------+---------------+
+/// |               |  pad length   |  next header  |               |
+/// +---------------+---------------+---------------+               |
+/// ~              integrity check value (icv_len bytes)            ~
+/// +---------------------------------------------------------------+
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EspTrailerSlice<'a> {
+    slice: &'a [u8],
+    icv_len: usize,
+}
+
+impl<'a> EspTrailerSlice<'a> {
+    /// Minimum size of the trailer (pad length & next header field).
+    pub const MIN_LEN: usize = 2;
+
+    /// Splits the data following an ESP header into the cleartext payload
+    /// and the trailer, assuming NULL encryption (rfc2410) and an
+    /// integrity check value of `icv_len` bytes.
+    ///
+    /// `esp_payload` must contain the data directly after the sequence number
+    /// up to the end of the IP payload (e.g. without ethernet padding).
+    ///
+    /// On success the trailer and the cleartext payload are returned. The
+    /// protocol of the payload is given by [`EspTrailerSlice::next_header`].
+    pub fn from_null_encrypted(esp_payload: &'a [u8], icv_len: usize) -> Result<(EspTrailerSlice<'a>, &'a [u8]), ReadError> {
+        use crate::ReadError::*;
+
+        let min_len = icv_len.saturating_add(EspTrailerSlice::MIN_LEN);
+        if esp_payload.len() < min_len {
+            return Err(UnexpectedEndOfSlice(min_len));
+        }
+
+        // the pad length is located directly in front of the next header
+        let trailer_start = esp_payload.len() - min_len;
+        let pad_length = esp_payload[trailer_start];
+        if trailer_start < usize::from(pad_length) {
+            return Err(EspPadLengthTooLarge(pad_length));
+        }
+
+        let payload_len = trailer_start - usize::from(pad_length);
+        Ok((
+            EspTrailerSlice {
+                slice: &esp_payload[payload_len..],
+                icv_len,
+            },
+            &esp_payload[..payload_len],
+        ))
+    }
+
+    /// Returns the slice containing the padding, trailer & integrity check value.
+    #[inline]
+    pub fn slice(&self) -> &'a [u8] {
+        self.slice
+    }
+
+    /// Returns the padding bytes located in front of the pad length field.
+    #[inline]
+    pub fn padding(&self) -> &'a [u8] {
+        &self.slice[..self.slice.len() - self.icv_len - EspTrailerSlice::MIN_LEN]
+    }
+
+    /// Number of padding bytes present in front of the trailer.
+    #[inline]
+    pub fn pad_length(&self) -> u8 {
+        self.slice[self.slice.len() - self.icv_len - 2]
+    }
+
+    /// Returns the IP protocol number of the protected payload.
+    ///
+    /// See [IpNumber] or [ip_number] for a definition of the known values.
+    #[inline]
+    pub fn next_header(&self) -> u8 {
+        self.slice[self.slice.len() - self.icv_len - 1]
+    }
+
+    /// Returns the integrity check value at the end of the packet.
+    #[inline]
+    pub fn icv(&self) -> &'a [u8] {
+        &self.slice[self.slice.len() - self.icv_len..]
+    }
+}
@@ -0,0 +1,487 @@
+use super::super::*;
+use arrayvec::ArrayVec;
+
+use std::fmt::{Debug, Formatter};
+use std::slice::from_raw_parts;
+
+/// Module containing the constants for the HIP packet types (rfc7401 & rfc8046).
+pub mod hip_packet_type {
+    /// HIP Initiator Packet.
+    pub const I1: u8 = 1;
+    /// HIP Responder Packet.
+    pub const R1: u8 = 2;
+    /// Second HIP Initiator Packet.
+    pub const I2: u8 = 3;
+    /// Second HIP Responder Packet.
+    pub const R2: u8 = 4;
+    /// HIP Update Packet.
+    pub const UPDATE: u8 = 16;
+    /// HIP Notify Packet.
+    pub const NOTIFY: u8 = 17;
+    /// HIP Association Closing Packet.
+    pub const CLOSE: u8 = 18;
+    /// HIP Association Closing Acknowledgment Packet.
+    pub const CLOSE_ACK: u8 = 19;
+}
+
+/// Parameter of a HIP header (type-length-value encoded, rfc7401 section 5.2.1).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct HipParameter<'a> {
+    /// Type of the parameter.
+    pub param_type: u16,
+    /// Contents of the parameter (without the padding).
+    pub contents: &'a [u8],
+}
+
+impl<'a> HipParameter<'a> {
+    /// Returns true if the parameter is critical (least significant bit of the
+    /// type is set) and has to be understood by the receiver.
+    #[inline]
+    pub fn is_critical(&self) -> bool {
+        0 != self.param_type & 1
+    }
+
+    /// Length of the parameter in bytes when serialized (including the
+    /// type, length & padding).
+    #[inline]
+    pub fn serialized_len(&self) -> usize {
+        // formula from rfc7401 section 5.2.1
+        11 + self.contents.len() - (self.contents.len() + 3) % 8
+    }
+}
+
+/// Errors that can occur when decoding the parameters of a HIP header.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum HipParameterReadError {
+    /// Returned if a parameter type was read, but there was not enough memory in the parameters left to completely read it.
+    UnexpectedEndOfSlice{ param_type: u16, expected_len: usize, actual_len: usize },
+}
+
+impl Error for HipParameterReadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        None
+    }
+}
+
+impl fmt::Display for HipParameterReadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use HipParameterReadError::*;
+        match self {
+            UnexpectedEndOfSlice{param_type, expected_len, actual_len} => {
+                write!(f, "HipParameterReadError: Not enough memory left in slice to read parameter of type {} (expected at least {} bytes, only {} bytes available).", param_type, expected_len, actual_len)
+            },
+        }
+    }
+}
+
+/// Allows iterating over the parameters of a HIP header.
+#[derive(Clone, Eq, PartialEq)]
+pub struct HipParametersIterator<'a> {
+    parameters: &'a [u8]
+}
+
+impl<'a> HipParametersIterator<'a> {
+    /// Creates a parameters iterator from a slice containing encoded HIP parameters.
+    pub fn from_slice(parameters: &'a [u8]) -> HipParametersIterator<'a> {
+        HipParametersIterator{ parameters }
+    }
+
+    /// Returns the non processed part of the parameters slice.
+    pub fn rest(&self) -> &'a [u8] {
+        self.parameters
+    }
+}
+
+impl<'a> Iterator for HipParametersIterator<'a> {
+    type Item = Result<HipParameter<'a>, HipParameterReadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        use HipParameterReadError::*;
+
+        if self.parameters.is_empty() {
+            return None;
+        }
+
+        if self.parameters.len() < 4 {
+            let actual_len = self.parameters.len();
+            let param_type = if actual_len >= 2 {
+                u16::from_be_bytes([self.parameters[0], self.parameters[1]])
+            } else {
+                u16::from(self.parameters[0]) << 8
+            };
+            self.parameters = &self.parameters[actual_len..];
+            return Some(Err(UnexpectedEndOfSlice{ param_type, expected_len: 4, actual_len }));
+        }
+
+        let param_type = u16::from_be_bytes([self.parameters[0], self.parameters[1]]);
+        let contents_len = usize::from(u16::from_be_bytes([self.parameters[2], self.parameters[3]]));
+        let len = 11 + contents_len - (contents_len + 3) % 8;
+        if self.parameters.len() < len {
+            let actual_len = self.parameters.len();
+            self.parameters = &self.parameters[actual_len..];
+            return Some(Err(UnexpectedEndOfSlice{ param_type, expected_len: len, actual_len }));
+        }
+        let contents = &self.parameters[4..4 + contents_len];
+        self.parameters = &self.parameters[len..];
+        Some(Ok(HipParameter{ param_type, contents }))
+    }
+}
+
+impl<'a> Debug for HipParametersIterator<'a> {
+    fn fmt(&self, fmt: &mut Formatter) -> Result<(), std::fmt::Error> {
+        let mut list = fmt.debug_list();
+
+        // create a copy and iterate over all elements
+        for it in self.clone() {
+            match it {
+                Ok(e) => { list.entry(&e); },
+                Err(e) => {
+                    list.entry(&Result::<(), HipParameterReadError>::Err(e.clone()));
+                }
+            }
+        }
+
+        list.finish()
+    }
+}
+
+/// Calculates the checksum of a HIP header (the checksum field itself is skipped).
+fn hip_checksum(source: [u8;16], destination: [u8;16], header: &[u8]) -> u16 {
+    checksum::Sum16BitWords::new()
+        .add_16bytes(source)
+        .add_16bytes(destination)
+        .add_4bytes((header.len() as u32).to_be_bytes())
+        .add_2bytes([0, ip_number::HIP])
+        .add_slice(&header[..4])
+        .add_slice(&header[6..])
+        .ones_complement()
+        .to_be()
+}
+
+/// Host Identity Protocol header (rfc7401).
+#[derive(Clone)]
+pub struct HipHeader {
+    /// IP protocol number of the header following the HIP header (should be `IPV6_NO_NEXT_HEADER`).
+    pub next_header: u8,
+    /// Type of the HIP packet (7 bit value).
+    pub packet_type: u8,
+    /// Version of the HIP protocol (4 bit value, 2 for rfc7401).
+    pub version: u8,
+    pub checksum: u16,
+    pub controls: u16,
+    /// Host identity tag of the sender.
+    pub sender_hit: [u8;16],
+    /// Host identity tag of the receiver.
+    pub receiver_hit: [u8;16],
+    /// Length of the encoded parameters.
+    parameters_len: usize,
+    /// Buffer containing the encoded parameters.
+    parameters_buffer: [u8;HipHeader::MAX_PARAMETERS_LEN],
+}
+
+impl Debug for HipHeader {
+    fn fmt(&self, formatter: &mut Formatter) -> Result<(), std::fmt::Error> {
+        write!(formatter, "HipHeader {{ next_header: {}, packet_type: {}, version: {}, checksum: {}, controls: {}, sender_hit: {:?}, receiver_hit: {:?}, parameters: {:?} }}",
+            self.next_header,
+            self.packet_type,
+            self.version,
+            self.checksum,
+            self.controls,
+            self.sender_hit,
+            self.receiver_hit,
+            self.parameters())
+    }
+}
+
+impl PartialEq for HipHeader {
+    fn eq(&self, other: &Self) -> bool {
+        self.next_header == other.next_header &&
+        self.packet_type == other.packet_type &&
+        self.version == other.version &&
+        self.checksum == other.checksum &&
+        self.controls == other.controls &&
+        self.sender_hit == other.sender_hit &&
+        self.receiver_hit == other.receiver_hit &&
+        self.raw_parameters() == other.raw_parameters()
+    }
+}
+
+impl Eq for HipHeader {}
+
+impl HipHeader {
+    /// Length of the HIP header without parameters.
+    pub const MIN_LEN: usize = 40;
+
+    /// Maximum length of the encoded parameters.
+    pub const MAX_PARAMETERS_LEN: usize = (0xff + 1)*8 - HipHeader::MIN_LEN;
+
+    /// HIP version defined by rfc7401.
+    pub const VERSION_2: u8 = 2;
+
+    /// Creates a HIP header with version 2 and no parameters.
+    pub fn new(next_header: u8, packet_type: u8, sender_hit: [u8;16], receiver_hit: [u8;16]) -> HipHeader {
+        HipHeader{
+            next_header,
+            packet_type,
+            version: HipHeader::VERSION_2,
+            checksum: 0,
+            controls: 0,
+            sender_hit,
+            receiver_hit,
+            parameters_len: 0,
+            parameters_buffer: [0;HipHeader::MAX_PARAMETERS_LEN],
+        }
+    }
+
+    /// Creates a HIP header with version 2 and the given parameters.
+    pub fn new_with_parameters(next_header: u8, packet_type: u8, sender_hit: [u8;16], receiver_hit: [u8;16], parameters: &[HipParameter]) -> Result<HipHeader, ValueError> {
+        let mut result = HipHeader::new(next_header, packet_type, sender_hit, receiver_hit);
+        result.set_parameters(parameters)?;
+        Ok(result)
+    }
+
+    /// Encodes the given parameters (including padding) and replaces the parameters of the header.
+    ///
+    /// Returns an `Ipv6ExtensionPayloadTooLarge` error with the length of the
+    /// header payload (header length - 2) if the parameters don't fit into the header.
+    pub fn set_parameters(&mut self, parameters: &[HipParameter]) -> Result<(), ValueError> {
+        let len: usize = parameters.iter().map(|p| p.serialized_len()).sum();
+        if len > HipHeader::MAX_PARAMETERS_LEN {
+            return Err(ValueError::Ipv6ExtensionPayloadTooLarge(HipHeader::MIN_LEN - 2 + len));
+        }
+        let mut offset = 0;
+        for p in parameters {
+            let param_len = p.serialized_len();
+            let buffer = &mut self.parameters_buffer[offset..offset + param_len];
+            buffer[..2].copy_from_slice(&p.param_type.to_be_bytes());
+            buffer[2..4].copy_from_slice(&(p.contents.len() as u16).to_be_bytes());
+            buffer[4..4 + p.contents.len()].copy_from_slice(p.contents);
+            for b in &mut buffer[4 + p.contents.len()..] {
+                *b = 0;
+            }
+            offset += param_len;
+        }
+        self.parameters_len = len;
+        Ok(())
+    }
+
+    /// Replaces the encoded parameters of the header.
+    ///
+    /// The length of the parameters must be a multiple of 8 and at most
+    /// [`HipHeader::MAX_PARAMETERS_LEN`]. Otherwise an `Ipv6ExtensionPayloadTooLarge`
+    /// or `Ipv6ExtensionPayloadLengthUnaligned` error with the length of the header
+    /// payload (header length - 2) is returned.
+    pub fn set_raw_parameters(&mut self, parameters: &[u8]) -> Result<(), ValueError> {
+        use ValueError::*;
+        let payload_len = HipHeader::MIN_LEN - 2 + parameters.len();
+        if parameters.len() > HipHeader::MAX_PARAMETERS_LEN {
+            return Err(Ipv6ExtensionPayloadTooLarge(payload_len));
+        }
+        match parameters.len() % 8 {
+            0 => {
+                self.parameters_buffer[..parameters.len()].copy_from_slice(parameters);
+                self.parameters_len = parameters.len();
+                Ok(())
+            },
+            _ => Err(Ipv6ExtensionPayloadLengthUnaligned(payload_len)),
+        }
+    }
+
+    /// Read a HIP header from a slice and return the header & unused parts of the slice.
+    pub fn from_slice(slice: &[u8]) -> Result<(HipHeader, &[u8]), ReadError> {
+        let s = HipHeaderSlice::from_slice(slice)?;
+        let rest = &slice[s.slice().len()..];
+        Ok((s.to_header(), rest))
+    }
+
+    /// Returns the encoded parameters.
+    #[inline]
+    pub fn raw_parameters(&self) -> &[u8] {
+        &self.parameters_buffer[..self.parameters_len]
+    }
+
+    /// Returns an iterator over the parameters of the header.
+    #[inline]
+    pub fn parameters(&self) -> HipParametersIterator<'_> {
+        HipParametersIterator::from_slice(self.raw_parameters())
+    }
+
+    /// Length of the header in bytes.
+    #[inline]
+    pub fn header_len(&self) -> usize {
+        HipHeader::MIN_LEN + self.parameters_len
+    }
+
+    /// Returns the serialized header.
+    ///
+    /// Returns an error if the packet type or version is too large.
+    pub fn to_bytes(&self) -> Result<ArrayVec<u8, { (0xff + 1)*8 }>, ValueError> {
+        max_check_u8(self.packet_type, 0b0111_1111, ErrorField::HipPacketType)?;
+        max_check_u8(self.version, 0b1111, ErrorField::HipVersion)?;
+
+        let mut result = ArrayVec::new();
+        let checksum_be = self.checksum.to_be_bytes();
+        let controls_be = self.controls.to_be_bytes();
+        result.extend([
+            self.next_header,
+            ((self.header_len() / 8) - 1) as u8,
+            self.packet_type,
+            (self.version << 4) | 1,
+            checksum_be[0],
+            checksum_be[1],
+            controls_be[0],
+            controls_be[1],
+        ]);
+        // SAFETY: the sum of the lengths is at most the capacity of the array
+        unsafe {
+            result.try_extend_from_slice(&self.sender_hit).unwrap_unchecked();
+            result.try_extend_from_slice(&self.receiver_hit).unwrap_unchecked();
+            result.try_extend_from_slice(self.raw_parameters()).unwrap_unchecked();
+        }
+        Ok(result)
+    }
+
+    /// Writes the header to the given writer.
+    pub fn write<T: io::Write + Sized>(&self, writer: &mut T) -> Result<(), WriteError> {
+        writer.write_all(&self.to_bytes()?)?;
+        Ok(())
+    }
+
+    /// Calculates the checksum of the header based on the given ipv6 source &
+    /// destination addresses (rfc7401 section 5.1.1).
+    pub fn calc_checksum(&self, source: [u8;16], destination: [u8;16]) -> Result<u16, ValueError> {
+        Ok(hip_checksum(source, destination, &self.to_bytes()?))
+    }
+}
+
+/// Slice containing a Host Identity Protocol header (rfc7401).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct HipHeaderSlice<'a> {
+    slice: &'a [u8],
+}
+
+impl<'a> HipHeaderSlice<'a> {
+    /// Creates a HIP header slice from a slice.
+    ///
+    /// Returns an `UnexpectedEndOfSlice` error if the slice is too short &
+    /// an `Ipv6HipHeaderLengthTooSmall` error if the header length field is
+    /// smaller then the fixed part of the header.
+    pub fn from_slice(slice: &'a [u8]) -> Result<HipHeaderSlice<'a>, ReadError> {
+        use crate::ReadError::*;
+        if slice.len() < 8 {
+            return Err(UnexpectedEndOfSlice(8));
+        }
+        let len = (usize::from(slice[1]) + 1)*8;
+        if len < HipHeader::MIN_LEN {
+            return Err(HipHeaderLengthTooSmall(slice[1]));
+        }
+        if slice.len() < len {
+            return Err(UnexpectedEndOfSlice(len));
+        }
+        Ok(HipHeaderSlice {
+            // SAFETY: the length was checked above
+            slice: unsafe {
+                from_raw_parts(slice.as_ptr(), len)
+            }
+        })
+    }
+
+    /// Creates a HIP header slice from a slice (assumes slice size &
+    /// content was validated before).
+    ///
+    /// # Safety
+    ///
+    /// The slice must have a length of at least 40 and `(slice[1] + 1)*8`.
+    pub unsafe fn from_slice_unchecked(slice: &'a [u8]) -> HipHeaderSlice<'a> {
+        HipHeaderSlice {
+            slice: from_raw_parts(
+                slice.as_ptr(),
+                ((*slice.get_unchecked(1) as usize) + 1)*8
+            )
+        }
+    }
+
+    /// Returns the slice containing the HIP header.
+    #[inline]
+    pub fn slice(&self) -> &'a [u8] {
+        self.slice
+    }
+
+    /// Returns the ip number of the header following the HIP header.
+    #[inline]
+    pub fn next_header(&self) -> u8 {
+        self.slice[0]
+    }
+
+    /// Returns the packet type (7 bit value).
+    #[inline]
+    pub fn packet_type(&self) -> u8 {
+        self.slice[2] & 0b0111_1111
+    }
+
+    /// Returns the HIP version (4 bit value).
+    #[inline]
+    pub fn version(&self) -> u8 {
+        self.slice[3] >> 4
+    }
+
+    /// Returns the checksum field of the header.
+    #[inline]
+    pub fn checksum(&self) -> u16 {
+        u16::from_be_bytes([self.slice[4], self.slice[5]])
+    }
+
+    /// Returns the controls field of the header.
+    #[inline]
+    pub fn controls(&self) -> u16 {
+        u16::from_be_bytes([self.slice[6], self.slice[7]])
+    }
+
+    /// Returns the host identity tag of the sender.
+    pub fn sender_hit(&self) -> [u8;16] {
+        let mut result = [0u8;16];
+        result.copy_from_slice(&self.slice[8..24]);
+        result
+    }
+
+    /// Returns the host identity tag of the receiver.
+    pub fn receiver_hit(&self) -> [u8;16] {
+        let mut result = [0u8;16];
+        result.copy_from_slice(&self.slice[24..40]);
+        result
+    }
+
+    /// Returns the encoded parameters.
+    #[inline]
+    pub fn raw_parameters(&self) -> &'a [u8] {
+        &self.slice[HipHeader::MIN_LEN..]
+    }
+
+    /// Returns an iterator over the parameters of the header.
+    #[inline]
+    pub fn parameters(&self) -> HipParametersIterator<'a> {
+        HipParametersIterator::from_slice(self.raw_parameters())
+    }
+
+    /// Calculates the checksum of the header based on the given ipv6 source &
+    /// destination addresses (rfc7401 section 5.1.1).
+    pub fn calc_checksum(&self, source: [u8;16], destination: [u8;16]) -> u16 {
+        hip_checksum(source, destination, self.slice)
+    }
+
+    /// Decode all the fields and copy the results to a [`HipHeader`].
+    pub fn to_header(&self) -> HipHeader {
+        let mut result = HipHeader::new(
+            self.next_header(),
+            self.packet_type(),
+            self.sender_hit(),
+            self.receiver_hit(),
+        );
+        result.version = self.version();
+        result.checksum = self.checksum();
+        result.controls = self.controls();
+        // the length is guranteed to be a multiple of 8 & to fit into the buffer
+        result.set_raw_parameters(self.raw_parameters()).unwrap();
+        result
+    }
+}
@@ -0,0 +1,446 @@
+use super::super::*;
+
+use arrayvec::ArrayVec;
+
+/// Message authentication code algorithm used to calculate the
+/// "Integrity Check Value" (ICV) of an IP Authentication Header (rfc4302).
+///
+/// Etherparse itself does not contain any cryptographic algorithms. Implement
+/// this trait for the MAC of your crypto crate of choice (e.g. HMAC-SHA1-96 or
+/// HMAC-SHA-256-128) and pass it to [`IpAuthenticatedPacketSlice::calc_icv`] or
+/// [`IpAuthenticatedPacketSlice::verify_icv`]. The key has to be set up by the
+/// implementation before it is passed in.
+pub trait IpAuthenticationMac {
+    /// Adds the given data to the MAC calculation.
+    fn update(&mut self, data: &[u8]);
+
+    /// Finishes the MAC calculation and writes the result to `icv`.
+    ///
+    /// The length of `icv` is the ICV length present in the authentication
+    /// header. If the algorithm produces a longer output (e.g. 20 bytes for
+    /// HMAC-SHA1 with an ICV of 12 bytes) it has to be truncated accordingly.
+    fn finalize(self, icv: &mut [u8]);
+}
+
+/// Slice containing an IPv4 or IPv6 packet with an authentication header,
+/// used to calculate & verify the "Integrity Check Value" (ICV) as
+/// described in rfc4302.
+///
+/// The ICV is calculated over the IP header, the extension headers, the
+/// authentication header (with the ICV field set to zero) and the payload.
+/// Fields that can change in transit are muted (set to zero) and fields
+/// that change in a predictable way are set to the values expected at the
+/// receiver:
+///
+/// * IPv4: The DSCP, ECN, flags, fragment offset, time to live & header
+///   checksum fields are zeroed. Options not classified as immutable by
+///   rfc4302 are zeroed completely. If an uncompleted source route option is
+///   present the destination is set to the last address of the route.
+/// * IPv6: The traffic class, flow label & hop limit are zeroed. The option data
+///   of hop by hop & destination options with the "may change en route" bit set
+///   are zeroed. If a routing header of type 0 or 2 with segments left is present
+///   the destination & routing header are set to the values expected at the
+///   final destination.
+///
+/// Extended sequence numbers (ESN) are currently not supported (the high
+/// order 32 bits of the sequence number are not added to the ICV input).
+///
+/// # Example
+///
+/// ```
+/// use etherparse::*;
+///
+/// // toy MAC, use a real HMAC from a crypto crate instead
+/// struct XorMac([u8;4], usize);
+/// impl IpAuthenticationMac for XorMac {
+///     fn update(&mut self, data: &[u8]) {
+///         for b in data {
+///             self.0[self.1 % 4] ^= b;
+///             self.1 += 1;
+///         }
+///     }
+///     fn finalize(self, icv: &mut [u8]) {
+///         icv.copy_from_slice(&self.0[..icv.len()]);
+///     }
+/// }
+///
+/// // packet with an authentication header (icv still zero)
+/// let auth = IpAuthenticationHeader::new(ip_number::UDP, 1, 2, &[0;4]).unwrap();
+/// let ip = Ipv4Header::new(
+///     (auth.header_len() + 4) as u16, 20, ip_number::AUTH, [1,2,3,4], [5,6,7,8]
+/// );
+/// let mut packet = Vec::new();
+/// ip.write(&mut packet).unwrap();
+/// auth.write(&mut packet).unwrap();
+/// packet.extend_from_slice(&[1,2,3,4]);
+///
+/// // calculate the icv and insert it in the packet
+/// let icv = IpAuthenticatedPacketSlice::from_ip_slice(&packet).unwrap()
+///           .calc_icv(XorMac([0;4], 0));
+/// let icv_offset = ip.header_len() + 12;
+/// packet[icv_offset..icv_offset + icv.len()].copy_from_slice(&icv);
+///
+/// // verify the icv (e.g. on the receiving side)
+/// let slice = IpAuthenticatedPacketSlice::from_ip_slice(&packet).unwrap();
+/// assert!(slice.verify_icv(XorMac([0;4], 0)));
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct IpAuthenticatedPacketSlice<'a> {
+    /// Slice containing the ip packet (limited to the length given in the ip header).
+    slice: &'a [u8],
+    /// Offset of the authentication header in the slice.
+    auth_offset: usize,
+    /// The authentication header.
+    auth: IpAuthenticationHeaderSlice<'a>,
+}
+
+impl<'a> IpAuthenticatedPacketSlice<'a> {
+    /// Locates the authentication header in a slice starting with an IPv4 or IPv6 header.
+    ///
+    /// The slice is limited to the length given in the ip header (e.g. to
+    /// remove ethernet padding). For IPv6 the authentication header is searched
+    /// after the hop by hop, destination options, routing & fragment headers.
+    ///
+    /// # Errors
+    ///
+    /// * `ReadError::IpAuthenticationHeaderNotPresent` if no authentication
+    ///   header directly follows the ip header & its extension headers.
+    /// * `ReadError::UnexpectedEndOfSlice` if the slice is shorter then the
+    ///   length given in the ip header.
+    /// * Any error generated while decoding the ip & extension headers.
+    pub fn from_ip_slice(slice: &'a [u8]) -> Result<IpAuthenticatedPacketSlice<'a>, ReadError> {
+        use ip_number::*;
+        use ReadError::*;
+
+        if slice.is_empty() {
+            return Err(UnexpectedEndOfSlice(1));
+        }
+
+        let (packet, offset, next_header) = match slice[0] >> 4 {
+            4 => {
+                let header = Ipv4HeaderSlice::from_slice(slice)?;
+                let total_len = usize::from(header.total_len());
+                if slice.len() < total_len {
+                    return Err(UnexpectedEndOfSlice(total_len));
+                }
+                (&slice[..total_len], header.slice().len(), header.protocol())
+            },
+            6 => {
+                let header = Ipv6HeaderSlice::from_slice(slice)?;
+                let total_len = header.slice().len() + ipv6_payload_len(&header, &slice[header.slice().len()..]);
+                if slice.len() < total_len {
+                    return Err(UnexpectedEndOfSlice(total_len));
+                }
+                let packet = &slice[..total_len];
+                let mut offset = header.slice().len();
+                let mut next_header = header.next_header();
+                loop {
+                    match next_header {
+                        IPV6_HOP_BY_HOP | IPV6_DEST_OPTIONS | IPV6_ROUTE => {
+                            let ext = Ipv6RawExtensionHeaderSlice::from_slice(&packet[offset..])
+                                      .map_err(|err| err.add_slice_offset(offset))?;
+                            offset += ext.slice().len();
+                            next_header = ext.next_header();
+                        },
+                        IPV6_FRAG => {
+                            let ext = Ipv6FragmentHeaderSlice::from_slice(&packet[offset..])
+                                      .map_err(|err| err.add_slice_offset(offset))?;
+                            offset += ext.slice().len();
+                            next_header = ext.next_header();
+                        },
+                        _ => break,
+                    }
+                }
+                (packet, offset, next_header)
+            },
+            version => return Err(IpUnsupportedVersion(version)),
+        };
+
+        if AUTH != next_header {
+            return Err(IpAuthenticationHeaderNotPresent);
+        }
+
+        let auth = IpAuthenticationHeaderSlice::from_slice(&packet[offset..])
+                   .map_err(|err| err.add_slice_offset(offset))?;
+
+        Ok(IpAuthenticatedPacketSlice {
+            slice: packet,
+            auth_offset: offset,
+            auth,
+        })
+    }
+
+    /// Returns the slice containing the ip packet.
+    #[inline]
+    pub fn slice(&self) -> &'a [u8] {
+        self.slice
+    }
+
+    /// Returns the offset of the authentication header in the ip packet.
+    #[inline]
+    pub fn auth_offset(&self) -> usize {
+        self.auth_offset
+    }
+
+    /// Returns the authentication header.
+    #[inline]
+    pub fn auth(&self) -> &IpAuthenticationHeaderSlice<'a> {
+        &self.auth
+    }
+
+    /// Passes the bytes covered by the ICV (with mutable fields muted) to the
+    /// given function in order.
+    ///
+    /// Concatenating all passed slices results in the same data as written by
+    /// [`IpAuthenticatedPacketSlice::write_icv_input`].
+    pub fn for_each_icv_input<F: FnMut(&[u8])>(&self, mut f: F) {
+        if 4 == self.slice[0] >> 4 {
+            self.ipv4_icv_input(&mut f);
+        } else {
+            self.ipv6_icv_input(&mut f);
+        }
+
+        // authentication header with a zeroed icv
+        let auth = self.auth.slice();
+        f(&auth[..12]);
+        const ZEROES: [u8; 64] = [0; 64];
+        let mut icv_len = auth.len() - 12;
+        while icv_len > 0 {
+            let len = std::cmp::min(icv_len, ZEROES.len());
+            f(&ZEROES[..len]);
+            icv_len -= len;
+        }
+
+        // everything after the authentication header is immutable
+        f(&self.slice[self.auth_offset + auth.len()..]);
+    }
+
+    /// Writes the bytes covered by the ICV (with mutable fields muted) to the given writer.
+    pub fn write_icv_input<T: io::Write + Sized>(&self, writer: &mut T) -> Result<(), WriteError> {
+        let mut result = Ok(());
+        self.for_each_icv_input(|data| {
+            if result.is_ok() {
+                result = writer.write_all(data);
+            }
+        });
+        result.map_err(WriteError::from)
+    }
+
+    /// Calculates the ICV with the given MAC.
+    ///
+    /// The length of the returned ICV is equal to the length of the ICV field
+    /// in the authentication header.
+    pub fn calc_icv<M: IpAuthenticationMac>(&self, mut mac: M) -> ArrayVec<u8, { IpAuthenticationHeader::MAX_ICV_LEN }> {
+        self.for_each_icv_input(|data| mac.update(data));
+        let mut icv = ArrayVec::from([0; IpAuthenticationHeader::MAX_ICV_LEN]);
+        icv.truncate(self.auth.raw_icv().len());
+        mac.finalize(&mut icv);
+        icv
+    }
+
+    /// Returns true if the ICV in the authentication header matches the
+    /// ICV calculated with the given MAC.
+    ///
+    /// The comparison is done in constant time (independent of the position
+    /// of the first differing byte).
+    pub fn verify_icv<M: IpAuthenticationMac>(&self, mac: M) -> bool {
+        let icv = self.calc_icv(mac);
+        0 == icv
+            .iter()
+            .zip(self.auth.raw_icv().iter())
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+    }
+
+    fn ipv4_icv_input<F: FnMut(&[u8])>(&self, f: &mut F) {
+        let header_len = self.auth_offset;
+        let mut header = [0u8; 60];
+        header[..header_len].copy_from_slice(&self.slice[..header_len]);
+
+        // mutable fields (dscp, ecn, flags, fragment offset, ttl & checksum)
+        header[1] = 0;
+        header[6] = 0;
+        header[7] = 0;
+        header[8] = 0;
+        header[10] = 0;
+        header[11] = 0;
+
+        // options
+        let mut i = 20;
+        while i < header_len {
+            match header[i] {
+                // end of options list
+                0 => break,
+                // no operation
+                1 => i += 1,
+                option_type => {
+                    let len = if i + 1 < header_len {
+                        usize::from(header[i + 1])
+                    } else {
+                        0
+                    };
+                    if len < 2 || i + len > header_len {
+                        // malformed option, mute the rest
+                        for b in &mut header[i..header_len] {
+                            *b = 0;
+                        }
+                        break;
+                    }
+                    match option_type {
+                        // security, extended security, commercial security,
+                        // router alert & sender directed multi-destination delivery
+                        130 | 133 | 134 | 148 | 149 => {},
+                        option_type => {
+                            // loose & strict source route: the destination
+                            // at the receiver is the last address in the route
+                            // (as long as the route is not yet completed)
+                            if (131 == option_type || 137 == option_type)
+                                && len >= 7
+                                && usize::from(header[i + 2]) <= len
+                            {
+                                let mut dst = [0u8; 4];
+                                dst.copy_from_slice(&header[i + len - 4..i + len]);
+                                header[16..20].copy_from_slice(&dst);
+                            }
+                            for b in &mut header[i..i + len] {
+                                *b = 0;
+                            }
+                        }
+                    }
+                    i += len;
+                }
+            }
+        }
+
+        f(&header[..header_len]);
+    }
+
+    fn ipv6_icv_input<F: FnMut(&[u8])>(&self, f: &mut F) {
+        use ip_number::*;
+
+        // the destination address is determined by the routing header
+        let mut header = [0u8; Ipv6Header::SERIALIZED_SIZE];
+        header.copy_from_slice(&self.slice[..Ipv6Header::SERIALIZED_SIZE]);
+
+        // mutable fields (traffic class, flow label & hop limit)
+        header[0] &= 0xf0;
+        header[1] = 0;
+        header[2] = 0;
+        header[3] = 0;
+        header[7] = 0;
+
+        // determine the final destination based on a routing header
+        // (the extension headers were already validated in the constructor)
+        {
+            let mut offset = Ipv6Header::SERIALIZED_SIZE;
+            let mut next_header = header[6];
+            while offset < self.auth_offset {
+                let ext = &self.slice[offset..];
+                let len = if IPV6_FRAG == next_header {
+                    8
+                } else {
+                    (usize::from(ext[1]) + 1) * 8
+                };
+                if IPV6_ROUTE == next_header {
+                    if let Some(dst) = Self::routing_final_destination(&ext[..len]) {
+                        header[24..40].copy_from_slice(&dst);
+                    }
+                }
+                next_header = ext[0];
+                offset += len;
+            }
+        }
+        f(&header);
+
+        // extension headers
+        let mut buffer = [0u8; 0x100 * 8];
+        let mut offset = Ipv6Header::SERIALIZED_SIZE;
+        let mut next_header = self.slice[6];
+        let original_destination = &self.slice[24..40];
+        while offset < self.auth_offset {
+            let len = if IPV6_FRAG == next_header {
+                8
+            } else {
+                (usize::from(self.slice[offset + 1]) + 1) * 8
+            };
+            let ext = &mut buffer[..len];
+            ext.copy_from_slice(&self.slice[offset..offset + len]);
+
+            match next_header {
+                IPV6_HOP_BY_HOP | IPV6_DEST_OPTIONS => Self::mute_ipv6_options(ext),
+                IPV6_ROUTE => Self::predict_routing(ext, original_destination),
+                _ => {},
+            }
+
+            next_header = ext[0];
+            f(ext);
+            offset += len;
+        }
+    }
+
+    /// Zeroes the option data of all options with the "may change en route" bit set.
+    fn mute_ipv6_options(ext: &mut [u8]) {
+        let mut i = 2;
+        while i < ext.len() {
+            let option_type = ext[i];
+            if 0 == option_type {
+                // pad1
+                i += 1;
+                continue;
+            }
+            if i + 1 >= ext.len() {
+                break;
+            }
+            let data_len = usize::from(ext[i + 1]);
+            let end = std::cmp::min(i + 2 + data_len, ext.len());
+            if 0 != option_type & 0b0010_0000 {
+                for b in &mut ext[i + 2..end] {
+                    *b = 0;
+                }
+            }
+            i = end;
+        }
+    }
+
+    /// Returns the addresses count in case of a type 0 or 2 routing header
+    /// with segments left.
+    fn routing_addresses(ext: &[u8]) -> Option<usize> {
+        let routing_type = ext[2];
+        let segments_left = usize::from(ext[3]);
+        let addresses = (ext.len() - 8) / 16;
+        if (0 == routing_type || 2 == routing_type)
+            && segments_left > 0
+            && segments_left <= addresses
+        {
+            Some(addresses)
+        } else {
+            None
+        }
+    }
+
+    fn routing_final_destination(ext: &[u8]) -> Option<[u8; 16]> {
+        Self::routing_addresses(ext).map(|addresses| {
+            let mut dst = [0u8; 16];
+            let start = 8 + (addresses - 1) * 16;
+            dst.copy_from_slice(&ext[start..start + 16]);
+            dst
+        })
+    }
+
+    /// Sets the address list & segments left of a routing header to the
+    /// values expected at the final destination.
+    fn predict_routing(ext: &mut [u8], current_destination: &[u8]) {
+        if let Some(addresses) = Self::routing_addresses(ext) {
+            // the next address to be swapped with the destination
+            let next = addresses - usize::from(ext[3]);
+
+            // shift the not yet visited addresses by one & insert the current destination
+            let start = 8 + next * 16;
+            let end = 8 + addresses * 16;
+            ext.copy_within(start..end - 16, start + 16);
+            ext[start..start + 16].copy_from_slice(current_destination);
+
+            // segments left
+            ext[3] = 0;
+        }
+    }
+}
@@ -6,7 +6,8 @@ use super::super::*;
 /// * Authentication Header
 ///
 /// Currently not supported:
-/// - Encapsulating Security Payload Header (ESP)
+/// - Encapsulating Security Payload Header (ESP), only available
+///   via [`Ipv4ExtensionsSlice::esp`]
 #[derive(Clone, Debug, Eq, PartialEq, Default)]
 pub struct Ipv4Extensions {
     pub auth: Option<IpAuthenticationHeader>,
@@ -16,12 +17,22 @@ pub struct Ipv4Extensions {
 ///
 /// Currently supported:
 /// * Authentication Header
-///
-/// Currently not supported:
 /// * Encapsulating Security Payload Header (ESP)
+///
+/// Note that the ESP header is not consumed when slicing via
+/// [`Ipv4ExtensionsSlice::from_slice`], as everything after the
+/// sequence number is encrypted. In this case the returned rest starts at the
+/// ESP header & the returned ip number is `ip_number::ENCAP_SEC`. Use
+/// [`Ipv4ExtensionsSlice::from_slice_esp_null`] to decode packets using NULL
+/// encryption (rfc2410).
 #[derive(Clone, Debug, Eq, PartialEq, Default)]
 pub struct Ipv4ExtensionsSlice<'a> {
     pub auth: Option<IpAuthenticationHeaderSlice<'a>>,
+    /// Unencrypted part of an ESP header (spi & sequence number).
+    pub esp: Option<EspHeaderSlice<'a>>,
+    /// Padding, trailer & integrity check value of an ESP packet (only set
+    /// when decoding packets using NULL encryption).
+    pub esp_trailer: Option<EspTrailerSlice<'a>>,
 }
 
 impl Ipv4Extensions {
@@ -128,22 +139,66 @@ impl<'a> Ipv4ExtensionsSlice<'a> {
 
     /// Read all known ipv4 extensions and return an `Ipv4ExtensionSlices` with the
     /// identified slices, the final ip number and a slice pointing to the non parsed data.
+    ///
+    /// If an ESP header is present it is set in the `esp` field, but the returned
+    /// slice still starts at the ESP header and `ip_number::ENCAP_SEC` is returned
+    /// as ip number, as the rest of the ESP packet is encrypted. If the slice is
+    /// too short to contain the ESP header, `esp` is left empty and no error
+    /// is returned.
     pub fn from_slice(start_ip_number: u8, start_slice: &'a [u8]) -> Result<(Ipv4ExtensionsSlice, u8, &[u8]), ReadError> {
+        Ipv4ExtensionsSlice::from_slice_with_esp(start_ip_number, start_slice, None)
+    }
+
+    /// Read all known ipv4 extensions and decode a present ESP header assuming
+    /// NULL encryption (rfc2410) with an integrity check value of `icv_len` bytes.
+    ///
+    /// The given slice must end at the end of the ip payload, as the ESP trailer
+    /// is located at the end of the packet. If an ESP header is present the trailer
+    /// is set in the `esp_trailer` field, the returned ip number is the next header
+    /// value of the trailer & the returned slice contains the cleartext payload
+    /// (without padding, trailer & integrity check value).
+    ///
+    /// If no ESP header is present the result is identical to
+    /// [`Ipv4ExtensionsSlice::from_slice`].
+    pub fn from_slice_esp_null(start_ip_number: u8, start_slice: &'a [u8], icv_len: usize) -> Result<(Ipv4ExtensionsSlice<'a>, u8, &'a [u8]), ReadError> {
+        Ipv4ExtensionsSlice::from_slice_with_esp(start_ip_number, start_slice, Some(icv_len))
+    }
+
+    fn from_slice_with_esp(start_ip_number: u8, start_slice: &'a [u8], esp_null_icv_len: Option<usize>) -> Result<(Ipv4ExtensionsSlice<'a>, u8, &'a [u8]), ReadError> {
         use ip_number::*;
-        if AUTH == start_ip_number {
-            let header = IpAuthenticationHeaderSlice::from_slice(start_slice)?;
-            let rest = &start_slice[header.slice().len()..];
-            let next_header = header.next_header();
-            Ok((
-                Ipv4ExtensionsSlice{
-                    auth: Some(header)
-                },
-                next_header,
-                rest
-            ))
-        } else {
-            Ok((Default::default(), start_ip_number, start_slice))
+
+        let mut result: Ipv4ExtensionsSlice<'a> = Default::default();
+        let mut next_header = start_ip_number;
+        let mut rest = start_slice;
+
+        if AUTH == next_header {
+            let header = IpAuthenticationHeaderSlice::from_slice(rest)?;
+            rest = &rest[header.slice().len()..];
+            next_header = header.next_header();
+            result.auth = Some(header);
         }
+
+        if ENCAP_SEC == next_header {
+            if let Some(icv_len) = esp_null_icv_len {
+                let offset = start_slice.len() - rest.len();
+                let header = EspHeaderSlice::from_slice(rest)
+                             .map_err(|err| err.add_slice_offset(offset))?;
+                let (trailer, payload) = EspTrailerSlice::from_null_encrypted(
+                    &rest[header.slice().len()..],
+                    icv_len
+                ).map_err(|err| err.add_slice_offset(offset + header.slice().len()))?;
+                next_header = trailer.next_header();
+                rest = payload;
+                result.esp = Some(header);
+                result.esp_trailer = Some(trailer);
+            } else {
+                // the esp header is not consumed, so a truncated
+                // header is treated like any other unknown payload
+                result.esp = EspHeaderSlice::from_slice(rest).ok();
+            }
+        }
+
+        Ok((result, next_header, rest))
     }
 
     /// Convert the slices into actual headers.
@@ -156,6 +211,6 @@ impl<'a> Ipv4ExtensionsSlice<'a> {
     /// Returns true if no IPv4 extension header is present (all fields `None`).
     #[inline]
     pub fn is_empty(&self) -> bool {
-        self.auth.is_none()
+        self.auth.is_none() && self.esp.is_none()
     }
 }
\ No newline at end of file
@@ -0,0 +1,502 @@
+use super::super::*;
+
+use arrayvec::ArrayVec;
+use std::fmt::{Debug, Formatter};
+
+/// Module containing the constants for ipv4 options (type numbers & sizes).
+pub mod ipv4_option {
+    /// `u8` identifying the "end of options list" ipv4 option.
+    pub const KIND_END: u8 = 0;
+    /// `u8` identifying a "no operation" ipv4 option.
+    pub const KIND_NOOP: u8 = 1;
+    /// `u8` identifying a "record route" ipv4 option.
+    pub const KIND_RECORD_ROUTE: u8 = 7;
+    /// `u8` identifying an "internet timestamp" ipv4 option.
+    pub const KIND_TIMESTAMP: u8 = 68;
+    /// `u8` identifying a "security" ipv4 option (rfc1108).
+    pub const KIND_SECURITY: u8 = 130;
+    /// `u8` identifying a "loose source and record route" ipv4 option.
+    pub const KIND_LOOSE_SOURCE_ROUTE: u8 = 131;
+    /// `u8` identifying a "strict source and record route" ipv4 option.
+    pub const KIND_STRICT_SOURCE_ROUTE: u8 = 137;
+    /// `u8` identifying a "router alert" ipv4 option (rfc2113).
+    pub const KIND_ROUTER_ALERT: u8 = 148;
+    /// Length in octets/bytes of the "end" ipv4 option (includes type value).
+    pub const LEN_END: u8 = 1;
+    /// Length in octets/bytes of the "no operation" ipv4 option (includes type value).
+    pub const LEN_NOOP: u8 = 1;
+    /// Length in octets/bytes of the "router alert" ipv4 option (includes type value).
+    pub const LEN_ROUTER_ALERT: u8 = 4;
+    /// Maximum number of addresses that fit into a route option.
+    pub const MAX_ROUTE_ADDRESSES: usize = 9;
+    /// Maximum number of timestamps (without addresses) that fit into a timestamp option.
+    pub const MAX_TIMESTAMPS: usize = 9;
+    /// Maximum number of address & timestamp pairs that fit into a timestamp option.
+    pub const MAX_ADDRESS_TIMESTAMPS: usize = 4;
+    /// Maximum length of the protection authority flags in a security option.
+    pub const MAX_PROTECTION_AUTHORITY_LEN: usize = 37;
+}
+
+/// Content of an ipv4 "internet timestamp" option (the variant
+/// determines the value of the flag field).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Ipv4Timestamps {
+    /// Flag 0: Only timestamps are recorded.
+    TimestampsOnly(ArrayVec<u32, { ipv4_option::MAX_TIMESTAMPS }>),
+    /// Flag 1: Each timestamp is preceded by the address of the recording router.
+    WithAddresses(ArrayVec<([u8;4], u32), { ipv4_option::MAX_ADDRESS_TIMESTAMPS }>),
+    /// Flag 3: The addresses are prespecified by the sender and a router
+    /// only records a timestamp if its address matches the next one.
+    Prespecified(ArrayVec<([u8;4], u32), { ipv4_option::MAX_ADDRESS_TIMESTAMPS }>),
+}
+
+impl Ipv4Timestamps {
+    /// Value of the flag field in the option.
+    pub fn flag(&self) -> u8 {
+        use Ipv4Timestamps::*;
+        match self {
+            TimestampsOnly(_) => 0,
+            WithAddresses(_) => 1,
+            Prespecified(_) => 3,
+        }
+    }
+}
+
+/// Options that can be present in the options part of an ipv4 header.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Ipv4OptionElement {
+    /// "No Operation" option (used to align options on a 32 bit boundary).
+    Noop,
+    /// "Record Route" option (rfc791).
+    ///
+    /// The pointer is the octet offset (relative to the start of the
+    /// option, minimum 4) of the next address to be recorded. The addresses
+    /// contain all slots of the route data (recorded or not).
+    RecordRoute{ pointer: u8, addresses: ArrayVec<[u8;4], { ipv4_option::MAX_ROUTE_ADDRESSES }> },
+    /// "Loose Source and Record Route" option (rfc791).
+    ///
+    /// The pointer is the octet offset (relative to the start of the
+    /// option, minimum 4) of the next source address to be processed.
+    LooseSourceRoute{ pointer: u8, addresses: ArrayVec<[u8;4], { ipv4_option::MAX_ROUTE_ADDRESSES }> },
+    /// "Strict Source and Record Route" option (rfc791).
+    ///
+    /// The pointer is the octet offset (relative to the start of the
+    /// option, minimum 4) of the next source address to be processed.
+    StrictSourceRoute{ pointer: u8, addresses: ArrayVec<[u8;4], { ipv4_option::MAX_ROUTE_ADDRESSES }> },
+    /// "Internet Timestamp" option (rfc791).
+    ///
+    /// The overflow field is 4 bit long (values between 0 and 15) and contains
+    /// the number of routers that could not register timestamps.
+    Timestamp{ pointer: u8, overflow: u8, timestamps: Ipv4Timestamps },
+    /// "Security" option as defined in rfc1108.
+    Security{ classification_level: u8, protection_authority: ArrayVec<u8, { ipv4_option::MAX_PROTECTION_AUTHORITY_LEN }> },
+    /// "Router Alert" option (rfc2113), a value of 0 means "router shall examine packet".
+    RouterAlert(u16),
+}
+
+impl Ipv4OptionElement {
+    /// Length of the option in bytes when serialized.
+    pub fn serialized_len(&self) -> usize {
+        use Ipv4OptionElement::*;
+        use Ipv4Timestamps::*;
+        match self {
+            Noop => 1,
+            RecordRoute{ addresses, .. } |
+            LooseSourceRoute{ addresses, .. } |
+            StrictSourceRoute{ addresses, .. } => 3 + addresses.len()*4,
+            Timestamp{ timestamps, .. } => 4 + match timestamps {
+                TimestampsOnly(t) => t.len()*4,
+                WithAddresses(t) | Prespecified(t) => t.len()*8,
+            },
+            Security{ protection_authority, .. } => 3 + protection_authority.len(),
+            RouterAlert(_) => 4,
+        }
+    }
+
+    /// Writes the option to the start of the given buffer (the caller
+    /// has to ensure the buffer has at least `serialized_len` bytes).
+    fn write_to_slice(&self, buffer: &mut [u8]) {
+        use Ipv4OptionElement::*;
+        use Ipv4Timestamps::*;
+        use ipv4_option::*;
+
+        let len = self.serialized_len();
+        match self {
+            Noop => {
+                buffer[0] = KIND_NOOP;
+            },
+            RecordRoute{ pointer, addresses } |
+            LooseSourceRoute{ pointer, addresses } |
+            StrictSourceRoute{ pointer, addresses } => {
+                buffer[0] = match self {
+                    RecordRoute{..} => KIND_RECORD_ROUTE,
+                    LooseSourceRoute{..} => KIND_LOOSE_SOURCE_ROUTE,
+                    _ => KIND_STRICT_SOURCE_ROUTE,
+                };
+                buffer[1] = len as u8;
+                buffer[2] = *pointer;
+                for (i, address) in addresses.iter().enumerate() {
+                    buffer[3 + i*4..3 + i*4 + 4].copy_from_slice(address);
+                }
+            },
+            Timestamp{ pointer, overflow, timestamps } => {
+                buffer[0] = KIND_TIMESTAMP;
+                buffer[1] = len as u8;
+                buffer[2] = *pointer;
+                buffer[3] = (overflow << 4) | timestamps.flag();
+                match timestamps {
+                    TimestampsOnly(t) => {
+                        for (i, timestamp) in t.iter().enumerate() {
+                            buffer[4 + i*4..4 + i*4 + 4].copy_from_slice(&timestamp.to_be_bytes());
+                        }
+                    },
+                    WithAddresses(t) | Prespecified(t) => {
+                        for (i, (address, timestamp)) in t.iter().enumerate() {
+                            let insert = &mut buffer[4 + i*8..4 + i*8 + 8];
+                            insert[..4].copy_from_slice(address);
+                            insert[4..].copy_from_slice(&timestamp.to_be_bytes());
+                        }
+                    },
+                }
+            },
+            Security{ classification_level, protection_authority } => {
+                buffer[0] = KIND_SECURITY;
+                buffer[1] = len as u8;
+                buffer[2] = *classification_level;
+                buffer[3..len].copy_from_slice(protection_authority);
+            },
+            RouterAlert(value) => {
+                buffer[0] = KIND_ROUTER_ALERT;
+                buffer[1] = LEN_ROUTER_ALERT;
+                buffer[2..4].copy_from_slice(&value.to_be_bytes());
+            },
+        }
+    }
+}
+
+impl Ipv4Header {
+    /// Sets the options of the header to the given option elements.
+    ///
+    /// The options are padded with zeros ("end of options list") to a
+    /// multiple of 4 bytes. If the options would need more then 40 bytes
+    /// an `ValueError::Ipv4OptionsLengthBad` error is returned and the
+    /// options of the header are not modified.
+    pub fn set_option_elements(&mut self, options: &[Ipv4OptionElement]) -> Result<(), ValueError> {
+        use crate::ValueError::*;
+
+        // check the values & calculate the required size
+        let mut required_len = 0;
+        for option in options {
+            if let Ipv4OptionElement::Timestamp{ overflow, .. } = option {
+                if *overflow > 0b1111 {
+                    return Err(U8TooLarge{
+                        value: *overflow,
+                        max: 0b1111,
+                        field: ErrorField::Ipv4OptionTimestampOverflow
+                    });
+                }
+            }
+            required_len += option.serialized_len();
+        }
+        let padded_len = required_len + (4 - required_len % 4) % 4;
+        if padded_len > 40 {
+            return Err(Ipv4OptionsLengthBad(padded_len));
+        }
+
+        // serialize
+        let mut buffer = [0u8;40];
+        let mut i = 0;
+        for option in options {
+            option.write_to_slice(&mut buffer[i..]);
+            i += option.serialized_len();
+        }
+        self.set_options(&buffer[..padded_len])
+    }
+
+    /// Returns an iterator over the typed ipv4 options of the header.
+    pub fn options_iterator(&self) -> Ipv4OptionsIterator<'_> {
+        Ipv4OptionsIterator::from_slice(self.options())
+    }
+
+    /// Returns the value of the "Router Alert" option (rfc2113) if present
+    /// in the options of the header.
+    pub fn router_alert(&self) -> Option<u16> {
+        self.options_iterator()
+            .map_while(|option| option.ok())
+            .find_map(|option| match option {
+                Ipv4OptionElement::RouterAlert(value) => Some(value),
+                _ => None,
+            })
+    }
+
+    /// Sets the value of the "Router Alert" option (rfc2113).
+    ///
+    /// If the header already contains a router alert option its value is
+    /// overwritten, otherwise the option is inserted in front of the existing
+    /// options. If there is not enough space left in the options an
+    /// `ValueError::Ipv4OptionsLengthBad` error is returned and the header
+    /// is not modified.
+    pub fn set_router_alert(&mut self, value: u16) -> Result<(), ValueError> {
+        // search for an existing router alert option
+        let options_len = self.options().len();
+        let mut existing = None;
+        {
+            let mut iter = self.options_iterator();
+            loop {
+                let start = options_len - iter.rest().len();
+                match iter.next() {
+                    Some(Ok(Ipv4OptionElement::RouterAlert(_))) => {
+                        existing = Some(start);
+                        break;
+                    },
+                    Some(Ok(_)) => {},
+                    _ => break,
+                }
+            }
+        }
+
+        let mut buffer = [0u8;44];
+        let len = match existing {
+            Some(start) => {
+                buffer[..options_len].copy_from_slice(self.options());
+                buffer[start + 2..start + 4].copy_from_slice(&value.to_be_bytes());
+                options_len
+            },
+            None => {
+                Ipv4OptionElement::RouterAlert(value).write_to_slice(&mut buffer);
+                buffer[4..4 + options_len].copy_from_slice(self.options());
+                4 + options_len
+            },
+        };
+        self.set_options(&buffer[..len])
+    }
+}
+
+impl<'a> Ipv4HeaderSlice<'a> {
+    /// Returns an iterator over the typed ipv4 options of the header.
+    pub fn options_iterator(&self) -> Ipv4OptionsIterator<'a> {
+        Ipv4OptionsIterator::from_slice(self.options())
+    }
+}
+
+/// Errors that can occour while reading the options of an ipv4 header.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Ipv4OptionReadError {
+    /// Returned if an option type was read, but there was not enough memory in the options left to completely read it.
+    UnexpectedEndOfSlice{ option_id: u8, expected_len: u8, actual_len: usize },
+
+    /// Returned if the option has an unexpected length value (e.g. != 4 for router alert).
+    UnexpectedSize{ option_id: u8, size: u8 },
+
+    /// Returned if a timestamp option with an unknown flag value is encountered.
+    UnknownTimestampFlag(u8),
+
+    /// Returned if an unknown ipv4 option type is encountered.
+    UnknownId(u8),
+}
+
+impl Error for Ipv4OptionReadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        None
+    }
+}
+
+impl fmt::Display for Ipv4OptionReadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use Ipv4OptionReadError::*;
+        match self {
+            UnexpectedEndOfSlice{option_id, expected_len, actual_len} => {
+                write!(f, "Ipv4OptionReadError: Not enough memory left in slice to read option of type {} (expected at least {} bytes, only {} bytes available).", option_id, expected_len, actual_len)
+            },
+            UnexpectedSize{option_id, size} => {
+                write!(f, "Ipv4OptionReadError: Length value of the option of type {} had unexpected value {}.", option_id, size)
+            },
+            UnknownTimestampFlag(flag) => {
+                write!(f, "Ipv4OptionReadError: Unknown timestamp option flag value {}.", flag)
+            },
+            UnknownId(id) => {
+                write!(f, "Ipv4OptionReadError: Unknown ipv4 option type value {}.", id)
+            }
+        }
+    }
+}
+
+/// Allows iterating over the options of an ipv4 header.
+#[derive(Clone, Eq, PartialEq)]
+pub struct Ipv4OptionsIterator<'a> {
+    options: &'a [u8]
+}
+
+impl<'a> Ipv4OptionsIterator<'a> {
+    /// Creates an options iterator from a slice containing encoded ipv4 options.
+    pub fn from_slice(options: &'a [u8]) -> Ipv4OptionsIterator<'a> {
+        Ipv4OptionsIterator{ options }
+    }
+
+    /// Returns the non processed part of the options slice.
+    pub fn rest(&self) -> &'a [u8] {
+        self.options
+    }
+
+    /// Reads the length of the current option & checks that it is in the
+    /// given bounds and completely present in the slice.
+    fn option_len(&self, min_len: u8, max_len: u8) -> Result<usize, Ipv4OptionReadError> {
+        use Ipv4OptionReadError::*;
+
+        let id = self.options[0];
+        if self.options.len() < 2 {
+            return Err(UnexpectedEndOfSlice{
+                option_id: id,
+                expected_len: 2,
+                actual_len: self.options.len()
+            });
+        }
+        let len = self.options[1];
+        if len < min_len || len > max_len {
+            return Err(UnexpectedSize{ option_id: id, size: len });
+        }
+        if self.options.len() < usize::from(len) {
+            return Err(UnexpectedEndOfSlice{
+                option_id: id,
+                expected_len: len,
+                actual_len: self.options.len()
+            });
+        }
+        Ok(usize::from(len))
+    }
+
+    fn read_route(&self) -> Result<(usize, u8, ArrayVec<[u8;4], { ipv4_option::MAX_ROUTE_ADDRESSES }>), Ipv4OptionReadError> {
+        use ipv4_option::*;
+
+        let len = self.option_len(3, 3 + (MAX_ROUTE_ADDRESSES as u8)*4)?;
+        if 0 != (len - 3) % 4 {
+            return Err(Ipv4OptionReadError::UnexpectedSize{
+                option_id: self.options[0],
+                size: len as u8
+            });
+        }
+        let addresses = self.options[3..len]
+            .chunks_exact(4)
+            .map(|c| [c[0], c[1], c[2], c[3]])
+            .collect();
+        Ok((len, self.options[2], addresses))
+    }
+
+    fn read_timestamp(&self) -> Result<(usize, Ipv4OptionElement), Ipv4OptionReadError> {
+        use Ipv4OptionReadError::*;
+        use Ipv4Timestamps::*;
+        use ipv4_option::*;
+
+        let len = self.option_len(4, 4 + (MAX_TIMESTAMPS as u8)*4)?;
+        let flag = self.options[3] & 0b1111;
+        let entry_len = match flag {
+            0 => 4,
+            1 | 3 => 8,
+            flag => return Err(UnknownTimestampFlag(flag)),
+        };
+        if 0 != (len - 4) % entry_len || (len - 4) / entry_len > MAX_TIMESTAMPS {
+            return Err(UnexpectedSize{
+                option_id: self.options[0],
+                size: len as u8
+            });
+        }
+        let entries = self.options[4..len].chunks_exact(entry_len);
+        let to_pair = |c: &[u8]| ([c[0], c[1], c[2], c[3]], u32::from_be_bytes([c[4], c[5], c[6], c[7]]));
+        let timestamps = match flag {
+            0 => TimestampsOnly(entries.map(|c| u32::from_be_bytes([c[0], c[1], c[2], c[3]])).collect()),
+            1 => WithAddresses(entries.map(to_pair).collect()),
+            _ => Prespecified(entries.map(to_pair).collect()),
+        };
+        Ok((
+            len,
+            Ipv4OptionElement::Timestamp{
+                pointer: self.options[2],
+                overflow: self.options[3] >> 4,
+                timestamps
+            }
+        ))
+    }
+}
+
+impl<'a> Iterator for Ipv4OptionsIterator<'a> {
+    type Item = Result<Ipv4OptionElement, Ipv4OptionReadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        use crate::Ipv4OptionReadError::*;
+        use crate::Ipv4OptionElement::*;
+        use ipv4_option::*;
+
+        if self.options.is_empty() {
+            return None;
+        }
+
+        // first determine the result & the length of the option
+        let result = match self.options[0] {
+            KIND_END => None,
+            KIND_NOOP => Some(Ok((1, Noop))),
+            KIND_RECORD_ROUTE => Some(
+                self.read_route().map(|(len, pointer, addresses)| (len, RecordRoute{ pointer, addresses }))
+            ),
+            KIND_LOOSE_SOURCE_ROUTE => Some(
+                self.read_route().map(|(len, pointer, addresses)| (len, LooseSourceRoute{ pointer, addresses }))
+            ),
+            KIND_STRICT_SOURCE_ROUTE => Some(
+                self.read_route().map(|(len, pointer, addresses)| (len, StrictSourceRoute{ pointer, addresses }))
+            ),
+            KIND_TIMESTAMP => Some(self.read_timestamp()),
+            KIND_SECURITY => Some(
+                self.option_len(3, 3 + MAX_PROTECTION_AUTHORITY_LEN as u8).map(|len| (
+                    len,
+                    Security{
+                        classification_level: self.options[2],
+                        protection_authority: self.options[3..len].iter().copied().collect(),
+                    }
+                ))
+            ),
+            KIND_ROUTER_ALERT => Some(
+                self.option_len(LEN_ROUTER_ALERT, LEN_ROUTER_ALERT).map(|len| (
+                    len,
+                    RouterAlert(u16::from_be_bytes([self.options[2], self.options[3]]))
+                ))
+            ),
+            id => Some(Err(UnknownId(id))),
+        };
+
+        // move the slice to the next option (or to the end in case of
+        // an error or the end of the options list)
+        match result {
+            Some(Ok((len, element))) => {
+                self.options = &self.options[len..];
+                Some(Ok(element))
+            },
+            None => {
+                let len = self.options.len();
+                self.options = &self.options[len..len];
+                None
+            },
+            Some(Err(err)) => {
+                let len = self.options.len();
+                self.options = &self.options[len..len];
+                Some(Err(err))
+            },
+        }
+    }
+}
+
+impl<'a> Debug for Ipv4OptionsIterator<'a> {
+    fn fmt(&self, fmt: &mut Formatter) -> Result<(), std::fmt::Error> {
+        let mut list = fmt.debug_list();
+
+        // create a copy and iterate over all elements
+        for it in self.clone() {
+            match it {
+                Ok(e) => { list.entry(&e); },
+                Err(e) => {
+                    list.entry(&Result::<(), Ipv4OptionReadError>::Err(e.clone()));
+                }
+            }
+        }
+
+        list.finish()
+    }
+}
@@ -277,6 +277,8 @@ impl Ipv6Header {
     }
 
     ///Sets the field total_length based on the size of the payload and the options. Returns an error if the payload is too big to fit.
+    ///
+    ///For payloads bigger then 65535 bytes (jumbograms) see [`Ipv6Header::set_payload_length_with_jumbo`].
     pub fn set_payload_length(&mut self, size: usize) -> Result<(), ValueError> {
         //check that the total length fits into the field
         const MAX_PAYLOAD_LENGTH: usize = std::u16::MAX as usize;
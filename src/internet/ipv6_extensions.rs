@@ -11,13 +11,13 @@ use std::slice::from_raw_parts;
 /// * Routing Header
 /// * Fragment
 /// * Authentication Header
+/// * IP Mobility
+/// * Host Identity Protocol (HIP)
+/// * Site Multihoming by IPv6 Intermediation (SHIM6)
 ///
 /// Currently not supported:
 ////
 /// * Encapsulating Security Payload Header (ESP)
-/// * Host Identity Protocol (HIP)
-/// * IP Mobility
-/// * Site Multihoming by IPv6 Intermediation (SHIM6)
 #[derive(Clone, Debug, Eq, PartialEq, Default)]
 pub struct Ipv6Extensions {
     pub hop_by_hop_options: Option<Ipv6RawExtensionHeader>,
@@ -25,6 +25,9 @@ pub struct Ipv6Extensions {
     pub routing: Option<Ipv6RoutingExtensions>,
     pub fragment: Option<Ipv6FragmentHeader>,
     pub auth: Option<IpAuthenticationHeader>,
+    pub mobility: Option<Ipv6MobilityHeader>,
+    pub hip: Option<HipHeader>,
+    pub shim6: Option<Shim6Header>,
 }
 
 impl Ipv6Extensions {
@@ -43,7 +46,9 @@ impl Ipv6Extensions {
     /// the struct. In such a scenario a struct with the data that could be parsed is returned
     /// together with the next header ip number and slice containing the unparsed data.
     ///
-    /// It is in the responsibility of the caller to handle a scenario like this.
+    /// It is in the responsibility of the caller to handle a scenario like this. Use
+    /// [`Ipv6ExtensionsChain::from_slice`] or [`Ipv6ExtensionsChainIter`] to decode
+    /// extension headers in any order & with any number of repetitions.
     ///
     /// The reason that no error is generated, is that even though according to RFC 8200 packets 
     /// "should" not contain more then one occurence of an extension header the RFC also specifies
@@ -139,6 +144,39 @@ impl Ipv6Extensions {
                         result.auth = Some(slice.to_header());
                     }
                 },
+                MOBILITY => {
+                    if result.mobility.is_some() {
+                        // more then one header of this type found -> abort parsing
+                        return Ok((result, next_header, rest))
+                    } else {
+                        let (header, header_rest) = Ipv6MobilityHeader::from_slice(rest)?;
+                        rest = header_rest;
+                        next_header = header.payload_proto;
+                        result.mobility = Some(header);
+                    }
+                },
+                HIP => {
+                    if result.hip.is_some() {
+                        // more then one header of this type found -> abort parsing
+                        return Ok((result, next_header, rest))
+                    } else {
+                        let (header, header_rest) = HipHeader::from_slice(rest)?;
+                        rest = header_rest;
+                        next_header = header.next_header;
+                        result.hip = Some(header);
+                    }
+                },
+                SHIM6 => {
+                    if result.shim6.is_some() {
+                        // more then one header of this type found -> abort parsing
+                        return Ok((result, next_header, rest))
+                    } else {
+                        let (header, header_rest) = Shim6Header::from_slice(rest)?;
+                        rest = header_rest;
+                        next_header = header.next_header();
+                        result.shim6 = Some(header);
+                    }
+                },
                 _ => {
                     // done parsing, the next header is not a known header extension
                     return Ok((result, next_header, rest))
@@ -180,6 +218,8 @@ impl Ipv6Extensions {
     pub fn read<T: io::Read + io::Seek + Sized>(reader: &mut T, start_ip_number: u8) -> Result<(Ipv6Extensions, u8), ReadError> {
         let mut result: Ipv6Extensions = Default::default();
         let mut next_protocol = start_ip_number;
+        // buffer for the mobility, HIP & Shim6 headers (only decodable from slices)
+        let mut buffer = [0u8;GENERIC_EXTENSION_MAX_LEN];
 
         use ip_number::*;
         use ReadError::*;
@@ -252,6 +292,39 @@ impl Ipv6Extensions {
                         result.auth = Some(header);
                     }
                 },
+                MOBILITY => {
+                    if result.mobility.is_some() {
+                        // more then one header of this type found -> abort parsing
+                        return Ok((result, next_protocol));
+                    } else {
+                        let len = read_generic_extension(reader, &mut buffer)?;
+                        let (header, _) = Ipv6MobilityHeader::from_slice(&buffer[..len])?;
+                        next_protocol = header.payload_proto;
+                        result.mobility = Some(header);
+                    }
+                },
+                HIP => {
+                    if result.hip.is_some() {
+                        // more then one header of this type found -> abort parsing
+                        return Ok((result, next_protocol));
+                    } else {
+                        let len = read_generic_extension(reader, &mut buffer)?;
+                        let (header, _) = HipHeader::from_slice(&buffer[..len])?;
+                        next_protocol = header.next_header;
+                        result.hip = Some(header);
+                    }
+                },
+                SHIM6 => {
+                    if result.shim6.is_some() {
+                        // more then one header of this type found -> abort parsing
+                        return Ok((result, next_protocol));
+                    } else {
+                        let len = read_generic_extension(reader, &mut buffer)?;
+                        let (header, _) = Shim6Header::from_slice(&buffer[..len])?;
+                        next_protocol = header.next_header();
+                        result.shim6 = Some(header);
+                    }
+                },
                 _ => {
                     // done parsing, the next header is not a known header extension
                     return Ok((result, next_protocol))
@@ -279,6 +352,9 @@ impl Ipv6Extensions {
             pub routing: bool,
             pub fragment: bool,
             pub auth: bool,
+            pub mobility: bool,
+            pub hip: bool,
+            pub shim6: bool,
             pub final_destination_options: bool
         }
 
@@ -288,6 +364,9 @@ impl Ipv6Extensions {
             routing: self.routing.is_some(),
             fragment: self.fragment.is_some(),
             auth: self.auth.is_some(),
+            mobility: self.mobility.is_some(),
+            hip: self.hip.is_some(),
+            shim6: self.shim6.is_some(),
             final_destination_options: if let Some(ref routing) = self.routing {
                 routing.final_destination_options.is_some()
             } else {
@@ -376,6 +455,36 @@ impl Ipv6Extensions {
                         break;
                     }
                 },
+                MOBILITY => {
+                    if needs_write.mobility {
+                        let header = &self.mobility.as_ref().unwrap();
+                        header.write(writer)?;
+                        next_header = header.payload_proto;
+                        needs_write.mobility = false;
+                    } else {
+                        break;
+                    }
+                },
+                HIP => {
+                    if needs_write.hip {
+                        let header = &self.hip.as_ref().unwrap();
+                        header.write(writer)?;
+                        next_header = header.next_header;
+                        needs_write.hip = false;
+                    } else {
+                        break;
+                    }
+                },
+                SHIM6 => {
+                    if needs_write.shim6 {
+                        let header = &self.shim6.as_ref().unwrap();
+                        header.write(writer)?;
+                        next_header = header.next_header();
+                        needs_write.shim6 = false;
+                    } else {
+                        break;
+                    }
+                },
                 _ => {
                     // reached an unknown next_header id, proceed to check if everything was written
                     break;
@@ -394,6 +503,12 @@ impl Ipv6Extensions {
             Err(Ipv6ExtensionNotReferenced(IPv6FragmentationHeader).into())
         } else if needs_write.auth {
             Err(Ipv6ExtensionNotReferenced(AuthenticationHeader).into())
+        } else if needs_write.mobility {
+            Err(Ipv6ExtensionNotReferenced(MobilityHeader).into())
+        } else if needs_write.hip {
+            Err(Ipv6ExtensionNotReferenced(Hip).into())
+        } else if needs_write.shim6 {
+            Err(Ipv6ExtensionNotReferenced(Shim6).into())
         } else if needs_write.final_destination_options {
             Err(Ipv6ExtensionNotReferenced(IPv6DestinationOptions).into())
         } else {
@@ -423,6 +538,15 @@ impl Ipv6Extensions {
         if let Some(ref header) = self.auth {
             result += header.header_len();
         }
+        if let Some(ref header) = self.mobility {
+            result += header.header_len();
+        }
+        if let Some(ref header) = self.hip {
+            result += header.header_len();
+        }
+        if let Some(ref header) = self.shim6 {
+            result += header.header_len();
+        }
 
         result
     }
@@ -451,6 +575,18 @@ impl Ipv6Extensions {
         // * Destination Options header
         // * Upper-Layer header
         //
+        // The Shim6 header is placed between the routing & the fragment
+        // header (rfc5533 section 4.6) and the mobility & HIP headers are
+        // placed last, as they are usually not followed by an upper layer
+        // header.
+        if let Some(ref mut header) = self.mobility {
+            header.payload_proto = next;
+            next = MOBILITY;
+        }
+        if let Some(ref mut header) = self.hip {
+            header.next_header = next;
+            next = HIP;
+        }
         if let Some(ref mut routing) = self.routing {
             if let Some(ref mut header) = routing.final_destination_options {
                 header.next_header = next;
@@ -465,6 +601,10 @@ impl Ipv6Extensions {
             header.next_header = next;
             next = IPV6_FRAG;
         }
+        if let Some(ref mut header) = self.shim6 {
+            header.set_next_header(next);
+            next = SHIM6;
+        }
         if let Some(ref mut routing) = self.routing {
             routing.routing.next_header = next;
             next = IPV6_ROUTE;
@@ -494,6 +634,9 @@ impl Ipv6Extensions {
             pub routing: bool,
             pub fragment: bool,
             pub auth: bool,
+            pub mobility: bool,
+            pub hip: bool,
+            pub shim6: bool,
             pub final_destination_options: bool
         }
 
@@ -503,6 +646,9 @@ impl Ipv6Extensions {
             routing: self.routing.is_some(),
             fragment: self.fragment.is_some(),
             auth: self.auth.is_some(),
+            mobility: self.mobility.is_some(),
+            hip: self.hip.is_some(),
+            shim6: self.shim6.is_some(),
             final_destination_options: if let Some(ref routing) = self.routing {
                 routing.final_destination_options.is_some()
             } else {
@@ -586,6 +732,30 @@ impl Ipv6Extensions {
                         break;
                     }
                 },
+                MOBILITY => {
+                    if outstanding_refs.mobility {
+                        next = self.mobility.as_ref().unwrap().payload_proto;
+                        outstanding_refs.mobility = false;
+                    } else {
+                        break;
+                    }
+                },
+                HIP => {
+                    if outstanding_refs.hip {
+                        next = self.hip.as_ref().unwrap().next_header;
+                        outstanding_refs.hip = false;
+                    } else {
+                        break;
+                    }
+                },
+                SHIM6 => {
+                    if outstanding_refs.shim6 {
+                        next = self.shim6.as_ref().unwrap().next_header();
+                        outstanding_refs.shim6 = false;
+                    } else {
+                        break;
+                    }
+                },
                 _ => break,
             }
         }
@@ -616,6 +786,21 @@ impl Ipv6Extensions {
                 Ipv6ExtensionNotReferenced(IpNumber::AuthenticationHeader)
             );
         }
+        if outstanding_refs.mobility {
+            return Err(
+                Ipv6ExtensionNotReferenced(IpNumber::MobilityHeader)
+            );
+        }
+        if outstanding_refs.hip {
+            return Err(
+                Ipv6ExtensionNotReferenced(IpNumber::Hip)
+            );
+        }
+        if outstanding_refs.shim6 {
+            return Err(
+                Ipv6ExtensionNotReferenced(IpNumber::Shim6)
+            );
+        }
         if outstanding_refs.final_destination_options {
             return Err(
                 Ipv6ExtensionNotReferenced(IpNumber::IPv6DestinationOptions)
@@ -649,10 +834,27 @@ impl Ipv6Extensions {
         self.destination_options.is_none() &&
         self.routing.is_none() &&
         self.fragment.is_none() &&
-        self.auth.is_none()
+        self.auth.is_none() &&
+        self.mobility.is_none() &&
+        self.hip.is_none() &&
+        self.shim6.is_none()
     }
 }
 
+/// Maximum length of an extension header using the generic extension
+/// header format (rfc6564), which stores the length in 8 octets
+/// (minus the first 8 octets) in the second byte.
+const GENERIC_EXTENSION_MAX_LEN: usize = (0xff + 1)*8;
+
+/// Reads an extension header using the generic extension header format
+/// (rfc6564) into the buffer & returns the length of the header.
+fn read_generic_extension<T: io::Read + Sized>(reader: &mut T, buffer: &mut [u8;GENERIC_EXTENSION_MAX_LEN]) -> Result<usize, ReadError> {
+    reader.read_exact(&mut buffer[..2])?;
+    let len = (usize::from(buffer[1]) + 1)*8;
+    reader.read_exact(&mut buffer[2..len])?;
+    Ok(len)
+}
+
 /// In case a route header is present it is also possible
 /// to attach a "final destination" header.
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -670,11 +872,9 @@ pub struct Ipv6RoutingExtensions {
 /// * Routing Header
 /// * Fragment
 /// * Authentication Header
-///
-/// Currently not supported:
-/// * Encapsulating Security Payload Header (ESP)
-/// * Host Identity Protocol (HIP)
+/// * Encapsulating Security Payload Header (ESP), see [`Ipv6ExtensionsSlice::esp`]
 /// * IP Mobility
+/// * Host Identity Protocol (HIP)
 /// * Site Multihoming by IPv6 Intermediation (SHIM6)
 #[derive(Clone, Debug, Eq, PartialEq, Default)]
 pub struct Ipv6ExtensionsSlice<'a> {
@@ -683,14 +883,53 @@ pub struct Ipv6ExtensionsSlice<'a> {
     /// True if a fragment header is present in the ipv6 header extensions that causes the payload to be fragmented.
     fragmented: bool,
     /// Slice containing ipv6 extension headers.
-    slice: &'a [u8]
+    slice: &'a [u8],
+    /// Unencrypted part of an ESP header following the extension headers.
+    esp: Option<EspHeaderSlice<'a>>,
+    /// Trailer of an ESP packet decoded with NULL encryption.
+    esp_trailer: Option<EspTrailerSlice<'a>>,
 }
 
 impl<'a> Ipv6ExtensionsSlice<'a> {
 
     /// Collects all ipv6 extension headers in a slice & checks if
     /// a fragmentation header that fragments the packet is present.
+    ///
+    /// The headers are accepted in any order & with any number of repetitions
+    /// (only the hop by hop header is required to be the first header). Use
+    /// `Ipv6ExtensionsChain::from(slice)` to get an owned copy of the headers.
+    ///
+    /// Mobility, HIP & Shim6 headers are also skipped, so the returned slice
+    /// starts at the payload following them (e.g. an upper layer protocol
+    /// after a Shim6 payload extension header).
+    ///
+    /// If an ESP header follows the extension headers it is accessible via
+    /// [`Ipv6ExtensionsSlice::esp`]. As the rest of the ESP packet is encrypted
+    /// the returned slice still starts at the ESP header and
+    /// `ip_number::ENCAP_SEC` is returned as ip number. If the slice is too
+    /// short to contain the ESP header, no ESP header is set and no error
+    /// is returned.
     pub fn from_slice(start_ip_number: u8, start_slice: &'a [u8]) -> Result<(Ipv6ExtensionsSlice, u8, &'a[u8]), ReadError> {
+        Ipv6ExtensionsSlice::from_slice_with_esp(start_ip_number, start_slice, None)
+    }
+
+    /// Collects all ipv6 extension headers in a slice and decodes a following
+    /// ESP header assuming NULL encryption (rfc2410) with an integrity check
+    /// value of `icv_len` bytes.
+    ///
+    /// The given slice must end at the end of the ip payload, as the ESP trailer
+    /// is located at the end of the packet. If an ESP header is present the
+    /// returned ip number is the next header value in the ESP trailer and the
+    /// returned slice contains the cleartext payload (without padding, trailer &
+    /// integrity check value).
+    ///
+    /// If no ESP header is present the result is identical to
+    /// [`Ipv6ExtensionsSlice::from_slice`].
+    pub fn from_slice_esp_null(start_ip_number: u8, start_slice: &'a [u8], icv_len: usize) -> Result<(Ipv6ExtensionsSlice<'a>, u8, &'a[u8]), ReadError> {
+        Ipv6ExtensionsSlice::from_slice_with_esp(start_ip_number, start_slice, Some(icv_len))
+    }
+
+    fn from_slice_with_esp(start_ip_number: u8, start_slice: &'a [u8], esp_null_icv_len: Option<usize>) -> Result<(Ipv6ExtensionsSlice<'a>, u8, &'a[u8]), ReadError> {
         let mut rest = start_slice;
         let mut next_header = start_ip_number;
         let mut fragmented = false;
@@ -758,19 +997,62 @@ impl<'a> Ipv6ExtensionsSlice<'a> {
                     };
                     next_header = slice.next_header();
                 },
+                MOBILITY => {
+                    let slice = Ipv6MobilityHeaderSlice::from_slice(rest)?;
+                    rest = &rest[slice.slice().len()..];
+                    next_header = slice.next_header();
+                },
+                HIP => {
+                    let slice = HipHeaderSlice::from_slice(rest)?;
+                    rest = &rest[slice.slice().len()..];
+                    next_header = slice.next_header();
+                },
+                SHIM6 => {
+                    let slice = Shim6HeaderSlice::from_slice(rest)?;
+                    rest = &rest[slice.slice().len()..];
+                    next_header = slice.next_header();
+                },
                 // done parsing, the next header is not a known/supported header extension
                 _ => break,
             }
         }
 
+        let ext_len = start_slice.len() - rest.len();
+        let mut esp = None;
+        let mut esp_trailer = None;
+
+        // the esp header is not part of the extension headers slice
+        // as its payload (potentially containing further extension
+        // headers) is in general encrypted
+        if ENCAP_SEC == next_header {
+            if let Some(icv_len) = esp_null_icv_len {
+                let header = EspHeaderSlice::from_slice(rest)
+                             .map_err(|err| err.add_slice_offset(ext_len))?;
+                let (trailer, payload) = EspTrailerSlice::from_null_encrypted(
+                    &rest[header.slice().len()..],
+                    icv_len
+                ).map_err(|err| err.add_slice_offset(ext_len + header.slice().len()))?;
+                next_header = trailer.next_header();
+                rest = payload;
+                esp = Some(header);
+                esp_trailer = Some(trailer);
+            } else {
+                // the esp header is not consumed, so a truncated
+                // header is treated like any other unknown payload
+                esp = EspHeaderSlice::from_slice(rest).ok();
+            }
+        }
+
         Ok((Ipv6ExtensionsSlice{
-            first_header: if rest.len() != start_slice.len() {
+            first_header: if 0 != ext_len {
                 Some(start_ip_number)
             } else {
                 None
             },
             fragmented,
-            slice: &start_slice[..start_slice.len() - rest.len()],
+            slice: &start_slice[..ext_len],
+            esp,
+            esp_trailer,
         }, next_header, rest))
     }
 
@@ -803,10 +1085,25 @@ impl<'a> Ipv6ExtensionsSlice<'a> {
         self.slice
     }
 
-    /// Returns true if no IPv6 extension header is present (slice is empty).
+    /// Returns the unencrypted part of the ESP header (spi & sequence number)
+    /// if an ESP header follows the extension headers.
+    #[inline]
+    pub fn esp(&self) -> Option<&EspHeaderSlice<'a>> {
+        self.esp.as_ref()
+    }
+
+    /// Returns the padding, trailer & integrity check value of the ESP packet
+    /// if it was decoded via [`Ipv6ExtensionsSlice::from_slice_esp_null`].
+    #[inline]
+    pub fn esp_trailer(&self) -> Option<&EspTrailerSlice<'a>> {
+        self.esp_trailer.as_ref()
+    }
+
+    /// Returns true if no IPv6 extension header is present (slice is empty
+    /// and no ESP header is present).
     #[inline]
     pub fn is_empty(&self) -> bool {
-        self.slice.is_empty()
+        self.slice.is_empty() && self.esp.is_none()
     }
 }
 
@@ -819,7 +1116,7 @@ impl<'a> Ipv6ExtensionsSlice<'a> {
 /// this enum and currently not supported (list taken on 2021-07-17
 /// from <https://www.iana.org/assignments/ipv6-parameters/ipv6-parameters.xhtml>):
 ///
-/// * Encapsulating Security Payload \[[RFC4303](https://datatracker.ietf.org/doc/html/rfc4303)\]
+/// * Encapsulating Security Payload \[[RFC4303](https://datatracker.ietf.org/doc/html/rfc4303)\] (see [`Ipv6ExtensionsSlice::esp`])
 /// * Mobility Header \[[RFC6275](https://datatracker.ietf.org/doc/html/rfc6275)\]
 /// * Host Identity Protocol \[[RFC7401](https://datatracker.ietf.org/doc/html/rfc7401)\]
 /// * Shim6 Protocol \[[RFC5533](https://datatracker.ietf.org/doc/html/rfc5533)\]
@@ -837,6 +1134,12 @@ pub enum Ipv6ExtensionSlice<'a> {
     DestinationOptions(Ipv6RawExtensionHeaderSlice<'a>),
     /// Authentication Header \[[RFC4302](https://datatracker.ietf.org/doc/html/rfc4302)\]
     Authentication(IpAuthenticationHeaderSlice<'a>),
+    /// Mobility Header \[[RFC6275](https://datatracker.ietf.org/doc/html/rfc6275)\]
+    Mobility(Ipv6MobilityHeaderSlice<'a>),
+    /// Host Identity Protocol \[[RFC7401](https://datatracker.ietf.org/doc/html/rfc7401)\]
+    Hip(HipHeaderSlice<'a>),
+    /// Shim6 Protocol \[[RFC5533](https://datatracker.ietf.org/doc/html/rfc5533)\]
+    Shim6(Shim6HeaderSlice<'a>),
 }
 
 impl<'a> IntoIterator for Ipv6ExtensionsSlice<'a> {
@@ -935,6 +1238,36 @@ impl<'a> Iterator for Ipv6ExtensionSliceIter<'a> {
                 self.next_header = slice.next_header();
                 Some(Authentication(slice))
             },
+            MOBILITY => unsafe {
+                let slice = Ipv6MobilityHeaderSlice::from_slice_unchecked(self.rest);
+                let len = slice.slice().len();
+                self.rest = from_raw_parts(
+                    self.rest.as_ptr().add(len),
+                    self.rest.len() - len
+                );
+                self.next_header = slice.next_header();
+                Some(Mobility(slice))
+            },
+            HIP => unsafe {
+                let slice = HipHeaderSlice::from_slice_unchecked(self.rest);
+                let len = slice.slice().len();
+                self.rest = from_raw_parts(
+                    self.rest.as_ptr().add(len),
+                    self.rest.len() - len
+                );
+                self.next_header = slice.next_header();
+                Some(Hip(slice))
+            },
+            SHIM6 => unsafe {
+                let slice = Shim6HeaderSlice::from_slice_unchecked(self.rest);
+                let len = slice.slice().len();
+                self.rest = from_raw_parts(
+                    self.rest.as_ptr().add(len),
+                    self.rest.len() - len
+                );
+                self.next_header = slice.next_header();
+                Some(Shim6(slice))
+            },
             // done parsing, the next header is not a known/supported header extension
             _ => None,
         }
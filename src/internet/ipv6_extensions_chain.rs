@@ -0,0 +1,330 @@
+use super::super::*;
+
+/// Owned IPv6 extension header as part of an [`Ipv6ExtensionsChain`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Ipv6ExtensionHeader {
+    /// IPv6 Hop-by-Hop Option \[[RFC8200](https://datatracker.ietf.org/doc/html/rfc8200)\]
+    HopByHop(Ipv6RawExtensionHeader),
+    /// Routing Header for IPv6 \[[RFC8200](https://datatracker.ietf.org/doc/html/rfc8200)\]
+    Routing(Ipv6RawExtensionHeader),
+    /// Fragment Header for IPv6 \[[RFC8200](https://datatracker.ietf.org/doc/html/rfc8200)\]
+    Fragment(Ipv6FragmentHeader),
+    /// Destination Options for IPv6 \[[RFC8200](https://datatracker.ietf.org/doc/html/rfc8200)\]
+    DestinationOptions(Ipv6RawExtensionHeader),
+    /// Authentication Header \[[RFC4302](https://datatracker.ietf.org/doc/html/rfc4302)\]
+    Authentication(IpAuthenticationHeader),
+    /// Mobility Header \[[RFC6275](https://datatracker.ietf.org/doc/html/rfc6275)\]
+    Mobility(Ipv6MobilityHeader),
+    /// Host Identity Protocol \[[RFC7401](https://datatracker.ietf.org/doc/html/rfc7401)\]
+    Hip(HipHeader),
+    /// Shim6 Protocol \[[RFC5533](https://datatracker.ietf.org/doc/html/rfc5533)\]
+    Shim6(Shim6Header),
+}
+
+impl Ipv6ExtensionHeader {
+    /// Returns the ip number identifying the header type.
+    pub fn ip_number(&self) -> u8 {
+        use ip_number::*;
+        use Ipv6ExtensionHeader::*;
+        match self {
+            HopByHop(_) => IPV6_HOP_BY_HOP,
+            Routing(_) => IPV6_ROUTE,
+            Fragment(_) => IPV6_FRAG,
+            DestinationOptions(_) => IPV6_DEST_OPTIONS,
+            Authentication(_) => AUTH,
+            Mobility(_) => MOBILITY,
+            Hip(_) => HIP,
+            Shim6(_) => SHIM6,
+        }
+    }
+
+    /// Returns the ip number of the header following this header.
+    pub fn next_header(&self) -> u8 {
+        use Ipv6ExtensionHeader::*;
+        match self {
+            HopByHop(header) | Routing(header) | DestinationOptions(header) => header.next_header,
+            Fragment(header) => header.next_header,
+            Authentication(header) => header.next_header,
+            Mobility(header) => header.payload_proto,
+            Hip(header) => header.next_header,
+            Shim6(header) => header.next_header(),
+        }
+    }
+
+    /// Sets the ip number of the header following this header.
+    pub fn set_next_header(&mut self, next_header: u8) {
+        use Ipv6ExtensionHeader::*;
+        match self {
+            HopByHop(header) | Routing(header) | DestinationOptions(header) => header.next_header = next_header,
+            Fragment(header) => header.next_header = next_header,
+            Authentication(header) => header.next_header = next_header,
+            Mobility(header) => header.payload_proto = next_header,
+            Hip(header) => header.next_header = next_header,
+            Shim6(header) => header.set_next_header(next_header),
+        }
+    }
+
+    /// Length of the header in bytes.
+    pub fn header_len(&self) -> usize {
+        use Ipv6ExtensionHeader::*;
+        match self {
+            HopByHop(header) | Routing(header) | DestinationOptions(header) => header.header_len(),
+            Fragment(header) => header.header_len(),
+            Authentication(header) => header.header_len(),
+            Mobility(header) => header.header_len(),
+            Hip(header) => header.header_len(),
+            Shim6(header) => header.header_len(),
+        }
+    }
+
+    /// Writes the header to the given writer.
+    pub fn write<T: io::Write + Sized>(&self, writer: &mut T) -> Result<(), WriteError> {
+        use Ipv6ExtensionHeader::*;
+        match self {
+            HopByHop(header) | Routing(header) | DestinationOptions(header) => header.write(writer),
+            Fragment(header) => header.write(writer),
+            Authentication(header) => header.write(writer),
+            Mobility(header) => header.write(writer),
+            Hip(header) => header.write(writer),
+            Shim6(header) => header.write(writer),
+        }
+    }
+}
+
+impl<'a> Ipv6ExtensionSlice<'a> {
+    /// Returns the ip number identifying the header type.
+    pub fn ip_number(&self) -> u8 {
+        use ip_number::*;
+        use Ipv6ExtensionSlice::*;
+        match self {
+            HopByHop(_) => IPV6_HOP_BY_HOP,
+            Routing(_) => IPV6_ROUTE,
+            Fragment(_) => IPV6_FRAG,
+            DestinationOptions(_) => IPV6_DEST_OPTIONS,
+            Authentication(_) => AUTH,
+            Mobility(_) => MOBILITY,
+            Hip(_) => HIP,
+            Shim6(_) => SHIM6,
+        }
+    }
+
+    /// Returns the ip number of the header following this header.
+    pub fn next_header(&self) -> u8 {
+        use Ipv6ExtensionSlice::*;
+        match self {
+            HopByHop(slice) | Routing(slice) | DestinationOptions(slice) => slice.next_header(),
+            Fragment(slice) => slice.next_header(),
+            Authentication(slice) => slice.next_header(),
+            Mobility(slice) => slice.next_header(),
+            Hip(slice) => slice.next_header(),
+            Shim6(slice) => slice.next_header(),
+        }
+    }
+
+    /// Slice containing the header.
+    pub fn slice(&self) -> &'a [u8] {
+        use Ipv6ExtensionSlice::*;
+        match self {
+            HopByHop(slice) | Routing(slice) | DestinationOptions(slice) => slice.slice(),
+            Fragment(slice) => slice.slice(),
+            Authentication(slice) => slice.slice(),
+            Mobility(slice) => slice.slice(),
+            Hip(slice) => slice.slice(),
+            Shim6(slice) => slice.slice(),
+        }
+    }
+
+    /// Decode the header into an owned [`Ipv6ExtensionHeader`].
+    pub fn to_header(&self) -> Ipv6ExtensionHeader {
+        use Ipv6ExtensionSlice::*;
+        match self {
+            HopByHop(slice) => Ipv6ExtensionHeader::HopByHop(slice.to_header()),
+            Routing(slice) => Ipv6ExtensionHeader::Routing(slice.to_header()),
+            Fragment(slice) => Ipv6ExtensionHeader::Fragment(slice.to_header()),
+            DestinationOptions(slice) => Ipv6ExtensionHeader::DestinationOptions(slice.to_header()),
+            Authentication(slice) => Ipv6ExtensionHeader::Authentication(slice.to_header()),
+            Mobility(slice) => Ipv6ExtensionHeader::Mobility(slice.to_header()),
+            Hip(slice) => Ipv6ExtensionHeader::Hip(slice.to_header()),
+            Shim6(slice) => Ipv6ExtensionHeader::Shim6(slice.to_header()),
+        }
+    }
+}
+
+/// Iterator decoding an arbitrary chain of IPv6 extension headers from a slice
+/// (without allocating).
+///
+/// In contrast to [`Ipv6Extensions::from_slice`] the headers are returned in the
+/// order they are present in the packet and headers occurring multiple times are
+/// returned multiple times (rfc8200 requires nodes to accept extension headers "in
+/// any order and occurring any number of times in the same packet").
+///
+/// The iterator stops when the next header is not an IPv6 extension header
+/// supported by [`Ipv6ExtensionSlice`]. Afterwards [`Ipv6ExtensionsChainIter::next_header`]
+/// & [`Ipv6ExtensionsChainIter::rest`] contain the ip number & slice of the
+/// following header (e.g. the transport layer).
+///
+/// The number of decoded headers is limited (by default to
+/// [`IPV6_MAX_NUM_HEADER_EXTENSIONS`]). If more headers are present a
+/// `ReadError::Ipv6TooManyHeaderExtensions` error is returned. After an error
+/// the iteration stops & `next_header` & `rest` point to the header that
+/// caused the error.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Ipv6ExtensionsChainIter<'a> {
+    next_header: u8,
+    rest: &'a [u8],
+    count: usize,
+    max_headers: usize,
+    done: bool,
+}
+
+impl<'a> Ipv6ExtensionsChainIter<'a> {
+    /// Creates an iterator decoding at most [`IPV6_MAX_NUM_HEADER_EXTENSIONS`] headers.
+    pub fn new(start_ip_number: u8, slice: &'a [u8]) -> Ipv6ExtensionsChainIter<'a> {
+        Ipv6ExtensionsChainIter::with_max_headers(start_ip_number, slice, IPV6_MAX_NUM_HEADER_EXTENSIONS)
+    }
+
+    /// Creates an iterator decoding at most `max_headers` headers.
+    pub fn with_max_headers(start_ip_number: u8, slice: &'a [u8], max_headers: usize) -> Ipv6ExtensionsChainIter<'a> {
+        Ipv6ExtensionsChainIter {
+            next_header: start_ip_number,
+            rest: slice,
+            count: 0,
+            max_headers,
+            done: false,
+        }
+    }
+
+    /// Ip number of the next header that was not yet decoded.
+    #[inline]
+    pub fn next_header(&self) -> u8 {
+        self.next_header
+    }
+
+    /// Slice starting at the next header that was not yet decoded.
+    #[inline]
+    pub fn rest(&self) -> &'a [u8] {
+        self.rest
+    }
+
+    fn fail(&mut self, err: ReadError) -> Option<Result<Ipv6ExtensionSlice<'a>, ReadError>> {
+        self.done = true;
+        Some(Err(err))
+    }
+}
+
+impl<'a> Iterator for Ipv6ExtensionsChainIter<'a> {
+    type Item = Result<Ipv6ExtensionSlice<'a>, ReadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        use ip_number::*;
+        use Ipv6ExtensionSlice::*;
+
+        if self.done {
+            return None;
+        }
+
+        if !matches!(self.next_header, IPV6_HOP_BY_HOP | IPV6_ROUTE | IPV6_FRAG | IPV6_DEST_OPTIONS | AUTH | MOBILITY | HIP | SHIM6) {
+            self.done = true;
+            return None;
+        }
+
+        if self.count >= self.max_headers {
+            return self.fail(ReadError::Ipv6TooManyHeaderExtensions);
+        }
+
+        // the hop by hop header is required to occur directly after the ipv6 header
+        if IPV6_HOP_BY_HOP == self.next_header && 0 != self.count {
+            return self.fail(ReadError::Ipv6HopByHopHeaderNotAtStart);
+        }
+
+        let result = match self.next_header {
+            IPV6_HOP_BY_HOP => Ipv6RawExtensionHeaderSlice::from_slice(self.rest).map(HopByHop),
+            IPV6_ROUTE => Ipv6RawExtensionHeaderSlice::from_slice(self.rest).map(Routing),
+            IPV6_DEST_OPTIONS => Ipv6RawExtensionHeaderSlice::from_slice(self.rest).map(DestinationOptions),
+            IPV6_FRAG => Ipv6FragmentHeaderSlice::from_slice(self.rest).map(Fragment),
+            AUTH => IpAuthenticationHeaderSlice::from_slice(self.rest).map(Authentication),
+            MOBILITY => Ipv6MobilityHeaderSlice::from_slice(self.rest).map(Mobility),
+            HIP => HipHeaderSlice::from_slice(self.rest).map(Hip),
+            _ => Shim6HeaderSlice::from_slice(self.rest).map(Shim6),
+        };
+
+        match result {
+            Ok(header) => {
+                self.rest = &self.rest[header.slice().len()..];
+                self.next_header = header.next_header();
+                self.count += 1;
+                Some(Ok(header))
+            },
+            Err(err) => self.fail(err),
+        }
+    }
+}
+
+/// Owned chain of IPv6 extension headers preserving the order & repetitions
+/// of the headers.
+///
+/// In contrast to [`Ipv6Extensions`] any number of headers of each type can be
+/// represented. The `next_header` fields of the headers are written as they
+/// are set, use [`Ipv6ExtensionsChain::set_next_headers`] to link the headers.
+#[derive(Clone, Debug, Eq, PartialEq, Default)]
+pub struct Ipv6ExtensionsChain {
+    pub headers: Vec<Ipv6ExtensionHeader>,
+}
+
+impl Ipv6ExtensionsChain {
+    /// Reads all extension headers from the slice.
+    ///
+    /// Returns the found ipv6 extension headers, the next header ip number after the read
+    /// headers and a slice containing the rest of the packet after the read headers.
+    ///
+    /// As all headers have a length of at least 8 octets the number of headers is
+    /// limited by the length of the slice.
+    pub fn from_slice(start_ip_number: u8, slice: &[u8]) -> Result<(Ipv6ExtensionsChain, u8, &[u8]), ReadError> {
+        let mut iter = Ipv6ExtensionsChainIter::with_max_headers(start_ip_number, slice, usize::MAX);
+        let mut headers = Vec::new();
+        for header in &mut iter {
+            headers.push(header?.to_header());
+        }
+        Ok((Ipv6ExtensionsChain{ headers }, iter.next_header(), iter.rest()))
+    }
+
+    /// Writes the headers in the order they are present in the chain.
+    pub fn write<T: io::Write + Sized>(&self, writer: &mut T) -> Result<(), WriteError> {
+        for header in &self.headers {
+            header.write(writer)?;
+        }
+        Ok(())
+    }
+
+    /// Length of all headers in bytes.
+    pub fn header_len(&self) -> usize {
+        self.headers.iter().map(|h| h.header_len()).sum()
+    }
+
+    /// Sets all the next_header fields of the headers based on the order of the
+    /// chain. The last header gets `last_protocol_number` as next header.
+    ///
+    /// Returns the ip number of the first header (or `last_protocol_number` if
+    /// the chain is empty) which has to be set as `next_header` in the ipv6 header.
+    pub fn set_next_headers(&mut self, last_protocol_number: u8) -> u8 {
+        let mut next = last_protocol_number;
+        for header in self.headers.iter_mut().rev() {
+            header.set_next_header(next);
+            next = header.ip_number();
+        }
+        next
+    }
+
+    /// Returns true if a fragment header is present that causes the payload to be fragmented.
+    pub fn is_fragmenting_payload(&self) -> bool {
+        self.headers.iter().any(|h| matches!(h, Ipv6ExtensionHeader::Fragment(f) if f.is_fragmenting_payload()))
+    }
+}
+
+impl From<Ipv6ExtensionsSlice<'_>> for Ipv6ExtensionsChain {
+    fn from(slice: Ipv6ExtensionsSlice<'_>) -> Ipv6ExtensionsChain {
+        Ipv6ExtensionsChain{
+            headers: slice.into_iter().map(|h| h.to_header()).collect(),
+        }
+    }
+}
@@ -0,0 +1,503 @@
+use super::super::*;
+use arrayvec::ArrayVec;
+
+use std::fmt::{Debug, Formatter};
+use std::slice::from_raw_parts;
+
+/// Module containing the constants for the mobility header message types (rfc6275).
+pub mod ipv6_mobility_type {
+    /// `u8` identifying a "Binding Refresh Request" message.
+    pub const BINDING_REFRESH_REQUEST: u8 = 0;
+    /// `u8` identifying a "Home Test Init" message.
+    pub const HOME_TEST_INIT: u8 = 1;
+    /// `u8` identifying a "Care-of Test Init" message.
+    pub const CARE_OF_TEST_INIT: u8 = 2;
+    /// `u8` identifying a "Home Test" message.
+    pub const HOME_TEST: u8 = 3;
+    /// `u8` identifying a "Care-of Test" message.
+    pub const CARE_OF_TEST: u8 = 4;
+    /// `u8` identifying a "Binding Update" message.
+    pub const BINDING_UPDATE: u8 = 5;
+    /// `u8` identifying a "Binding Acknowledgement" message.
+    pub const BINDING_ACKNOWLEDGEMENT: u8 = 6;
+    /// `u8` identifying a "Binding Error" message.
+    pub const BINDING_ERROR: u8 = 7;
+}
+
+/// Message contained in an ipv6 mobility header (rfc6275).
+///
+/// `options` contains the encoded mobility options following the fixed
+/// part of the message (including padding).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Ipv6MobilityMessage<'a> {
+    BindingRefreshRequest{ options: &'a [u8] },
+    HomeTestInit{ home_init_cookie: [u8;8], options: &'a [u8] },
+    CareOfTestInit{ care_of_init_cookie: [u8;8], options: &'a [u8] },
+    HomeTest{ home_nonce_index: u16, home_init_cookie: [u8;8], home_keygen_token: [u8;8], options: &'a [u8] },
+    CareOfTest{ care_of_nonce_index: u16, care_of_init_cookie: [u8;8], care_of_keygen_token: [u8;8], options: &'a [u8] },
+    BindingUpdate{
+        sequence_number: u16,
+        /// "A" flag, requesting a binding acknowledgement.
+        acknowledge: bool,
+        /// "H" flag, requesting the receiver to act as home agent.
+        home_registration: bool,
+        /// "L" flag, set if the home address has the same interface identifier as the link-local address.
+        link_local_address_compatibility: bool,
+        /// "K" flag, set if IPsec SAs survive movements.
+        key_management_mobility_capability: bool,
+        /// Lifetime in units of 4 seconds.
+        lifetime: u16,
+        options: &'a [u8],
+    },
+    BindingAcknowledgement{
+        status: u8,
+        /// "K" flag, set if IPsec SAs survive movements.
+        key_management_mobility_capability: bool,
+        sequence_number: u16,
+        /// Lifetime in units of 4 seconds.
+        lifetime: u16,
+        options: &'a [u8],
+    },
+    BindingError{ status: u8, home_address: [u8;16], options: &'a [u8] },
+    /// Message with a type not decoded by etherparse (`data` contains the
+    /// message data after the checksum).
+    Unknown{ mh_type: u8, data: &'a [u8] },
+}
+
+impl<'a> Ipv6MobilityMessage<'a> {
+    /// Decodes the message data (data after the checksum field) of a
+    /// mobility header with the given type.
+    pub fn from_message_data(mh_type: u8, data: &'a [u8]) -> Result<Ipv6MobilityMessage<'a>, ReadError> {
+        use ipv6_mobility_type::*;
+        use Ipv6MobilityMessage::*;
+
+        let fixed_len = Self::fixed_len(mh_type);
+        if data.len() < fixed_len {
+            return Err(ReadError::UnexpectedEndOfSlice(fixed_len));
+        }
+        let options = &data[fixed_len..];
+        let u8x8 = |start: usize| {
+            let mut result = [0u8;8];
+            result.copy_from_slice(&data[start..start + 8]);
+            result
+        };
+
+        Ok(match mh_type {
+            BINDING_REFRESH_REQUEST => BindingRefreshRequest{ options },
+            HOME_TEST_INIT => HomeTestInit{ home_init_cookie: u8x8(2), options },
+            CARE_OF_TEST_INIT => CareOfTestInit{ care_of_init_cookie: u8x8(2), options },
+            HOME_TEST => HomeTest{
+                home_nonce_index: u16::from_be_bytes([data[0], data[1]]),
+                home_init_cookie: u8x8(2),
+                home_keygen_token: u8x8(10),
+                options,
+            },
+            CARE_OF_TEST => CareOfTest{
+                care_of_nonce_index: u16::from_be_bytes([data[0], data[1]]),
+                care_of_init_cookie: u8x8(2),
+                care_of_keygen_token: u8x8(10),
+                options,
+            },
+            BINDING_UPDATE => BindingUpdate{
+                sequence_number: u16::from_be_bytes([data[0], data[1]]),
+                acknowledge: 0 != data[2] & 0b1000_0000,
+                home_registration: 0 != data[2] & 0b0100_0000,
+                link_local_address_compatibility: 0 != data[2] & 0b0010_0000,
+                key_management_mobility_capability: 0 != data[2] & 0b0001_0000,
+                lifetime: u16::from_be_bytes([data[4], data[5]]),
+                options,
+            },
+            BINDING_ACKNOWLEDGEMENT => BindingAcknowledgement{
+                status: data[0],
+                key_management_mobility_capability: 0 != data[1] & 0b1000_0000,
+                sequence_number: u16::from_be_bytes([data[2], data[3]]),
+                lifetime: u16::from_be_bytes([data[4], data[5]]),
+                options,
+            },
+            BINDING_ERROR => BindingError{
+                status: data[0],
+                home_address: {
+                    let mut result = [0u8;16];
+                    result.copy_from_slice(&data[2..18]);
+                    result
+                },
+                options,
+            },
+            mh_type => Unknown{ mh_type, data },
+        })
+    }
+
+    /// Length of the fixed part of the message data (before the options).
+    fn fixed_len(mh_type: u8) -> usize {
+        use ipv6_mobility_type::*;
+        match mh_type {
+            BINDING_REFRESH_REQUEST => 2,
+            HOME_TEST_INIT | CARE_OF_TEST_INIT => 10,
+            HOME_TEST | CARE_OF_TEST => 18,
+            BINDING_UPDATE | BINDING_ACKNOWLEDGEMENT => 6,
+            BINDING_ERROR => 18,
+            _ => 0,
+        }
+    }
+
+    /// Returns the mobility header type value of the message.
+    pub fn mh_type(&self) -> u8 {
+        use ipv6_mobility_type::*;
+        use Ipv6MobilityMessage::*;
+        match self {
+            BindingRefreshRequest{..} => BINDING_REFRESH_REQUEST,
+            HomeTestInit{..} => HOME_TEST_INIT,
+            CareOfTestInit{..} => CARE_OF_TEST_INIT,
+            HomeTest{..} => HOME_TEST,
+            CareOfTest{..} => CARE_OF_TEST,
+            BindingUpdate{..} => BINDING_UPDATE,
+            BindingAcknowledgement{..} => BINDING_ACKNOWLEDGEMENT,
+            BindingError{..} => BINDING_ERROR,
+            Unknown{ mh_type, .. } => *mh_type,
+        }
+    }
+
+    /// Length of the message data in bytes when serialized (without padding).
+    pub fn serialized_len(&self) -> usize {
+        use Ipv6MobilityMessage::*;
+        match self {
+            BindingRefreshRequest{ options } |
+            HomeTestInit{ options, .. } |
+            CareOfTestInit{ options, .. } |
+            HomeTest{ options, .. } |
+            CareOfTest{ options, .. } |
+            BindingUpdate{ options, .. } |
+            BindingAcknowledgement{ options, .. } |
+            BindingError{ options, .. } => Self::fixed_len(self.mh_type()) + options.len(),
+            Unknown{ data, .. } => data.len(),
+        }
+    }
+
+    /// Writes the message data to the start of the buffer (the buffer must
+    /// have at least the length `serialized_len()`).
+    fn write_to_slice(&self, buffer: &mut [u8]) {
+        use Ipv6MobilityMessage::*;
+
+        let fixed_len = Self::fixed_len(self.mh_type());
+        for b in &mut buffer[..fixed_len] {
+            *b = 0;
+        }
+        let options = match self {
+            BindingRefreshRequest{ options } => options,
+            HomeTestInit{ home_init_cookie: cookie, options } |
+            CareOfTestInit{ care_of_init_cookie: cookie, options } => {
+                buffer[2..10].copy_from_slice(cookie);
+                options
+            },
+            HomeTest{ home_nonce_index: index, home_init_cookie: cookie, home_keygen_token: token, options } |
+            CareOfTest{ care_of_nonce_index: index, care_of_init_cookie: cookie, care_of_keygen_token: token, options } => {
+                buffer[..2].copy_from_slice(&index.to_be_bytes());
+                buffer[2..10].copy_from_slice(cookie);
+                buffer[10..18].copy_from_slice(token);
+                options
+            },
+            BindingUpdate{
+                sequence_number,
+                acknowledge,
+                home_registration,
+                link_local_address_compatibility,
+                key_management_mobility_capability,
+                lifetime,
+                options
+            } => {
+                buffer[..2].copy_from_slice(&sequence_number.to_be_bytes());
+                buffer[2] = (if *acknowledge { 0b1000_0000 } else { 0 }) |
+                    (if *home_registration { 0b0100_0000 } else { 0 }) |
+                    (if *link_local_address_compatibility { 0b0010_0000 } else { 0 }) |
+                    (if *key_management_mobility_capability { 0b0001_0000 } else { 0 });
+                buffer[4..6].copy_from_slice(&lifetime.to_be_bytes());
+                options
+            },
+            BindingAcknowledgement{ status, key_management_mobility_capability, sequence_number, lifetime, options } => {
+                buffer[0] = *status;
+                buffer[1] = if *key_management_mobility_capability { 0b1000_0000 } else { 0 };
+                buffer[2..4].copy_from_slice(&sequence_number.to_be_bytes());
+                buffer[4..6].copy_from_slice(&lifetime.to_be_bytes());
+                options
+            },
+            BindingError{ status, home_address, options } => {
+                buffer[0] = *status;
+                buffer[2..18].copy_from_slice(home_address);
+                options
+            },
+            Unknown{ data, .. } => data,
+        };
+        buffer[fixed_len..fixed_len + options.len()].copy_from_slice(options);
+    }
+}
+
+/// Calculates the checksum of a mobility header (the checksum field itself is skipped).
+fn mobility_checksum(source: [u8;16], destination: [u8;16], header: &[u8]) -> u16 {
+    checksum::Sum16BitWords::new()
+        .add_16bytes(source)
+        .add_16bytes(destination)
+        .add_4bytes((header.len() as u32).to_be_bytes())
+        .add_2bytes([0, ip_number::MOBILITY])
+        .add_slice(&header[..4])
+        .add_slice(&header[6..])
+        .ones_complement()
+        .to_be()
+}
+
+/// IPv6 mobility header (rfc6275).
+#[derive(Clone)]
+pub struct Ipv6MobilityHeader {
+    /// IP protocol number of the header following the mobility header
+    /// (`payload proto` field, should be `IPV6_NO_NEXT_HEADER`).
+    pub payload_proto: u8,
+    /// Type of the message contained in the header.
+    pub mh_type: u8,
+    pub checksum: u16,
+    /// Length of the header in 8 octets (minus the first 8 octets).
+    header_length: u8,
+    /// Message data after the checksum field.
+    message_data_buffer: [u8;Ipv6MobilityHeader::MAX_MESSAGE_DATA_LEN],
+}
+
+impl Debug for Ipv6MobilityHeader {
+    fn fmt(&self, formatter: &mut Formatter) -> Result<(), std::fmt::Error> {
+        write!(formatter, "Ipv6MobilityHeader {{ payload_proto: {}, mh_type: {}, checksum: {}, message_data: {:?} }}",
+            self.payload_proto,
+            self.mh_type,
+            self.checksum,
+            self.message_data())
+    }
+}
+
+impl PartialEq for Ipv6MobilityHeader {
+    fn eq(&self, other: &Self) -> bool {
+        self.payload_proto == other.payload_proto &&
+        self.mh_type == other.mh_type &&
+        self.checksum == other.checksum &&
+        self.message_data() == other.message_data()
+    }
+}
+
+impl Eq for Ipv6MobilityHeader {}
+
+impl Ipv6MobilityHeader {
+    /// Minimum length of the message data of a mobility header.
+    pub const MIN_MESSAGE_DATA_LEN: usize = 2;
+
+    /// Maximum length of the message data of a mobility header.
+    pub const MAX_MESSAGE_DATA_LEN: usize = 0xff*8 + 2;
+
+    /// Creates a mobility header containing the given message. The options
+    /// of the message are padded with a Pad1 or PadN option so the header length
+    /// is a multiple of 8 octets.
+    ///
+    /// The checksum is set to 0, use [`Ipv6MobilityHeader::calc_checksum`] to
+    /// calculate it.
+    pub fn new(payload_proto: u8, message: &Ipv6MobilityMessage) -> Result<Ipv6MobilityHeader, ValueError> {
+        let len = message.serialized_len();
+        if len > Ipv6MobilityHeader::MAX_MESSAGE_DATA_LEN {
+            return Err(ValueError::Ipv6ExtensionPayloadTooLarge(len + 4));
+        }
+        let mut buffer = [0u8;Ipv6MobilityHeader::MAX_MESSAGE_DATA_LEN];
+        message.write_to_slice(&mut buffer);
+
+        // pad to a multiple of 8 octets (the header before the message data is 6 bytes long)
+        let padding = if len < Ipv6MobilityHeader::MIN_MESSAGE_DATA_LEN {
+            Ipv6MobilityHeader::MIN_MESSAGE_DATA_LEN - len
+        } else {
+            (8 - (len + 6) % 8) % 8
+        };
+        if len + padding > Ipv6MobilityHeader::MAX_MESSAGE_DATA_LEN {
+            return Err(ValueError::Ipv6ExtensionPayloadTooLarge(len + padding + 4));
+        }
+        write_ipv6_padding(&mut buffer[len..], padding);
+
+        Ipv6MobilityHeader::new_raw(payload_proto, message.mh_type(), &buffer[..len + padding])
+    }
+
+    /// Creates a mobility header with the given message data (data after the
+    /// checksum field).
+    ///
+    /// The message data must have at least a length of 2 bytes, at most a length
+    /// of 2042 bytes & `(message_data.len() + 6) % 8 == 0` must be true. Otherwise an
+    /// `Ipv6ExtensionPayloadTooSmall`, `Ipv6ExtensionPayloadTooLarge` or
+    /// `Ipv6ExtensionPayloadLengthUnaligned` error with the length of the
+    /// header payload (message data + 4) is returned.
+    pub fn new_raw(payload_proto: u8, mh_type: u8, message_data: &[u8]) -> Result<Ipv6MobilityHeader, ValueError> {
+        use ValueError::*;
+        let payload_len = message_data.len() + 4;
+        if message_data.len() < Ipv6MobilityHeader::MIN_MESSAGE_DATA_LEN {
+            Err(Ipv6ExtensionPayloadTooSmall(payload_len))
+        } else if message_data.len() > Ipv6MobilityHeader::MAX_MESSAGE_DATA_LEN {
+            Err(Ipv6ExtensionPayloadTooLarge(payload_len))
+        } else {
+            match (payload_len + 2) % 8 {
+                0 => {
+                    let mut result = Ipv6MobilityHeader{
+                        payload_proto,
+                        mh_type,
+                        checksum: 0,
+                        header_length: ((message_data.len() - 2) / 8) as u8,
+                        message_data_buffer: [0;Ipv6MobilityHeader::MAX_MESSAGE_DATA_LEN],
+                    };
+                    result.message_data_buffer[..message_data.len()].copy_from_slice(message_data);
+                    Ok(result)
+                },
+                _ => Err(Ipv6ExtensionPayloadLengthUnaligned(payload_len)),
+            }
+        }
+    }
+
+    /// Read a mobility header from a slice and return the header & unused parts of the slice.
+    pub fn from_slice(slice: &[u8]) -> Result<(Ipv6MobilityHeader, &[u8]), ReadError> {
+        let s = Ipv6MobilityHeaderSlice::from_slice(slice)?;
+        let rest = &slice[s.slice().len()..];
+        Ok((s.to_header(), rest))
+    }
+
+    /// Returns the message data (data after the checksum field).
+    pub fn message_data(&self) -> &[u8] {
+        &self.message_data_buffer[..2 + usize::from(self.header_length)*8]
+    }
+
+    /// Decodes the message contained in the header.
+    pub fn message(&self) -> Result<Ipv6MobilityMessage<'_>, ReadError> {
+        Ipv6MobilityMessage::from_message_data(self.mh_type, self.message_data())
+    }
+
+    /// Length of the header in bytes.
+    pub fn header_len(&self) -> usize {
+        8 + usize::from(self.header_length)*8
+    }
+
+    /// Returns the serialized header.
+    pub fn to_bytes(&self) -> ArrayVec<u8, { 8 + Ipv6MobilityHeader::MAX_MESSAGE_DATA_LEN - 2 }> {
+        let mut result = ArrayVec::new();
+        let checksum_be = self.checksum.to_be_bytes();
+        result.extend([
+            self.payload_proto,
+            self.header_length,
+            self.mh_type,
+            0,
+            checksum_be[0],
+            checksum_be[1],
+        ]);
+        // SAFETY: the message data has at most the length of the remaining capacity
+        unsafe {
+            result.try_extend_from_slice(self.message_data()).unwrap_unchecked();
+        }
+        result
+    }
+
+    /// Writes the header to the given writer.
+    pub fn write<T: io::Write + Sized>(&self, writer: &mut T) -> Result<(), WriteError> {
+        writer.write_all(&self.to_bytes())?;
+        Ok(())
+    }
+
+    /// Calculates the checksum of the header based on the given ipv6 source &
+    /// destination addresses (rfc6275 section 6.1.1).
+    pub fn calc_checksum(&self, source: [u8;16], destination: [u8;16]) -> u16 {
+        mobility_checksum(source, destination, &self.to_bytes())
+    }
+}
+
+/// Slice containing an ipv6 mobility header (rfc6275).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Ipv6MobilityHeaderSlice<'a> {
+    slice: &'a [u8],
+}
+
+impl<'a> Ipv6MobilityHeaderSlice<'a> {
+    /// Creates a mobility header slice from a slice.
+    pub fn from_slice(slice: &'a [u8]) -> Result<Ipv6MobilityHeaderSlice<'a>, ReadError> {
+        use crate::ReadError::*;
+        if slice.len() < 8 {
+            return Err(UnexpectedEndOfSlice(8));
+        }
+        let len = (usize::from(slice[1]) + 1)*8;
+        if slice.len() < len {
+            return Err(UnexpectedEndOfSlice(len));
+        }
+        Ok(Ipv6MobilityHeaderSlice {
+            // SAFETY: the length was checked above
+            slice: unsafe {
+                from_raw_parts(slice.as_ptr(), len)
+            }
+        })
+    }
+
+    /// Creates a mobility header slice from a slice (assumes slice size &
+    /// content was validated before).
+    ///
+    /// # Safety
+    ///
+    /// The slice must have a length of at least 8 and `(slice[1] + 1)*8`.
+    pub unsafe fn from_slice_unchecked(slice: &'a [u8]) -> Ipv6MobilityHeaderSlice<'a> {
+        Ipv6MobilityHeaderSlice {
+            slice: from_raw_parts(
+                slice.as_ptr(),
+                ((*slice.get_unchecked(1) as usize) + 1)*8
+            )
+        }
+    }
+
+    /// Returns the slice containing the mobility header.
+    #[inline]
+    pub fn slice(&self) -> &'a [u8] {
+        self.slice
+    }
+
+    /// Returns the ip number of the header following the mobility header
+    /// (`payload proto` field).
+    #[inline]
+    pub fn payload_proto(&self) -> u8 {
+        self.slice[0]
+    }
+
+    /// Alias of [`Ipv6MobilityHeaderSlice::payload_proto`].
+    #[inline]
+    pub fn next_header(&self) -> u8 {
+        self.payload_proto()
+    }
+
+    /// Returns the type of the message contained in the header.
+    #[inline]
+    pub fn mh_type(&self) -> u8 {
+        self.slice[2]
+    }
+
+    /// Returns the checksum field of the header.
+    #[inline]
+    pub fn checksum(&self) -> u16 {
+        u16::from_be_bytes([self.slice[4], self.slice[5]])
+    }
+
+    /// Returns the message data (data after the checksum field).
+    #[inline]
+    pub fn message_data(&self) -> &'a [u8] {
+        &self.slice[6..]
+    }
+
+    /// Decodes the message contained in the header.
+    pub fn message(&self) -> Result<Ipv6MobilityMessage<'a>, ReadError> {
+        Ipv6MobilityMessage::from_message_data(self.mh_type(), self.message_data())
+    }
+
+    /// Calculates the checksum of the header based on the given ipv6 source &
+    /// destination addresses (rfc6275 section 6.1.1).
+    pub fn calc_checksum(&self, source: [u8;16], destination: [u8;16]) -> u16 {
+        mobility_checksum(source, destination, self.slice)
+    }
+
+    /// Decode all the fields and copy the results to an [`Ipv6MobilityHeader`].
+    pub fn to_header(&self) -> Ipv6MobilityHeader {
+        let mut result = Ipv6MobilityHeader::new_raw(
+            self.payload_proto(),
+            self.mh_type(),
+            self.message_data()
+        ).unwrap();
+        result.checksum = self.checksum();
+        result
+    }
+}
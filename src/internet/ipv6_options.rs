@@ -0,0 +1,633 @@
+use super::super::*;
+
+use std::fmt::{Debug, Formatter};
+
+/// Module containing the constants for ipv6 hop by hop & destination options (type numbers & sizes).
+pub mod ipv6_option {
+    /// `u8` identifying a "Pad1" option (single byte of padding).
+    pub const TYPE_PAD1: u8 = 0;
+    /// `u8` identifying a "PadN" option (multiple bytes of padding).
+    pub const TYPE_PADN: u8 = 1;
+    /// `u8` identifying a "Tunnel Encapsulation Limit" option (rfc2473).
+    pub const TYPE_TUNNEL_ENCAPSULATION_LIMIT: u8 = 0x04;
+    /// `u8` identifying a "Router Alert" option (rfc2711).
+    pub const TYPE_ROUTER_ALERT: u8 = 0x05;
+    /// `u8` identifying a "Common Architecture Label IPv6 Security Option" (CALIPSO, rfc5570).
+    pub const TYPE_CALIPSO: u8 = 0x07;
+    /// `u8` identifying a "Jumbo Payload" option (rfc2675).
+    pub const TYPE_JUMBO_PAYLOAD: u8 = 0xc2;
+    /// `u8` identifying a "Home Address" option (rfc6275).
+    pub const TYPE_HOME_ADDRESS: u8 = 0xc9;
+    /// Length of the data of a "Tunnel Encapsulation Limit" option.
+    pub const DATA_LEN_TUNNEL_ENCAPSULATION_LIMIT: u8 = 1;
+    /// Length of the data of a "Router Alert" option.
+    pub const DATA_LEN_ROUTER_ALERT: u8 = 2;
+    /// Minimum length of the data of a "CALIPSO" option (without compartment bitmap).
+    pub const DATA_MIN_LEN_CALIPSO: u8 = 8;
+    /// Length of the data of a "Jumbo Payload" option.
+    pub const DATA_LEN_JUMBO_PAYLOAD: u8 = 4;
+    /// Length of the data of a "Home Address" option.
+    pub const DATA_LEN_HOME_ADDRESS: u8 = 16;
+    /// "Router Alert" option value indicating a Multicast Listener Discovery message (rfc2710).
+    pub const ROUTER_ALERT_MLD: u16 = 0;
+    /// "Router Alert" option value indicating a RSVP message (rfc2711).
+    pub const ROUTER_ALERT_RSVP: u16 = 1;
+}
+
+/// Action that must be taken by a node processing an option with an
+/// unrecognized option type (encoded in the highest two bits of the type).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Ipv6OptionUnknownAction {
+    /// `00`: Skip over this option and continue processing the header.
+    Skip,
+    /// `01`: Discard the packet.
+    Discard,
+    /// `10`: Discard the packet and send an ICMP Parameter Problem (code 2)
+    /// to the source address (regardless of the destination address).
+    DiscardSendIcmp,
+    /// `11`: Discard the packet and send an ICMP Parameter Problem (code 2)
+    /// to the source address if the destination was not a multicast address.
+    DiscardSendIcmpNotMulticast,
+}
+
+impl Ipv6OptionUnknownAction {
+    /// Decodes the action from the highest two bits of an option type.
+    pub fn from_option_type(option_type: u8) -> Ipv6OptionUnknownAction {
+        use Ipv6OptionUnknownAction::*;
+        match option_type >> 6 {
+            0 => Skip,
+            1 => Discard,
+            2 => DiscardSendIcmp,
+            _ => DiscardSendIcmpNotMulticast,
+        }
+    }
+}
+
+/// Options that can be present in an ipv6 hop by hop or destination options header.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Ipv6OptionElement<'a> {
+    /// Single byte of padding.
+    Pad1,
+    /// Padding of 2 or more bytes (the value is the number of zero bytes
+    /// following the type & length fields).
+    PadN(u8),
+    /// "Tunnel Encapsulation Limit" option (rfc2473), number of further
+    /// nested encapsulations permitted for the packet.
+    TunnelEncapsulationLimit(u8),
+    /// "Router Alert" option (rfc2711).
+    RouterAlert(u16),
+    /// "Common Architecture Label IPv6 Security Option" (rfc5570).
+    ///
+    /// The length of the compartment bitmap must be a multiple of 4 bytes.
+    Calipso{ domain_of_interpretation: u32, sensitivity_level: u8, checksum: u16, compartment_bitmap: &'a [u8] },
+    /// "Jumbo Payload" option (rfc2675) containing the length of the
+    /// packet (excluding the ipv6 header).
+    JumboPayload(u32),
+    /// "Home Address" option (rfc6275).
+    HomeAddress([u8;16]),
+    /// Option with a type not decoded by etherparse (or an option that is
+    /// present with a non standard length).
+    Unknown{ option_type: u8, data: &'a [u8] },
+}
+
+impl<'a> Ipv6OptionElement<'a> {
+    /// Returns the type value of the option.
+    pub fn option_type(&self) -> u8 {
+        use Ipv6OptionElement::*;
+        use ipv6_option::*;
+        match self {
+            Pad1 => TYPE_PAD1,
+            PadN(_) => TYPE_PADN,
+            TunnelEncapsulationLimit(_) => TYPE_TUNNEL_ENCAPSULATION_LIMIT,
+            RouterAlert(_) => TYPE_ROUTER_ALERT,
+            Calipso{..} => TYPE_CALIPSO,
+            JumboPayload(_) => TYPE_JUMBO_PAYLOAD,
+            HomeAddress(_) => TYPE_HOME_ADDRESS,
+            Unknown{ option_type, .. } => *option_type,
+        }
+    }
+
+    /// Returns the action a node has to take if it does not recognize the option type.
+    #[inline]
+    pub fn unknown_action(&self) -> Ipv6OptionUnknownAction {
+        Ipv6OptionUnknownAction::from_option_type(self.option_type())
+    }
+
+    /// Returns true if the option data may change en route to the
+    /// packet's final destination (third highest bit of the option type).
+    #[inline]
+    pub fn may_change_en_route(&self) -> bool {
+        0 != self.option_type() & 0b0010_0000
+    }
+
+    /// Length of the option in bytes when serialized (including the type & length fields).
+    pub fn serialized_len(&self) -> usize {
+        use Ipv6OptionElement::*;
+        use ipv6_option::*;
+        match self {
+            Pad1 => 1,
+            PadN(len) => 2 + usize::from(*len),
+            TunnelEncapsulationLimit(_) => 2 + usize::from(DATA_LEN_TUNNEL_ENCAPSULATION_LIMIT),
+            RouterAlert(_) => 2 + usize::from(DATA_LEN_ROUTER_ALERT),
+            Calipso{ compartment_bitmap, .. } => 2 + usize::from(DATA_MIN_LEN_CALIPSO) + compartment_bitmap.len(),
+            JumboPayload(_) => 2 + usize::from(DATA_LEN_JUMBO_PAYLOAD),
+            HomeAddress(_) => 2 + usize::from(DATA_LEN_HOME_ADDRESS),
+            Unknown{ data, .. } => 2 + data.len(),
+        }
+    }
+
+    /// Alignment requirement `xn+y` of the option as `(x, y)` (offset of
+    /// the option type relative to the start of the extension header).
+    pub fn alignment(&self) -> (usize, usize) {
+        use Ipv6OptionElement::*;
+        match self {
+            RouterAlert(_) => (2, 0),
+            Calipso{..} | JumboPayload(_) => (4, 2),
+            HomeAddress(_) => (8, 6),
+            _ => (1, 0),
+        }
+    }
+
+    /// Checks that the option data can be represented in the option length field.
+    fn check_len(&self) -> Result<(), ValueError> {
+        use Ipv6OptionElement::*;
+        let data_len = match self {
+            PadN(_) | Pad1 | TunnelEncapsulationLimit(_) |
+            RouterAlert(_) | JumboPayload(_) | HomeAddress(_) => return Ok(()),
+            Calipso{ compartment_bitmap, .. } => {
+                if 0 != compartment_bitmap.len() % 4 {
+                    return Err(ValueError::Ipv6OptionDataLengthBad(compartment_bitmap.len()));
+                }
+                self.serialized_len() - 2
+            },
+            Unknown{ data, .. } => data.len(),
+        };
+        if data_len > 0xff {
+            Err(ValueError::Ipv6OptionDataLengthBad(data_len))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Writes the option to the start of the given buffer (the caller has
+    /// to ensure the buffer has at least `serialized_len` bytes and the
+    /// length was checked via `check_len`).
+    fn write_to_slice(&self, buffer: &mut [u8]) {
+        use Ipv6OptionElement::*;
+
+        let len = self.serialized_len();
+        buffer[0] = self.option_type();
+        if len == 1 {
+            return;
+        }
+        buffer[1] = (len - 2) as u8;
+        let data = &mut buffer[2..len];
+        match self {
+            Pad1 => {},
+            PadN(_) => {
+                for b in data.iter_mut() {
+                    *b = 0;
+                }
+            },
+            TunnelEncapsulationLimit(value) => data[0] = *value,
+            RouterAlert(value) => data.copy_from_slice(&value.to_be_bytes()),
+            Calipso{ domain_of_interpretation, sensitivity_level, checksum, compartment_bitmap } => {
+                data[..4].copy_from_slice(&domain_of_interpretation.to_be_bytes());
+                data[4] = (compartment_bitmap.len() / 4) as u8;
+                data[5] = *sensitivity_level;
+                data[6..8].copy_from_slice(&checksum.to_be_bytes());
+                data[8..].copy_from_slice(compartment_bitmap);
+            },
+            JumboPayload(value) => data.copy_from_slice(&value.to_be_bytes()),
+            HomeAddress(value) => data.copy_from_slice(value),
+            Unknown{ data: value, .. } => data.copy_from_slice(value),
+        }
+    }
+}
+
+/// Writes the padding needed to fill `len` bytes starting at `buffer[0]`.
+pub(crate) fn write_ipv6_padding(buffer: &mut [u8], len: usize) {
+    match len {
+        0 => {},
+        1 => buffer[0] = ipv6_option::TYPE_PAD1,
+        len => {
+            buffer[0] = ipv6_option::TYPE_PADN;
+            buffer[1] = (len - 2) as u8;
+            for b in &mut buffer[2..len] {
+                *b = 0;
+            }
+        }
+    }
+}
+
+impl Ipv6RawExtensionHeader {
+    /// Creates a hop by hop or destination options header containing the given options.
+    ///
+    /// Padding is inserted in front of options with an alignment requirement
+    /// (e.g. `4n+2` for the jumbo payload option) and at the end of the header so
+    /// that the length of the header is a multiple of 8 octets.
+    ///
+    /// # Errors
+    ///
+    /// * `ValueError::Ipv6OptionDataLengthBad` if the data of an option can not be
+    ///   represented in the option (bigger then 255 bytes or an unaligned
+    ///   CALIPSO compartment bitmap).
+    /// * `ValueError::Ipv6ExtensionPayloadTooLarge` if the options do not fit into
+    ///   a single extension header.
+    pub fn new_with_options(next_header: u8, options: &[Ipv6OptionElement]) -> Result<Ipv6RawExtensionHeader, ValueError> {
+        const MAX_LEN: usize = Ipv6RawExtensionHeader::MAX_PAYLOAD_LEN + 2;
+        let mut buffer = [0u8; MAX_LEN];
+
+        // offset relative to the start of the header (next header & length included)
+        let mut offset = 2;
+        for option in options {
+            option.check_len()?;
+
+            let (x, y) = option.alignment();
+            let padding = (x + y - offset % x) % x;
+            let end = offset + padding + option.serialized_len();
+            if end > MAX_LEN {
+                return Err(ValueError::Ipv6ExtensionPayloadTooLarge(end - 2));
+            }
+            write_ipv6_padding(&mut buffer[offset..], padding);
+            option.write_to_slice(&mut buffer[offset + padding..]);
+            offset = end;
+        }
+
+        // pad to a multiple of 8 octets
+        let padding = (8 - offset % 8) % 8;
+        if offset + padding > MAX_LEN {
+            return Err(ValueError::Ipv6ExtensionPayloadTooLarge(offset + padding - 2));
+        }
+        write_ipv6_padding(&mut buffer[offset..], padding);
+        offset += padding;
+
+        Ipv6RawExtensionHeader::new_raw(next_header, &buffer[2..offset])
+    }
+
+    /// Returns an iterator over the options in the header.
+    ///
+    /// Note that only hop by hop & destination options headers contain options.
+    pub fn options_iterator(&self) -> Ipv6OptionsIterator<'_> {
+        Ipv6OptionsIterator::from_slice(self.payload())
+    }
+
+    /// Returns the value of the first "Jumbo Payload" option (rfc2675) in the
+    /// header (if present).
+    pub fn jumbo_payload_length(&self) -> Option<u32> {
+        jumbo_payload_length(self.options_iterator())
+    }
+}
+
+impl<'a> Ipv6RawExtensionHeaderSlice<'a> {
+    /// Returns an iterator over the options in the header.
+    ///
+    /// Note that only hop by hop & destination options headers contain options.
+    pub fn options_iterator(&self) -> Ipv6OptionsIterator<'a> {
+        Ipv6OptionsIterator::from_slice(self.payload())
+    }
+
+    /// Returns the value of the first "Jumbo Payload" option (rfc2675) in the
+    /// header (if present).
+    pub fn jumbo_payload_length(&self) -> Option<u32> {
+        jumbo_payload_length(self.options_iterator())
+    }
+}
+
+/// Returns the value of the first jumbo payload option (stops at the first error).
+fn jumbo_payload_length(options: Ipv6OptionsIterator) -> Option<u32> {
+    for option in options {
+        match option {
+            Ok(Ipv6OptionElement::JumboPayload(value)) => return Some(value),
+            Ok(_) => {},
+            Err(_) => return None,
+        }
+    }
+    None
+}
+
+/// Returns the length of the payload of an ipv6 packet (data after the ipv6 header).
+///
+/// If the payload length field is zero and a hop by hop header with a jumbo
+/// payload option is present (rfc2675) the value of the option is returned.
+pub(crate) fn ipv6_payload_len(header: &Ipv6HeaderSlice, payload: &[u8]) -> usize {
+    if 0 == header.payload_length() && ip_number::IPV6_HOP_BY_HOP == header.next_header() {
+        Ipv6RawExtensionHeaderSlice::from_slice(payload)
+            .ok()
+            .and_then(|hop_by_hop| hop_by_hop.jumbo_payload_length())
+            .map(|len| len as usize)
+            .unwrap_or(0)
+    } else {
+        usize::from(header.payload_length())
+    }
+}
+
+impl Ipv6Extensions {
+    /// Returns the value of the "Jumbo Payload" option (rfc2675) if a hop by hop
+    /// header containing the option is present.
+    pub fn jumbo_payload_length(&self) -> Option<u32> {
+        self.hop_by_hop_options
+            .as_ref()
+            .and_then(|header| header.jumbo_payload_length())
+    }
+
+    /// Returns the value of the first "Router Alert" option (rfc2711) if a hop
+    /// by hop header containing the option is present.
+    pub fn router_alert(&self) -> Option<u16> {
+        self.hop_by_hop_options
+            .as_ref()?
+            .options_iterator()
+            .take_while(|option| option.is_ok())
+            .find_map(|option| match option {
+                Ok(Ipv6OptionElement::RouterAlert(value)) => Some(value),
+                _ => None,
+            })
+    }
+
+    /// Sets the value of the "Router Alert" option (rfc2711) in the hop by hop
+    /// header (e.g. [`ipv6_option::ROUTER_ALERT_MLD`] for MLD messages).
+    ///
+    /// If no hop by hop header is present a new header is added. If the hop by
+    /// hop header does not yet contain a router alert option, the option is
+    /// inserted in front of the existing options (followed by padding so the
+    /// alignment of the existing options is not changed).
+    ///
+    /// # Errors
+    ///
+    /// `ValueError::Ipv6ExtensionPayloadTooLarge` is returned if the existing
+    /// hop by hop header is too big to add the option.
+    pub fn set_router_alert(&mut self, value: u16) -> Result<(), ValueError> {
+        use ipv6_option::*;
+
+        let value_be = value.to_be_bytes();
+        let header = match self.hop_by_hop_options.as_mut() {
+            Some(header) => header,
+            None => {
+                self.hop_by_hop_options = Some(Ipv6RawExtensionHeader::new_with_options(
+                    ip_number::IPV6_NO_NEXT_HEADER,
+                    &[Ipv6OptionElement::RouterAlert(value)]
+                )?);
+                return Ok(());
+            }
+        };
+
+        // overwrite the value if an option is already present
+        let mut iter = header.options_iterator();
+        while let Some(Ok(option)) = iter.next() {
+            if let Ipv6OptionElement::RouterAlert(_) = option {
+                let end = header.payload().len() - iter.rest().len();
+                let mut payload = header.payload().to_vec();
+                payload[end - 2..end].copy_from_slice(&value_be);
+                return header.set_payload(&payload);
+            }
+        }
+
+        // insert the option & 4 bytes of padding (keeps the alignment
+        // of the following options as the header grows by 8 bytes)
+        let mut payload = Vec::with_capacity(header.payload().len() + 8);
+        payload.extend_from_slice(&[
+            TYPE_ROUTER_ALERT, DATA_LEN_ROUTER_ALERT, value_be[0], value_be[1],
+            TYPE_PADN, 2, 0, 0,
+        ]);
+        payload.extend_from_slice(header.payload());
+        header.set_payload(&payload)
+    }
+}
+
+impl<'a> Ipv6ExtensionsSlice<'a> {
+    /// Returns the value of the "Jumbo Payload" option (rfc2675) if a hop by hop
+    /// header containing the option is present.
+    pub fn jumbo_payload_length(&self) -> Option<u32> {
+        if Some(ip_number::IPV6_HOP_BY_HOP) == self.first_header() {
+            Ipv6RawExtensionHeaderSlice::from_slice(self.slice())
+                .ok()
+                .and_then(|header| header.jumbo_payload_length())
+        } else {
+            None
+        }
+    }
+}
+
+impl Ipv6Header {
+    /// Sets the payload length of the header & inserts a "Jumbo Payload" option
+    /// (rfc2675) in the hop by hop header if the payload is bigger then what can
+    /// be represented by the `payload_length` field.
+    ///
+    /// The given `size` is the length of the data after the extension headers.
+    /// If the packet is a jumbogram the `payload_length` is set to zero and
+    /// the jumbo payload option is added (or updated) in the hop by hop header of
+    /// `extensions`. If no hop by hop header was present before, one is created and
+    /// inserted between the ipv6 header and the first next header.
+    ///
+    /// # Errors
+    ///
+    /// `ValueError::Ipv6PayloadLengthTooLarge` is returned if the packet is too big
+    /// for a jumbogram or a fragment header is present (rfc2675 does not allow
+    /// jumbograms to be fragmented). `ValueError::Ipv6PayloadLengthTooLarge` is
+    /// also returned if the options of an existing hop by hop header can not be decoded.
+    pub fn set_payload_length_with_jumbo(&mut self, extensions: &mut Ipv6Extensions, size: usize) -> Result<(), ValueError> {
+        use ValueError::*;
+        use Ipv6OptionElement::*;
+
+        let payload_len = size.checked_add(extensions.header_len()).ok_or(Ipv6PayloadLengthTooLarge(size))?;
+        if payload_len <= usize::from(u16::MAX) {
+            // remove the jumbo option (if present) as it is no longer valid
+            if let Some(hop_by_hop) = extensions.hop_by_hop_options.as_ref() {
+                if hop_by_hop.jumbo_payload_length().is_some() {
+                    let options = hop_by_hop.options_iterator()
+                        .filter(|o| !matches!(o, Ok(Pad1) | Ok(PadN(_)) | Ok(JumboPayload(_))))
+                        .collect::<Result<Vec<_>, _>>()
+                        .map_err(|_| Ipv6PayloadLengthTooLarge(payload_len))?;
+                    let new_header = Ipv6RawExtensionHeader::new_with_options(hop_by_hop.next_header, &options)?;
+                    extensions.hop_by_hop_options = Some(new_header);
+                    return self.set_payload_length_with_jumbo(extensions, size);
+                }
+            }
+            return self.set_payload_length(payload_len);
+        }
+        if extensions.fragment.is_some() {
+            return Err(Ipv6PayloadLengthTooLarge(payload_len));
+        }
+
+        // build the new hop by hop header
+        let (next_header, mut options) = match extensions.hop_by_hop_options.as_ref() {
+            Some(hop_by_hop) => (
+                hop_by_hop.next_header,
+                hop_by_hop.options_iterator()
+                    .filter(|o| !matches!(o, Ok(Pad1) | Ok(PadN(_)) | Ok(JumboPayload(_))))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|_| Ipv6PayloadLengthTooLarge(payload_len))?
+            ),
+            None => (self.next_header, Vec::new()),
+        };
+        let previous_len = extensions.hop_by_hop_options.as_ref().map(|h| h.header_len()).unwrap_or(0);
+        options.push(JumboPayload(0));
+        let new_len = Ipv6RawExtensionHeader::new_with_options(next_header, &options)?.header_len();
+
+        // determine the jumbo payload length
+        let jumbo_len = (payload_len - previous_len)
+            .checked_add(new_len)
+            .filter(|len| *len <= u32::MAX as usize)
+            .ok_or(Ipv6PayloadLengthTooLarge(payload_len))?;
+        if let Some(last) = options.last_mut() {
+            *last = JumboPayload(jumbo_len as u32);
+        }
+        let new_header = Ipv6RawExtensionHeader::new_with_options(next_header, &options)?;
+
+        if extensions.hop_by_hop_options.is_none() {
+            self.next_header = ip_number::IPV6_HOP_BY_HOP;
+        }
+        extensions.hop_by_hop_options = Some(new_header);
+        self.payload_length = 0;
+        Ok(())
+    }
+}
+
+/// Errors that can occour while reading the options of an ipv6 hop by hop or destination options header.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Ipv6OptionReadError {
+    /// Returned if an option type was read, but there was not enough memory in the options left to completely read it.
+    UnexpectedEndOfSlice{ option_type: u8, expected_len: usize, actual_len: usize },
+
+    /// Returned if the option has an unexpected length value (e.g. a CALIPSO option with a length smaller then 8).
+    UnexpectedSize{ option_type: u8, size: u8 },
+}
+
+impl Error for Ipv6OptionReadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        None
+    }
+}
+
+impl fmt::Display for Ipv6OptionReadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use Ipv6OptionReadError::*;
+        match self {
+            UnexpectedEndOfSlice{option_type, expected_len, actual_len} => {
+                write!(f, "Ipv6OptionReadError: Not enough memory left in slice to read option of type {} (expected at least {} bytes, only {} bytes available).", option_type, expected_len, actual_len)
+            },
+            UnexpectedSize{option_type, size} => {
+                write!(f, "Ipv6OptionReadError: Length value of the option of type {} had unexpected value {}.", option_type, size)
+            },
+        }
+    }
+}
+
+/// Allows iterating over the options of an ipv6 hop by hop or destination options header.
+///
+/// Options with known fixed length types but non standard lengths are
+/// returned as [`Ipv6OptionElement::Unknown`] (the data length is not
+/// enforced by rfc8200 for unrecognized options). CALIPSO options with a
+/// length not matching the compartment bitmap length are returned as
+/// [`Ipv6OptionReadError::UnexpectedSize`] error, after which the iteration
+/// stops (as the following options can not be trusted).
+#[derive(Clone, Eq, PartialEq)]
+pub struct Ipv6OptionsIterator<'a> {
+    options: &'a [u8]
+}
+
+impl<'a> Ipv6OptionsIterator<'a> {
+    /// Creates an options iterator from a slice containing encoded ipv6 options
+    /// (the payload of the extension header after the header length field).
+    pub fn from_slice(options: &'a [u8]) -> Ipv6OptionsIterator<'a> {
+        Ipv6OptionsIterator{ options }
+    }
+
+    /// Returns the non processed part of the options slice.
+    pub fn rest(&self) -> &'a [u8] {
+        self.options
+    }
+}
+
+impl<'a> Iterator for Ipv6OptionsIterator<'a> {
+    type Item = Result<Ipv6OptionElement<'a>, Ipv6OptionReadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        use Ipv6OptionElement::*;
+        use Ipv6OptionReadError::*;
+        use ipv6_option::*;
+
+        if self.options.is_empty() {
+            return None;
+        }
+
+        let option_type = self.options[0];
+        if TYPE_PAD1 == option_type {
+            self.options = &self.options[1..];
+            return Some(Ok(Pad1));
+        }
+
+        // determine & check the length
+        if self.options.len() < 2 {
+            let actual_len = self.options.len();
+            self.options = &self.options[actual_len..];
+            return Some(Err(UnexpectedEndOfSlice{ option_type, expected_len: 2, actual_len }));
+        }
+        let data_len = self.options[1];
+        let len = 2 + usize::from(data_len);
+        if self.options.len() < len {
+            let actual_len = self.options.len();
+            self.options = &self.options[actual_len..];
+            return Some(Err(UnexpectedEndOfSlice{ option_type, expected_len: len, actual_len }));
+        }
+        let data = &self.options[2..len];
+        self.options = &self.options[len..];
+
+        // decode the data
+        let result = match (option_type, data_len) {
+            (TYPE_PADN, len) => PadN(len),
+            (TYPE_TUNNEL_ENCAPSULATION_LIMIT, DATA_LEN_TUNNEL_ENCAPSULATION_LIMIT) => {
+                TunnelEncapsulationLimit(data[0])
+            },
+            (TYPE_ROUTER_ALERT, DATA_LEN_ROUTER_ALERT) => {
+                RouterAlert(u16::from_be_bytes([data[0], data[1]]))
+            },
+            (TYPE_CALIPSO, len) => {
+                let bitmap_len = if len >= DATA_MIN_LEN_CALIPSO {
+                    usize::from(data[4])*4
+                } else {
+                    0
+                };
+                if len < DATA_MIN_LEN_CALIPSO || usize::from(len) != usize::from(DATA_MIN_LEN_CALIPSO) + bitmap_len {
+                    let rest = self.options.len();
+                    self.options = &self.options[rest..];
+                    return Some(Err(UnexpectedSize{ option_type, size: len }));
+                }
+                Calipso{
+                    domain_of_interpretation: u32::from_be_bytes([data[0], data[1], data[2], data[3]]),
+                    sensitivity_level: data[5],
+                    checksum: u16::from_be_bytes([data[6], data[7]]),
+                    compartment_bitmap: &data[8..],
+                }
+            },
+            (TYPE_JUMBO_PAYLOAD, DATA_LEN_JUMBO_PAYLOAD) => {
+                JumboPayload(u32::from_be_bytes([data[0], data[1], data[2], data[3]]))
+            },
+            (TYPE_HOME_ADDRESS, DATA_LEN_HOME_ADDRESS) => {
+                let mut address = [0u8;16];
+                address.copy_from_slice(data);
+                HomeAddress(address)
+            },
+            (option_type, _) => Unknown{ option_type, data },
+        };
+        Some(Ok(result))
+    }
+}
+
+impl<'a> Debug for Ipv6OptionsIterator<'a> {
+    fn fmt(&self, fmt: &mut Formatter) -> Result<(), std::fmt::Error> {
+        let mut list = fmt.debug_list();
+
+        // create a copy and iterate over all elements
+        for it in self.clone() {
+            match it {
+                Ok(e) => { list.entry(&e); },
+                Err(e) => {
+                    list.entry(&Result::<(), Ipv6OptionReadError>::Err(e.clone()));
+                }
+            }
+        }
+
+        list.finish()
+    }
+}
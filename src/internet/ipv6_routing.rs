@@ -0,0 +1,441 @@
+use super::super::*;
+
+use std::slice::from_raw_parts;
+
+/// Module containing the constants for the ipv6 routing header types.
+pub mod ipv6_routing_type {
+    /// `u8` identifying a "Source Route" routing header (deprecated by rfc5095).
+    pub const SOURCE_ROUTE: u8 = 0;
+    /// `u8` identifying a "Nimrod" routing header (deprecated).
+    pub const NIMROD: u8 = 1;
+    /// `u8` identifying a "Type 2" routing header used by mobile ipv6 (rfc6275).
+    pub const TYPE2: u8 = 2;
+    /// `u8` identifying a "RPL Source Route" routing header (rfc6554).
+    pub const RPL_SOURCE_ROUTE: u8 = 3;
+    /// `u8` identifying a "Segment Routing" routing header (SRH, rfc8754).
+    pub const SEGMENT_ROUTING: u8 = 4;
+}
+
+/// Module containing the constants for the TLVs of a segment routing header (rfc8754).
+pub mod ipv6_srh_tlv {
+    /// `u8` identifying a "Pad1" TLV (single byte of padding).
+    pub const TYPE_PAD1: u8 = 0;
+    /// `u8` identifying a "PadN" TLV (multiple bytes of padding).
+    pub const TYPE_PADN: u8 = 4;
+    /// `u8` identifying a "HMAC" TLV.
+    pub const TYPE_HMAC: u8 = 5;
+}
+
+/// Typed content of an ipv6 routing header.
+///
+/// The variants reference the address lists & data of the header they were
+/// decoded from. The same enum can be passed to
+/// [`Ipv6RawExtensionHeader::new_with_routing`] to build a routing header.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Ipv6RoutingHeader<'a> {
+    /// Type 0 "Source Route" header (deprecated by rfc5095).
+    SourceRoute{ segments_left: u8, addresses: &'a [[u8;16]] },
+    /// Type 2 routing header containing the home address of a mobile node (rfc6275).
+    Type2{ segments_left: u8, home_address: [u8;16] },
+    /// Type 3 "RPL Source Route" header (rfc6554).
+    ///
+    /// `addresses` contains the compressed addresses (without padding). All
+    /// addresses except the last one have the first `cmpr_i` octets elided, the
+    /// last address has the first `cmpr_e` octets elided. The elided octets are
+    /// identical to the destination address of the ipv6 header.
+    Rpl{ segments_left: u8, cmpr_i: u8, cmpr_e: u8, addresses: &'a [u8] },
+    /// Type 4 "Segment Routing" header (SRH, rfc8754).
+    ///
+    /// The segment list is encoded in reverse order, `segments[0]` is the last
+    /// segment of the path. The `last_entry` field of the header is derived from
+    /// the number of segments. `tlvs` contains the encoded TLVs following the
+    /// segment list (including any padding TLVs).
+    SegmentRouting{ segments_left: u8, flags: u8, tag: u16, segments: &'a [[u8;16]], tlvs: &'a [u8] },
+    /// Routing header with a type not decoded by etherparse (`data` starts
+    /// after the segments left field).
+    Unknown{ routing_type: u8, segments_left: u8, data: &'a [u8] },
+}
+
+/// Converts a slice with a length that is a multiple of 16 to a slice of addresses.
+fn to_addresses(slice: &[u8]) -> &[[u8;16]] {
+    debug_assert_eq!(0, slice.len() % 16);
+    // SAFETY: [u8;16] has an alignment of 1 & the length was checked
+    unsafe {
+        from_raw_parts(slice.as_ptr() as *const [u8;16], slice.len() / 16)
+    }
+}
+
+impl<'a> Ipv6RoutingHeader<'a> {
+    /// Decodes the payload of a routing header (the data after the next header
+    /// & header length fields).
+    pub fn from_payload(payload: &'a [u8]) -> Result<Ipv6RoutingHeader<'a>, Ipv6RoutingReadError> {
+        use ipv6_routing_type::*;
+        use Ipv6RoutingHeader::*;
+
+        if payload.len() < Ipv6RawExtensionHeader::MIN_PAYLOAD_LEN {
+            return Err(Ipv6RoutingReadError::UnexpectedSize{
+                routing_type: payload.first().copied().unwrap_or(0),
+                size: payload.len() + 2,
+            });
+        }
+
+        let routing_type = payload[0];
+        let segments_left = payload[1];
+        let data = &payload[2..];
+        let size_error = Err(Ipv6RoutingReadError::UnexpectedSize{
+            routing_type,
+            size: payload.len() + 2,
+        });
+
+        match routing_type {
+            SOURCE_ROUTE => {
+                let addresses = &data[4..];
+                match addresses.len() % 16 {
+                    0 => Ok(SourceRoute{
+                        segments_left,
+                        addresses: to_addresses(addresses),
+                    }),
+                    _ => size_error,
+                }
+            },
+            TYPE2 => {
+                if data.len() != 4 + 16 {
+                    size_error
+                } else {
+                    let mut home_address = [0u8;16];
+                    home_address.copy_from_slice(&data[4..]);
+                    Ok(Type2{ segments_left, home_address })
+                }
+            },
+            RPL_SOURCE_ROUTE => {
+                let cmpr_i = data[0] >> 4;
+                let cmpr_e = data[0] & 0xf;
+                let pad = usize::from(data[1] >> 4);
+                if data.len() < 4 + pad {
+                    return size_error;
+                }
+                let addresses = &data[4..data.len() - pad];
+                if Self::rpl_address_count(cmpr_i, cmpr_e, addresses.len()).is_none() {
+                    size_error
+                } else {
+                    Ok(Rpl{ segments_left, cmpr_i, cmpr_e, addresses })
+                }
+            },
+            SEGMENT_ROUTING => {
+                let segments_len = (usize::from(data[0]) + 1) * 16;
+                if data.len() < 4 + segments_len {
+                    size_error
+                } else {
+                    Ok(SegmentRouting{
+                        segments_left,
+                        flags: data[1],
+                        tag: u16::from_be_bytes([data[2], data[3]]),
+                        segments: to_addresses(&data[4..4 + segments_len]),
+                        tlvs: &data[4 + segments_len..],
+                    })
+                }
+            },
+            _ => Ok(Unknown{ routing_type, segments_left, data }),
+        }
+    }
+
+    /// Returns the number of addresses in a RPL source route header with the
+    /// given compression values & compressed addresses length (`None` if the
+    /// length does not match).
+    fn rpl_address_count(cmpr_i: u8, cmpr_e: u8, len: usize) -> Option<usize> {
+        let size_i = 16 - usize::from(cmpr_i);
+        let size_e = 16 - usize::from(cmpr_e);
+        if len < size_e {
+            return None;
+        }
+        match (len - size_e) % size_i {
+            0 => Some((len - size_e) / size_i + 1),
+            _ => None,
+        }
+    }
+
+    /// Returns the routing type value of the header.
+    pub fn routing_type(&self) -> u8 {
+        use ipv6_routing_type::*;
+        use Ipv6RoutingHeader::*;
+        match self {
+            SourceRoute{..} => SOURCE_ROUTE,
+            Type2{..} => TYPE2,
+            Rpl{..} => RPL_SOURCE_ROUTE,
+            SegmentRouting{..} => SEGMENT_ROUTING,
+            Unknown{ routing_type, .. } => *routing_type,
+        }
+    }
+
+    /// Returns the number of route segments remaining (number of explicitly
+    /// listed intermediate nodes still to be visited).
+    pub fn segments_left(&self) -> u8 {
+        use Ipv6RoutingHeader::*;
+        match self {
+            SourceRoute{ segments_left, .. } |
+            Type2{ segments_left, .. } |
+            Rpl{ segments_left, .. } |
+            SegmentRouting{ segments_left, .. } |
+            Unknown{ segments_left, .. } => *segments_left,
+        }
+    }
+
+    /// Returns the number of addresses contained in the header (`None` for
+    /// unknown routing types).
+    pub fn address_count(&self) -> Option<usize> {
+        use Ipv6RoutingHeader::*;
+        match self {
+            SourceRoute{ addresses, .. } => Some(addresses.len()),
+            Type2{..} => Some(1),
+            Rpl{ cmpr_i, cmpr_e, addresses, .. } => Self::rpl_address_count(*cmpr_i, *cmpr_e, addresses.len()),
+            SegmentRouting{ segments, .. } => Some(segments.len()),
+            Unknown{..} => None,
+        }
+    }
+
+    /// Returns the address of the final destination of the packet, which is
+    /// the destination that has to be used in the pseudo header of upper layer
+    /// checksums (rfc8200 section 8.1).
+    ///
+    /// `destination` is the destination address present in the ipv6 header.
+    /// If no segments are left (or the routing type is unknown or the
+    /// segments left value is bigger then the number of addresses) the given
+    /// destination is returned.
+    pub fn effective_destination(&self, destination: [u8;16]) -> [u8;16] {
+        use Ipv6RoutingHeader::*;
+
+        let segments_left = usize::from(self.segments_left());
+        if 0 == segments_left || self.address_count().map(|c| segments_left > c).unwrap_or(true) {
+            return destination;
+        }
+
+        match self {
+            SourceRoute{ addresses, .. } => addresses[addresses.len() - 1],
+            Type2{ home_address, .. } => *home_address,
+            Rpl{ cmpr_e, addresses, .. } => {
+                // the elided octets are identical to the current destination
+                let mut result = destination;
+                let size_e = 16 - usize::from(*cmpr_e);
+                result[usize::from(*cmpr_e)..].copy_from_slice(&addresses[addresses.len() - size_e..]);
+                result
+            },
+            SegmentRouting{ segments, .. } => segments[0],
+            Unknown{..} => destination,
+        }
+    }
+
+    /// Writes the payload of the routing header (without padding) to the given buffer
+    /// and returns the written length.
+    fn write_payload(&self, buffer: &mut [u8]) -> usize {
+        use Ipv6RoutingHeader::*;
+
+        buffer[0] = self.routing_type();
+        buffer[1] = self.segments_left();
+        match self {
+            SourceRoute{ addresses, .. } => {
+                buffer[2..6].copy_from_slice(&[0;4]);
+                for (i, address) in addresses.iter().enumerate() {
+                    buffer[6 + i*16..6 + (i + 1)*16].copy_from_slice(address);
+                }
+                6 + addresses.len()*16
+            },
+            Type2{ home_address, .. } => {
+                buffer[2..6].copy_from_slice(&[0;4]);
+                buffer[6..22].copy_from_slice(home_address);
+                22
+            },
+            Rpl{ cmpr_i, cmpr_e, addresses, .. } => {
+                let pad = (8 - (addresses.len() + 8) % 8) % 8;
+                buffer[2] = (cmpr_i << 4) | cmpr_e;
+                buffer[3] = (pad as u8) << 4;
+                buffer[4] = 0;
+                buffer[5] = 0;
+                buffer[6..6 + addresses.len()].copy_from_slice(addresses);
+                let end = 6 + addresses.len();
+                for b in &mut buffer[end..end + pad] {
+                    *b = 0;
+                }
+                end + pad
+            },
+            SegmentRouting{ flags, tag, segments, tlvs, .. } => {
+                buffer[2] = (segments.len() - 1) as u8;
+                buffer[3] = *flags;
+                buffer[4..6].copy_from_slice(&tag.to_be_bytes());
+                for (i, segment) in segments.iter().enumerate() {
+                    buffer[6 + i*16..6 + (i + 1)*16].copy_from_slice(segment);
+                }
+                let end = 6 + segments.len()*16;
+                buffer[end..end + tlvs.len()].copy_from_slice(tlvs);
+                end + tlvs.len()
+            },
+            Unknown{ data, .. } => {
+                buffer[2..2 + data.len()].copy_from_slice(data);
+                2 + data.len()
+            },
+        }
+    }
+
+    /// Checks that the header can be serialized.
+    fn check_values(&self) -> Result<(), ValueError> {
+        use Ipv6RoutingHeader::*;
+        use ValueError::*;
+
+        // maximum payload length
+        let len = match self {
+            SourceRoute{ addresses, .. } => 6 + addresses.len()*16,
+            Type2{..} => 22,
+            Rpl{ cmpr_i, cmpr_e, addresses, .. } => {
+                max_check_u8(*cmpr_i, 15, ErrorField::Ipv6RplCmprI)?;
+                max_check_u8(*cmpr_e, 15, ErrorField::Ipv6RplCmprE)?;
+                if Self::rpl_address_count(*cmpr_i, *cmpr_e, addresses.len()).is_none() {
+                    return Err(Ipv6RoutingAddressesLengthBad(addresses.len()));
+                }
+                6 + addresses.len()
+            },
+            SegmentRouting{ segments, tlvs, .. } => {
+                if segments.is_empty() || segments.len() > 0x100 {
+                    return Err(Ipv6RoutingAddressesLengthBad(segments.len()*16));
+                }
+                6 + segments.len()*16 + tlvs.len()
+            },
+            Unknown{ data, .. } => 2 + data.len(),
+        };
+        if len > Ipv6RawExtensionHeader::MAX_PAYLOAD_LEN {
+            Err(Ipv6ExtensionPayloadTooLarge(len))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl Ipv6RawExtensionHeader {
+    /// Creates a routing header with the given content.
+    ///
+    /// The padding of RPL source route headers is calculated automatically.
+    /// The TLVs of segment routing headers are padded with a Pad1 or PadN TLV
+    /// so the header length is a multiple of 8 octets.
+    ///
+    /// # Errors
+    ///
+    /// * `ValueError::U8TooLarge` if the `cmpr_i` or `cmpr_e` value of a RPL source
+    ///   route header is bigger then 15.
+    /// * `ValueError::Ipv6RoutingAddressesLengthBad` if the compressed RPL addresses
+    ///   length does not match the compression values or if a segment routing header
+    ///   contains no or more then 256 segments.
+    /// * `ValueError::Ipv6ExtensionPayloadTooLarge` if the content does not fit into a
+    ///   single extension header.
+    /// * `ValueError::Ipv6ExtensionPayloadLengthUnaligned` if the data of an unknown
+    ///   routing header type does not result in a multiple of 8 octets.
+    pub fn new_with_routing(next_header: u8, routing: &Ipv6RoutingHeader) -> Result<Ipv6RawExtensionHeader, ValueError> {
+        routing.check_values()?;
+
+        let mut buffer = [0u8; Ipv6RawExtensionHeader::MAX_PAYLOAD_LEN];
+        let mut len = routing.write_payload(&mut buffer);
+
+        // pad the segment routing tlvs to a multiple of 8 octets
+        if let Ipv6RoutingHeader::SegmentRouting{..} = routing {
+            let padding = (8 - (len + 2) % 8) % 8;
+            if len + padding > Ipv6RawExtensionHeader::MAX_PAYLOAD_LEN {
+                return Err(ValueError::Ipv6ExtensionPayloadTooLarge(len + padding));
+            }
+            match padding {
+                0 => {},
+                1 => buffer[len] = ipv6_srh_tlv::TYPE_PAD1,
+                padding => {
+                    buffer[len] = ipv6_srh_tlv::TYPE_PADN;
+                    buffer[len + 1] = (padding - 2) as u8;
+                },
+            }
+            len += padding;
+        }
+
+        Ipv6RawExtensionHeader::new_raw(next_header, &buffer[..len])
+    }
+
+    /// Decodes the content of the header as routing header.
+    ///
+    /// Note that this only makes sense for headers with the ip number `IPV6_ROUTE`.
+    pub fn routing_header(&self) -> Result<Ipv6RoutingHeader<'_>, Ipv6RoutingReadError> {
+        Ipv6RoutingHeader::from_payload(self.payload())
+    }
+}
+
+impl<'a> Ipv6RawExtensionHeaderSlice<'a> {
+    /// Decodes the content of the header as routing header.
+    ///
+    /// Note that this only makes sense for headers with the ip number `IPV6_ROUTE`.
+    pub fn routing_header(&self) -> Result<Ipv6RoutingHeader<'a>, Ipv6RoutingReadError> {
+        Ipv6RoutingHeader::from_payload(self.payload())
+    }
+}
+
+impl Ipv6RoutingExtensions {
+    /// Decodes the routing header.
+    pub fn routing_header(&self) -> Result<Ipv6RoutingHeader<'_>, Ipv6RoutingReadError> {
+        self.routing.routing_header()
+    }
+}
+
+impl Ipv6Extensions {
+    /// Returns the final destination of the packet based on the routing header
+    /// (if present), which has to be used in the pseudo header of upper layer
+    /// checksums.
+    ///
+    /// `destination` is the destination address present in the ipv6 header. It
+    /// is returned if no routing header is present or if the routing header
+    /// can not be decoded.
+    pub fn effective_destination(&self, destination: [u8;16]) -> [u8;16] {
+        self.routing
+            .as_ref()
+            .and_then(|r| r.routing_header().ok())
+            .map(|r| r.effective_destination(destination))
+            .unwrap_or(destination)
+    }
+}
+
+impl<'a> Ipv6ExtensionsSlice<'a> {
+    /// Returns the final destination of the packet based on the routing header
+    /// (if present), which has to be used in the pseudo header of upper layer
+    /// checksums.
+    ///
+    /// `destination` is the destination address present in the ipv6 header. It
+    /// is returned if no routing header is present or if the routing header
+    /// can not be decoded.
+    pub fn effective_destination(&self, destination: [u8;16]) -> [u8;16] {
+        for ext in self.clone() {
+            if let Ipv6ExtensionSlice::Routing(routing) = ext {
+                return routing
+                    .routing_header()
+                    .map(|r| r.effective_destination(destination))
+                    .unwrap_or(destination);
+            }
+        }
+        destination
+    }
+}
+
+/// Errors that can occour while decoding an ipv6 routing header.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Ipv6RoutingReadError {
+    /// Returned if the length of a routing header does not match the length
+    /// required by its routing type (e.g. a type 2 header not containing exactly
+    /// one address or a segment list not fitting into the header).
+    UnexpectedSize{ routing_type: u8, size: usize },
+}
+
+impl Error for Ipv6RoutingReadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        None
+    }
+}
+
+impl fmt::Display for Ipv6RoutingReadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use Ipv6RoutingReadError::*;
+        match self {
+            UnexpectedSize{routing_type, size} => {
+                write!(f, "Ipv6RoutingReadError: Routing header of type {} has an unexpected length of {} bytes.", routing_type, size)
+            },
+        }
+    }
+}
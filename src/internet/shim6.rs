@@ -0,0 +1,399 @@
+use super::super::*;
+use arrayvec::ArrayVec;
+
+use std::fmt::{Debug, Formatter};
+use std::slice::from_raw_parts;
+
+/// Module containing the constants for the Shim6 control message types (rfc5533).
+pub mod shim6_message_type {
+    /// Context establishment initiator message (I1).
+    pub const I1: u8 = 1;
+    /// Context establishment responder message (R1).
+    pub const R1: u8 = 2;
+    /// Second context establishment initiator message (I2).
+    pub const I2: u8 = 3;
+    /// Second context establishment responder message (R2).
+    pub const R2: u8 = 4;
+    /// Context recovery responder message (R1bis).
+    pub const R1BIS: u8 = 5;
+    /// Context recovery initiator message (I2bis).
+    pub const I2BIS: u8 = 6;
+    /// Update Request message.
+    pub const UPDATE_REQUEST: u8 = 64;
+    /// Update Acknowledgement message.
+    pub const UPDATE_ACKNOWLEDGEMENT: u8 = 65;
+    /// Keepalive message (rfc5534).
+    pub const KEEPALIVE: u8 = 66;
+    /// Probe message (rfc5534).
+    pub const PROBE: u8 = 67;
+}
+
+/// Shim6 payload extension header (rfc5533 section 5.1).
+///
+/// Sent in front of the payload of a packet using a Shim6 context.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Shim6PayloadHeader {
+    /// IP protocol number of the header following the Shim6 header.
+    pub next_header: u8,
+    /// Context tag of the receiver (47 bit value).
+    pub receiver_context_tag: u64,
+}
+
+impl Shim6PayloadHeader {
+    /// Serialized size of the header in bytes.
+    pub const LEN: usize = 8;
+
+    /// Maximum value of the receiver context tag.
+    pub const MAX_RECEIVER_CONTEXT_TAG: u64 = 0x7fff_ffff_ffff;
+
+    /// Returns the serialized header.
+    ///
+    /// Returns an error if the receiver context tag is bigger then 47 bits.
+    pub fn to_bytes(&self) -> Result<[u8;8], ValueError> {
+        if self.receiver_context_tag > Shim6PayloadHeader::MAX_RECEIVER_CONTEXT_TAG {
+            return Err(ValueError::U64TooLarge{
+                value: self.receiver_context_tag,
+                max: Shim6PayloadHeader::MAX_RECEIVER_CONTEXT_TAG,
+                field: ErrorField::Shim6ReceiverContextTag,
+            });
+        }
+        let tag = self.receiver_context_tag.to_be_bytes();
+        Ok([
+            self.next_header,
+            0,
+            0b1000_0000 | tag[2],
+            tag[3],
+            tag[4],
+            tag[5],
+            tag[6],
+            tag[7],
+        ])
+    }
+}
+
+/// Shim6 control message header (rfc5533 section 5.2).
+#[derive(Clone)]
+pub struct Shim6ControlHeader {
+    /// IP protocol number of the header following the Shim6 header (should be `IPV6_NO_NEXT_HEADER`).
+    pub next_header: u8,
+    /// Type of the control message (7 bit value).
+    pub message_type: u8,
+    /// Message type specific 7 bit value.
+    pub type_specific: u8,
+    pub checksum: u16,
+    /// Length of the header in 8 octets (minus the first 8 octets).
+    header_length: u8,
+    /// Message data after the checksum field.
+    message_data_buffer: [u8;Shim6ControlHeader::MAX_MESSAGE_DATA_LEN],
+}
+
+impl Debug for Shim6ControlHeader {
+    fn fmt(&self, formatter: &mut Formatter) -> Result<(), std::fmt::Error> {
+        write!(formatter, "Shim6ControlHeader {{ next_header: {}, message_type: {}, type_specific: {}, checksum: {}, message_data: {:?} }}",
+            self.next_header,
+            self.message_type,
+            self.type_specific,
+            self.checksum,
+            self.message_data())
+    }
+}
+
+impl PartialEq for Shim6ControlHeader {
+    fn eq(&self, other: &Self) -> bool {
+        self.next_header == other.next_header &&
+        self.message_type == other.message_type &&
+        self.type_specific == other.type_specific &&
+        self.checksum == other.checksum &&
+        self.message_data() == other.message_data()
+    }
+}
+
+impl Eq for Shim6ControlHeader {}
+
+impl Shim6ControlHeader {
+    /// Minimum length of the message data of a Shim6 control header.
+    pub const MIN_MESSAGE_DATA_LEN: usize = 2;
+
+    /// Maximum length of the message data of a Shim6 control header.
+    pub const MAX_MESSAGE_DATA_LEN: usize = 0xff*8 + 2;
+
+    /// Creates a Shim6 control header with the given message data (data after
+    /// the checksum field).
+    ///
+    /// The message data must have at least a length of 2 bytes, at most a length
+    /// of 2042 bytes & `(message_data.len() + 6) % 8 == 0` must be true. Otherwise an
+    /// `Ipv6ExtensionPayloadTooSmall`, `Ipv6ExtensionPayloadTooLarge` or
+    /// `Ipv6ExtensionPayloadLengthUnaligned` error with the length of the
+    /// header payload (message data + 4) is returned.
+    ///
+    /// The checksum is set to 0, use [`Shim6ControlHeader::calc_checksum`] to
+    /// calculate it.
+    pub fn new(next_header: u8, message_type: u8, type_specific: u8, message_data: &[u8]) -> Result<Shim6ControlHeader, ValueError> {
+        use ValueError::*;
+        let payload_len = message_data.len() + 4;
+        if message_data.len() < Shim6ControlHeader::MIN_MESSAGE_DATA_LEN {
+            Err(Ipv6ExtensionPayloadTooSmall(payload_len))
+        } else if message_data.len() > Shim6ControlHeader::MAX_MESSAGE_DATA_LEN {
+            Err(Ipv6ExtensionPayloadTooLarge(payload_len))
+        } else {
+            match (payload_len + 2) % 8 {
+                0 => {
+                    let mut result = Shim6ControlHeader{
+                        next_header,
+                        message_type,
+                        type_specific,
+                        checksum: 0,
+                        header_length: ((message_data.len() - 2) / 8) as u8,
+                        message_data_buffer: [0;Shim6ControlHeader::MAX_MESSAGE_DATA_LEN],
+                    };
+                    result.message_data_buffer[..message_data.len()].copy_from_slice(message_data);
+                    Ok(result)
+                },
+                _ => Err(Ipv6ExtensionPayloadLengthUnaligned(payload_len)),
+            }
+        }
+    }
+
+    /// Returns the message data (data after the checksum field).
+    pub fn message_data(&self) -> &[u8] {
+        &self.message_data_buffer[..2 + usize::from(self.header_length)*8]
+    }
+
+    /// Length of the header in bytes.
+    pub fn header_len(&self) -> usize {
+        8 + usize::from(self.header_length)*8
+    }
+
+    /// Returns the serialized header.
+    ///
+    /// Returns an error if the message type or the type specific value is bigger then 7 bits.
+    pub fn to_bytes(&self) -> Result<ArrayVec<u8, { 8 + Shim6ControlHeader::MAX_MESSAGE_DATA_LEN - 2 }>, ValueError> {
+        max_check_u8(self.message_type, 0b0111_1111, ErrorField::Shim6MessageType)?;
+        max_check_u8(self.type_specific, 0b0111_1111, ErrorField::Shim6TypeSpecific)?;
+
+        let mut result = ArrayVec::new();
+        let checksum_be = self.checksum.to_be_bytes();
+        result.extend([
+            self.next_header,
+            self.header_length,
+            self.message_type,
+            self.type_specific << 1,
+            checksum_be[0],
+            checksum_be[1],
+        ]);
+        // SAFETY: the message data has at most the length of the remaining capacity
+        unsafe {
+            result.try_extend_from_slice(self.message_data()).unwrap_unchecked();
+        }
+        Ok(result)
+    }
+
+    /// Calculates the checksum of the header (rfc5533 section 5.2, no pseudo
+    /// header is part of the checksum).
+    pub fn calc_checksum(&self) -> Result<u16, ValueError> {
+        Ok(shim6_control_checksum(&self.to_bytes()?))
+    }
+}
+
+/// Calculates the checksum of a Shim6 control header (the checksum field itself is skipped).
+fn shim6_control_checksum(header: &[u8]) -> u16 {
+    checksum::Sum16BitWords::new()
+        .add_slice(&header[..4])
+        .add_slice(&header[6..])
+        .ones_complement()
+        .to_be()
+}
+
+/// Shim6 header (rfc5533).
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[allow(clippy::large_enum_variant)]
+pub enum Shim6Header {
+    /// Payload extension header (P bit set).
+    Payload(Shim6PayloadHeader),
+    /// Control message (P bit not set).
+    Control(Shim6ControlHeader),
+}
+
+impl Shim6Header {
+    /// Read a Shim6 header from a slice and return the header & unused parts of the slice.
+    pub fn from_slice(slice: &[u8]) -> Result<(Shim6Header, &[u8]), ReadError> {
+        let s = Shim6HeaderSlice::from_slice(slice)?;
+        let rest = &slice[s.slice().len()..];
+        Ok((s.to_header(), rest))
+    }
+
+    /// Returns the ip number of the header following the Shim6 header.
+    pub fn next_header(&self) -> u8 {
+        match self {
+            Shim6Header::Payload(header) => header.next_header,
+            Shim6Header::Control(header) => header.next_header,
+        }
+    }
+
+    /// Sets the ip number of the header following the Shim6 header.
+    pub fn set_next_header(&mut self, next_header: u8) {
+        match self {
+            Shim6Header::Payload(header) => header.next_header = next_header,
+            Shim6Header::Control(header) => header.next_header = next_header,
+        }
+    }
+
+    /// Length of the header in bytes.
+    pub fn header_len(&self) -> usize {
+        match self {
+            Shim6Header::Payload(_) => Shim6PayloadHeader::LEN,
+            Shim6Header::Control(header) => header.header_len(),
+        }
+    }
+
+    /// Writes the header to the given writer.
+    pub fn write<T: io::Write + Sized>(&self, writer: &mut T) -> Result<(), WriteError> {
+        match self {
+            Shim6Header::Payload(header) => writer.write_all(&header.to_bytes()?)?,
+            Shim6Header::Control(header) => writer.write_all(&header.to_bytes()?)?,
+        }
+        Ok(())
+    }
+}
+
+/// Slice containing a Shim6 header (rfc5533).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Shim6HeaderSlice<'a> {
+    slice: &'a [u8],
+}
+
+impl<'a> Shim6HeaderSlice<'a> {
+    /// Creates a Shim6 header slice from a slice.
+    pub fn from_slice(slice: &'a [u8]) -> Result<Shim6HeaderSlice<'a>, ReadError> {
+        use crate::ReadError::*;
+        if slice.len() < 8 {
+            return Err(UnexpectedEndOfSlice(8));
+        }
+        let len = (usize::from(slice[1]) + 1)*8;
+        if slice.len() < len {
+            return Err(UnexpectedEndOfSlice(len));
+        }
+        Ok(Shim6HeaderSlice {
+            // SAFETY: the length was checked above
+            slice: unsafe {
+                from_raw_parts(slice.as_ptr(), len)
+            }
+        })
+    }
+
+    /// Creates a Shim6 header slice from a slice (assumes slice size &
+    /// content was validated before).
+    ///
+    /// # Safety
+    ///
+    /// The slice must have a length of at least 8 and `(slice[1] + 1)*8`.
+    pub unsafe fn from_slice_unchecked(slice: &'a [u8]) -> Shim6HeaderSlice<'a> {
+        Shim6HeaderSlice {
+            slice: from_raw_parts(
+                slice.as_ptr(),
+                ((*slice.get_unchecked(1) as usize) + 1)*8
+            )
+        }
+    }
+
+    /// Returns the slice containing the Shim6 header.
+    #[inline]
+    pub fn slice(&self) -> &'a [u8] {
+        self.slice
+    }
+
+    /// Returns the ip number of the header following the Shim6 header.
+    #[inline]
+    pub fn next_header(&self) -> u8 {
+        self.slice[0]
+    }
+
+    /// Returns true if the header is a payload extension header (P bit set)
+    /// and false if it is a control message.
+    #[inline]
+    pub fn is_payload_extension(&self) -> bool {
+        0 != self.slice[2] & 0b1000_0000
+    }
+
+    /// Returns the receiver context tag if the header is a payload extension header.
+    pub fn receiver_context_tag(&self) -> Option<u64> {
+        if self.is_payload_extension() {
+            Some(u64::from_be_bytes([
+                0,
+                0,
+                self.slice[2] & 0b0111_1111,
+                self.slice[3],
+                self.slice[4],
+                self.slice[5],
+                self.slice[6],
+                self.slice[7],
+            ]))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the message type if the header is a control message.
+    pub fn message_type(&self) -> Option<u8> {
+        if self.is_payload_extension() {
+            None
+        } else {
+            Some(self.slice[2] & 0b0111_1111)
+        }
+    }
+
+    /// Returns the type specific value if the header is a control message.
+    pub fn type_specific(&self) -> Option<u8> {
+        if self.is_payload_extension() {
+            None
+        } else {
+            Some(self.slice[3] >> 1)
+        }
+    }
+
+    /// Returns the checksum if the header is a control message.
+    pub fn checksum(&self) -> Option<u16> {
+        if self.is_payload_extension() {
+            None
+        } else {
+            Some(u16::from_be_bytes([self.slice[4], self.slice[5]]))
+        }
+    }
+
+    /// Returns the message data (data after the checksum) if the header is a control message.
+    pub fn message_data(&self) -> Option<&'a [u8]> {
+        if self.is_payload_extension() {
+            None
+        } else {
+            Some(&self.slice[6..])
+        }
+    }
+
+    /// Calculates the checksum if the header is a control message.
+    pub fn calc_checksum(&self) -> Option<u16> {
+        if self.is_payload_extension() {
+            None
+        } else {
+            Some(shim6_control_checksum(self.slice))
+        }
+    }
+
+    /// Decode all the fields and copy the results to a [`Shim6Header`].
+    pub fn to_header(&self) -> Shim6Header {
+        if let Some(receiver_context_tag) = self.receiver_context_tag() {
+            Shim6Header::Payload(Shim6PayloadHeader{
+                next_header: self.next_header(),
+                receiver_context_tag,
+            })
+        } else {
+            let mut result = Shim6ControlHeader::new(
+                self.next_header(),
+                self.slice[2] & 0b0111_1111,
+                self.slice[3] >> 1,
+                &self.slice[6..],
+            ).unwrap();
+            result.checksum = u16::from_be_bytes([self.slice[4], self.slice[5]]);
+            Shim6Header::Control(result)
+        }
+    }
+}
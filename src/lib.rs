@@ -3,11 +3,13 @@
 //! Currently supported are:
 //! * Ethernet II
 //! * IEEE 802.1Q VLAN Tagging Header
+//! * ARP (IPv4 over Ethernet)
 //! * IPv4
 //! * IPv6 (supporting the most common extension headers, but not all)
 //! * UDP
 //! * TCP
 //! * ICMP & ICMPv6 (not all message types are supported)
+//! * IGMP (versions 1, 2 & 3)
 //! 
 //! # Usage
 //! 
@@ -111,23 +113,27 @@
 //! * [`Ethernet2HeaderSlice::from_slice`]
 //! * [`SingleVlanHeaderSlice::from_slice`]
 //! * [`DoubleVlanHeaderSlice::from_slice`]
+//! * [`ArpPacketSlice::from_slice`]
 //! * [`Ipv4HeaderSlice::from_slice`]
 //! * [`Ipv4ExtensionsSlice::from_slice`]
 //! * [`Ipv6HeaderSlice::from_slice`]
 //! * [`Ipv6ExtensionsSlice::from_slice`]
 //! * [`Ipv6RawExtensionHeaderSlice::from_slice`]
 //! * [`IpAuthenticationHeaderSlice::from_slice`]
+//! * [`EspHeaderSlice::from_slice`]
 //! * [`Ipv6FragmentHeaderSlice::from_slice`]
 //! * [`UdpHeaderSlice::from_slice`]
 //! * [`TcpHeaderSlice::from_slice`]
 //! * [`Icmpv4Slice::from_slice`]
 //! * [`Icmpv6Slice::from_slice`]
+//! * [`IgmpSlice::from_slice`]
 //!
 //! And for deserialization into the corresponding header structs have a look at:
 //!
 //! * [`Ethernet2Header::read`] & [`Ethernet2Header::from_slice`]
 //! * [`SingleVlanHeader::read`] & [`SingleVlanHeader::from_slice`]
 //! * [`DoubleVlanHeader::read`] & [`DoubleVlanHeader::from_slice`]
+//! * [`ArpEthIpv4Packet::from_slice`]
 //! * [`IpHeader::read`] & [`IpHeader::from_slice`]
 //! * [`Ipv4Header::read`] & [`Ipv4Header::from_slice`]
 //! * [`Ipv4Extensions::read`] & [`Ipv4Extensions::from_slice`]
@@ -135,11 +141,13 @@
 //! * [`Ipv6Extensions::read`] & [`Ipv6Extensions::from_slice`]
 //! * [`Ipv6RawExtensionHeader::read`] & [`Ipv6RawExtensionHeader::from_slice`]
 //! * [`IpAuthenticationHeader::read`] & [`IpAuthenticationHeader::from_slice`]
+//! * [`EspHeader::read`] & [`EspHeader::from_slice`]
 //! * [`Ipv6FragmentHeader::read`] & [`Ipv6FragmentHeader::from_slice`]
 //! * [`UdpHeader::read`] & [`UdpHeader::from_slice`]
 //! * [`TcpHeader::read`] & [`TcpHeader::from_slice`]
 //! * [`Icmpv4Header::read`] & [`Icmpv4Header::from_slice`]
 //! * [`Icmpv6Header::read`] & [`Icmpv6Header::from_slice`]
+//! * [`IgmpHeader::read`] & [`IgmpHeader::from_slice`]
 //!
 //! # How to generate fake packet data?
 //! ## Packet Builder
@@ -182,6 +190,7 @@
 //! * [`Ethernet2Header::write`]
 //! * [`SingleVlanHeader::write`]
 //! * [`DoubleVlanHeader::write`]
+//! * [`ArpEthIpv4Packet::write`]
 //! * [`Ipv4Header::write`]
 //! * [`Ipv4Header::write_raw`]
 //! * [`Ipv4Extensions::write`]
@@ -189,11 +198,13 @@
 //! * [`Ipv6Extensions::write`]
 //! * [`Ipv6RawExtensionHeader::write`]
 //! * [`IpAuthenticationHeader::write`]
+//! * [`EspHeader::write`]
 //! * [`Ipv6FragmentHeader::write`]
 //! * [`UdpHeader::write`]
 //! * [`TcpHeader::write`]
 //! * [`Icmpv4Header::write`]
 //! * [`Icmpv6Header::write`]
+//! * [`IgmpHeader::write`]
 //!
 //! # Roadmap
 //! * Documentation
@@ -204,6 +215,7 @@
 //! * IEEE 802.3
 //! 
 //! # References
+//! * An Ethernet Address Resolution Protocol [RFC 826](https://datatracker.ietf.org/doc/html/rfc826)
 //! * Darpa Internet Program Protocol Specification [RFC 791](https://tools.ietf.org/html/rfc791)
 //! * Internet Protocol, Version 6 (IPv6) Specification [RFC 8200](https://tools.ietf.org/html/rfc8200)
 //! * [IANA Protocol Numbers](https://www.iana.org/assignments/protocol-numbers/protocol-numbers.xhtml)
@@ -215,6 +227,8 @@
 //! * The Addition of Explicit Congestion Notification (ECN) to IP [RFC 3168](https://tools.ietf.org/html/rfc3168)
 //! * Robust Explicit Congestion Notification (ECN) Signaling with Nonces [RFC 3540](https://tools.ietf.org/html/rfc3540)
 //! * IP Authentication Header [RFC 4302](https://tools.ietf.org/html/rfc4302)
+//! * IP Encapsulating Security Payload (ESP) [RFC 4303](https://tools.ietf.org/html/rfc4303)
+//! * The NULL Encryption Algorithm and Its Use With IPsec [RFC 2410](https://tools.ietf.org/html/rfc2410)
 //! * Mobility Support in IPv6 [RFC 6275](https://tools.ietf.org/html/rfc6275)
 //! * Host Identity Protocol Version 2 (HIPv2) [RFC 7401](https://tools.ietf.org/html/rfc7401)
 //! * Shim6: Level 3 Multihoming Shim Protocol for IPv6 [RFC 5533](https://tools.ietf.org/html/rfc5533)
@@ -225,9 +239,17 @@
 //! * Requirements for IP Version 4 Routers [RFC 1812](https://datatracker.ietf.org/doc/html/rfc1812)
 //! * Internet Control Message Protocol (ICMPv6) for the Internet Protocol Version 6 (IPv6) Specification [RFC 4443](https://datatracker.ietf.org/doc/html/rfc4443)
 //! * ICMP Router Discovery Messages [RFC 1256](https://datatracker.ietf.org/doc/html/rfc1256)
+//! * Internet Standard Subnetting Procedure [RFC 950](https://datatracker.ietf.org/doc/html/rfc950)
+//! * PROBE: A Utility for Probing Interfaces [RFC 8335](https://datatracker.ietf.org/doc/html/rfc8335)
+//! * Extended ICMP to Support Multi-Part Messages [RFC 4884](https://datatracker.ietf.org/doc/html/rfc4884)
+//! * ICMP Extensions for Multiprotocol Label Switching [RFC 4950](https://datatracker.ietf.org/doc/html/rfc4950)
+//! * Extending ICMP for Interface and Next-Hop Identification [RFC 5837](https://datatracker.ietf.org/doc/html/rfc5837)
 //! * [Internet Control Message Protocol version 6 (ICMPv6) Parameters](https://www.iana.org/assignments/icmpv6-parameters/icmpv6-parameters.xhtml)
 //! * Multicast Listener Discovery (MLD) for IPv6 [RFC 2710](https://datatracker.ietf.org/doc/html/rfc2710)
 //! * Neighbor Discovery for IP version 6 (IPv6) [RFC 4861](https://datatracker.ietf.org/doc/html/rfc4861)
+//! * Host Extensions for IP Multicasting [RFC 1112](https://datatracker.ietf.org/doc/html/rfc1112)
+//! * Internet Group Management Protocol, Version 2 [RFC 2236](https://datatracker.ietf.org/doc/html/rfc2236)
+//! * Internet Group Management Protocol, Version 3 [RFC 3376](https://datatracker.ietf.org/doc/html/rfc3376)
 
 // # Reason for 'bool_comparison' disable:
  //
@@ -255,21 +277,36 @@ mod link;
 pub use crate::link::LinkSlice;
 pub use crate::link::ethernet::*;
 pub use crate::link::vlan_tagging::*;
+pub use crate::link::arp::*;
 
 mod internet;
+pub use crate::internet::esp::*;
 pub use crate::internet::ip::*;
 pub use crate::internet::ip_authentication::*;
+pub use crate::internet::ip_authentication_icv::*;
 pub use crate::internet::ipv4::*;
+pub use crate::internet::ipv4_options::*;
 pub use crate::internet::ipv4_extensions::*;
 pub use crate::internet::ipv6::*;
 pub use crate::internet::ipv6_extensions::*;
+pub use crate::internet::ipv6_extensions_chain::*;
 pub use crate::internet::ipv6_raw_extension::*;
+pub use crate::internet::ipv6_options::*;
+pub use crate::internet::ipv6_routing::*;
+pub use crate::internet::ipv6_mobility::*;
+pub use crate::internet::hip::*;
+pub use crate::internet::shim6::*;
 pub use crate::internet::ipv6_fragment::*;
 
 mod transport;
 pub use crate::transport::icmp::*;
+pub use crate::transport::icmp_embedded_packet::*;
+pub use crate::transport::icmp_extensions::*;
 pub use crate::transport::icmpv4_impl::*;
 pub use crate::transport::icmpv6_impl::*;
+pub use crate::transport::igmp_impl::*;
+pub use crate::transport::mld::*;
+pub use crate::transport::ndp_options::*;
 pub use crate::transport::tcp::*;
 pub use crate::transport::udp::*;
 pub use crate::transport::TransportHeader;
@@ -286,6 +323,42 @@ pub use crate::packet_decoder::*;
 mod packet_slicing;
 pub use crate::packet_slicing::*;
 
+mod icmp_reply;
+pub use crate::icmp_reply::*;
+
+mod neighbor_cache;
+pub use crate::neighbor_cache::*;
+
+mod tcp_teardown;
+pub use crate::tcp_teardown::*;
+
+mod tcp_reassembly;
+pub use crate::tcp_reassembly::*;
+
+mod tcp_tracking;
+pub use crate::tcp_tracking::*;
+
+mod flow_key;
+pub use crate::flow_key::*;
+
+/// Toeplitz hashing as used for receive side scaling (RSS) by network cards.
+pub mod rss;
+
+mod flow_table;
+pub use crate::flow_table::*;
+
+mod netflow;
+pub use crate::netflow::*;
+
+mod flow_export;
+pub use crate::flow_export::*;
+
+mod flow_decoder;
+pub use crate::flow_decoder::*;
+
+mod sflow;
+pub use crate::sflow::*;
+
 pub mod packet_filter;
 
 ///Contains the size when serialized.
@@ -323,11 +396,61 @@ pub enum ReadError {
     IpAuthenticationHeaderTooSmallPayloadLength(u8),
     ///Error given if the data_offset field in a TCP header is smaller then the minimum size of the tcp header itself.
     TcpDataOffsetTooSmall(u8),
+    /// Error if the header length in a HIP header is smaller then the minimum size of 4 (40 bytes).
+    HipHeaderLengthTooSmall(u8),
     /// Error when the packet size is too big (e.g larger then can be represendted in a length field).
     ///
     /// This error can be triggered by
     /// * `Icmpv6Slice::from_slice`
     Icmpv6PacketTooBig(usize),
+    /// Error when the pad length in the trailer of a NULL encrypted ESP packet
+    /// is bigger then the data present in front of the trailer.
+    EspPadLengthTooLarge(u8),
+    /// Error when an ip authentication header was expected directly after
+    /// the ip header & its extension headers but a different protocol is present.
+    ///
+    /// This error can be triggered by
+    /// * `IpAuthenticatedPacketSlice::from_ip_slice`
+    IpAuthenticationHeaderNotPresent,
+    /// Error when the version of an ICMP extension structure is not 2 (rfc4884).
+    ///
+    /// This error can be triggered by
+    /// * `IcmpExtensionsSlice::from_slice`
+    IcmpExtensionUnsupportedVersion(u8),
+    /// Error when an ARP packet does not resolve IPv4 addresses to ethernet addresses
+    /// (contains the hardware type & protocol type of the packet).
+    ///
+    /// This error can be triggered by
+    /// * `ArpEthIpv4Packet::from_slice`
+    ArpUnsupportedAddressType{ hardware_type: u16, protocol_type: u16 },
+    /// Error when the version of a NetFlow or IPFIX message is not supported
+    /// or not the expected one (contains the version of the message).
+    ///
+    /// This error can be triggered by
+    /// * `NetflowV5Header::from_slice`
+    /// * `NetflowV9Header::from_slice`
+    /// * `IpfixHeader::from_slice`
+    /// * `FlowTemplateCache::decode`
+    FlowExportUnsupportedVersion(u16),
+    /// Error when the length field of an IPFIX message or the length field of a
+    /// NetFlow v9/IPFIX set is smaller then the header itself.
+    ///
+    /// This error can be triggered by
+    /// * `IpfixHeader::from_slice`
+    /// * `FlowTemplateCache::decode`
+    FlowExportLengthTooSmall(u16),
+    /// Error when the version of an sFlow datagram is not 5 (contains the
+    /// version of the datagram).
+    ///
+    /// This error can be triggered by
+    /// * `SflowDatagram::from_slice`
+    SflowUnsupportedVersion(u32),
+    /// Error when the agent address type of an sFlow datagram is not
+    /// unknown (0), IPv4 (1) or IPv6 (2).
+    ///
+    /// This error can be triggered by
+    /// * `SflowDatagram::from_slice`
+    SflowUnsupportedAddressType(u32),
 }
 
 impl ReadError {
@@ -400,8 +523,35 @@ impl fmt::Display for ReadError {
             TcpDataOffsetTooSmall(data_offset) => { //u8
                 write!(f, "ReadError: TCP data offset too small. The data offset value {} in the tcp header is smaller then the tcp header itself.", data_offset)
             },
+            HipHeaderLengthTooSmall(header_length) => { //u8
+                write!(f, "ReadError: HIP header length too small. The header length value {} in the HIP header is smaller then the minimum of 4.", header_length)
+            },
             Icmpv6PacketTooBig(size) => {
                 write!(f, "ReadError: ICMPv6 packet length {} is bigger then can be represented in an u32.", size)
+            },
+            EspPadLengthTooLarge(pad_length) => {
+                write!(f, "ReadError: ESP pad length {} is bigger then the data present in front of the ESP trailer.", pad_length)
+            },
+            IpAuthenticationHeaderNotPresent => {
+                write!(f, "ReadError: Expected an IP authentication header after the IP header & extension headers but a different protocol was present.")
+            },
+            IcmpExtensionUnsupportedVersion(version) => {
+                write!(f, "ReadError: Unsupported ICMP extension structure version {} (only version 2 is supported).", version)
+            },
+            ArpUnsupportedAddressType{ hardware_type, protocol_type } => {
+                write!(f, "ReadError: Unsupported ARP address types (hardware type {} & protocol type {:#06x}), only ethernet & IPv4 addresses are supported.", hardware_type, protocol_type)
+            },
+            FlowExportUnsupportedVersion(version) => {
+                write!(f, "ReadError: Unsupported or unexpected NetFlow/IPFIX version {} (supported are 5, 9 & 10).", version)
+            },
+            FlowExportLengthTooSmall(length) => {
+                write!(f, "ReadError: NetFlow/IPFIX length value {} is smaller then the message or set header itself.", length)
+            },
+            SflowUnsupportedVersion(version) => {
+                write!(f, "ReadError: Unsupported sFlow datagram version {} (only version 5 is supported).", version)
+            },
+            SflowUnsupportedAddressType(address_type) => {
+                write!(f, "ReadError: Unsupported sFlow agent address type {} (supported are 0, 1 & 2).", address_type)
             }
         }
     }
@@ -512,6 +662,16 @@ pub enum ValueError {
     Ipv6ExtensionPayloadTooLarge(usize),
     /// Error when a given payload length is not aligned to be a multiple of 8 octets when 6 is substracted and can not be represented by the header length field.
     Ipv6ExtensionPayloadLengthUnaligned(usize),
+    /// Error when the data of an ipv6 hop by hop or destination option can not be represented in the option (bigger then 255 bytes or an unaligned CALIPSO compartment bitmap).
+    Ipv6OptionDataLengthBad(usize),
+    /// Error when an ICMPv6 neighbor discovery option is bigger then what can be represented in the option length field (2040 bytes).
+    NdpOptionDataLengthBad(usize),
+    /// Error when a MLDv2 multicast address record can not be represented (more then 65535 sources or auxiliary data not a multiple of 4 bytes or bigger then 1020 bytes).
+    MulticastAddressRecordLengthBad(usize),
+    /// Error when an IGMPv3 group record can not be represented (more then 65535 sources or auxiliary data not a multiple of 4 bytes or bigger then 1020 bytes).
+    IgmpGroupRecordLengthBad(usize),
+    /// Error when the addresses of an ipv6 routing header can not be represented in the header (RPL addresses not matching the compression values or a segment list with no or more then 256 segments).
+    Ipv6RoutingAddressesLengthBad(usize),
     /// Error when a given authentication header icv size is not a multiple of 4 bytes or bigger then 1016 bytes and therefor can not be represented in the header length field.
     IpAuthenticationHeaderBadIcvLength(usize),
     /// Error when a header in `Ipv4Extensions` is never written as it is never referenced by any of the other `next_header` fields or the initial `protocol`.
@@ -534,6 +694,8 @@ pub enum ValueError {
     U16TooLarge{value: u16, max: u16, field: ErrorField},
     /// Error when a u32 field in a header has a larger value then supported.
     U32TooLarge{value: u32, max: u32, field: ErrorField},
+    /// Error when a u64 field in a header has a larger value then supported.
+    U64TooLarge{value: u64, max: u64, field: ErrorField},
     /// Error when an Icmpv6 payload is found in an IPv4 packet.
     Icmpv6InIpv4,
 }
@@ -564,6 +726,21 @@ impl fmt::Display for ValueError {
             Ipv6ExtensionPayloadLengthUnaligned(size) => {
                 write!(f, "IPv6 extensions header 'payload length ({} bytes) + 2' is not multiple of 8 (+ 2 for the `next_header` and `header_length` fields). This is required as the header length field can only express lengths in multiple of 8 bytes.", size)
             },
+            Ipv6OptionDataLengthBad(size) => {
+                write!(f, "IPv6 option data length ({} bytes) can not be represented in the option (either bigger then 255 bytes or an unaligned CALIPSO compartment bitmap).", size)
+            },
+            NdpOptionDataLengthBad(size) => {
+                write!(f, "ICMPv6 neighbor discovery option length ({} bytes) is bigger then the maximum of 2040 bytes that can be represented in the option length field.", size)
+            },
+            MulticastAddressRecordLengthBad(size) => {
+                write!(f, "MLDv2 multicast address record (size {} bytes) can not be represented (either more then 65535 sources or the auxiliary data is not a multiple of 4 bytes or bigger then 1020 bytes).", size)
+            },
+            IgmpGroupRecordLengthBad(size) => {
+                write!(f, "IGMPv3 group record (size {} bytes) can not be represented (either more then 65535 sources or the auxiliary data is not a multiple of 4 bytes or bigger then 1020 bytes).", size)
+            },
+            Ipv6RoutingAddressesLengthBad(size) => {
+                write!(f, "IPv6 routing header addresses length ({} bytes) can not be represented in the header (either not matching the RPL compression values or a segment list with no or more then 256 segments).", size)
+            },
             IpAuthenticationHeaderBadIcvLength(size) => {
                 write!(f, "IP authentication header 'raw_icv' value has a length ({} bytes) is either not a multiple of 4 bytes or bigger then the maximum of 1016 bytes.", size)
             },
@@ -594,6 +771,9 @@ impl fmt::Display for ValueError {
             U32TooLarge{value, max, field} => {
                 write!(f, "The value {} of the field '{}' is larger then the allowed maximum of {}.", value, field, max)
             },
+            U64TooLarge{value, max, field} => {
+                write!(f, "The value {} of the field '{}' is larger then the allowed maximum of {}.", value, field, max)
+            },
             Icmpv6InIpv4 => {
                 write!(f, "ICMPv6 packet can not be combined with IPv4 headers.")
             },
@@ -608,9 +788,25 @@ pub enum ErrorField {
     Ipv4Dscp,
     Ipv4Ecn,
     Ipv4FragmentsOffset,
+    /// Ipv4OptionElement::Timestamp.overflow
+    Ipv4OptionTimestampOverflow,
     Ipv6FlowLabel,
     /// Ipv6 fragment header fragment offset field.
     Ipv6FragmentOffset,
+    /// Ipv6RoutingHeader::Rpl.cmpr_i
+    Ipv6RplCmprI,
+    /// Ipv6RoutingHeader::Rpl.cmpr_e
+    Ipv6RplCmprE,
+    /// HipHeader.packet_type
+    HipPacketType,
+    /// HipHeader.version
+    HipVersion,
+    /// Shim6PayloadHeader.receiver_context_tag
+    Shim6ReceiverContextTag,
+    /// Shim6ControlHeader.message_type
+    Shim6MessageType,
+    /// Shim6ControlHeader.type_specific
+    Shim6TypeSpecific,
     ///VlanTaggingHeader.priority_code_point
     VlanTagPriorityCodePoint,
     ///VlanTaggingHeader.vlan_identifier
@@ -625,8 +821,16 @@ impl fmt::Display for ErrorField {
             Ipv4Dscp => write!(f, "Ipv4Header.differentiated_services_code_point"),
             Ipv4Ecn => write!(f, "Ipv4Header.explicit_congestion_notification"),
             Ipv4FragmentsOffset => write!(f, "Ipv4Header.fragments_offset"),
+            Ipv4OptionTimestampOverflow => write!(f, "Ipv4OptionElement::Timestamp.overflow"),
             Ipv6FlowLabel => write!(f, "Ipv6Header.flow_label"),
             Ipv6FragmentOffset => write!(f, "Ipv6FragmentHeader.fragment_offset"),
+            Ipv6RplCmprI => write!(f, "Ipv6RoutingHeader::Rpl.cmpr_i"),
+            Ipv6RplCmprE => write!(f, "Ipv6RoutingHeader::Rpl.cmpr_e"),
+            HipPacketType => write!(f, "HipHeader.packet_type"),
+            HipVersion => write!(f, "HipHeader.version"),
+            Shim6ReceiverContextTag => write!(f, "Shim6PayloadHeader.receiver_context_tag"),
+            Shim6MessageType => write!(f, "Shim6ControlHeader.message_type"),
+            Shim6TypeSpecific => write!(f, "Shim6ControlHeader.type_specific"),
             VlanTagPriorityCodePoint => write!(f, "SingleVlanHeader.priority_code_point"),
             VlanTagVlanId => write!(f, "SingleVlanHeader.vlan_identifier")
         }
@@ -0,0 +1,195 @@
+use super::super::*;
+
+use std::io;
+
+/// Constants for the hardware type field of ARP packets.
+pub mod arp_hardware_type {
+    /// Ethernet (10Mb).
+    pub const ETHERNET: u16 = 1;
+}
+
+/// Constants for the operation field of ARP packets.
+pub mod arp_operation {
+    /// ARP request (rfc826).
+    pub const REQUEST: u16 = 1;
+    /// ARP reply (rfc826).
+    pub const REPLY: u16 = 2;
+}
+
+/// ARP packet (rfc826) resolving an IPv4 address to an ethernet MAC address.
+///
+/// The hardware type (ethernet), protocol type (IPv4) and the address
+/// lengths (6 & 4) are implied by this type and are not stored.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ArpEthIpv4Packet {
+    /// Operation of the packet (see [`arp_operation`] for possible values).
+    pub operation: u16,
+    /// MAC address of the sender.
+    pub sender_hw_addr: [u8;6],
+    /// IPv4 address of the sender.
+    pub sender_protocol_addr: [u8;4],
+    /// MAC address of the target (ignored in requests).
+    pub target_hw_addr: [u8;6],
+    /// IPv4 address of the target.
+    pub target_protocol_addr: [u8;4],
+}
+
+impl SerializedSize for ArpEthIpv4Packet {
+    /// Serialized size of the packet in bytes/octets.
+    const SERIALIZED_SIZE: usize = 28;
+}
+
+impl ArpEthIpv4Packet {
+    /// Read an ARP packet from a slice and return the packet & unused parts of the slice.
+    ///
+    /// Returns `ReadError::ArpUnsupportedAddressType` if the hardware type, protocol
+    /// type or address lengths do not match ethernet & IPv4.
+    pub fn from_slice(slice: &[u8]) -> Result<(ArpEthIpv4Packet, &[u8]), ReadError> {
+        let packet = ArpPacketSlice::from_slice(slice)?;
+        let rest = &slice[packet.slice().len()..];
+        match packet.to_eth_ipv4() {
+            Some(result) => Ok((result, rest)),
+            None => Err(ReadError::ArpUnsupportedAddressType{
+                hardware_type: packet.hardware_type(),
+                protocol_type: packet.protocol_type(),
+            }),
+        }
+    }
+
+    /// Writes the packet to the given writer.
+    pub fn write<T: io::Write + Sized>(&self, writer: &mut T) -> Result<(), io::Error> {
+        writer.write_all(&self.to_bytes())
+    }
+
+    /// Length of the serialized packet in bytes.
+    #[inline]
+    pub fn header_len(&self) -> usize {
+        ArpEthIpv4Packet::SERIALIZED_SIZE
+    }
+
+    /// Returns the serialized form of the packet.
+    pub fn to_bytes(&self) -> [u8;28] {
+        let hw_type = arp_hardware_type::ETHERNET.to_be_bytes();
+        let protocol_type = ether_type::IPV4.to_be_bytes();
+        let operation = self.operation.to_be_bytes();
+        let s_hw = self.sender_hw_addr;
+        let s_ip = self.sender_protocol_addr;
+        let t_hw = self.target_hw_addr;
+        let t_ip = self.target_protocol_addr;
+        [
+            hw_type[0], hw_type[1],
+            protocol_type[0], protocol_type[1],
+            6, 4,
+            operation[0], operation[1],
+            s_hw[0], s_hw[1], s_hw[2], s_hw[3], s_hw[4], s_hw[5],
+            s_ip[0], s_ip[1], s_ip[2], s_ip[3],
+            t_hw[0], t_hw[1], t_hw[2], t_hw[3], t_hw[4], t_hw[5],
+            t_ip[0], t_ip[1], t_ip[2], t_ip[3],
+        ]
+    }
+}
+
+/// A slice containing an ARP packet (rfc826) with arbitrary hardware & protocol
+/// address types.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ArpPacketSlice<'a> {
+    slice: &'a [u8],
+}
+
+impl<'a> ArpPacketSlice<'a> {
+    /// Creates a slice containing an ARP packet (the slice is cut to the
+    /// length of the packet, e.g. to exclude ethernet padding).
+    pub fn from_slice(slice: &'a [u8]) -> Result<ArpPacketSlice<'a>, ReadError> {
+        use crate::ReadError::*;
+        if slice.len() < 8 {
+            return Err(UnexpectedEndOfSlice(8));
+        }
+        let len = 8 + 2*(usize::from(slice[4]) + usize::from(slice[5]));
+        if slice.len() < len {
+            return Err(UnexpectedEndOfSlice(len));
+        }
+        Ok(ArpPacketSlice {
+            slice: &slice[..len],
+        })
+    }
+
+    /// Returns the slice containing the ARP packet.
+    #[inline]
+    pub fn slice(&self) -> &'a [u8] {
+        self.slice
+    }
+
+    /// Read the hardware type field (see [`arp_hardware_type`] for possible values).
+    #[inline]
+    pub fn hardware_type(&self) -> u16 {
+        u16::from_be_bytes([self.slice[0], self.slice[1]])
+    }
+
+    /// Read the protocol type field (an ether type value, e.g. `ether_type::IPV4`).
+    #[inline]
+    pub fn protocol_type(&self) -> u16 {
+        u16::from_be_bytes([self.slice[2], self.slice[3]])
+    }
+
+    /// Read the length of the hardware addresses in bytes.
+    #[inline]
+    pub fn hw_addr_len(&self) -> u8 {
+        self.slice[4]
+    }
+
+    /// Read the length of the protocol addresses in bytes.
+    #[inline]
+    pub fn protocol_addr_len(&self) -> u8 {
+        self.slice[5]
+    }
+
+    /// Read the operation field (see [`arp_operation`] for possible values).
+    #[inline]
+    pub fn operation(&self) -> u16 {
+        u16::from_be_bytes([self.slice[6], self.slice[7]])
+    }
+
+    /// Slice containing the hardware address of the sender.
+    pub fn sender_hw_addr(&self) -> &'a [u8] {
+        let start = 8;
+        &self.slice[start..start + usize::from(self.hw_addr_len())]
+    }
+
+    /// Slice containing the protocol address of the sender.
+    pub fn sender_protocol_addr(&self) -> &'a [u8] {
+        let start = 8 + usize::from(self.hw_addr_len());
+        &self.slice[start..start + usize::from(self.protocol_addr_len())]
+    }
+
+    /// Slice containing the hardware address of the target.
+    pub fn target_hw_addr(&self) -> &'a [u8] {
+        let start = 8 + usize::from(self.hw_addr_len()) + usize::from(self.protocol_addr_len());
+        &self.slice[start..start + usize::from(self.hw_addr_len())]
+    }
+
+    /// Slice containing the protocol address of the target.
+    pub fn target_protocol_addr(&self) -> &'a [u8] {
+        let start = 8 + 2*usize::from(self.hw_addr_len()) + usize::from(self.protocol_addr_len());
+        &self.slice[start..start + usize::from(self.protocol_addr_len())]
+    }
+
+    /// Decodes the packet if it resolves an IPv4 address to an ethernet MAC
+    /// address (returns `None` otherwise).
+    pub fn to_eth_ipv4(&self) -> Option<ArpEthIpv4Packet> {
+        if arp_hardware_type::ETHERNET != self.hardware_type() ||
+           ether_type::IPV4 != self.protocol_type() ||
+           6 != self.hw_addr_len() ||
+           4 != self.protocol_addr_len()
+        {
+            return None;
+        }
+        let s = self.slice;
+        Some(ArpEthIpv4Packet {
+            operation: self.operation(),
+            sender_hw_addr: [s[8], s[9], s[10], s[11], s[12], s[13]],
+            sender_protocol_addr: [s[14], s[15], s[16], s[17]],
+            target_hw_addr: [s[18], s[19], s[20], s[21], s[22], s[23]],
+            target_protocol_addr: [s[24], s[25], s[26], s[27]],
+        })
+    }
+}
@@ -0,0 +1,657 @@
+use super::*;
+
+use std::collections::{BTreeSet, HashMap};
+
+/// Timing & retransmission parameters of a [`NeighborCache`].
+///
+/// All times are in milliseconds. The default values are the ones
+/// recommended in rfc4861 section 10.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NeighborCacheConfig {
+    /// Time a neighbor is considered reachable after its reachability
+    /// was confirmed (default 30000).
+    pub reachable_time: u64,
+    /// Time between retransmitted solicitations (default 1000).
+    pub retrans_timer: u64,
+    /// Time waited before a stale entry that is in use is probed (default 5000).
+    pub delay_first_probe_time: u64,
+    /// Number of multicast (broadcast for ARP) solicitations sent before the
+    /// resolution of an address is given up (default 3).
+    pub max_multicast_solicit: u32,
+    /// Number of unicast solicitations sent before a stale entry is
+    /// removed (default 3).
+    pub max_unicast_solicit: u32,
+    /// Maximum number of IPv4 and of IPv6 neighbor entries (the least
+    /// recently used entry gets removed if a new entry exceeds the limit,
+    /// default 1024).
+    ///
+    /// A value of 0 is treated like 1, as the entry of an address that
+    /// gets resolved is always added to the cache.
+    pub max_entries: usize,
+}
+
+impl Default for NeighborCacheConfig {
+    fn default() -> NeighborCacheConfig {
+        NeighborCacheConfig {
+            reachable_time: 30_000,
+            retrans_timer: 1_000,
+            delay_first_probe_time: 5_000,
+            max_multicast_solicit: 3,
+            max_unicast_solicit: 3,
+            max_entries: 1024,
+        }
+    }
+}
+
+/// Reachability state of a neighbor (rfc4861 section 7.3.2).
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum NeighborState {
+    /// Address resolution is in progress and the link-layer address
+    /// is not yet known.
+    Incomplete,
+    /// The neighbor was recently confirmed to be reachable.
+    Reachable,
+    /// The link-layer address is known, but the reachability was not
+    /// confirmed recently.
+    Stale,
+    /// The entry was used while stale, a probe is sent if no confirmation
+    /// arrives before the timer expires.
+    Delay,
+    /// Unicast solicitations are sent to confirm the reachability.
+    Probe,
+}
+
+/// Entry of a [`NeighborCache`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NeighborEntry {
+    /// Link-layer (MAC) address of the neighbor (`None` while incomplete).
+    pub link_address: Option<[u8;6]>,
+    /// Reachability state of the neighbor.
+    pub state: NeighborState,
+    /// Timestamp at which the timer of the current state expires (`None`
+    /// for stale entries).
+    pub timer: Option<u64>,
+    /// Number of solicitations sent in the current state.
+    pub solicitations_sent: u32,
+}
+
+impl NeighborEntry {
+    /// Link-layer address if the entry can be used to send packets.
+    fn usable_link_address(&self) -> Option<[u8;6]> {
+        match self.state {
+            NeighborState::Incomplete => None,
+            _ => self.link_address,
+        }
+    }
+
+    /// Updates the entry with a link-layer address received from the neighbor.
+    ///
+    /// `confirmed` has to be set if the packet confirms the reachability (e.g. a
+    /// solicited advertisement or an ARP reply addressed to the cache).
+    fn update(&mut self, link_address: [u8;6], confirmed: bool, now: u64, config: &NeighborCacheConfig) {
+        if confirmed {
+            self.link_address = Some(link_address);
+            self.state = NeighborState::Reachable;
+            self.timer = Some(now.saturating_add(config.reachable_time));
+            self.solicitations_sent = 0;
+        } else if self.link_address != Some(link_address) || NeighborState::Incomplete == self.state {
+            self.link_address = Some(link_address);
+            self.state = NeighborState::Stale;
+            self.timer = None;
+            self.solicitations_sent = 0;
+        }
+    }
+
+    /// Entry of a neighbor that sent its link-layer address without
+    /// confirming its reachability.
+    fn stale(link_address: [u8;6]) -> NeighborEntry {
+        NeighborEntry {
+            link_address: Some(link_address),
+            state: NeighborState::Stale,
+            timer: None,
+            solicitations_sent: 0,
+        }
+    }
+}
+
+/// I/O free neighbor cache resolving IPv4 addresses via ARP (rfc826) and
+/// IPv6 addresses via the neighbor discovery protocol (rfc4861) on an
+/// ethernet link.
+///
+/// The cache is driven by the caller:
+///
+/// * Received packets are passed to [`NeighborCache::receive`]. The cache learns
+///   the link-layer addresses contained in ARP packets, neighbor solicitations
+///   & advertisements and returns a reply frame if a request was addressed to one
+///   of the local addresses.
+/// * [`NeighborCache::resolve_ipv4`] & [`NeighborCache::resolve_ipv6`] return the
+///   link-layer address of a neighbor and start the address resolution if the
+///   address is not yet known.
+/// * [`NeighborCache::poll`] has to be called when the timer returned by
+///   [`NeighborCache::next_timer`] expires. It updates the states of the
+///   entries and returns the solicitations that have to be sent.
+///
+/// All timestamps are passed in by the caller (in milliseconds from an arbitrary
+/// monotonic clock). The same state machine (rfc4861 section 7.3.2) is used for
+/// IPv4 & IPv6 neighbors.
+///
+/// # Example
+///
+/// ```
+/// use etherparse::*;
+///
+/// let mut cache = NeighborCache::new([2,0,0,0,0,1], NeighborCacheConfig::default());
+/// cache.add_ipv4_address([192,168,1,1]);
+///
+/// // starting the address resolution
+/// assert_eq!(None, cache.resolve_ipv4([192,168,1,2], 0));
+/// let requests = cache.poll(0);
+/// assert_eq!(1, requests.len());
+///
+/// // the ARP reply of the neighbor
+/// let mut reply = Vec::new();
+/// Ethernet2Header{
+///     source: [2,0,0,0,0,2],
+///     destination: [2,0,0,0,0,1],
+///     ether_type: ether_type::ARP,
+/// }.write(&mut reply).unwrap();
+/// ArpEthIpv4Packet{
+///     operation: arp_operation::REPLY,
+///     sender_hw_addr: [2,0,0,0,0,2],
+///     sender_protocol_addr: [192,168,1,2],
+///     target_hw_addr: [2,0,0,0,0,1],
+///     target_protocol_addr: [192,168,1,1],
+/// }.write(&mut reply).unwrap();
+/// assert_eq!(None, cache.receive(&SlicedPacket::from_ethernet(&reply).unwrap(), 10));
+///
+/// assert_eq!(Some([2,0,0,0,0,2]), cache.resolve_ipv4([192,168,1,2], 20));
+/// ```
+#[derive(Clone, Debug)]
+pub struct NeighborCache {
+    config: NeighborCacheConfig,
+    link_address: [u8;6],
+    ipv4_addresses: Vec<[u8;4]>,
+    ipv6_addresses: Vec<[u8;16]>,
+    ipv4_neighbors: NeighborTable<[u8;4]>,
+    ipv6_neighbors: NeighborTable<[u8;16]>,
+}
+
+impl NeighborCache {
+    /// Creates an empty cache for an interface with the given MAC address.
+    pub fn new(link_address: [u8;6], config: NeighborCacheConfig) -> NeighborCache {
+        NeighborCache {
+            config,
+            link_address,
+            ipv4_addresses: Vec::new(),
+            ipv6_addresses: Vec::new(),
+            ipv4_neighbors: NeighborTable::new(),
+            ipv6_neighbors: NeighborTable::new(),
+        }
+    }
+
+    /// MAC address of the local interface.
+    pub fn link_address(&self) -> [u8;6] {
+        self.link_address
+    }
+
+    /// Timing & retransmission parameters of the cache.
+    pub fn config(&self) -> &NeighborCacheConfig {
+        &self.config
+    }
+
+    /// Adds a local IPv4 address (ARP requests for it are answered). The first
+    /// added address is used as sender address of ARP requests.
+    pub fn add_ipv4_address(&mut self, address: [u8;4]) {
+        if false == self.ipv4_addresses.contains(&address) {
+            self.ipv4_addresses.push(address);
+        }
+    }
+
+    /// Adds a local IPv6 address (neighbor solicitations for it are answered). The
+    /// first added address is used as source address of neighbor solicitations.
+    pub fn add_ipv6_address(&mut self, address: [u8;16]) {
+        if false == self.ipv6_addresses.contains(&address) {
+            self.ipv6_addresses.push(address);
+        }
+    }
+
+    /// Local IPv4 addresses.
+    pub fn ipv4_addresses(&self) -> &[[u8;4]] {
+        &self.ipv4_addresses
+    }
+
+    /// Local IPv6 addresses.
+    pub fn ipv6_addresses(&self) -> &[[u8;16]] {
+        &self.ipv6_addresses
+    }
+
+    /// Returns the cache entry of an IPv4 neighbor.
+    pub fn ipv4_neighbor(&self, address: [u8;4]) -> Option<&NeighborEntry> {
+        self.ipv4_neighbors.get(&address)
+    }
+
+    /// Returns the cache entry of an IPv6 neighbor.
+    pub fn ipv6_neighbor(&self, address: [u8;16]) -> Option<&NeighborEntry> {
+        self.ipv6_neighbors.get(&address)
+    }
+
+    /// Returns the MAC address packets to the given IPv4 address have to be
+    /// sent to.
+    ///
+    /// If the address is unknown an incomplete entry is created & `None` is
+    /// returned. The ARP requests are returned by the following calls to
+    /// [`NeighborCache::poll`]. Multicast & the limited broadcast addresses
+    /// are mapped directly to MAC addresses (rfc1112 6.4).
+    pub fn resolve_ipv4(&mut self, address: [u8;4], now: u64) -> Option<[u8;6]> {
+        if [0xff;4] == address {
+            return Some([0xff;6]);
+        }
+        if 0xe0 == address[0] & 0xf0 {
+            return Some([0x01, 0x00, 0x5e, address[1] & 0x7f, address[2], address[3]]);
+        }
+        self.ipv4_neighbors.resolve(address, now, &self.config)
+    }
+
+    /// Returns the MAC address packets to the given IPv6 address have to be
+    /// sent to.
+    ///
+    /// If the address is unknown an incomplete entry is created & `None` is
+    /// returned. The neighbor solicitations are returned by the following
+    /// calls to [`NeighborCache::poll`]. Multicast addresses are mapped
+    /// directly to MAC addresses (rfc2464 7).
+    pub fn resolve_ipv6(&mut self, address: [u8;16], now: u64) -> Option<[u8;6]> {
+        if 0xff == address[0] {
+            return Some(ipv6_multicast_link_address(address));
+        }
+        self.ipv6_neighbors.resolve(address, now, &self.config)
+    }
+
+    /// Timestamp at which [`NeighborCache::poll`] has to be called next (`None`
+    /// if no timer is running).
+    pub fn next_timer(&self) -> Option<u64> {
+        self.ipv4_neighbors.entries()
+            .chain(self.ipv6_neighbors.entries())
+            .filter_map(|entry| entry.timer)
+            .min()
+    }
+
+    /// Processes the expired timers of all entries and returns the ethernet
+    /// frames (ARP requests & neighbor solicitations) that have to be sent.
+    ///
+    /// Entries whose resolution or probing failed are removed.
+    pub fn poll(&mut self, now: u64) -> Vec<Vec<u8>> {
+        let mut result = Vec::new();
+
+        let mut solicitations = Vec::new();
+        self.ipv4_neighbors.poll(now, &self.config, &mut solicitations);
+        for (address, unicast) in solicitations {
+            result.push(self.arp_request(address, unicast));
+        }
+
+        let mut solicitations = Vec::new();
+        self.ipv6_neighbors.poll(now, &self.config, &mut solicitations);
+        for (address, unicast) in solicitations {
+            result.push(self.neighbor_solicitation(address, unicast));
+        }
+
+        result
+    }
+
+    /// Processes a received packet.
+    ///
+    /// ARP packets, neighbor solicitations & neighbor advertisements are used to
+    /// update the cache (other packets are ignored). If the packet is an ARP request
+    /// or a neighbor solicitation for a local address, the reply frame is returned.
+    ///
+    /// Replies are sent with the same vlan headers as the request.
+    pub fn receive(&mut self, packet: &SlicedPacket, now: u64) -> Option<Vec<u8>> {
+        if Some(ether_type::ARP) == packet.payload_ether_type() {
+            return self.receive_arp(packet, now);
+        }
+        match (&packet.ip, &packet.transport) {
+            (Some(InternetSlice::Ipv6(ip, _)), Some(TransportSlice::Icmpv6(icmp))) => {
+                self.receive_ndp(packet, ip, icmp, now)
+            },
+            _ => None,
+        }
+    }
+
+    fn receive_arp(&mut self, packet: &SlicedPacket, now: u64) -> Option<Vec<u8>> {
+        let arp = ArpPacketSlice::from_slice(packet.payload).ok()?.to_eth_ipv4()?;
+        if arp.sender_hw_addr == self.link_address {
+            return None;
+        }
+        let for_us = self.ipv4_addresses.contains(&arp.target_protocol_addr);
+
+        // learn the address of the sender (rfc826 "Packet Reception")
+        if [0;4] != arp.sender_protocol_addr {
+            let confirmed = for_us && arp_operation::REPLY == arp.operation;
+            if let Some(entry) = self.ipv4_neighbors.get_mut(arp.sender_protocol_addr, now) {
+                entry.update(arp.sender_hw_addr, confirmed, now, &self.config);
+            } else if for_us {
+                let mut entry = NeighborEntry::stale(arp.sender_hw_addr);
+                entry.update(arp.sender_hw_addr, confirmed, now, &self.config);
+                self.ipv4_neighbors.insert(arp.sender_protocol_addr, entry, now, self.config.max_entries);
+            }
+        }
+
+        // reply to requests for local addresses
+        if for_us && arp_operation::REQUEST == arp.operation {
+            let mut frame = link_headers(self.link_address, arp.sender_hw_addr, ether_type::ARP, packet);
+            frame.extend_from_slice(&ArpEthIpv4Packet {
+                operation: arp_operation::REPLY,
+                sender_hw_addr: self.link_address,
+                sender_protocol_addr: arp.target_protocol_addr,
+                target_hw_addr: arp.sender_hw_addr,
+                target_protocol_addr: arp.sender_protocol_addr,
+            }.to_bytes());
+            Some(frame)
+        } else {
+            None
+        }
+    }
+
+    fn receive_ndp(&mut self, packet: &SlicedPacket, ip: &Ipv6HeaderSlice, icmp: &Icmpv6Slice, now: u64) -> Option<Vec<u8>> {
+        // validate the message (rfc4861 7.1.1 & 7.1.2)
+        if 255 != ip.hop_limit() || 0 != icmp.code_u8() || false == icmp.is_checksum_valid(ip.source(), ip.destination()) {
+            return None;
+        }
+        let link_address_option = |option_type: u8| {
+            icmp.ndp_options()?
+                .filter_map(|option| option.ok())
+                .find(|option| option.option_type() == option_type)
+                .and_then(|option| match option {
+                    NdpOption::SourceLinkLayerAddress(value) |
+                    NdpOption::TargetLinkLayerAddress(value) if value.len() >= 6 => {
+                        Some([value[0], value[1], value[2], value[3], value[4], value[5]])
+                    },
+                    _ => None,
+                })
+        };
+
+        match icmp.icmp_type() {
+            Icmpv6Type::NeighborSolicitation(solicitation) => {
+                let source_link_address = link_address_option(ndp_option::TYPE_SOURCE_LINK_LAYER_ADDRESS);
+                let unspecified_source = [0;16] == ip.source();
+                let for_us = self.ipv6_addresses.contains(&solicitation.target_address);
+
+                // learn the address of the sender (rfc4861 7.2.3)
+                if let (Some(link_address), false) = (source_link_address, unspecified_source) {
+                    if let Some(entry) = self.ipv6_neighbors.get_mut(ip.source(), now) {
+                        entry.update(link_address, false, now, &self.config);
+                    } else if for_us {
+                        self.ipv6_neighbors.insert(ip.source(), NeighborEntry::stale(link_address), now, self.config.max_entries);
+                    }
+                }
+
+                if false == for_us {
+                    return None;
+                }
+
+                // reply (rfc4861 7.2.4), duplicate address detection probes
+                // are answered to the all-nodes multicast address
+                let (destination, destination_link_address, solicited) = if unspecified_source {
+                    let all_nodes = [0xff, 0x02, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1];
+                    (all_nodes, ipv6_multicast_link_address(all_nodes), false)
+                } else {
+                    let link_address = source_link_address.or_else(|| {
+                        packet.link.as_ref().map(|link| match link {
+                            LinkSlice::Ethernet2(eth) => eth.source(),
+                        })
+                    })?;
+                    (ip.source(), link_address, true)
+                };
+                let advertisement = icmpv6::NeighborAdvertisementHeader {
+                    router: false,
+                    solicited,
+                    override_flag: true,
+                    target_address: solicitation.target_address,
+                };
+                Some(ndp_frame(
+                    link_headers(self.link_address, destination_link_address, ether_type::IPV6, packet),
+                    solicitation.target_address,
+                    destination,
+                    Icmpv6Type::NeighborAdvertisement(advertisement),
+                    Some(NdpOption::TargetLinkLayerAddress(&self.link_address))
+                ))
+            },
+            Icmpv6Type::NeighborAdvertisement(advertisement) => {
+                // process the advertisement (rfc4861 7.2.5)
+                let target_link_address = link_address_option(ndp_option::TYPE_TARGET_LINK_LAYER_ADDRESS);
+                if let Some(entry) = self.ipv6_neighbors.get_mut(advertisement.target_address, now) {
+                    match (entry.state, target_link_address) {
+                        (NeighborState::Incomplete, None) => {},
+                        (NeighborState::Incomplete, Some(link_address)) => {
+                            entry.update(link_address, advertisement.solicited, now, &self.config);
+                        },
+                        (_, Some(link_address)) if false == advertisement.override_flag && entry.link_address != Some(link_address) => {
+                            if NeighborState::Reachable == entry.state {
+                                entry.state = NeighborState::Stale;
+                                entry.timer = None;
+                            }
+                        },
+                        (_, link_address) => {
+                            let link_address = link_address.or(entry.link_address);
+                            if let Some(link_address) = link_address {
+                                entry.update(link_address, advertisement.solicited, now, &self.config);
+                            }
+                        },
+                    }
+                }
+                None
+            },
+            _ => None,
+        }
+    }
+
+    /// Returns an ARP request frame (broadcast if no unicast address is given).
+    fn arp_request(&self, target: [u8;4], unicast: Option<[u8;6]>) -> Vec<u8> {
+        let mut frame = Ethernet2Header {
+            source: self.link_address,
+            destination: unicast.unwrap_or([0xff;6]),
+            ether_type: ether_type::ARP,
+        }.to_bytes().to_vec();
+        frame.extend_from_slice(&ArpEthIpv4Packet {
+            operation: arp_operation::REQUEST,
+            sender_hw_addr: self.link_address,
+            sender_protocol_addr: self.ipv4_addresses.first().copied().unwrap_or([0;4]),
+            target_hw_addr: [0;6],
+            target_protocol_addr: target,
+        }.to_bytes());
+        frame
+    }
+
+    /// Returns a neighbor solicitation frame (sent to the solicited-node
+    /// multicast address if no unicast address is given).
+    fn neighbor_solicitation(&self, target: [u8;16], unicast: Option<[u8;6]>) -> Vec<u8> {
+        let (destination, destination_link_address) = match unicast {
+            Some(link_address) => (target, link_address),
+            None => {
+                let solicited_node = [
+                    0xff, 0x02, 0, 0, 0, 0, 0, 0,
+                    0, 0, 0, 0x01, 0xff, target[13], target[14], target[15]
+                ];
+                (solicited_node, ipv6_multicast_link_address(solicited_node))
+            }
+        };
+        let source = self.ipv6_addresses.first().copied().unwrap_or([0;16]);
+        // the source link-layer address option must not be included if
+        // the source address is unspecified (rfc4861 4.3)
+        let option = if [0;16] == source {
+            None
+        } else {
+            Some(NdpOption::SourceLinkLayerAddress(&self.link_address))
+        };
+        let link_header = Ethernet2Header {
+            source: self.link_address,
+            destination: destination_link_address,
+            ether_type: ether_type::IPV6,
+        };
+        ndp_frame(
+            link_header.to_bytes().to_vec(),
+            source,
+            destination,
+            Icmpv6Type::NeighborSolicitation(icmpv6::NeighborSolicitationHeader {
+                target_address: target,
+            }),
+            option
+        )
+    }
+}
+
+/// Neighbor entries of one address family with an index ordered by the time
+/// the entries were last used (used to find the least recently used entry
+/// without iterating over all entries).
+#[derive(Clone, Debug)]
+struct NeighborTable<A> {
+    /// Entries together with the time they were last used.
+    entries: HashMap<A, (u64, NeighborEntry)>,
+    by_last: BTreeSet<(u64, A)>,
+}
+
+impl<A: std::hash::Hash + Ord + Copy> NeighborTable<A> {
+    fn new() -> NeighborTable<A> {
+        NeighborTable {
+            entries: HashMap::new(),
+            by_last: BTreeSet::new(),
+        }
+    }
+
+    fn get(&self, address: &A) -> Option<&NeighborEntry> {
+        self.entries.get(address).map(|(_, entry)| entry)
+    }
+
+    /// Returns the entry of the given address & marks it as used.
+    fn get_mut(&mut self, address: A, now: u64) -> Option<&mut NeighborEntry> {
+        let (last, entry) = self.entries.get_mut(&address)?;
+        self.by_last.remove(&(*last, address));
+        self.by_last.insert((now, address));
+        *last = now;
+        Some(entry)
+    }
+
+    /// Adds an entry & removes the least recently used entry if the
+    /// table is full.
+    fn insert(&mut self, address: A, entry: NeighborEntry, now: u64, max_entries: usize) {
+        if let Some((last, _)) = self.entries.get(&address) {
+            self.by_last.remove(&(*last, address));
+        } else if self.entries.len() >= max_entries {
+            let oldest = self.by_last.iter().next().copied();
+            if let Some((last, oldest)) = oldest {
+                self.by_last.remove(&(last, oldest));
+                self.entries.remove(&oldest);
+            }
+        }
+        self.by_last.insert((now, address));
+        self.entries.insert(address, (now, entry));
+    }
+
+    fn entries(&self) -> impl Iterator<Item = &NeighborEntry> {
+        self.entries.values().map(|(_, entry)| entry)
+    }
+
+    /// Looks up an address & creates an incomplete entry if it is not present.
+    fn resolve(&mut self, address: A, now: u64, config: &NeighborCacheConfig) -> Option<[u8;6]> {
+        if self.get_mut(address, now).is_none() {
+            self.insert(address, NeighborEntry {
+                link_address: None,
+                state: NeighborState::Incomplete,
+                // the first solicitation is sent on the next poll
+                timer: Some(now),
+                solicitations_sent: 0,
+            }, now, config.max_entries);
+        }
+        let entry = self.get_mut(address, now)?;
+        if NeighborState::Stale == entry.state {
+            entry.state = NeighborState::Delay;
+            entry.timer = Some(now.saturating_add(config.delay_first_probe_time));
+        }
+        entry.usable_link_address()
+    }
+
+    /// Processes the expired timers of the entries and collects the
+    /// addresses that have to be solicited (with the MAC address for unicast
+    /// solicitations).
+    fn poll(&mut self, now: u64, config: &NeighborCacheConfig, solicitations: &mut Vec<(A, Option<[u8;6]>)>) {
+        use NeighborState::*;
+        let by_last = &mut self.by_last;
+        self.entries.retain(|address, (last, entry)| {
+            match entry.timer {
+                Some(timer) if timer <= now => {},
+                _ => return true,
+            }
+            let max_solicit = match entry.state {
+                Incomplete => config.max_multicast_solicit,
+                Reachable => {
+                    entry.state = Stale;
+                    entry.timer = None;
+                    return true;
+                },
+                Stale => return true,
+                Delay => {
+                    entry.state = Probe;
+                    entry.solicitations_sent = 0;
+                    config.max_unicast_solicit
+                },
+                Probe => config.max_unicast_solicit,
+            };
+            if entry.solicitations_sent >= max_solicit {
+                by_last.remove(&(*last, *address));
+                return false;
+            }
+            entry.solicitations_sent += 1;
+            entry.timer = Some(now.saturating_add(config.retrans_timer));
+            solicitations.push((*address, entry.usable_link_address()));
+            true
+        });
+    }
+}
+
+/// Appends the IPv6 header & the neighbor discovery message (with calculated
+/// checksum) to the given link headers.
+fn ndp_frame(link_headers: Vec<u8>, source: [u8;16], destination: [u8;16], icmp_type: Icmpv6Type, option: Option<NdpOption>) -> Vec<u8> {
+    // the link-layer address options & the header values are always in
+    // the representable range, so the conversions can not fail
+    let options = option.map(|option| option.to_bytes().unwrap()).unwrap_or_default();
+    let icmp = Icmpv6Header::with_checksum(icmp_type, source, destination, &options).unwrap();
+    // ndp messages are always sent with a hop limit of 255 (rfc4861 7.1)
+    let ip = Ipv6Header {
+        traffic_class: 0,
+        flow_label: 0,
+        payload_length: (icmp.header_len() + options.len()) as u16,
+        next_header: ip_number::IPV6_ICMP,
+        hop_limit: 255,
+        source,
+        destination,
+    };
+
+    let mut frame = link_headers;
+    ip.write(&mut frame).unwrap();
+    icmp.write(&mut frame).unwrap();
+    frame.extend_from_slice(&options);
+    frame
+}
+
+/// Returns the MAC address an IPv6 multicast address is mapped to (rfc2464 7).
+fn ipv6_multicast_link_address(address: [u8;16]) -> [u8;6] {
+    [0x33, 0x33, address[12], address[13], address[14], address[15]]
+}
+
+/// Returns the ethernet II header (& the vlan headers of the request)
+/// for a reply to the given request.
+fn link_headers(source: [u8;6], destination: [u8;6], payload_ether_type: u16, request: &SlicedPacket) -> Vec<u8> {
+    let mut result = Ethernet2Header {
+        source,
+        destination,
+        ether_type: match (&request.link, &request.vlan) {
+            (Some(LinkSlice::Ethernet2(eth)), Some(_)) => eth.ether_type(),
+            _ => payload_ether_type,
+        },
+    }.to_bytes().to_vec();
+    match &request.vlan {
+        Some(VlanSlice::SingleVlan(vlan)) => result.extend_from_slice(vlan.slice()),
+        Some(VlanSlice::DoubleVlan(vlan)) => result.extend_from_slice(vlan.slice()),
+        None => {},
+    }
+    result
+}
@@ -0,0 +1,440 @@
+use super::*;
+
+use std::io;
+
+/// Field types used in NetFlow v9 & IPFIX templates (IPFIX information
+/// element identifiers, the values below 128 are shared with NetFlow v9).
+pub mod flow_field_type {
+    /// Number of bytes (`IN_BYTES`/`octetDeltaCount`).
+    pub const OCTET_DELTA_COUNT: u16 = 1;
+    /// Number of packets (`IN_PKTS`/`packetDeltaCount`).
+    pub const PACKET_DELTA_COUNT: u16 = 2;
+    /// IP protocol number (`PROTOCOL`/`protocolIdentifier`).
+    pub const PROTOCOL_IDENTIFIER: u16 = 4;
+    /// IPv4 type of service or IPv6 traffic class (`SRC_TOS`/`ipClassOfService`).
+    pub const IP_CLASS_OF_SERVICE: u16 = 5;
+    /// TCP flags (`TCP_FLAGS`/`tcpControlBits`).
+    pub const TCP_CONTROL_BITS: u16 = 6;
+    /// Source port (`L4_SRC_PORT`/`sourceTransportPort`).
+    pub const SOURCE_TRANSPORT_PORT: u16 = 7;
+    /// IPv4 source address (`IPV4_SRC_ADDR`/`sourceIPv4Address`).
+    pub const SOURCE_IPV4_ADDRESS: u16 = 8;
+    /// Prefix length of the IPv4 source address (`SRC_MASK`/`sourceIPv4PrefixLength`).
+    pub const SOURCE_IPV4_PREFIX_LENGTH: u16 = 9;
+    /// SNMP index of the input interface (`INPUT_SNMP`/`ingressInterface`).
+    pub const INGRESS_INTERFACE: u16 = 10;
+    /// Destination port (`L4_DST_PORT`/`destinationTransportPort`).
+    pub const DESTINATION_TRANSPORT_PORT: u16 = 11;
+    /// IPv4 destination address (`IPV4_DST_ADDR`/`destinationIPv4Address`).
+    pub const DESTINATION_IPV4_ADDRESS: u16 = 12;
+    /// Prefix length of the IPv4 destination address (`DST_MASK`/`destinationIPv4PrefixLength`).
+    pub const DESTINATION_IPV4_PREFIX_LENGTH: u16 = 13;
+    /// SNMP index of the output interface (`OUTPUT_SNMP`/`egressInterface`).
+    pub const EGRESS_INTERFACE: u16 = 14;
+    /// IPv4 address of the next hop router (`IPV4_NEXT_HOP`/`ipNextHopIPv4Address`).
+    pub const IP_NEXT_HOP_IPV4_ADDRESS: u16 = 15;
+    /// Autonomous system number of the source (`SRC_AS`/`bgpSourceAsNumber`).
+    pub const BGP_SOURCE_AS_NUMBER: u16 = 16;
+    /// Autonomous system number of the destination (`DST_AS`/`bgpDestinationAsNumber`).
+    pub const BGP_DESTINATION_AS_NUMBER: u16 = 17;
+    /// System uptime in milliseconds at the last packet (`LAST_SWITCHED`/`flowEndSysUpTime`).
+    pub const FLOW_END_SYS_UP_TIME: u16 = 21;
+    /// System uptime in milliseconds at the first packet (`FIRST_SWITCHED`/`flowStartSysUpTime`).
+    pub const FLOW_START_SYS_UP_TIME: u16 = 22;
+    /// IPv6 source address (`IPV6_SRC_ADDR`/`sourceIPv6Address`).
+    pub const SOURCE_IPV6_ADDRESS: u16 = 27;
+    /// IPv6 destination address (`IPV6_DST_ADDR`/`destinationIPv6Address`).
+    pub const DESTINATION_IPV6_ADDRESS: u16 = 28;
+    /// Prefix length of the IPv6 source address (`IPV6_SRC_MASK`/`sourceIPv6PrefixLength`).
+    pub const SOURCE_IPV6_PREFIX_LENGTH: u16 = 29;
+    /// Prefix length of the IPv6 destination address (`IPV6_DST_MASK`/`destinationIPv6PrefixLength`).
+    pub const DESTINATION_IPV6_PREFIX_LENGTH: u16 = 30;
+    /// Source MAC address (`IN_SRC_MAC`/`sourceMacAddress`).
+    pub const SOURCE_MAC_ADDRESS: u16 = 56;
+    /// VLAN identifier (`SRC_VLAN`/`vlanId`).
+    pub const VLAN_ID: u16 = 58;
+    /// Destination MAC address (`IN_DST_MAC`/`destinationMacAddress`).
+    pub const DESTINATION_MAC_ADDRESS: u16 = 80;
+    /// Reason why the flow ended (`flowEndReason`, see [`crate::FlowEndReason`]).
+    pub const FLOW_END_REASON: u16 = 136;
+    /// Unix timestamp in seconds of the first packet (`flowStartSeconds`).
+    pub const FLOW_START_SECONDS: u16 = 150;
+    /// Unix timestamp in seconds of the last packet (`flowEndSeconds`).
+    pub const FLOW_END_SECONDS: u16 = 151;
+    /// Unix timestamp in milliseconds of the first packet (`flowStartMilliseconds`).
+    pub const FLOW_START_MILLISECONDS: u16 = 152;
+    /// Unix timestamp in milliseconds of the last packet (`flowEndMilliseconds`).
+    pub const FLOW_END_MILLISECONDS: u16 = 153;
+}
+
+/// Maximum number of flow records in a NetFlow v5 message.
+pub const NETFLOW_V5_MAX_RECORDS: usize = 30;
+
+/// Header of a NetFlow v5 export message.
+///
+/// The version (5) is implied by this type and not stored.
+#[derive(Clone, Debug, Eq, PartialEq, Default)]
+pub struct NetflowV5Header {
+    /// Number of flow records following the header.
+    pub count: u16,
+    /// Milliseconds since the exporting device was booted.
+    pub sys_uptime: u32,
+    /// Seconds since the unix epoch.
+    pub unix_secs: u32,
+    /// Residual nanoseconds since the unix epoch.
+    pub unix_nsecs: u32,
+    /// Number of flow records exported before this message.
+    pub flow_sequence: u32,
+    pub engine_type: u8,
+    pub engine_id: u8,
+    /// Sampling mode (first two bits) & sampling interval (remaining 14 bits).
+    pub sampling_interval: u16,
+}
+
+impl SerializedSize for NetflowV5Header {
+    /// Serialized size of the header in bytes/octets.
+    const SERIALIZED_SIZE: usize = 24;
+}
+
+impl NetflowV5Header {
+    /// Read a NetFlow v5 header from a slice and return the header & unused parts of the slice.
+    ///
+    /// Returns `ReadError::FlowExportUnsupportedVersion` if the version is not 5.
+    pub fn from_slice(slice: &[u8]) -> Result<(NetflowV5Header, &[u8]), ReadError> {
+        use crate::ReadError::*;
+        if slice.len() < NetflowV5Header::SERIALIZED_SIZE {
+            return Err(UnexpectedEndOfSlice(NetflowV5Header::SERIALIZED_SIZE));
+        }
+        let version = read_u16(slice, 0);
+        if 5 != version {
+            return Err(FlowExportUnsupportedVersion(version));
+        }
+        Ok((
+            NetflowV5Header {
+                count: read_u16(slice, 2),
+                sys_uptime: read_u32(slice, 4),
+                unix_secs: read_u32(slice, 8),
+                unix_nsecs: read_u32(slice, 12),
+                flow_sequence: read_u32(slice, 16),
+                engine_type: slice[20],
+                engine_id: slice[21],
+                sampling_interval: read_u16(slice, 22),
+            },
+            &slice[NetflowV5Header::SERIALIZED_SIZE..]
+        ))
+    }
+
+    /// Writes the header to the given writer.
+    pub fn write<T: io::Write + Sized>(&self, writer: &mut T) -> Result<(), io::Error> {
+        writer.write_all(&self.to_bytes())
+    }
+
+    /// Returns the serialized form of the header.
+    pub fn to_bytes(&self) -> [u8;24] {
+        let count = self.count.to_be_bytes();
+        let uptime = self.sys_uptime.to_be_bytes();
+        let secs = self.unix_secs.to_be_bytes();
+        let nsecs = self.unix_nsecs.to_be_bytes();
+        let seq = self.flow_sequence.to_be_bytes();
+        let sampling = self.sampling_interval.to_be_bytes();
+        [
+            0, 5,
+            count[0], count[1],
+            uptime[0], uptime[1], uptime[2], uptime[3],
+            secs[0], secs[1], secs[2], secs[3],
+            nsecs[0], nsecs[1], nsecs[2], nsecs[3],
+            seq[0], seq[1], seq[2], seq[3],
+            self.engine_type, self.engine_id,
+            sampling[0], sampling[1],
+        ]
+    }
+}
+
+/// Flow record of a NetFlow v5 export message.
+#[derive(Clone, Debug, Eq, PartialEq, Default)]
+pub struct NetflowV5Record {
+    pub source: [u8;4],
+    pub destination: [u8;4],
+    /// IPv4 address of the next hop router.
+    pub next_hop: [u8;4],
+    /// SNMP index of the input interface.
+    pub input: u16,
+    /// SNMP index of the output interface.
+    pub output: u16,
+    /// Number of packets.
+    pub packets: u32,
+    /// Number of bytes on the IP layer.
+    pub octets: u32,
+    /// System uptime in milliseconds at the first packet.
+    pub first: u32,
+    /// System uptime in milliseconds at the last packet.
+    pub last: u32,
+    pub source_port: u16,
+    pub destination_port: u16,
+    /// Union of the TCP flags of all packets.
+    pub tcp_flags: u8,
+    /// IP protocol number.
+    pub protocol: u8,
+    /// IP type of service byte.
+    pub tos: u8,
+    /// Autonomous system number of the source.
+    pub source_as: u16,
+    /// Autonomous system number of the destination.
+    pub destination_as: u16,
+    /// Prefix length of the source address.
+    pub source_mask: u8,
+    /// Prefix length of the destination address.
+    pub destination_mask: u8,
+}
+
+impl SerializedSize for NetflowV5Record {
+    /// Serialized size of the record in bytes/octets.
+    const SERIALIZED_SIZE: usize = 48;
+}
+
+impl NetflowV5Record {
+    /// Read a NetFlow v5 record from a slice and return the record & unused parts of the slice.
+    pub fn from_slice(slice: &[u8]) -> Result<(NetflowV5Record, &[u8]), ReadError> {
+        if slice.len() < NetflowV5Record::SERIALIZED_SIZE {
+            return Err(ReadError::UnexpectedEndOfSlice(NetflowV5Record::SERIALIZED_SIZE));
+        }
+        Ok((
+            NetflowV5Record {
+                source: [slice[0], slice[1], slice[2], slice[3]],
+                destination: [slice[4], slice[5], slice[6], slice[7]],
+                next_hop: [slice[8], slice[9], slice[10], slice[11]],
+                input: read_u16(slice, 12),
+                output: read_u16(slice, 14),
+                packets: read_u32(slice, 16),
+                octets: read_u32(slice, 20),
+                first: read_u32(slice, 24),
+                last: read_u32(slice, 28),
+                source_port: read_u16(slice, 32),
+                destination_port: read_u16(slice, 34),
+                tcp_flags: slice[37],
+                protocol: slice[38],
+                tos: slice[39],
+                source_as: read_u16(slice, 40),
+                destination_as: read_u16(slice, 42),
+                source_mask: slice[44],
+                destination_mask: slice[45],
+            },
+            &slice[NetflowV5Record::SERIALIZED_SIZE..]
+        ))
+    }
+
+    /// Writes the record to the given writer.
+    pub fn write<T: io::Write + Sized>(&self, writer: &mut T) -> Result<(), io::Error> {
+        writer.write_all(&self.to_bytes())
+    }
+
+    /// Returns the serialized form of the record.
+    pub fn to_bytes(&self) -> [u8;48] {
+        let src = self.source;
+        let dst = self.destination;
+        let hop = self.next_hop;
+        let input = self.input.to_be_bytes();
+        let output = self.output.to_be_bytes();
+        let packets = self.packets.to_be_bytes();
+        let octets = self.octets.to_be_bytes();
+        let first = self.first.to_be_bytes();
+        let last = self.last.to_be_bytes();
+        let src_port = self.source_port.to_be_bytes();
+        let dst_port = self.destination_port.to_be_bytes();
+        let src_as = self.source_as.to_be_bytes();
+        let dst_as = self.destination_as.to_be_bytes();
+        [
+            src[0], src[1], src[2], src[3],
+            dst[0], dst[1], dst[2], dst[3],
+            hop[0], hop[1], hop[2], hop[3],
+            input[0], input[1],
+            output[0], output[1],
+            packets[0], packets[1], packets[2], packets[3],
+            octets[0], octets[1], octets[2], octets[3],
+            first[0], first[1], first[2], first[3],
+            last[0], last[1], last[2], last[3],
+            src_port[0], src_port[1],
+            dst_port[0], dst_port[1],
+            0, // pad
+            self.tcp_flags,
+            self.protocol,
+            self.tos,
+            src_as[0], src_as[1],
+            dst_as[0], dst_as[1],
+            self.source_mask,
+            self.destination_mask,
+            0, 0, // pad
+        ]
+    }
+}
+
+/// Header of a NetFlow v9 export message (rfc3954).
+///
+/// The version (9) is implied by this type and not stored.
+#[derive(Clone, Debug, Eq, PartialEq, Default)]
+pub struct NetflowV9Header {
+    /// Number of template & data records in the message.
+    pub count: u16,
+    /// Milliseconds since the exporting device was booted.
+    pub sys_uptime: u32,
+    /// Seconds since the unix epoch.
+    pub unix_secs: u32,
+    /// Number of messages sent by the exporter before this message.
+    pub sequence_number: u32,
+    /// Identifies the exporter observation domain.
+    pub source_id: u32,
+}
+
+impl SerializedSize for NetflowV9Header {
+    /// Serialized size of the header in bytes/octets.
+    const SERIALIZED_SIZE: usize = 20;
+}
+
+impl NetflowV9Header {
+    /// Read a NetFlow v9 header from a slice and return the header & unused parts of the slice.
+    ///
+    /// Returns `ReadError::FlowExportUnsupportedVersion` if the version is not 9.
+    pub fn from_slice(slice: &[u8]) -> Result<(NetflowV9Header, &[u8]), ReadError> {
+        use crate::ReadError::*;
+        if slice.len() < NetflowV9Header::SERIALIZED_SIZE {
+            return Err(UnexpectedEndOfSlice(NetflowV9Header::SERIALIZED_SIZE));
+        }
+        let version = read_u16(slice, 0);
+        if 9 != version {
+            return Err(FlowExportUnsupportedVersion(version));
+        }
+        Ok((
+            NetflowV9Header {
+                count: read_u16(slice, 2),
+                sys_uptime: read_u32(slice, 4),
+                unix_secs: read_u32(slice, 8),
+                sequence_number: read_u32(slice, 12),
+                source_id: read_u32(slice, 16),
+            },
+            &slice[NetflowV9Header::SERIALIZED_SIZE..]
+        ))
+    }
+
+    /// Writes the header to the given writer.
+    pub fn write<T: io::Write + Sized>(&self, writer: &mut T) -> Result<(), io::Error> {
+        writer.write_all(&self.to_bytes())
+    }
+
+    /// Returns the serialized form of the header.
+    pub fn to_bytes(&self) -> [u8;20] {
+        let count = self.count.to_be_bytes();
+        let uptime = self.sys_uptime.to_be_bytes();
+        let secs = self.unix_secs.to_be_bytes();
+        let seq = self.sequence_number.to_be_bytes();
+        let source_id = self.source_id.to_be_bytes();
+        [
+            0, 9,
+            count[0], count[1],
+            uptime[0], uptime[1], uptime[2], uptime[3],
+            secs[0], secs[1], secs[2], secs[3],
+            seq[0], seq[1], seq[2], seq[3],
+            source_id[0], source_id[1], source_id[2], source_id[3],
+        ]
+    }
+}
+
+/// Header of an IPFIX message (rfc7011).
+///
+/// The version (10) is implied by this type and not stored.
+#[derive(Clone, Debug, Eq, PartialEq, Default)]
+pub struct IpfixHeader {
+    /// Length of the message in bytes (including the header).
+    pub length: u16,
+    /// Seconds since the unix epoch at which the message was exported.
+    pub export_time: u32,
+    /// Number of data records sent by the exporter before this message.
+    pub sequence_number: u32,
+    pub observation_domain_id: u32,
+}
+
+impl SerializedSize for IpfixHeader {
+    /// Serialized size of the header in bytes/octets.
+    const SERIALIZED_SIZE: usize = 16;
+}
+
+impl IpfixHeader {
+    /// Read an IPFIX header from a slice and return the header & the rest of
+    /// the message (the slice is cut to the length given in the header).
+    ///
+    /// Returns `ReadError::FlowExportUnsupportedVersion` if the version is not 10
+    /// and `ReadError::FlowExportLengthTooSmall` if the length is smaller then the
+    /// header itself.
+    pub fn from_slice(slice: &[u8]) -> Result<(IpfixHeader, &[u8]), ReadError> {
+        use crate::ReadError::*;
+        if slice.len() < IpfixHeader::SERIALIZED_SIZE {
+            return Err(UnexpectedEndOfSlice(IpfixHeader::SERIALIZED_SIZE));
+        }
+        let version = read_u16(slice, 0);
+        if 10 != version {
+            return Err(FlowExportUnsupportedVersion(version));
+        }
+        let length = read_u16(slice, 2);
+        if usize::from(length) < IpfixHeader::SERIALIZED_SIZE {
+            return Err(FlowExportLengthTooSmall(length));
+        }
+        if slice.len() < usize::from(length) {
+            return Err(UnexpectedEndOfSlice(usize::from(length)));
+        }
+        Ok((
+            IpfixHeader {
+                length,
+                export_time: read_u32(slice, 4),
+                sequence_number: read_u32(slice, 8),
+                observation_domain_id: read_u32(slice, 12),
+            },
+            &slice[IpfixHeader::SERIALIZED_SIZE..usize::from(length)]
+        ))
+    }
+
+    /// Writes the header to the given writer.
+    pub fn write<T: io::Write + Sized>(&self, writer: &mut T) -> Result<(), io::Error> {
+        writer.write_all(&self.to_bytes())
+    }
+
+    /// Returns the serialized form of the header.
+    pub fn to_bytes(&self) -> [u8;16] {
+        let length = self.length.to_be_bytes();
+        let time = self.export_time.to_be_bytes();
+        let seq = self.sequence_number.to_be_bytes();
+        let domain = self.observation_domain_id.to_be_bytes();
+        [
+            0, 10,
+            length[0], length[1],
+            time[0], time[1], time[2], time[3],
+            seq[0], seq[1], seq[2], seq[3],
+            domain[0], domain[1], domain[2], domain[3],
+        ]
+    }
+}
+
+/// Field specifier of a NetFlow v9 or IPFIX template.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct FlowTemplateField {
+    /// Field type (see [`flow_field_type`] for known values). For enterprise
+    /// specific IPFIX fields the enterprise bit is not part of the value.
+    pub field_type: u16,
+    /// Length of the field in bytes ([`FLOW_FIELD_VARIABLE_LENGTH`] for
+    /// variable length IPFIX fields).
+    pub length: u16,
+    /// Enterprise number of enterprise specific IPFIX fields.
+    pub enterprise_number: Option<u32>,
+}
+
+/// Length value of a template field indicating that the field has a
+/// variable length (IPFIX only).
+pub const FLOW_FIELD_VARIABLE_LENGTH: u16 = 0xffff;
+
+#[inline]
+pub(crate) fn read_u16(slice: &[u8], offset: usize) -> u16 {
+    u16::from_be_bytes([slice[offset], slice[offset + 1]])
+}
+
+#[inline]
+pub(crate) fn read_u32(slice: &[u8], offset: usize) -> u32 {
+    u32::from_be_bytes([slice[offset], slice[offset + 1], slice[offset + 2], slice[offset + 3]])
+}
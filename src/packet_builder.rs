@@ -68,6 +68,15 @@ use std::{io, marker};
 ///     * [`PacketBuilderStep<IpHeader>::icmpv6_raw`]
 ///     * [`PacketBuilderStep<IpHeader>::icmpv6_echo_request`]
 ///     * [`PacketBuilderStep<IpHeader>::icmpv6_echo_reply`]
+///     * [`PacketBuilderStep<IpHeader>::icmpv6_router_advertisement`]
+///     * [`PacketBuilderStep<IpHeader>::icmpv6_neighbor_solicitation`]
+///     * [`PacketBuilderStep<IpHeader>::icmpv6_neighbor_advertisement`]
+///     * [`PacketBuilderStep<IpHeader>::icmpv6_multicast_listener_query`]
+///     * [`PacketBuilderStep<IpHeader>::icmpv6_multicast_listener_report`]
+///     * [`PacketBuilderStep<IpHeader>::icmpv6_multicast_listener_done`]
+///     * [`PacketBuilderStep<IpHeader>::icmpv6_multicast_listener_query_v2`]
+///     * [`PacketBuilderStep<IpHeader>::icmpv6_multicast_listener_report_v2`]
+///     * [`PacketBuilderStep<IpHeader>::igmp`]
 /// * Options after an TCP header was added:
 ///     * [`PacketBuilderStep<TcpHeader>::write`]
 ///     * [`PacketBuilderStep<TcpHeader>::size`]
@@ -91,6 +100,9 @@ use std::{io, marker};
 /// * Options after an ICMPv6 header was added:
 ///     * [`PacketBuilderStep<Icmpv6Header>::write`]
 ///     * [`PacketBuilderStep<Icmpv6Header>::size`]
+/// * Options after an IGMP header was added:
+///     * [`PacketBuilderStep<IgmpHeader>::write`]
+///     * [`PacketBuilderStep<IgmpHeader>::size`]
 ///
 pub struct PacketBuilder {}
 
@@ -133,7 +145,8 @@ impl PacketBuilder {
                 }),
                 vlan_header: None,
                 ip_header: None,
-                transport_header: None
+                transport_header: None,
+                error: None
             },
             _marker: marker::PhantomData::<Ethernet2Header>{}
         }
@@ -171,7 +184,8 @@ impl PacketBuilder {
                 ethernet2_header: None,
                 vlan_header: None,
                 ip_header: None,
-                transport_header: None
+                transport_header: None,
+                error: None
             },
             _marker: marker::PhantomData::<Ethernet2Header>{}
         }.ipv4(source, destination, time_to_live)
@@ -213,7 +227,8 @@ impl PacketBuilder {
                 ethernet2_header: None,
                 vlan_header: None,
                 ip_header: None,
-                transport_header: None
+                transport_header: None,
+                error: None
             },
             _marker: marker::PhantomData::<Ethernet2Header>{}
         }.ipv6(source, destination, hop_limit)
@@ -289,7 +304,8 @@ impl PacketBuilder {
                 ethernet2_header: None,
                 vlan_header: None,
                 ip_header: None,
-                transport_header: None
+                transport_header: None,
+                error: None
             },
             _marker: marker::PhantomData::<Ethernet2Header>{}
         }.ip(ip_header)
@@ -300,7 +316,9 @@ struct PacketImpl {
     ethernet2_header: Option<Ethernet2Header>,
     ip_header: Option<IpHeader>,
     vlan_header: Option<VlanHeader>,
-    transport_header: Option<TransportHeader>
+    transport_header: Option<TransportHeader>,
+    /// Error that occurred while building the packet (returned by `write`).
+    error: Option<ValueError>,
 }
 
 ///An unfinished packet that is build with the packet builder
@@ -1119,6 +1137,421 @@ impl PacketBuilderStep<IpHeader> {
         }
     }
 
+    /// Adds an ICMPv6 neighbor discovery "Router Advertisement" message (rfc4861).
+    ///
+    /// The neighbor discovery options (e.g. prefix informations) are passed as
+    /// payload and can be serialized via [`NdpOption::write`].
+    ///
+    /// # Example
+    ///
+    /// Basic usage: 
+    ///
+    /// ```
+    /// # use etherparse::{PacketBuilder, NdpOption, NdpPrefixInformation, icmpv6::RouterAdvertisementHeader};
+    /// #
+    /// let builder = PacketBuilder::
+    ///     ipv6(
+    ///         //source (link local address of the router)
+    ///         [0xfe,0x80,0,0,0,0,0,0,0,0,0,0,0,0,0,1],
+    ///         //destination (all nodes multicast address)
+    ///         [0xff,0x02,0,0,0,0,0,0,0,0,0,0,0,0,0,1],
+    ///         //hop_limit (required to be 255 for neighbor discovery)
+    ///         255)
+    ///    .icmpv6_router_advertisement(
+    ///         RouterAdvertisementHeader{
+    ///             cur_hop_limit: 64,
+    ///             managed_address_config: false,
+    ///             other_config: false,
+    ///             router_lifetime: 1800,
+    ///             reachable_time: 0,
+    ///             retrans_timer: 0,
+    ///         }
+    ///     );
+    ///
+    /// // options of the router advertisement
+    /// let mut payload = Vec::new();
+    /// NdpOption::SourceLinkLayerAddress(&[1,2,3,4,5,6]).write(&mut payload).unwrap();
+    /// NdpOption::Mtu(1500).write(&mut payload).unwrap();
+    /// NdpOption::PrefixInformation(NdpPrefixInformation{
+    ///     prefix_length: 64,
+    ///     on_link: true,
+    ///     autonomous: true,
+    ///     valid_lifetime: 2592000,
+    ///     preferred_lifetime: 604800,
+    ///     prefix: [0x20,0x01,0x0d,0xb8,0,0,0,0,0,0,0,0,0,0,0,0],
+    /// }).write(&mut payload).unwrap();
+    ///
+    /// //get some memory to store the result
+    /// let mut result = Vec::<u8>::with_capacity(
+    ///                     builder.size(payload.len()));
+    ///
+    /// //serialize
+    /// builder.write(&mut result, &payload).unwrap();
+    /// ```
+    pub fn icmpv6_router_advertisement(self, header: icmpv6::RouterAdvertisementHeader) -> PacketBuilderStep<Icmpv6Header> {
+        self.icmpv6(Icmpv6Type::RouterAdvertisement(header))
+    }
+
+    /// Adds an ICMPv6 neighbor discovery "Neighbor Solicitation" message (rfc4861).
+    ///
+    /// The neighbor discovery options (e.g. the source link-layer address) are
+    /// passed as payload and can be serialized via [`NdpOption::write`].
+    ///
+    /// # Example
+    ///
+    /// Basic usage: 
+    ///
+    /// ```
+    /// # use etherparse::{PacketBuilder, NdpOption};
+    /// #
+    /// let target = [0xfe,0x80,0,0,0,0,0,0,0,0,0,0,0,0x12,0x34,0x56];
+    /// let builder = PacketBuilder::
+    ///     ipv6(
+    ///         //source
+    ///         [0xfe,0x80,0,0,0,0,0,0,0,0,0,0,0,0,0,1],
+    ///         //destination (solicited node multicast address of the target)
+    ///         [0xff,0x02,0,0,0,0,0,0,0,0,0,1,0xff,0x12,0x34,0x56],
+    ///         //hop_limit (required to be 255 for neighbor discovery)
+    ///         255)
+    ///    .icmpv6_neighbor_solicitation(target);
+    ///
+    /// // options of the neighbor solicitation
+    /// let mut payload = Vec::new();
+    /// NdpOption::SourceLinkLayerAddress(&[1,2,3,4,5,6]).write(&mut payload).unwrap();
+    ///
+    /// //get some memory to store the result
+    /// let mut result = Vec::<u8>::with_capacity(
+    ///                     builder.size(payload.len()));
+    ///
+    /// //serialize
+    /// builder.write(&mut result, &payload).unwrap();
+    /// ```
+    pub fn icmpv6_neighbor_solicitation(self, target_address: [u8;16]) -> PacketBuilderStep<Icmpv6Header> {
+        self.icmpv6(Icmpv6Type::NeighborSolicitation(
+            icmpv6::NeighborSolicitationHeader{ target_address }
+        ))
+    }
+
+    /// Adds an ICMPv6 neighbor discovery "Neighbor Advertisement" message (rfc4861).
+    ///
+    /// The neighbor discovery options (e.g. the target link-layer address) are
+    /// passed as payload and can be serialized via [`NdpOption::write`].
+    ///
+    /// # Example
+    ///
+    /// Basic usage: 
+    ///
+    /// ```
+    /// # use etherparse::{PacketBuilder, NdpOption, icmpv6::NeighborAdvertisementHeader};
+    /// #
+    /// let builder = PacketBuilder::
+    ///     ipv6(
+    ///         //source
+    ///         [0xfe,0x80,0,0,0,0,0,0,0,0,0,0,0,0x12,0x34,0x56],
+    ///         //destination
+    ///         [0xfe,0x80,0,0,0,0,0,0,0,0,0,0,0,0,0,1],
+    ///         //hop_limit (required to be 255 for neighbor discovery)
+    ///         255)
+    ///    .icmpv6_neighbor_advertisement(
+    ///         NeighborAdvertisementHeader{
+    ///             router: false,
+    ///             solicited: true,
+    ///             override_flag: true,
+    ///             target_address: [0xfe,0x80,0,0,0,0,0,0,0,0,0,0,0,0x12,0x34,0x56],
+    ///         }
+    ///     );
+    ///
+    /// // options of the neighbor advertisement
+    /// let mut payload = Vec::new();
+    /// NdpOption::TargetLinkLayerAddress(&[1,2,3,4,5,6]).write(&mut payload).unwrap();
+    ///
+    /// //get some memory to store the result
+    /// let mut result = Vec::<u8>::with_capacity(
+    ///                     builder.size(payload.len()));
+    ///
+    /// //serialize
+    /// builder.write(&mut result, &payload).unwrap();
+    /// ```
+    pub fn icmpv6_neighbor_advertisement(self, header: icmpv6::NeighborAdvertisementHeader) -> PacketBuilderStep<Icmpv6Header> {
+        self.icmpv6(Icmpv6Type::NeighborAdvertisement(header))
+    }
+
+    /// Adds a MLDv1 "Multicast Listener Query" message (rfc2710) and a
+    /// "Router Alert" option to the hop by hop header of the IPv6 header.
+    /// `write` returns an error if the option can not be added to the
+    /// hop by hop header (e.g. because it has reached its maximum size).
+    ///
+    /// Note that MLD messages must be sent with a link-local source address
+    /// and a hop limit of 1.
+    ///
+    /// # Example
+    ///
+    /// Basic usage: 
+    ///
+    /// ```
+    /// # use etherparse::PacketBuilder;
+    /// #
+    /// let builder = PacketBuilder::
+    ///     ipv6(
+    ///         //source
+    ///         [0xfe,0x80,0,0,0,0,0,0,0,0,0,0,0,0,0,1],
+    ///         //destination (all nodes multicast address)
+    ///         [0xff,0x02,0,0,0,0,0,0,0,0,0,0,0,0,0,1],
+    ///         //hop_limit
+    ///         1)
+    ///    .icmpv6_multicast_listener_query(
+    ///         10000, // maximum response delay in milliseconds
+    ///         [0;16], // multicast address (zero for a general query)
+    ///     );
+    ///
+    /// //get some memory to store the result
+    /// let mut result = Vec::<u8>::with_capacity(builder.size(0));
+    ///
+    /// //serialize
+    /// builder.write(&mut result, &[]).unwrap();
+    /// ```
+    pub fn icmpv6_multicast_listener_query(self, maximum_response_delay: u16, multicast_address: [u8;16]) -> PacketBuilderStep<Icmpv6Header> {
+        self.icmpv6_mld(Icmpv6Type::MulticastListenerQuery(
+            icmpv6::MulticastListenerHeader{ maximum_response_delay, multicast_address }
+        ))
+    }
+
+    /// Adds a MLDv1 "Multicast Listener Report" message (rfc2710) and a
+    /// "Router Alert" option to the hop by hop header of the IPv6 header.
+    /// `write` returns an error if the option can not be added to the
+    /// hop by hop header (e.g. because it has reached its maximum size).
+    ///
+    /// Note that MLD messages must be sent with a link-local source address
+    /// and a hop limit of 1.
+    ///
+    /// # Example
+    ///
+    /// Basic usage: 
+    ///
+    /// ```
+    /// # use etherparse::PacketBuilder;
+    /// #
+    /// let group = [0xff,0x02,0,0,0,0,0,0,0,0,0,0,0,0,0,0xfb];
+    /// let builder = PacketBuilder::
+    ///     ipv6(
+    ///         //source
+    ///         [0xfe,0x80,0,0,0,0,0,0,0,0,0,0,0,0,0,1],
+    ///         //destination (the reported multicast address)
+    ///         group,
+    ///         //hop_limit
+    ///         1)
+    ///    .icmpv6_multicast_listener_report(group);
+    ///
+    /// //get some memory to store the result
+    /// let mut result = Vec::<u8>::with_capacity(builder.size(0));
+    ///
+    /// //serialize
+    /// builder.write(&mut result, &[]).unwrap();
+    /// ```
+    pub fn icmpv6_multicast_listener_report(self, multicast_address: [u8;16]) -> PacketBuilderStep<Icmpv6Header> {
+        self.icmpv6_mld(Icmpv6Type::MulticastListenerReport(
+            icmpv6::MulticastListenerHeader{ maximum_response_delay: 0, multicast_address }
+        ))
+    }
+
+    /// Adds a MLDv1 "Multicast Listener Done" message (rfc2710) and a
+    /// "Router Alert" option to the hop by hop header of the IPv6 header.
+    /// `write` returns an error if the option can not be added to the
+    /// hop by hop header (e.g. because it has reached its maximum size).
+    ///
+    /// Note that MLD messages must be sent with a link-local source address
+    /// and a hop limit of 1.
+    ///
+    /// # Example
+    ///
+    /// Basic usage: 
+    ///
+    /// ```
+    /// # use etherparse::PacketBuilder;
+    /// #
+    /// let builder = PacketBuilder::
+    ///     ipv6(
+    ///         //source
+    ///         [0xfe,0x80,0,0,0,0,0,0,0,0,0,0,0,0,0,1],
+    ///         //destination (all routers multicast address)
+    ///         [0xff,0x02,0,0,0,0,0,0,0,0,0,0,0,0,0,2],
+    ///         //hop_limit
+    ///         1)
+    ///    .icmpv6_multicast_listener_done([0xff,0x02,0,0,0,0,0,0,0,0,0,0,0,0,0,0xfb]);
+    ///
+    /// //get some memory to store the result
+    /// let mut result = Vec::<u8>::with_capacity(builder.size(0));
+    ///
+    /// //serialize
+    /// builder.write(&mut result, &[]).unwrap();
+    /// ```
+    pub fn icmpv6_multicast_listener_done(self, multicast_address: [u8;16]) -> PacketBuilderStep<Icmpv6Header> {
+        self.icmpv6_mld(Icmpv6Type::MulticastListenerDone(
+            icmpv6::MulticastListenerHeader{ maximum_response_delay: 0, multicast_address }
+        ))
+    }
+
+    /// Adds a MLDv2 "Multicast Listener Query" message (rfc3810) and a
+    /// "Router Alert" option to the hop by hop header of the IPv6 header.
+    /// `write` returns an error if the option can not be added to the
+    /// hop by hop header (e.g. because it has reached its maximum size).
+    ///
+    /// The source addresses are passed as payload (the `number_of_sources`
+    /// field in the header has to match the number of addresses).
+    ///
+    /// # Example
+    ///
+    /// Basic usage: 
+    ///
+    /// ```
+    /// # use etherparse::{PacketBuilder, icmpv6::MulticastListenerQueryV2Header};
+    /// #
+    /// let source = [0x20,0x01,0x0d,0xb8,0,0,0,0,0,0,0,0,0,0,0,1];
+    /// let builder = PacketBuilder::
+    ///     ipv6(
+    ///         //source
+    ///         [0xfe,0x80,0,0,0,0,0,0,0,0,0,0,0,0,0,1],
+    ///         //destination (the queried multicast address)
+    ///         [0xff,0x3e,0,0,0,0,0,0,0,0,0,0,0,0,0,1],
+    ///         //hop_limit
+    ///         1)
+    ///    .icmpv6_multicast_listener_query_v2(
+    ///         MulticastListenerQueryV2Header{
+    ///             maximum_response_code: 10000,
+    ///             multicast_address: [0xff,0x3e,0,0,0,0,0,0,0,0,0,0,0,0,0,1],
+    ///             suppress_router_side_processing: false,
+    ///             querier_robustness_variable: 2,
+    ///             querier_query_interval_code: 125,
+    ///             number_of_sources: 1,
+    ///         }
+    ///     );
+    ///
+    /// //get some memory to store the result
+    /// let mut result = Vec::<u8>::with_capacity(builder.size(source.len()));
+    ///
+    /// //serialize
+    /// builder.write(&mut result, &source).unwrap();
+    /// ```
+    pub fn icmpv6_multicast_listener_query_v2(self, header: icmpv6::MulticastListenerQueryV2Header) -> PacketBuilderStep<Icmpv6Header> {
+        self.icmpv6_mld(Icmpv6Type::MulticastListenerQueryV2(header))
+    }
+
+    /// Adds a MLDv2 "Multicast Listener Report" message (rfc3810) and a
+    /// "Router Alert" option to the hop by hop header of the IPv6 header.
+    /// `write` returns an error if the option can not be added to the
+    /// hop by hop header (e.g. because it has reached its maximum size).
+    ///
+    /// The multicast address records are passed as payload and can be
+    /// serialized via [`MulticastAddressRecord::write`].
+    ///
+    /// # Example
+    ///
+    /// Basic usage: 
+    ///
+    /// ```
+    /// # use etherparse::{PacketBuilder, MulticastAddressRecord, mld_record_type};
+    /// #
+    /// let builder = PacketBuilder::
+    ///     ipv6(
+    ///         //source
+    ///         [0xfe,0x80,0,0,0,0,0,0,0,0,0,0,0,0,0,1],
+    ///         //destination (all MLDv2-capable routers)
+    ///         [0xff,0x02,0,0,0,0,0,0,0,0,0,0,0,0,0,0x16],
+    ///         //hop_limit
+    ///         1)
+    ///    .icmpv6_multicast_listener_report_v2(1);
+    ///
+    /// // records of the report
+    /// let mut payload = Vec::new();
+    /// MulticastAddressRecord{
+    ///     record_type: mld_record_type::CHANGE_TO_EXCLUDE_MODE,
+    ///     multicast_address: [0xff,0x02,0,0,0,0,0,0,0,0,0,0,0,0,0,0xfb],
+    ///     sources: &[],
+    ///     aux_data: &[],
+    /// }.write(&mut payload).unwrap();
+    ///
+    /// //get some memory to store the result
+    /// let mut result = Vec::<u8>::with_capacity(builder.size(payload.len()));
+    ///
+    /// //serialize
+    /// builder.write(&mut result, &payload).unwrap();
+    /// ```
+    pub fn icmpv6_multicast_listener_report_v2(self, number_of_records: u16) -> PacketBuilderStep<Icmpv6Header> {
+        self.icmpv6_mld(Icmpv6Type::MulticastListenerReportV2{ number_of_records })
+    }
+
+    /// Adds the given MLD message & a "Router Alert" option to the hop by hop
+    /// header (required by rfc2710 & rfc3810).
+    fn icmpv6_mld(mut self, icmp_type: Icmpv6Type) -> PacketBuilderStep<Icmpv6Header> {
+        if let Some(IpHeader::Version6(_, ref mut ext)) = self.state.ip_header {
+            // only fails if the hop by hop header is already at its maximum
+            // size, the error is returned when the packet gets written
+            if let Err(err) = ext.set_router_alert(ipv6_option::ROUTER_ALERT_MLD) {
+                self.state.error = Some(err);
+            }
+        }
+        self.icmpv6(icmp_type)
+    }
+
+    /// Adds an IGMP header of the given [`IgmpType`] to the packet.
+    ///
+    /// If the IP header is an IPv4 header a "Router Alert" option is
+    /// added to its options (required by rfc2236 & rfc3376). `write`
+    /// returns an error if the options have no space left for the option.
+    /// Note that IGMP messages are usually sent with a time to live of 1.
+    ///
+    /// The payload contains the parts of the message following the header
+    /// (the source addresses of IGMPv3 queries or the group records of
+    /// IGMPv3 reports).
+    ///
+    /// # Example
+    ///
+    /// Basic usage: 
+    ///
+    /// ```
+    /// # use etherparse::{PacketBuilder, IgmpType, IgmpGroupRecord, igmp_record_type};
+    /// #
+    /// let builder = PacketBuilder::
+    ///    ipv4([192,168,1,1],  //source ip
+    ///          [224,0,0,22],  //desitionation ip (all IGMPv3 capable routers)
+    ///          1)             //time to life
+    ///    .igmp(IgmpType::MembershipReportV3{ number_of_records: 1 });
+    ///
+    /// // the group records are part of the payload
+    /// let mut payload = Vec::new();
+    /// IgmpGroupRecord{
+    ///     record_type: igmp_record_type::CHANGE_TO_EXCLUDE_MODE,
+    ///     multicast_address: [239,1,2,3],
+    ///     sources: &[],
+    ///     aux_data: &[],
+    /// }.write(&mut payload).unwrap();
+    ///
+    /// //get some memory to store the result
+    /// let mut result = Vec::<u8>::with_capacity(
+    ///                     builder.size(payload.len()));
+    ///
+    /// //serialize
+    /// builder.write(&mut result, &payload).unwrap();
+    /// ```
+    pub fn igmp(mut self, igmp_type: IgmpType) -> PacketBuilderStep<IgmpHeader> {
+        if let Some(IpHeader::Version4(ref mut ip, _)) = self.state.ip_header {
+            // only fails if the options have no space left, the error
+            // is returned when the packet gets written
+            if let Err(err) = ip.set_router_alert(0) {
+                self.state.error = Some(err);
+            }
+        }
+        self.state.transport_header = Some(TransportHeader::Igmp(IgmpHeader{
+            igmp_type,
+            checksum: 0, // calculated later
+        }));
+        //return for next step
+        PacketBuilderStep {
+            state: self.state,
+            _marker: marker::PhantomData::<IgmpHeader>{}
+        }
+    }
+
     /// Adds an UDP header.
     ///
     /// # Example
@@ -1243,6 +1676,18 @@ impl PacketBuilderStep<Icmpv6Header> {
     }
 }
 
+impl PacketBuilderStep<IgmpHeader> {
+    ///Write all the headers and the payload.
+    pub fn write<T: io::Write + Sized>(self, writer: &mut T, payload: &[u8]) -> Result<(),WriteError> {
+        final_write(self, writer, payload)
+    }
+
+    ///Returns the size of the packet when it is serialized
+    pub fn size(&self, payload_size: usize) -> usize {
+        final_size(self, payload_size)
+    }
+}
+
 impl PacketBuilderStep<UdpHeader> {
     ///Write all the headers and the payload.
     pub fn write<T: io::Write + Sized>(self, writer: &mut T, payload: &[u8]) -> Result<(),WriteError> {
@@ -1342,7 +1787,12 @@ impl PacketBuilderStep<TcpHeader> {
 
 ///Write all the headers and the payload.
 fn final_write<T: io::Write + Sized, B>(builder: PacketBuilderStep<B>, writer: &mut T, payload: &[u8]) -> Result<(),WriteError> {
-    
+
+    //errors that occurred while building the packet
+    if let Some(err) = builder.state.error {
+        return Err(WriteError::ValueError(err));
+    }
+
     let ip_ether_type = {
         use crate::IpHeader::*;
         match builder.state.ip_header {
@@ -1405,8 +1855,8 @@ fn final_write<T: io::Write + Sized, B>(builder: PacketBuilderStep<B>, writer: &
                     ip.write(writer)?;
                     ext.write(writer, ip.protocol)?;
                 },
-                Version6(mut ip, ext) => {
-                    ip.set_payload_length(ext.header_len() + payload.len())?;
+                Version6(mut ip, mut ext) => {
+                    ip.set_payload_length_with_jumbo(&mut ext, payload.len())?;
                     ip.write(writer)?;
                     ext.write(writer, ip.next_header)?;
                 }
@@ -1422,6 +1872,7 @@ fn final_write<T: io::Write + Sized, B>(builder: PacketBuilderStep<B>, writer: &
                     match transport {
                         Icmpv4(_) => {},
                         Icmpv6(_) => {},
+                        Igmp(_) => {},
                         Udp(ref mut udp) => { udp.length = transport_size as u16; }
                         Tcp(_) => {},
                     }
@@ -1431,6 +1882,7 @@ fn final_write<T: io::Write + Sized, B>(builder: PacketBuilderStep<B>, writer: &
                         match transport {
                             Icmpv4(_) => ip_number::ICMP,
                             Icmpv6(_) => ip_number::IPV6_ICMP,
+                            Igmp(_) => ip_number::IGMP,
                             Udp(_) => ip_number::UDP,
                             Tcp(_) => ip_number::TCP
                         }
@@ -1446,12 +1898,20 @@ fn final_write<T: io::Write + Sized, B>(builder: PacketBuilderStep<B>, writer: &
                 Version6(mut ip, mut ext) => {
                     //set total length
                     let transport_size = transport.header_len() + payload.len();
-                    ip.set_payload_length(ext.header_len() + transport_size)?;
+                    ip.set_payload_length_with_jumbo(&mut ext, transport_size)?;
                     use crate::TransportHeader::*;
                     match transport {
                         Icmpv4(_) => {},
                         Icmpv6(_) => {},
-                        Udp(ref mut udp) => { udp.length = transport_size as u16; }
+                        Igmp(_) => {},
+                        Udp(ref mut udp) => {
+                            // the udp length is set to zero in jumbograms (rfc2675)
+                            udp.length = if transport_size > usize::from(u16::MAX) {
+                                0
+                            } else {
+                                transport_size as u16
+                            };
+                        }
                         Tcp(_) => {}
                     }
         
@@ -1460,13 +1920,19 @@ fn final_write<T: io::Write + Sized, B>(builder: PacketBuilderStep<B>, writer: &
                         match transport {
                             Icmpv4(_) => ip_number::ICMP,
                             Icmpv6(_) => ip_number::IPV6_ICMP,
+                            Igmp(_) => ip_number::IGMP,
                             Udp(_) => ip_number::UDP,
                             Tcp(_) => ip_number::TCP
                         }
                     );
         
-                    //calculate the udp checksum
-                    transport.update_checksum_ipv6(&ip, payload)?;
+                    //calculate the udp checksum (the pseudo header contains
+                    //the final destination in case a routing header is present)
+                    {
+                        let mut pseudo_ip = ip.clone();
+                        pseudo_ip.destination = ext.effective_destination(ip.destination);
+                        transport.update_checksum_ipv6(&pseudo_ip, payload)?;
+                    }
         
                     //write (will automatically calculate the checksum)
                     ip.write(writer)?;
@@ -1496,11 +1962,24 @@ fn final_size<B>(builder: &PacketBuilderStep<B>, payload_size: usize) -> usize {
         None => 0 
     } + match builder.state.ip_header {
         Some(Version4(ref value, ref ext)) => value.header_len() + ext.header_len(),
-        Some(Version6(_, ref ext)) => Ipv6Header::SERIALIZED_SIZE + ext.header_len(),
+        Some(Version6(ref value, ref ext)) => {
+            // jumbograms need an additional hop by hop option
+            let transport_size = match builder.state.transport_header {
+                Some(ref transport) => transport.header_len(),
+                None => 0
+            } + payload_size;
+            let mut jumbo_ip = value.clone();
+            let mut jumbo_ext = ext.clone();
+            match jumbo_ip.set_payload_length_with_jumbo(&mut jumbo_ext, transport_size) {
+                Ok(()) => Ipv6Header::SERIALIZED_SIZE + jumbo_ext.header_len(),
+                Err(_) => Ipv6Header::SERIALIZED_SIZE + ext.header_len(),
+            }
+        },
         None => 0
     } + match builder.state.transport_header {
         Some(Icmpv4(ref value)) => value.header_len(),
         Some(Icmpv6(ref value)) => value.header_len(),
+        Some(Igmp(ref value)) => value.header_len(),
         Some(Udp(_)) => UdpHeader::SERIALIZED_SIZE,
         Some(Tcp(ref value)) => value.header_len() as usize,
         None => 0
@@ -1520,7 +1999,8 @@ mod whitebox_tests {
                 ethernet2_header: None,
                 ip_header: None,
                 vlan_header: None,
-                transport_header: None
+                transport_header: None,
+                error: None
             },
             _marker: marker::PhantomData::<UdpHeader>{}
         }.size(0));
@@ -1536,7 +2016,8 @@ mod whitebox_tests {
                     ethernet2_header: None,
                     ip_header: None,
                     vlan_header: None,
-                    transport_header: None
+                    transport_header: None,
+                    error: None
                 },
                 _marker: marker::PhantomData::<UdpHeader>{}
             },
@@ -130,7 +130,7 @@ impl<'a> PacketHeaders<'a> {
                 // is not fragmented
                 if false == fragmented {
                     //parse the transport layer
-                    let (transport, transport_rest) = read_transport(ip_protocol, rest)?;
+                    let (transport, transport_rest) = read_ipv4_transport(ip_protocol, rest)?;
 
                     //assign to the output
                     rest = transport_rest;
@@ -279,7 +279,7 @@ impl<'a> PacketHeaders<'a> {
                 // is not fragmented
                 if false == fragmented {
                     //parse the transport layer
-                    let (transport, transport_rest) = read_transport(ip_protocol, rest)?;
+                    let (transport, transport_rest) = read_ipv4_transport(ip_protocol, rest)?;
 
                     //assign to the output
                     rest = transport_rest;
@@ -364,15 +364,16 @@ impl<'a> PacketHeaders<'a> {
             payload: &[],
         };
 
-        let (transport_proto, rest) = {
-            let (ip, transport_proto, rest) = IpHeader::from_slice(packet)?;
-            // update output
-            result.ip = Some(ip);
-            (transport_proto, rest)
-        };
+        let (ip, transport_proto, rest) = IpHeader::from_slice(packet)?;
 
         // try to parse the transport header
-        let (transport, rest) = read_transport(transport_proto, rest)?;
+        let (transport, rest) = match &ip {
+            IpHeader::Version4(_, _) => read_ipv4_transport(transport_proto, rest)?,
+            IpHeader::Version6(_, _) => read_transport(transport_proto, rest)?,
+        };
+
+        // update output
+        result.ip = Some(ip);
 
         // update output
         result.transport = transport;
@@ -415,6 +416,21 @@ impl<'a> PacketHeaders<'a> {
     }
 }
 
+/// helper function to process transport headers following an ipv4 header
+/// (including IGMP, which is only defined for ipv4)
+fn read_ipv4_transport(
+    protocol: u8,
+    rest: &[u8],
+) -> Result<(Option<TransportHeader>, &[u8]), ReadError> {
+    match protocol {
+        ip_number::IGMP => {
+            Ok(IgmpHeader::from_slice(rest)?)
+            .map( |value| (Some(TransportHeader::Igmp(value.0)), value.1))
+        },
+        _ => read_transport(protocol, rest),
+    }
+}
+
 /// helper function to process transport headers
 fn read_transport(
     protocol: u8,
@@ -24,6 +24,8 @@ pub enum TransportSlice<'a> {
     Icmpv4(Icmpv4Slice<'a>),
     /// A slice containing an Icmp6 header
     Icmpv6(Icmpv6Slice<'a>),
+    /// A slice containing an IGMP header
+    Igmp(IgmpSlice<'a>),
     /// A slice containing an UDP header.
     Udp(UdpHeaderSlice<'a>),
     /// A slice containing a TCP header.
@@ -92,6 +94,9 @@ pub struct SlicedPacket<'a> {
     /// For example if transport field contains Some(Udp(_)) then the payload field points to the udp payload.
     /// On the other hand if the transport field contains None then the payload contains the payload of
     /// next field containing a Some value (in order of transport, ip, vlan, link).
+    ///
+    /// Data following the ip payload (e.g. ethernet padding) is part of the payload.
+    /// Use [`SlicedPacket::limit_to_ip_payload`] to remove it.
     pub payload: &'a [u8]
 }
 
@@ -246,6 +251,64 @@ impl<'a> SlicedPacket<'a> {
         CursorSlice::new(data).slice_ip()
     }
 
+    /// Seperates a network packet slice starting with an ethernet II header into
+    /// different slices and decodes ESP packets assuming NULL encryption (rfc2410).
+    ///
+    /// Works like [`SlicedPacket::from_ethernet`], but if an IPsec ESP header is
+    /// encountered, the ESP trailer (padding, pad length, next header & integrity
+    /// check value) is split of the end of the ip payload and the slicing continues
+    /// with the cleartext payload as transport layer. The ESP header & trailer are
+    /// accessible via the ip extensions.
+    ///
+    /// `icv_len` is the length of the integrity check value in bytes configured in
+    /// the security association (e.g. 12 for HMAC-SHA1-96).
+    ///
+    /// # Example
+    ///
+    ///```
+    /// # use etherparse::*;
+    /// # let builder = PacketBuilder::
+    /// #    ethernet2([1,2,3,4,5,6],     //source mac
+    /// #               [7,8,9,10,11,12]) //destionation mac
+    /// #    .ipv4([192,168,1,1], //source ip
+    /// #          [192,168,1,2], //desitionation ip
+    /// #          20)            //time to life
+    /// #    .udp(21,    //source port
+    /// #         1234); //desitnation port
+    /// # let mut udp_packet = Vec::<u8>::with_capacity(builder.size(4));
+    /// # builder.write(&mut udp_packet, &[1,2,3,4]).unwrap();
+    /// # // wrap the udp packet in a NULL encrypted ESP packet
+    /// # let mut packet = Vec::new();
+    /// # packet.extend_from_slice(&udp_packet[..14 + 20]);
+    /// # packet[14 + 9] = ip_number::ENCAP_SEC;
+    /// # packet.extend_from_slice(&EspHeader{ spi: 1, sequence_number: 2 }.to_bytes());
+    /// # packet.extend_from_slice(&udp_packet[14 + 20..]);
+    /// # packet.extend_from_slice(&[1, 2, 2, ip_number::UDP]); // padding & trailer
+    /// # packet.extend_from_slice(&[0;12]); // icv
+    /// # let total_len = (packet.len() - 14) as u16;
+    /// # packet[14 + 2..14 + 4].copy_from_slice(&total_len.to_be_bytes());
+    /// let sliced = SlicedPacket::from_ethernet_esp_null(&packet, 12).unwrap();
+    /// if let Some(InternetSlice::Ipv4(_, ext)) = &sliced.ip {
+    ///     println!("spi: {}", ext.esp.as_ref().unwrap().spi());
+    /// }
+    /// assert!(matches!(sliced.transport, Some(TransportSlice::Udp(_))));
+    /// ```
+    pub fn from_ethernet_esp_null(data: &'a [u8], icv_len: usize) -> Result<SlicedPacket<'a>, ReadError> {
+        let mut cursor = CursorSlice::new(data);
+        cursor.esp_null_icv_len = Some(icv_len);
+        cursor.slice_ethernet2()
+    }
+
+    /// Seperates a network packet slice starting with an IPv4 or IPv6 header into
+    /// different slices and decodes ESP packets assuming NULL encryption (rfc2410).
+    ///
+    /// See [`SlicedPacket::from_ethernet_esp_null`] for more details.
+    pub fn from_ip_esp_null(data: &'a [u8], icv_len: usize) -> Result<SlicedPacket<'a>, ReadError> {
+        let mut cursor = CursorSlice::new(data);
+        cursor.esp_null_icv_len = Some(icv_len);
+        cursor.slice_ip()
+    }
+
     /// If the slice in the `payload` field contains an ethernet payload
     /// this method returns the ether type number describing the payload type.
     ///
@@ -283,13 +346,61 @@ impl<'a> SlicedPacket<'a> {
             }
         }
     }
+
+    /// Cuts the given part of the packet (e.g. `payload`) at the end of the ip
+    /// payload as defined by the length fields of the ip header.
+    ///
+    /// This removes data following the ip payload (e.g. the padding of
+    /// ethernet frames smaller than the minimum frame size). If no ip header
+    /// is present or the ip header does not define the payload length
+    /// (IPv6 jumbograms) the slice is returned unchanged.
+    ///
+    /// The given slice has to be a part of the sliced packet (e.g. the
+    /// `payload` field or the slice of a transport header).
+    ///
+    /// ```
+    /// use etherparse::{PacketBuilder, SlicedPacket};
+    ///
+    /// let builder = PacketBuilder::
+    ///     ethernet2([1,2,3,4,5,6], [7,8,9,10,11,12])
+    ///     .ipv4([192,168,1,1], [192,168,1,2], 20)
+    ///     .udp(21, 1234);
+    /// let mut packet = Vec::<u8>::with_capacity(60);
+    /// builder.write(&mut packet, &[1,2,3,4]).unwrap();
+    /// // ethernet padding up to the minimum frame size
+    /// packet.resize(60, 0);
+    ///
+    /// let sliced = SlicedPacket::from_ethernet(&packet).unwrap();
+    /// assert_eq!(&[1,2,3,4], sliced.limit_to_ip_payload(sliced.payload));
+    /// ```
+    pub fn limit_to_ip_payload(&self, slice: &'a [u8]) -> &'a [u8] {
+        use InternetSlice::*;
+        let end = match &self.ip {
+            Some(Ipv4(header, _)) => {
+                header.slice().as_ptr() as usize + usize::from(header.total_len())
+            },
+            Some(Ipv6(header, _)) if 0 != header.payload_length() => {
+                header.slice().as_ptr() as usize + header.slice().len() + usize::from(header.payload_length())
+            },
+            _ => return slice,
+        };
+        let len = end.saturating_sub(slice.as_ptr() as usize);
+        if len < slice.len() {
+            &slice[..len]
+        } else {
+            slice
+        }
+    }
 }
 
 ///Helper class for slicing packets
 struct CursorSlice<'a> {
     pub slice: &'a [u8],
     pub offset: usize,
-    pub result: SlicedPacket<'a>
+    pub result: SlicedPacket<'a>,
+    /// Length of the integrity check value if ESP packets should be
+    /// decoded assuming NULL encryption.
+    pub esp_null_icv_len: Option<usize>,
 }
 
 impl<'a> CursorSlice<'a> {
@@ -304,7 +415,8 @@ impl<'a> CursorSlice<'a> {
                 ip: None,
                 transport: None,
                 payload: slice
-            }
+            },
+            esp_null_icv_len: None,
         }
     }
 
@@ -320,10 +432,43 @@ impl<'a> CursorSlice<'a> {
     }
 
     fn move_to_slice(&mut self, other: &'a[u8]) {
-        self.offset += self.slice.len() - other.len();
+        // other is always a sub slice of self.slice, but does not
+        // necessarily end at the same position (e.g. ESP trailer)
+        self.offset += (other.as_ptr() as usize) - (self.slice.as_ptr() as usize);
         self.slice = other;
     }
 
+    /// Returns the part of the current slice that is covered by the ip payload
+    /// length if ESP packets get decoded (as the ESP trailer is located at the end
+    /// of the ip payload). Otherwise the complete slice is returned.
+    fn ip_payload_for_esp(&self, payload_len: usize) -> Result<&'a [u8], ReadError> {
+        if self.esp_null_icv_len.is_some() {
+            if self.slice.len() < payload_len {
+                Err(ReadError::UnexpectedEndOfSlice(self.offset + payload_len))
+            } else {
+                Ok(&self.slice[..payload_len])
+            }
+        } else {
+            Ok(self.slice)
+        }
+    }
+
+    fn slice_ipv4_extensions(&self, protocol: u8, slice: &'a [u8]) -> Result<(Ipv4ExtensionsSlice<'a>, u8, &'a [u8]), ReadError> {
+        if let Some(icv_len) = self.esp_null_icv_len {
+            Ipv4ExtensionsSlice::from_slice_esp_null(protocol, slice, icv_len)
+        } else {
+            Ipv4ExtensionsSlice::from_slice(protocol, slice)
+        }
+    }
+
+    fn slice_ipv6_extensions(&self, next_header: u8, slice: &'a [u8]) -> Result<(Ipv6ExtensionsSlice<'a>, u8, &'a [u8]), ReadError> {
+        if let Some(icv_len) = self.esp_null_icv_len {
+            Ipv6ExtensionsSlice::from_slice_esp_null(next_header, slice, icv_len)
+        } else {
+            Ipv6ExtensionsSlice::from_slice(next_header, slice)
+        }
+    }
+
     pub fn slice_ethernet2(mut self) -> Result<SlicedPacket<'a>, ReadError> {
         use LinkSlice::*;
         use ether_type::*;
@@ -427,7 +572,10 @@ impl<'a> CursorSlice<'a> {
         self.move_by_slice(ip_header.slice());
 
         // slice extensions
-        let (ip_ext, protocol, rest) = Ipv4ExtensionsSlice::from_slice(ip_header.protocol(), self.slice)
+        let ip_payload = self.ip_payload_for_esp(
+            usize::from(ip_header.total_len()).saturating_sub(ip_header.slice().len())
+        )?;
+        let (ip_ext, protocol, rest) = self.slice_ipv4_extensions(ip_header.protocol(), ip_payload)
                                        .map_err(|err| 
                                             err.add_slice_offset(self.offset)
                                        )?;
@@ -444,6 +592,7 @@ impl<'a> CursorSlice<'a> {
                 ip_number::TCP => self.slice_tcp(),
                 ip_number::ICMP => self.slice_icmp4(),
                 ip_number::IPV6_ICMP => self.slice_icmp6(),
+                ip_number::IGMP => self.slice_igmp(),
                 value => {
                     use TransportSlice::*;
                     self.result.transport = Some(Unknown(value));
@@ -465,7 +614,8 @@ impl<'a> CursorSlice<'a> {
         self.move_by_slice(ip.slice());
 
         //extension headers
-        let (ip_ext, next_header, rest) = Ipv6ExtensionsSlice::from_slice(ip.next_header(), self.slice)
+        let ip_payload = self.ip_payload_for_esp(ipv6_payload_len(&ip, self.slice))?;
+        let (ip_ext, next_header, rest) = self.slice_ipv6_extensions(ip.next_header(), ip_payload)
                                           .map_err(|err| 
                                               err.add_slice_offset(self.offset)
                                           )?;
@@ -527,6 +677,24 @@ impl<'a> CursorSlice<'a> {
         self.slice_payload()
     }
 
+    pub fn slice_igmp(mut self) -> Result<SlicedPacket<'a>, ReadError> {
+        use crate::TransportSlice::*;
+
+        // cut off data following the ip payload (e.g. ethernet padding)
+        // as the IGMP version is determined based on the message length
+        let result = IgmpSlice::from_slice(self.result.limit_to_ip_payload(self.slice))
+                     .map_err(|err| 
+                        err.add_slice_offset(self.offset)
+                     )?;
+
+        //set the new data
+        self.move_by_slice(result.slice());
+        self.result.transport = Some(Igmp(result));
+
+        //done
+        self.slice_payload()
+    }
+
     pub fn slice_udp(mut self) -> Result<SlicedPacket<'a>, ReadError> {
         use crate::TransportSlice::*;
 
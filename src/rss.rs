@@ -0,0 +1,61 @@
+/// Default 40 byte RSS key used by Microsoft in the RSS verification suite
+/// (also the default key of many network card drivers).
+pub const MICROSOFT_KEY: [u8;40] = [
+    0x6d, 0x5a, 0x56, 0xda, 0x25, 0x5b, 0x0e, 0xc2,
+    0x41, 0x67, 0x25, 0x3d, 0x43, 0xa3, 0x8f, 0xb0,
+    0xd0, 0xca, 0x2b, 0xcb, 0xae, 0x7b, 0x30, 0xb4,
+    0x77, 0xcb, 0x2d, 0xa3, 0x80, 0x30, 0xf2, 0x0c,
+    0x6a, 0x42, 0xb7, 0x3b, 0xbe, 0xac, 0x01, 0xfa,
+];
+
+/// 40 byte RSS key consisting of the repeated 16 bit pattern `0x6d5a`.
+///
+/// As the key repeats every 16 bits, swapping the source & destination
+/// addresses (and ports) of the hash input results in the same hash
+/// (symmetric RSS), so both directions of a flow get assigned to the same
+/// queue.
+pub const SYMMETRIC_KEY: [u8;40] = [
+    0x6d, 0x5a, 0x6d, 0x5a, 0x6d, 0x5a, 0x6d, 0x5a,
+    0x6d, 0x5a, 0x6d, 0x5a, 0x6d, 0x5a, 0x6d, 0x5a,
+    0x6d, 0x5a, 0x6d, 0x5a, 0x6d, 0x5a, 0x6d, 0x5a,
+    0x6d, 0x5a, 0x6d, 0x5a, 0x6d, 0x5a, 0x6d, 0x5a,
+    0x6d, 0x5a, 0x6d, 0x5a, 0x6d, 0x5a, 0x6d, 0x5a,
+];
+
+/// Calculates the Toeplitz hash of the given input as done by network
+/// cards for receive side scaling (RSS).
+///
+/// For every set bit of the input (most significant bit first) the 32 bit
+/// window of the key starting at the position of that bit is xored to the
+/// result. Key bits past the end of the key are treated as 0, so the key
+/// should be at least 4 bytes longer then the input (the 40 byte keys
+/// support inputs of up to 36 bytes, e.g. IPv6 addresses & ports).
+///
+/// # Example
+///
+/// ```
+/// use etherparse::rss::{toeplitz_hash, MICROSOFT_KEY};
+///
+/// // source 66.9.149.187, destination 161.142.100.80
+/// let input = [66, 9, 149, 187, 161, 142, 100, 80];
+/// assert_eq!(0x323e8fc2, toeplitz_hash(&MICROSOFT_KEY, &input));
+/// ```
+pub fn toeplitz_hash(key: &[u8], input: &[u8]) -> u32 {
+    let key_byte = |index: usize| -> u8 {
+        key.get(index).copied().unwrap_or(0)
+    };
+
+    let mut result = 0u32;
+    // 32 bit key window starting at the current input bit
+    let mut window = u32::from_be_bytes([key_byte(0), key_byte(1), key_byte(2), key_byte(3)]);
+    for (index, byte) in input.iter().enumerate() {
+        let next = key_byte(index + 4);
+        for bit in 0..8 {
+            if 0 != byte & (0x80 >> bit) {
+                result ^= window;
+            }
+            window = (window << 1) | u32::from((next >> (7 - bit)) & 1);
+        }
+    }
+    result
+}
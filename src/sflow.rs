@@ -0,0 +1,577 @@
+use super::*;
+
+/// Header protocol values of sFlow v5 sampled header records (the link
+/// layer protocol of the sampled packet header).
+pub mod sflow_header_protocol {
+    /// Ethernet frame (can be decoded with `SlicedPacket::from_ethernet`).
+    pub const ETHERNET_ISO88023: u32 = 1;
+    pub const ISO88024_TOKENBUS: u32 = 2;
+    pub const ISO88025_TOKENRING: u32 = 3;
+    pub const FDDI: u32 = 4;
+    pub const FRAME_RELAY: u32 = 5;
+    pub const X25: u32 = 6;
+    pub const PPP: u32 = 7;
+    pub const SMDS: u32 = 8;
+    pub const AAL5: u32 = 9;
+    pub const AAL5_IP: u32 = 10;
+    /// IPv4 packet (can be decoded with `SlicedPacket::from_ip`).
+    pub const IPV4: u32 = 11;
+    /// IPv6 packet (can be decoded with `SlicedPacket::from_ip`).
+    pub const IPV6: u32 = 12;
+    pub const MPLS: u32 = 13;
+    pub const POS: u32 = 14;
+}
+
+/// Address of an sFlow agent or next hop router.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub enum SflowAddress {
+    /// Address type 0 (no address present).
+    Unknown,
+    Ipv4([u8;4]),
+    Ipv6([u8;16]),
+}
+
+/// Interface of an sFlow flow sample the sampled packet was received on
+/// or sent to.
+///
+/// The meaning of `value` depends on `format`:
+///
+/// * `0` - `value` is the ifIndex of the interface (0 if unknown).
+/// * `1` - the packet was discarded & `value` contains the reason.
+/// * `2` - the packet was sent to `value` interfaces (0x3fffffff if the
+///   number is unknown).
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct SflowInterface {
+    pub format: u32,
+    pub value: u32,
+}
+
+impl SflowInterface {
+    /// Decodes an interface in the compact (non expanded) format, where the
+    /// format is stored in the upper 2 bits.
+    pub fn from_compact(value: u32) -> SflowInterface {
+        SflowInterface {
+            format: value >> 30,
+            value: value & 0x3fff_ffff,
+        }
+    }
+}
+
+/// Raw packet header record of an sFlow flow sample.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SflowSampledHeader<'a> {
+    /// Link layer protocol of `header` (see [`sflow_header_protocol`]).
+    pub header_protocol: u32,
+    /// Original length of the sampled packet.
+    pub frame_length: u32,
+    /// Number of bytes removed from the packet before `header` (e.g. the
+    /// ethernet FCS).
+    pub stripped: u32,
+    /// Start of the sampled packet (usually truncated).
+    pub header: &'a [u8],
+}
+
+impl<'a> SflowSampledHeader<'a> {
+    /// Decodes the sampled packet header with [`SlicedPacket::from_ethernet`]
+    /// (ethernet headers) or [`SlicedPacket::from_ip`] (IPv4 & IPv6 headers).
+    ///
+    /// `None` is returned for all other header protocols. As sampled
+    /// headers are usually truncated the payload of the resulting slice can
+    /// be shorter then indicated by the headers.
+    pub fn sliced_packet(&self) -> Option<Result<SlicedPacket<'a>, ReadError>> {
+        use sflow_header_protocol::*;
+        match self.header_protocol {
+            ETHERNET_ISO88023 => Some(SlicedPacket::from_ethernet(self.header)),
+            IPV4 | IPV6 => Some(SlicedPacket::from_ip(self.header)),
+            _ => None,
+        }
+    }
+}
+
+/// Record of an sFlow flow sample.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SflowFlowRecord<'a> {
+    /// Raw packet header (format 1).
+    SampledHeader(SflowSampledHeader<'a>),
+    /// Ethernet frame data (format 2).
+    SampledEthernet{ length: u32, source: [u8;6], destination: [u8;6], ether_type: u32 },
+    /// IPv4 packet data (format 3).
+    SampledIpv4{
+        length: u32,
+        protocol: u32,
+        source: [u8;4],
+        destination: [u8;4],
+        source_port: u32,
+        destination_port: u32,
+        tcp_flags: u32,
+        tos: u32,
+    },
+    /// IPv6 packet data (format 4).
+    SampledIpv6{
+        length: u32,
+        protocol: u32,
+        source: [u8;16],
+        destination: [u8;16],
+        source_port: u32,
+        destination_port: u32,
+        tcp_flags: u32,
+        priority: u32,
+    },
+    /// Extended switch data (format 1001).
+    ExtendedSwitch{ source_vlan: u32, source_priority: u32, destination_vlan: u32, destination_priority: u32 },
+    /// Extended router data (format 1002).
+    ExtendedRouter{ next_hop: SflowAddress, source_mask_len: u32, destination_mask_len: u32 },
+    /// Record with an unknown format or an enterprise specific record.
+    Unknown{ enterprise: u32, format: u32, data: &'a [u8] },
+}
+
+/// Generic interface counters (counter record format 1, rfc2233).
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct SflowInterfaceCounters {
+    pub if_index: u32,
+    pub if_type: u32,
+    pub if_speed: u64,
+    pub if_direction: u32,
+    pub if_status: u32,
+    pub if_in_octets: u64,
+    pub if_in_ucast_pkts: u32,
+    pub if_in_multicast_pkts: u32,
+    pub if_in_broadcast_pkts: u32,
+    pub if_in_discards: u32,
+    pub if_in_errors: u32,
+    pub if_in_unknown_protos: u32,
+    pub if_out_octets: u64,
+    pub if_out_ucast_pkts: u32,
+    pub if_out_multicast_pkts: u32,
+    pub if_out_broadcast_pkts: u32,
+    pub if_out_discards: u32,
+    pub if_out_errors: u32,
+    pub if_promiscuous_mode: u32,
+}
+
+/// Ethernet interface counters (counter record format 2, rfc2358).
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct SflowEthernetCounters {
+    pub alignment_errors: u32,
+    pub fcs_errors: u32,
+    pub single_collision_frames: u32,
+    pub multiple_collision_frames: u32,
+    pub sqe_test_errors: u32,
+    pub deferred_transmissions: u32,
+    pub late_collisions: u32,
+    pub excessive_collisions: u32,
+    pub internal_mac_transmit_errors: u32,
+    pub carrier_sense_errors: u32,
+    pub frame_too_longs: u32,
+    pub internal_mac_receive_errors: u32,
+    pub symbol_errors: u32,
+}
+
+/// Record of an sFlow counters sample.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SflowCounterRecord<'a> {
+    /// Generic interface counters (format 1).
+    GenericInterface(SflowInterfaceCounters),
+    /// Ethernet interface counters (format 2).
+    EthernetInterface(SflowEthernetCounters),
+    /// Record with an unknown format or an enterprise specific record.
+    Unknown{ enterprise: u32, format: u32, data: &'a [u8] },
+}
+
+/// sFlow flow sample (compact format 1 or expanded format 3).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SflowFlowSample<'a> {
+    /// True if the sample was encoded in the expanded format.
+    pub expanded: bool,
+    pub sequence_number: u32,
+    /// Type of the data source (0 = ifIndex, 1 = smonVlanDataSource,
+    /// 2 = entPhysicalEntry).
+    pub source_id_type: u32,
+    pub source_id_index: u32,
+    pub sampling_rate: u32,
+    /// Total number of packets that could have been sampled.
+    pub sample_pool: u32,
+    /// Number of samples dropped due to a lack of resources.
+    pub drops: u32,
+    pub input: SflowInterface,
+    pub output: SflowInterface,
+    pub records: Vec<SflowFlowRecord<'a>>,
+}
+
+/// sFlow counters sample (compact format 2 or expanded format 4).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SflowCountersSample<'a> {
+    /// True if the sample was encoded in the expanded format.
+    pub expanded: bool,
+    pub sequence_number: u32,
+    pub source_id_type: u32,
+    pub source_id_index: u32,
+    pub records: Vec<SflowCounterRecord<'a>>,
+}
+
+/// Sample contained in an sFlow datagram.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SflowSample<'a> {
+    Flow(SflowFlowSample<'a>),
+    Counters(SflowCountersSample<'a>),
+    /// Sample with an unknown format or an enterprise specific sample.
+    Unknown{ enterprise: u32, format: u32, data: &'a [u8] },
+}
+
+/// Decoded sFlow v5 datagram (e.g. the payload of a UDP packet sent to
+/// port 6343).
+///
+/// # Example
+///
+/// ```
+/// # use etherparse::*;
+/// # let builder = PacketBuilder::ethernet2([2,3,4,5,6,7], [8,9,10,11,12,13])
+/// #     .ipv4([192,168,1,1], [192,168,1,2], 20)
+/// #     .udp(1234, 53);
+/// # let mut frame = Vec::<u8>::with_capacity(builder.size(4));
+/// # builder.write(&mut frame, &[1,2,3,4]).unwrap();
+/// # let push = |datagram: &mut Vec<u8>, values: &[u32]| for value in values {
+/// #     datagram.extend_from_slice(&value.to_be_bytes());
+/// # };
+/// # let mut datagram = Vec::new();
+/// # // datagram header (version, agent address, sub agent id, sequence number, uptime, sample count)
+/// # push(&mut datagram, &[5, 1, 0x0a00_0001, 0, 1, 1000, 1]);
+/// # // flow sample (format, length, sequence number, source id, sampling rate, pool, drops, input, output, record count)
+/// # let sample_len = 4*8 + 4*6 + 48;
+/// # push(&mut datagram, &[1, sample_len, 1, 0, 100, 100, 0, 1, 2, 1]);
+/// # // sampled header record (format, length, protocol, frame length, stripped, header length)
+/// # push(&mut datagram, &[1, 4*4 + 48, 1, frame.len() as u32 + 4, 4, frame.len() as u32]);
+/// # datagram.extend_from_slice(&frame);
+/// # datagram.extend_from_slice(&[0, 0]);
+/// use etherparse::{SflowDatagram, SflowFlowRecord, SflowSample};
+///
+/// let sflow = SflowDatagram::from_slice(&datagram).unwrap();
+/// for sample in &sflow.samples {
+///     if let SflowSample::Flow(flow) = sample {
+///         for record in &flow.records {
+///             if let SflowFlowRecord::SampledHeader(header) = record {
+///                 // the sampled frame gets decoded with SlicedPacket
+///                 let packet = header.sliced_packet().unwrap().unwrap();
+///                 assert!(packet.ip.is_some());
+///             }
+///         }
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SflowDatagram<'a> {
+    pub agent_address: SflowAddress,
+    pub sub_agent_id: u32,
+    pub sequence_number: u32,
+    /// Time since the agent was booted in milliseconds.
+    pub uptime: u32,
+    pub samples: Vec<SflowSample<'a>>,
+}
+
+impl<'a> SflowDatagram<'a> {
+    /// sFlow version supported by the decoder.
+    pub const VERSION: u32 = 5;
+
+    /// Decodes an sFlow v5 datagram including all samples & records.
+    ///
+    /// Samples & records with an unknown format are returned as `Unknown`
+    /// values containing the raw data. Data after the last sample is ignored.
+    pub fn from_slice(slice: &'a [u8]) -> Result<SflowDatagram<'a>, ReadError> {
+        let mut cursor = SflowCursor{ slice, offset: 0 };
+
+        let version = cursor.u32()?;
+        if SflowDatagram::VERSION != version {
+            return Err(ReadError::SflowUnsupportedVersion(version));
+        }
+        let agent_address = cursor.address()?;
+        let sub_agent_id = cursor.u32()?;
+        let sequence_number = cursor.u32()?;
+        let uptime = cursor.u32()?;
+
+        let sample_count = cursor.u32()?;
+        let mut samples = Vec::new();
+        for _ in 0..sample_count {
+            let (enterprise, format, data) = cursor.element()?;
+            samples.push(SflowSample::decode(enterprise, format, data)?);
+        }
+
+        Ok(SflowDatagram {
+            agent_address,
+            sub_agent_id,
+            sequence_number,
+            uptime,
+            samples,
+        })
+    }
+}
+
+impl<'a> SflowSample<'a> {
+    fn decode(enterprise: u32, format: u32, mut cursor: SflowCursor<'a>) -> Result<SflowSample<'a>, ReadError> {
+        use SflowSample::*;
+        Ok(match (enterprise, format) {
+            (0, 1) | (0, 3) => {
+                let expanded = 3 == format;
+                let sequence_number = cursor.u32()?;
+                let (source_id_type, source_id_index) = cursor.source_id(expanded)?;
+                let sampling_rate = cursor.u32()?;
+                let sample_pool = cursor.u32()?;
+                let drops = cursor.u32()?;
+                let input = cursor.interface(expanded)?;
+                let output = cursor.interface(expanded)?;
+
+                let record_count = cursor.u32()?;
+                let mut records = Vec::new();
+                for _ in 0..record_count {
+                    let (enterprise, format, data) = cursor.element()?;
+                    records.push(SflowFlowRecord::decode(enterprise, format, data)?);
+                }
+                Flow(SflowFlowSample {
+                    expanded,
+                    sequence_number,
+                    source_id_type,
+                    source_id_index,
+                    sampling_rate,
+                    sample_pool,
+                    drops,
+                    input,
+                    output,
+                    records,
+                })
+            },
+            (0, 2) | (0, 4) => {
+                let expanded = 4 == format;
+                let sequence_number = cursor.u32()?;
+                let (source_id_type, source_id_index) = cursor.source_id(expanded)?;
+
+                let record_count = cursor.u32()?;
+                let mut records = Vec::new();
+                for _ in 0..record_count {
+                    let (enterprise, format, data) = cursor.element()?;
+                    records.push(SflowCounterRecord::decode(enterprise, format, data)?);
+                }
+                Counters(SflowCountersSample {
+                    expanded,
+                    sequence_number,
+                    source_id_type,
+                    source_id_index,
+                    records,
+                })
+            },
+            _ => Unknown{ enterprise, format, data: cursor.slice },
+        })
+    }
+}
+
+impl<'a> SflowFlowRecord<'a> {
+    fn decode(enterprise: u32, format: u32, mut cursor: SflowCursor<'a>) -> Result<SflowFlowRecord<'a>, ReadError> {
+        use SflowFlowRecord::*;
+        Ok(match (enterprise, format) {
+            (0, 1) => {
+                let header_protocol = cursor.u32()?;
+                let frame_length = cursor.u32()?;
+                let stripped = cursor.u32()?;
+                let header_len = cursor.u32()? as usize;
+                SampledHeader(SflowSampledHeader {
+                    header_protocol,
+                    frame_length,
+                    stripped,
+                    header: cursor.opaque(header_len)?,
+                })
+            },
+            (0, 2) => {
+                let length = cursor.u32()?;
+                let mut source = [0u8;6];
+                source.copy_from_slice(cursor.opaque(6)?);
+                let mut destination = [0u8;6];
+                destination.copy_from_slice(cursor.opaque(6)?);
+                SampledEthernet {
+                    length,
+                    source,
+                    destination,
+                    ether_type: cursor.u32()?,
+                }
+            },
+            (0, 3) => {
+                let length = cursor.u32()?;
+                let protocol = cursor.u32()?;
+                let mut source = [0u8;4];
+                source.copy_from_slice(cursor.opaque(4)?);
+                let mut destination = [0u8;4];
+                destination.copy_from_slice(cursor.opaque(4)?);
+                SampledIpv4 {
+                    length,
+                    protocol,
+                    source,
+                    destination,
+                    source_port: cursor.u32()?,
+                    destination_port: cursor.u32()?,
+                    tcp_flags: cursor.u32()?,
+                    tos: cursor.u32()?,
+                }
+            },
+            (0, 4) => {
+                let length = cursor.u32()?;
+                let protocol = cursor.u32()?;
+                let mut source = [0u8;16];
+                source.copy_from_slice(cursor.opaque(16)?);
+                let mut destination = [0u8;16];
+                destination.copy_from_slice(cursor.opaque(16)?);
+                SampledIpv6 {
+                    length,
+                    protocol,
+                    source,
+                    destination,
+                    source_port: cursor.u32()?,
+                    destination_port: cursor.u32()?,
+                    tcp_flags: cursor.u32()?,
+                    priority: cursor.u32()?,
+                }
+            },
+            (0, 1001) => ExtendedSwitch {
+                source_vlan: cursor.u32()?,
+                source_priority: cursor.u32()?,
+                destination_vlan: cursor.u32()?,
+                destination_priority: cursor.u32()?,
+            },
+            (0, 1002) => ExtendedRouter {
+                next_hop: cursor.address()?,
+                source_mask_len: cursor.u32()?,
+                destination_mask_len: cursor.u32()?,
+            },
+            _ => Unknown{ enterprise, format, data: cursor.slice },
+        })
+    }
+}
+
+impl<'a> SflowCounterRecord<'a> {
+    fn decode(enterprise: u32, format: u32, mut cursor: SflowCursor<'a>) -> Result<SflowCounterRecord<'a>, ReadError> {
+        use SflowCounterRecord::*;
+        Ok(match (enterprise, format) {
+            (0, 1) => GenericInterface(SflowInterfaceCounters {
+                if_index: cursor.u32()?,
+                if_type: cursor.u32()?,
+                if_speed: cursor.u64()?,
+                if_direction: cursor.u32()?,
+                if_status: cursor.u32()?,
+                if_in_octets: cursor.u64()?,
+                if_in_ucast_pkts: cursor.u32()?,
+                if_in_multicast_pkts: cursor.u32()?,
+                if_in_broadcast_pkts: cursor.u32()?,
+                if_in_discards: cursor.u32()?,
+                if_in_errors: cursor.u32()?,
+                if_in_unknown_protos: cursor.u32()?,
+                if_out_octets: cursor.u64()?,
+                if_out_ucast_pkts: cursor.u32()?,
+                if_out_multicast_pkts: cursor.u32()?,
+                if_out_broadcast_pkts: cursor.u32()?,
+                if_out_discards: cursor.u32()?,
+                if_out_errors: cursor.u32()?,
+                if_promiscuous_mode: cursor.u32()?,
+            }),
+            (0, 2) => EthernetInterface(SflowEthernetCounters {
+                alignment_errors: cursor.u32()?,
+                fcs_errors: cursor.u32()?,
+                single_collision_frames: cursor.u32()?,
+                multiple_collision_frames: cursor.u32()?,
+                sqe_test_errors: cursor.u32()?,
+                deferred_transmissions: cursor.u32()?,
+                late_collisions: cursor.u32()?,
+                excessive_collisions: cursor.u32()?,
+                internal_mac_transmit_errors: cursor.u32()?,
+                carrier_sense_errors: cursor.u32()?,
+                frame_too_longs: cursor.u32()?,
+                internal_mac_receive_errors: cursor.u32()?,
+                symbol_errors: cursor.u32()?,
+            }),
+            _ => Unknown{ enterprise, format, data: cursor.slice },
+        })
+    }
+}
+
+/// Reads the XDR encoded values of an sFlow datagram & keeps track of the
+/// offset in the datagram (used in the `UnexpectedEndOfSlice` errors).
+struct SflowCursor<'a> {
+    slice: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> SflowCursor<'a> {
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8], ReadError> {
+        if self.slice.len() < len {
+            Err(ReadError::UnexpectedEndOfSlice(self.offset + len))
+        } else {
+            let (result, rest) = self.slice.split_at(len);
+            self.slice = rest;
+            self.offset += len;
+            Ok(result)
+        }
+    }
+
+    /// Reads opaque data & skips the padding to the next multiple of 4.
+    fn opaque(&mut self, len: usize) -> Result<&'a [u8], ReadError> {
+        let padding = (4 - len % 4) % 4;
+        let result = self.bytes(len)?;
+        self.bytes(padding.min(self.slice.len()))?;
+        Ok(result)
+    }
+
+    fn u32(&mut self) -> Result<u32, ReadError> {
+        let b = self.bytes(4)?;
+        Ok(u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    fn u64(&mut self) -> Result<u64, ReadError> {
+        let b = self.bytes(8)?;
+        Ok(u64::from_be_bytes([b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]]))
+    }
+
+    fn address(&mut self) -> Result<SflowAddress, ReadError> {
+        use SflowAddress::*;
+        match self.u32()? {
+            0 => Ok(Unknown),
+            1 => {
+                let mut result = [0u8;4];
+                result.copy_from_slice(self.bytes(4)?);
+                Ok(Ipv4(result))
+            },
+            2 => {
+                let mut result = [0u8;16];
+                result.copy_from_slice(self.bytes(16)?);
+                Ok(Ipv6(result))
+            },
+            value => Err(ReadError::SflowUnsupportedAddressType(value)),
+        }
+    }
+
+    /// Reads the source id type & index (packed into one value in the
+    /// compact format).
+    fn source_id(&mut self, expanded: bool) -> Result<(u32, u32), ReadError> {
+        if expanded {
+            Ok((self.u32()?, self.u32()?))
+        } else {
+            let value = self.u32()?;
+            Ok((value >> 24, value & 0x00ff_ffff))
+        }
+    }
+
+    fn interface(&mut self, expanded: bool) -> Result<SflowInterface, ReadError> {
+        if expanded {
+            Ok(SflowInterface {
+                format: self.u32()?,
+                value: self.u32()?,
+            })
+        } else {
+            Ok(SflowInterface::from_compact(self.u32()?))
+        }
+    }
+
+    /// Reads the data format & length of a sample or record & returns the
+    /// enterprise, format & a cursor over the data.
+    fn element(&mut self) -> Result<(u32, u32, SflowCursor<'a>), ReadError> {
+        let data_format = self.u32()?;
+        let len = self.u32()? as usize;
+        let offset = self.offset;
+        let data = self.opaque(len)?;
+        Ok((data_format >> 12, data_format & 0xfff, SflowCursor{ slice: data, offset }))
+    }
+}
@@ -0,0 +1,464 @@
+use super::*;
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// IP address of a TCP stream endpoint.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub enum TcpStreamAddress {
+    Ipv4([u8;4]),
+    Ipv6([u8;16]),
+}
+
+/// Identifies one direction of a TCP connection (the sender of the
+/// segments is the source).
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct TcpStreamKey {
+    pub source: TcpStreamAddress,
+    pub source_port: u16,
+    pub destination: TcpStreamAddress,
+    pub destination_port: u16,
+}
+
+impl TcpStreamKey {
+    /// Returns the key of the direction the given TCP segment is sent in
+    /// (`None` if the packet does not contain an IP header & TCP header).
+    pub fn from_sliced_packet(packet: &SlicedPacket) -> Option<TcpStreamKey> {
+        let tcp = match &packet.transport {
+            Some(TransportSlice::Tcp(tcp)) => tcp,
+            _ => return None,
+        };
+        let (source, destination) = match &packet.ip {
+            Some(InternetSlice::Ipv4(ip, _)) => (
+                TcpStreamAddress::Ipv4(ip.source()),
+                TcpStreamAddress::Ipv4(ip.destination())
+            ),
+            Some(InternetSlice::Ipv6(ip, _)) => (
+                TcpStreamAddress::Ipv6(ip.source()),
+                TcpStreamAddress::Ipv6(ip.destination())
+            ),
+            None => return None,
+        };
+        Some(TcpStreamKey {
+            source,
+            source_port: tcp.source_port(),
+            destination,
+            destination_port: tcp.destination_port(),
+        })
+    }
+
+    /// Returns the key of the opposite direction.
+    pub fn reversed(&self) -> TcpStreamKey {
+        TcpStreamKey {
+            source: self.destination,
+            source_port: self.destination_port,
+            destination: self.source,
+            destination_port: self.source_port,
+        }
+    }
+}
+
+/// Events generated by a [`TcpReassembler`].
+///
+/// `offset` values are relative to the start of the stream (the first byte
+/// after the SYN or, if the SYN was not observed, the first observed byte).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum TcpStreamEvent<'a> {
+    /// Contiguous bytes of the stream in direction `key`.
+    Data{ key: TcpStreamKey, offset: u64, data: &'a [u8] },
+    /// Bytes of the stream that were not received and will not be delivered
+    /// (the memory limits were reached or the connection got evicted).
+    Gap{ key: TcpStreamKey, offset: u64, len: u64 },
+    /// All bytes up to the FIN of the stream in direction `key` have been delivered.
+    Fin{ key: TcpStreamKey },
+    /// The sender of the stream in direction `key` reset the connection (both
+    /// directions are closed).
+    Reset{ key: TcpStreamKey },
+    /// The stream in direction `key` was closed because the connection was idle for
+    /// too long, the connection limit was reached or all connections were flushed.
+    Evicted{ key: TcpStreamKey },
+}
+
+/// Limits of a [`TcpReassembler`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TcpReassemblerConfig {
+    /// Maximum number of out of order bytes buffered per direction.
+    pub max_buffered_bytes_per_stream: usize,
+    /// Maximum number of out of order bytes buffered over all connections.
+    pub max_buffered_bytes: usize,
+    /// Maximum number of tracked connections (the least recently active
+    /// connection gets evicted if a new connection exceeds the limit).
+    pub max_connections: usize,
+    /// Time after which an idle connection gets evicted by [`TcpReassembler::evict_idle`]
+    /// (in the same unit as the `now` values passed to the reassembler).
+    pub idle_timeout: u64,
+}
+
+impl Default for TcpReassemblerConfig {
+    fn default() -> TcpReassemblerConfig {
+        TcpReassemblerConfig {
+            max_buffered_bytes_per_stream: 256*1024,
+            max_buffered_bytes: 64*1024*1024,
+            max_connections: 65536,
+            idle_timeout: 300_000,
+        }
+    }
+}
+
+/// I/O free reassembler of the byte streams of TCP connections.
+///
+/// The reassembler consumes sliced packets and delivers the contiguous
+/// bytes of both directions of each connection via a callback. Retransmitted
+/// & overlapping bytes are only delivered once (the first received copy of
+/// a byte is used). Out of order bytes are buffered till the missing bytes are
+/// received or one of the memory limits is reached (in which case the missing
+/// bytes are reported as a [`TcpStreamEvent::Gap`]).
+///
+/// # Example
+///
+/// ```
+/// # use etherparse::*;
+/// # let builder = PacketBuilder::
+/// #    ipv4([192,168,1,1], [192,168,1,2], 20)
+/// #    .tcp(1234, 80, 999, 4000)
+/// #    .syn();
+/// # let mut syn = Vec::<u8>::with_capacity(builder.size(0));
+/// # builder.write(&mut syn, &[]).unwrap();
+/// # let builder = PacketBuilder::
+/// #    ipv4([192,168,1,1], [192,168,1,2], 20)
+/// #    .tcp(1234, 80, 1000, 4000);
+/// # let mut first = Vec::<u8>::with_capacity(builder.size(4));
+/// # builder.write(&mut first, &[1,2,3,4]).unwrap();
+/// # let builder = PacketBuilder::
+/// #    ipv4([192,168,1,1], [192,168,1,2], 20)
+/// #    .tcp(1234, 80, 1004, 4000);
+/// # let mut second = Vec::<u8>::with_capacity(builder.size(2));
+/// # builder.write(&mut second, &[5,6]).unwrap();
+/// let mut reassembler = TcpReassembler::new(TcpReassemblerConfig::default());
+/// let mut stream = Vec::new();
+///
+/// // the second data segment arrives first & gets buffered
+/// for packet in &[syn, second, first] {
+///     let sliced = SlicedPacket::from_ip(&packet).unwrap();
+///     reassembler.process(&sliced, 0, |event| {
+///         if let TcpStreamEvent::Data{ data, .. } = event {
+///             stream.extend_from_slice(data);
+///         }
+///     });
+/// }
+/// assert_eq!(vec![1,2,3,4,5,6], stream);
+/// ```
+#[derive(Clone, Debug)]
+pub struct TcpReassembler {
+    config: TcpReassemblerConfig,
+    /// Connections by the key of the smaller direction.
+    connections: HashMap<TcpStreamKey, Connection>,
+    /// Keys of the connections ordered by the time of their last activity
+    /// (used to find the least recently active & idle connections without
+    /// iterating over all connections).
+    by_last: BTreeSet<(u64, TcpStreamKey)>,
+    buffered_bytes: usize,
+}
+
+#[derive(Clone, Debug, Default)]
+struct Connection {
+    last_activity: u64,
+    /// Streams in the direction of the connection key (index 0) & the reversed direction (index 1).
+    streams: [Stream;2],
+}
+
+#[derive(Clone, Debug, Default)]
+struct Stream {
+    /// Sequence number of the byte at offset 0 (`None` till the first segment is received).
+    base_sequence_number: Option<u32>,
+    /// Offset of the next byte to be delivered.
+    delivered: u64,
+    /// Offset of the FIN (if one was received).
+    fin: Option<u64>,
+    /// Set after the FIN event was generated.
+    finished: bool,
+    /// Non overlapping out of order segments by offset.
+    segments: BTreeMap<u64, Vec<u8>>,
+    buffered_bytes: usize,
+}
+
+impl TcpReassembler {
+    /// Creates a reassembler without any connections.
+    pub fn new(config: TcpReassemblerConfig) -> TcpReassembler {
+        TcpReassembler {
+            config,
+            connections: HashMap::new(),
+            by_last: BTreeSet::new(),
+            buffered_bytes: 0,
+        }
+    }
+
+    /// Limits of the reassembler.
+    pub fn config(&self) -> &TcpReassemblerConfig {
+        &self.config
+    }
+
+    /// Number of currently tracked connections.
+    pub fn connection_count(&self) -> usize {
+        self.connections.len()
+    }
+
+    /// Number of out of order bytes currently buffered.
+    pub fn buffered_bytes(&self) -> usize {
+        self.buffered_bytes
+    }
+
+    /// Processes a packet observed at the time `now` and calls `on_event` for each
+    /// generated event.
+    ///
+    /// Returns `false` if the packet does not contain an IP header & TCP header (and
+    /// was ignored). New connections are only tracked if the segment contains a SYN
+    /// or data.
+    pub fn process<F: FnMut(TcpStreamEvent)>(&mut self, packet: &SlicedPacket, now: u64, mut on_event: F) -> bool {
+        let key = match TcpStreamKey::from_sliced_packet(packet) {
+            Some(key) => key,
+            None => return false,
+        };
+        let tcp = match &packet.transport {
+            Some(TransportSlice::Tcp(tcp)) => tcp,
+            _ => return false,
+        };
+        // data following the ip payload (e.g. ethernet padding) is not part of the segment
+        let payload = packet.limit_to_ip_payload(packet.payload);
+        let reversed = key.reversed();
+        let (connection_key, index) = if key <= reversed {
+            (key, 0)
+        } else {
+            (reversed, 1)
+        };
+
+        if !self.connections.contains_key(&connection_key) {
+            if tcp.rst() || (!tcp.syn() && payload.is_empty()) {
+                return true;
+            }
+            if self.connections.len() >= self.config.max_connections {
+                self.evict_least_recently_active(&mut on_event);
+            }
+            self.connections.insert(connection_key, Connection::default());
+        }
+
+        let TcpReassembler{ config, connections, by_last, buffered_bytes } = self;
+        let connection = connections.get_mut(&connection_key).unwrap();
+        by_last.remove(&(connection.last_activity, connection_key));
+        connection.last_activity = now;
+
+        if tcp.rst() {
+            // deliver the buffered bytes of both directions before the reset
+            let mut connection = connections.remove(&connection_key).unwrap();
+            for (i, stream) in connection.streams.iter_mut().enumerate() {
+                let stream_key = if 0 == i {
+                    connection_key
+                } else {
+                    connection_key.reversed()
+                };
+                *buffered_bytes -= stream.buffered_bytes;
+                stream.flush(&stream_key, &mut on_event);
+            }
+            on_event(TcpStreamEvent::Reset{ key });
+            return true;
+        }
+        by_last.insert((now, connection_key));
+
+        let stream = &mut connection.streams[index];
+        let mut sequence_number = tcp.sequence_number();
+        if tcp.syn() {
+            sequence_number = sequence_number.wrapping_add(1);
+        }
+        if stream.base_sequence_number.is_none() {
+            stream.base_sequence_number = Some(sequence_number);
+        }
+        if !stream.finished {
+            let offset = stream.offset(sequence_number);
+            if tcp.fin() && stream.fin.is_none() {
+                let fin = offset + payload.len() as i64;
+                if fin >= stream.delivered as i64 {
+                    stream.fin = Some(fin as u64);
+                }
+            }
+
+            let before = stream.buffered_bytes;
+            stream.receive(&key, offset, payload, &mut on_event);
+            *buffered_bytes = *buffered_bytes + stream.buffered_bytes - before;
+
+            // enforce the memory limits by skipping the missing bytes
+            while !stream.segments.is_empty() &&
+                  (stream.buffered_bytes > config.max_buffered_bytes_per_stream ||
+                   *buffered_bytes > config.max_buffered_bytes)
+            {
+                let before = stream.buffered_bytes;
+                stream.skip_gap(&key, &mut on_event);
+                *buffered_bytes -= before - stream.buffered_bytes;
+            }
+            stream.check_fin(&key, &mut on_event);
+        }
+
+        if connection.streams[0].finished && connection.streams[1].finished {
+            connections.remove(&connection_key);
+            by_last.remove(&(now, connection_key));
+        }
+        true
+    }
+
+    /// Evicts all connections that were not active since `idle_timeout` (delivering
+    /// buffered bytes & gaps before generating the [`TcpStreamEvent::Evicted`] events).
+    pub fn evict_idle<F: FnMut(TcpStreamEvent)>(&mut self, now: u64, mut on_event: F) {
+        let timeout = self.config.idle_timeout;
+        let mut idle: Vec<TcpStreamKey> = self.by_last.iter()
+            .take_while(|(last_activity, _)| now.saturating_sub(*last_activity) >= timeout)
+            .map(|(_, key)| *key)
+            .collect();
+        idle.sort();
+        for key in idle {
+            self.evict(&key, &mut on_event);
+        }
+    }
+
+    /// Evicts all connections (e.g. at the end of a capture), delivering buffered
+    /// bytes & gaps before generating the [`TcpStreamEvent::Evicted`] events.
+    pub fn flush<F: FnMut(TcpStreamEvent)>(&mut self, mut on_event: F) {
+        let mut keys: Vec<TcpStreamKey> = self.connections.keys().copied().collect();
+        keys.sort();
+        for key in keys {
+            self.evict(&key, &mut on_event);
+        }
+    }
+
+    fn evict_least_recently_active<F: FnMut(TcpStreamEvent)>(&mut self, on_event: &mut F) {
+        let oldest = self.by_last.iter().next().map(|(_, key)| *key);
+        if let Some(key) = oldest {
+            self.evict(&key, on_event);
+        }
+    }
+
+    fn evict<F: FnMut(TcpStreamEvent)>(&mut self, connection_key: &TcpStreamKey, on_event: &mut F) {
+        if let Some(mut connection) = self.connections.remove(connection_key) {
+            self.by_last.remove(&(connection.last_activity, *connection_key));
+            for (i, stream) in connection.streams.iter_mut().enumerate() {
+                let key = if 0 == i {
+                    *connection_key
+                } else {
+                    connection_key.reversed()
+                };
+                self.buffered_bytes -= stream.buffered_bytes;
+                stream.flush(&key, on_event);
+                stream.check_fin(&key, on_event);
+                if !stream.finished {
+                    on_event(TcpStreamEvent::Evicted{ key });
+                }
+            }
+        }
+    }
+}
+
+impl Stream {
+    /// Stream offset of the given sequence number (negative if the
+    /// sequence number is before the start of the stream).
+    fn offset(&self, sequence_number: u32) -> i64 {
+        let base = self.base_sequence_number.unwrap_or(sequence_number);
+        let next = base.wrapping_add(self.delivered as u32);
+        self.delivered as i64 + i64::from(sequence_number.wrapping_sub(next) as i32)
+    }
+
+    /// Delivers or buffers the data of a received segment starting at the given offset.
+    fn receive<F: FnMut(TcpStreamEvent)>(&mut self, key: &TcpStreamKey, offset: i64, data: &[u8], on_event: &mut F) {
+        // cut off already delivered bytes & bytes after the FIN
+        let start = std::cmp::max(offset, self.delivered as i64);
+        let mut end = offset + data.len() as i64;
+        if let Some(fin) = self.fin {
+            end = std::cmp::min(end, fin as i64);
+        }
+        if start >= end {
+            return;
+        }
+        let data = &data[(start - offset) as usize..(end - offset) as usize];
+        let start = start as u64;
+
+        // directly deliver the bytes that are next in the stream (up to the
+        // first buffered segment as the first received copy of a byte is used)
+        let direct = if start == self.delivered {
+            let first_segment = self.segments.keys().next().copied().unwrap_or(u64::MAX);
+            std::cmp::min(data.len() as u64, first_segment - start) as usize
+        } else {
+            0
+        };
+        if direct > 0 {
+            on_event(TcpStreamEvent::Data{ key: *key, offset: start, data: &data[..direct] });
+            self.delivered += direct as u64;
+        }
+        self.insert(start + direct as u64, &data[direct..]);
+        self.deliver_buffered(key, on_event);
+    }
+
+    /// Buffers the parts of the data that are not already buffered.
+    fn insert(&mut self, offset: u64, data: &[u8]) {
+        let end = offset + data.len() as u64;
+        let mut start = offset;
+        if let Some((s, segment)) = self.segments.range(..=start).next_back() {
+            start = std::cmp::max(start, s + segment.len() as u64);
+        }
+        while start < end {
+            let next = self.segments.range(start..end).next()
+                .map(|(s, segment)| (*s, s + segment.len() as u64));
+            let piece_end = next.map(|(s, _)| s).unwrap_or(end);
+            if piece_end > start {
+                let piece = data[(start - offset) as usize..(piece_end - offset) as usize].to_vec();
+                self.buffered_bytes += piece.len();
+                self.segments.insert(start, piece);
+            }
+            match next {
+                Some((_, segment_end)) => start = segment_end,
+                None => break,
+            }
+        }
+    }
+
+    /// Delivers buffered segments that are next in the stream.
+    fn deliver_buffered<F: FnMut(TcpStreamEvent)>(&mut self, key: &TcpStreamKey, on_event: &mut F) {
+        while let Some(start) = self.segments.keys().next().copied() {
+            if start > self.delivered {
+                break;
+            }
+            let segment = self.segments.remove(&start).unwrap();
+            self.buffered_bytes -= segment.len();
+            let end = start + segment.len() as u64;
+            if end > self.delivered {
+                let skip = (self.delivered - start) as usize;
+                on_event(TcpStreamEvent::Data{ key: *key, offset: self.delivered, data: &segment[skip..] });
+                self.delivered = end;
+            }
+        }
+    }
+
+    /// Reports the bytes till the next buffered segment as missing & delivers
+    /// the following buffered segments.
+    fn skip_gap<F: FnMut(TcpStreamEvent)>(&mut self, key: &TcpStreamKey, on_event: &mut F) {
+        if let Some(start) = self.segments.keys().next().copied() {
+            if start > self.delivered {
+                on_event(TcpStreamEvent::Gap{ key: *key, offset: self.delivered, len: start - self.delivered });
+                self.delivered = start;
+            }
+            self.deliver_buffered(key, on_event);
+        }
+    }
+
+    /// Delivers all buffered segments (reporting the missing bytes between them as gaps).
+    fn flush<F: FnMut(TcpStreamEvent)>(&mut self, key: &TcpStreamKey, on_event: &mut F) {
+        while !self.segments.is_empty() {
+            self.skip_gap(key, on_event);
+        }
+    }
+
+    /// Generates the FIN event if all bytes before the FIN were delivered.
+    fn check_fin<F: FnMut(TcpStreamEvent)>(&mut self, key: &TcpStreamKey, on_event: &mut F) {
+        if let Some(fin) = self.fin {
+            if !self.finished && self.delivered >= fin {
+                self.finished = true;
+                on_event(TcpStreamEvent::Fin{ key: *key });
+            }
+        }
+    }
+}
@@ -0,0 +1,245 @@
+use super::*;
+
+use std::io;
+
+/// Control flags of the segments generated by a [`TcpTeardownBuilder`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum TcpTeardownKind {
+    /// Segments with only the RST flag set (the ACK flag is still set
+    /// for segments toward the sender if the observed segment had no
+    /// ACK flag, as required by rfc793).
+    Rst,
+    /// Segments with the RST & ACK flags set.
+    RstAck,
+    /// Segments with the FIN & ACK flags set (graceful close).
+    FinAck,
+}
+
+/// Errors that can occur when generating TCP teardown segments.
+#[derive(Debug)]
+pub enum TcpTeardownError {
+    /// Error if the observed packet does not contain an IP header & TCP header.
+    NoTcpHeader,
+    /// Error while writing the segment.
+    WriteError(WriteError),
+}
+
+impl fmt::Display for TcpTeardownError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use TcpTeardownError::*;
+        match self {
+            NoTcpHeader => {
+                write!(f, "TcpTeardownError: The observed packet does not contain an IP header & TCP header.")
+            },
+            WriteError(err) => err.fmt(f),
+        }
+    }
+}
+
+impl Error for TcpTeardownError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            TcpTeardownError::NoTcpHeader => None,
+            TcpTeardownError::WriteError(ref err) => Some(err),
+        }
+    }
+}
+
+impl From<WriteError> for TcpTeardownError {
+    fn from(err: WriteError) -> TcpTeardownError {
+        TcpTeardownError::WriteError(err)
+    }
+}
+
+impl From<io::Error> for TcpTeardownError {
+    fn from(err: io::Error) -> TcpTeardownError {
+        TcpTeardownError::WriteError(WriteError::IoError(err))
+    }
+}
+
+impl From<ValueError> for TcpTeardownError {
+    fn from(err: ValueError) -> TcpTeardownError {
+        TcpTeardownError::WriteError(WriteError::ValueError(err))
+    }
+}
+
+/// Generates segments tearing down the TCP connection of an observed packet
+/// (e.g. for an intrusion prevention system).
+///
+/// For each observed segment a segment toward the sender and a segment toward
+/// the receiver of the observed segment can be generated. The sequence &
+/// acknowledgment numbers are derived from the observed segment
+/// (`SEG.LEN` contains the payload length defined by the IP header plus one for
+/// the SYN & FIN flags):
+///
+/// * Toward the sender (swapped addresses & ports): `SEQ = SEG.ACK` & `ACK = SEG.SEQ + SEG.LEN`.
+///   If the observed segment has no ACK flag `SEQ` is set to 0 & the ACK flag is always
+///   set (rfc793 "Reset Generation").
+/// * Toward the receiver (same addresses & ports as the observed segment): `SEQ = SEG.SEQ + SEG.LEN`
+///   & `ACK = SEG.ACK`. The window size is copied from the observed segment.
+///
+/// The ethernet II & vlan headers of the observed packet are copied (with swapped MAC
+/// addresses for segments toward the sender). A new IPv4 or IPv6 header (without options
+/// & extension headers) is written. All lengths & checksums are calculated.
+///
+/// # Example
+///
+/// ```
+/// # use etherparse::*;
+/// # let builder = PacketBuilder::
+/// #    ethernet2([2,3,4,5,6,7], [8,9,10,11,12,13])
+/// #    .ipv4([192,168,1,1], [192,168,1,2], 20)
+/// #    .tcp(1234, 80, 1000, 4000)
+/// #    .ack(2000);
+/// # let mut packet = Vec::<u8>::with_capacity(builder.size(4));
+/// # builder.write(&mut packet, &[1,2,3,4]).unwrap();
+/// let observed = SlicedPacket::from_ethernet(&packet).unwrap();
+///
+/// let builder = TcpTeardownBuilder::new(TcpTeardownKind::RstAck);
+/// let mut to_sender = Vec::new();
+/// builder.write_to_sender(&mut to_sender, &observed).unwrap();
+/// let mut to_receiver = Vec::new();
+/// builder.write_to_receiver(&mut to_receiver, &observed).unwrap();
+///
+/// let reset = PacketHeaders::from_ethernet_slice(&to_sender).unwrap();
+/// match reset.transport {
+///     Some(TransportHeader::Tcp(tcp)) => {
+///         assert!(tcp.rst);
+///         assert_eq!(2000, tcp.sequence_number);
+///         assert_eq!(1004, tcp.acknowledgment_number);
+///     },
+///     _ => panic!("expected tcp header"),
+/// }
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TcpTeardownBuilder {
+    kind: TcpTeardownKind,
+    time_to_live: u8,
+}
+
+impl TcpTeardownBuilder {
+    /// Creates a builder generating segments of the given kind with a time
+    /// to live (hop limit) of 64.
+    pub fn new(kind: TcpTeardownKind) -> TcpTeardownBuilder {
+        TcpTeardownBuilder {
+            kind,
+            time_to_live: 64,
+        }
+    }
+
+    /// Set the time to live (IPv4) or hop limit (IPv6) of the generated segments.
+    pub fn time_to_live(mut self, value: u8) -> TcpTeardownBuilder {
+        self.time_to_live = value;
+        self
+    }
+
+    /// Writes the segment toward the sender of the observed segment.
+    pub fn write_to_sender<T: io::Write + Sized>(&self, writer: &mut T, observed: &SlicedPacket) -> Result<(), TcpTeardownError> {
+        let tcp = observed_tcp(observed)?;
+        let next_sequence_number = tcp.sequence_number().wrapping_add(segment_len(&tcp, observed));
+        let (sequence_number, ack) = if tcp.ack() {
+            (tcp.acknowledgment_number(), TcpTeardownKind::Rst != self.kind)
+        } else {
+            (0, true)
+        };
+        let mut header = TcpHeader::new(tcp.destination_port(), tcp.source_port(), sequence_number, 0);
+        self.set_flags(&mut header, ack, next_sequence_number);
+        self.write(writer, observed, true, header)
+    }
+
+    /// Writes the segment toward the receiver of the observed segment.
+    pub fn write_to_receiver<T: io::Write + Sized>(&self, writer: &mut T, observed: &SlicedPacket) -> Result<(), TcpTeardownError> {
+        let tcp = observed_tcp(observed)?;
+        let mut header = TcpHeader::new(
+            tcp.source_port(),
+            tcp.destination_port(),
+            tcp.sequence_number().wrapping_add(segment_len(&tcp, observed)),
+            tcp.window_size()
+        );
+        let ack = tcp.ack() && TcpTeardownKind::Rst != self.kind;
+        self.set_flags(&mut header, ack, tcp.acknowledgment_number());
+        self.write(writer, observed, false, header)
+    }
+
+    fn set_flags(&self, header: &mut TcpHeader, ack: bool, acknowledgment_number: u32) {
+        match self.kind {
+            TcpTeardownKind::Rst | TcpTeardownKind::RstAck => header.rst = true,
+            TcpTeardownKind::FinAck => header.fin = true,
+        }
+        if ack {
+            header.ack = true;
+            header.acknowledgment_number = acknowledgment_number;
+        }
+    }
+
+    fn write<T: io::Write + Sized>(&self, writer: &mut T, observed: &SlicedPacket, swap: bool, mut tcp: TcpHeader) -> Result<(), TcpTeardownError> {
+        // link layer
+        if let Some(LinkSlice::Ethernet2(eth)) = &observed.link {
+            let (source, destination) = if swap {
+                (eth.destination(), eth.source())
+            } else {
+                (eth.source(), eth.destination())
+            };
+            Ethernet2Header {
+                source,
+                destination,
+                ether_type: eth.ether_type(),
+            }.write(writer)?;
+        }
+        if let Some(vlan) = &observed.vlan {
+            vlan.to_header().write(writer)?;
+        }
+
+        // ip & tcp header
+        match &observed.ip {
+            Some(InternetSlice::Ipv4(ip, _)) => {
+                let (source, destination) = if swap {
+                    (ip.destination(), ip.source())
+                } else {
+                    (ip.source(), ip.destination())
+                };
+                tcp.checksum = tcp.calc_checksum_ipv4_raw(source, destination, &[])?;
+                Ipv4Header::new(tcp.header_len(), self.time_to_live, ip_number::TCP, source, destination)
+                    .write(writer)?;
+            },
+            Some(InternetSlice::Ipv6(ip, _)) => {
+                let (source, destination) = if swap {
+                    (ip.destination(), ip.source())
+                } else {
+                    (ip.source(), ip.destination())
+                };
+                tcp.checksum = tcp.calc_checksum_ipv6_raw(source, destination, &[])?;
+                Ipv6Header {
+                    traffic_class: 0,
+                    flow_label: 0,
+                    payload_length: tcp.header_len(),
+                    next_header: ip_number::TCP,
+                    hop_limit: self.time_to_live,
+                    source,
+                    destination,
+                }.write(writer)?;
+            },
+            // checked by observed_tcp
+            None => return Err(TcpTeardownError::NoTcpHeader),
+        }
+        tcp.write(writer)?;
+        Ok(())
+    }
+}
+
+/// Returns the tcp header of the observed packet.
+fn observed_tcp<'a>(observed: &SlicedPacket<'a>) -> Result<TcpHeaderSlice<'a>, TcpTeardownError> {
+    match (&observed.ip, &observed.transport) {
+        (Some(_), Some(TransportSlice::Tcp(tcp))) => Ok(tcp.clone()),
+        _ => Err(TcpTeardownError::NoTcpHeader),
+    }
+}
+
+/// Length of the segment in sequence space (payload plus SYN & FIN flags).
+///
+/// Data following the ip payload (e.g. ethernet padding) is not counted.
+fn segment_len(tcp: &TcpHeaderSlice, observed: &SlicedPacket) -> u32 {
+    (observed.limit_to_ip_payload(observed.payload).len() as u32)
+        .wrapping_add(u32::from(tcp.syn()))
+        .wrapping_add(u32::from(tcp.fin()))
+}
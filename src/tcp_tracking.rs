@@ -0,0 +1,534 @@
+use super::*;
+
+use std::collections::{BTreeSet, HashMap};
+
+/// State of a TCP endpoint (rfc9293).
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum TcpState {
+    Listen,
+    SynSent,
+    SynReceived,
+    Established,
+    FinWait1,
+    FinWait2,
+    CloseWait,
+    Closing,
+    LastAck,
+    TimeWait,
+    Closed,
+}
+
+/// States of a connection tracked by a [`TcpConnectionTracker`].
+///
+/// The states of the endpoints are derived from the observed segments
+/// assuming that all observed segments reach their destination.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct TcpConnectionInfo {
+    /// Key of the direction from the initiator (sender of the first SYN) to the responder.
+    pub key: TcpStreamKey,
+    /// State of the endpoint that initiated the connection.
+    pub initiator_state: TcpState,
+    /// State of the endpoint that responded to the initial SYN.
+    pub responder_state: TcpState,
+    /// Time the last valid segment of the connection was observed.
+    pub last_activity: u64,
+}
+
+/// Errors for segments rejected by a [`TcpConnectionTracker`] (rejected segments
+/// do not change the state of the tracked connection).
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum TcpTrackingError {
+    /// The packet does not contain an IP header & TCP header.
+    NoTcpHeader,
+    /// Invalid combination of flags (SYN together with FIN or RST, or none of SYN, ACK & RST set).
+    InvalidFlags,
+    /// Segment (other then an initial SYN) of a connection that is not tracked.
+    UnknownConnection,
+    /// Segment not allowed for a sender in the given state (e.g. a SYN on an established
+    /// connection or data after a FIN).
+    UnexpectedSegment{ state: TcpState },
+    /// Data sent by the responder before the three way handshake was completed.
+    DataBeforeHandshake,
+    /// The sequence numbers of the segment are outside of the receive window.
+    OutOfWindow,
+    /// The acknowledgment number acknowledges unsent data or is too old.
+    InvalidAcknowledgment,
+}
+
+impl fmt::Display for TcpTrackingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use TcpTrackingError::*;
+        match self {
+            NoTcpHeader => write!(f, "TcpTrackingError: The packet does not contain an IP header & TCP header."),
+            InvalidFlags => write!(f, "TcpTrackingError: Invalid combination of TCP flags (SYN together with FIN or RST, or none of SYN, ACK & RST set)."),
+            UnknownConnection => write!(f, "TcpTrackingError: Segment (other then an initial SYN) of a connection that is not tracked."),
+            UnexpectedSegment{ state } => write!(f, "TcpTrackingError: Unexpected segment from an endpoint in the state {:?}.", state),
+            DataBeforeHandshake => write!(f, "TcpTrackingError: Data sent before the three way handshake was completed."),
+            OutOfWindow => write!(f, "TcpTrackingError: Sequence numbers of the segment are outside of the receive window."),
+            InvalidAcknowledgment => write!(f, "TcpTrackingError: Acknowledgment number acknowledges unsent data or is too old."),
+        }
+    }
+}
+
+impl Error for TcpTrackingError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        None
+    }
+}
+
+/// Limits & idle timeouts of a [`TcpConnectionTracker`] (the timeouts are in the
+/// same unit as the `now` values passed to the tracker).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TcpConnectionTrackerConfig {
+    /// Maximum number of tracked connections (the least recently active
+    /// connection gets evicted if a new connection exceeds the limit).
+    pub max_connections: usize,
+    /// Timeout of connections with an endpoint in the LISTEN, SYN-SENT or SYN-RECEIVED state.
+    pub handshake_timeout: u64,
+    /// Timeout of connections with both endpoints in the ESTABLISHED state.
+    pub established_timeout: u64,
+    /// Timeout of connections that are being closed.
+    pub closing_timeout: u64,
+    /// Timeout of connections with endpoints only in the TIME-WAIT & CLOSED states.
+    pub time_wait_timeout: u64,
+    /// Timeout of connections with both endpoints in the CLOSED state.
+    pub closed_timeout: u64,
+}
+
+impl Default for TcpConnectionTrackerConfig {
+    fn default() -> TcpConnectionTrackerConfig {
+        TcpConnectionTrackerConfig {
+            max_connections: 65536,
+            handshake_timeout: 120_000,
+            established_timeout: 432_000_000,
+            closing_timeout: 120_000,
+            time_wait_timeout: 120_000,
+            closed_timeout: 10_000,
+        }
+    }
+}
+
+/// Tracks the states of TCP connections and validates the observed segments
+/// (e.g. for a firewall).
+///
+/// Besides the flags the sequence & acknowledgment numbers of each segment are
+/// validated against the windows advertised by the endpoints (including window
+/// scaling).
+///
+/// # Example
+///
+/// ```
+/// # use etherparse::*;
+/// # fn segment(builder: PacketBuilderStep<TcpHeader>) -> Vec<u8> {
+/// #     let mut result = Vec::<u8>::with_capacity(builder.size(0));
+/// #     builder.write(&mut result, &[]).unwrap();
+/// #     result
+/// # }
+/// # let syn = segment(PacketBuilder::ipv4([192,168,1,1], [192,168,1,2], 20).tcp(1234, 80, 1000, 4000).syn());
+/// # let syn_ack = segment(PacketBuilder::ipv4([192,168,1,2], [192,168,1,1], 20).tcp(80, 1234, 5000, 4000).syn().ack(1001));
+/// # let ack = segment(PacketBuilder::ipv4([192,168,1,1], [192,168,1,2], 20).tcp(1234, 80, 1001, 4000).ack(5001));
+/// let mut tracker = TcpConnectionTracker::new(TcpConnectionTrackerConfig::default());
+///
+/// tracker.process(&SlicedPacket::from_ip(&syn).unwrap(), 0).unwrap();
+/// tracker.process(&SlicedPacket::from_ip(&syn_ack).unwrap(), 1).unwrap();
+/// let info = tracker.process(&SlicedPacket::from_ip(&ack).unwrap(), 2).unwrap();
+///
+/// assert_eq!(TcpState::Established, info.initiator_state);
+/// assert_eq!(TcpState::Established, info.responder_state);
+/// ```
+#[derive(Clone, Debug)]
+pub struct TcpConnectionTracker {
+    config: TcpConnectionTrackerConfig,
+    /// Connections by the key of the smaller direction.
+    connections: HashMap<TcpStreamKey, Connection>,
+    /// Keys of the connections ordered by the time of their last activity
+    /// (used to find the least recently active connection without iterating
+    /// over all connections).
+    by_last: BTreeSet<(u64, TcpStreamKey)>,
+}
+
+#[derive(Clone, Debug)]
+struct Connection {
+    info: TcpConnectionInfo,
+    /// Windows of the initiator (index 0) & responder (index 1).
+    windows: [Window;2],
+}
+
+/// Sequence space tracking of one endpoint.
+#[derive(Clone, Copy, Debug, Default)]
+struct Window {
+    /// Initial sequence number.
+    isn: u32,
+    /// Sequence number after the last sent byte.
+    end: u32,
+    /// Maximum sequence number allowed by the windows advertised by the peer.
+    max_end: u32,
+    /// Maximum (scaled) window advertised by the endpoint.
+    max_win: u32,
+    /// Window scale shift (`None` if window scaling is not used).
+    scale: Option<u8>,
+    /// Sequence number of the FIN (if one was sent).
+    fin: Option<u32>,
+}
+
+/// Values of the observed segment relevant for the tracking.
+struct Segment {
+    sequence_number: u32,
+    acknowledgment_number: Option<u32>,
+    syn: bool,
+    fin: bool,
+    rst: bool,
+    payload_len: u32,
+    window_size: u16,
+    window_scale: Option<u8>,
+}
+
+impl TcpConnectionTracker {
+    /// Creates a tracker without any connections.
+    pub fn new(config: TcpConnectionTrackerConfig) -> TcpConnectionTracker {
+        TcpConnectionTracker {
+            config,
+            connections: HashMap::new(),
+            by_last: BTreeSet::new(),
+        }
+    }
+
+    /// Limits & timeouts of the tracker.
+    pub fn config(&self) -> &TcpConnectionTrackerConfig {
+        &self.config
+    }
+
+    /// Number of currently tracked connections.
+    pub fn connection_count(&self) -> usize {
+        self.connections.len()
+    }
+
+    /// Returns the connection a key of either direction belongs to.
+    pub fn connection(&self, key: &TcpStreamKey) -> Option<TcpConnectionInfo> {
+        self.connections.get(&connection_key(key)).map(|c| c.info)
+    }
+
+    /// Validates a packet observed at the time `now` & updates the state of its connection.
+    ///
+    /// A new connection is only tracked for an initial SYN (SYN flag without ACK flag).
+    /// Connections that exceeded their idle timeout are treated as not tracked.
+    pub fn process(&mut self, packet: &SlicedPacket, now: u64) -> Result<TcpConnectionInfo, TcpTrackingError> {
+        use TcpTrackingError::*;
+        let key = TcpStreamKey::from_sliced_packet(packet).ok_or(NoTcpHeader)?;
+        let tcp = match &packet.transport {
+            Some(TransportSlice::Tcp(tcp)) => tcp,
+            _ => return Err(NoTcpHeader),
+        };
+        let segment = Segment {
+            sequence_number: tcp.sequence_number(),
+            acknowledgment_number: if tcp.ack() {
+                Some(tcp.acknowledgment_number())
+            } else {
+                None
+            },
+            syn: tcp.syn(),
+            fin: tcp.fin(),
+            rst: tcp.rst(),
+            // data following the ip payload (e.g. ethernet padding) is not part of the segment
+            payload_len: packet.limit_to_ip_payload(packet.payload).len() as u32,
+            window_size: tcp.window_size(),
+            window_scale: tcp.options_iterator().find_map(|option| match option {
+                Ok(TcpOptionElement::WindowScale(value)) => Some(std::cmp::min(value, 14)),
+                _ => None,
+            }),
+        };
+        if (segment.syn && (segment.fin || segment.rst)) ||
+           !(segment.syn || segment.rst || segment.acknowledgment_number.is_some())
+        {
+            return Err(InvalidFlags);
+        }
+
+        // remove the connection if it timed out
+        let connection_key = connection_key(&key);
+        if let Some(connection) = self.connections.get(&connection_key) {
+            if now.saturating_sub(connection.info.last_activity) >= self.timeout(&connection.info) {
+                self.remove(&connection_key);
+            }
+        }
+
+        let initial_syn = segment.syn && segment.acknowledgment_number.is_none();
+        let reopen = match self.connections.get(&connection_key) {
+            Some(connection) => initial_syn && connection.reopened_by(&key, &segment),
+            None => {
+                if !initial_syn {
+                    return Err(UnknownConnection);
+                }
+                if self.connections.len() >= self.config.max_connections {
+                    self.evict_least_recently_active();
+                }
+                true
+            },
+        };
+        if reopen {
+            let connection = Connection::new(key, &segment, now);
+            let info = connection.info;
+            self.remove(&connection_key);
+            self.by_last.insert((now, connection_key));
+            self.connections.insert(connection_key, connection);
+            return Ok(info);
+        }
+
+        let connection = self.connections.get_mut(&connection_key).unwrap();
+        connection.process(&key, &segment)?;
+        self.by_last.remove(&(connection.info.last_activity, connection_key));
+        self.by_last.insert((now, connection_key));
+        connection.info.last_activity = now;
+        Ok(connection.info)
+    }
+
+    /// Removes all connections that exceeded their idle timeout & returns them.
+    pub fn evict_idle(&mut self, now: u64) -> Vec<TcpConnectionInfo> {
+        let mut idle: Vec<TcpConnectionInfo> = self.connections.values()
+            .filter(|c| now.saturating_sub(c.info.last_activity) >= self.timeout(&c.info))
+            .map(|c| c.info)
+            .collect();
+        idle.sort_by_key(|info| info.key);
+        for info in &idle {
+            self.remove(&connection_key(&info.key));
+        }
+        idle
+    }
+
+    /// Idle timeout of a connection based on the states of its endpoints.
+    fn timeout(&self, info: &TcpConnectionInfo) -> u64 {
+        use TcpState::*;
+        let handshake = |state| matches!(state, Listen | SynSent | SynReceived);
+        let closed = |state| matches!(state, TimeWait | Closed);
+        match (info.initiator_state, info.responder_state) {
+            (Closed, Closed) => self.config.closed_timeout,
+            (a, b) if handshake(a) || handshake(b) => self.config.handshake_timeout,
+            (Established, Established) => self.config.established_timeout,
+            (a, b) if closed(a) && closed(b) => self.config.time_wait_timeout,
+            _ => self.config.closing_timeout,
+        }
+    }
+
+    fn evict_least_recently_active(&mut self) {
+        let oldest = self.by_last.iter().next().map(|(_, key)| *key);
+        if let Some(key) = oldest {
+            self.remove(&key);
+        }
+    }
+
+    /// Removes a connection (by the key of the smaller direction).
+    fn remove(&mut self, connection_key: &TcpStreamKey) {
+        if let Some(connection) = self.connections.remove(connection_key) {
+            self.by_last.remove(&(connection.info.last_activity, *connection_key));
+        }
+    }
+}
+
+impl Connection {
+    /// Creates a connection based on an initial SYN.
+    fn new(key: TcpStreamKey, syn: &Segment, now: u64) -> Connection {
+        Connection {
+            info: TcpConnectionInfo {
+                key,
+                initiator_state: TcpState::SynSent,
+                responder_state: TcpState::Listen,
+                last_activity: now,
+            },
+            windows: [Window::from_syn(syn), Window::default()],
+        }
+    }
+
+    /// Returns true if an initial SYN from the sender with the given key starts a
+    /// new connection (previous connection closed or SYN with a new initial
+    /// sequence number).
+    fn reopened_by(&self, key: &TcpStreamKey, syn: &Segment) -> bool {
+        use TcpState::*;
+        match (self.info.initiator_state, self.info.responder_state) {
+            (TimeWait, _) | (Closed, _) if matches!(self.info.responder_state, TimeWait | Closed) => true,
+            (SynSent, Listen) => *key == self.info.key && syn.sequence_number != self.windows[0].isn,
+            _ => false,
+        }
+    }
+
+    fn state_mut(&mut self, index: usize) -> &mut TcpState {
+        if 0 == index {
+            &mut self.info.initiator_state
+        } else {
+            &mut self.info.responder_state
+        }
+    }
+
+    /// Validates a segment (other then a new initial SYN) & updates the states.
+    fn process(&mut self, key: &TcpStreamKey, segment: &Segment) -> Result<(), TcpTrackingError> {
+        use TcpState::*;
+        use TcpTrackingError::*;
+
+        let (s, r) = if *key == self.info.key { (0, 1) } else { (1, 0) };
+        let sender_state = *self.state_mut(s);
+        let receiver_state = *self.state_mut(r);
+        let sequence_number = segment.sequence_number;
+
+        if segment.rst {
+            let sender = &self.windows[s];
+            let receiver = &self.windows[r];
+            let valid = if SynSent == receiver_state {
+                // reset as answer to a SYN
+                segment.acknowledgment_number == Some(receiver.end)
+            } else {
+                seq_le(sender.end.wrapping_sub(std::cmp::max(receiver.max_win, 1)), sequence_number) &&
+                seq_le(sequence_number, sender.max_end)
+            };
+            if !valid {
+                return Err(OutOfWindow);
+            }
+            *self.state_mut(s) = Closed;
+            *self.state_mut(r) = Closed;
+            return Ok(());
+        }
+
+        if segment.syn {
+            return match (sender_state, receiver_state, segment.acknowledgment_number) {
+                // retransmitted initial SYN
+                (SynSent, _, None) if sequence_number == self.windows[s].isn => Ok(()),
+                // SYN-ACK
+                (Listen, SynSent, Some(ack)) => {
+                    if ack != self.windows[r].end {
+                        return Err(InvalidAcknowledgment);
+                    }
+                    let mut sender = Window::from_syn(segment);
+                    let receiver = &mut self.windows[r];
+                    if sender.scale.is_none() || receiver.scale.is_none() {
+                        sender.scale = None;
+                        receiver.scale = None;
+                    }
+                    // windows in SYN segments are never scaled
+                    receiver.max_end = ack.wrapping_add(std::cmp::max(u32::from(segment.window_size), 1));
+                    sender.max_end = sender.end.wrapping_add(std::cmp::max(receiver.max_win, 1));
+                    self.windows[s] = sender;
+                    *self.state_mut(s) = SynReceived;
+                    *self.state_mut(r) = Established;
+                    Ok(())
+                },
+                // retransmitted SYN-ACK
+                (SynReceived, _, Some(ack)) if sequence_number == self.windows[s].isn &&
+                                               ack == self.windows[r].isn.wrapping_add(1) => Ok(()),
+                _ => Err(UnexpectedSegment{ state: sender_state }),
+            };
+        }
+
+        // segments without SYN are only allowed after the SYN of the sender was acknowledged
+        if let Listen | SynSent = sender_state {
+            return Err(UnexpectedSegment{ state: sender_state });
+        }
+        if SynReceived == sender_state && segment.payload_len > 0 {
+            return Err(DataBeforeHandshake);
+        }
+        let sender = self.windows[s];
+        let receiver = self.windows[r];
+        let data_end = sequence_number.wrapping_add(segment.payload_len);
+        if let Some(fin) = sender.fin {
+            if segment.payload_len > 0 && seq_lt(fin, data_end) {
+                return Err(UnexpectedSegment{ state: sender_state });
+            }
+        }
+        let end = data_end.wrapping_add(u32::from(segment.fin));
+        if !seq_le(sequence_number, sender.max_end) ||
+           !seq_le(sender.end.wrapping_sub(std::cmp::max(receiver.max_win, 1)), end)
+        {
+            return Err(OutOfWindow);
+        }
+        if let Some(ack) = segment.acknowledgment_number {
+            if !seq_le(ack, receiver.end) ||
+               !seq_le(receiver.end.wrapping_sub(std::cmp::max(sender.max_win, 66000)), ack)
+            {
+                return Err(InvalidAcknowledgment);
+            }
+        }
+
+        // update the windows
+        {
+            let sender = &mut self.windows[s];
+            sender.end = seq_max(sender.end, end);
+            if let Some(ack) = segment.acknowledgment_number {
+                let window = u32::from(segment.window_size) << sender.scale.unwrap_or(0);
+                sender.max_win = std::cmp::max(sender.max_win, window);
+                let receiver = &mut self.windows[r];
+                receiver.max_end = seq_max(receiver.max_end, ack.wrapping_add(std::cmp::max(window, 1)));
+            }
+        }
+
+        // acknowledgment of the SYN or FIN of the receiver
+        if let Some(ack) = segment.acknowledgment_number {
+            let receiver = self.windows[r];
+            let state = self.state_mut(r);
+            if SynReceived == *state && seq_lt(receiver.isn, ack) {
+                *state = Established;
+            }
+            if let Some(fin) = receiver.fin {
+                if seq_lt(fin, ack) {
+                    *state = match *state {
+                        FinWait1 => FinWait2,
+                        Closing => TimeWait,
+                        LastAck => Closed,
+                        other => other,
+                    };
+                }
+            }
+        }
+
+        // FIN of the sender
+        if segment.fin && self.windows[s].fin.is_none() {
+            self.windows[s].fin = Some(data_end);
+            let state = self.state_mut(s);
+            *state = match *state {
+                SynReceived | Established => FinWait1,
+                CloseWait => LastAck,
+                other => other,
+            };
+            let state = self.state_mut(r);
+            *state = match *state {
+                SynReceived | Established => CloseWait,
+                FinWait1 => Closing,
+                FinWait2 => TimeWait,
+                other => other,
+            };
+        }
+        Ok(())
+    }
+}
+
+impl Window {
+    /// Creates the window of an endpoint based on its SYN.
+    fn from_syn(syn: &Segment) -> Window {
+        let end = syn.sequence_number
+            .wrapping_add(1)
+            .wrapping_add(syn.payload_len);
+        Window {
+            isn: syn.sequence_number,
+            end,
+            max_end: end,
+            max_win: std::cmp::max(u32::from(syn.window_size), 1),
+            scale: syn.window_scale,
+            fin: None,
+        }
+    }
+}
+
+/// Key of the connection (key of the smaller direction).
+fn connection_key(key: &TcpStreamKey) -> TcpStreamKey {
+    std::cmp::min(*key, key.reversed())
+}
+
+/// Returns true if the sequence number `a` is before or equal to `b`.
+fn seq_le(a: u32, b: u32) -> bool {
+    (b.wrapping_sub(a) as i32) >= 0
+}
+
+/// Returns true if the sequence number `a` is before `b`.
+fn seq_lt(a: u32, b: u32) -> bool {
+    (b.wrapping_sub(a) as i32) > 0
+}
+
+/// Returns the later of the two sequence numbers.
+fn seq_max(a: u32, b: u32) -> u32 {
+    if seq_lt(a, b) { b } else { a }
+}
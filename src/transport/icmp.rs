@@ -33,3 +33,134 @@ impl IcmpEchoHeader {
         }
     }
 }
+
+/// Header part of an "Extended Echo Request" message (RFC 8335) shared
+/// between ICMPv4 and ICMPv6.
+///
+/// The interface identification object that describes the probed interface
+/// is not part of the header and is stored in the payload of the message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ExtendedEchoRequestHeader {
+    /// An identifier to aid in matching Extended Echo Replies to Extended Echo Requests. May be zero.
+    pub id: u16,
+    /// A sequence number to aid in matching Extended Echo Replies to Extended Echo Requests. May be zero.
+    pub seq: u8,
+    /// Set if the probed interface resides on the proxy node ("L" bit).
+    pub local: bool,
+}
+
+impl ExtendedEchoRequestHeader {
+    /// Return the id, seq & flags encoded to the on the wire format.
+    #[inline]
+    pub fn to_bytes(&self) -> [u8; 4] {
+        let id_be = self.id.to_be_bytes();
+        [id_be[0], id_be[1], self.seq, if self.local { 1 } else { 0 }]
+    }
+
+    /// Decodes the id, seq & flags from the on the wire format
+    /// (reserved bits are ignored).
+    #[inline]
+    pub fn from_bytes(bytes5to8: [u8; 4]) -> ExtendedEchoRequestHeader {
+        ExtendedEchoRequestHeader {
+            id: u16::from_be_bytes([bytes5to8[0], bytes5to8[1]]),
+            seq: bytes5to8[2],
+            local: 0 != bytes5to8[3] & 1,
+        }
+    }
+}
+
+/// Code values of an "Extended Echo Reply" message (RFC 8335).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ExtendedEchoReplyCode {
+    /// No Error
+    NoError = 0,
+    /// Malformed Query
+    MalformedQuery = 1,
+    /// No Such Interface
+    NoSuchInterface = 2,
+    /// No Such Table Entry
+    NoSuchTableEntry = 3,
+    /// Multiple Interfaces Satisfy Query
+    MultipleInterfacesSatisfyQuery = 4,
+}
+
+impl ExtendedEchoReplyCode {
+    /// Tries to convert a code [`u8`] value to a [`ExtendedEchoReplyCode`] value.
+    ///
+    /// Returns [`None`] in case the code value is not known as an extended echo reply code.
+    #[inline]
+    pub fn from_u8(code_u8: u8) -> Option<ExtendedEchoReplyCode> {
+        use ExtendedEchoReplyCode::*;
+        match code_u8 {
+            0 => Some(NoError),
+            1 => Some(MalformedQuery),
+            2 => Some(NoSuchInterface),
+            3 => Some(NoSuchTableEntry),
+            4 => Some(MultipleInterfacesSatisfyQuery),
+            _ => None,
+        }
+    }
+
+    /// Returns the [`u8`] value of the code.
+    #[inline]
+    pub fn code_u8(&self) -> u8 {
+        *self as u8
+    }
+}
+
+/// Header part of an "Extended Echo Reply" message (RFC 8335) shared
+/// between ICMPv4 and ICMPv6.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ExtendedEchoReplyHeader {
+    /// Code of the reply.
+    pub code: ExtendedEchoReplyCode,
+    /// Identifier copied from the Extended Echo Request.
+    pub id: u16,
+    /// Sequence number copied from the Extended Echo Request.
+    pub seq: u8,
+    /// State of the neighbor cache entry of the probed interface (only
+    /// the lower 3 bits are serialized, only meaningful if the probed
+    /// interface resides on a neighbor).
+    ///
+    /// Defined values are 0 (Reserved), 1 (Incomplete), 2 (Reachable),
+    /// 3 (Stale), 4 (Delay), 5 (Probe) & 6 (Failed).
+    pub state: u8,
+    /// Set if the probed interface is active ("A" bit).
+    pub active: bool,
+    /// Set if the probed interface runs IPv4 ("4" bit).
+    pub ipv4: bool,
+    /// Set if the probed interface runs IPv6 ("6" bit).
+    pub ipv6: bool,
+}
+
+impl ExtendedEchoReplyHeader {
+    /// Return the id, seq, state & flags encoded to the on the wire format.
+    #[inline]
+    pub fn to_bytes(&self) -> [u8; 4] {
+        let id_be = self.id.to_be_bytes();
+        [
+            id_be[0],
+            id_be[1],
+            self.seq,
+            ((self.state & 0b111) << 5)
+                | if self.active { 0b100 } else { 0 }
+                | if self.ipv4 { 0b10 } else { 0 }
+                | if self.ipv6 { 0b1 } else { 0 },
+        ]
+    }
+
+    /// Decodes the id, seq, state & flags from the on the wire format
+    /// (reserved bits are ignored).
+    #[inline]
+    pub fn from_bytes(code: ExtendedEchoReplyCode, bytes5to8: [u8; 4]) -> ExtendedEchoReplyHeader {
+        ExtendedEchoReplyHeader {
+            code,
+            id: u16::from_be_bytes([bytes5to8[0], bytes5to8[1]]),
+            seq: bytes5to8[2],
+            state: bytes5to8[3] >> 5,
+            active: 0 != bytes5to8[3] & 0b100,
+            ipv4: 0 != bytes5to8[3] & 0b10,
+            ipv6: 0 != bytes5to8[3] & 0b1,
+        }
+    }
+}
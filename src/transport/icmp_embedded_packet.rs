@@ -0,0 +1,149 @@
+use super::super::*;
+
+/// Slice containing the packet quoted in an ICMP error message (e.g. the
+/// packet that caused a "Destination Unreachable" or "Time Exceeded" message).
+///
+/// ICMP error messages only contain the leading bytes of the offending packet.
+/// Because of this the packet is sliced in a tolerant mode:
+///
+/// * Length fields in the IP header (`total_len` or `payload_length`) that
+///   exceed the quoted bytes are accepted.
+/// * Transport headers that are truncated are not decoded (`transport` is set to
+///   `None`). The quoted bytes of the transport header can still be found in
+///   `payload`.
+///
+/// The ports of the offending packet can be accessed via
+/// [`IcmpEmbeddedPacketSlice::source_port`] & [`IcmpEmbeddedPacketSlice::destination_port`]
+/// as long as the first 4 bytes of the TCP or UDP header are present.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct IcmpEmbeddedPacketSlice<'a> {
+    /// IPv4 or IPv6 header and IP extension headers of the quoted packet.
+    pub ip: InternetSlice<'a>,
+    /// IP number of the protocol following the ip header & extension headers.
+    pub ip_number: u8,
+    /// Transport header if it was completely contained in the quoted data
+    /// (`None` if the header was truncated, the payload is fragmented or
+    /// the protocol is not supported by etherparse).
+    pub transport: Option<TransportSlice<'a>>,
+    /// Quoted data after the last decoded header.
+    pub payload: &'a [u8],
+}
+
+impl<'a> IcmpEmbeddedPacketSlice<'a> {
+    /// Slices the packet quoted in an ICMP error message (starting with the IP header).
+    ///
+    /// # Errors
+    ///
+    /// Errors are only returned if the IP header or IP extension headers can not be
+    /// decoded (e.g. not enough data for the IP header or an unsupported IP version).
+    pub fn from_slice(slice: &'a [u8]) -> Result<IcmpEmbeddedPacketSlice<'a>, ReadError> {
+        use crate::ReadError::*;
+
+        if slice.is_empty() {
+            return Err(UnexpectedEndOfSlice(1));
+        }
+
+        // ip header & extension headers
+        let (ip, ip_number, fragmented, rest) = match slice[0] >> 4 {
+            4 => {
+                let header = Ipv4HeaderSlice::from_slice(slice)?;
+                let header_len = header.slice().len();
+                // cut the payload to the total length (if the total length is
+                // smaller then the quoted data)
+                let payload_len = usize::from(header.total_len()) - header_len;
+                let payload = &slice[header_len..];
+                let payload = if payload_len < payload.len() {
+                    &payload[..payload_len]
+                } else {
+                    payload
+                };
+                let (ext, ip_number, rest) = Ipv4ExtensionsSlice::from_slice(header.protocol(), payload)
+                    .map_err(|err| err.add_slice_offset(header_len))?;
+                let fragmented = header.is_fragmenting_payload();
+                (InternetSlice::Ipv4(header, ext), ip_number, fragmented, rest)
+            }
+            6 => {
+                let header = Ipv6HeaderSlice::from_slice(slice)?;
+                let header_len = header.slice().len();
+                let payload = &slice[header_len..];
+                let payload_len = ipv6_payload_len(&header, payload);
+                let payload = if payload_len < payload.len() {
+                    &payload[..payload_len]
+                } else {
+                    payload
+                };
+                let (ext, ip_number, rest) = Ipv6ExtensionsSlice::from_slice(header.next_header(), payload)
+                    .map_err(|err| err.add_slice_offset(header_len))?;
+                let fragmented = ext.is_fragmenting_payload();
+                (InternetSlice::Ipv6(header, ext), ip_number, fragmented, rest)
+            }
+            version => return Err(IpUnsupportedVersion(version)),
+        };
+
+        // transport header (only decoded if it was not truncated)
+        let transport = if fragmented {
+            None
+        } else {
+            use crate::TransportSlice::*;
+            match ip_number {
+                ip_number::UDP => UdpHeaderSlice::from_slice(rest).ok().map(Udp),
+                ip_number::TCP => TcpHeaderSlice::from_slice(rest).ok().map(Tcp),
+                ip_number::ICMP => Icmpv4Slice::from_slice(rest).ok().map(Icmpv4),
+                ip_number::IPV6_ICMP => Icmpv6Slice::from_slice(rest).ok().map(Icmpv6),
+                ip_number::IGMP => IgmpSlice::from_slice(rest).ok().map(Igmp),
+                _ => None,
+            }
+        };
+
+        let payload = match &transport {
+            Some(TransportSlice::Udp(udp)) => &rest[udp.slice().len()..],
+            Some(TransportSlice::Tcp(tcp)) => &rest[tcp.slice().len()..],
+            // the icmp & igmp slices contain their payload
+            Some(TransportSlice::Icmpv4(icmp)) => icmp.payload(),
+            Some(TransportSlice::Icmpv6(icmp)) => icmp.payload(),
+            Some(TransportSlice::Igmp(igmp)) => igmp.payload(),
+            Some(TransportSlice::Unknown(_)) | None => rest,
+        };
+
+        Ok(IcmpEmbeddedPacketSlice {
+            ip,
+            ip_number,
+            transport,
+            payload,
+        })
+    }
+
+    /// Returns the source port of the quoted TCP or UDP packet (also if the
+    /// transport header was truncated, as long as the port is present).
+    pub fn source_port(&self) -> Option<u16> {
+        self.ports().map(|(source, _)| source)
+    }
+
+    /// Returns the destination port of the quoted TCP or UDP packet (also if the
+    /// transport header was truncated, as long as the port is present).
+    pub fn destination_port(&self) -> Option<u16> {
+        self.ports().map(|(_, destination)| destination)
+    }
+
+    /// Returns the source & destination port of the quoted TCP or UDP packet.
+    fn ports(&self) -> Option<(u16, u16)> {
+        use crate::TransportSlice::*;
+        match &self.transport {
+            Some(Udp(udp)) => Some((udp.source_port(), udp.destination_port())),
+            Some(Tcp(tcp)) => Some((tcp.source_port(), tcp.destination_port())),
+            Some(_) => None,
+            None => match self.ip_number {
+                // truncated header (not decoded for fragmented payloads)
+                ip_number::UDP | ip_number::TCP
+                    if self.payload.len() >= 4 && !self.ip.is_fragmenting_payload() =>
+                {
+                    Some((
+                        u16::from_be_bytes([self.payload[0], self.payload[1]]),
+                        u16::from_be_bytes([self.payload[2], self.payload[3]]),
+                    ))
+                }
+                _ => None,
+            },
+        }
+    }
+}
@@ -0,0 +1,486 @@
+use super::super::*;
+
+use std::fmt::{Debug, Formatter};
+
+/// Module containing the constants for ICMP extension structures
+/// (rfc4884) and the extension objects decoded by etherparse.
+pub mod icmp_extension {
+    /// Version of the ICMP extension structure (rfc4884).
+    pub const VERSION: u8 = 2;
+    /// Length of the extension header (version, reserved bits & checksum).
+    pub const HEADER_LEN: usize = 4;
+    /// Length of an extension object header (length, class-num & c-type).
+    pub const OBJECT_HEADER_LEN: usize = 4;
+    /// `class_num` of a "MPLS Label Stack" object (rfc4950).
+    pub const CLASS_NUM_MPLS_LABEL_STACK: u8 = 1;
+    /// `class_num` of a "Interface Information" object (rfc5837).
+    pub const CLASS_NUM_INTERFACE_INFORMATION: u8 = 2;
+    /// `class_num` of a "Interface Identification" object (rfc8335).
+    pub const CLASS_NUM_INTERFACE_IDENTIFICATION: u8 = 3;
+    /// `c_type` of a "MPLS Label Stack" object containing incoming label stack entries (rfc4950).
+    pub const C_TYPE_MPLS_INCOMING_LABEL_STACK: u8 = 1;
+    /// `c_type` of a "Interface Identification" object identifying the interface by name (rfc8335).
+    pub const C_TYPE_INTERFACE_IDENTIFICATION_NAME: u8 = 1;
+    /// `c_type` of a "Interface Identification" object identifying the interface by index (rfc8335).
+    pub const C_TYPE_INTERFACE_IDENTIFICATION_INDEX: u8 = 2;
+    /// `c_type` of a "Interface Identification" object identifying the interface by address (rfc8335).
+    pub const C_TYPE_INTERFACE_IDENTIFICATION_ADDRESS: u8 = 3;
+    /// Address family number for IPv4 addresses (used in the "Interface Information" object).
+    pub const AFI_IPV4: u16 = 1;
+    /// Address family number for IPv6 addresses (used in the "Interface Information" object).
+    pub const AFI_IPV6: u16 = 2;
+}
+
+/// Slice containing an ICMP extension structure (rfc4884) appended after
+/// the original datagram in ICMP error messages (or the payload of an
+/// "Extended Echo Request").
+///
+/// ```text
+/// 0               1               2               3               4
+/// +---------------------------------------------------------------+
+/// |Version|       (Reserved)      |           Checksum            |
+/// +---------------------------------------------------------------+
+/// |                                                               |
+/// ...                     <extension objects>                   ...
+/// |                                                               |
+/// +---------------------------------------------------------------+
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct IcmpExtensionsSlice<'a> {
+    slice: &'a [u8],
+}
+
+impl<'a> IcmpExtensionsSlice<'a> {
+    /// Creates a slice containing an ICMP extension structure.
+    ///
+    /// # Errors
+    ///
+    /// * `ReadError::UnexpectedEndOfSlice` if the slice is smaller then the extension header (4 bytes).
+    /// * `ReadError::IcmpExtensionUnsupportedVersion` if the version is not 2.
+    pub fn from_slice(slice: &'a [u8]) -> Result<IcmpExtensionsSlice<'a>, ReadError> {
+        use crate::ReadError::*;
+        if slice.len() < icmp_extension::HEADER_LEN {
+            return Err(UnexpectedEndOfSlice(icmp_extension::HEADER_LEN));
+        }
+        let version = slice[0] >> 4;
+        if icmp_extension::VERSION != version {
+            return Err(IcmpExtensionUnsupportedVersion(version));
+        }
+        Ok(IcmpExtensionsSlice { slice })
+    }
+
+    /// Returns the version of the extension structure (always 2).
+    #[inline]
+    pub fn version(&self) -> u8 {
+        self.slice[0] >> 4
+    }
+
+    /// Returns the checksum field of the extension structure.
+    #[inline]
+    pub fn checksum(&self) -> u16 {
+        u16::from_be_bytes([self.slice[2], self.slice[3]])
+    }
+
+    /// Returns true if the checksum of the extension structure is valid.
+    ///
+    /// A checksum field with the value 0 indicates that no checksum was
+    /// transmitted (rfc4884), in this case `true` is returned as well.
+    pub fn is_checksum_valid(&self) -> bool {
+        0 == self.checksum()
+            || 0 == checksum::Sum16BitWords::new()
+                .add_slice(self.slice)
+                .ones_complement()
+    }
+
+    /// Returns an iterator over the extension objects.
+    #[inline]
+    pub fn objects(&self) -> IcmpExtensionObjectsIterator<'a> {
+        IcmpExtensionObjectsIterator::from_slice(&self.slice[icmp_extension::HEADER_LEN..])
+    }
+
+    /// Returns the slice containing the extension structure.
+    #[inline]
+    pub fn slice(&self) -> &'a [u8] {
+        self.slice
+    }
+}
+
+/// Splits the payload of an ICMP error message into the original datagram
+/// & the extension structure based on the "length" field (rfc4884).
+///
+/// `original_datagram_len` is the length field value converted to bytes.
+pub(crate) fn split_icmp_multipart_payload(
+    payload: &[u8],
+    original_datagram_len: usize,
+) -> (&[u8], Option<IcmpExtensionsSlice<'_>>) {
+    if 0 == original_datagram_len || payload.len() < original_datagram_len {
+        (payload, None)
+    } else {
+        (
+            &payload[..original_datagram_len],
+            IcmpExtensionsSlice::from_slice(&payload[original_datagram_len..]).ok(),
+        )
+    }
+}
+
+/// Decoded entry of a "MPLS Label Stack" extension object (rfc4950).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct IcmpMplsLabelStackEntry {
+    /// MPLS label (20 bits).
+    pub label: u32,
+    /// Traffic class (formerly "experimental use" bits, 3 bits).
+    pub traffic_class: u8,
+    /// Set if the entry is the bottom of the label stack.
+    pub bottom_of_stack: bool,
+    /// Time to live.
+    pub ttl: u8,
+}
+
+impl IcmpMplsLabelStackEntry {
+    /// Decodes a label stack entry from its on the wire format.
+    pub fn from_bytes(bytes: [u8; 4]) -> IcmpMplsLabelStackEntry {
+        IcmpMplsLabelStackEntry {
+            label: u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]) >> 4,
+            traffic_class: (bytes[2] >> 1) & 0b111,
+            bottom_of_stack: 0 != bytes[2] & 1,
+            ttl: bytes[3],
+        }
+    }
+
+    /// Returns the on the wire format of the entry (bits of the label &
+    /// traffic class that can not be represented are ignored).
+    pub fn to_bytes(&self) -> [u8; 4] {
+        let l = (self.label << 4).to_be_bytes();
+        [
+            l[1],
+            l[2],
+            l[3] | ((self.traffic_class & 0b111) << 1) | if self.bottom_of_stack { 1 } else { 0 },
+            self.ttl,
+        ]
+    }
+}
+
+/// IP address contained in an "Interface Information" extension object (rfc5837).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum IcmpInterfaceAddress {
+    Ipv4([u8; 4]),
+    Ipv6([u8; 16]),
+}
+
+/// Content of an "Interface Information" extension object (rfc5837).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct IcmpInterfaceInformation<'a> {
+    /// Role of the interface (0: incoming IP interface, 1: sub-IP component
+    /// of the incoming IP interface, 2: outgoing IP interface, 3: IP next hop).
+    pub role: u8,
+    /// Index of the interface (`ifIndex`).
+    pub if_index: Option<u32>,
+    /// IP address of the interface.
+    pub address: Option<IcmpInterfaceAddress>,
+    /// Name of the interface (UTF-8, including the zero padding).
+    pub name: Option<&'a [u8]>,
+    /// MTU of the interface.
+    pub mtu: Option<u32>,
+}
+
+/// Content of an "Interface Identification" extension object (rfc8335)
+/// identifying the interface probed by an "Extended Echo Request".
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum IcmpInterfaceIdentification<'a> {
+    /// Interface identified by name (including the zero padding).
+    Name(&'a [u8]),
+    /// Interface identified by its index (`ifIndex`).
+    Index(u32),
+    /// Interface identified by an address (`afi` being the address family number).
+    Address { afi: u16, address: &'a [u8] },
+}
+
+/// Extension object contained in an ICMP extension structure.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum IcmpExtensionObject<'a> {
+    /// "MPLS Label Stack" object (rfc4950), the entries can be decoded
+    /// with [`IcmpMplsLabelStackEntry::from_bytes`].
+    MplsLabelStack(&'a [[u8; 4]]),
+    /// "Interface Information" object (rfc5837).
+    InterfaceInformation(IcmpInterfaceInformation<'a>),
+    /// "Interface Identification" object (rfc8335).
+    InterfaceIdentification(IcmpInterfaceIdentification<'a>),
+    /// Object not decoded by etherparse (`payload` contains all bytes after
+    /// the object header).
+    Unknown { class_num: u8, c_type: u8, payload: &'a [u8] },
+}
+
+impl<'a> IcmpExtensionObject<'a> {
+    /// Returns the `class_num` of the object.
+    pub fn class_num(&self) -> u8 {
+        use IcmpExtensionObject::*;
+        match self {
+            MplsLabelStack(_) => icmp_extension::CLASS_NUM_MPLS_LABEL_STACK,
+            InterfaceInformation(_) => icmp_extension::CLASS_NUM_INTERFACE_INFORMATION,
+            InterfaceIdentification(_) => icmp_extension::CLASS_NUM_INTERFACE_IDENTIFICATION,
+            Unknown { class_num, .. } => *class_num,
+        }
+    }
+}
+
+/// Errors that can occour while reading the objects of an ICMP extension structure.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum IcmpExtensionObjectReadError {
+    /// Returned if not enough data is left in the slice to read an object.
+    UnexpectedEndOfSlice { expected_len: usize, actual_len: usize },
+
+    /// Returned if the length of an object is smaller then the object header or
+    /// does not match the content of the object.
+    UnexpectedLength { class_num: u8, c_type: u8, length: u16 },
+
+    /// Returned if an "Interface Information" object contains an address
+    /// with an unsupported address family.
+    UnsupportedAddressFamily(u16),
+}
+
+impl Error for IcmpExtensionObjectReadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        None
+    }
+}
+
+impl fmt::Display for IcmpExtensionObjectReadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use IcmpExtensionObjectReadError::*;
+        match self {
+            UnexpectedEndOfSlice { expected_len, actual_len } => {
+                write!(f, "IcmpExtensionObjectReadError: Not enough memory left in slice to read extension object (expected at least {} bytes, only {} bytes available).", expected_len, actual_len)
+            },
+            UnexpectedLength { class_num, c_type, length } => {
+                write!(f, "IcmpExtensionObjectReadError: Length value {} of the extension object with class-num {} and c-type {} does not match the content of the object.", length, class_num, c_type)
+            },
+            UnsupportedAddressFamily(afi) => {
+                write!(f, "IcmpExtensionObjectReadError: Address family {} in interface information object is not supported.", afi)
+            },
+        }
+    }
+}
+
+/// Allows iterating over the objects of an ICMP extension structure.
+///
+/// After an error was returned the iterator stops (all following calls
+/// to `next` return `None`).
+#[derive(Clone, Eq, PartialEq)]
+pub struct IcmpExtensionObjectsIterator<'a> {
+    objects: &'a [u8],
+}
+
+impl<'a> IcmpExtensionObjectsIterator<'a> {
+    /// Creates an objects iterator from a slice containing encoded extension
+    /// objects (the data after the extension header).
+    pub fn from_slice(objects: &'a [u8]) -> IcmpExtensionObjectsIterator<'a> {
+        IcmpExtensionObjectsIterator { objects }
+    }
+
+    /// Returns the non processed part of the objects slice.
+    pub fn rest(&self) -> &'a [u8] {
+        self.objects
+    }
+
+    /// Returns an error & moves the iterator to the end of the slice.
+    fn error(
+        &mut self,
+        err: IcmpExtensionObjectReadError,
+    ) -> Option<Result<IcmpExtensionObject<'a>, IcmpExtensionObjectReadError>> {
+        let rest = self.objects.len();
+        self.objects = &self.objects[rest..];
+        Some(Err(err))
+    }
+}
+
+/// Decodes the payload of an "Interface Information" object (returns `None`
+/// if the payload does not match the flags in the c-type).
+fn decode_interface_information(
+    c_type: u8,
+    payload: &[u8],
+) -> Result<Option<IcmpInterfaceInformation<'_>>, IcmpExtensionObjectReadError> {
+    fn read_u32(rest: &mut &[u8]) -> Option<u32> {
+        if rest.len() < 4 {
+            None
+        } else {
+            let value = u32::from_be_bytes([rest[0], rest[1], rest[2], rest[3]]);
+            *rest = &rest[4..];
+            Some(value)
+        }
+    }
+
+    let mut rest = payload;
+
+    let if_index = if 0 != c_type & 0b1000 {
+        match read_u32(&mut rest) {
+            Some(value) => Some(value),
+            None => return Ok(None),
+        }
+    } else {
+        None
+    };
+
+    let address = if 0 != c_type & 0b100 {
+        if rest.len() < 4 {
+            return Ok(None);
+        }
+        let afi = u16::from_be_bytes([rest[0], rest[1]]);
+        match afi {
+            icmp_extension::AFI_IPV4 => {
+                if rest.len() < 8 {
+                    return Ok(None);
+                }
+                let address = [rest[4], rest[5], rest[6], rest[7]];
+                rest = &rest[8..];
+                Some(IcmpInterfaceAddress::Ipv4(address))
+            }
+            icmp_extension::AFI_IPV6 => {
+                if rest.len() < 20 {
+                    return Ok(None);
+                }
+                let mut address = [0u8; 16];
+                address.copy_from_slice(&rest[4..20]);
+                rest = &rest[20..];
+                Some(IcmpInterfaceAddress::Ipv6(address))
+            }
+            afi => return Err(IcmpExtensionObjectReadError::UnsupportedAddressFamily(afi)),
+        }
+    } else {
+        None
+    };
+
+    let name = if 0 != c_type & 0b10 {
+        // the length octet includes itself
+        let len = match rest.first() {
+            Some(len) => usize::from(*len),
+            None => return Ok(None),
+        };
+        if len < 1 || rest.len() < len {
+            return Ok(None);
+        }
+        let name = &rest[1..len];
+        rest = &rest[len..];
+        Some(name)
+    } else {
+        None
+    };
+
+    let mtu = if 0 != c_type & 0b1 {
+        match read_u32(&mut rest) {
+            Some(value) => Some(value),
+            None => return Ok(None),
+        }
+    } else {
+        None
+    };
+
+    if !rest.is_empty() {
+        return Ok(None);
+    }
+
+    Ok(Some(IcmpInterfaceInformation {
+        role: c_type >> 6,
+        if_index,
+        address,
+        name,
+        mtu,
+    }))
+}
+
+impl<'a> Iterator for IcmpExtensionObjectsIterator<'a> {
+    type Item = Result<IcmpExtensionObject<'a>, IcmpExtensionObjectReadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        use icmp_extension::*;
+        use IcmpExtensionObject::*;
+        use IcmpExtensionObjectReadError::*;
+
+        if self.objects.is_empty() {
+            return None;
+        }
+
+        // determine & check the length
+        if self.objects.len() < OBJECT_HEADER_LEN {
+            let actual_len = self.objects.len();
+            return self.error(UnexpectedEndOfSlice { expected_len: OBJECT_HEADER_LEN, actual_len });
+        }
+        let length = u16::from_be_bytes([self.objects[0], self.objects[1]]);
+        let class_num = self.objects[2];
+        let c_type = self.objects[3];
+        let len = usize::from(length);
+        if len < OBJECT_HEADER_LEN {
+            // a length smaller then the header would lead to an infinite loop
+            return self.error(UnexpectedLength { class_num, c_type, length });
+        }
+        if self.objects.len() < len {
+            let actual_len = self.objects.len();
+            return self.error(UnexpectedEndOfSlice { expected_len: len, actual_len });
+        }
+        let payload = &self.objects[OBJECT_HEADER_LEN..len];
+
+        // decode the data
+        let result = match (class_num, c_type) {
+            (CLASS_NUM_MPLS_LABEL_STACK, C_TYPE_MPLS_INCOMING_LABEL_STACK) => {
+                if 0 != payload.len() & 0b11 {
+                    return self.error(UnexpectedLength { class_num, c_type, length });
+                }
+                // SAFETY: [u8;4] has an alignment of 1 & the length
+                // was checked to be a multiple of 4
+                MplsLabelStack(unsafe {
+                    std::slice::from_raw_parts(payload.as_ptr() as *const [u8; 4], payload.len() / 4)
+                })
+            }
+            (CLASS_NUM_INTERFACE_INFORMATION, _) => {
+                match decode_interface_information(c_type, payload) {
+                    Ok(Some(info)) => InterfaceInformation(info),
+                    Ok(None) => return self.error(UnexpectedLength { class_num, c_type, length }),
+                    Err(err) => return self.error(err),
+                }
+            }
+            (CLASS_NUM_INTERFACE_IDENTIFICATION, C_TYPE_INTERFACE_IDENTIFICATION_NAME) => {
+                InterfaceIdentification(IcmpInterfaceIdentification::Name(payload))
+            }
+            (CLASS_NUM_INTERFACE_IDENTIFICATION, C_TYPE_INTERFACE_IDENTIFICATION_INDEX) => {
+                if payload.len() != 4 {
+                    return self.error(UnexpectedLength { class_num, c_type, length });
+                }
+                InterfaceIdentification(IcmpInterfaceIdentification::Index(u32::from_be_bytes([
+                    payload[0], payload[1], payload[2], payload[3],
+                ])))
+            }
+            (CLASS_NUM_INTERFACE_IDENTIFICATION, C_TYPE_INTERFACE_IDENTIFICATION_ADDRESS) => {
+                // AFI (2 bytes), address length (1 byte), reserved (1 byte) & address
+                if payload.len() < 4 || payload.len() < 4 + usize::from(payload[2]) {
+                    return self.error(UnexpectedLength { class_num, c_type, length });
+                }
+                InterfaceIdentification(IcmpInterfaceIdentification::Address {
+                    afi: u16::from_be_bytes([payload[0], payload[1]]),
+                    address: &payload[4..4 + usize::from(payload[2])],
+                })
+            }
+            (class_num, c_type) => Unknown { class_num, c_type, payload },
+        };
+        self.objects = &self.objects[len..];
+        Some(Ok(result))
+    }
+}
+
+impl<'a> Debug for IcmpExtensionObjectsIterator<'a> {
+    fn fmt(&self, fmt: &mut Formatter) -> Result<(), std::fmt::Error> {
+        let mut list = fmt.debug_list();
+
+        // create a copy and iterate over all elements
+        for it in self.clone() {
+            match it {
+                Ok(e) => {
+                    list.entry(&e);
+                }
+                Err(e) => {
+                    list.entry(&Result::<(), IcmpExtensionObjectReadError>::Err(e.clone()));
+                }
+            }
+        }
+
+        list.finish()
+    }
+}
@@ -54,6 +54,12 @@ pub mod icmpv4 {
     /// ICMPv4 type value indicating a "Address Mask Reply (Deprecated)" message (defined in in [RFC 950](https://tools.ietf.org/html/rfc950), deprecated in [RFC 6918](https://tools.ietf.org/html/rfc6918)).
     pub const TYPE_ADDRESSREPLY: u8 = 18;
 
+    /// ICMPv4 type value indicating a "Extended Echo Request" message (defined in [RFC 8335](https://tools.ietf.org/html/rfc8335)).
+    pub const TYPE_EXT_ECHO_REQUEST: u8 = 42;
+
+    /// ICMPv4 type value indicating a "Extended Echo Reply" message (defined in [RFC 8335](https://tools.ietf.org/html/rfc8335)).
+    pub const TYPE_EXT_ECHO_REPLY: u8 = 43;
+
     /// ICMP destination unreachable code for "Net Unreachable" (defined in [RFC 792](https://tools.ietf.org/html/rfc792))
     pub const CODE_DST_UNREACH_NET: u8 = 0;
 
@@ -333,6 +339,128 @@ pub mod icmpv4 {
         }
     }
 
+    /// Header part of an ICMPv4 "Router Advertisement" message (defined in
+    /// [RFC 1256](https://tools.ietf.org/html/rfc1256)).
+    ///
+    /// The router address entries are not part of the header and can be
+    /// decoded via [`crate::Icmpv4Slice::router_advertisement_entries`].
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct RouterAdvertisementHeader {
+        /// Number of router addresses advertised in this message.
+        pub num_addrs: u8,
+        /// Number of 32-bit words of information per each router address
+        /// (2 in the version of the protocol described in RFC 1256).
+        pub addr_entry_size: u8,
+        /// Maximum number of seconds that the router addresses may be
+        /// considered valid.
+        pub lifetime: u16,
+    }
+
+    impl RouterAdvertisementHeader {
+        /// Returns the header fields encoded to the on the wire format.
+        #[inline]
+        pub fn to_bytes(&self) -> [u8; 4] {
+            let lifetime_be = self.lifetime.to_be_bytes();
+            [self.num_addrs, self.addr_entry_size, lifetime_be[0], lifetime_be[1]]
+        }
+
+        /// Decodes the header fields from the on the wire format.
+        #[inline]
+        pub fn from_bytes(bytes5to8: [u8; 4]) -> RouterAdvertisementHeader {
+            RouterAdvertisementHeader {
+                num_addrs: bytes5to8[0],
+                addr_entry_size: bytes5to8[1],
+                lifetime: u16::from_be_bytes([bytes5to8[2], bytes5to8[3]]),
+            }
+        }
+    }
+
+    /// Router address & preference level entry of an ICMPv4 "Router Advertisement"
+    /// message (defined in [RFC 1256](https://tools.ietf.org/html/rfc1256)).
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct RouterAdvertisementEntry {
+        /// The sending router's IP address on the interface from which
+        /// the message is sent.
+        pub router_address: [u8; 4],
+        /// The preferability of the router address as a default router
+        /// address, relative to other router addresses on the same subnet
+        /// (higher values mean more preferable).
+        pub preference_level: i32,
+    }
+
+    impl RouterAdvertisementEntry {
+        /// The size in bytes/octets of a serialized router address entry
+        /// (with an address entry size of 2).
+        pub const SERIALIZED_SIZE: usize = 8;
+
+        /// Returns the entry encoded to the on the wire format.
+        #[inline]
+        pub fn to_bytes(&self) -> [u8; 8] {
+            let a = self.router_address;
+            let p = self.preference_level.to_be_bytes();
+            [a[0], a[1], a[2], a[3], p[0], p[1], p[2], p[3]]
+        }
+
+        /// Decodes an entry from the on the wire format.
+        #[inline]
+        pub fn from_bytes(bytes: [u8; 8]) -> RouterAdvertisementEntry {
+            RouterAdvertisementEntry {
+                router_address: [bytes[0], bytes[1], bytes[2], bytes[3]],
+                preference_level: i32::from_be_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]),
+            }
+        }
+    }
+
+    /// Iterator over the router address entries of an ICMPv4 "Router Advertisement" message.
+    ///
+    /// Any additional words of an entry (in case the address entry size
+    /// is bigger then 2) are skipped.
+    #[derive(Clone, Debug, Eq, PartialEq)]
+    pub struct RouterAdvertisementEntriesIterator<'a> {
+        pub(crate) entry_len: usize,
+        pub(crate) rest: &'a [u8],
+    }
+
+    impl<'a> Iterator for RouterAdvertisementEntriesIterator<'a> {
+        type Item = RouterAdvertisementEntry;
+
+        fn next(&mut self) -> Option<RouterAdvertisementEntry> {
+            // note: entry_len is at least RouterAdvertisementEntry::SERIALIZED_SIZE (8)
+            if self.rest.len() < self.entry_len {
+                return None;
+            }
+            let r = self.rest;
+            let entry = RouterAdvertisementEntry::from_bytes([
+                r[0], r[1], r[2], r[3], r[4], r[5], r[6], r[7],
+            ]);
+            self.rest = &r[self.entry_len..];
+            Some(entry)
+        }
+    }
+
+    /// A ICMPv4 "Address Mask Request" or "Address Mask Reply" message
+    /// (defined in [RFC 950](https://tools.ietf.org/html/rfc950)).
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    pub struct AddressMaskMessage {
+        pub id: u16,
+        pub seq: u16,
+        pub address_mask: [u8; 4],
+    }
+
+    impl AddressMaskMessage {
+        /// The size in bytes/octets of an address mask request or reply message.
+        pub const SERIALIZED_SIZE: usize = 12;
+
+        /// Decodes the address mask message part of an ICMPv4 message.
+        pub fn from_bytes(bytes: [u8;8]) -> AddressMaskMessage {
+            AddressMaskMessage{
+                id: u16::from_be_bytes([bytes[0], bytes[1]]),
+                seq: u16::from_be_bytes([bytes[2], bytes[3]]),
+                address_mask: [bytes[4], bytes[5], bytes[6], bytes[7]],
+            }
+        }
+    }
+
     /// ICMPv4 "Parameter Problem" code value for "Pointer indicates the error".
     pub const CODE_PARAMETER_PROBLEM_POINTER_INDICATES_ERROR: u8 = 0;
 
@@ -570,6 +698,141 @@ pub enum Icmpv4Type {
     /// For the `Icmpv4Type::TimestampReply` type the entire ICMP packet is 
     /// contained within the header. The payload data is empty.
     TimestampReply(TimestampMessage),
+
+    /// Message sent by routers to advertise their addresses (defined in
+    /// [RFC 1256](https://tools.ietf.org/html/rfc1256)).
+    ///
+    /// # What is part of the header for `Icmpv4Type::RouterAdvertisement`?
+    ///
+    /// For the `Icmpv4Type::RouterAdvertisement` type the first 8 bytes/octets
+    /// of the ICMP packet are part of the header. The router address entries
+    /// are part of the payload and can be decoded via
+    /// [`Icmpv4Slice::router_advertisement_entries`].
+    ///
+    /// ```text
+    /// 0               1               2               3               4
+    /// +---------------------------------------------------------------+  -
+    /// |       9       |       0       |  checksum (in Icmpv4Header)   |  |
+    /// +---------------------------------------------------------------+  | part of header & type
+    /// |[v].num_addrs  |[v].addr_ent...|         [value].lifetime      |  ↓
+    /// +---------------------------------------------------------------+  -
+    /// |                       Router Address[1]                       |  |
+    /// +---------------------------------------------------------------+  |
+    /// |                      Preference Level[1]                      |  | part of payload
+    /// +---------------------------------------------------------------+  |
+    /// ...                          ...                              ...  ↓
+    /// +---------------------------------------------------------------+  -
+    /// ```
+    RouterAdvertisement(RouterAdvertisementHeader),
+
+    /// Message sent by hosts to prompt routers to send router advertisements
+    /// (defined in [RFC 1256](https://tools.ietf.org/html/rfc1256)).
+    ///
+    /// # What is part of the header for `Icmpv4Type::RouterSolicitation`?
+    ///
+    /// For the `Icmpv4Type::RouterSolicitation` type the first 8 bytes/octets
+    /// of the ICMP packet are part of the header. The `reserved` part is not
+    /// stored and droped.
+    ///
+    /// ```text
+    /// 0               1               2               3               4
+    /// +---------------------------------------------------------------+  -
+    /// |       10      |       0       |  checksum (in Icmpv4Header)   |  |
+    /// +---------------------------------------------------------------+  | part of header & type
+    /// |                           <reserved>                          |  ↓
+    /// +---------------------------------------------------------------+  -
+    /// ```
+    RouterSolicitation,
+
+    /// Deprecated "Information Request" message (defined in RFC792,
+    /// deprecated in [RFC 6918](https://tools.ietf.org/html/rfc6918)).
+    ///
+    /// # What is part of the header for `Icmpv4Type::InfoRequest`?
+    ///
+    /// For the `Icmpv4Type::InfoRequest` type the first 8 bytes/octets of the
+    /// ICMP packet (including the `id` and `seq` fields) are part of the header.
+    InfoRequest(IcmpEchoHeader),
+
+    /// Deprecated "Information Reply" message (defined in RFC792,
+    /// deprecated in [RFC 6918](https://tools.ietf.org/html/rfc6918)).
+    ///
+    /// # What is part of the header for `Icmpv4Type::InfoReply`?
+    ///
+    /// For the `Icmpv4Type::InfoReply` type the first 8 bytes/octets of the
+    /// ICMP packet (including the `id` and `seq` fields) are part of the header.
+    InfoReply(IcmpEchoHeader),
+
+    /// Deprecated "Address Mask Request" message (defined in
+    /// [RFC 950](https://tools.ietf.org/html/rfc950), deprecated in
+    /// [RFC 6918](https://tools.ietf.org/html/rfc6918)).
+    ///
+    /// # What is part of the header for `Icmpv4Type::AddressMaskRequest`?
+    ///
+    /// For the `Icmpv4Type::AddressMaskRequest` type the first 12 bytes/octets
+    /// (including the address mask) are part of the header. The payload
+    /// data is usually empty.
+    ///
+    /// ```text
+    /// 0               1               2               3               4
+    /// +---------------------------------------------------------------+  -
+    /// |       17      |       0       |  checksum (in Icmpv4Header)   |  |
+    /// +---------------------------------------------------------------+  |
+    /// |          [value].id           |         [value].seq           |  | part of header & type
+    /// +---------------------------------------------------------------+  |
+    /// |                     [value].address_mask                      |  ↓
+    /// +---------------------------------------------------------------+  -
+    /// ```
+    AddressMaskRequest(AddressMaskMessage),
+
+    /// Deprecated answer to an `AddressMaskRequest` message.
+    ///
+    /// # What is part of the header for `Icmpv4Type::AddressMaskReply`?
+    ///
+    /// For the `Icmpv4Type::AddressMaskReply` type the first 12 bytes/octets
+    /// (including the address mask) are part of the header. The payload
+    /// data is usually empty.
+    AddressMaskReply(AddressMaskMessage),
+
+    /// Request for the status of an interface ("PROBE", defined in
+    /// [RFC 8335](https://tools.ietf.org/html/rfc8335)).
+    ///
+    /// # What is part of the header for `Icmpv4Type::ExtendedEchoRequest`?
+    ///
+    /// For the `Icmpv4Type::ExtendedEchoRequest` type the first 8 bytes/octets
+    /// of the ICMP packet are part of the header. The ICMP extension structure
+    /// identifying the probed interface is part of the payload.
+    ///
+    /// ```text
+    /// 0               1               2               3               4
+    /// +---------------------------------------------------------------+  -
+    /// |       42      |       0       |  checksum (in Icmpv4Header)   |  |
+    /// +---------------------------------------------------------------+  | part of header & type
+    /// |          [value].id           |   [v].seq     | reserved    |L|  ↓
+    /// +---------------------------------------------------------------+  -
+    /// |                                                               |  |
+    /// ...                 <ICMP extension structure>                ...  | part of payload
+    /// |                                                               |  ↓
+    /// +---------------------------------------------------------------+  -
+    /// ```
+    ExtendedEchoRequest(ExtendedEchoRequestHeader),
+
+    /// Answer to an `ExtendedEchoRequest` message (defined in
+    /// [RFC 8335](https://tools.ietf.org/html/rfc8335)).
+    ///
+    /// # What is part of the header for `Icmpv4Type::ExtendedEchoReply`?
+    ///
+    /// For the `Icmpv4Type::ExtendedEchoReply` type the first 8 bytes/octets
+    /// of the ICMP packet are part of the header.
+    ///
+    /// ```text
+    /// 0               1               2               3               4
+    /// +---------------------------------------------------------------+  -
+    /// |       43      | [value].code  |  checksum (in Icmpv4Header)   |  |
+    /// +---------------------------------------------------------------+  | part of header & type
+    /// |          [value].id           |   [v].seq     |State|Res|A|4|6|  ↓
+    /// +---------------------------------------------------------------+  -
+    /// ```
+    ExtendedEchoReply(ExtendedEchoReplyHeader),
 }
 
 impl Icmpv4Type {
@@ -589,8 +852,15 @@ impl Icmpv4Type {
             | Redirect(_)
             | EchoRequest(_)
             | TimeExceeded(_)
-            | ParameterProblem(_) => 8,
+            | ParameterProblem(_)
+            | RouterAdvertisement(_)
+            | RouterSolicitation
+            | InfoRequest(_)
+            | InfoReply(_)
+            | ExtendedEchoRequest(_)
+            | ExtendedEchoReply(_) => 8,
             TimestampRequest(_) | TimestampReply(_) => TimestampMessage::SERIALIZED_SIZE,
+            AddressMaskRequest(_) | AddressMaskReply(_) => AddressMaskMessage::SERIALIZED_SIZE,
         }
     }
 
@@ -610,8 +880,17 @@ impl Icmpv4Type {
             | Redirect(_)
             | EchoRequest(_)
             | TimeExceeded(_)
-            | ParameterProblem(_) => None,
-            TimestampRequest(_) | TimestampReply(_) => Some(0),
+            | ParameterProblem(_)
+            | RouterAdvertisement(_)
+            | ExtendedEchoRequest(_) => None,
+            TimestampRequest(_)
+            | TimestampReply(_)
+            | RouterSolicitation
+            | InfoRequest(_)
+            | InfoReply(_)
+            | AddressMaskRequest(_)
+            | AddressMaskReply(_)
+            | ExtendedEchoReply(_) => Some(0),
         }
     }
 
@@ -711,6 +990,33 @@ impl Icmpv4Type {
                 .add_4bytes(msg.originate_timestamp.to_be_bytes())
                 .add_4bytes(msg.receive_timestamp.to_be_bytes())
                 .add_4bytes(msg.transmit_timestamp.to_be_bytes()),
+            RouterAdvertisement(header) => checksum::Sum16BitWords::new()
+                .add_2bytes([TYPE_ROUTER_ADVERTISEMENT, 0])
+                .add_4bytes(header.to_bytes()),
+            RouterSolicitation => checksum::Sum16BitWords::new()
+                .add_2bytes([TYPE_ROUTER_SOLICITATION, 0]),
+            InfoRequest(header) => checksum::Sum16BitWords::new()
+                .add_2bytes([TYPE_INFO_REQUEST, 0])
+                .add_4bytes(header.to_bytes()),
+            InfoReply(header) => checksum::Sum16BitWords::new()
+                .add_2bytes([TYPE_INFO_REPLY, 0])
+                .add_4bytes(header.to_bytes()),
+            AddressMaskRequest(msg) => checksum::Sum16BitWords::new()
+                .add_2bytes([TYPE_ADDRESS, 0])
+                .add_2bytes(msg.id.to_be_bytes())
+                .add_2bytes(msg.seq.to_be_bytes())
+                .add_4bytes(msg.address_mask),
+            AddressMaskReply(msg) => checksum::Sum16BitWords::new()
+                .add_2bytes([TYPE_ADDRESSREPLY, 0])
+                .add_2bytes(msg.id.to_be_bytes())
+                .add_2bytes(msg.seq.to_be_bytes())
+                .add_4bytes(msg.address_mask),
+            ExtendedEchoRequest(header) => checksum::Sum16BitWords::new()
+                .add_2bytes([TYPE_EXT_ECHO_REQUEST, 0])
+                .add_4bytes(header.to_bytes()),
+            ExtendedEchoReply(header) => checksum::Sum16BitWords::new()
+                .add_2bytes([TYPE_EXT_ECHO_REPLY, header.code.code_u8()])
+                .add_4bytes(header.to_bytes()),
         }
         .add_slice(payload)
         .ones_complement()
@@ -797,6 +1103,20 @@ impl Icmpv4Header {
                     }.header())
                 }
             },
+            TYPE_ADDRESS | TYPE_ADDRESSREPLY => {
+                if 0 == bytes[1] {
+                    // Address mask messages need the address mask read
+                    reader.read_exact(&mut bytes[8..AddressMaskMessage::SERIALIZED_SIZE])?;
+                    Ok(Icmpv4Slice {
+                        slice: &bytes[..AddressMaskMessage::SERIALIZED_SIZE]
+                    }.header())
+                } else {
+                    // fallback to unknown
+                    Ok(Icmpv4Slice {
+                        slice: &bytes[..8]
+                    }.header())
+                }
+            },
             _ => Ok(Icmpv4Slice {
                 slice: &bytes[..8]
             }.header())
@@ -912,6 +1232,27 @@ impl Icmpv4Header {
             ])
         };
 
+        let re_address_mask_msg = |type_u8: u8,
+                                   msg: &AddressMaskMessage|
+         -> ArrayVec<u8, { Icmpv4Header::MAX_SERIALIZED_SIZE }> {
+            let id = msg.id.to_be_bytes();
+            let seq = msg.seq.to_be_bytes();
+            let m = msg.address_mask;
+
+            let mut re = ArrayVec::from([
+                type_u8, 0, checksum_be[0], checksum_be[1],
+                id[0], id[1], seq[0], seq[1],
+                m[0], m[1], m[2], m[3],
+                0, 0, 0, 0,
+                0, 0, 0, 0,
+            ]);
+            // SAFETY: Safe as u8 has no destruction behavior and as 12 is smaller then 20.
+            unsafe {
+                re.set_len(AddressMaskMessage::SERIALIZED_SIZE);
+            }
+            re
+        };
+
         use Icmpv4Type::*;
         match self.icmp_type {
             Unknown {
@@ -975,6 +1316,16 @@ impl Icmpv4Header {
             }
             TimestampRequest(ref msg) => re_timestamp_msg(TYPE_TIMESTAMP, msg),
             TimestampReply(ref msg) => re_timestamp_msg(TYPE_TIMESTAMP_REPLY, msg),
+            RouterAdvertisement(header) => re_4u8(TYPE_ROUTER_ADVERTISEMENT, 0, header.to_bytes()),
+            RouterSolicitation => re_zero(TYPE_ROUTER_SOLICITATION, 0),
+            InfoRequest(header) => re_2u16(TYPE_INFO_REQUEST, 0, header.id, header.seq),
+            InfoReply(header) => re_2u16(TYPE_INFO_REPLY, 0, header.id, header.seq),
+            AddressMaskRequest(ref msg) => re_address_mask_msg(TYPE_ADDRESS, msg),
+            AddressMaskReply(ref msg) => re_address_mask_msg(TYPE_ADDRESSREPLY, msg),
+            ExtendedEchoRequest(header) => re_4u8(TYPE_EXT_ECHO_REQUEST, 0, header.to_bytes()),
+            ExtendedEchoReply(header) => {
+                re_4u8(TYPE_EXT_ECHO_REPLY, header.code.code_u8(), header.to_bytes())
+            }
         }
     }
 }
@@ -994,7 +1345,9 @@ impl<'a> Icmpv4Slice<'a> {
     /// # Errors
     ///
     /// The function will return an `Err` `ReadError::UnexpectedEndOfSlice`
-    /// if the given slice is too small.
+    /// if the given slice is too small (this includes address mask messages
+    /// shorter then 12 bytes) and an `Err` `ReadError::UnexpectedLenOfSlice`
+    /// if a timestamp message does not have a length of 20 bytes.
     #[inline]
     pub fn from_slice(slice: &'a [u8]) -> Result<Icmpv4Slice<'a>, ReadError> {
         // check length
@@ -1019,6 +1372,11 @@ impl<'a> Icmpv4Slice<'a> {
                     });
                 }
             }
+            TYPE_ADDRESS | TYPE_ADDRESSREPLY
+                if 0 == icmp_code && slice.len() < AddressMaskMessage::SERIALIZED_SIZE =>
+            {
+                return Err(UnexpectedEndOfSlice(AddressMaskMessage::SERIALIZED_SIZE));
+            }
             _ => {}
         }
 
@@ -1046,6 +1404,9 @@ impl<'a> Icmpv4Slice<'a> {
             } else {
                 8
             },
+            TYPE_ADDRESS | TYPE_ADDRESSREPLY if 0 == self.code_u8() => {
+                AddressMaskMessage::SERIALIZED_SIZE
+            }
             _ => 8,
         }
     }
@@ -1184,6 +1545,48 @@ impl<'a> Icmpv4Slice<'a> {
                     }
                 }
             }
+            TYPE_ROUTER_ADVERTISEMENT if 0 == self.code_u8() => {
+                return RouterAdvertisement(RouterAdvertisementHeader::from_bytes(self.bytes5to8()));
+            }
+            TYPE_ROUTER_SOLICITATION if 0 == self.code_u8() => {
+                return RouterSolicitation;
+            }
+            TYPE_INFO_REQUEST if 0 == self.code_u8() => {
+                return InfoRequest(IcmpEchoHeader::from_bytes(self.bytes5to8()));
+            }
+            TYPE_INFO_REPLY if 0 == self.code_u8() => {
+                return InfoReply(IcmpEchoHeader::from_bytes(self.bytes5to8()));
+            }
+            TYPE_ADDRESS | TYPE_ADDRESSREPLY if 0 == self.code_u8() => {
+                // SAFETY:
+                // Safe as the contructor checks that the slice has
+                // at least the length of AddressMaskMessage::SERIALIZED_SIZE (12).
+                let msg = AddressMaskMessage::from_bytes(unsafe {
+                    [
+                        *self.slice.get_unchecked(4),
+                        *self.slice.get_unchecked(5),
+                        *self.slice.get_unchecked(6),
+                        *self.slice.get_unchecked(7),
+                        *self.slice.get_unchecked(8),
+                        *self.slice.get_unchecked(9),
+                        *self.slice.get_unchecked(10),
+                        *self.slice.get_unchecked(11),
+                    ]
+                });
+                return if TYPE_ADDRESS == self.type_u8() {
+                    AddressMaskRequest(msg)
+                } else {
+                    AddressMaskReply(msg)
+                };
+            }
+            TYPE_EXT_ECHO_REQUEST if 0 == self.code_u8() => {
+                return ExtendedEchoRequest(ExtendedEchoRequestHeader::from_bytes(self.bytes5to8()));
+            }
+            TYPE_EXT_ECHO_REPLY => {
+                if let Some(code) = ExtendedEchoReplyCode::from_u8(self.code_u8()) {
+                    return ExtendedEchoReply(ExtendedEchoReplyHeader::from_bytes(code, self.bytes5to8()));
+                }
+            }
             _ => {}
         }
 
@@ -1249,8 +1652,13 @@ impl<'a> Icmpv4Slice<'a> {
     /// | `.header().icmp_type` or `.icmp_type()`                                                                                                    | Payload Content                                                              |
     /// |--------------------------------------------------------------------------------------------------------------------------------------------|------------------------------------------------------------------------------|
     /// | [`Icmpv4Type::EchoReply`]<br>[`Icmpv4Type::EchoRequest`]<br>                                                                               | Data part of the echo message                                                |
-    /// | [`Icmpv4Type::DestinationUnreachable`]<br>[`Icmpv4Type::Redirect`]<br>[`Icmpv4Type::TimeExceeded`]<br>[`Icmpv4Type::ParameterProblem`]<br> | Internet Header + 64 bits of Original Data Datagram causing the ICMP message |
+    /// | [`Icmpv4Type::DestinationUnreachable`]<br>[`Icmpv4Type::Redirect`]<br>[`Icmpv4Type::TimeExceeded`]<br>[`Icmpv4Type::ParameterProblem`]<br> | Internet Header + 64 bits of Original Data Datagram causing the ICMP message (followed by an optional extension structure, see [`Icmpv4Slice::original_datagram`] & [`Icmpv4Slice::extensions`]) |
     /// | [`Icmpv4Type::TimestampRequest`]<br>[`Icmpv4Type::TimestampReply`]<br>                                                                     | Nothing                                                                      |
+    /// | [`Icmpv4Type::RouterAdvertisement`]                                                                                                        | Router address entries (see [`Icmpv4Slice::router_advertisement_entries`])   |
+    /// | [`Icmpv4Type::RouterSolicitation`]<br>[`Icmpv4Type::InfoRequest`]<br>[`Icmpv4Type::InfoReply`]<br>                                         | Nothing                                                                      |
+    /// | [`Icmpv4Type::AddressMaskRequest`]<br>[`Icmpv4Type::AddressMaskReply`]<br>                                                                 | Nothing (bytes after the address mask if present)                            |
+    /// | [`Icmpv4Type::ExtendedEchoRequest`]                                                                                                        | ICMP extension structure identifying the probed interface                    |
+    /// | [`Icmpv4Type::ExtendedEchoReply`]                                                                                                          | Nothing                                                                      |
     /// | [`Icmpv4Type::Unknown`]                                                                                                                    | Everything after the 8th byte/octet of the ICMP packet.                      |
     #[inline]
     pub fn payload(&self) -> &'a [u8] {
@@ -1268,6 +1676,13 @@ impl<'a> Icmpv4Slice<'a> {
                 8
             },
             // SAFETY:
+            // Lenght safe as the contructor checks that the slice has
+            // at least the length of AddressMaskMessage::SERIALIZED_SIZE (12)
+            // for the messages types TYPE_ADDRESS and TYPE_ADDRESSREPLY.
+            TYPE_ADDRESS | TYPE_ADDRESSREPLY if 0 == self.code_u8() => {
+                AddressMaskMessage::SERIALIZED_SIZE
+            }
+            // SAFETY:
             // Lneght safe as the contructor checks that the slice has
             // at least the length of Icmpv6Header::MIN_SERIALIZED_SIZE(8) for
             // all message types.
@@ -1279,6 +1694,85 @@ impl<'a> Icmpv4Slice<'a> {
         unsafe { from_raw_parts(self.slice.as_ptr().add(header_len), self.slice.len() - header_len) }
     }
 
+    /// Returns an iterator over the router address entries if the slice
+    /// contains a "Router Advertisement" message.
+    ///
+    /// Returns `None` if the message is not a router advertisement, if the
+    /// address entry size is smaller then 2 or if the payload is too short
+    /// to contain all advertised entries.
+    pub fn router_advertisement_entries(&self) -> Option<RouterAdvertisementEntriesIterator<'a>> {
+        match self.icmp_type() {
+            Icmpv4Type::RouterAdvertisement(header) => {
+                let entry_len = usize::from(header.addr_entry_size) * 4;
+                if entry_len < RouterAdvertisementEntry::SERIALIZED_SIZE {
+                    return None;
+                }
+                let entries_len = usize::from(header.num_addrs) * entry_len;
+                let payload = self.payload();
+                if payload.len() < entries_len {
+                    return None;
+                }
+                Some(RouterAdvertisementEntriesIterator {
+                    entry_len,
+                    rest: &payload[..entries_len],
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the "Internet Header + leading octets of the original datagram"
+    /// part of the payload.
+    ///
+    /// For "Destination Unreachable", "Time Exceeded" & "Parameter Problem"
+    /// messages with a non zero "length" field (rfc4884) only the bytes
+    /// covered by the length field are returned (the ICMP extension structure
+    /// following them can be accessed via [`Icmpv4Slice::extensions`]).
+    /// In all other cases the complete payload is returned.
+    pub fn original_datagram(&self) -> &'a [u8] {
+        self.split_multipart_payload().0
+    }
+
+    /// Returns the ICMP extension structure (rfc4884) appended after the
+    /// original datagram in "Destination Unreachable", "Time Exceeded" &
+    /// "Parameter Problem" messages.
+    ///
+    /// Returns `None` if the message type does not support extensions,
+    /// the "length" field is zero, the payload is shorter then the value
+    /// in the "length" field or no valid extension header is present.
+    pub fn extensions(&self) -> Option<IcmpExtensionsSlice<'a>> {
+        self.split_multipart_payload().1
+    }
+
+    /// Slices the packet quoted in "Destination Unreachable", "Redirect",
+    /// "Time Exceeded" & "Parameter Problem" messages.
+    ///
+    /// The quoted packet is sliced in a tolerant mode (truncated transport
+    /// headers & length fields exceeding the quoted data are accepted, see
+    /// [`IcmpEmbeddedPacketSlice`]). ICMP extension structures following
+    /// the quoted packet are not included.
+    ///
+    /// Returns `None` if the message type does not contain a quoted packet.
+    pub fn embedded_packet(&self) -> Option<Result<IcmpEmbeddedPacketSlice<'a>, ReadError>> {
+        match self.type_u8() {
+            TYPE_DEST_UNREACH | TYPE_REDIRECT | TYPE_TIME_EXCEEDED | TYPE_PARAMETER_PROBLEM => {
+                Some(IcmpEmbeddedPacketSlice::from_slice(self.original_datagram()))
+            }
+            _ => None,
+        }
+    }
+
+    /// Splits the payload into the original datagram & the extension structure.
+    fn split_multipart_payload(&self) -> (&'a [u8], Option<IcmpExtensionsSlice<'a>>) {
+        match self.type_u8() {
+            TYPE_DEST_UNREACH | TYPE_TIME_EXCEEDED | TYPE_PARAMETER_PROBLEM => {
+                // length is in 32-bit words
+                split_icmp_multipart_payload(self.payload(), usize::from(self.slice[5]) * 4)
+            }
+            _ => (self.payload(), None),
+        }
+    }
+
     /// Returns the slice containing the ICMPv4 packet.
     #[inline]
     pub fn slice(&self) -> &'a [u8] {
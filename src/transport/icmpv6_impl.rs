@@ -72,10 +72,13 @@ pub mod icmpv6 {
     /// ICMPv6 type value indicating a "Inverse Neighbor Discovery Advertisement" message.
     pub const TYPE_INVERSE_NEIGHBOR_DISCOVERY_ADVERTISEMENT: u8 = 142;
 
-    /// ICMPv6 type value indicating a "Extended Echo Request" message.
+    /// ICMPv6 type value indicating a "Version 2 Multicast Listener Report" message (rfc3810).
+    pub const TYPE_MULTICAST_LISTENER_REPORT_V2: u8 = 143;
+
+    /// ICMPv6 type value indicating a "Extended Echo Request" message (rfc8335).
     pub const TYPE_EXT_ECHO_REQUEST: u8 = 160;
 
-    /// ICMPv6 type value indicating a "Extended Echo Reply" message.
+    /// ICMPv6 type value indicating a "Extended Echo Reply" message (rfc8335).
     pub const TYPE_EXT_ECHO_REPLY: u8 = 161;
 
     /// ICMPv6 destination unreachable code for "no route to destination".
@@ -321,6 +324,192 @@ pub mod icmpv6 {
         pub pointer: u32,
     }
 
+    /// Length of the header of a "Router Solicitation" message in bytes/octets.
+    pub const ROUTER_SOLICITATION_HEADER_LEN: usize = 8;
+
+    /// Length of the header of a "Router Advertisement" message in bytes/octets.
+    pub const ROUTER_ADVERTISEMENT_HEADER_LEN: usize = 16;
+
+    /// Length of the header of a "Neighbor Solicitation" message in bytes/octets.
+    pub const NEIGHBOR_SOLICITATION_HEADER_LEN: usize = 24;
+
+    /// Length of the header of a "Neighbor Advertisement" message in bytes/octets.
+    pub const NEIGHBOR_ADVERTISEMENT_HEADER_LEN: usize = 24;
+
+    /// Length of the header of a "Redirect" message in bytes/octets.
+    pub const REDIRECT_HEADER_LEN: usize = 40;
+
+    /// Length of the header of a MLDv1 "Multicast Listener Query", "Report" or "Done" message in bytes/octets.
+    pub const MULTICAST_LISTENER_HEADER_LEN: usize = 24;
+
+    /// Length of the header of a MLDv2 "Multicast Listener Query" message in bytes/octets (without the source addresses).
+    pub const MULTICAST_LISTENER_QUERY_V2_HEADER_LEN: usize = 28;
+
+    /// Length of the header of a MLDv2 "Multicast Listener Report" message in bytes/octets (without the records).
+    pub const MULTICAST_LISTENER_REPORT_V2_HEADER_LEN: usize = 8;
+
+    /// Fixed part of MLDv1 (rfc2710) "Multicast Listener Query", "Report" & "Done"
+    /// messages following the type, code & checksum fields.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct MulticastListenerHeader {
+        /// Maximum allowed delay in milliseconds before sending a responding
+        /// report (only meaningful in queries, zero otherwise).
+        pub maximum_response_delay: u16,
+        /// Multicast address the message refers to (zero in general queries).
+        pub multicast_address: [u8;16],
+    }
+
+    /// Fixed part of a MLDv2 (rfc3810) "Multicast Listener Query" message
+    /// following the type, code & checksum fields.
+    ///
+    /// The source addresses following the fixed part are part of the payload
+    /// and can be accessed via [`crate::Icmpv6Slice::mldv2_query_sources`].
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct MulticastListenerQueryV2Header {
+        /// Encoded maximum response delay (see [`MulticastListenerQueryV2Header::max_response_delay`]).
+        pub maximum_response_code: u16,
+        /// Multicast address being queried (zero in general queries).
+        pub multicast_address: [u8;16],
+        /// "S" flag, routers receiving the query should suppress timer updates.
+        pub suppress_router_side_processing: bool,
+        /// Querier's robustness variable (3 bits, higher bits are ignored
+        /// when serializing).
+        pub querier_robustness_variable: u8,
+        /// Encoded query interval of the querier (see [`MulticastListenerQueryV2Header::querier_query_interval`]).
+        pub querier_query_interval_code: u8,
+        /// Number of source addresses present in the query.
+        pub number_of_sources: u16,
+    }
+
+    impl MulticastListenerQueryV2Header {
+        /// Decodes the maximum response delay in milliseconds from the
+        /// `maximum_response_code` field (rfc3810 section 5.1.3).
+        pub fn max_response_delay(&self) -> u32 {
+            let code = u32::from(self.maximum_response_code);
+            if code < 0x8000 {
+                code
+            } else {
+                let mantissa = code & 0xfff;
+                let exponent = (code >> 12) & 0b111;
+                (mantissa | 0x1000) << (exponent + 3)
+            }
+        }
+
+        /// Decodes the querier's query interval in seconds from the
+        /// `querier_query_interval_code` field (rfc3810 section 5.1.9).
+        pub fn querier_query_interval(&self) -> u32 {
+            let code = u32::from(self.querier_query_interval_code);
+            if code < 0x80 {
+                code
+            } else {
+                let mantissa = code & 0xf;
+                let exponent = (code >> 4) & 0b111;
+                (mantissa | 0x10) << (exponent + 3)
+            }
+        }
+    }
+
+    /// Fixed part of an ICMPv6 "Router Advertisement" message (rfc4861) following
+    /// the type, code & checksum fields.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct RouterAdvertisementHeader {
+        /// Default value that should be placed in the hop limit field of
+        /// outgoing IP packets (0 means unspecified).
+        pub cur_hop_limit: u8,
+        /// "Managed address configuration" flag (addresses are available via DHCPv6).
+        pub managed_address_config: bool,
+        /// "Other configuration" flag (other configuration information is available via DHCPv6).
+        pub other_config: bool,
+        /// Lifetime in seconds of the router as a default router (0 means the
+        /// router is not a default router).
+        pub router_lifetime: u16,
+        /// Time in milliseconds a node assumes a neighbor is reachable after
+        /// having received a reachability confirmation (0 means unspecified).
+        pub reachable_time: u32,
+        /// Time in milliseconds between retransmitted neighbor solicitation
+        /// messages (0 means unspecified).
+        pub retrans_timer: u32,
+    }
+
+    impl RouterAdvertisementHeader {
+        /// Returns the serialized bytes following the type, code & checksum fields.
+        pub fn to_bytes(&self) -> [u8;12] {
+            let flags = if self.managed_address_config { 0b1000_0000 } else { 0 } |
+                        if self.other_config { 0b0100_0000 } else { 0 };
+            let lifetime = self.router_lifetime.to_be_bytes();
+            let reachable = self.reachable_time.to_be_bytes();
+            let retrans = self.retrans_timer.to_be_bytes();
+            [
+                self.cur_hop_limit, flags, lifetime[0], lifetime[1],
+                reachable[0], reachable[1], reachable[2], reachable[3],
+                retrans[0], retrans[1], retrans[2], retrans[3],
+            ]
+        }
+
+        /// Decodes the header from the bytes following the type, code & checksum fields.
+        pub fn from_bytes(bytes: [u8;12]) -> RouterAdvertisementHeader {
+            RouterAdvertisementHeader {
+                cur_hop_limit: bytes[0],
+                managed_address_config: 0 != bytes[1] & 0b1000_0000,
+                other_config: 0 != bytes[1] & 0b0100_0000,
+                router_lifetime: u16::from_be_bytes([bytes[2], bytes[3]]),
+                reachable_time: u32::from_be_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]),
+                retrans_timer: u32::from_be_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]),
+            }
+        }
+    }
+
+    /// Fixed part of an ICMPv6 "Neighbor Solicitation" message (rfc4861) following
+    /// the type, code & checksum fields.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct NeighborSolicitationHeader {
+        /// IP address of the target of the solicitation (must not be a multicast address).
+        pub target_address: [u8;16],
+    }
+
+    /// Fixed part of an ICMPv6 "Neighbor Advertisement" message (rfc4861) following
+    /// the type, code & checksum fields.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct NeighborAdvertisementHeader {
+        /// "Router" flag (the sender is a router).
+        pub router: bool,
+        /// "Solicited" flag (the advertisement was sent in response to a neighbor solicitation).
+        pub solicited: bool,
+        /// "Override" flag (the advertisement should override an existing cache entry).
+        pub override_flag: bool,
+        /// For solicited advertisements the target address field of the
+        /// solicitation. For unsolicited advertisements the address whose
+        /// link-layer address has changed.
+        pub target_address: [u8;16],
+    }
+
+    impl NeighborAdvertisementHeader {
+        /// Returns the flags byte (first byte after the checksum).
+        pub fn flags_u8(&self) -> u8 {
+            let mut result = 0;
+            if self.router {
+                result |= 0b1000_0000;
+            }
+            if self.solicited {
+                result |= 0b0100_0000;
+            }
+            if self.override_flag {
+                result |= 0b0010_0000;
+            }
+            result
+        }
+    }
+
+    /// Fixed part of an ICMPv6 "Redirect" message (rfc4861) following
+    /// the type, code & checksum fields.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct RedirectHeader {
+        /// An IP address that is a better first hop to use for the destination address.
+        pub target_address: [u8;16],
+        /// The IP address of the destination that is redirected to the target.
+        pub destination_address: [u8;16],
+    }
+
 } // mod icmpv6
 
 use icmpv6::*;
@@ -362,6 +551,18 @@ use icmpv6::*;
 ///             ParameterProblem(header) => println!("{:?}", header),
 ///             EchoRequest(header) => println!("{:?}", header),
 ///             EchoReply(header) => println!("{:?}", header),
+///             RouterSolicitation => println!("RouterSolicitation"),
+///             RouterAdvertisement(header) => println!("{:?}", header),
+///             NeighborSolicitation(header) => println!("{:?}", header),
+///             NeighborAdvertisement(header) => println!("{:?}", header),
+///             Redirect(header) => println!("{:?}", header),
+///             MulticastListenerQuery(header) => println!("{:?}", header),
+///             MulticastListenerReport(header) => println!("{:?}", header),
+///             MulticastListenerDone(header) => println!("{:?}", header),
+///             MulticastListenerQueryV2(header) => println!("{:?}", header),
+///             MulticastListenerReportV2{ number_of_records } => println!("{}", number_of_records),
+///             ExtendedEchoRequest(header) => println!("{:?}", header),
+///             ExtendedEchoReply(header) => println!("{:?}", header),
 ///         }
 ///     },
 ///     _ => {},
@@ -651,6 +852,260 @@ pub enum Icmpv6Type {
     /// The data received in the ICMPv6 Echo Request message MUST be returned
     /// entirely and unmodified in the ICMPv6 Echo Reply message.
     EchoReply(IcmpEchoHeader),
+
+    /// Neighbor discovery "Router Solicitation" message (rfc4861).
+    ///
+    /// # What is part of the header for `Icmpv6Type::RouterSolicitation`?
+    ///
+    /// The first 8 bytes/octets (containing only reserved bytes after the
+    /// checksum) are part of the header. The options are part of the payload
+    /// and can be decoded via [`Icmpv6Slice::ndp_options`].
+    ///
+    /// ```text
+    /// 0               1               2               3               4
+    /// +---------------------------------------------------------------+  -
+    /// |      133      |       0       |  checksum (in Icmpv6Header)   |  |
+    /// +---------------------------------------------------------------+  | part of header & type
+    /// |                           reserved                            |  ↓
+    /// +---------------------------------------------------------------+  -
+    /// |                                                               |  |
+    /// ...                         <options>                         ...  | part of payload
+    /// |                                                               |  ↓
+    /// +---------------------------------------------------------------+  -
+    /// ```
+    RouterSolicitation,
+
+    /// Neighbor discovery "Router Advertisement" message (rfc4861).
+    ///
+    /// # What is part of the header for `Icmpv6Type::RouterAdvertisement`?
+    ///
+    /// The first 16 bytes/octets are part of the header. The options are part
+    /// of the payload and can be decoded via [`Icmpv6Slice::ndp_options`].
+    ///
+    /// ```text
+    /// 0               1               2               3               4
+    /// +---------------------------------------------------------------+  -
+    /// |      134      |       0       |  checksum (in Icmpv6Header)   |  |
+    /// +---------------------------------------------------------------+  |
+    /// | cur_hop_limit |M|O| reserved  |        router_lifetime        |  | part of header & type
+    /// +---------------------------------------------------------------+  |
+    /// |                        reachable_time                         |  |
+    /// +---------------------------------------------------------------+  |
+    /// |                         retrans_timer                         |  ↓
+    /// +---------------------------------------------------------------+  -
+    /// |                                                               |  |
+    /// ...                         <options>                         ...  | part of payload
+    /// |                                                               |  ↓
+    /// +---------------------------------------------------------------+  -
+    /// ```
+    RouterAdvertisement(icmpv6::RouterAdvertisementHeader),
+
+    /// Neighbor discovery "Neighbor Solicitation" message (rfc4861).
+    ///
+    /// # What is part of the header for `Icmpv6Type::NeighborSolicitation`?
+    ///
+    /// The first 24 bytes/octets (including the target address) are part of
+    /// the header. The options are part of the payload and can be decoded
+    /// via [`Icmpv6Slice::ndp_options`].
+    ///
+    /// ```text
+    /// 0               1               2               3               4
+    /// +---------------------------------------------------------------+  -
+    /// |      135      |       0       |  checksum (in Icmpv6Header)   |  |
+    /// +---------------------------------------------------------------+  |
+    /// |                           reserved                            |  | part of header & type
+    /// +---------------------------------------------------------------+  |
+    /// |                                                               |  |
+    /// ...                    [value].target_address                 ...  |
+    /// |                                                               |  ↓
+    /// +---------------------------------------------------------------+  -
+    /// |                                                               |  |
+    /// ...                         <options>                         ...  | part of payload
+    /// |                                                               |  ↓
+    /// +---------------------------------------------------------------+  -
+    /// ```
+    NeighborSolicitation(icmpv6::NeighborSolicitationHeader),
+
+    /// Neighbor discovery "Neighbor Advertisement" message (rfc4861).
+    ///
+    /// # What is part of the header for `Icmpv6Type::NeighborAdvertisement`?
+    ///
+    /// The first 24 bytes/octets (including the flags and the target address)
+    /// are part of the header. The options are part of the payload and can be
+    /// decoded via [`Icmpv6Slice::ndp_options`].
+    ///
+    /// ```text
+    /// 0               1               2               3               4
+    /// +---------------------------------------------------------------+  -
+    /// |      136      |       0       |  checksum (in Icmpv6Header)   |  |
+    /// +---------------------------------------------------------------+  |
+    /// |R|S|O|                      reserved                           |  | part of header & type
+    /// +---------------------------------------------------------------+  |
+    /// |                                                               |  |
+    /// ...                    [value].target_address                 ...  |
+    /// |                                                               |  ↓
+    /// +---------------------------------------------------------------+  -
+    /// |                                                               |  |
+    /// ...                         <options>                         ...  | part of payload
+    /// |                                                               |  ↓
+    /// +---------------------------------------------------------------+  -
+    /// ```
+    NeighborAdvertisement(icmpv6::NeighborAdvertisementHeader),
+
+    /// Neighbor discovery "Redirect" message (rfc4861).
+    ///
+    /// # What is part of the header for `Icmpv6Type::Redirect`?
+    ///
+    /// The first 40 bytes/octets (including the target & destination address)
+    /// are part of the header. The options are part of the payload and can be
+    /// decoded via [`Icmpv6Slice::ndp_options`].
+    ///
+    /// ```text
+    /// 0               1               2               3               4
+    /// +---------------------------------------------------------------+  -
+    /// |      137      |       0       |  checksum (in Icmpv6Header)   |  |
+    /// +---------------------------------------------------------------+  |
+    /// |                           reserved                            |  |
+    /// +---------------------------------------------------------------+  | part of header & type
+    /// |                                                               |  |
+    /// ...                    [value].target_address                 ...  |
+    /// |                                                               |  |
+    /// +---------------------------------------------------------------+  |
+    /// |                                                               |  |
+    /// ...                 [value].destination_address               ...  |
+    /// |                                                               |  ↓
+    /// +---------------------------------------------------------------+  -
+    /// |                                                               |  |
+    /// ...                         <options>                         ...  | part of payload
+    /// |                                                               |  ↓
+    /// +---------------------------------------------------------------+  -
+    /// ```
+    Redirect(icmpv6::RedirectHeader),
+
+    /// MLDv1 "Multicast Listener Query" message (rfc2710).
+    ///
+    /// # What is part of the header for `Icmpv6Type::MulticastListenerQuery`?
+    ///
+    /// All 24 bytes/octets of the message are part of the header. Queries are
+    /// only decoded as MLDv1 queries if the message has exactly a length of 24
+    /// bytes (rfc3810 section 8.1), longer queries are decoded as
+    /// [`Icmpv6Type::MulticastListenerQueryV2`].
+    ///
+    /// ```text
+    /// 0               1               2               3               4
+    /// +---------------------------------------------------------------+  -
+    /// |      130      |       0       |  checksum (in Icmpv6Header)   |  |
+    /// +---------------------------------------------------------------+  |
+    /// |  [value].maximum_response_delay |           reserved          |  | part of header & type
+    /// +---------------------------------------------------------------+  |
+    /// |                                                               |  |
+    /// ...                  [value].multicast_address                ...  |
+    /// |                                                               |  ↓
+    /// +---------------------------------------------------------------+  -
+    /// ```
+    MulticastListenerQuery(icmpv6::MulticastListenerHeader),
+
+    /// MLDv1 "Multicast Listener Report" message (rfc2710).
+    ///
+    /// # What is part of the header for `Icmpv6Type::MulticastListenerReport`?
+    ///
+    /// All 24 bytes/octets of the message are part of the header (same
+    /// layout as [`Icmpv6Type::MulticastListenerQuery`] with the type 131).
+    MulticastListenerReport(icmpv6::MulticastListenerHeader),
+
+    /// MLDv1 "Multicast Listener Done" message (rfc2710).
+    ///
+    /// # What is part of the header for `Icmpv6Type::MulticastListenerDone`?
+    ///
+    /// All 24 bytes/octets of the message are part of the header (same
+    /// layout as [`Icmpv6Type::MulticastListenerQuery`] with the type 132).
+    MulticastListenerDone(icmpv6::MulticastListenerHeader),
+
+    /// MLDv2 "Multicast Listener Query" message (rfc3810).
+    ///
+    /// # What is part of the header for `Icmpv6Type::MulticastListenerQueryV2`?
+    ///
+    /// The first 28 bytes/octets are part of the header. The source addresses
+    /// are part of the payload and can be accessed via [`Icmpv6Slice::mldv2_query_sources`].
+    ///
+    /// ```text
+    /// 0               1               2               3               4
+    /// +---------------------------------------------------------------+  -
+    /// |      130      |       0       |  checksum (in Icmpv6Header)   |  |
+    /// +---------------------------------------------------------------+  |
+    /// |  maximum_response_code        |           reserved            |  |
+    /// +---------------------------------------------------------------+  | part of header & type
+    /// |                                                               |  |
+    /// ...                  [value].multicast_address                ...  |
+    /// |                                                               |  |
+    /// +---------------------------------------------------------------+  |
+    /// | resv  |S| QRV |     QQIC      |       number_of_sources       |  ↓
+    /// +---------------------------------------------------------------+  -
+    /// |                                                               |  |
+    /// ...                    <source addresses>                     ...  | part of payload
+    /// |                                                               |  ↓
+    /// +---------------------------------------------------------------+  -
+    /// ```
+    MulticastListenerQueryV2(icmpv6::MulticastListenerQueryV2Header),
+
+    /// MLDv2 "Multicast Listener Report" message (rfc3810).
+    ///
+    /// # What is part of the header for `Icmpv6Type::MulticastListenerReportV2`?
+    ///
+    /// The first 8 bytes/octets are part of the header. The multicast address
+    /// records are part of the payload and can be decoded via [`Icmpv6Slice::mldv2_records`].
+    ///
+    /// ```text
+    /// 0               1               2               3               4
+    /// +---------------------------------------------------------------+  -
+    /// |      143      |       0       |  checksum (in Icmpv6Header)   |  |
+    /// +---------------------------------------------------------------+  | part of header & type
+    /// |           reserved            |       number_of_records       |  ↓
+    /// +---------------------------------------------------------------+  -
+    /// |                                                               |  |
+    /// ...                  <multicast address records>              ...  | part of payload
+    /// |                                                               |  ↓
+    /// +---------------------------------------------------------------+  -
+    /// ```
+    MulticastListenerReportV2{ number_of_records: u16 },
+
+    /// "Extended Echo Request" message requesting the status of an
+    /// interface ("PROBE", rfc8335).
+    ///
+    /// # What is part of the header for `Icmpv6Type::ExtendedEchoRequest`?
+    ///
+    /// The first 8 bytes/octets are part of the header. The ICMP extension
+    /// structure identifying the probed interface is part of the payload.
+    ///
+    /// ```text
+    /// 0               1               2               3               4
+    /// +---------------------------------------------------------------+  -
+    /// |      160      |       0       |  checksum (in Icmpv6Header)   |  |
+    /// +---------------------------------------------------------------+  | part of header & type
+    /// |          [value].id           |   [v].seq     | reserved    |L|  ↓
+    /// +---------------------------------------------------------------+  -
+    /// |                                                               |  |
+    /// ...                 <ICMP extension structure>                ...  | part of payload
+    /// |                                                               |  ↓
+    /// +---------------------------------------------------------------+  -
+    /// ```
+    ExtendedEchoRequest(ExtendedEchoRequestHeader),
+
+    /// "Extended Echo Reply" message (rfc8335).
+    ///
+    /// # What is part of the header for `Icmpv6Type::ExtendedEchoReply`?
+    ///
+    /// All 8 bytes/octets of the message are part of the header.
+    ///
+    /// ```text
+    /// 0               1               2               3               4
+    /// +---------------------------------------------------------------+  -
+    /// |      161      | [value].code  |  checksum (in Icmpv6Header)   |  |
+    /// +---------------------------------------------------------------+  | part of header & type
+    /// |          [value].id           |   [v].seq     |State|Res|A|4|6|  ↓
+    /// +---------------------------------------------------------------+  -
+    /// ```
+    ExtendedEchoReply(ExtendedEchoReplyHeader),
 }
 
 impl Icmpv6Type {
@@ -671,6 +1126,18 @@ impl Icmpv6Type {
             ParameterProblem(_) => TYPE_PARAMETER_PROBLEM,
             EchoRequest(_) => TYPE_ECHO_REQUEST,
             EchoReply(_) => TYPE_ECHO_REPLY,
+            RouterSolicitation => TYPE_ROUTER_SOLICITATION,
+            RouterAdvertisement(_) => TYPE_ROUTER_ADVERTISEMENT,
+            NeighborSolicitation(_) => TYPE_NEIGHBOR_SOLICITATION,
+            NeighborAdvertisement(_) => TYPE_NEIGHBOR_ADVERTISEMENT,
+            Redirect(_) => TYPE_REDIRECT_MESSAGE,
+            MulticastListenerQuery(_) |
+            MulticastListenerQueryV2(_) => TYPE_MULTICAST_LISTENER_QUERY,
+            MulticastListenerReport(_) => TYPE_MULTICAST_LISTENER_REPORT,
+            MulticastListenerDone(_) => TYPE_MULTICAST_LISTENER_REDUCTION,
+            MulticastListenerReportV2{ .. } => TYPE_MULTICAST_LISTENER_REPORT_V2,
+            ExtendedEchoRequest(_) => TYPE_EXT_ECHO_REQUEST,
+            ExtendedEchoReply(_) => TYPE_EXT_ECHO_REPLY,
         }
     }
 
@@ -690,6 +1157,18 @@ impl Icmpv6Type {
             ParameterProblem(header) => header.code.code_u8(),
             EchoRequest(_) => 0,
             EchoReply(_) => 0,
+            RouterSolicitation |
+            RouterAdvertisement(_) |
+            NeighborSolicitation(_) |
+            NeighborAdvertisement(_) |
+            Redirect(_) |
+            MulticastListenerQuery(_) |
+            MulticastListenerReport(_) |
+            MulticastListenerDone(_) |
+            MulticastListenerQueryV2(_) |
+            MulticastListenerReportV2{ .. } |
+            ExtendedEchoRequest(_) => 0,
+            ExtendedEchoReply(header) => header.code.code_u8(),
         }
     }
 
@@ -766,6 +1245,28 @@ impl Icmpv6Type {
                     pseudo_sum.add_2bytes([TYPE_ECHO_REPLY, 0])
                     .add_4bytes(echo.to_bytes())
                 }
+                ExtendedEchoRequest(header) => {
+                    pseudo_sum.add_2bytes([TYPE_EXT_ECHO_REQUEST, 0])
+                    .add_4bytes(header.to_bytes())
+                }
+                ExtendedEchoReply(header) => {
+                    pseudo_sum.add_2bytes([TYPE_EXT_ECHO_REPLY, header.code.code_u8()])
+                    .add_4bytes(header.to_bytes())
+                }
+                RouterSolicitation |
+                RouterAdvertisement(_) |
+                NeighborSolicitation(_) |
+                NeighborAdvertisement(_) |
+                Redirect(_) |
+                MulticastListenerQuery(_) |
+                MulticastListenerReport(_) |
+                MulticastListenerDone(_) |
+                MulticastListenerQueryV2(_) |
+                MulticastListenerReportV2{ .. } => {
+                    // the checksum field is zero in the serialized
+                    // header & does not influence the sum
+                    pseudo_sum.add_slice(&Icmpv6Header::new(*self).to_bytes())
+                }
             }
             .add_slice(payload)
             .ones_complement()
@@ -803,7 +1304,19 @@ impl Icmpv6Type {
             | TimeExceeded(_)
             | ParameterProblem(_)
             | EchoRequest(_)
-            | EchoReply(_) => 8,
+            | EchoReply(_)
+            | RouterSolicitation
+            | ExtendedEchoRequest(_)
+            | ExtendedEchoReply(_) => 8,
+            RouterAdvertisement(_) => ROUTER_ADVERTISEMENT_HEADER_LEN,
+            NeighborSolicitation(_) => NEIGHBOR_SOLICITATION_HEADER_LEN,
+            NeighborAdvertisement(_) => NEIGHBOR_ADVERTISEMENT_HEADER_LEN,
+            Redirect(_) => REDIRECT_HEADER_LEN,
+            MulticastListenerQuery(_)
+            | MulticastListenerReport(_)
+            | MulticastListenerDone(_) => MULTICAST_LISTENER_HEADER_LEN,
+            MulticastListenerQueryV2(_) => MULTICAST_LISTENER_QUERY_V2_HEADER_LEN,
+            MulticastListenerReportV2{ .. } => MULTICAST_LISTENER_REPORT_V2_HEADER_LEN,
         }
     }
 
@@ -823,11 +1336,45 @@ impl Icmpv6Type {
             | TimeExceeded(_)
             | ParameterProblem(_)
             | EchoRequest(_)
-            | EchoReply(_) => None,
+            | EchoReply(_)
+            | RouterSolicitation
+            | RouterAdvertisement(_)
+            | NeighborSolicitation(_)
+            | NeighborAdvertisement(_)
+            | Redirect(_)
+            | MulticastListenerQueryV2(_)
+            | MulticastListenerReportV2{ .. }
+            | ExtendedEchoRequest(_) => None,
+            MulticastListenerQuery(_)
+            | MulticastListenerReport(_)
+            | MulticastListenerDone(_)
+            | ExtendedEchoReply(_) => Some(0),
         }
     }
 }
 
+/// Returns the header length of a neighbor discovery or MLD message (`None`
+/// if the type & code are not a known neighbor discovery or MLD message).
+///
+/// Note that for queries the length of a MLDv1 query is returned.
+fn extended_header_len(type_u8: u8, code_u8: u8) -> Option<usize> {
+    if 0 != code_u8 {
+        return None;
+    }
+    match type_u8 {
+        TYPE_MULTICAST_LISTENER_QUERY |
+        TYPE_MULTICAST_LISTENER_REPORT |
+        TYPE_MULTICAST_LISTENER_REDUCTION => Some(MULTICAST_LISTENER_HEADER_LEN),
+        TYPE_MULTICAST_LISTENER_REPORT_V2 => Some(MULTICAST_LISTENER_REPORT_V2_HEADER_LEN),
+        TYPE_ROUTER_SOLICITATION => Some(ROUTER_SOLICITATION_HEADER_LEN),
+        TYPE_ROUTER_ADVERTISEMENT => Some(ROUTER_ADVERTISEMENT_HEADER_LEN),
+        TYPE_NEIGHBOR_SOLICITATION => Some(NEIGHBOR_SOLICITATION_HEADER_LEN),
+        TYPE_NEIGHBOR_ADVERTISEMENT => Some(NEIGHBOR_ADVERTISEMENT_HEADER_LEN),
+        TYPE_REDIRECT_MESSAGE => Some(REDIRECT_HEADER_LEN),
+        _ => None,
+    }
+}
+
 /// The statically sized data at the start of an ICMPv6 packet (at least the first 8 bytes of an ICMPv6 packet).
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Icmpv6Header {
@@ -886,12 +1433,21 @@ impl Icmpv6Header {
     }
 
     /// Read a ICMPv6 header from the given reader
+    ///
+    /// Note that as the length of the message is not known when reading from
+    /// a reader, MLD queries are always decoded as MLDv1 queries
+    /// ([`Icmpv6Type::MulticastListenerQuery`]). Use [`Icmpv6Slice`] to
+    /// decode MLDv2 queries.
     pub fn read<T: io::Read + Sized>(reader: &mut T) -> Result<Icmpv6Header, ReadError> {
         // read the initial 8 bytes
-        let mut start = [0u8;8];
-        reader.read_exact(&mut start)?;
+        let mut bytes = [0u8;Icmpv6Header::MAX_SERIALIZED_SIZE];
+        reader.read_exact(&mut bytes[..8])?;
+
+        // read the rest of the header in case of neighbor discovery messages
+        let len = extended_header_len(bytes[0], bytes[1]).unwrap_or(8);
+        reader.read_exact(&mut bytes[8..len])?;
         Ok(Icmpv6Slice{
-            slice: &start
+            slice: &bytes[..len]
         }.header())
     }
 
@@ -981,6 +1537,12 @@ impl Icmpv6Header {
             re
         };
 
+        let return_extended = |type_u8: u8, bytes5to8: [u8;4], extension: &[u8]| -> ArrayVec<u8, { Icmpv6Header::MAX_SERIALIZED_SIZE }> {
+            let mut re = return_4u8(type_u8, 0, bytes5to8);
+            re.try_extend_from_slice(extension).unwrap();
+            re
+        };
+
         use Icmpv6Type::*;
         match self.icmp_type {
             Unknown {
@@ -1008,6 +1570,69 @@ impl Icmpv6Header {
             EchoReply(echo) => {
                 return_4u8(TYPE_ECHO_REPLY, 0, echo.to_bytes())
             },
+            RouterSolicitation => {
+                return_trivial(TYPE_ROUTER_SOLICITATION, 0)
+            },
+            RouterAdvertisement(header) => {
+                let bytes = header.to_bytes();
+                return_extended(
+                    TYPE_ROUTER_ADVERTISEMENT,
+                    [bytes[0], bytes[1], bytes[2], bytes[3]],
+                    &bytes[4..]
+                )
+            },
+            NeighborSolicitation(header) => {
+                return_extended(TYPE_NEIGHBOR_SOLICITATION, [0;4], &header.target_address)
+            },
+            NeighborAdvertisement(header) => {
+                return_extended(
+                    TYPE_NEIGHBOR_ADVERTISEMENT,
+                    [header.flags_u8(), 0, 0, 0],
+                    &header.target_address
+                )
+            },
+            Redirect(header) => {
+                let mut re = return_extended(TYPE_REDIRECT_MESSAGE, [0;4], &header.target_address);
+                re.try_extend_from_slice(&header.destination_address).unwrap();
+                re
+            },
+            MulticastListenerQuery(header) |
+            MulticastListenerReport(header) |
+            MulticastListenerDone(header) => {
+                let delay = header.maximum_response_delay.to_be_bytes();
+                return_extended(
+                    self.icmp_type.type_u8(),
+                    [delay[0], delay[1], 0, 0],
+                    &header.multicast_address
+                )
+            },
+            MulticastListenerQueryV2(header) => {
+                let code = header.maximum_response_code.to_be_bytes();
+                let num_sources = header.number_of_sources.to_be_bytes();
+                let mut re = return_extended(
+                    TYPE_MULTICAST_LISTENER_QUERY,
+                    [code[0], code[1], 0, 0],
+                    &header.multicast_address
+                );
+                re.try_extend_from_slice(&[
+                    if header.suppress_router_side_processing { 0b1000 } else { 0 } |
+                    (header.querier_robustness_variable & 0b111),
+                    header.querier_query_interval_code,
+                    num_sources[0],
+                    num_sources[1],
+                ]).unwrap();
+                re
+            },
+            MulticastListenerReportV2{ number_of_records } => {
+                let records = number_of_records.to_be_bytes();
+                return_4u8(TYPE_MULTICAST_LISTENER_REPORT_V2, 0, [0, 0, records[0], records[1]])
+            },
+            ExtendedEchoRequest(header) => {
+                return_4u8(TYPE_EXT_ECHO_REQUEST, 0, header.to_bytes())
+            },
+            ExtendedEchoReply(header) => {
+                return_4u8(TYPE_EXT_ECHO_REPLY, header.code.code_u8(), header.to_bytes())
+            },
         }
     }
 }
@@ -1057,7 +1682,7 @@ impl<'a> Icmpv6Slice<'a> {
     /// [`Icmpv6Header`] when [`Icmpv6Slice::header`] gets called.
     #[inline]
     pub fn header_len(&self) -> usize {
-        8
+        self.icmp_type().header_len()
     }
 
     /// Decode the header values (excluding the checksum) into an [`Icmpv6Type`] enum.
@@ -1102,6 +1727,31 @@ impl<'a> Icmpv6Slice<'a> {
                     return EchoReply(IcmpEchoHeader::from_bytes(self.bytes5to8()));
                 }
             }
+            TYPE_EXT_ECHO_REQUEST if 0 == self.code_u8() => {
+                return ExtendedEchoRequest(ExtendedEchoRequestHeader::from_bytes(self.bytes5to8()));
+            }
+            TYPE_EXT_ECHO_REPLY => {
+                if let Some(code) = ExtendedEchoReplyCode::from_u8(self.code_u8()) {
+                    return ExtendedEchoReply(ExtendedEchoReplyHeader::from_bytes(code, self.bytes5to8()));
+                }
+            }
+            TYPE_ROUTER_SOLICITATION |
+            TYPE_ROUTER_ADVERTISEMENT |
+            TYPE_NEIGHBOR_SOLICITATION |
+            TYPE_NEIGHBOR_ADVERTISEMENT |
+            TYPE_REDIRECT_MESSAGE => {
+                if let Some(result) = self.ndp_type() {
+                    return result;
+                }
+            }
+            TYPE_MULTICAST_LISTENER_QUERY |
+            TYPE_MULTICAST_LISTENER_REPORT |
+            TYPE_MULTICAST_LISTENER_REDUCTION |
+            TYPE_MULTICAST_LISTENER_REPORT_V2 => {
+                if let Some(result) = self.mld_type() {
+                    return result;
+                }
+            }
             _ => {}
         }
         Unknown {
@@ -1111,6 +1761,138 @@ impl<'a> Icmpv6Slice<'a> {
         }
     }
 
+    /// Decodes a neighbor discovery message (returns `None` if the code is
+    /// unknown or the slice is too short to contain the message header).
+    fn ndp_type(&self) -> Option<Icmpv6Type> {
+        use Icmpv6Type::*;
+
+        let len = extended_header_len(self.type_u8(), self.code_u8())?;
+        if self.slice.len() < len {
+            return None;
+        }
+        let address = |offset: usize| -> [u8;16] {
+            let mut result = [0u8;16];
+            result.copy_from_slice(&self.slice[offset..offset + 16]);
+            result
+        };
+        Some(match self.type_u8() {
+            TYPE_ROUTER_SOLICITATION => RouterSolicitation,
+            TYPE_ROUTER_ADVERTISEMENT => {
+                let mut bytes = [0u8;12];
+                bytes.copy_from_slice(&self.slice[4..16]);
+                RouterAdvertisement(RouterAdvertisementHeader::from_bytes(bytes))
+            },
+            TYPE_NEIGHBOR_SOLICITATION => NeighborSolicitation(
+                NeighborSolicitationHeader{
+                    target_address: address(8),
+                }
+            ),
+            TYPE_NEIGHBOR_ADVERTISEMENT => NeighborAdvertisement(
+                NeighborAdvertisementHeader{
+                    router: 0 != self.slice[4] & 0b1000_0000,
+                    solicited: 0 != self.slice[4] & 0b0100_0000,
+                    override_flag: 0 != self.slice[4] & 0b0010_0000,
+                    target_address: address(8),
+                }
+            ),
+            _ => Redirect(
+                RedirectHeader{
+                    target_address: address(8),
+                    destination_address: address(24),
+                }
+            ),
+        })
+    }
+
+    /// Decodes a MLD message (returns `None` if the code is unknown or the
+    /// slice has a length not matching the message type).
+    fn mld_type(&self) -> Option<Icmpv6Type> {
+        use Icmpv6Type::*;
+
+        let len = extended_header_len(self.type_u8(), self.code_u8())?;
+        if self.slice.len() < len {
+            return None;
+        }
+        let mut multicast_address = [0u8;16];
+        if len >= MULTICAST_LISTENER_HEADER_LEN {
+            multicast_address.copy_from_slice(&self.slice[8..24]);
+        }
+        let v1 = MulticastListenerHeader{
+            maximum_response_delay: u16::from_be_bytes([self.slice[4], self.slice[5]]),
+            multicast_address,
+        };
+        Some(match self.type_u8() {
+            TYPE_MULTICAST_LISTENER_QUERY => {
+                // the version of a query is determined by its length (rfc3810 section 8.1)
+                if self.slice.len() == MULTICAST_LISTENER_HEADER_LEN {
+                    MulticastListenerQuery(v1)
+                } else if self.slice.len() >= MULTICAST_LISTENER_QUERY_V2_HEADER_LEN {
+                    MulticastListenerQueryV2(MulticastListenerQueryV2Header{
+                        maximum_response_code: v1.maximum_response_delay,
+                        multicast_address,
+                        suppress_router_side_processing: 0 != self.slice[24] & 0b1000,
+                        querier_robustness_variable: self.slice[24] & 0b111,
+                        querier_query_interval_code: self.slice[25],
+                        number_of_sources: u16::from_be_bytes([self.slice[26], self.slice[27]]),
+                    })
+                } else {
+                    return None;
+                }
+            },
+            TYPE_MULTICAST_LISTENER_REPORT => MulticastListenerReport(v1),
+            TYPE_MULTICAST_LISTENER_REDUCTION => MulticastListenerDone(v1),
+            _ => MulticastListenerReportV2{
+                number_of_records: u16::from_be_bytes([self.slice[6], self.slice[7]]),
+            },
+        })
+    }
+
+    /// Returns the source addresses of a MLDv2 query (`None` if the message
+    /// is not a MLDv2 query or if the slice is too short to contain the number
+    /// of sources given in the header).
+    pub fn mldv2_query_sources(&self) -> Option<&'a [[u8;16]]> {
+        if let Icmpv6Type::MulticastListenerQueryV2(header) = self.icmp_type() {
+            let payload = self.payload();
+            let len = usize::from(header.number_of_sources)*16;
+            if payload.len() < len {
+                None
+            } else {
+                // SAFETY: [u8;16] has an alignment of 1 & the length
+                // was checked to be big enough
+                Some(unsafe {
+                    from_raw_parts(payload.as_ptr() as *const [u8;16], usize::from(header.number_of_sources))
+                })
+            }
+        } else {
+            None
+        }
+    }
+
+    /// Returns an iterator over the multicast address records of a MLDv2
+    /// report (`None` if the message is not a MLDv2 report).
+    pub fn mldv2_records(&self) -> Option<MulticastAddressRecordsIterator<'a>> {
+        if let Icmpv6Type::MulticastListenerReportV2{ number_of_records } = self.icmp_type() {
+            Some(MulticastAddressRecordsIterator::from_slice(number_of_records, self.payload()))
+        } else {
+            None
+        }
+    }
+
+    /// Returns an iterator over the neighbor discovery options in the payload
+    /// if the message is a neighbor discovery message (router & neighbor
+    /// solicitations & advertisements or redirects).
+    pub fn ndp_options(&self) -> Option<NdpOptionsIterator<'a>> {
+        use Icmpv6Type::*;
+        match self.icmp_type() {
+            RouterSolicitation |
+            RouterAdvertisement(_) |
+            NeighborSolicitation(_) |
+            NeighborAdvertisement(_) |
+            Redirect(_) => Some(NdpOptionsIterator::from_slice(self.payload())),
+            _ => None,
+        }
+    }
+
     /// Returns "type" value in the ICMPv6 header.
     #[inline]
     pub fn type_u8(&self) -> u8 {
@@ -1184,11 +1966,67 @@ impl<'a> Icmpv6Slice<'a> {
     }
 
     /// Returns a slice to the bytes not covered by `.header()`.
+    ///
+    /// For "Destination Unreachable" & "Time Exceeded" messages the payload
+    /// can contain an ICMP extension structure after the original datagram
+    /// (see [`Icmpv6Slice::original_datagram`] & [`Icmpv6Slice::extensions`]).
     #[inline]
     pub fn payload(&self) -> &'a [u8] {
         // SAFETY:
-        // Safe as the contructor checks that the slice has
-        // at least the length of Icmpv6Header::MIN_SERIALIZED_SIZE(8).
-        unsafe { from_raw_parts(self.slice.as_ptr().add(8), self.slice.len() - 8) }
+        // Safe as the header length is only bigger then
+        // Icmpv6Header::MIN_SERIALIZED_SIZE (8) if the slice is
+        // long enough to contain the header.
+        let header_len = self.header_len();
+        unsafe { from_raw_parts(self.slice.as_ptr().add(header_len), self.slice.len() - header_len) }
+    }
+
+    /// Returns the "as much of invoking packet as possible" part of the payload.
+    ///
+    /// For "Destination Unreachable" & "Time Exceeded" messages with a non
+    /// zero "length" field (rfc4884) only the bytes covered by the length
+    /// field are returned (the ICMP extension structure following them can be
+    /// accessed via [`Icmpv6Slice::extensions`]). In all other cases the
+    /// complete payload is returned.
+    pub fn original_datagram(&self) -> &'a [u8] {
+        self.split_multipart_payload().0
+    }
+
+    /// Returns the ICMP extension structure (rfc4884) appended after the
+    /// original datagram in "Destination Unreachable" & "Time Exceeded" messages.
+    ///
+    /// Returns `None` if the message type does not support extensions,
+    /// the "length" field is zero, the payload is shorter then the value
+    /// in the "length" field or no valid extension header is present.
+    pub fn extensions(&self) -> Option<IcmpExtensionsSlice<'a>> {
+        self.split_multipart_payload().1
+    }
+
+    /// Slices the packet quoted in "Destination Unreachable", "Packet Too Big",
+    /// "Time Exceeded" & "Parameter Problem" messages.
+    ///
+    /// The quoted packet is sliced in a tolerant mode (truncated transport
+    /// headers & length fields exceeding the quoted data are accepted, see
+    /// [`IcmpEmbeddedPacketSlice`]). ICMP extension structures following
+    /// the quoted packet are not included.
+    ///
+    /// Returns `None` if the message type does not contain a quoted packet.
+    pub fn embedded_packet(&self) -> Option<Result<IcmpEmbeddedPacketSlice<'a>, ReadError>> {
+        match self.type_u8() {
+            TYPE_DST_UNREACH | TYPE_PACKET_TOO_BIG | TYPE_TIME_EXCEEDED | TYPE_PARAMETER_PROBLEM => {
+                Some(IcmpEmbeddedPacketSlice::from_slice(self.original_datagram()))
+            }
+            _ => None,
+        }
+    }
+
+    /// Splits the payload into the original datagram & the extension structure.
+    fn split_multipart_payload(&self) -> (&'a [u8], Option<IcmpExtensionsSlice<'a>>) {
+        match self.type_u8() {
+            TYPE_DST_UNREACH | TYPE_TIME_EXCEEDED => {
+                // length is in 64-bit words
+                split_icmp_multipart_payload(self.payload(), usize::from(self.slice[4]) * 8)
+            }
+            _ => (self.payload(), None),
+        }
     }
 }
@@ -0,0 +1,640 @@
+use super::super::*;
+
+use arrayvec::ArrayVec;
+use std::fmt::{Debug, Formatter};
+use std::slice::from_raw_parts;
+
+/// Module containing IGMP related types and constants.
+pub mod igmp {
+
+    /// IGMP type value of a "Membership Query" message (all versions, defined in [RFC 1112](https://tools.ietf.org/html/rfc1112), [RFC 2236](https://tools.ietf.org/html/rfc2236) & [RFC 3376](https://tools.ietf.org/html/rfc3376)).
+    pub const TYPE_MEMBERSHIP_QUERY: u8 = 0x11;
+
+    /// IGMP type value of a "Version 1 Membership Report" message (defined in [RFC 1112](https://tools.ietf.org/html/rfc1112)).
+    pub const TYPE_MEMBERSHIP_REPORT_V1: u8 = 0x12;
+
+    /// IGMP type value of a "Version 2 Membership Report" message (defined in [RFC 2236](https://tools.ietf.org/html/rfc2236)).
+    pub const TYPE_MEMBERSHIP_REPORT_V2: u8 = 0x16;
+
+    /// IGMP type value of a "Leave Group" message (defined in [RFC 2236](https://tools.ietf.org/html/rfc2236)).
+    pub const TYPE_LEAVE_GROUP: u8 = 0x17;
+
+    /// IGMP type value of a "Version 3 Membership Report" message (defined in [RFC 3376](https://tools.ietf.org/html/rfc3376)).
+    pub const TYPE_MEMBERSHIP_REPORT_V3: u8 = 0x22;
+
+    /// Length in bytes of an IGMPv1 & IGMPv2 header (all message types).
+    pub const HEADER_LEN: usize = 8;
+
+    /// Length in bytes of the fixed part of an IGMPv3 "Membership Query" message.
+    pub const MEMBERSHIP_QUERY_V3_HEADER_LEN: usize = 12;
+
+    /// Fixed part of an IGMPv1 or IGMPv2 "Membership Query" message.
+    ///
+    /// IGMPv1 queries ([RFC 1112](https://tools.ietf.org/html/rfc1112))
+    /// are identical to IGMPv2 queries with a `max_response_time` of 0.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct MembershipQueryHeader {
+        /// Maximum time in units of 1/10 second before sending a responding
+        /// report (0 in IGMPv1 queries).
+        pub max_response_time: u8,
+        /// Group address being queried (zero in general queries).
+        pub group_address: [u8;4],
+    }
+
+    /// Fixed part of an IGMPv3 (rfc3376) "Membership Query" message.
+    ///
+    /// The source addresses following the fixed part are part of the payload
+    /// and can be accessed via [`crate::IgmpSlice::v3_query_sources`].
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct MembershipQueryV3Header {
+        /// Encoded maximum response time (see [`MembershipQueryV3Header::max_response_time`]).
+        pub max_response_code: u8,
+        /// Group address being queried (zero in general queries).
+        pub group_address: [u8;4],
+        /// "S" flag, routers receiving the query should suppress timer updates.
+        pub suppress_router_side_processing: bool,
+        /// Querier's robustness variable (3 bits, higher bits are ignored
+        /// when serializing).
+        pub querier_robustness_variable: u8,
+        /// Encoded query interval of the querier (see [`MembershipQueryV3Header::querier_query_interval`]).
+        pub querier_query_interval_code: u8,
+        /// Number of source addresses present in the query.
+        pub number_of_sources: u16,
+    }
+
+    impl MembershipQueryV3Header {
+        /// Decodes the maximum response time in units of 1/10 second from
+        /// the `max_response_code` field (rfc3376 section 4.1.1).
+        pub fn max_response_time(&self) -> u32 {
+            decode_exponential(self.max_response_code)
+        }
+
+        /// Decodes the querier's query interval in seconds from the
+        /// `querier_query_interval_code` field (rfc3376 section 4.1.7).
+        pub fn querier_query_interval(&self) -> u32 {
+            decode_exponential(self.querier_query_interval_code)
+        }
+    }
+
+    /// Decodes the 8 bit floating point format used by IGMPv3.
+    fn decode_exponential(code: u8) -> u32 {
+        let code = u32::from(code);
+        if code < 0x80 {
+            code
+        } else {
+            let mantissa = code & 0xf;
+            let exponent = (code >> 4) & 0b111;
+            (mantissa | 0x10) << (exponent + 3)
+        }
+    }
+}
+
+use igmp::*;
+
+/// Module containing the record types of IGMPv3 group records (rfc3376).
+pub mod igmp_record_type {
+    /// The interface has a filter mode of INCLUDE for the listed sources (current state record).
+    pub const MODE_IS_INCLUDE: u8 = 1;
+    /// The interface has a filter mode of EXCLUDE for the listed sources (current state record).
+    pub const MODE_IS_EXCLUDE: u8 = 2;
+    /// The filter mode changed to INCLUDE (filter mode change record).
+    pub const CHANGE_TO_INCLUDE_MODE: u8 = 3;
+    /// The filter mode changed to EXCLUDE (filter mode change record).
+    pub const CHANGE_TO_EXCLUDE_MODE: u8 = 4;
+    /// The listed sources should be additionally received (source list change record).
+    pub const ALLOW_NEW_SOURCES: u8 = 5;
+    /// The listed sources should no longer be received (source list change record).
+    pub const BLOCK_OLD_SOURCES: u8 = 6;
+}
+
+/// Different kinds of IGMP messages (all versions).
+///
+/// The version of a "Membership Query" is determined by the length of the
+/// message (rfc3376 section 7.1): Queries with a length of exactly 8 bytes
+/// are IGMPv1 or IGMPv2 queries and queries with a length of at least 12
+/// bytes are IGMPv3 queries. Messages with a length in between are decoded
+/// as [`IgmpType::Unknown`].
+///
+/// Reports & leave messages are only decoded if the unused fields are zero,
+/// otherwise they are also decoded as [`IgmpType::Unknown`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IgmpType {
+    /// IGMPv1 or IGMPv2 "Membership Query" message.
+    MembershipQuery(igmp::MembershipQueryHeader),
+    /// IGMPv3 "Membership Query" message (the source addresses are part of the payload).
+    MembershipQueryV3(igmp::MembershipQueryV3Header),
+    /// IGMPv1 "Membership Report" message.
+    MembershipReportV1{ group_address: [u8;4] },
+    /// IGMPv2 "Membership Report" message.
+    MembershipReportV2{ group_address: [u8;4] },
+    /// IGMPv2 "Leave Group" message.
+    LeaveGroup{ group_address: [u8;4] },
+    /// IGMPv3 "Membership Report" message (the group records are part of the payload).
+    MembershipReportV3{ number_of_records: u16 },
+    /// Message type not decoded by etherparse (or a message with a length
+    /// or field values not matching the type).
+    Unknown {
+        /// IGMP type (present in the first byte of the IGMP packet).
+        type_u8: u8,
+        /// Second byte of the IGMP packet ("Max Resp Code" or unused depending on the type).
+        max_response_code: u8,
+        /// Bytes located at th 5th, 6th, 7th and 8th position of the IGMP packet.
+        bytes5to8: [u8;4],
+    },
+}
+
+impl IgmpType {
+    /// Returns the type value (first byte of the IGMP header) of this type.
+    pub fn type_u8(&self) -> u8 {
+        use IgmpType::*;
+        match self {
+            MembershipQuery(_) | MembershipQueryV3(_) => TYPE_MEMBERSHIP_QUERY,
+            MembershipReportV1{ .. } => TYPE_MEMBERSHIP_REPORT_V1,
+            MembershipReportV2{ .. } => TYPE_MEMBERSHIP_REPORT_V2,
+            LeaveGroup{ .. } => TYPE_LEAVE_GROUP,
+            MembershipReportV3{ .. } => TYPE_MEMBERSHIP_REPORT_V3,
+            Unknown{ type_u8, .. } => *type_u8,
+        }
+    }
+
+    /// Returns the length in bytes/octets of the header of
+    /// this IGMP message type.
+    #[inline]
+    pub fn header_len(&self) -> usize {
+        match self {
+            IgmpType::MembershipQueryV3(_) => MEMBERSHIP_QUERY_V3_HEADER_LEN,
+            _ => HEADER_LEN,
+        }
+    }
+
+    /// If the IGMP type has a fixed size returns the number of
+    /// bytes that should be present after the header of this type.
+    #[inline]
+    pub fn fixed_payload_size(&self) -> Option<usize> {
+        use IgmpType::*;
+        match self {
+            MembershipQuery(_) |
+            MembershipReportV1{ .. } |
+            MembershipReportV2{ .. } |
+            LeaveGroup{ .. } => Some(0),
+            MembershipQueryV3(_) |
+            MembershipReportV3{ .. } |
+            Unknown{ .. } => None,
+        }
+    }
+
+    /// Calculate the IGMP checksum value (calculated over the header
+    /// & the payload without a pseudo header).
+    pub fn calc_checksum(&self, payload: &[u8]) -> u16 {
+        let header = IgmpHeader{
+            igmp_type: *self,
+            checksum: 0,
+        };
+        checksum::Sum16BitWords::new()
+            .add_slice(&header.to_bytes())
+            .add_slice(payload)
+            .ones_complement()
+            .to_be()
+    }
+}
+
+/// A header of an IGMP packet (all versions).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IgmpHeader {
+    /// Type & type specific values.
+    pub igmp_type: IgmpType,
+    /// Checksum in the IGMP header.
+    pub checksum: u16,
+}
+
+impl IgmpHeader {
+    /// Minimum number of bytes/octets an IgmpHeader takes up
+    /// in serialized form.
+    pub const MIN_SERIALIZED_SIZE: usize = 8;
+
+    /// Maximum number of bytes/octets an IgmpHeader takes up
+    /// in serialized form (IGMPv3 "Membership Query").
+    pub const MAX_SERIALIZED_SIZE: usize = 12;
+
+    /// Constructs an [`IgmpHeader`] using the given type
+    /// and the checksum set to 0.
+    pub fn new(igmp_type: IgmpType) -> IgmpHeader {
+        IgmpHeader {
+            igmp_type,
+            checksum: 0,
+        }
+    }
+
+    /// Creates a [`IgmpHeader`] with a checksum calculated based on the given payload.
+    pub fn with_checksum(igmp_type: IgmpType, payload: &[u8]) -> IgmpHeader {
+        let checksum = igmp_type.calc_checksum(payload);
+        IgmpHeader {
+            igmp_type,
+            checksum,
+        }
+    }
+
+    /// Reads an IGMP header from a slice directly and returns a tuple containing the resulting header & unused part of the slice.
+    #[inline]
+    pub fn from_slice(slice: &[u8]) -> Result<(IgmpHeader, &[u8]), ReadError> {
+        let header = IgmpSlice::from_slice(slice)?.header();
+        let rest = &slice[header.header_len()..];
+        Ok((header, rest))
+    }
+
+    /// Reads an IGMP header from the given reader.
+    ///
+    /// Note that as the length of the message is not known when reading from
+    /// a reader, queries are always decoded as IGMPv1 or IGMPv2 queries
+    /// ([`IgmpType::MembershipQuery`]). Use [`IgmpSlice`] to decode IGMPv3
+    /// queries.
+    pub fn read<T: io::Read + Sized>(reader: &mut T) -> Result<IgmpHeader, ReadError> {
+        let mut bytes = [0u8;IgmpHeader::MIN_SERIALIZED_SIZE];
+        reader.read_exact(&mut bytes)?;
+        Ok(IgmpSlice {
+            slice: &bytes
+        }.header())
+    }
+
+    /// Write the IGMP header to the given writer.
+    pub fn write<T: io::Write + Sized>(&self, writer: &mut T) -> Result<(), WriteError> {
+        writer.write_all(&self.to_bytes()).map_err(WriteError::from)
+    }
+
+    /// Length in bytes/octets of this header type.
+    #[inline]
+    pub fn header_len(&self) -> usize {
+        self.igmp_type.header_len()
+    }
+
+    /// If the IGMP type has a fixed size returns the number of
+    /// bytes that should be present after the header of this type.
+    #[inline]
+    pub fn fixed_payload_size(&self) -> Option<usize> {
+        self.igmp_type.fixed_payload_size()
+    }
+
+    /// Calculates & updates the checksum in the header.
+    pub fn update_checksum(&mut self, payload: &[u8]) {
+        self.checksum = self.igmp_type.calc_checksum(payload);
+    }
+
+    /// Converts the header to the on the wire bytes.
+    pub fn to_bytes(&self) -> ArrayVec<u8, { IgmpHeader::MAX_SERIALIZED_SIZE }> {
+        use IgmpType::*;
+
+        let checksum_be = self.checksum.to_be_bytes();
+        let re_8 = |type_u8: u8, code_u8: u8, bytes5to8: [u8;4]| -> ArrayVec<u8, { IgmpHeader::MAX_SERIALIZED_SIZE }> {
+            let mut re = ArrayVec::from([
+                type_u8, code_u8, checksum_be[0], checksum_be[1],
+                bytes5to8[0], bytes5to8[1], bytes5to8[2], bytes5to8[3],
+                0, 0, 0, 0,
+            ]);
+            re.truncate(HEADER_LEN);
+            re
+        };
+
+        match self.igmp_type {
+            MembershipQuery(header) => re_8(TYPE_MEMBERSHIP_QUERY, header.max_response_time, header.group_address),
+            MembershipQueryV3(header) => {
+                let a = header.group_address;
+                let flags = if header.suppress_router_side_processing { 0b1000 } else { 0 } |
+                            (header.querier_robustness_variable & 0b111);
+                let n = header.number_of_sources.to_be_bytes();
+                ArrayVec::from([
+                    TYPE_MEMBERSHIP_QUERY, header.max_response_code, checksum_be[0], checksum_be[1],
+                    a[0], a[1], a[2], a[3],
+                    flags, header.querier_query_interval_code, n[0], n[1],
+                ])
+            },
+            MembershipReportV1{ group_address } => re_8(TYPE_MEMBERSHIP_REPORT_V1, 0, group_address),
+            MembershipReportV2{ group_address } => re_8(TYPE_MEMBERSHIP_REPORT_V2, 0, group_address),
+            LeaveGroup{ group_address } => re_8(TYPE_LEAVE_GROUP, 0, group_address),
+            MembershipReportV3{ number_of_records } => {
+                let n = number_of_records.to_be_bytes();
+                re_8(TYPE_MEMBERSHIP_REPORT_V3, 0, [0, 0, n[0], n[1]])
+            },
+            Unknown{ type_u8, max_response_code, bytes5to8 } => re_8(type_u8, max_response_code, bytes5to8),
+        }
+    }
+}
+
+/// A slice containing an IGMP packet (all versions).
+///
+/// Struct allows the selective read of fields in the IGMP
+/// packet.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct IgmpSlice<'a> {
+    slice: &'a [u8],
+}
+
+impl<'a> IgmpSlice<'a> {
+    /// Creates a slice containing an IGMP packet.
+    ///
+    /// # Errors
+    ///
+    /// The function will return an `Err` `ReadError::UnexpectedEndOfSlice`
+    /// if the given slice is too small.
+    #[inline]
+    pub fn from_slice(slice: &'a [u8]) -> Result<IgmpSlice<'a>, ReadError> {
+        if slice.len() < IgmpHeader::MIN_SERIALIZED_SIZE {
+            return Err(ReadError::UnexpectedEndOfSlice(IgmpHeader::MIN_SERIALIZED_SIZE));
+        }
+        Ok(IgmpSlice { slice })
+    }
+
+    /// Decode the header values into an [`IgmpHeader`] struct.
+    #[inline]
+    pub fn header(&self) -> IgmpHeader {
+        IgmpHeader {
+            igmp_type: self.igmp_type(),
+            checksum: self.checksum(),
+        }
+    }
+
+    /// Number of bytes/octets that will be converted into a
+    /// [`IgmpHeader`] when [`IgmpSlice::header`] gets called.
+    #[inline]
+    pub fn header_len(&self) -> usize {
+        self.igmp_type().header_len()
+    }
+
+    /// Decode the header values (excluding the checksum) into an [`IgmpType`] enum.
+    pub fn igmp_type(&self) -> IgmpType {
+        use IgmpType::*;
+
+        let mut bytes5to8 = [0u8;4];
+        bytes5to8.copy_from_slice(&self.slice[4..8]);
+        let unknown = Unknown{
+            type_u8: self.type_u8(),
+            max_response_code: self.max_response_code(),
+            bytes5to8,
+        };
+
+        match self.type_u8() {
+            TYPE_MEMBERSHIP_QUERY => {
+                if self.slice.len() == HEADER_LEN {
+                    MembershipQuery(MembershipQueryHeader{
+                        max_response_time: self.max_response_code(),
+                        group_address: bytes5to8,
+                    })
+                } else if self.slice.len() >= MEMBERSHIP_QUERY_V3_HEADER_LEN {
+                    MembershipQueryV3(MembershipQueryV3Header{
+                        max_response_code: self.max_response_code(),
+                        group_address: bytes5to8,
+                        suppress_router_side_processing: 0 != self.slice[8] & 0b1000,
+                        querier_robustness_variable: self.slice[8] & 0b111,
+                        querier_query_interval_code: self.slice[9],
+                        number_of_sources: u16::from_be_bytes([self.slice[10], self.slice[11]]),
+                    })
+                } else {
+                    unknown
+                }
+            },
+            TYPE_MEMBERSHIP_REPORT_V1 if 0 == self.max_response_code() => MembershipReportV1{ group_address: bytes5to8 },
+            TYPE_MEMBERSHIP_REPORT_V2 if 0 == self.max_response_code() => MembershipReportV2{ group_address: bytes5to8 },
+            TYPE_LEAVE_GROUP if 0 == self.max_response_code() => LeaveGroup{ group_address: bytes5to8 },
+            TYPE_MEMBERSHIP_REPORT_V3 if 0 == self.max_response_code() && [0, 0] == bytes5to8[..2] => {
+                MembershipReportV3{
+                    number_of_records: u16::from_be_bytes([bytes5to8[2], bytes5to8[3]]),
+                }
+            },
+            _ => unknown,
+        }
+    }
+
+    /// Returns "type" value in the IGMP header.
+    #[inline]
+    pub fn type_u8(&self) -> u8 {
+        self.slice[0]
+    }
+
+    /// Returns the second byte of the IGMP header ("Max Resp Code" in
+    /// queries, unused in all other messages).
+    #[inline]
+    pub fn max_response_code(&self) -> u8 {
+        self.slice[1]
+    }
+
+    /// Returns "checksum" value in the IGMP header.
+    #[inline]
+    pub fn checksum(&self) -> u16 {
+        u16::from_be_bytes([self.slice[2], self.slice[3]])
+    }
+
+    /// Returns if the checksum in the slice is correct (calculated over
+    /// the whole slice).
+    pub fn is_checksum_valid(&self) -> bool {
+        checksum::Sum16BitWords::new()
+            .add_slice(self.slice)
+            .ones_complement()
+            == 0
+    }
+
+    /// Returns the slice containing the payload of the IGMP packet
+    /// (everything after the header).
+    #[inline]
+    pub fn payload(&self) -> &'a [u8] {
+        &self.slice[self.header_len()..]
+    }
+
+    /// Returns the source addresses of an IGMPv3 query (`None` if the message
+    /// is not an IGMPv3 query or if the slice is too short to contain the
+    /// number of sources given in the header).
+    pub fn v3_query_sources(&self) -> Option<&'a [[u8;4]]> {
+        if let IgmpType::MembershipQueryV3(header) = self.igmp_type() {
+            let payload = self.payload();
+            let len = usize::from(header.number_of_sources)*4;
+            if payload.len() < len {
+                None
+            } else {
+                // SAFETY: [u8;4] has an alignment of 1 & the length
+                // was checked to be big enough
+                Some(unsafe {
+                    from_raw_parts(payload.as_ptr() as *const [u8;4], usize::from(header.number_of_sources))
+                })
+            }
+        } else {
+            None
+        }
+    }
+
+    /// Returns an iterator over the group records of an IGMPv3 report
+    /// (`None` if the message is not an IGMPv3 report).
+    pub fn v3_group_records(&self) -> Option<IgmpGroupRecordsIterator<'a>> {
+        if let IgmpType::MembershipReportV3{ number_of_records } = self.igmp_type() {
+            Some(IgmpGroupRecordsIterator::from_slice(number_of_records, self.payload()))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the slice containing the IGMP packet.
+    #[inline]
+    pub fn slice(&self) -> &'a [u8] {
+        self.slice
+    }
+}
+
+/// Group record contained in the payload of an IGMPv3 report
+/// ([`IgmpType::MembershipReportV3`]).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct IgmpGroupRecord<'a> {
+    /// Type of the record (see [`igmp_record_type`]).
+    pub record_type: u8,
+    /// The multicast address the record refers to.
+    pub multicast_address: [u8;4],
+    /// Source addresses of the record.
+    pub sources: &'a [[u8;4]],
+    /// Auxiliary data (must be a multiple of 4 bytes, not defined by rfc3376).
+    pub aux_data: &'a [u8],
+}
+
+impl<'a> IgmpGroupRecord<'a> {
+    /// Minimum length of a record in bytes (record without sources & auxiliary data).
+    pub const MIN_LEN: usize = 8;
+
+    /// Maximum length of the auxiliary data (the length field counts units of 4 bytes).
+    pub const MAX_AUX_DATA_LEN: usize = 0xff*4;
+
+    /// Length of the record in bytes when serialized.
+    pub fn serialized_len(&self) -> usize {
+        IgmpGroupRecord::MIN_LEN + self.sources.len()*4 + self.aux_data.len()
+    }
+
+    /// Writes the record to the given writer.
+    ///
+    /// # Errors
+    ///
+    /// `ValueError::IgmpGroupRecordLengthBad` is returned if the record
+    /// contains more then 65535 sources or if the auxiliary data is not a
+    /// multiple of 4 bytes or bigger then 1020 bytes.
+    pub fn write<T: io::Write + Sized>(&self, writer: &mut T) -> Result<(), WriteError> {
+        use ValueError::IgmpGroupRecordLengthBad;
+
+        if self.sources.len() > usize::from(u16::MAX) {
+            return Err(WriteError::ValueError(IgmpGroupRecordLengthBad(self.serialized_len())));
+        }
+        let aux_len = match self.aux_data.len() % 4 {
+            0 if self.aux_data.len() <= IgmpGroupRecord::MAX_AUX_DATA_LEN => (self.aux_data.len() / 4) as u8,
+            _ => return Err(WriteError::ValueError(IgmpGroupRecordLengthBad(self.serialized_len()))),
+        };
+        let num_sources_be = (self.sources.len() as u16).to_be_bytes();
+        writer.write_all(&[self.record_type, aux_len, num_sources_be[0], num_sources_be[1]])?;
+        writer.write_all(&self.multicast_address)?;
+        for source in self.sources {
+            writer.write_all(source)?;
+        }
+        writer.write_all(self.aux_data)?;
+        Ok(())
+    }
+}
+
+/// Errors that can occour while reading the group records of an IGMPv3 report.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum IgmpGroupRecordReadError {
+    /// Returned if there was not enough memory left in the slice to read the record.
+    UnexpectedEndOfSlice{ expected_len: usize, actual_len: usize },
+}
+
+impl Error for IgmpGroupRecordReadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        None
+    }
+}
+
+impl fmt::Display for IgmpGroupRecordReadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use IgmpGroupRecordReadError::*;
+        match self {
+            UnexpectedEndOfSlice{expected_len, actual_len} => {
+                write!(f, "IgmpGroupRecordReadError: Not enough memory left in slice to read group record (expected at least {} bytes, only {} bytes available).", expected_len, actual_len)
+            },
+        }
+    }
+}
+
+/// Allows iterating over the group records in the payload of an IGMPv3 report.
+///
+/// The iterator stops after the number of records given in the report header
+/// or after an error was returned.
+#[derive(Clone, Eq, PartialEq)]
+pub struct IgmpGroupRecordsIterator<'a> {
+    number_of_records: u16,
+    slice: &'a [u8],
+}
+
+impl<'a> IgmpGroupRecordsIterator<'a> {
+    /// Creates an iterator over `number_of_records` records encoded in the given slice.
+    pub fn from_slice(number_of_records: u16, slice: &'a [u8]) -> IgmpGroupRecordsIterator<'a> {
+        IgmpGroupRecordsIterator{ number_of_records, slice }
+    }
+
+    /// Returns the non processed part of the slice.
+    pub fn rest(&self) -> &'a [u8] {
+        self.slice
+    }
+}
+
+impl<'a> Iterator for IgmpGroupRecordsIterator<'a> {
+    type Item = Result<IgmpGroupRecord<'a>, IgmpGroupRecordReadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        use IgmpGroupRecordReadError::*;
+
+        if 0 == self.number_of_records {
+            return None;
+        }
+
+        // determine & check the length
+        let len = if self.slice.len() < IgmpGroupRecord::MIN_LEN {
+            IgmpGroupRecord::MIN_LEN
+        } else {
+            IgmpGroupRecord::MIN_LEN +
+                usize::from(u16::from_be_bytes([self.slice[2], self.slice[3]]))*4 +
+                usize::from(self.slice[1])*4
+        };
+        if self.slice.len() < len {
+            let actual_len = self.slice.len();
+            self.number_of_records = 0;
+            self.slice = &self.slice[actual_len..];
+            return Some(Err(UnexpectedEndOfSlice{ expected_len: len, actual_len }));
+        }
+
+        let record = &self.slice[..len];
+        let sources_end = IgmpGroupRecord::MIN_LEN + usize::from(u16::from_be_bytes([record[2], record[3]]))*4;
+        let sources = &record[IgmpGroupRecord::MIN_LEN..sources_end];
+        let mut multicast_address = [0u8;4];
+        multicast_address.copy_from_slice(&record[4..8]);
+
+        self.number_of_records -= 1;
+        self.slice = &self.slice[len..];
+        Some(Ok(IgmpGroupRecord{
+            record_type: record[0],
+            multicast_address,
+            // SAFETY: [u8;4] has an alignment of 1 & the length is a multiple of 4
+            sources: unsafe {
+                from_raw_parts(sources.as_ptr() as *const [u8;4], sources.len() / 4)
+            },
+            aux_data: &record[sources_end..],
+        }))
+    }
+}
+
+impl<'a> Debug for IgmpGroupRecordsIterator<'a> {
+    fn fmt(&self, fmt: &mut Formatter) -> Result<(), std::fmt::Error> {
+        let mut list = fmt.debug_list();
+
+        // create a copy and iterate over all elements
+        for it in self.clone() {
+            match it {
+                Ok(e) => { list.entry(&e); },
+                Err(e) => {
+                    list.entry(&Result::<(), IgmpGroupRecordReadError>::Err(e.clone()));
+                }
+            }
+        }
+
+        list.finish()
+    }
+}
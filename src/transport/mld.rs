@@ -0,0 +1,183 @@
+use super::super::*;
+
+use std::fmt::{Debug, Formatter};
+use std::slice::from_raw_parts;
+
+/// Module containing the record types of MLDv2 multicast address records (rfc3810).
+pub mod mld_record_type {
+    /// The interface has a filter mode of INCLUDE for the listed sources (current state record).
+    pub const MODE_IS_INCLUDE: u8 = 1;
+    /// The interface has a filter mode of EXCLUDE for the listed sources (current state record).
+    pub const MODE_IS_EXCLUDE: u8 = 2;
+    /// The filter mode changed to INCLUDE (filter mode change record).
+    pub const CHANGE_TO_INCLUDE_MODE: u8 = 3;
+    /// The filter mode changed to EXCLUDE (filter mode change record).
+    pub const CHANGE_TO_EXCLUDE_MODE: u8 = 4;
+    /// The listed sources should be additionally received (source list change record).
+    pub const ALLOW_NEW_SOURCES: u8 = 5;
+    /// The listed sources should no longer be received (source list change record).
+    pub const BLOCK_OLD_SOURCES: u8 = 6;
+}
+
+/// Multicast address record contained in the payload of a MLDv2 report
+/// ([`Icmpv6Type::MulticastListenerReportV2`]).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MulticastAddressRecord<'a> {
+    /// Type of the record (see [`mld_record_type`]).
+    pub record_type: u8,
+    /// The multicast address the record refers to.
+    pub multicast_address: [u8;16],
+    /// Source addresses of the record.
+    pub sources: &'a [[u8;16]],
+    /// Auxiliary data (must be a multiple of 4 bytes, not defined by rfc3810).
+    pub aux_data: &'a [u8],
+}
+
+impl<'a> MulticastAddressRecord<'a> {
+    /// Minimum length of a record in bytes (record without sources & auxiliary data).
+    pub const MIN_LEN: usize = 20;
+
+    /// Maximum length of the auxiliary data (the length field counts units of 4 bytes).
+    pub const MAX_AUX_DATA_LEN: usize = 0xff*4;
+
+    /// Length of the record in bytes when serialized.
+    pub fn serialized_len(&self) -> usize {
+        MulticastAddressRecord::MIN_LEN + self.sources.len()*16 + self.aux_data.len()
+    }
+
+    /// Writes the record to the given writer.
+    ///
+    /// # Errors
+    ///
+    /// `ValueError::MulticastAddressRecordLengthBad` is returned if the record
+    /// contains more then 65535 sources or if the auxiliary data is not a
+    /// multiple of 4 bytes or bigger then 1020 bytes.
+    pub fn write<T: io::Write + Sized>(&self, writer: &mut T) -> Result<(), WriteError> {
+        use ValueError::MulticastAddressRecordLengthBad;
+
+        if self.sources.len() > usize::from(u16::MAX) {
+            return Err(WriteError::ValueError(MulticastAddressRecordLengthBad(self.serialized_len())));
+        }
+        let aux_len = match self.aux_data.len() % 4 {
+            0 if self.aux_data.len() <= MulticastAddressRecord::MAX_AUX_DATA_LEN => (self.aux_data.len() / 4) as u8,
+            _ => return Err(WriteError::ValueError(MulticastAddressRecordLengthBad(self.serialized_len()))),
+        };
+        let num_sources_be = (self.sources.len() as u16).to_be_bytes();
+        writer.write_all(&[self.record_type, aux_len, num_sources_be[0], num_sources_be[1]])?;
+        writer.write_all(&self.multicast_address)?;
+        for source in self.sources {
+            writer.write_all(source)?;
+        }
+        writer.write_all(self.aux_data)?;
+        Ok(())
+    }
+}
+
+/// Errors that can occour while reading the multicast address records of a MLDv2 report.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum MulticastAddressRecordReadError {
+    /// Returned if there was not enough memory left in the slice to read the record.
+    UnexpectedEndOfSlice{ expected_len: usize, actual_len: usize },
+}
+
+impl Error for MulticastAddressRecordReadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        None
+    }
+}
+
+impl fmt::Display for MulticastAddressRecordReadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use MulticastAddressRecordReadError::*;
+        match self {
+            UnexpectedEndOfSlice{expected_len, actual_len} => {
+                write!(f, "MulticastAddressRecordReadError: Not enough memory left in slice to read multicast address record (expected at least {} bytes, only {} bytes available).", expected_len, actual_len)
+            },
+        }
+    }
+}
+
+/// Allows iterating over the multicast address records in the payload of a MLDv2 report.
+///
+/// The iterator stops after the number of records given in the report header
+/// or after an error was returned.
+#[derive(Clone, Eq, PartialEq)]
+pub struct MulticastAddressRecordsIterator<'a> {
+    number_of_records: u16,
+    slice: &'a [u8],
+}
+
+impl<'a> MulticastAddressRecordsIterator<'a> {
+    /// Creates an iterator over `number_of_records` records encoded in the given slice.
+    pub fn from_slice(number_of_records: u16, slice: &'a [u8]) -> MulticastAddressRecordsIterator<'a> {
+        MulticastAddressRecordsIterator{ number_of_records, slice }
+    }
+
+    /// Returns the non processed part of the slice.
+    pub fn rest(&self) -> &'a [u8] {
+        self.slice
+    }
+}
+
+impl<'a> Iterator for MulticastAddressRecordsIterator<'a> {
+    type Item = Result<MulticastAddressRecord<'a>, MulticastAddressRecordReadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        use MulticastAddressRecordReadError::*;
+
+        if 0 == self.number_of_records {
+            return None;
+        }
+
+        // determine & check the length
+        let len = if self.slice.len() < MulticastAddressRecord::MIN_LEN {
+            MulticastAddressRecord::MIN_LEN
+        } else {
+            MulticastAddressRecord::MIN_LEN +
+                usize::from(u16::from_be_bytes([self.slice[2], self.slice[3]]))*16 +
+                usize::from(self.slice[1])*4
+        };
+        if self.slice.len() < len {
+            let actual_len = self.slice.len();
+            self.number_of_records = 0;
+            self.slice = &self.slice[actual_len..];
+            return Some(Err(UnexpectedEndOfSlice{ expected_len: len, actual_len }));
+        }
+
+        let record = &self.slice[..len];
+        let sources_end = MulticastAddressRecord::MIN_LEN + usize::from(u16::from_be_bytes([record[2], record[3]]))*16;
+        let sources = &record[MulticastAddressRecord::MIN_LEN..sources_end];
+        let mut multicast_address = [0u8;16];
+        multicast_address.copy_from_slice(&record[4..20]);
+
+        self.number_of_records -= 1;
+        self.slice = &self.slice[len..];
+        Some(Ok(MulticastAddressRecord{
+            record_type: record[0],
+            multicast_address,
+            // SAFETY: [u8;16] has an alignment of 1 & the length is a multiple of 16
+            sources: unsafe {
+                from_raw_parts(sources.as_ptr() as *const [u8;16], sources.len() / 16)
+            },
+            aux_data: &record[sources_end..],
+        }))
+    }
+}
+
+impl<'a> Debug for MulticastAddressRecordsIterator<'a> {
+    fn fmt(&self, fmt: &mut Formatter) -> Result<(), std::fmt::Error> {
+        let mut list = fmt.debug_list();
+
+        // create a copy and iterate over all elements
+        for it in self.clone() {
+            match it {
+                Ok(e) => { list.entry(&e); },
+                Err(e) => {
+                    list.entry(&Result::<(), MulticastAddressRecordReadError>::Err(e.clone()));
+                }
+            }
+        }
+
+        list.finish()
+    }
+}
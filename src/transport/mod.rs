@@ -1,6 +1,11 @@
 pub mod icmp;
+pub mod icmp_embedded_packet;
+pub mod icmp_extensions;
 pub mod icmpv4_impl;
 pub mod icmpv6_impl;
+pub mod igmp_impl;
+pub mod mld;
+pub mod ndp_options;
 pub mod udp;
 pub mod tcp;
 
@@ -15,6 +20,7 @@ pub enum TransportHeader {
     Tcp(tcp::TcpHeader),
     Icmpv4(Icmpv4Header),
     Icmpv6(Icmpv6Header),
+    Igmp(IgmpHeader),
 }
 
 impl TransportHeader {
@@ -107,6 +113,28 @@ impl TransportHeader {
         }
     }
 
+    /// Returns Result::Some containing the IGMP header if self has the value Igmp.
+    /// Otherwise None is returned.
+    pub fn igmp(self) -> Option<IgmpHeader> {
+        use crate::TransportHeader::*;
+        if let Igmp(value) = self {
+            Some(value)
+        } else {
+            None
+        }
+    }
+
+    /// Returns Result::Some containing the IGMP header if self has the value Igmp.
+    /// Otherwise None is returned.
+    pub fn mut_igmp(&mut self) -> Option<&mut IgmpHeader> {
+        use crate::TransportHeader::*;
+        if let Igmp(value) = self {
+            Some(value)
+        } else {
+            None
+        }
+    }
+
     /// Returns the size of the transport header (in case of UDP fixed,
     /// in case of TCP cotanining the options).
     pub fn header_len(&self) -> usize {
@@ -116,6 +144,7 @@ impl TransportHeader {
             Tcp(value) => usize::from(value.header_len()),
             Icmpv4(value) => value.header_len(),
             Icmpv6(value) => value.header_len(),
+            Igmp(value) => value.header_len(),
         }
     }
 
@@ -134,6 +163,9 @@ impl TransportHeader {
                 header.update_checksum(payload);
             },
             Icmpv6(_) => return Err(ValueError::Icmpv6InIpv4),
+            Igmp(header) => {
+                header.update_checksum(payload);
+            },
         }
         Ok(())
     }
@@ -145,6 +177,7 @@ impl TransportHeader {
         match self {
             Icmpv4(header) => header.update_checksum(payload),
             Icmpv6(header) => header.update_checksum(ip_header.source, ip_header.destination, payload)?,
+            Igmp(header) => header.update_checksum(payload),
             Udp(header) => {
                 header.checksum = header.calc_checksum_ipv6(ip_header, payload)?;
             },
@@ -161,6 +194,7 @@ impl TransportHeader {
         match self {
             Icmpv4(value) => value.write(writer),
             Icmpv6(value) => value.write(writer),
+            Igmp(value) => value.write(writer),
             Udp(value) => value.write(writer),
             Tcp(value) => value.write(writer).map_err(WriteError::from)
         }
@@ -0,0 +1,351 @@
+use super::super::*;
+
+use std::fmt::{Debug, Formatter};
+use std::slice::from_raw_parts;
+
+/// Module containing the constants for ICMPv6 neighbor discovery options (type numbers & sizes).
+pub mod ndp_option {
+    /// `u8` identifying a "Source Link-Layer Address" option (rfc4861).
+    pub const TYPE_SOURCE_LINK_LAYER_ADDRESS: u8 = 1;
+    /// `u8` identifying a "Target Link-Layer Address" option (rfc4861).
+    pub const TYPE_TARGET_LINK_LAYER_ADDRESS: u8 = 2;
+    /// `u8` identifying a "Prefix Information" option (rfc4861).
+    pub const TYPE_PREFIX_INFORMATION: u8 = 3;
+    /// `u8` identifying a "Redirected Header" option (rfc4861).
+    pub const TYPE_REDIRECTED_HEADER: u8 = 4;
+    /// `u8` identifying a "MTU" option (rfc4861).
+    pub const TYPE_MTU: u8 = 5;
+    /// `u8` identifying a "Nonce" option (rfc3971).
+    pub const TYPE_NONCE: u8 = 14;
+    /// `u8` identifying a "Recursive DNS Server" option (rfc8106).
+    pub const TYPE_RECURSIVE_DNS_SERVER: u8 = 25;
+    /// `u8` identifying a "DNS Search List" option (rfc8106).
+    pub const TYPE_DNS_SEARCH_LIST: u8 = 31;
+    /// Length of a "Prefix Information" option in bytes (including type & length).
+    pub const LEN_PREFIX_INFORMATION: usize = 32;
+    /// Length of a "MTU" option in bytes (including type & length).
+    pub const LEN_MTU: usize = 8;
+    /// Maximum length of an option in bytes (the length field counts units of 8 bytes).
+    pub const MAX_LEN: usize = 0xff*8;
+    /// "On-link" flag in the flags field of a "Prefix Information" option.
+    pub const PREFIX_FLAG_ON_LINK: u8 = 0b1000_0000;
+    /// "Autonomous address-configuration" flag in the flags field of a "Prefix Information" option.
+    pub const PREFIX_FLAG_AUTONOMOUS: u8 = 0b0100_0000;
+}
+
+/// Content of a "Prefix Information" neighbor discovery option (rfc4861).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct NdpPrefixInformation {
+    /// Number of leading bits in the prefix that are valid.
+    pub prefix_length: u8,
+    /// True if the prefix can be used for on-link determination.
+    pub on_link: bool,
+    /// True if the prefix can be used for stateless address configuration.
+    pub autonomous: bool,
+    /// Time in seconds the prefix is valid for on-link determination (`0xffffffff` represents infinity).
+    pub valid_lifetime: u32,
+    /// Time in seconds addresses generated from the prefix remain preferred (`0xffffffff` represents infinity).
+    pub preferred_lifetime: u32,
+    /// An IP address or a prefix of an IP address (bits after `prefix_length` are zero).
+    pub prefix: [u8;16],
+}
+
+/// Options that can be present in ICMPv6 neighbor discovery messages
+/// (router & neighbor solicitations & advertisements and redirects).
+///
+/// Options containing variable length data (e.g. link-layer addresses) are
+/// padded with zeros to a multiple of 8 bytes when serialized. When decoded
+/// the padding is part of the returned data.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum NdpOption<'a> {
+    /// "Source Link-Layer Address" option (e.g. the 6 byte MAC address for ethernet).
+    SourceLinkLayerAddress(&'a [u8]),
+    /// "Target Link-Layer Address" option (e.g. the 6 byte MAC address for ethernet).
+    TargetLinkLayerAddress(&'a [u8]),
+    /// "Prefix Information" option.
+    PrefixInformation(NdpPrefixInformation),
+    /// "Redirected Header" option containing (a part of) the redirected packet.
+    RedirectedHeader(&'a [u8]),
+    /// "MTU" option containing the recommended MTU of the link.
+    Mtu(u32),
+    /// "Nonce" option (rfc3971) used to match solicitations & advertisements.
+    Nonce(&'a [u8]),
+    /// "Recursive DNS Server" option (rfc8106), `lifetime` is the time in
+    /// seconds the addresses can be used for name resolution.
+    RecursiveDnsServer{ lifetime: u32, addresses: &'a [[u8;16]] },
+    /// "DNS Search List" option (rfc8106), `domain_names` contains the domain
+    /// names encoded as described in rfc1035 section 3.1.
+    DnsSearchList{ lifetime: u32, domain_names: &'a [u8] },
+    /// Option with a type not decoded by etherparse (`data` contains all bytes after
+    /// the type & length fields).
+    Unknown{ option_type: u8, data: &'a [u8] },
+}
+
+impl<'a> NdpOption<'a> {
+    /// Returns the type value of the option.
+    pub fn option_type(&self) -> u8 {
+        use NdpOption::*;
+        use ndp_option::*;
+        match self {
+            SourceLinkLayerAddress(_) => TYPE_SOURCE_LINK_LAYER_ADDRESS,
+            TargetLinkLayerAddress(_) => TYPE_TARGET_LINK_LAYER_ADDRESS,
+            PrefixInformation(_) => TYPE_PREFIX_INFORMATION,
+            RedirectedHeader(_) => TYPE_REDIRECTED_HEADER,
+            Mtu(_) => TYPE_MTU,
+            Nonce(_) => TYPE_NONCE,
+            RecursiveDnsServer{..} => TYPE_RECURSIVE_DNS_SERVER,
+            DnsSearchList{..} => TYPE_DNS_SEARCH_LIST,
+            Unknown{ option_type, .. } => *option_type,
+        }
+    }
+
+    /// Length of the option in bytes when serialized (including the type & length
+    /// fields and the padding to a multiple of 8 bytes).
+    pub fn serialized_len(&self) -> usize {
+        use NdpOption::*;
+        use ndp_option::*;
+        let unpadded = match self {
+            SourceLinkLayerAddress(data) |
+            TargetLinkLayerAddress(data) |
+            Nonce(data) |
+            Unknown{ data, .. } => 2 + data.len(),
+            PrefixInformation(_) => LEN_PREFIX_INFORMATION,
+            RedirectedHeader(data) => 8 + data.len(),
+            Mtu(_) => LEN_MTU,
+            RecursiveDnsServer{ addresses, .. } => 8 + addresses.len()*16,
+            DnsSearchList{ domain_names, .. } => 8 + domain_names.len(),
+        };
+        // round up to the next multiple of 8
+        unpadded + (8 - unpadded % 8) % 8
+    }
+
+    /// Writes the option to the given writer (padded with zeros to a multiple of 8 bytes).
+    ///
+    /// # Errors
+    ///
+    /// `ValueError::NdpOptionDataLengthBad` is returned if the option is bigger
+    /// then what can be represented in the length field (2040 bytes).
+    pub fn write<T: io::Write + Sized>(&self, writer: &mut T) -> Result<(), WriteError> {
+        writer.write_all(&self.to_bytes()?)?;
+        Ok(())
+    }
+
+    /// Returns the serialized option (padded with zeros to a multiple of 8 bytes).
+    ///
+    /// # Errors
+    ///
+    /// `ValueError::NdpOptionDataLengthBad` is returned if the option is bigger
+    /// then what can be represented in the length field (2040 bytes).
+    pub fn to_bytes(&self) -> Result<Vec<u8>, ValueError> {
+        use NdpOption::*;
+
+        let len = self.serialized_len();
+        if len > ndp_option::MAX_LEN {
+            return Err(ValueError::NdpOptionDataLengthBad(len));
+        }
+        let mut result = vec![0u8;len];
+        result[0] = self.option_type();
+        result[1] = (len / 8) as u8;
+        match self {
+            SourceLinkLayerAddress(data) |
+            TargetLinkLayerAddress(data) |
+            Nonce(data) |
+            Unknown{ data, .. } => {
+                result[2..2 + data.len()].copy_from_slice(data);
+            },
+            PrefixInformation(value) => {
+                result[2] = value.prefix_length;
+                result[3] = if value.on_link { ndp_option::PREFIX_FLAG_ON_LINK } else { 0 } |
+                            if value.autonomous { ndp_option::PREFIX_FLAG_AUTONOMOUS } else { 0 };
+                result[4..8].copy_from_slice(&value.valid_lifetime.to_be_bytes());
+                result[8..12].copy_from_slice(&value.preferred_lifetime.to_be_bytes());
+                result[16..32].copy_from_slice(&value.prefix);
+            },
+            RedirectedHeader(data) => {
+                result[8..8 + data.len()].copy_from_slice(data);
+            },
+            Mtu(value) => {
+                result[4..8].copy_from_slice(&value.to_be_bytes());
+            },
+            RecursiveDnsServer{ lifetime, addresses } => {
+                result[4..8].copy_from_slice(&lifetime.to_be_bytes());
+                for (i, address) in addresses.iter().enumerate() {
+                    result[8 + i*16..8 + (i + 1)*16].copy_from_slice(address);
+                }
+            },
+            DnsSearchList{ lifetime, domain_names } => {
+                result[4..8].copy_from_slice(&lifetime.to_be_bytes());
+                result[8..8 + domain_names.len()].copy_from_slice(domain_names);
+            },
+        }
+        Ok(result)
+    }
+}
+
+/// Errors that can occour while reading the options of an ICMPv6 neighbor discovery message.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum NdpOptionReadError {
+    /// Returned if an option type was read, but there was not enough memory in the options left to completely read it.
+    UnexpectedEndOfSlice{ option_type: u8, expected_len: usize, actual_len: usize },
+
+    /// Returned if the option has an unexpected length value (e.g. zero or a
+    /// "Prefix Information" option with a length different then 4).
+    UnexpectedSize{ option_type: u8, size: u8 },
+}
+
+impl Error for NdpOptionReadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        None
+    }
+}
+
+impl fmt::Display for NdpOptionReadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use NdpOptionReadError::*;
+        match self {
+            UnexpectedEndOfSlice{option_type, expected_len, actual_len} => {
+                write!(f, "NdpOptionReadError: Not enough memory left in slice to read option of type {} (expected at least {} bytes, only {} bytes available).", option_type, expected_len, actual_len)
+            },
+            UnexpectedSize{option_type, size} => {
+                write!(f, "NdpOptionReadError: Length value of the option of type {} had unexpected value {}.", option_type, size)
+            },
+        }
+    }
+}
+
+/// Allows iterating over the options of an ICMPv6 neighbor discovery message.
+///
+/// After an error was returned the iterator stops (all following calls
+/// to `next` return `None`).
+#[derive(Clone, Eq, PartialEq)]
+pub struct NdpOptionsIterator<'a> {
+    options: &'a [u8]
+}
+
+impl<'a> NdpOptionsIterator<'a> {
+    /// Creates an options iterator from a slice containing encoded neighbor
+    /// discovery options (the data after the fixed part of the message).
+    pub fn from_slice(options: &'a [u8]) -> NdpOptionsIterator<'a> {
+        NdpOptionsIterator{ options }
+    }
+
+    /// Returns the non processed part of the options slice.
+    pub fn rest(&self) -> &'a [u8] {
+        self.options
+    }
+
+    /// Returns an error & moves the iterator to the end of the slice.
+    fn error(&mut self, err: NdpOptionReadError) -> Option<Result<NdpOption<'a>, NdpOptionReadError>> {
+        let rest = self.options.len();
+        self.options = &self.options[rest..];
+        Some(Err(err))
+    }
+}
+
+impl<'a> Iterator for NdpOptionsIterator<'a> {
+    type Item = Result<NdpOption<'a>, NdpOptionReadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        use NdpOption::*;
+        use NdpOptionReadError::*;
+        use ndp_option::*;
+
+        if self.options.is_empty() {
+            return None;
+        }
+
+        // determine & check the length
+        let option_type = self.options[0];
+        if self.options.len() < 2 {
+            let actual_len = self.options.len();
+            return self.error(UnexpectedEndOfSlice{ option_type, expected_len: 2, actual_len });
+        }
+        let size = self.options[1];
+        if 0 == size {
+            // a zero length would lead to an infinite loop (rfc4861 requires
+            // the message to be discarded in this case)
+            return self.error(UnexpectedSize{ option_type, size });
+        }
+        let len = usize::from(size)*8;
+        if self.options.len() < len {
+            let actual_len = self.options.len();
+            return self.error(UnexpectedEndOfSlice{ option_type, expected_len: len, actual_len });
+        }
+        let option = &self.options[..len];
+
+        // decode the data
+        let result = match option_type {
+            TYPE_SOURCE_LINK_LAYER_ADDRESS => SourceLinkLayerAddress(&option[2..]),
+            TYPE_TARGET_LINK_LAYER_ADDRESS => TargetLinkLayerAddress(&option[2..]),
+            TYPE_PREFIX_INFORMATION => {
+                if len != LEN_PREFIX_INFORMATION {
+                    return self.error(UnexpectedSize{ option_type, size });
+                }
+                let mut prefix = [0u8;16];
+                prefix.copy_from_slice(&option[16..32]);
+                PrefixInformation(NdpPrefixInformation{
+                    prefix_length: option[2],
+                    on_link: 0 != option[3] & PREFIX_FLAG_ON_LINK,
+                    autonomous: 0 != option[3] & PREFIX_FLAG_AUTONOMOUS,
+                    valid_lifetime: u32::from_be_bytes([option[4], option[5], option[6], option[7]]),
+                    preferred_lifetime: u32::from_be_bytes([option[8], option[9], option[10], option[11]]),
+                    prefix,
+                })
+            },
+            TYPE_REDIRECTED_HEADER => RedirectedHeader(&option[8..]),
+            TYPE_MTU => {
+                if len != LEN_MTU {
+                    return self.error(UnexpectedSize{ option_type, size });
+                }
+                Mtu(u32::from_be_bytes([option[4], option[5], option[6], option[7]]))
+            },
+            TYPE_NONCE => Nonce(&option[2..]),
+            TYPE_RECURSIVE_DNS_SERVER => {
+                // the option has to contain at least one address & the
+                // size has to be odd (8 bytes + n*16 bytes)
+                if size < 3 || 0 == size & 1 {
+                    return self.error(UnexpectedSize{ option_type, size });
+                }
+                let addresses = &option[8..];
+                RecursiveDnsServer{
+                    lifetime: u32::from_be_bytes([option[4], option[5], option[6], option[7]]),
+                    // SAFETY: [u8;16] has an alignment of 1 & the length
+                    // was checked to be a multiple of 16
+                    addresses: unsafe {
+                        from_raw_parts(addresses.as_ptr() as *const [u8;16], addresses.len() / 16)
+                    },
+                }
+            },
+            TYPE_DNS_SEARCH_LIST => {
+                if size < 2 {
+                    return self.error(UnexpectedSize{ option_type, size });
+                }
+                DnsSearchList{
+                    lifetime: u32::from_be_bytes([option[4], option[5], option[6], option[7]]),
+                    domain_names: &option[8..],
+                }
+            },
+            option_type => Unknown{ option_type, data: &option[2..] },
+        };
+        self.options = &self.options[len..];
+        Some(Ok(result))
+    }
+}
+
+impl<'a> Debug for NdpOptionsIterator<'a> {
+    fn fmt(&self, fmt: &mut Formatter) -> Result<(), std::fmt::Error> {
+        let mut list = fmt.debug_list();
+
+        // create a copy and iterate over all elements
+        for it in self.clone() {
+            match it {
+                Ok(e) => { list.entry(&e); },
+                Err(e) => {
+                    list.entry(&Result::<(), NdpOptionReadError>::Err(e.clone()));
+                }
+            }
+        }
+
+        list.finish()
+    }
+}
@@ -1,5 +1,6 @@
 use super::super::*;
 
+use arrayvec::ArrayVec;
 use std::fmt::{Debug, Formatter};
 use std::slice::from_raw_parts;
 
@@ -144,11 +145,22 @@ impl TcpHeader {
                     })
                 },
                 Timestamp(_, _) => 10,
+                Md5Signature(_) => 18,
+                UserTimeout{ .. } => 4,
+                Authentication{ mac, .. } => 4 + mac.len(),
+                MultipathTcp{ data, .. } => 3 + data.len(),
+                FastOpenCookie(cookie) => 2 + cookie.len(),
+                Unknown{ data, .. } => 2 + data.len(),
             }
         });
 
         if self.options_buffer.len() < required_length {
             Err(TcpOptionWriteError::NotEnoughSpace(required_length))
+        } else if let Some(timeout) = options.iter().find_map(|x| match x {
+            UserTimeout{ timeout, .. } if *timeout > tcp_option::MAX_USER_TIMEOUT => Some(*timeout),
+            _ => None,
+        }) {
+            Err(TcpOptionWriteError::UserTimeoutTooLarge(timeout))
         } else {
 
             //reset the options to null
@@ -238,7 +250,63 @@ impl TcpHeader {
                         insert[1] = 10;
                         insert[2..6].copy_from_slice(&a.to_be_bytes());
                         insert[6..10].copy_from_slice(&b.to_be_bytes());
-                    }
+                    },
+                    Md5Signature(digest) => {
+                        let insert = &mut self.options_buffer[i..i + 18];
+                        i += 18;
+
+                        insert[0] = KIND_MD5_SIGNATURE;
+                        insert[1] = 18;
+                        insert[2..18].copy_from_slice(digest);
+                    },
+                    UserTimeout{ granularity_minutes, timeout } => {
+                        let insert = &mut self.options_buffer[i..i + 4];
+                        i += 4;
+
+                        let value = timeout | if *granularity_minutes { 0x8000 } else { 0 };
+                        insert[0] = KIND_USER_TIMEOUT;
+                        insert[1] = 4;
+                        insert[2..4].copy_from_slice(&value.to_be_bytes());
+                    },
+                    Authentication{ key_id, rnext_key_id, mac } => {
+                        let len = 4 + mac.len();
+                        let insert = &mut self.options_buffer[i..i + len];
+                        i += len;
+
+                        insert[0] = KIND_AUTHENTICATION;
+                        insert[1] = len as u8;
+                        insert[2] = *key_id;
+                        insert[3] = *rnext_key_id;
+                        insert[4..].copy_from_slice(mac);
+                    },
+                    MultipathTcp{ subtype, flags, data } => {
+                        let len = 3 + data.len();
+                        let insert = &mut self.options_buffer[i..i + len];
+                        i += len;
+
+                        insert[0] = KIND_MULTIPATH_TCP;
+                        insert[1] = len as u8;
+                        insert[2] = ((subtype & 0xf) << 4) | (flags & 0xf);
+                        insert[3..].copy_from_slice(data);
+                    },
+                    FastOpenCookie(cookie) => {
+                        let len = 2 + cookie.len();
+                        let insert = &mut self.options_buffer[i..i + len];
+                        i += len;
+
+                        insert[0] = KIND_FAST_OPEN_COOKIE;
+                        insert[1] = len as u8;
+                        insert[2..].copy_from_slice(cookie);
+                    },
+                    Unknown{ kind, data } => {
+                        let len = 2 + data.len();
+                        let insert = &mut self.options_buffer[i..i + len];
+                        i += len;
+
+                        insert[0] = *kind;
+                        insert[1] = len as u8;
+                        insert[2..].copy_from_slice(data);
+                    },
                 }
             }
             //set the new data offset
@@ -1007,6 +1075,39 @@ pub enum TcpOptionElement {
     SelectiveAcknowledgement((u32,u32), [Option<(u32,u32)>;3]),
     ///Timestamp & echo (first number is the sender timestamp, the second the echo timestamp)
     Timestamp(u32, u32),
+    /// "MD5 Signature" option (rfc2385) containing the 16 byte MD5 digest.
+    Md5Signature([u8;16]),
+    /// "User Timeout" option (rfc5482).
+    ///
+    /// The timeout is 15 bit long (values between 0 and 0x7fff) and is given in
+    /// minutes if `granularity_minutes` is set, otherwise in seconds. Larger
+    /// values are rejected by [`TcpHeader::set_options`].
+    UserTimeout{ granularity_minutes: bool, timeout: u16 },
+    /// "TCP Authentication Option" (rfc5925) containing the key ids & the message
+    /// authentication code.
+    Authentication{
+        key_id: u8,
+        rnext_key_id: u8,
+        mac: ArrayVec<u8, { tcp_option::MAX_AUTHENTICATION_MAC_LEN }>
+    },
+    /// "Multipath TCP" option (rfc8684).
+    ///
+    /// The subtype (see [`mptcp_subtype`] for possible values) & the subtype
+    /// specific flags (e.g. the version in `MP_CAPABLE` options) are both 4 bit
+    /// long. `data` contains the subtype specific rest of the option.
+    MultipathTcp{
+        subtype: u8,
+        flags: u8,
+        data: ArrayVec<u8, { tcp_option::MAX_MULTIPATH_TCP_DATA_LEN }>
+    },
+    /// "TCP Fast Open Cookie" option (rfc7413). An empty cookie is a cookie request.
+    FastOpenCookie(ArrayVec<u8, { tcp_option::MAX_FAST_OPEN_COOKIE_LEN }>),
+    /// Option with a kind not known to etherparse (`data` contains the
+    /// option data after the kind & length fields).
+    ///
+    /// Note that an unknown option with a kind value known to etherparse will
+    /// be decoded as the known option when read again.
+    Unknown{ kind: u8, data: ArrayVec<u8, { tcp_option::MAX_UNKNOWN_DATA_LEN }> },
 }
 
 ///Errors that can occour while reading the options of a TCP header.
@@ -1021,6 +1122,9 @@ pub enum TcpOptionReadError {
     ///Returned if an unknown tcp header option is encountered.
     ///
     ///The first element is the identifier and the slice contains the rest of data left in the options.
+    ///
+    ///Note that `TcpOptionsIterator` no longer returns this error, options with unknown kinds
+    ///are returned as `TcpOptionElement::Unknown` instead.
     UnknownId(u8),
 }
 
@@ -1056,7 +1160,10 @@ pub enum TcpOptionWriteError {
     ///The options size is limited by the 4 bit data_offset field in the header which describes
     ///the total tcp header size in multiple of 4 bytes. This leads to a maximum size for the options
     ///part of the header of 4*(15 - 5) (minus 5 for the size of the tcp header itself). 
-    NotEnoughSpace(usize)
+    NotEnoughSpace(usize),
+    ///The timeout of a "user timeout" option is bigger then the maximum
+    ///value that can be stored in its 15 bit field (0x7fff).
+    UserTimeoutTooLarge(u16),
 }
 
 impl Error for TcpOptionWriteError {
@@ -1072,6 +1179,9 @@ impl fmt::Display for TcpOptionWriteError {
             NotEnoughSpace(size) => {
                 write!(f, "TcpOptionWriteError: Not enough memory to store all options in the options section of a tcp header (maximum 40 bytes can be stored, the options would have needed {} bytes).", size)
             },
+            UserTimeoutTooLarge(timeout) => {
+                write!(f, "TcpOptionWriteError: The user timeout {} is bigger then the maximum allowed value of {}.", timeout, tcp_option::MAX_USER_TIMEOUT)
+            },
         }
     }
 }
@@ -1147,6 +1257,16 @@ pub mod tcp_option {
     pub const KIND_SELECTIVE_ACK: u8 = 5;
     /// `u8` identifying a "timestamp and echo of previous timestamp" tcp option.
     pub const KIND_TIMESTAMP: u8 = 8;
+    /// `u8` identifying a "MD5 signature" tcp option (rfc2385).
+    pub const KIND_MD5_SIGNATURE: u8 = 19;
+    /// `u8` identifying a "user timeout" tcp option (rfc5482).
+    pub const KIND_USER_TIMEOUT: u8 = 28;
+    /// `u8` identifying a "tcp authentication option" (rfc5925).
+    pub const KIND_AUTHENTICATION: u8 = 29;
+    /// `u8` identifying a "multipath tcp" option (rfc8684).
+    pub const KIND_MULTIPATH_TCP: u8 = 30;
+    /// `u8` identifying a "tcp fast open cookie" option (rfc7413).
+    pub const KIND_FAST_OPEN_COOKIE: u8 = 34;
     /// Length in octets/bytes of the "end" tcp option (includes kind value).
     pub const LEN_END: u8 = 1;
     /// Length in octets/bytes of the "no operation" tcp option (includes kind value).
@@ -1159,6 +1279,46 @@ pub mod tcp_option {
     pub const LEN_SELECTIVE_ACK_PERMITTED: u8 = 2;
     /// Length in octets/bytes of the "timestamp and echo of previous timestamp" tcp option (includes kind value).
     pub const LEN_TIMESTAMP: u8 = 10;
+    /// Length in octets/bytes of the "MD5 signature" tcp option (includes kind value).
+    pub const LEN_MD5_SIGNATURE: u8 = 18;
+    /// Length in octets/bytes of the "user timeout" tcp option (includes kind value).
+    pub const LEN_USER_TIMEOUT: u8 = 4;
+    /// Maximum value of the 15 bit timeout in a "user timeout" option.
+    pub const MAX_USER_TIMEOUT: u16 = 0x7fff;
+    /// Maximum length of the message authentication code in a "tcp authentication option".
+    pub const MAX_AUTHENTICATION_MAC_LEN: usize = 36;
+    /// Maximum length of the data after the subtype in a "multipath tcp" option.
+    pub const MAX_MULTIPATH_TCP_DATA_LEN: usize = 37;
+    /// Maximum length of the cookie in a "tcp fast open cookie" option.
+    pub const MAX_FAST_OPEN_COOKIE_LEN: usize = 16;
+    /// Minimum length of a non empty cookie in a "tcp fast open cookie" option.
+    pub const MIN_FAST_OPEN_COOKIE_LEN: usize = 4;
+    /// Maximum length of the data in an unknown tcp option.
+    pub const MAX_UNKNOWN_DATA_LEN: usize = 38;
+}
+
+/// Module containing the subtype values of "multipath tcp" options (rfc8684).
+pub mod mptcp_subtype {
+    /// Multipath capable.
+    pub const MP_CAPABLE: u8 = 0x0;
+    /// Join connection.
+    pub const MP_JOIN: u8 = 0x1;
+    /// Data sequence signal (data ack & data sequence mapping).
+    pub const DSS: u8 = 0x2;
+    /// Add address.
+    pub const ADD_ADDR: u8 = 0x3;
+    /// Remove address.
+    pub const REMOVE_ADDR: u8 = 0x4;
+    /// Change subflow priority.
+    pub const MP_PRIO: u8 = 0x5;
+    /// Fallback.
+    pub const MP_FAIL: u8 = 0x6;
+    /// Fast close.
+    pub const MP_FASTCLOSE: u8 = 0x7;
+    /// Subflow reset.
+    pub const MP_TCPRST: u8 = 0x8;
+    /// Private use.
+    pub const MP_EXPERIMENTAL: u8 = 0xf;
 }
 
 impl<'a> TcpOptionsIterator<'a> {
@@ -1201,6 +1361,31 @@ impl<'a> Iterator for TcpOptionsIterator<'a> {
             }
         };
 
+        // checks the length field of options with a variable length and returns
+        // the length of the option
+        let expect_size_range = |min_size: u8, max_size: u8, slice: &[u8]| -> Result<usize, TcpOptionReadError> {
+            if slice.len() < 2 {
+                Err(UnexpectedEndOfSlice{
+                    option_id: slice[0],
+                    expected_len: 2,
+                    actual_len: slice.len()
+                })
+            } else if slice[1] < min_size || slice[1] > max_size {
+                Err(UnexpectedSize{
+                    option_id: slice[0],
+                    size: slice[1]
+                })
+            } else if slice.len() < usize::from(slice[1]) {
+                Err(UnexpectedEndOfSlice{
+                    option_id: slice[0],
+                    expected_len: slice[1],
+                    actual_len: slice.len()
+                })
+            } else {
+                Ok(usize::from(slice[1]))
+            }
+        };
+
         if self.options.is_empty() {
             None
         } else {
@@ -1337,9 +1522,89 @@ impl<'a> Iterator for TcpOptionsIterator<'a> {
                     }
                 },
 
+                KIND_MD5_SIGNATURE => {
+                    match expect_specific_size(LEN_MD5_SIGNATURE, self.options) {
+                        Err(value) => Some(Err(value)),
+                        _ => {
+                            let mut digest = [0u8;16];
+                            digest.copy_from_slice(&self.options[2..18]);
+                            self.options = &self.options[18..];
+                            Some(Ok(Md5Signature(digest)))
+                        }
+                    }
+                },
+                KIND_USER_TIMEOUT => {
+                    match expect_specific_size(LEN_USER_TIMEOUT, self.options) {
+                        Err(value) => Some(Err(value)),
+                        _ => {
+                            let value = u16::from_be_bytes([self.options[2], self.options[3]]);
+                            self.options = &self.options[4..];
+                            Some(Ok(UserTimeout{
+                                granularity_minutes: 0 != value & 0x8000,
+                                timeout: value & 0x7fff,
+                            }))
+                        }
+                    }
+                },
+                KIND_AUTHENTICATION => {
+                    match expect_size_range(4, 4 + MAX_AUTHENTICATION_MAC_LEN as u8, self.options) {
+                        Err(value) => Some(Err(value)),
+                        Ok(len) => {
+                            let result = Authentication{
+                                key_id: self.options[2],
+                                rnext_key_id: self.options[3],
+                                mac: self.options[4..len].iter().copied().collect(),
+                            };
+                            self.options = &self.options[len..];
+                            Some(Ok(result))
+                        }
+                    }
+                },
+                KIND_MULTIPATH_TCP => {
+                    match expect_size_range(3, 3 + MAX_MULTIPATH_TCP_DATA_LEN as u8, self.options) {
+                        Err(value) => Some(Err(value)),
+                        Ok(len) => {
+                            let result = MultipathTcp{
+                                subtype: self.options[2] >> 4,
+                                flags: self.options[2] & 0xf,
+                                data: self.options[3..len].iter().copied().collect(),
+                            };
+                            self.options = &self.options[len..];
+                            Some(Ok(result))
+                        }
+                    }
+                },
+                KIND_FAST_OPEN_COOKIE => {
+                    match expect_size_range(2, 2 + MAX_FAST_OPEN_COOKIE_LEN as u8, self.options) {
+                        Err(value) => Some(Err(value)),
+                        // a cookie is either empty (cookie request) or has a minimum length
+                        Ok(len) if len != 2 && len < 2 + MIN_FAST_OPEN_COOKIE_LEN => {
+                            Some(Err(UnexpectedSize{
+                                option_id: self.options[0],
+                                size: self.options[1]
+                            }))
+                        },
+                        Ok(len) => {
+                            let result = FastOpenCookie(self.options[2..len].iter().copied().collect());
+                            self.options = &self.options[len..];
+                            Some(Ok(result))
+                        }
+                    }
+                },
+
                 //unknown id
-                _ => {
-                    Some(Err(UnknownId(self.options[0])))
+                kind => {
+                    match expect_size_range(2, 2 + MAX_UNKNOWN_DATA_LEN as u8, self.options) {
+                        Err(value) => Some(Err(value)),
+                        Ok(len) => {
+                            let result = Unknown{
+                                kind,
+                                data: self.options[2..len].iter().copied().collect(),
+                            };
+                            self.options = &self.options[len..];
+                            Some(Ok(result))
+                        }
+                    }
                 },
             };
 
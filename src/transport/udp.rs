@@ -118,13 +118,20 @@ impl UdpHeader {
     }
 
     fn calc_checksum_ipv6_internal(&self, source: [u8;16], destination: [u8;16], payload: &[u8]) -> u16 {
+        // a length of zero is used in jumbograms (rfc2675), in this case the
+        // length of the pseudo header is calculated from the payload
+        let length = if 0 == self.length {
+            (UdpHeader::SERIALIZED_SIZE + payload.len()) as u32
+        } else {
+            u32::from(self.length)
+        };
         self.calc_checksum_post_ip(
             //pseudo header
             checksum::Sum16BitWords::new()
             .add_16bytes(source)
             .add_16bytes(destination)
             .add_2bytes([0, ip_number::UDP])
-            .add_2bytes(self.length.to_be_bytes()),
+            .add_4bytes(length.to_be_bytes()),
             payload
         )
     }
@@ -301,6 +308,9 @@ impl<'a> UdpHeaderSlice<'a> {
     }
 
     /// Reads the "length" from the slice.
+    ///
+    /// Note that the length is set to zero in ipv6 jumbograms (rfc2675), in this
+    /// case the length has to be determined via the jumbo payload option.
     #[inline]
     pub fn length(&self) -> u16 {
         // SAFETY:
@@ -93,11 +93,65 @@ proptest! {
             &format!("{}", TcpDataOffsetTooSmall(arg_u8))
         );
 
+        //HipHeaderLengthTooSmall
+        assert_eq!(
+            &format!("ReadError: HIP header length too small. The header length value {} in the HIP header is smaller then the minimum of 4.", arg_u8),
+            &format!("{}", HipHeaderLengthTooSmall(arg_u8))
+        );
+
         //TcpDataOffsetTooSmall
         assert_eq!(
             &format!("ReadError: ICMPv6 packet length {} is bigger then can be represented in an u32.", arg_usize),
             &format!("{}", Icmpv6PacketTooBig(arg_usize))
         );
+
+        //EspPadLengthTooLarge
+        assert_eq!(
+            &format!("ReadError: ESP pad length {} is bigger then the data present in front of the ESP trailer.", arg_u8),
+            &format!("{}", EspPadLengthTooLarge(arg_u8))
+        );
+
+        //IpAuthenticationHeaderNotPresent
+        assert_eq!(
+            "ReadError: Expected an IP authentication header after the IP header & extension headers but a different protocol was present.",
+            &format!("{}", IpAuthenticationHeaderNotPresent)
+        );
+
+        //IcmpExtensionUnsupportedVersion
+        assert_eq!(
+            &format!("ReadError: Unsupported ICMP extension structure version {} (only version 2 is supported).", arg_u8),
+            &format!("{}", IcmpExtensionUnsupportedVersion(arg_u8))
+        );
+
+        //ArpUnsupportedAddressType
+        assert_eq!(
+            "ReadError: Unsupported ARP address types (hardware type 6 & protocol type 0x86dd), only ethernet & IPv4 addresses are supported.",
+            &format!("{}", ArpUnsupportedAddressType{ hardware_type: 6, protocol_type: 0x86dd })
+        );
+
+        //FlowExportUnsupportedVersion
+        assert_eq!(
+            "ReadError: Unsupported or unexpected NetFlow/IPFIX version 7 (supported are 5, 9 & 10).",
+            &format!("{}", FlowExportUnsupportedVersion(7))
+        );
+
+        //FlowExportLengthTooSmall
+        assert_eq!(
+            "ReadError: NetFlow/IPFIX length value 3 is smaller then the message or set header itself.",
+            &format!("{}", FlowExportLengthTooSmall(3))
+        );
+
+        //SflowUnsupportedVersion
+        assert_eq!(
+            "ReadError: Unsupported sFlow datagram version 4 (only version 5 is supported).",
+            &format!("{}", SflowUnsupportedVersion(4))
+        );
+
+        //SflowUnsupportedAddressType
+        assert_eq!(
+            "ReadError: Unsupported sFlow agent address type 3 (supported are 0, 1 & 2).",
+            &format!("{}", SflowUnsupportedAddressType(3))
+        );
     }
 }
 
@@ -125,7 +179,16 @@ fn read_error_source() {
         Ipv6HopByHopHeaderNotAtStart,
         IpAuthenticationHeaderTooSmallPayloadLength(0),
         TcpDataOffsetTooSmall(0),
+        HipHeaderLengthTooSmall(0),
         Icmpv6PacketTooBig(0),
+        EspPadLengthTooLarge(0),
+        IpAuthenticationHeaderNotPresent,
+        IcmpExtensionUnsupportedVersion(0),
+        ArpUnsupportedAddressType{ hardware_type: 0, protocol_type: 0 },
+        FlowExportUnsupportedVersion(0),
+        FlowExportLengthTooSmall(0),
+        SflowUnsupportedVersion(0),
+        SflowUnsupportedAddressType(0),
     ];
 
     for value in &none_values {
@@ -151,7 +214,16 @@ fn read_error_debug() {
         Ipv6HopByHopHeaderNotAtStart,
         IpAuthenticationHeaderTooSmallPayloadLength(0),
         TcpDataOffsetTooSmall(0),
+        HipHeaderLengthTooSmall(0),
         Icmpv6PacketTooBig(0),
+        EspPadLengthTooLarge(0),
+        IpAuthenticationHeaderNotPresent,
+        IcmpExtensionUnsupportedVersion(0),
+        ArpUnsupportedAddressType{ hardware_type: 0, protocol_type: 0 },
+        FlowExportUnsupportedVersion(0),
+        FlowExportLengthTooSmall(0),
+        SflowUnsupportedVersion(0),
+        SflowUnsupportedAddressType(0),
     ];
 
     for value in &values {
@@ -227,6 +299,11 @@ fn value_error_source() {
         Ipv4PayloadLengthTooLarge(0),
         Ipv6PayloadLengthTooLarge(0),
         Ipv6ExtensionPayloadTooLarge(0),
+        Ipv6OptionDataLengthBad(0),
+        NdpOptionDataLengthBad(0),
+        MulticastAddressRecordLengthBad(0),
+        IgmpGroupRecordLengthBad(0),
+        Ipv6RoutingAddressesLengthBad(0),
         IpAuthenticationHeaderBadIcvLength(0),
         Ipv4ExtensionNotReferenced(IpNumber::Icmp),
         Ipv6ExtensionNotReferenced(IpNumber::Icmp),
@@ -236,6 +313,7 @@ fn value_error_source() {
         U8TooLarge{ value:0, max:0, field:ErrorField::Ipv4Dscp },
         U16TooLarge{ value:0, max:0, field:ErrorField::Ipv4Dscp },
         U32TooLarge{ value:0, max:0, field:ErrorField::Ipv4Dscp },
+        U64TooLarge{ value:0, max:0, field:ErrorField::Ipv4Dscp },
         Icmpv6InIpv4,
     ];
 
@@ -253,6 +331,11 @@ fn value_error_debug() {
         Ipv4PayloadLengthTooLarge(0),
         Ipv6PayloadLengthTooLarge(0),
         Ipv6ExtensionPayloadTooLarge(0),
+        Ipv6OptionDataLengthBad(0),
+        NdpOptionDataLengthBad(0),
+        MulticastAddressRecordLengthBad(0),
+        IgmpGroupRecordLengthBad(0),
+        Ipv6RoutingAddressesLengthBad(0),
         IpAuthenticationHeaderBadIcvLength(0),
         Ipv4ExtensionNotReferenced(IpNumber::Icmp),
         Ipv6ExtensionNotReferenced(IpNumber::Icmp),
@@ -262,6 +345,7 @@ fn value_error_debug() {
         U8TooLarge{ value:0, max:0, field:ErrorField::Ipv4Dscp },
         U16TooLarge{ value:0, max:0, field:ErrorField::Ipv4Dscp },
         U32TooLarge{ value:0, max:0, field:ErrorField::Ipv4Dscp },
+        U64TooLarge{ value:0, max:0, field:ErrorField::Ipv4Dscp },
         Icmpv6InIpv4,
     ];
 
@@ -279,6 +363,8 @@ proptest! {
         max_u16 in any::<u16>(),
         value_u32 in any::<u32>(),
         max_u32 in any::<u32>(),
+        value_u64 in any::<u64>(),
+        max_u64 in any::<u64>(),
         arg_usize in any::<usize>(),
         field in error_field_any(),
         ip_protocol_number in ip_number_any(),
@@ -309,6 +395,36 @@ proptest! {
             &format!("{}", Ipv6ExtensionPayloadTooSmall(arg_usize))
         );
 
+        //Ipv6OptionDataLengthBad
+        assert_eq!(
+            &format!("IPv6 option data length ({} bytes) can not be represented in the option (either bigger then 255 bytes or an unaligned CALIPSO compartment bitmap).", arg_usize),
+            &format!("{}", Ipv6OptionDataLengthBad(arg_usize))
+        );
+
+        //NdpOptionDataLengthBad
+        assert_eq!(
+            &format!("ICMPv6 neighbor discovery option length ({} bytes) is bigger then the maximum of 2040 bytes that can be represented in the option length field.", arg_usize),
+            &format!("{}", NdpOptionDataLengthBad(arg_usize))
+        );
+
+        //MulticastAddressRecordLengthBad
+        assert_eq!(
+            &format!("MLDv2 multicast address record (size {} bytes) can not be represented (either more then 65535 sources or the auxiliary data is not a multiple of 4 bytes or bigger then 1020 bytes).", arg_usize),
+            &format!("{}", MulticastAddressRecordLengthBad(arg_usize))
+        );
+
+        //IgmpGroupRecordLengthBad
+        assert_eq!(
+            &format!("IGMPv3 group record (size {} bytes) can not be represented (either more then 65535 sources or the auxiliary data is not a multiple of 4 bytes or bigger then 1020 bytes).", arg_usize),
+            &format!("{}", IgmpGroupRecordLengthBad(arg_usize))
+        );
+
+        //Ipv6RoutingAddressesLengthBad
+        assert_eq!(
+            &format!("IPv6 routing header addresses length ({} bytes) can not be represented in the header (either not matching the RPL compression values or a segment list with no or more then 256 segments).", arg_usize),
+            &format!("{}", Ipv6RoutingAddressesLengthBad(arg_usize))
+        );
+
         //Ipv6ExtensionPayloadTooLarge
         assert_eq!(
             &format!("IPv6 extensions header payload length is too large. The payload size ({} bytes) is larger then what can be be represented by the 'extended header size' field in an IPv6 extension header.", arg_usize),
@@ -393,6 +509,16 @@ proptest! {
             })
         );
 
+        //U64TooLarge
+        assert_eq!(
+            &format!("The value {} of the field '{}' is larger then the allowed maximum of {}.", value_u64, field, max_u64),
+            &format!("{}", U64TooLarge{
+                value: value_u64,
+                max: max_u64,
+                field: field.clone()
+            })
+        );
+
         // Icmpv6InIpv4
         assert_eq!(
             &format!("ICMPv6 packet can not be combined with IPv4 headers."),
@@ -409,8 +535,16 @@ fn error_field_display() {
     assert_eq!("Ipv4Header.differentiated_services_code_point", &format!("{}", Ipv4Dscp));
     assert_eq!("Ipv4Header.explicit_congestion_notification", &format!("{}", Ipv4Ecn));
     assert_eq!("Ipv4Header.fragments_offset", &format!("{}", Ipv4FragmentsOffset));
+    assert_eq!("Ipv4OptionElement::Timestamp.overflow", &format!("{}", Ipv4OptionTimestampOverflow));
     assert_eq!("Ipv6Header.flow_label", &format!("{}", Ipv6FlowLabel));
     assert_eq!("Ipv6FragmentHeader.fragment_offset", &format!("{}", Ipv6FragmentOffset));
+    assert_eq!("Ipv6RoutingHeader::Rpl.cmpr_i", &format!("{}", Ipv6RplCmprI));
+    assert_eq!("Ipv6RoutingHeader::Rpl.cmpr_e", &format!("{}", Ipv6RplCmprE));
+    assert_eq!("HipHeader.packet_type", &format!("{}", HipPacketType));
+    assert_eq!("HipHeader.version", &format!("{}", HipVersion));
+    assert_eq!("Shim6PayloadHeader.receiver_context_tag", &format!("{}", Shim6ReceiverContextTag));
+    assert_eq!("Shim6ControlHeader.message_type", &format!("{}", Shim6MessageType));
+    assert_eq!("Shim6ControlHeader.type_specific", &format!("{}", Shim6TypeSpecific));
     assert_eq!("SingleVlanHeader.priority_code_point", &format!("{}", VlanTagPriorityCodePoint));
     assert_eq!("SingleVlanHeader.vlan_identifier", &format!("{}", VlanTagVlanId));
 }
@@ -0,0 +1,687 @@
+use super::*;
+
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+
+const BOOT_TIME: u64 = 1_600_000_000_000;
+
+fn exporter_addr(last: u8) -> SocketAddr {
+    SocketAddr::new(IpAddr::V4(Ipv4Addr::new(10, 0, 0, last)), 4739)
+}
+
+fn record(ipv6: bool) -> FlowRecord {
+    FlowRecord {
+        key: FlowKey {
+            vlan_id: Some(12),
+            addresses: if ipv6 {
+                FlowAddresses::Ipv6{ source: [1;16], destination: [2;16] }
+            } else {
+                FlowAddresses::Ipv4{ source: [192,168,1,1], destination: [192,168,1,2] }
+            },
+            protocol: ip_number::UDP,
+            source_port: 1234,
+            destination_port: 53,
+        },
+        packets: 2,
+        bytes: 100,
+        first: BOOT_TIME + 1000,
+        last: BOOT_TIME + 2000,
+        tcp_flags: 0,
+        tos: 0x28,
+        end_reason: FlowEndReason::IdleTimeout,
+    }
+}
+
+fn export(format: FlowExportFormat, records: &[FlowRecord]) -> Vec<u8> {
+    FlowExporter::new(
+        FlowExporterConfig {
+            format,
+            source_id: 7,
+            ..Default::default()
+        },
+        BOOT_TIME
+    ).export(records, BOOT_TIME + 3000).remove(0)
+}
+
+/// Builds an IPFIX message containing the given sets (set id & content).
+fn ipfix_message(sets: &[(u16, &[u8])]) -> Vec<u8> {
+    let mut result = Vec::new();
+    for (id, content) in sets {
+        result.extend_from_slice(&id.to_be_bytes());
+        result.extend_from_slice(&((content.len() + 4) as u16).to_be_bytes());
+        result.extend_from_slice(content);
+    }
+    let mut message = IpfixHeader {
+        length: (IpfixHeader::SERIALIZED_SIZE + result.len()) as u16,
+        export_time: 0,
+        sequence_number: 0,
+        observation_domain_id: 1,
+    }.to_bytes().to_vec();
+    message.extend_from_slice(&result);
+    message
+}
+
+#[test]
+fn header_from_slice() {
+    use ReadError::*;
+
+    // netflow v5 header & record
+    {
+        let header = NetflowV5Header {
+            count: 1,
+            sys_uptime: 2,
+            unix_secs: 3,
+            unix_nsecs: 4,
+            flow_sequence: 5,
+            engine_type: 6,
+            engine_id: 7,
+            sampling_interval: 8,
+        };
+        let bytes = header.to_bytes();
+        assert_eq!((header.clone(), &[][..]), NetflowV5Header::from_slice(&bytes).unwrap());
+        assert_matches!(NetflowV5Header::from_slice(&bytes[..23]), Err(UnexpectedEndOfSlice(24)));
+        let mut bad = bytes;
+        bad[1] = 9;
+        assert_matches!(NetflowV5Header::from_slice(&bad), Err(FlowExportUnsupportedVersion(9)));
+
+        let record = NetflowV5Record {
+            source: [1,2,3,4],
+            destination: [5,6,7,8],
+            next_hop: [9,10,11,12],
+            input: 13,
+            output: 14,
+            packets: 15,
+            octets: 16,
+            first: 17,
+            last: 18,
+            source_port: 19,
+            destination_port: 20,
+            tcp_flags: 21,
+            protocol: 22,
+            tos: 23,
+            source_as: 24,
+            destination_as: 25,
+            source_mask: 26,
+            destination_mask: 27,
+        };
+        let mut bytes = record.to_bytes().to_vec();
+        bytes.push(99);
+        assert_eq!((record, &[99u8][..]), NetflowV5Record::from_slice(&bytes).unwrap());
+        assert_matches!(NetflowV5Record::from_slice(&bytes[..47]), Err(UnexpectedEndOfSlice(48)));
+    }
+
+    // netflow v9 header
+    {
+        let header = NetflowV9Header {
+            count: 1,
+            sys_uptime: 2,
+            unix_secs: 3,
+            sequence_number: 4,
+            source_id: 5,
+        };
+        let bytes = header.to_bytes();
+        assert_eq!((header, &[][..]), NetflowV9Header::from_slice(&bytes).unwrap());
+        assert_matches!(NetflowV9Header::from_slice(&bytes[..19]), Err(UnexpectedEndOfSlice(20)));
+        let mut bad = bytes;
+        bad[1] = 10;
+        assert_matches!(NetflowV9Header::from_slice(&bad), Err(FlowExportUnsupportedVersion(10)));
+    }
+
+    // ipfix header (cut to the length)
+    {
+        let header = IpfixHeader {
+            length: 18,
+            export_time: 2,
+            sequence_number: 3,
+            observation_domain_id: 4,
+        };
+        let mut bytes = header.to_bytes().to_vec();
+        bytes.extend_from_slice(&[1,2,3]);
+        assert_eq!((header, &[1u8,2][..]), IpfixHeader::from_slice(&bytes).unwrap());
+        assert_matches!(IpfixHeader::from_slice(&bytes[..15]), Err(UnexpectedEndOfSlice(16)));
+        assert_matches!(IpfixHeader::from_slice(&bytes[..17]), Err(UnexpectedEndOfSlice(18)));
+        let mut bad = bytes.clone();
+        bad[1] = 9;
+        assert_matches!(IpfixHeader::from_slice(&bad), Err(FlowExportUnsupportedVersion(9)));
+        let mut bad = bytes;
+        bad[3] = 15;
+        assert_matches!(IpfixHeader::from_slice(&bad), Err(FlowExportLengthTooSmall(15)));
+    }
+}
+
+#[test]
+fn field_decode() {
+    use flow_field_type::*;
+    use FlowField::*;
+
+    let field = |field_type, length| FlowTemplateField{ field_type, length, enterprise_number: None };
+
+    // nominal size & reduced size encoding
+    assert_eq!(OctetDeltaCount(0x0102030405060708), FlowField::decode(&field(OCTET_DELTA_COUNT, 8), &[1,2,3,4,5,6,7,8]));
+    assert_eq!(OctetDeltaCount(0x0102), FlowField::decode(&field(OCTET_DELTA_COUNT, 2), &[1,2]));
+    assert_eq!(PacketDeltaCount(3), FlowField::decode(&field(PACKET_DELTA_COUNT, 4), &[0,0,0,3]));
+    assert_eq!(TcpControlBits(0x12), FlowField::decode(&field(TCP_CONTROL_BITS, 1), &[0x12]));
+    assert_eq!(SourceTransportPort(0x1234), FlowField::decode(&field(SOURCE_TRANSPORT_PORT, 2), &[0x12, 0x34]));
+    assert_eq!(IngressInterface(0x010203), FlowField::decode(&field(INGRESS_INTERFACE, 3), &[1,2,3]));
+    assert_eq!(SourceIpv4Address([1,2,3,4]), FlowField::decode(&field(SOURCE_IPV4_ADDRESS, 4), &[1,2,3,4]));
+    assert_eq!(DestinationIpv6Address([9;16]), FlowField::decode(&field(DESTINATION_IPV6_ADDRESS, 16), &[9;16]));
+    assert_eq!(SourceMacAddress([1,2,3,4,5,6]), FlowField::decode(&field(SOURCE_MAC_ADDRESS, 6), &[1,2,3,4,5,6]));
+    assert_eq!(FlowEndReason(2), FlowField::decode(&field(FLOW_END_REASON, 1), &[2]));
+    assert_eq!(FlowEndMilliseconds(1000), FlowField::decode(&field(FLOW_END_MILLISECONDS, 8), &[0,0,0,0,0,0,3,0xe8]));
+
+    // unexpected lengths
+    assert_eq!(
+        Unknown{ field_type: SOURCE_TRANSPORT_PORT, enterprise_number: None, data: &[1,2,3] },
+        FlowField::decode(&field(SOURCE_TRANSPORT_PORT, 3), &[1,2,3])
+    );
+    assert_eq!(
+        Unknown{ field_type: SOURCE_IPV4_ADDRESS, enterprise_number: None, data: &[1,2] },
+        FlowField::decode(&field(SOURCE_IPV4_ADDRESS, 2), &[1,2])
+    );
+    assert_eq!(
+        Unknown{ field_type: PROTOCOL_IDENTIFIER, enterprise_number: None, data: &[] },
+        FlowField::decode(&field(PROTOCOL_IDENTIFIER, 0), &[])
+    );
+
+    // unknown & enterprise specific fields
+    assert_eq!(
+        Unknown{ field_type: 1000, enterprise_number: None, data: &[1] },
+        FlowField::decode(&field(1000, 1), &[1])
+    );
+    assert_eq!(
+        Unknown{ field_type: SOURCE_TRANSPORT_PORT, enterprise_number: Some(9), data: &[1,2] },
+        FlowField::decode(
+            &FlowTemplateField{ field_type: SOURCE_TRANSPORT_PORT, length: 2, enterprise_number: Some(9) },
+            &[1,2]
+        )
+    );
+}
+
+#[test]
+fn netflow_v5() {
+    let mut cache = FlowTemplateCache::new();
+    let message = export(FlowExportFormat::NetflowV5, &[record(false), record(false)]);
+    match cache.decode(exporter_addr(1), &message).unwrap() {
+        FlowExportMessage::NetflowV5{ header, records } => {
+            assert_eq!(2, header.count);
+            assert_eq!(3000, header.sys_uptime);
+            assert_eq!(2, records.len());
+            assert_eq!([192,168,1,1], records[0].source);
+            assert_eq!(1234, records[0].source_port);
+            assert_eq!(1000, records[1].first);
+            assert_eq!(0x28, records[1].tos);
+        },
+        value => panic!("unexpected message {:?}", value),
+    }
+    assert!(cache.is_empty());
+
+    // records missing
+    assert_matches!(
+        cache.decode(exporter_addr(1), &message[..24 + 48 + 47]),
+        Err(ReadError::UnexpectedEndOfSlice(120))
+    );
+}
+
+#[test]
+fn netflow_v9() {
+    use FlowField::*;
+
+    let mut cache = FlowTemplateCache::new();
+    let message = export(FlowExportFormat::NetflowV9, &[record(false), record(true)]);
+
+    // data without templates
+    {
+        let data_only = {
+            let mut exporter = FlowExporter::new(
+                FlowExporterConfig {
+                    format: FlowExportFormat::NetflowV9,
+                    source_id: 7,
+                    ..Default::default()
+                },
+                BOOT_TIME
+            );
+            exporter.export(&[record(false)], BOOT_TIME);
+            exporter.export(&[record(false)], BOOT_TIME).remove(0)
+        };
+        match cache.decode(exporter_addr(1), &data_only).unwrap() {
+            FlowExportMessage::NetflowV9{ sets, .. } => {
+                assert_eq!(1, sets.len());
+                assert_matches!(sets[0], FlowSet::UnknownTemplate{ template_id: 256, .. });
+            },
+            value => panic!("unexpected message {:?}", value),
+        }
+    }
+
+    match cache.decode(exporter_addr(1), &message).unwrap() {
+        FlowExportMessage::NetflowV9{ header, sets } => {
+            assert_eq!(4, header.count);
+            assert_eq!(7, header.source_id);
+            assert_eq!(3, sets.len());
+            match &sets[0] {
+                FlowSet::Templates(templates) => {
+                    let expected: Vec<FlowTemplate> = FlowExporter::new(
+                        FlowExporterConfig {
+                            format: FlowExportFormat::NetflowV9,
+                            ..Default::default()
+                        },
+                        0
+                    ).templates().into_iter().map(|(template_id, fields)| FlowTemplate {
+                        template_id,
+                        scope_field_count: 0,
+                        fields,
+                    }).collect();
+                    assert_eq!(&expected, templates);
+                },
+                value => panic!("unexpected set {:?}", value),
+            }
+            assert_eq!(
+                FlowSet::Data {
+                    template_id: 256,
+                    records: vec![vec![
+                        SourceIpv4Address([192,168,1,1]),
+                        DestinationIpv4Address([192,168,1,2]),
+                        SourceTransportPort(1234),
+                        DestinationTransportPort(53),
+                        ProtocolIdentifier(ip_number::UDP),
+                        IpClassOfService(0x28),
+                        TcpControlBits(0),
+                        PacketDeltaCount(2),
+                        OctetDeltaCount(100),
+                        FlowStartSysUpTime(1000),
+                        FlowEndSysUpTime(2000),
+                        VlanId(12),
+                    ]],
+                },
+                sets[1]
+            );
+            match &sets[2] {
+                FlowSet::Data{ template_id, records } => {
+                    assert_eq!(257, *template_id);
+                    assert_eq!(1, records.len());
+                    assert_eq!(SourceIpv6Address([1;16]), records[0][0]);
+                    assert_eq!(DestinationIpv6Address([2;16]), records[0][1]);
+                },
+                value => panic!("unexpected set {:?}", value),
+            }
+        },
+        value => panic!("unexpected message {:?}", value),
+    }
+    assert_eq!(2, cache.len());
+    let scope = FlowTemplateScope {
+        exporter: exporter_addr(1),
+        version: 9,
+        domain_id: 7,
+    };
+    assert_eq!(12, cache.template(&scope, 256).unwrap().fields.len());
+    assert!(cache.template(&FlowTemplateScope{ domain_id: 8, ..scope }, 256).is_none());
+    assert!(cache.template(&FlowTemplateScope{ version: 10, ..scope }, 256).is_none());
+
+    // templates are not shared between exporters
+    {
+        let mut other = message.clone();
+        // replace the template set with a reserved set
+        other[21] = 2;
+        match cache.decode(exporter_addr(2), &other).unwrap() {
+            FlowExportMessage::NetflowV9{ sets, .. } => {
+                assert_eq!(2, sets.len());
+                assert_matches!(sets[0], FlowSet::UnknownTemplate{ template_id: 256, .. });
+            },
+            value => panic!("unexpected message {:?}", value),
+        }
+        cache.remove_exporter(exporter_addr(2));
+        assert_eq!(2, cache.len());
+        cache.remove_exporter(exporter_addr(1));
+        assert!(cache.is_empty());
+    }
+
+    // options template
+    {
+        let mut message = NetflowV9Header {
+            count: 2,
+            sys_uptime: 0,
+            unix_secs: 0,
+            sequence_number: 0,
+            source_id: 1,
+        }.to_bytes().to_vec();
+        message.extend_from_slice(&[
+            // options template set
+            0, 1, 0, 20,
+            1, 0, // template id
+            0, 4, // scope length
+            0, 4, // options length
+            0, 1, 0, 4, // scope: system
+            0, 34, 0, 4, // sampling interval
+            0, 0, // padding
+            // data set
+            1, 0, 0, 12,
+            0, 0, 0, 9,
+            0, 0, 0, 100,
+        ]);
+        match cache.decode(exporter_addr(1), &message).unwrap() {
+            FlowExportMessage::NetflowV9{ sets, .. } => {
+                assert_eq!(
+                    FlowSet::Templates(vec![FlowTemplate {
+                        template_id: 256,
+                        scope_field_count: 1,
+                        fields: vec![
+                            FlowTemplateField{ field_type: 1, length: 4, enterprise_number: None },
+                            FlowTemplateField{ field_type: 34, length: 4, enterprise_number: None },
+                        ],
+                    }]),
+                    sets[0]
+                );
+                assert_eq!(
+                    FlowSet::Data {
+                        template_id: 256,
+                        records: vec![vec![
+                            // scope field types are not information elements
+                            OctetDeltaCount(9),
+                            Unknown{ field_type: 34, enterprise_number: None, data: &[0,0,0,100] },
+                        ]],
+                    },
+                    sets[1]
+                );
+            },
+            value => panic!("unexpected message {:?}", value),
+        }
+    }
+}
+
+#[test]
+fn ipfix() {
+    use FlowField::*;
+
+    let mut cache = FlowTemplateCache::new();
+    let message = export(FlowExportFormat::Ipfix, &[record(false)]);
+
+    // decoded from the payload of a sliced udp packet
+    let builder = PacketBuilder::ipv4([10,0,0,1], [10,0,0,2], 64).udp(4739, 4739);
+    let mut packet = Vec::with_capacity(builder.size(message.len()));
+    builder.write(&mut packet, &message).unwrap();
+    let sliced = SlicedPacket::from_ip(&packet).unwrap();
+
+    match cache.decode(exporter_addr(1), sliced.payload).unwrap() {
+        FlowExportMessage::Ipfix{ header, sets } => {
+            assert_eq!(message.len(), usize::from(header.length));
+            assert_eq!(7, header.observation_domain_id);
+            assert_eq!(2, sets.len());
+            assert_eq!(
+                FlowSet::Data {
+                    template_id: 256,
+                    records: vec![vec![
+                        SourceIpv4Address([192,168,1,1]),
+                        DestinationIpv4Address([192,168,1,2]),
+                        SourceTransportPort(1234),
+                        DestinationTransportPort(53),
+                        ProtocolIdentifier(ip_number::UDP),
+                        IpClassOfService(0x28),
+                        TcpControlBits(0),
+                        PacketDeltaCount(2),
+                        OctetDeltaCount(100),
+                        FlowStartMilliseconds(BOOT_TIME + 1000),
+                        FlowEndMilliseconds(BOOT_TIME + 2000),
+                        VlanId(12),
+                        FlowEndReason(etherparse::FlowEndReason::IdleTimeout as u8),
+                    ]],
+                },
+                sets[1]
+            );
+        },
+        value => panic!("unexpected message {:?}", value),
+    }
+    let scope = FlowTemplateScope {
+        exporter: exporter_addr(1),
+        version: 10,
+        domain_id: 7,
+    };
+    assert_eq!(13, cache.template(&scope, 256).unwrap().fields.len());
+}
+
+#[test]
+fn ipfix_handcrafted() {
+    use flow_field_type::*;
+    use FlowField::*;
+
+    let mut cache = FlowTemplateCache::new();
+    let message = ipfix_message(&[
+        (2, &[
+            1, 0, 0, 4, // template 256 with 4 fields
+            0, 7, 0, 2, // source port
+            0x80, 7, 0, 2, 0, 0, 0, 9, // enterprise specific field
+            0x01, 0x5f, 0xff, 0xff, // variable length (351, interfaceName)
+            0, 1, 0, 2, // octet delta count (reduced size)
+        ]),
+        (3, &[
+            1, 1, 0, 2, 0, 1, // options template 257 with 2 fields & 1 scope field
+            0, 10, 0, 4, // ingress interface
+            0, 58, 0, 2, // vlan id
+            0, 0, // padding
+        ]),
+        (256, &[
+            0, 80, 1, 2, 1, b'a', 0, 1,
+            0, 81, 4, 5, 255, 0, 2, b'b', b'c', 0, 2,
+            0, 0, // padding
+        ]),
+        (257, &[0, 0, 0, 3, 0, 4]),
+        (300, &[1, 2, 3, 4]),
+        (4, &[1, 2, 3, 4]),
+    ]);
+    match cache.decode(exporter_addr(1), &message).unwrap() {
+        FlowExportMessage::Ipfix{ sets, .. } => {
+            // reserved set is skipped
+            assert_eq!(5, sets.len());
+            assert_eq!(
+                FlowSet::Templates(vec![FlowTemplate {
+                    template_id: 256,
+                    scope_field_count: 0,
+                    fields: vec![
+                        FlowTemplateField{ field_type: SOURCE_TRANSPORT_PORT, length: 2, enterprise_number: None },
+                        FlowTemplateField{ field_type: 7, length: 2, enterprise_number: Some(9) },
+                        FlowTemplateField{ field_type: 351, length: FLOW_FIELD_VARIABLE_LENGTH, enterprise_number: None },
+                        FlowTemplateField{ field_type: OCTET_DELTA_COUNT, length: 2, enterprise_number: None },
+                    ],
+                }]),
+                sets[0]
+            );
+            assert_eq!(
+                FlowSet::Templates(vec![FlowTemplate {
+                    template_id: 257,
+                    scope_field_count: 1,
+                    fields: vec![
+                        FlowTemplateField{ field_type: INGRESS_INTERFACE, length: 4, enterprise_number: None },
+                        FlowTemplateField{ field_type: VLAN_ID, length: 2, enterprise_number: None },
+                    ],
+                }]),
+                sets[1]
+            );
+            assert_eq!(
+                FlowSet::Data {
+                    template_id: 256,
+                    records: vec![
+                        vec![
+                            SourceTransportPort(80),
+                            Unknown{ field_type: 7, enterprise_number: Some(9), data: &[1, 2] },
+                            Unknown{ field_type: 351, enterprise_number: None, data: b"a" },
+                            OctetDeltaCount(1),
+                        ],
+                        vec![
+                            SourceTransportPort(81),
+                            Unknown{ field_type: 7, enterprise_number: Some(9), data: &[4, 5] },
+                            Unknown{ field_type: 351, enterprise_number: None, data: b"bc" },
+                            OctetDeltaCount(2),
+                        ],
+                    ],
+                },
+                sets[2]
+            );
+            assert_eq!(
+                FlowSet::Data {
+                    template_id: 257,
+                    records: vec![vec![IngressInterface(3), VlanId(4)]],
+                },
+                sets[3]
+            );
+            assert_eq!(
+                FlowSet::UnknownTemplate{ template_id: 300, data: &[1, 2, 3, 4] },
+                sets[4]
+            );
+        },
+        value => panic!("unexpected message {:?}", value),
+    }
+    assert_eq!(2, cache.len());
+
+    // template withdrawal
+    let message = ipfix_message(&[(2, &[1, 0, 0, 0])]);
+    cache.decode(exporter_addr(1), &message).unwrap();
+    assert_eq!(1, cache.len());
+    let scope = FlowTemplateScope {
+        exporter: exporter_addr(1),
+        version: 10,
+        domain_id: 1,
+    };
+    assert!(cache.template(&scope, 256).is_none());
+    assert!(cache.template(&scope, 257).is_some());
+}
+
+#[test]
+fn netflow_v9_max_length_field() {
+    // 0xffff is a regular length for NetFlow v9 (variable length
+    // fields only exist in IPFIX)
+    let mut cache = FlowTemplateCache::new();
+    let mut message = NetflowV9Header {
+        count: 2,
+        sys_uptime: 0,
+        unix_secs: 0,
+        sequence_number: 0,
+        source_id: 1,
+    }.to_bytes().to_vec();
+    message.extend_from_slice(&[
+        // template set
+        0, 0, 0, 12,
+        1, 0, 0, 1, // template 256 with 1 field
+        0, 82, 0xff, 0xff, // interface name with a length of 65535
+        // data set
+        1, 0, 0, 8,
+        1, b'a', 0, 0,
+    ]);
+    match cache.decode(exporter_addr(1), &message).unwrap() {
+        FlowExportMessage::NetflowV9{ sets, .. } => {
+            assert_eq!(
+                FlowSet::Data{ template_id: 256, records: Vec::new() },
+                sets[1]
+            );
+        },
+        value => panic!("unexpected message {:?}", value),
+    }
+}
+
+#[test]
+fn max_templates() {
+    assert_eq!(FlowTemplateCache::DEFAULT_MAX_TEMPLATES, FlowTemplateCache::new().max_templates());
+    assert_eq!(FlowTemplateCache::DEFAULT_MAX_TEMPLATES, FlowTemplateCache::default().max_templates());
+
+    let scope = |exporter: u8| FlowTemplateScope {
+        exporter: exporter_addr(exporter),
+        version: 10,
+        domain_id: 1,
+    };
+    let template = |id: u8| ipfix_message(&[(2, &[1, id, 0, 1, 0, 7, 0, 2])]);
+    let data = |id: u8| ipfix_message(&[(256 + u16::from(id), &[0, 80])]);
+
+    let mut cache = FlowTemplateCache::with_max_templates(2);
+    assert_eq!(2, cache.max_templates());
+    cache.decode(exporter_addr(1), &template(0)).unwrap();
+    cache.decode(exporter_addr(2), &template(1)).unwrap();
+    // using a template marks it as recently used
+    cache.decode(exporter_addr(1), &data(0)).unwrap();
+    cache.decode(exporter_addr(3), &template(2)).unwrap();
+    assert_eq!(2, cache.len());
+    assert!(cache.template(&scope(1), 256).is_some());
+    assert!(cache.template(&scope(2), 257).is_none());
+    assert!(cache.template(&scope(3), 258).is_some());
+
+    // refreshing a template does not remove other templates
+    cache.decode(exporter_addr(3), &template(2)).unwrap();
+    assert_eq!(2, cache.len());
+    assert!(cache.template(&scope(1), 256).is_some());
+
+    // removed templates are no longer considered for the eviction
+    cache.remove_exporter(exporter_addr(1));
+    assert_eq!(1, cache.len());
+    cache.decode(exporter_addr(4), &template(3)).unwrap();
+    assert_eq!(2, cache.len());
+    assert!(cache.template(&scope(3), 258).is_some());
+    assert!(cache.template(&scope(4), 259).is_some());
+
+    // a maximum of 0 is treated like 1
+    let mut cache = FlowTemplateCache::with_max_templates(0);
+    cache.decode(exporter_addr(1), &template(0)).unwrap();
+    cache.decode(exporter_addr(1), &template(1)).unwrap();
+    assert_eq!(1, cache.len());
+    assert!(cache.template(&scope(1), 257).is_some());
+}
+
+#[test]
+fn decode_errors() {
+    use ReadError::*;
+
+    let mut cache = FlowTemplateCache::new();
+    let addr = exporter_addr(1);
+
+    assert_matches!(cache.decode(addr, &[0]), Err(UnexpectedEndOfSlice(2)));
+    assert_matches!(cache.decode(addr, &[0, 7, 0, 0]), Err(FlowExportUnsupportedVersion(7)));
+    assert_matches!(cache.decode(addr, &[0, 9, 0, 0]), Err(UnexpectedEndOfSlice(20)));
+
+    // set length too small
+    {
+        let mut message = ipfix_message(&[(256, &[])]);
+        message[19] = 3;
+        assert_matches!(cache.decode(addr, &message), Err(FlowExportLengthTooSmall(3)));
+    }
+
+    // set longer then the message
+    {
+        let mut message = ipfix_message(&[(256, &[1, 2])]);
+        message[19] = 8;
+        assert_matches!(cache.decode(addr, &message), Err(UnexpectedEndOfSlice(24)));
+    }
+
+    // truncated template
+    {
+        let message = ipfix_message(&[(2, &[1, 0, 0, 2, 0, 7, 0, 2])]);
+        assert_matches!(cache.decode(addr, &message), Err(UnexpectedEndOfSlice(32)));
+        let message = ipfix_message(&[(2, &[1, 0, 0, 1, 0x80, 7, 0, 2, 0, 0])]);
+        assert_matches!(cache.decode(addr, &message), Err(UnexpectedEndOfSlice(32)));
+        assert!(cache.is_empty());
+    }
+
+    // counts larger then the data
+    {
+        let header = NetflowV5Header {
+            count: u16::MAX,
+            sys_uptime: 0,
+            unix_secs: 0,
+            unix_nsecs: 0,
+            flow_sequence: 0,
+            engine_type: 0,
+            engine_id: 0,
+            sampling_interval: 0,
+        };
+        assert_matches!(
+            cache.decode(addr, &header.to_bytes()),
+            Err(UnexpectedEndOfSlice(_))
+        );
+        let message = ipfix_message(&[(2, &[1, 0, 0xff, 0xff])]);
+        assert_matches!(cache.decode(addr, &message), Err(UnexpectedEndOfSlice(28)));
+        assert!(cache.is_empty());
+    }
+
+    // netflow v9 padding after the last set is ignored
+    {
+        let mut message = NetflowV9Header::default().to_bytes().to_vec();
+        message.extend_from_slice(&[0, 0, 0]);
+        assert_eq!(
+            FlowExportMessage::NetflowV9{ header: Default::default(), sets: Vec::new() },
+            cache.decode(addr, &message).unwrap()
+        );
+    }
+}
@@ -0,0 +1,378 @@
+use super::*;
+
+const BOOT_TIME: u64 = 1_600_000_000_000;
+
+fn record(source_port: u16, ipv6: bool) -> FlowRecord {
+    FlowRecord {
+        key: FlowKey {
+            vlan_id: Some(0x123),
+            addresses: if ipv6 {
+                FlowAddresses::Ipv6{ source: [1;16], destination: [2;16] }
+            } else {
+                FlowAddresses::Ipv4{ source: [192,168,1,1], destination: [192,168,1,2] }
+            },
+            protocol: ip_number::TCP,
+            source_port,
+            destination_port: 80,
+        },
+        packets: 3,
+        bytes: 0x1_0000_0000,
+        first: BOOT_TIME + 1000,
+        last: BOOT_TIME + 2000,
+        tcp_flags: 0x1b,
+        tos: 0x28,
+        end_reason: FlowEndReason::EndOfFlow,
+    }
+}
+
+fn exporter(format: FlowExportFormat) -> FlowExporter {
+    FlowExporter::new(
+        FlowExporterConfig {
+            format,
+            source_id: 0x01020304,
+            engine_type: 1,
+            engine_id: 2,
+            ..Default::default()
+        },
+        BOOT_TIME
+    )
+}
+
+fn u16_at(message: &[u8], offset: usize) -> u16 {
+    u16::from_be_bytes([message[offset], message[offset + 1]])
+}
+
+fn u32_at(message: &[u8], offset: usize) -> u32 {
+    u32::from_be_bytes([message[offset], message[offset + 1], message[offset + 2], message[offset + 3]])
+}
+
+#[test]
+fn config_default() {
+    let config: FlowExporterConfig = Default::default();
+    assert_eq!(FlowExportFormat::Ipfix, config.format);
+    assert_eq!(0, config.source_id);
+    assert_eq!(0, config.engine_type);
+    assert_eq!(0, config.engine_id);
+    assert_eq!(1400, config.max_message_len);
+    assert_eq!(20, config.template_refresh_interval);
+
+    let exporter = FlowExporter::new(config.clone(), 0);
+    assert_eq!(&config, exporter.config());
+    assert_eq!(0, exporter.sequence_number());
+}
+
+#[test]
+fn headers_to_bytes() {
+    assert_eq!(
+        [
+            0, 5, 0, 2,
+            1, 2, 3, 4,
+            5, 6, 7, 8,
+            9, 10, 11, 12,
+            13, 14, 15, 16,
+            17, 18, 19, 20,
+        ],
+        NetflowV5Header {
+            count: 2,
+            sys_uptime: 0x01020304,
+            unix_secs: 0x05060708,
+            unix_nsecs: 0x090a0b0c,
+            flow_sequence: 0x0d0e0f10,
+            engine_type: 17,
+            engine_id: 18,
+            sampling_interval: 0x1314,
+        }.to_bytes()
+    );
+    assert_eq!(
+        [
+            0, 9, 0, 2,
+            1, 2, 3, 4,
+            5, 6, 7, 8,
+            9, 10, 11, 12,
+            13, 14, 15, 16,
+        ],
+        NetflowV9Header {
+            count: 2,
+            sys_uptime: 0x01020304,
+            unix_secs: 0x05060708,
+            sequence_number: 0x090a0b0c,
+            source_id: 0x0d0e0f10,
+        }.to_bytes()
+    );
+    assert_eq!(
+        [
+            0, 10, 0, 20,
+            1, 2, 3, 4,
+            5, 6, 7, 8,
+            9, 10, 11, 12,
+        ],
+        IpfixHeader {
+            length: 20,
+            export_time: 0x01020304,
+            sequence_number: 0x05060708,
+            observation_domain_id: 0x090a0b0c,
+        }.to_bytes()
+    );
+
+    // write
+    let mut buffer = Vec::new();
+    NetflowV5Header::default().write(&mut buffer).unwrap();
+    NetflowV5Record::default().write(&mut buffer).unwrap();
+    NetflowV9Header::default().write(&mut buffer).unwrap();
+    IpfixHeader::default().write(&mut buffer).unwrap();
+    assert_eq!(
+        NetflowV5Header::SERIALIZED_SIZE
+            + NetflowV5Record::SERIALIZED_SIZE
+            + NetflowV9Header::SERIALIZED_SIZE
+            + IpfixHeader::SERIALIZED_SIZE,
+        buffer.len()
+    );
+}
+
+#[test]
+fn netflow_v5() {
+    let mut exporter = exporter(FlowExportFormat::NetflowV5);
+    assert!(exporter.templates().is_empty());
+
+    // ipv6 records are skipped
+    let messages = exporter.export(&[record(1, false), record(2, true), record(3, false)], BOOT_TIME + 3500);
+    assert_eq!(1, messages.len());
+    let message = &messages[0];
+    assert_eq!(24 + 2*48, message.len());
+    assert_eq!(
+        NetflowV5Header {
+            count: 2,
+            sys_uptime: 3500,
+            unix_secs: 1_600_000_003,
+            unix_nsecs: 500_000_000,
+            flow_sequence: 0,
+            engine_type: 1,
+            engine_id: 2,
+            sampling_interval: 0,
+        }.to_bytes(),
+        message[..24]
+    );
+    assert_eq!(
+        NetflowV5Record {
+            source: [192,168,1,1],
+            destination: [192,168,1,2],
+            packets: 3,
+            // saturated
+            octets: u32::MAX,
+            first: 1000,
+            last: 2000,
+            source_port: 1,
+            destination_port: 80,
+            tcp_flags: 0x1b,
+            protocol: ip_number::TCP,
+            tos: 0x28,
+            ..Default::default()
+        }.to_bytes(),
+        message[24..72]
+    );
+    assert_eq!(3, u16_at(message, 72 + 32));
+    assert_eq!(2, exporter.sequence_number());
+
+    // split into messages based on the maximum length
+    let records: Vec<FlowRecord> = (0..31).map(|i| record(i, false)).collect();
+    let messages = exporter.export(&records, BOOT_TIME);
+    assert_eq!(2, messages.len());
+    assert_eq!(24 + 28*48, messages[0].len());
+    assert_eq!(28, u16_at(&messages[0], 2));
+    assert_eq!(2, u32_at(&messages[0], 16));
+    assert_eq!(24 + 3*48, messages[1].len());
+    assert_eq!(30, u32_at(&messages[1], 16));
+    assert_eq!(33, exporter.sequence_number());
+
+    // split into messages with at most 30 records
+    let mut exporter = FlowExporter::new(
+        FlowExporterConfig {
+            format: FlowExportFormat::NetflowV5,
+            max_message_len: 65535,
+            ..Default::default()
+        },
+        BOOT_TIME
+    );
+    let messages = exporter.export(&records, BOOT_TIME);
+    assert_eq!(2, messages.len());
+    assert_eq!(24 + 30*48, messages[0].len());
+    assert_eq!(24 + 48, messages[1].len());
+    assert_eq!(30, u32_at(&messages[1], 16));
+
+    // nothing to export
+    assert!(exporter.export(&[record(1, true)], BOOT_TIME).is_empty());
+    assert!(exporter.export(&[], BOOT_TIME).is_empty());
+}
+
+#[test]
+fn netflow_v9() {
+    let mut exporter = FlowExporter::new(
+        FlowExporterConfig {
+            format: FlowExportFormat::NetflowV9,
+            source_id: 0x01020304,
+            template_refresh_interval: 2,
+            ..Default::default()
+        },
+        BOOT_TIME
+    );
+
+    let templates = exporter.templates();
+    assert_eq!(2, templates.len());
+    assert_eq!(FLOW_EXPORT_IPV4_TEMPLATE_ID, templates[0].0);
+    assert_eq!(FLOW_EXPORT_IPV6_TEMPLATE_ID, templates[1].0);
+    assert_eq!(
+        FlowTemplateField{ field_type: flow_field_type::SOURCE_IPV4_ADDRESS, length: 4, enterprise_number: None },
+        templates[0].1[0]
+    );
+    assert_eq!(
+        FlowTemplateField{ field_type: flow_field_type::SOURCE_IPV6_ADDRESS, length: 16, enterprise_number: None },
+        templates[1].1[0]
+    );
+    assert!(templates[0].1.contains(&FlowTemplateField{ field_type: flow_field_type::FLOW_START_SYS_UP_TIME, length: 4, enterprise_number: None }));
+    let ipv4_len: usize = templates[0].1.iter().map(|f| usize::from(f.length)).sum();
+    assert_eq!(41, ipv4_len);
+    let ipv6_len: usize = templates[1].1.iter().map(|f| usize::from(f.length)).sum();
+    assert_eq!(65, ipv6_len);
+
+    // first message contains the templates
+    let messages = exporter.export(&[record(1, false), record(2, false), record(3, true)], BOOT_TIME + 5000);
+    assert_eq!(1, messages.len());
+    let message = &messages[0];
+    assert_eq!(
+        NetflowV9Header {
+            count: 5,
+            sys_uptime: 5000,
+            unix_secs: 1_600_000_005,
+            sequence_number: 0,
+            source_id: 0x01020304,
+        }.to_bytes(),
+        message[..20]
+    );
+
+    // template flowset
+    let templates_len = 4 + 2*(4 + 12*4);
+    assert_eq!(0, u16_at(message, 20));
+    assert_eq!(templates_len, usize::from(u16_at(message, 22)));
+    assert_eq!(256, u16_at(message, 24));
+    assert_eq!(12, u16_at(message, 26));
+    assert_eq!(flow_field_type::SOURCE_IPV4_ADDRESS, u16_at(message, 28));
+    assert_eq!(4, u16_at(message, 30));
+
+    // ipv4 data flowset (padded to a multiple of 4)
+    let ipv4_set = 20 + templates_len;
+    assert_eq!(256, u16_at(message, ipv4_set));
+    assert_eq!(4 + 2*41 + 2, usize::from(u16_at(message, ipv4_set + 2)));
+    {
+        let data = &message[ipv4_set + 4..ipv4_set + 4 + 41];
+        assert_eq!([192,168,1,1,192,168,1,2], data[..8]);
+        assert_eq!([0,1,0,80,ip_number::TCP,0x28,0x1b], data[8..15]);
+        assert_eq!(3, u64::from_be_bytes([data[15], data[16], data[17], data[18], data[19], data[20], data[21], data[22]]));
+        assert_eq!(0x1_0000_0000, u64::from_be_bytes([data[23], data[24], data[25], data[26], data[27], data[28], data[29], data[30]]));
+        assert_eq!(1000, u32_at(data, 31));
+        assert_eq!(2000, u32_at(data, 35));
+        assert_eq!(0x123, u16_at(data, 39));
+    }
+
+    // ipv6 data flowset
+    let ipv6_set = ipv4_set + 4 + 2*41 + 2;
+    assert_eq!(257, u16_at(message, ipv6_set));
+    assert_eq!(4 + 65 + 3, usize::from(u16_at(message, ipv6_set + 2)));
+    assert_eq!(ipv6_set + 4 + 65 + 3, message.len());
+    assert_eq!(1, exporter.sequence_number());
+
+    // templates are resent after the refresh interval
+    let message = exporter.export(&[record(1, false)], BOOT_TIME).remove(0);
+    assert_eq!(1, u32_at(&message, 12));
+    assert_eq!(1, u16_at(&message, 2));
+    assert_eq!(256, u16_at(&message, 20));
+    let message = exporter.export(&[record(1, false)], BOOT_TIME).remove(0);
+    assert_eq!(3, u16_at(&message, 2));
+    assert_eq!(0, u16_at(&message, 20));
+    let message = exporter.export(&[record(1, false)], BOOT_TIME).remove(0);
+    assert_eq!(1, u16_at(&message, 2));
+    assert_eq!(4, exporter.sequence_number());
+}
+
+#[test]
+fn ipfix() {
+    let mut exporter = exporter(FlowExportFormat::Ipfix);
+
+    let templates = exporter.templates();
+    assert_eq!(
+        Some(&FlowTemplateField{ field_type: flow_field_type::FLOW_END_REASON, length: 1, enterprise_number: None }),
+        templates[0].1.last()
+    );
+    let ipv4_len: usize = templates[0].1.iter().map(|f| usize::from(f.length)).sum();
+    assert_eq!(50, ipv4_len);
+    let ipv6_len: usize = templates[1].1.iter().map(|f| usize::from(f.length)).sum();
+    assert_eq!(74, ipv6_len);
+
+    let messages = exporter.export(&[record(1, true), record(2, false)], BOOT_TIME + 5000);
+    assert_eq!(1, messages.len());
+    let message = &messages[0];
+    assert_eq!(
+        IpfixHeader {
+            length: message.len() as u16,
+            export_time: 1_600_000_005,
+            sequence_number: 0,
+            observation_domain_id: 0x01020304,
+        }.to_bytes(),
+        message[..16]
+    );
+
+    // template set
+    let templates_len = 4 + 2*(4 + 13*4);
+    assert_eq!(2, u16_at(message, 16));
+    assert_eq!(templates_len, usize::from(u16_at(message, 18)));
+
+    // ipv6 data set
+    let ipv6_set = 16 + templates_len;
+    assert_eq!(257, u16_at(message, ipv6_set));
+    assert_eq!(4 + 74 + 2, usize::from(u16_at(message, ipv6_set + 2)));
+    {
+        let data = &message[ipv6_set + 4..];
+        assert_eq!([1;16], data[..16]);
+        assert_eq!([2;16], data[16..32]);
+        assert_eq!(BOOT_TIME + 1000, u64::from_be_bytes([data[55], data[56], data[57], data[58], data[59], data[60], data[61], data[62]]));
+        assert_eq!(0x123, u16_at(data, 71));
+        assert_eq!(FlowEndReason::EndOfFlow as u8, data[73]);
+    }
+
+    // ipv4 data set
+    let ipv4_set = ipv6_set + 4 + 74 + 2;
+    assert_eq!(256, u16_at(message, ipv4_set));
+    assert_eq!(4 + 50 + 2, usize::from(u16_at(message, ipv4_set + 2)));
+    assert_eq!(ipv4_set + 4 + 50 + 2, message.len());
+
+    // sequence number counts data records
+    assert_eq!(2, exporter.sequence_number());
+
+    // messages are split based on the maximum length
+    let mut exporter = FlowExporter::new(
+        FlowExporterConfig {
+            max_message_len: 16 + templates_len + 4 + 2*50 + 3,
+            ..Default::default()
+        },
+        BOOT_TIME
+    );
+    let records: Vec<FlowRecord> = (0..5).map(|i| record(i, false)).collect();
+    let messages = exporter.export(&records, BOOT_TIME);
+    assert_eq!(2, messages.len());
+    assert_eq!(16 + templates_len + 4 + 2*50, messages[0].len());
+    assert_eq!(messages[0].len(), usize::from(u16_at(&messages[0], 2)));
+    assert_eq!(0, u32_at(&messages[0], 8));
+    // without templates 3 records fit
+    assert_eq!(16 + 4 + 3*50 + 2, messages[1].len());
+    assert_eq!(2, u32_at(&messages[1], 8));
+    assert_eq!(5, exporter.sequence_number());
+
+    // at least one record is part of every message
+    let mut exporter = FlowExporter::new(
+        FlowExporterConfig {
+            max_message_len: 0,
+            ..Default::default()
+        },
+        BOOT_TIME
+    );
+    assert_eq!(5, exporter.export(&records, BOOT_TIME).len());
+}
@@ -0,0 +1,283 @@
+use super::*;
+
+use etherparse::rss::*;
+
+/// Serializes the packet described by the given builder.
+macro_rules! serialize {
+    ($builder:expr, $payload:expr) => {{
+        let mut result = Vec::new();
+        $builder.write(&mut result, $payload).unwrap();
+        result
+    }};
+}
+
+/// Extracts the key via `SlicedPacket` & `PacketHeaders` and checks
+/// that both result in the same key.
+fn keys(packet: &[u8]) -> Option<FlowKey> {
+    let sliced = FlowKey::from_sliced_packet(&SlicedPacket::from_ethernet(packet).unwrap());
+    let decoded = FlowKey::from_packet_headers(&PacketHeaders::from_ethernet_slice(packet).unwrap());
+    assert_eq!(sliced, decoded);
+    sliced
+}
+
+#[test]
+fn toeplitz_hash_verification_suite() {
+    // values from the Microsoft RSS verification suite
+    // (source address, source port, destination address, destination port, ipv4 hash, tcp hash)
+    let ipv4 = [
+        ([66,9,149,187], 2794, [161,142,100,80], 1766, 0x323e8fc2, 0x51ccc178),
+        ([199,92,111,2], 14230, [65,69,140,83], 4739, 0xd718262a, 0xc626b0ea),
+        ([24,19,198,95], 12898, [12,22,207,184], 38024, 0xd2d0a5de, 0x5c2b394a),
+        ([38,27,205,30], 48228, [209,142,163,6], 2217, 0x82989176, 0xafc7327f),
+        ([153,39,163,191], 44251, [202,188,127,2], 1303, 0x5d1809c5, 0x10e828a2),
+    ];
+    for (source, source_port, destination, destination_port, ip_hash, tcp_hash) in ipv4.iter() {
+        let mut key = FlowKey{
+            vlan_id: None,
+            addresses: FlowAddresses::Ipv4{ source: *source, destination: *destination },
+            protocol: ip_number::TCP,
+            source_port: *source_port,
+            destination_port: *destination_port,
+        };
+        assert_eq!(*tcp_hash, key.rss_hash(&MICROSOFT_KEY));
+        // udp uses the same input
+        key.protocol = ip_number::UDP;
+        assert_eq!(*tcp_hash, key.rss_hash(&MICROSOFT_KEY));
+        // protocols without ports only hash the addresses
+        key.protocol = ip_number::ICMP;
+        assert_eq!(8, key.rss_input().len());
+        assert_eq!(*ip_hash, key.rss_hash(&MICROSOFT_KEY));
+    }
+
+    let ipv6 = [
+        (
+            [0x3f,0xfe,0x25,0x01,0x02,0x00,0x1f,0xff,0,0,0,0,0,0,0,7], 2794,
+            [0x3f,0xfe,0x25,0x01,0x02,0x00,0,3,0,0,0,0,0,0,0,1], 1766,
+            0x2cc18cd5, 0x40207d3d
+        ),
+        (
+            [0x3f,0xfe,0x05,0x01,0,8,0,0,0x02,0x60,0x97,0xff,0xfe,0x40,0xef,0xab], 14230,
+            [0xff,0x02,0,0,0,0,0,0,0,0,0,0,0,0,0,1], 4739,
+            0x0f0c461c, 0xdde51bbf
+        ),
+        (
+            [0x3f,0xfe,0x19,0x00,0x45,0x45,0,3,0x02,0x00,0xf8,0xff,0xfe,0x21,0x67,0xcf], 44251,
+            [0xfe,0x80,0,0,0,0,0,0,0x02,0x00,0xf8,0xff,0xfe,0x21,0x67,0xcf], 38024,
+            0x4b61e985, 0x02d1feef
+        ),
+    ];
+    for (source, source_port, destination, destination_port, ip_hash, tcp_hash) in ipv6.iter() {
+        let mut key = FlowKey{
+            vlan_id: None,
+            addresses: FlowAddresses::Ipv6{ source: *source, destination: *destination },
+            protocol: ip_number::TCP,
+            source_port: *source_port,
+            destination_port: *destination_port,
+        };
+        assert_eq!(36, key.rss_input().len());
+        assert_eq!(*tcp_hash, key.rss_hash(&MICROSOFT_KEY));
+        key.protocol = ip_number::IPV6_ICMP;
+        assert_eq!(*ip_hash, key.rss_hash(&MICROSOFT_KEY));
+    }
+}
+
+#[test]
+fn toeplitz_hash_short_key() {
+    // missing key bits are treated as 0
+    assert_eq!(0, toeplitz_hash(&[], &[0xff, 0xff]));
+    assert_eq!(0x80000000, toeplitz_hash(&[0x80], &[0x80]));
+    assert_eq!(0x00000001, toeplitz_hash(&[0,0,0,0x01], &[0x80]));
+    assert_eq!(0x00004000, toeplitz_hash(&[0,0,0,0x80], &[0x01]));
+    assert_eq!(0, toeplitz_hash(&MICROSOFT_KEY, &[]));
+}
+
+#[test]
+fn symmetric_key() {
+    let key = FlowKey{
+        vlan_id: Some(12),
+        addresses: FlowAddresses::Ipv6{
+            source: [1,2,3,4,5,6,7,8,9,10,11,12,13,14,15,16],
+            destination: [0xfe,0x80,0,0,0,0,0,0,0,0,0,0,0,0,0,1],
+        },
+        protocol: ip_number::UDP,
+        source_port: 1234,
+        destination_port: 53,
+    };
+    assert_eq!(
+        key.rss_hash(&SYMMETRIC_KEY),
+        key.reversed().rss_hash(&SYMMETRIC_KEY)
+    );
+    // not the case for the default key
+    assert_ne!(
+        key.rss_hash(&MICROSOFT_KEY),
+        key.reversed().rss_hash(&MICROSOFT_KEY)
+    );
+}
+
+#[test]
+fn canonical() {
+    let key = FlowKey{
+        vlan_id: None,
+        addresses: FlowAddresses::Ipv4{ source: [10,0,0,2], destination: [10,0,0,1] },
+        protocol: ip_number::TCP,
+        source_port: 80,
+        destination_port: 1234,
+    };
+    assert!(!key.is_canonical());
+    assert!(key.reversed().is_canonical());
+    assert_eq!(key.reversed(), key.canonical());
+    assert_eq!(key.canonical(), key.reversed().canonical());
+    assert_eq!(key, key.reversed().reversed());
+
+    // same address, ports decide
+    let key = FlowKey{
+        addresses: FlowAddresses::Ipv4{ source: [10,0,0,1], destination: [10,0,0,1] },
+        ..key
+    };
+    assert!(key.is_canonical());
+    assert!(!key.reversed().is_canonical());
+    assert_eq!(80, key.reversed().canonical().source_port);
+
+    // equal source & destination
+    let key = FlowKey{ destination_port: 80, ..key };
+    assert!(key.is_canonical());
+    assert_eq!(key, key.canonical());
+
+    // without vlan
+    let key = FlowKey{ vlan_id: Some(1), ..key };
+    assert_eq!(None, key.without_vlan().vlan_id);
+    assert_eq!(FlowKey{ vlan_id: None, ..key }, key.without_vlan());
+}
+
+#[test]
+fn extraction() {
+    const MAC_A: [u8;6] = [2,3,4,5,6,7];
+    const MAC_B: [u8;6] = [8,9,10,11,12,13];
+    const IPV6_A: [u8;16] = [0xfe,0x80,0,0,0,0,0,0,0,0,0,0,0,0,0,1];
+    const IPV6_B: [u8;16] = [0xfe,0x80,0,0,0,0,0,0,0,0,0,0,0,0,0,2];
+
+    // udp with double vlan (outer vlan is used)
+    {
+        let packet = serialize!(
+            PacketBuilder::ethernet2(MAC_A, MAC_B)
+                .double_vlan(12, 34)
+                .ipv4([192,168,1,1], [192,168,1,2], 20)
+                .udp(1234, 53),
+            &[1,2,3]
+        );
+        assert_eq!(
+            Some(FlowKey{
+                vlan_id: Some(12),
+                addresses: FlowAddresses::Ipv4{ source: [192,168,1,1], destination: [192,168,1,2] },
+                protocol: ip_number::UDP,
+                source_port: 1234,
+                destination_port: 53,
+            }),
+            keys(&packet)
+        );
+    }
+
+    // tcp over ipv6 with single vlan
+    {
+        let packet = serialize!(
+            PacketBuilder::ethernet2(MAC_A, MAC_B)
+                .single_vlan(7)
+                .ipv6(IPV6_A, IPV6_B, 20)
+                .tcp(80, 4321, 1, 1000),
+            &[]
+        );
+        assert_eq!(
+            Some(FlowKey{
+                vlan_id: Some(7),
+                addresses: FlowAddresses::Ipv6{ source: IPV6_A, destination: IPV6_B },
+                protocol: ip_number::TCP,
+                source_port: 80,
+                destination_port: 4321,
+            }),
+            keys(&packet)
+        );
+    }
+
+    // icmp echo request & reply result in the same canonical key
+    {
+        let request = keys(&serialize!(
+            PacketBuilder::ethernet2(MAC_A, MAC_B)
+                .ipv4([192,168,1,1], [192,168,1,2], 20)
+                .icmpv4_echo_request(0x1234, 1),
+            &[]
+        )).unwrap();
+        let reply = keys(&serialize!(
+            PacketBuilder::ethernet2(MAC_B, MAC_A)
+                .ipv4([192,168,1,2], [192,168,1,1], 20)
+                .icmpv4_echo_reply(0x1234, 1),
+            &[]
+        )).unwrap();
+        assert_eq!(ip_number::ICMP, request.protocol);
+        assert_eq!((0x1234, 0x1234), (request.source_port, request.destination_port));
+        assert_eq!(request.reversed(), reply);
+        assert_eq!(request.canonical(), reply.canonical());
+    }
+
+    // icmpv6 echo & other icmpv6 messages
+    {
+        let request = keys(&serialize!(
+            PacketBuilder::ethernet2(MAC_A, MAC_B)
+                .ipv6(IPV6_A, IPV6_B, 20)
+                .icmpv6_echo_request(0x4321, 1),
+            &[]
+        )).unwrap();
+        assert_eq!(ip_number::IPV6_ICMP, request.protocol);
+        assert_eq!((0x4321, 0x4321), (request.source_port, request.destination_port));
+
+        let solicitation = keys(&serialize!(
+            PacketBuilder::ethernet2(MAC_A, MAC_B)
+                .ipv6(IPV6_A, IPV6_B, 20)
+                .icmpv6_neighbor_solicitation(IPV6_B),
+            &[]
+        )).unwrap();
+        assert_eq!(ip_number::IPV6_ICMP, solicitation.protocol);
+        assert_eq!((0, 0), (solicitation.source_port, solicitation.destination_port));
+    }
+
+    // fragmented packet (no ports available)
+    {
+        let mut header = Ipv4Header::new(0, 20, ip_number::UDP, [192,168,1,1], [192,168,1,2]);
+        header.more_fragments = true;
+        let packet = serialize!(
+            PacketBuilder::ethernet2(MAC_A, MAC_B)
+                .ip(IpHeader::Version4(header, Default::default()))
+                .udp(1234, 53),
+            &[]
+        );
+        assert_eq!(
+            Some(FlowKey{
+                vlan_id: None,
+                addresses: FlowAddresses::Ipv4{ source: [192,168,1,1], destination: [192,168,1,2] },
+                protocol: ip_number::UDP,
+                source_port: 0,
+                destination_port: 0,
+            }),
+            keys(&packet)
+        );
+    }
+
+    // no ip header
+    {
+        let packet = serialize!(
+            PacketBuilder::ethernet2(MAC_A, MAC_B)
+                .ipv4([192,168,1,1], [192,168,1,2], 20)
+                .udp(1234, 53),
+            &[]
+        );
+        let sliced = SlicedPacket::from_ethernet(&packet).unwrap();
+        assert_eq!(
+            None,
+            FlowKey::from_sliced_packet(&SlicedPacket{ ip: None, ..sliced })
+        );
+        let decoded = PacketHeaders::from_ethernet_slice(&packet).unwrap();
+        assert_eq!(
+            None,
+            FlowKey::from_packet_headers(&PacketHeaders{ ip: None, ..decoded })
+        );
+    }
+}
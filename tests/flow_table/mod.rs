@@ -0,0 +1,276 @@
+use super::*;
+
+const CLIENT: [u8;4] = [192,168,1,1];
+const SERVER: [u8;4] = [192,168,1,2];
+
+fn udp(from_client: bool, payload_len: usize) -> Vec<u8> {
+    let builder = if from_client {
+        PacketBuilder::ipv4(CLIENT, SERVER, 20).udp(1234, 53)
+    } else {
+        PacketBuilder::ipv4(SERVER, CLIENT, 20).udp(53, 1234)
+    };
+    let payload = vec![0u8;payload_len];
+    let mut result = Vec::with_capacity(builder.size(payload.len()));
+    builder.write(&mut result, &payload).unwrap();
+    result
+}
+
+fn udp_port(source_port: u16) -> Vec<u8> {
+    let builder = PacketBuilder::ipv4(CLIENT, SERVER, 20).udp(source_port, 53);
+    let mut result = Vec::with_capacity(builder.size(0));
+    builder.write(&mut result, &[]).unwrap();
+    result
+}
+
+fn tcp(builder: PacketBuilderStep<TcpHeader>) -> Vec<u8> {
+    let mut result = Vec::with_capacity(builder.size(0));
+    builder.write(&mut result, &[]).unwrap();
+    result
+}
+
+fn process(table: &mut FlowTable, packet: &[u8], now: u64) -> Vec<FlowRecord> {
+    table.process(&SlicedPacket::from_ip(packet).unwrap(), now)
+}
+
+fn key(source_port: u16) -> FlowKey {
+    FlowKey {
+        vlan_id: None,
+        addresses: FlowAddresses::Ipv4{ source: CLIENT, destination: SERVER },
+        protocol: ip_number::UDP,
+        source_port,
+        destination_port: 53,
+    }
+}
+
+#[test]
+fn config_default() {
+    let config: FlowTableConfig = Default::default();
+    assert_eq!(65536, config.max_flows);
+    assert_eq!(1_800_000, config.active_timeout);
+    assert_eq!(15_000, config.idle_timeout);
+
+    let table = FlowTable::new(config.clone());
+    assert_eq!(&config, table.config());
+    assert_eq!(0, table.flow_count());
+}
+
+#[test]
+fn accumulate() {
+    let mut table = FlowTable::new(Default::default());
+
+    // packets in both directions result in two unidirectional flows
+    assert!(process(&mut table, &udp(true, 10), 1000).is_empty());
+    assert!(process(&mut table, &udp(false, 100), 1100).is_empty());
+    assert!(process(&mut table, &udp(true, 20), 1200).is_empty());
+    assert_eq!(2, table.flow_count());
+
+    let flow = *table.flow(&key(1234)).unwrap();
+    assert_eq!(
+        FlowRecord {
+            key: key(1234),
+            packets: 2,
+            bytes: 2*28 + 10 + 20,
+            first: 1000,
+            last: 1200,
+            tcp_flags: 0,
+            tos: 0,
+            end_reason: FlowEndReason::ForcedEnd,
+        },
+        flow
+    );
+    let reply = *table.flow(&key(1234).reversed()).unwrap();
+    assert_eq!(1, reply.packets);
+    assert_eq!(128, reply.bytes);
+
+    // flush emits all flows
+    let flushed = table.flush();
+    assert_eq!(vec![flow, reply], flushed);
+    assert_eq!(0, table.flow_count());
+
+    // packets without ip header are ignored
+    {
+        let packet = udp(true, 0);
+        let sliced = SlicedPacket::from_ip(&packet).unwrap();
+        assert!(table.process(&SlicedPacket{ ip: None, ..sliced }, 0).is_empty());
+        assert_eq!(0, table.flow_count());
+    }
+}
+
+#[test]
+fn tos_and_ipv6() {
+    let mut table = FlowTable::new(Default::default());
+
+    // ipv4 type of service of the first packet
+    for (dscp, now) in [(10, 1), (20, 2)].iter() {
+        let mut header = Ipv4Header::new(0, 20, ip_number::UDP, CLIENT, SERVER);
+        header.differentiated_services_code_point = *dscp;
+        header.explicit_congestion_notification = 1;
+        let builder = PacketBuilder::ip(IpHeader::Version4(header, Default::default()))
+            .udp(1234, 53);
+        let mut packet = Vec::with_capacity(builder.size(0));
+        builder.write(&mut packet, &[]).unwrap();
+        process(&mut table, &packet, *now);
+    }
+    assert_eq!((10 << 2) | 1, table.flow(&key(1234)).unwrap().tos);
+
+    // ipv6 traffic class & length
+    let header = Ipv6Header {
+        traffic_class: 0x12,
+        flow_label: 0,
+        payload_length: 0,
+        next_header: ip_number::UDP,
+        hop_limit: 20,
+        source: [1;16],
+        destination: [2;16],
+    };
+    let builder = PacketBuilder::ip(IpHeader::Version6(header, Default::default()))
+        .udp(1234, 53);
+    let mut packet = Vec::with_capacity(builder.size(4));
+    builder.write(&mut packet, &[1,2,3,4]).unwrap();
+    process(&mut table, &packet, 3);
+
+    let key = FlowKey {
+        vlan_id: None,
+        addresses: FlowAddresses::Ipv6{ source: [1;16], destination: [2;16] },
+        protocol: ip_number::UDP,
+        source_port: 1234,
+        destination_port: 53,
+    };
+    let flow = table.flow(&key).unwrap();
+    assert_eq!(0x12, flow.tos);
+    assert_eq!(40 + 8 + 4, flow.bytes);
+}
+
+#[test]
+fn tcp_flags_and_end_of_flow() {
+    let mut table = FlowTable::new(Default::default());
+
+    // fin
+    assert!(process(&mut table, &tcp(PacketBuilder::ipv4(CLIENT, SERVER, 20).tcp(1234, 80, 0, 1000).syn()), 0).is_empty());
+    assert!(process(&mut table, &tcp(PacketBuilder::ipv4(CLIENT, SERVER, 20).tcp(1234, 80, 1, 1000).ack(1).psh()), 1).is_empty());
+    let emitted = process(&mut table, &tcp(PacketBuilder::ipv4(CLIENT, SERVER, 20).tcp(1234, 80, 1, 1000).ack(1).fin()), 2);
+    assert_eq!(1, emitted.len());
+    assert_eq!(3, emitted[0].packets);
+    // ack, psh, syn & fin
+    assert_eq!(0x1b, emitted[0].tcp_flags);
+    assert_eq!(ip_number::TCP, emitted[0].key.protocol);
+    assert_eq!(FlowEndReason::EndOfFlow, emitted[0].end_reason);
+    assert_eq!(0, table.flow_count());
+
+    // rst
+    let emitted = process(&mut table, &tcp(PacketBuilder::ipv4(SERVER, CLIENT, 20).tcp(80, 1234, 1, 1000).rst()), 3);
+    assert_eq!(1, emitted.len());
+    assert_eq!(0x04, emitted[0].tcp_flags);
+    assert_eq!(FlowEndReason::EndOfFlow, emitted[0].end_reason);
+    assert_eq!(0, table.flow_count());
+}
+
+#[test]
+fn timeouts() {
+    let mut table = FlowTable::new(FlowTableConfig {
+        max_flows: 10,
+        active_timeout: 1000,
+        idle_timeout: 100,
+    });
+
+    // idle timeout detected when processing a packet
+    process(&mut table, &udp(true, 0), 0);
+    let emitted = process(&mut table, &udp(true, 0), 100);
+    assert_eq!(1, emitted.len());
+    assert_eq!((0, 0, 1), (emitted[0].first, emitted[0].last, emitted[0].packets));
+    assert_eq!(FlowEndReason::IdleTimeout, emitted[0].end_reason);
+    assert_eq!(100, table.flow(&key(1234)).unwrap().first);
+
+    // active timeout detected when processing a packet
+    for now in (150..1100).step_by(50) {
+        assert!(process(&mut table, &udp(true, 0), now).is_empty());
+    }
+    let emitted = process(&mut table, &udp(true, 0), 1100);
+    assert_eq!(1, emitted.len());
+    assert_eq!((100, 1050, 20), (emitted[0].first, emitted[0].last, emitted[0].packets));
+    assert_eq!(FlowEndReason::ActiveTimeout, emitted[0].end_reason);
+    assert_eq!(1, table.flow(&key(1234)).unwrap().packets);
+    table.flush();
+
+    // expire
+    process(&mut table, &udp_port(1), 0);
+    process(&mut table, &udp_port(2), 50);
+    process(&mut table, &udp_port(3), 90);
+    for now in (100..1100).step_by(50) {
+        process(&mut table, &udp_port(3), now);
+    }
+    assert!(table.expire(50).is_empty());
+    let expired = table.expire(100);
+    assert_eq!(1, expired.len());
+    assert_eq!(key(1), expired[0].key);
+    assert_eq!(FlowEndReason::IdleTimeout, expired[0].end_reason);
+
+    let expired = table.expire(1100);
+    assert_eq!(2, expired.len());
+    assert_eq!(key(2), expired[0].key);
+    assert_eq!(FlowEndReason::IdleTimeout, expired[0].end_reason);
+    assert_eq!(key(3), expired[1].key);
+    assert_eq!(FlowEndReason::ActiveTimeout, expired[1].end_reason);
+    assert_eq!(0, table.flow_count());
+}
+
+#[test]
+fn max_flows() {
+    let mut table = FlowTable::new(FlowTableConfig {
+        max_flows: 2,
+        ..Default::default()
+    });
+    assert!(process(&mut table, &udp_port(1), 0).is_empty());
+    assert!(process(&mut table, &udp_port(2), 1).is_empty());
+    assert!(process(&mut table, &udp_port(1), 2).is_empty());
+
+    // least recently active flow gets evicted
+    let emitted = process(&mut table, &udp_port(3), 3);
+    assert_eq!(1, emitted.len());
+    assert_eq!(key(2), emitted[0].key);
+    assert_eq!(FlowEndReason::LackOfResources, emitted[0].end_reason);
+    assert_eq!(2, table.flow_count());
+    assert!(table.flow(&key(1)).is_some());
+    assert!(table.flow(&key(3)).is_some());
+
+    // flows are evicted in the order of their last activity
+    let mut table = FlowTable::new(FlowTableConfig {
+        max_flows: 100,
+        ..Default::default()
+    });
+    for port in 0..100 {
+        assert!(process(&mut table, &udp_port(port), u64::from(port)).is_empty());
+    }
+    for port in (0..100).step_by(2) {
+        assert!(process(&mut table, &udp_port(port), 100 + u64::from(port)).is_empty());
+    }
+    let expected = (1..100).step_by(2).chain((0..100).step_by(2));
+    for (i, port) in expected.enumerate() {
+        let emitted = process(&mut table, &udp_port(1000 + i as u16), 200 + i as u64);
+        assert_eq!(1, emitted.len());
+        assert_eq!(key(port), emitted[0].key);
+    }
+    assert_eq!(100, table.flow_count());
+
+    // expired & flushed flows are no longer evicted
+    assert_eq!(50, table.expire(200 + 49 + 15_000).len());
+    for port in 2000..2050 {
+        assert!(process(&mut table, &udp_port(port), 20_000).is_empty());
+    }
+    assert_eq!(key(1050), process(&mut table, &udp_port(3000), 20_000)[0].key);
+    assert_eq!(100, table.flush().len());
+    assert!(process(&mut table, &udp_port(3001), 20_000).is_empty());
+
+    // a maximum of 0 is treated like 1
+    let mut table = FlowTable::new(FlowTableConfig {
+        max_flows: 0,
+        ..Default::default()
+    });
+    assert!(process(&mut table, &udp_port(1), 0).is_empty());
+    assert_eq!(1, table.flow_count());
+    let emitted = process(&mut table, &udp_port(2), 1);
+    assert_eq!(1, emitted.len());
+    assert_eq!(key(1), emitted[0].key);
+    assert_eq!(1, table.flow_count());
+    assert!(table.flow(&key(2)).is_some());
+}
@@ -0,0 +1,531 @@
+use super::*;
+
+mod icmp_error_reply_builder {
+    use super::*;
+
+    fn ipv4_udp(
+        destination_mac: [u8;6],
+        source: [u8;4],
+        destination: [u8;4],
+        payload: &[u8]
+    ) -> Vec<u8> {
+        let builder = PacketBuilder::ethernet2([2,3,4,5,6,7], destination_mac)
+            .single_vlan(123)
+            .ipv4(source, destination, 20)
+            .udp(1234, 53);
+        let mut result = Vec::with_capacity(builder.size(payload.len()));
+        builder.write(&mut result, payload).unwrap();
+        result
+    }
+
+    fn ipv6_udp(destination: [u8;16], payload: &[u8]) -> Vec<u8> {
+        let builder = PacketBuilder::ipv6([1;16], destination, 20)
+            .udp(1234, 53);
+        let mut result = Vec::with_capacity(builder.size(payload.len()));
+        builder.write(&mut result, payload).unwrap();
+        result
+    }
+
+    #[test]
+    fn write_icmpv4() {
+        let icmp_type = Icmpv4Type::DestinationUnreachable(icmpv4::DestUnreachableHeader::Port);
+
+        // normal reply
+        {
+            let packet = ipv4_udp([8,9,10,11,12,13], [192,168,1,1], [192,168,1,2], &[1,2,3,4]);
+            let received = SlicedPacket::from_ethernet(&packet).unwrap();
+
+            let mut buffer = Vec::new();
+            IcmpErrorReplyBuilder::new().write_icmpv4(&mut buffer, &received, icmp_type.clone()).unwrap();
+
+            let reply = PacketHeaders::from_ethernet_slice(&buffer).unwrap();
+            assert_eq!(
+                Some(Ethernet2Header{
+                    source: [8,9,10,11,12,13],
+                    destination: [2,3,4,5,6,7],
+                    ether_type: ether_type::VLAN_TAGGED_FRAME,
+                }),
+                reply.link
+            );
+            assert_eq!(received.vlan.as_ref().map(|v| v.to_header()), reply.vlan);
+            let ip = match reply.ip.unwrap() {
+                IpHeader::Version4(ip, _) => ip,
+                _ => panic!("expected ipv4 header"),
+            };
+            assert_eq!([192,168,1,2], ip.source);
+            assert_eq!([192,168,1,1], ip.destination);
+            assert_eq!(64, ip.time_to_live);
+            assert_eq!(ip_number::ICMP, ip.protocol);
+            assert_eq!(ip.calc_header_checksum().unwrap(), ip.header_checksum);
+
+            // quoted ip packet
+            let quoted = &packet[Ethernet2Header::SERIALIZED_SIZE + SingleVlanHeader::SERIALIZED_SIZE..];
+            assert_eq!(quoted, reply.payload);
+            let icmp = reply.transport.unwrap().icmpv4().unwrap();
+            assert_eq!(icmp_type, icmp.icmp_type);
+            assert_eq!(icmp_type.calc_checksum(quoted), icmp.checksum);
+        }
+
+        // time to live, source address & buffer reuse
+        {
+            let packet = ipv4_udp([8,9,10,11,12,13], [192,168,1,1], [192,168,1,2], &[1,2,3,4]);
+            let received = SlicedPacket::from_ethernet(&packet).unwrap();
+            let builder = IcmpErrorReplyBuilder::default()
+                .time_to_live(10)
+                .ipv4_source([10,0,0,1]);
+
+            let mut buffer = Vec::with_capacity(1500);
+            for _ in 0..2 {
+                buffer.clear();
+                builder.write_icmpv4(&mut buffer, &received, icmp_type.clone()).unwrap();
+                let reply = SlicedPacket::from_ethernet(&buffer).unwrap();
+                match reply.ip.unwrap() {
+                    InternetSlice::Ipv4(ip, _) => {
+                        assert_eq!(10, ip.ttl());
+                        assert_eq!([10,0,0,1], ip.source());
+                    },
+                    _ => panic!("expected ipv4 header"),
+                }
+            }
+        }
+
+        // quoted data limited to 576 bytes (ip packet) & the total length
+        {
+            let mut packet = ipv4_udp([8,9,10,11,12,13], [192,168,1,1], [192,168,1,2], &[0xab;1000]);
+            let received = SlicedPacket::from_ethernet(&packet).unwrap();
+            let mut buffer = Vec::new();
+            IcmpErrorReplyBuilder::new().write_icmpv4(&mut buffer, &received, icmp_type.clone()).unwrap();
+            let reply = SlicedPacket::from_ethernet(&buffer).unwrap();
+            match reply.ip.unwrap() {
+                InternetSlice::Ipv4(ip, _) => assert_eq!(ICMPV4_ERROR_MAX_PACKET_LEN, usize::from(ip.total_len())),
+                _ => panic!("expected ipv4 header"),
+            }
+            match reply.transport.unwrap() {
+                TransportSlice::Icmpv4(icmp) => assert_eq!(ICMPV4_ERROR_MAX_PACKET_LEN - 20 - 8, icmp.payload().len()),
+                _ => panic!("expected icmpv4 slice"),
+            }
+
+            // ethernet padding is not quoted
+            packet.truncate(18 + 20 + 8 + 4);
+            packet[18 + 2..18 + 4].copy_from_slice(&(20u16 + 8 + 4).to_be_bytes());
+            packet.extend_from_slice(&[0;10]);
+            let received = SlicedPacket::from_ethernet(&packet).unwrap();
+            let mut buffer = Vec::new();
+            IcmpErrorReplyBuilder::new().write_icmpv4(&mut buffer, &received, icmp_type.clone()).unwrap();
+            let reply = SlicedPacket::from_ethernet(&buffer).unwrap();
+            match reply.transport.unwrap() {
+                TransportSlice::Icmpv4(icmp) => assert_eq!(&packet[18..18 + 20 + 8 + 4], icmp.payload()),
+                _ => panic!("expected icmpv4 slice"),
+            }
+        }
+    }
+
+    #[test]
+    fn write_icmpv6() {
+        let icmp_type = Icmpv6Type::DestinationUnreachable(icmpv6::DestUnreachableCode::Port);
+
+        // normal reply
+        {
+            let packet = ipv6_udp([2;16], &[1,2,3,4]);
+            let received = SlicedPacket::from_ip(&packet).unwrap();
+
+            let mut buffer = Vec::new();
+            IcmpErrorReplyBuilder::new().write_icmpv6(&mut buffer, &received, icmp_type).unwrap();
+
+            let reply = PacketHeaders::from_ip_slice(&buffer).unwrap();
+            assert_eq!(None, reply.link);
+            let ip = match reply.ip.unwrap() {
+                IpHeader::Version6(ip, _) => ip,
+                _ => panic!("expected ipv6 header"),
+            };
+            assert_eq!([2;16], ip.source);
+            assert_eq!([1;16], ip.destination);
+            assert_eq!(64, ip.hop_limit);
+            assert_eq!(ip_number::IPV6_ICMP, ip.next_header);
+            assert_eq!(&packet[..], reply.payload);
+            let icmp = reply.transport.unwrap().icmpv6().unwrap();
+            assert_eq!(icmp_type, icmp.icmp_type);
+            assert_eq!(
+                icmp_type.calc_checksum([2;16], [1;16], &packet).unwrap(),
+                icmp.checksum
+            );
+        }
+
+        // quoted data limited to 1280 bytes (ip packet) & source address
+        {
+            let packet = ipv6_udp([2;16], &[0xab;2000]);
+            let received = SlicedPacket::from_ip(&packet).unwrap();
+            let mut buffer = Vec::new();
+            IcmpErrorReplyBuilder::new()
+                .ipv6_source([3;16])
+                .write_icmpv6(&mut buffer, &received, icmp_type)
+                .unwrap();
+            assert_eq!(ICMPV6_ERROR_MAX_PACKET_LEN, buffer.len());
+            let reply = SlicedPacket::from_ip(&buffer).unwrap();
+            match reply.ip.unwrap() {
+                InternetSlice::Ipv6(ip, _) => assert_eq!([3;16], ip.source()),
+                _ => panic!("expected ipv6 header"),
+            }
+            match reply.transport.unwrap() {
+                TransportSlice::Icmpv6(icmp) => assert_eq!(&packet[..ICMPV6_ERROR_MAX_PACKET_LEN - 48], icmp.payload()),
+                _ => panic!("expected icmpv6 slice"),
+            }
+        }
+
+        // replies to multicast packets
+        {
+            let mut multicast = [0;16];
+            multicast[0] = 0xff;
+            multicast[15] = 1;
+            let packet = ipv6_udp(multicast, &[1,2,3,4]);
+            let received = SlicedPacket::from_ip(&packet).unwrap();
+            let mut buffer = Vec::new();
+
+            // allowed for "packet too big" & unrecognized options
+            IcmpErrorReplyBuilder::new()
+                .write_icmpv6(&mut buffer, &received, Icmpv6Type::PacketTooBig{ mtu: 1280 })
+                .unwrap();
+            IcmpErrorReplyBuilder::new()
+                .write_icmpv6(&mut buffer, &received, Icmpv6Type::ParameterProblem(icmpv6::ParameterProblemHeader{
+                    code: icmpv6::ParameterProblemCode::UnrecognizedIpv6Option,
+                    pointer: 40,
+                }))
+                .unwrap();
+
+            // forbidden otherwise
+            assert_matches!(
+                IcmpErrorReplyBuilder::new().write_icmpv6(&mut buffer, &received, icmp_type),
+                Err(IcmpErrorReplyError::MulticastOrBroadcast)
+            );
+        }
+    }
+
+    #[test]
+    fn errors() {
+        use IcmpErrorReplyError::*;
+        let v4_type = Icmpv4Type::TimeExceeded(icmpv4::TimeExceededCode::TtlExceededInTransit);
+        let v6_type = Icmpv6Type::TimeExceeded(icmpv6::TimeExceededCode::HopLimitExceeded);
+        let builder = IcmpErrorReplyBuilder::new();
+        let v4_packet = ipv4_udp([8,9,10,11,12,13], [192,168,1,1], [192,168,1,2], &[1,2,3,4]);
+        let v4_received = SlicedPacket::from_ethernet(&v4_packet).unwrap();
+        let v6_packet = ipv6_udp([2;16], &[1,2,3,4]);
+        let v6_received = SlicedPacket::from_ip(&v6_packet).unwrap();
+        let mut buffer = Vec::new();
+
+        // no ip header
+        {
+            let received = SlicedPacket{
+                link: None,
+                vlan: None,
+                ip: None,
+                transport: None,
+                payload: &[],
+            };
+            assert_matches!(builder.write_icmpv4(&mut buffer, &received, v4_type.clone()), Err(NoIpHeader));
+            assert_matches!(builder.write_icmpv6(&mut buffer, &received, v6_type), Err(NoIpHeader));
+        }
+
+        // version mismatch
+        assert_matches!(builder.write_icmpv4(&mut buffer, &v6_received, v4_type.clone()), Err(IpVersionMismatch));
+        assert_matches!(builder.write_icmpv6(&mut buffer, &v4_received, v6_type), Err(IpVersionMismatch));
+
+        // not an error type
+        assert_matches!(
+            builder.write_icmpv4(&mut buffer, &v4_received, Icmpv4Type::EchoReply(IcmpEchoHeader{ id: 1, seq: 2 })),
+            Err(NotAnErrorType)
+        );
+        assert_matches!(
+            builder.write_icmpv6(&mut buffer, &v6_received, Icmpv6Type::EchoReply(IcmpEchoHeader{ id: 1, seq: 2 })),
+            Err(NotAnErrorType)
+        );
+
+        // reply to an icmp error
+        {
+            let mut packet = Vec::new();
+            builder.write_icmpv4(&mut packet, &v4_received, v4_type.clone()).unwrap();
+            let received = SlicedPacket::from_ethernet(&packet).unwrap();
+            assert_matches!(builder.write_icmpv4(&mut buffer, &received, v4_type.clone()), Err(ReplyToIcmpError));
+
+            let mut packet = Vec::new();
+            builder.write_icmpv6(&mut packet, &v6_received, v6_type).unwrap();
+            let received = SlicedPacket::from_ip(&packet).unwrap();
+            assert_matches!(builder.write_icmpv6(&mut buffer, &received, v6_type), Err(ReplyToIcmpError));
+        }
+
+        // non first fragments
+        {
+            let mut header = Ipv4Header::new(8, 20, ip_number::UDP, [192,168,1,1], [192,168,1,2]);
+            header.fragments_offset = 1;
+            let mut packet = Vec::new();
+            header.write(&mut packet).unwrap();
+            packet.extend_from_slice(&[0;8]);
+            let received = SlicedPacket::from_ip(&packet).unwrap();
+            assert_matches!(builder.write_icmpv4(&mut buffer, &received, v4_type.clone()), Err(NonFirstFragment));
+
+            let builder_v6 = PacketBuilder::ipv6([1;16], [2;16], 20);
+            let mut packet = Vec::new();
+            let fragment = Ipv6FragmentHeader::new(ip_number::UDP, 1, false, 0);
+            builder_v6.write(&mut packet, ip_number::IPV6_FRAG, &fragment.to_bytes().unwrap()).unwrap();
+            let received = SlicedPacket::from_ip(&packet).unwrap();
+            assert_matches!(builder.write_icmpv6(&mut buffer, &received, v6_type), Err(NonFirstFragment));
+        }
+
+        // multicast & broadcast
+        {
+            let packets = [
+                ipv4_udp([8,9,10,11,12,13], [192,168,1,1], [224,0,0,1], &[]),
+                ipv4_udp([8,9,10,11,12,13], [192,168,1,1], [255,255,255,255], &[]),
+                ipv4_udp([8,9,10,11,12,13], [0,0,0,0], [192,168,1,2], &[]),
+                ipv4_udp([8,9,10,11,12,13], [224,0,0,1], [192,168,1,2], &[]),
+                ipv4_udp([0xff;6], [192,168,1,1], [192,168,1,2], &[]),
+            ];
+            for packet in &packets {
+                let received = SlicedPacket::from_ethernet(packet).unwrap();
+                assert_matches!(builder.write_icmpv4(&mut buffer, &received, v4_type.clone()), Err(MulticastOrBroadcast));
+            }
+
+            let builder_v6 = PacketBuilder::ipv6([0;16], [2;16], 20).udp(1, 2);
+            let mut packet = Vec::new();
+            builder_v6.write(&mut packet, &[]).unwrap();
+            let received = SlicedPacket::from_ip(&packet).unwrap();
+            assert_matches!(builder.write_icmpv6(&mut buffer, &received, v6_type), Err(MulticastOrBroadcast));
+        }
+
+        // write error
+        {
+            let mut slice = [0u8;10];
+            let mut writer = std::io::Cursor::new(&mut slice[..]);
+            assert_matches!(
+                builder.write_icmpv4(&mut writer, &v4_received, v4_type.clone()),
+                Err(WriteError(etherparse::WriteError::IoError(_)))
+            );
+        }
+    }
+
+    #[test]
+    fn error_display_source() {
+        use IcmpErrorReplyError::*;
+        use std::error::Error;
+
+        let values = [
+            (NoIpHeader, "IcmpErrorReplyError: The received packet has no IP header."),
+            (IpVersionMismatch, "IcmpErrorReplyError: The IP version of the received packet does not match the ICMP version of the error message (ICMPv4 is required for IPv4 & ICMPv6 for IPv6)."),
+            (NotAnErrorType, "IcmpErrorReplyError: The given ICMP type is not an error message type."),
+            (ReplyToIcmpError, "IcmpErrorReplyError: ICMP error messages must not be sent in reply to ICMP error messages."),
+            (NonFirstFragment, "IcmpErrorReplyError: ICMP error messages must not be sent in reply to fragments other then the first fragment."),
+            (MulticastOrBroadcast, "IcmpErrorReplyError: ICMP error messages must not be sent in reply to packets sent to a multicast or broadcast address or from an address not identifying a single host."),
+        ];
+        for (value, expected) in &values {
+            assert_eq!(*expected, &format!("{}", value));
+            assert!(value.source().is_none());
+            assert!(!format!("{:?}", value).is_empty());
+        }
+
+        let err = IcmpErrorReplyError::from(std::io::Error::new(std::io::ErrorKind::UnexpectedEof, "some error"));
+        assert_eq!("some error", &format!("{}", err));
+        assert!(err.source().is_some());
+    }
+}
+
+mod icmp_echo_reply {
+    use super::*;
+
+    fn ipv4_echo(source_mac: [u8;6], destination_mac: [u8;6], source: [u8;4], destination: [u8;4], time_to_live: u8, reply: bool, payload: &[u8]) -> Vec<u8> {
+        let builder = PacketBuilder::ethernet2(source_mac, destination_mac)
+            .single_vlan(123)
+            .ipv4(source, destination, time_to_live);
+        let builder = if reply {
+            builder.icmpv4_echo_reply(0x1234, 0x5678)
+        } else {
+            builder.icmpv4_echo_request(0x1234, 0x5678)
+        };
+        let mut result = Vec::with_capacity(builder.size(payload.len()));
+        builder.write(&mut result, payload).unwrap();
+        result
+    }
+
+    fn ipv6_echo(source: [u8;16], destination: [u8;16], hop_limit: u8, reply: bool, payload: &[u8]) -> Vec<u8> {
+        let builder = PacketBuilder::ipv6(source, destination, hop_limit);
+        let builder = if reply {
+            builder.icmpv6_echo_reply(0x1234, 0x5678)
+        } else {
+            builder.icmpv6_echo_request(0x1234, 0x5678)
+        };
+        let mut result = Vec::with_capacity(builder.size(payload.len()));
+        builder.write(&mut result, payload).unwrap();
+        result
+    }
+
+    #[test]
+    fn ipv4() {
+        let payload = [1,2,3,4,5,6,7,8,9];
+        let request = ipv4_echo([2,3,4,5,6,7], [8,9,10,11,12,13], [192,168,1,1], [192,168,1,2], 20, false, &payload);
+        let expected = ipv4_echo([8,9,10,11,12,13], [2,3,4,5,6,7], [192,168,1,2], [192,168,1,1], 64, true, &payload);
+
+        // write
+        {
+            let mut buffer = Vec::new();
+            write_icmp_echo_reply(&mut buffer, &SlicedPacket::from_ethernet(&request).unwrap(), 64).unwrap();
+            assert_eq!(expected, buffer);
+        }
+
+        // in place
+        {
+            let mut packet = request.clone();
+            icmp_echo_reply_in_place_ethernet(&mut packet, 64).unwrap();
+            assert_eq!(expected, packet);
+
+            let mut packet = request[18..].to_vec();
+            icmp_echo_reply_in_place_ip(&mut packet, 64).unwrap();
+            assert_eq!(&expected[18..], &packet[..]);
+        }
+    }
+
+    #[test]
+    fn ipv4_padded_ethernet_frame() {
+        // echo request padded to the minimum ethernet frame size
+        let payload = [1,2,3,4];
+        let mut request = ipv4_echo([2,3,4,5,6,7], [8,9,10,11,12,13], [192,168,1,1], [192,168,1,2], 20, false, &payload);
+        let expected = ipv4_echo([8,9,10,11,12,13], [2,3,4,5,6,7], [192,168,1,2], [192,168,1,1], 64, true, &payload);
+        assert!(request.len() < 60);
+        request.resize(60, 0xee);
+
+        // write (padding is not part of the reply)
+        {
+            let mut buffer = Vec::new();
+            write_icmp_echo_reply(&mut buffer, &SlicedPacket::from_ethernet(&request).unwrap(), 64).unwrap();
+            assert_eq!(expected, buffer);
+        }
+
+        // in place (padding is not part of the checksum & left unchanged)
+        {
+            let mut packet = request.clone();
+            icmp_echo_reply_in_place_ethernet(&mut packet, 64).unwrap();
+            assert_eq!(&expected[..], &packet[..expected.len()]);
+            assert_eq!(&request[expected.len()..], &packet[expected.len()..]);
+        }
+    }
+
+    #[test]
+    fn ipv6() {
+        let payload = [1,2,3,4,5,6,7,8,9];
+        let request = ipv6_echo([1;16], [2;16], 20, false, &payload);
+        let expected = ipv6_echo([2;16], [1;16], 30, true, &payload);
+
+        // write
+        {
+            let mut buffer = Vec::new();
+            write_icmp_echo_reply(&mut buffer, &SlicedPacket::from_ip(&request).unwrap(), 30).unwrap();
+            assert_eq!(expected, buffer);
+        }
+
+        // in place
+        {
+            let mut packet = request.clone();
+            icmp_echo_reply_in_place_ip(&mut packet, 30).unwrap();
+            assert_eq!(expected, packet);
+        }
+
+        // in place with ethernet header
+        {
+            let mut packet = Vec::new();
+            Ethernet2Header{
+                source: [2,3,4,5,6,7],
+                destination: [8,9,10,11,12,13],
+                ether_type: ether_type::IPV6,
+            }.write(&mut packet).unwrap();
+            packet.extend_from_slice(&request);
+            icmp_echo_reply_in_place_ethernet(&mut packet, 30).unwrap();
+
+            let reply = SlicedPacket::from_ethernet(&packet).unwrap();
+            match reply.link.unwrap() {
+                LinkSlice::Ethernet2(eth) => {
+                    assert_eq!([8,9,10,11,12,13], eth.source());
+                    assert_eq!([2,3,4,5,6,7], eth.destination());
+                }
+            }
+            assert_eq!(&expected[..], &packet[Ethernet2Header::SERIALIZED_SIZE..]);
+        }
+    }
+
+    #[test]
+    fn errors() {
+        use IcmpEchoReplyError::*;
+
+        // not an echo request
+        {
+            let reply = ipv4_echo([2,3,4,5,6,7], [8,9,10,11,12,13], [192,168,1,1], [192,168,1,2], 20, true, &[]);
+            let reply_v6 = ipv6_echo([1;16], [2;16], 20, true, &[]);
+            let udp = {
+                let builder = PacketBuilder::ipv4([192,168,1,1], [192,168,1,2], 20).udp(1, 2);
+                let mut result = Vec::new();
+                builder.write(&mut result, &[]).unwrap();
+                result
+            };
+            let mut buffer = Vec::new();
+            assert_matches!(
+                write_icmp_echo_reply(&mut buffer, &SlicedPacket::from_ethernet(&reply).unwrap(), 64),
+                Err(NotAnEchoRequest)
+            );
+            assert_matches!(
+                write_icmp_echo_reply(&mut buffer, &SlicedPacket::from_ip(&reply_v6).unwrap(), 64),
+                Err(NotAnEchoRequest)
+            );
+            assert_matches!(
+                write_icmp_echo_reply(&mut buffer, &SlicedPacket::from_ip(&udp).unwrap(), 64),
+                Err(NotAnEchoRequest)
+            );
+            assert!(buffer.is_empty());
+
+            let mut packet = reply.clone();
+            assert_matches!(icmp_echo_reply_in_place_ethernet(&mut packet, 64), Err(NotAnEchoRequest));
+            assert_eq!(reply, packet);
+            let mut packet = reply_v6.clone();
+            assert_matches!(icmp_echo_reply_in_place_ip(&mut packet, 64), Err(NotAnEchoRequest));
+            assert_eq!(reply_v6, packet);
+            let mut packet = udp.clone();
+            assert_matches!(icmp_echo_reply_in_place_ip(&mut packet, 64), Err(NotAnEchoRequest));
+        }
+
+        // read error
+        {
+            let mut packet = ipv6_echo([1;16], [2;16], 20, false, &[]);
+            assert_matches!(
+                icmp_echo_reply_in_place_ip(&mut packet[..39], 64),
+                Err(ReadError(etherparse::ReadError::UnexpectedEndOfSlice(_)))
+            );
+        }
+
+        // write error
+        {
+            let request = ipv6_echo([1;16], [2;16], 20, false, &[]);
+            let mut slice = [0u8;10];
+            let mut writer = std::io::Cursor::new(&mut slice[..]);
+            assert_matches!(
+                write_icmp_echo_reply(&mut writer, &SlicedPacket::from_ip(&request).unwrap(), 64),
+                Err(WriteError(etherparse::WriteError::IoError(_)))
+            );
+        }
+    }
+
+    #[test]
+    fn error_display_source() {
+        use IcmpEchoReplyError::*;
+        use std::error::Error;
+
+        assert_eq!(
+            "IcmpEchoReplyError: The packet is not an ICMPv4 echo request in an IPv4 packet or an ICMPv6 echo request in an IPv6 packet.",
+            &format!("{}", NotAnEchoRequest)
+        );
+        assert!(NotAnEchoRequest.source().is_none());
+        assert!(!format!("{:?}", NotAnEchoRequest).is_empty());
+
+        let err = IcmpEchoReplyError::from(etherparse::ReadError::UnexpectedEndOfSlice(1));
+        assert_eq!(format!("{}", etherparse::ReadError::UnexpectedEndOfSlice(1)), format!("{}", err));
+        assert!(err.source().is_some());
+
+        let err = IcmpEchoReplyError::from(std::io::Error::new(std::io::ErrorKind::UnexpectedEof, "some error"));
+        assert_eq!("some error", &format!("{}", err));
+        assert!(err.source().is_some());
+    }
+}
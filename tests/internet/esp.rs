@@ -0,0 +1,98 @@
+use super::super::*;
+
+use std::io::Cursor;
+
+proptest! {
+    #[test]
+    fn header_read_write(spi in any::<u32>(), sequence_number in any::<u32>()) {
+        let header = EspHeader{ spi, sequence_number };
+        assert_eq!(8, header.header_len());
+
+        // write & read
+        let mut buffer = Vec::with_capacity(8);
+        header.write(&mut buffer).unwrap();
+        assert_eq!(&buffer[..], &header.to_bytes()[..]);
+        assert_eq!(&buffer[..4], &spi.to_be_bytes());
+        assert_eq!(&buffer[4..], &sequence_number.to_be_bytes());
+        assert_eq!(header, EspHeader::read(&mut Cursor::new(&buffer)).unwrap());
+
+        // from_slice
+        buffer.push(1);
+        let (actual, rest) = EspHeader::from_slice(&buffer).unwrap();
+        assert_eq!(header, actual);
+        assert_eq!(&[1], rest);
+
+        // slice
+        let slice = EspHeaderSlice::from_slice(&buffer).unwrap();
+        assert_eq!(&buffer[..8], slice.slice());
+        assert_eq!(spi, slice.spi());
+        assert_eq!(sequence_number, slice.sequence_number());
+        assert_eq!(header, slice.to_header());
+
+        // too small
+        for len in 0..8 {
+            assert_matches!(
+                EspHeaderSlice::from_slice(&buffer[..len]),
+                Err(ReadError::UnexpectedEndOfSlice(8))
+            );
+            assert_matches!(
+                EspHeader::from_slice(&buffer[..len]),
+                Err(ReadError::UnexpectedEndOfSlice(8))
+            );
+            assert_matches!(
+                EspHeader::read(&mut Cursor::new(&buffer[..len])),
+                Err(ReadError::IoError(_))
+            );
+        }
+    }
+}
+
+#[test]
+fn trailer_from_null_encrypted() {
+    // payload, padding, pad length, next header, icv
+    let data = [
+        10, 11, 12, 13, 14,
+        1, 2, 3,
+        3, ip_number::UDP,
+        20, 21, 22, 23
+    ];
+
+    // ok
+    {
+        let (trailer, payload) = EspTrailerSlice::from_null_encrypted(&data, 4).unwrap();
+        assert_eq!(&[10, 11, 12, 13, 14], payload);
+        assert_eq!(&data[5..], trailer.slice());
+        assert_eq!(&[1, 2, 3], trailer.padding());
+        assert_eq!(3, trailer.pad_length());
+        assert_eq!(ip_number::UDP, trailer.next_header());
+        assert_eq!(&[20, 21, 22, 23], trailer.icv());
+    }
+
+    // no icv
+    {
+        let (trailer, payload) = EspTrailerSlice::from_null_encrypted(&data[..10], 0).unwrap();
+        assert_eq!(&[10, 11, 12, 13, 14], payload);
+        assert_eq!(ip_number::UDP, trailer.next_header());
+        assert!(trailer.icv().is_empty());
+    }
+
+    // only trailer
+    {
+        let (trailer, payload) = EspTrailerSlice::from_null_encrypted(&[0, ip_number::TCP], 0).unwrap();
+        assert!(payload.is_empty());
+        assert!(trailer.padding().is_empty());
+        assert_eq!(ip_number::TCP, trailer.next_header());
+    }
+
+    // too small for trailer & icv
+    assert_matches!(
+        EspTrailerSlice::from_null_encrypted(&data, 13),
+        Err(ReadError::UnexpectedEndOfSlice(15))
+    );
+
+    // pad length bigger then the data in front
+    assert_matches!(
+        EspTrailerSlice::from_null_encrypted(&[1, 2, 3, 4, ip_number::UDP], 0),
+        Err(ReadError::EspPadLengthTooLarge(4))
+    );
+}
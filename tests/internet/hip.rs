@@ -0,0 +1,167 @@
+use super::super::*;
+
+#[test]
+fn new_with_parameters() {
+    let parameters = [
+        HipParameter{ param_type: 0x0201, contents: &[1, 2, 3, 4] },
+        HipParameter{ param_type: 0x0202, contents: &[5;5] },
+        HipParameter{ param_type: 0x0203, contents: &[] },
+    ];
+    let header = HipHeader::new_with_parameters(
+        ip_number::IPV6_NO_NEXT_HEADER,
+        hip_packet_type::I1,
+        [1;16],
+        [2;16],
+        &parameters
+    ).unwrap();
+    assert_eq!(HipHeader::VERSION_2, header.version);
+    assert_eq!(40 + 8 + 16 + 8, header.header_len());
+    assert_eq!(
+        &[
+            0x02, 0x01, 0, 4, 1, 2, 3, 4,
+            0x02, 0x02, 0, 5, 5, 5, 5, 5, 5, 0, 0, 0, 0, 0, 0, 0,
+            0x02, 0x03, 0, 0, 0, 0, 0, 0,
+        ],
+        header.raw_parameters()
+    );
+    assert_eq!(
+        parameters.to_vec(),
+        header.parameters().map(|p| p.unwrap()).collect::<Vec<_>>()
+    );
+    assert!(parameters[0].is_critical());
+    assert!(!parameters[1].is_critical());
+
+    // write & read
+    let mut buffer = Vec::new();
+    header.write(&mut buffer).unwrap();
+    buffer.extend_from_slice(&[1, 2]);
+    assert_eq!(
+        &[ip_number::IPV6_NO_NEXT_HEADER, 8, hip_packet_type::I1, 0x21, 0, 0, 0, 0],
+        &buffer[..8]
+    );
+    let (decoded, rest) = HipHeader::from_slice(&buffer).unwrap();
+    assert_eq!(header, decoded);
+    assert_eq!(&[1, 2], rest);
+
+    let slice = HipHeaderSlice::from_slice(&buffer).unwrap();
+    assert_eq!(&buffer[..buffer.len() - 2], slice.slice());
+    assert_eq!(ip_number::IPV6_NO_NEXT_HEADER, slice.next_header());
+    assert_eq!(hip_packet_type::I1, slice.packet_type());
+    assert_eq!(HipHeader::VERSION_2, slice.version());
+    assert_eq!(0, slice.controls());
+    assert_eq!([1;16], slice.sender_hit());
+    assert_eq!([2;16], slice.receiver_hit());
+    assert_eq!(header.raw_parameters(), slice.raw_parameters());
+    assert_eq!(3, slice.parameters().count());
+    assert_eq!(header, slice.to_header());
+}
+
+#[test]
+fn value_errors() {
+    let mut header = HipHeader::new(0, 0x80, [0;16], [0;16]);
+    assert_eq!(
+        Err(ValueError::U8TooLarge{ value: 0x80, max: 0x7f, field: ErrorField::HipPacketType }),
+        header.to_bytes()
+    );
+    header.packet_type = hip_packet_type::R1;
+    header.version = 0x10;
+    assert_eq!(
+        Err(ValueError::U8TooLarge{ value: 0x10, max: 0xf, field: ErrorField::HipVersion }),
+        header.to_bytes()
+    );
+
+    assert_eq!(
+        Err(ValueError::Ipv6ExtensionPayloadLengthUnaligned(38 + 4)),
+        header.set_raw_parameters(&[0;4])
+    );
+    assert_eq!(
+        Err(ValueError::Ipv6ExtensionPayloadTooLarge(38 + HipHeader::MAX_PARAMETERS_LEN + 8)),
+        header.set_raw_parameters(&[0;HipHeader::MAX_PARAMETERS_LEN + 8])
+    );
+    let contents = [0;HipHeader::MAX_PARAMETERS_LEN];
+    assert_eq!(
+        Err(ValueError::Ipv6ExtensionPayloadTooLarge(38 + HipHeader::MAX_PARAMETERS_LEN + 8)),
+        header.set_parameters(&[HipParameter{ param_type: 1, contents: &contents }])
+    );
+}
+
+#[test]
+fn from_slice_errors() {
+    assert_matches!(
+        HipHeaderSlice::from_slice(&[0;7]),
+        Err(ReadError::UnexpectedEndOfSlice(8))
+    );
+    assert_matches!(
+        HipHeaderSlice::from_slice(&[0, 3, 0, 0, 0, 0, 0, 0]),
+        Err(ReadError::HipHeaderLengthTooSmall(3))
+    );
+    assert_matches!(
+        HipHeaderSlice::from_slice(&[0, 4, 0, 0, 0, 0, 0, 0]),
+        Err(ReadError::UnexpectedEndOfSlice(40))
+    );
+}
+
+#[test]
+fn parameters_iterator_errors() {
+    use HipParameterReadError::*;
+
+    // too short for the type & length
+    {
+        let mut iter = HipParametersIterator::from_slice(&[1, 2, 3]);
+        assert_eq!(
+            Some(Err(UnexpectedEndOfSlice{ param_type: 0x0102, expected_len: 4, actual_len: 3 })),
+            iter.next()
+        );
+        assert_eq!(None, iter.next());
+    }
+    // padding missing
+    {
+        let mut iter = HipParametersIterator::from_slice(&[1, 2, 0, 1, 9]);
+        assert_eq!(
+            Some(Err(UnexpectedEndOfSlice{ param_type: 0x0102, expected_len: 8, actual_len: 5 })),
+            iter.next()
+        );
+        assert_eq!(0, iter.rest().len());
+    }
+    assert_eq!(
+        "HipParameterReadError: Not enough memory left in slice to read parameter of type 1 (expected at least 8 bytes, only 5 bytes available).",
+        &format!("{}", UnexpectedEndOfSlice{ param_type: 1, expected_len: 8, actual_len: 5 })
+    );
+}
+
+#[test]
+fn checksum() {
+    let source = [1;16];
+    let destination = [2;16];
+    let mut header = HipHeader::new_with_parameters(
+        ip_number::IPV6_NO_NEXT_HEADER,
+        hip_packet_type::R2,
+        [3;16],
+        [4;16],
+        &[HipParameter{ param_type: 0x0201, contents: &[1, 2, 3] }]
+    ).unwrap();
+    header.checksum = header.calc_checksum(source, destination).unwrap();
+
+    let bytes = header.to_bytes().unwrap();
+    let sum = etherparse::checksum::Sum16BitWords::new()
+        .add_16bytes(source)
+        .add_16bytes(destination)
+        .add_4bytes((bytes.len() as u32).to_be_bytes())
+        .add_2bytes([0, ip_number::HIP])
+        .add_slice(&bytes)
+        .ones_complement();
+    assert_eq!(0, sum);
+
+    let slice = HipHeaderSlice::from_slice(&bytes).unwrap();
+    assert_eq!(header.checksum, slice.checksum());
+    assert_eq!(header.checksum, slice.calc_checksum(source, destination));
+}
+
+#[test]
+fn debug() {
+    let iter = HipParametersIterator::from_slice(&[0, 1, 0, 0, 0, 0, 0, 0, 9]);
+    assert_eq!(
+        "[HipParameter { param_type: 1, contents: [] }, Err(UnexpectedEndOfSlice { param_type: 2304, expected_len: 4, actual_len: 1 })]",
+        &format!("{:?}", iter)
+    );
+}
@@ -0,0 +1,287 @@
+use super::super::*;
+
+/// Simple MAC xor-ing all bytes together (enough to check the icv handling).
+struct XorMac {
+    state: [u8;4],
+    pos: usize,
+}
+
+impl XorMac {
+    fn new() -> XorMac {
+        XorMac{ state: [0;4], pos: 0 }
+    }
+}
+
+impl IpAuthenticationMac for XorMac {
+    fn update(&mut self, data: &[u8]) {
+        for b in data {
+            self.state[self.pos % 4] ^= b;
+            self.pos += 1;
+        }
+    }
+
+    fn finalize(self, icv: &mut [u8]) {
+        for (i, b) in icv.iter_mut().enumerate() {
+            *b = self.state[i % 4];
+        }
+    }
+}
+
+fn check_icv_input(packet: &[u8], auth_offset: usize, expected: &[u8]) {
+    let slice = IpAuthenticatedPacketSlice::from_ip_slice(packet).unwrap();
+    assert_eq!(auth_offset, slice.auth_offset());
+    assert_eq!(&packet[auth_offset..auth_offset + 16], slice.auth().slice());
+
+    // write_icv_input
+    let mut actual = Vec::new();
+    slice.write_icv_input(&mut actual).unwrap();
+    assert_eq!(expected, &actual[..]);
+
+    // calc & verify icv
+    let icv = slice.calc_icv(XorMac::new());
+    {
+        let mut mac = XorMac::new();
+        mac.update(expected);
+        let mut expected_icv = [0u8;4];
+        mac.finalize(&mut expected_icv);
+        assert_eq!(&expected_icv[..], &icv[..]);
+    }
+    assert_eq!(icv[..] == *slice.auth().raw_icv(), slice.verify_icv(XorMac::new()));
+
+    let mut modified = packet.to_vec();
+    modified[auth_offset + 12..auth_offset + 16].copy_from_slice(&icv);
+    let modified_slice = IpAuthenticatedPacketSlice::from_ip_slice(&modified).unwrap();
+    assert!(modified_slice.verify_icv(XorMac::new()));
+
+    // changing a muted field (time to live or hop limit) is not detected
+    let hop_limit_index = if 4 == packet[0] >> 4 { 8 } else { 7 };
+    modified[hop_limit_index] = modified[hop_limit_index].wrapping_sub(1);
+    assert!(IpAuthenticatedPacketSlice::from_ip_slice(&modified).unwrap().verify_icv(XorMac::new()));
+
+    // changing the payload is detected
+    let last = modified.len() - 1;
+    modified[last] ^= 0xff;
+    let last_ip = if 4 == packet[0] >> 4 {
+        usize::from(u16::from_be_bytes([packet[2], packet[3]]))
+    } else {
+        40 + usize::from(u16::from_be_bytes([packet[4], packet[5]]))
+    };
+    assert_eq!(
+        last >= last_ip,
+        IpAuthenticatedPacketSlice::from_ip_slice(&modified).unwrap().verify_icv(XorMac::new())
+    );
+}
+
+#[test]
+fn ipv4() {
+    let packet = [
+        // ipv4 header
+        0x48, 0xb8, 0, 52,
+        0x12, 0x34, 0x40, 0x00,
+        64, ip_number::AUTH, 0xab, 0xcd,
+        1, 2, 3, 4,
+        5, 6, 7, 8,
+        // options (nop, router alert & loose source route)
+        1,
+        148, 4, 0, 0,
+        131, 7, 4, 9, 9, 9, 9,
+        // authentication header
+        ip_number::UDP, 2, 0, 0,
+        0, 0, 0, 1,
+        0, 0, 0, 2,
+        0xaa, 0xaa, 0xaa, 0xaa,
+        // payload
+        1, 2, 3, 4,
+        // ethernet padding
+        0xff, 0xff, 0xff,
+    ];
+    let expected = [
+        // ipv4 header
+        0x48, 0, 0, 52,
+        0x12, 0x34, 0, 0,
+        0, ip_number::AUTH, 0, 0,
+        1, 2, 3, 4,
+        9, 9, 9, 9,
+        // options
+        1,
+        148, 4, 0, 0,
+        0, 0, 0, 0, 0, 0, 0,
+        // authentication header
+        ip_number::UDP, 2, 0, 0,
+        0, 0, 0, 1,
+        0, 0, 0, 2,
+        0, 0, 0, 0,
+        // payload
+        1, 2, 3, 4,
+    ];
+    check_icv_input(&packet, 32, &expected);
+
+    // completed source route (pointer behind the last address)
+    {
+        let mut packet = packet;
+        packet[27] = 8;
+        let mut expected = expected;
+        expected[16..20].copy_from_slice(&[5, 6, 7, 8]);
+        check_icv_input(&packet, 32, &expected);
+    }
+
+    // malformed option (length bigger then the options)
+    {
+        let mut packet = packet;
+        packet[26] = 8;
+        let mut expected = expected;
+        expected[16..20].copy_from_slice(&[5, 6, 7, 8]);
+        check_icv_input(&packet, 32, &expected);
+    }
+
+    // end of options list stops the option processing
+    {
+        let mut packet = packet;
+        packet[20] = 0;
+        let mut expected = expected;
+        expected[16..20].copy_from_slice(&[5, 6, 7, 8]);
+        expected[20] = 0;
+        expected[25..32].copy_from_slice(&packet[25..32]);
+        check_icv_input(&packet, 32, &expected);
+    }
+}
+
+#[test]
+fn ipv6() {
+    let mut packet = vec![
+        // ipv6 header
+        0x6a, 0xbc, 0xde, 0xf0,
+        0, 68, ip_number::IPV6_HOP_BY_HOP, 64,
+    ];
+    packet.extend_from_slice(&[1;16]);
+    packet.extend_from_slice(&[2;16]);
+    // hop by hop header (option with the change en route bit set & pad1s)
+    packet.extend_from_slice(&[ip_number::IPV6_ROUTE, 0, 0x3e, 2, 0xaa, 0xbb, 0, 0]);
+    // routing header type 0 with one segment left
+    packet.extend_from_slice(&[ip_number::AUTH, 4, 0, 1, 0, 0, 0, 0]);
+    packet.extend_from_slice(&[3;16]);
+    packet.extend_from_slice(&[4;16]);
+    // authentication header
+    packet.extend_from_slice(&[
+        ip_number::UDP, 2, 0, 0,
+        0, 0, 0, 1,
+        0, 0, 0, 2,
+        0xaa, 0xaa, 0xaa, 0xaa,
+    ]);
+    // payload
+    packet.extend_from_slice(&[1, 2, 3, 4]);
+
+    let mut expected = vec![
+        0x60, 0, 0, 0,
+        0, 68, ip_number::IPV6_HOP_BY_HOP, 0,
+    ];
+    expected.extend_from_slice(&[1;16]);
+    expected.extend_from_slice(&[4;16]);
+    expected.extend_from_slice(&[ip_number::IPV6_ROUTE, 0, 0x3e, 2, 0, 0, 0, 0]);
+    expected.extend_from_slice(&[ip_number::AUTH, 4, 0, 0, 0, 0, 0, 0]);
+    expected.extend_from_slice(&[3;16]);
+    expected.extend_from_slice(&[2;16]);
+    expected.extend_from_slice(&[
+        ip_number::UDP, 2, 0, 0,
+        0, 0, 0, 1,
+        0, 0, 0, 2,
+        0, 0, 0, 0,
+    ]);
+    expected.extend_from_slice(&[1, 2, 3, 4]);
+
+    check_icv_input(&packet, 88, &expected);
+
+    // routing header without segments left stays unchanged
+    {
+        let mut packet = packet.clone();
+        packet[51] = 0;
+        let mut expected = packet.clone();
+        expected[..8].copy_from_slice(&[0x60, 0, 0, 0, 0, 68, ip_number::IPV6_HOP_BY_HOP, 0]);
+        expected[44..46].copy_from_slice(&[0, 0]);
+        expected[100..104].copy_from_slice(&[0, 0, 0, 0]);
+        check_icv_input(&packet, 88, &expected);
+    }
+
+    // unknown routing type stays unchanged
+    {
+        let mut packet = packet.clone();
+        packet[50] = 4;
+        let mut expected = packet.clone();
+        expected[..8].copy_from_slice(&[0x60, 0, 0, 0, 0, 68, ip_number::IPV6_HOP_BY_HOP, 0]);
+        expected[44..46].copy_from_slice(&[0, 0]);
+        expected[100..104].copy_from_slice(&[0, 0, 0, 0]);
+        check_icv_input(&packet, 88, &expected);
+    }
+}
+
+#[test]
+fn from_ip_slice_errors() {
+    use ReadError::*;
+
+    // empty
+    assert_matches!(
+        IpAuthenticatedPacketSlice::from_ip_slice(&[]),
+        Err(UnexpectedEndOfSlice(1))
+    );
+
+    // unsupported version
+    assert_matches!(
+        IpAuthenticatedPacketSlice::from_ip_slice(&[0x50]),
+        Err(IpUnsupportedVersion(5))
+    );
+
+    let auth = IpAuthenticationHeader::new(ip_number::UDP, 1, 2, &[0;4]).unwrap();
+
+    // ipv4
+    {
+        let ip = Ipv4Header::new(auth.header_len() as u16, 20, ip_number::AUTH, [1,2,3,4], [5,6,7,8]);
+        let mut packet = Vec::new();
+        ip.write(&mut packet).unwrap();
+        auth.write(&mut packet).unwrap();
+        assert!(IpAuthenticatedPacketSlice::from_ip_slice(&packet).is_ok());
+
+        // total length bigger then the slice
+        assert_matches!(
+            IpAuthenticatedPacketSlice::from_ip_slice(&packet[..packet.len() - 1]),
+            Err(UnexpectedEndOfSlice(36))
+        );
+
+        // no authentication header
+        packet[9] = ip_number::UDP;
+        assert_matches!(
+            IpAuthenticatedPacketSlice::from_ip_slice(&packet),
+            Err(IpAuthenticationHeaderNotPresent)
+        );
+    }
+
+    // ipv6
+    {
+        let ip = Ipv6Header{
+            traffic_class: 0,
+            flow_label: 0,
+            payload_length: (8 + auth.header_len()) as u16,
+            next_header: ip_number::IPV6_FRAG,
+            hop_limit: 1,
+            source: [1;16],
+            destination: [2;16],
+        };
+        let mut packet = Vec::new();
+        ip.write(&mut packet).unwrap();
+        Ipv6FragmentHeader::new(ip_number::AUTH, 0, false, 1).write(&mut packet).unwrap();
+        auth.write(&mut packet).unwrap();
+        assert_eq!(48, IpAuthenticatedPacketSlice::from_ip_slice(&packet).unwrap().auth_offset());
+
+        // payload length bigger then the slice
+        assert_matches!(
+            IpAuthenticatedPacketSlice::from_ip_slice(&packet[..packet.len() - 1]),
+            Err(UnexpectedEndOfSlice(64))
+        );
+
+        // no authentication header
+        packet[40] = ip_number::UDP;
+        assert_matches!(
+            IpAuthenticatedPacketSlice::from_ip_slice(&packet),
+            Err(IpAuthenticationHeaderNotPresent)
+        );
+    }
+}
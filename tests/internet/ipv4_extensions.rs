@@ -363,6 +363,8 @@ mod slice {
                     slice,
                     Ipv4ExtensionsSlice{
                         auth: None,
+                        esp: None,
+                        esp_trailer: None,
                     }
                 );
                 assert_eq!(next, UDP);
@@ -386,6 +388,8 @@ mod slice {
                         auth: Some(
                             IpAuthenticationHeaderSlice::from_slice(&buffer).unwrap()
                         ),
+                        esp: None,
+                        esp_trailer: None,
                     }
                 );
                 assert_eq!(next, auth.next_header);
@@ -404,6 +408,78 @@ mod slice {
         }
     }
 
+    #[test]
+    fn from_slice_esp() {
+        // authentication header followed by esp
+        let auth = IpAuthenticationHeader::new(ENCAP_SEC, 0, 0, &[]).unwrap();
+        let mut data = Vec::new();
+        auth.write(&mut data).unwrap();
+        data.extend_from_slice(&EspHeader{ spi: 1, sequence_number: 2 }.to_bytes());
+        data.extend_from_slice(&[
+            1, 2, 3, // payload
+            1, // padding
+            1, UDP, // trailer
+            9, 9, 9, 9, // icv
+        ]);
+
+        // without decoding
+        {
+            let (slice, next, rest) = Ipv4ExtensionsSlice::from_slice(AUTH, &data).unwrap();
+            assert_eq!(auth, slice.auth.as_ref().unwrap().to_header());
+            assert_eq!(EspHeader{ spi: 1, sequence_number: 2 }, slice.esp.as_ref().unwrap().to_header());
+            assert!(slice.esp_trailer.is_none());
+            assert_eq!(ENCAP_SEC, next);
+            assert_eq!(&data[12..], rest);
+
+            // the owned extensions do not contain the esp header
+            let (header, next, rest) = Ipv4Extensions::from_slice(AUTH, &data).unwrap();
+            assert_eq!(Some(auth.clone()), header.auth);
+            assert_eq!(ENCAP_SEC, next);
+            assert_eq!(&data[12..], rest);
+        }
+
+        // null decoding
+        {
+            let (slice, next, rest) = Ipv4ExtensionsSlice::from_slice_esp_null(AUTH, &data, 4).unwrap();
+            assert!(slice.auth.is_some());
+            assert!(slice.esp.is_some());
+            let trailer = slice.esp_trailer.unwrap();
+            assert_eq!(&[1], trailer.padding());
+            assert_eq!(UDP, trailer.next_header());
+            assert_eq!(UDP, next);
+            assert_eq!(&[1, 2, 3], rest);
+        }
+
+        // esp directly after the ip header
+        {
+            let (slice, next, rest) = Ipv4ExtensionsSlice::from_slice_esp_null(ENCAP_SEC, &data[12..], 4).unwrap();
+            assert!(slice.auth.is_none());
+            assert!(!slice.is_empty());
+            assert_eq!(UDP, next);
+            assert_eq!(&[1, 2, 3], rest);
+        }
+
+        // errors (offsets are relative to the start of the extensions)
+        {
+            let (slice, next, rest) = Ipv4ExtensionsSlice::from_slice(AUTH, &data[..12 + 7]).unwrap();
+            assert!(slice.esp.is_none());
+            assert_eq!(ENCAP_SEC, next);
+            assert_eq!(&data[12..12 + 7], rest);
+        }
+        assert_matches!(
+            Ipv4ExtensionsSlice::from_slice_esp_null(AUTH, &data[..12 + 7], 0),
+            Err(UnexpectedEndOfSlice(20))
+        );
+        assert_matches!(
+            Ipv4ExtensionsSlice::from_slice_esp_null(AUTH, &data, 10),
+            Err(UnexpectedEndOfSlice(32))
+        );
+        assert_matches!(
+            Ipv4ExtensionsSlice::from_slice_esp_null(ENCAP_SEC, &[0, 0, 0, 0, 0, 0, 0, 0, 1, UDP], 0),
+            Err(ReadError::EspPadLengthTooLarge(1))
+        );
+    }
+
     proptest! {
         #[test]
         fn to_header(auth in ip_authentication_any()) {
@@ -411,6 +487,8 @@ mod slice {
             assert_eq!(
                 Ipv4ExtensionsSlice{
                     auth: None,
+                    esp: None,
+                    esp_trailer: None,
                 }.to_header(),
                 Ipv4Extensions{
                     auth: None,
@@ -428,6 +506,8 @@ mod slice {
                     auth: Some(
                         IpAuthenticationHeaderSlice::from_slice(&buffer).unwrap()
                     ),
+                    esp: None,
+                    esp_trailer: None,
                 };
                 assert_eq!(
                     slice.to_header(),
@@ -445,6 +525,8 @@ mod slice {
         assert!(
             Ipv4ExtensionsSlice{
                 auth: None,
+                esp: None,
+                esp_trailer: None,
             }.is_empty()
         );
 
@@ -460,6 +542,8 @@ mod slice {
                 false,
                 Ipv4ExtensionsSlice{
                     auth: Some(IpAuthenticationHeaderSlice::from_slice(&buffer).unwrap()),
+                    esp: None,
+                    esp_trailer: None,
                 }.is_empty()
             );
         }
@@ -470,11 +554,13 @@ mod slice {
         fn debug(auth in ip_authentication_any()) {
             // None
             assert_eq!(
-                &format!("Ipv4ExtensionsSlice {{ auth: {:?} }}", Option::<IpAuthenticationHeader>::None),
+                &format!("Ipv4ExtensionsSlice {{ auth: {:?}, esp: None, esp_trailer: None }}", Option::<IpAuthenticationHeader>::None),
                 &format!(
                     "{:?}",
                     Ipv4ExtensionsSlice {
                         auth: None,
+                        esp: None,
+                        esp_trailer: None,
                     }
                 )
             );
@@ -487,11 +573,13 @@ mod slice {
             };
             let auth_slice = IpAuthenticationHeaderSlice::from_slice(&buffer).unwrap();
             assert_eq!(
-                &format!("Ipv4ExtensionsSlice {{ auth: {:?} }}", Some(auth_slice.clone())),
+                &format!("Ipv4ExtensionsSlice {{ auth: {:?}, esp: None, esp_trailer: None }}", Some(auth_slice.clone())),
                 &format!(
                     "{:?}",
                     Ipv4ExtensionsSlice {
                         auth: Some(auth_slice.clone()),
+                        esp: None,
+                        esp_trailer: None,
                     }
                 )
             );
@@ -505,11 +593,15 @@ mod slice {
             {
                 let header = Ipv4ExtensionsSlice{
                     auth: None,
+                    esp: None,
+                    esp_trailer: None,
                 };
                 assert_eq!(
                     header.clone(),
                     Ipv4ExtensionsSlice{
                         auth: None,
+                        esp: None,
+                        esp_trailer: None,
                     }
                 );
             }
@@ -524,11 +616,15 @@ mod slice {
                 let auth_slice = IpAuthenticationHeaderSlice::from_slice(&buffer).unwrap();
                 let slice = Ipv4ExtensionsSlice {
                     auth: Some(auth_slice.clone()),
+                    esp: None,
+                    esp_trailer: None,
                 };
                 assert_eq!(
                     slice.clone(),
                     Ipv4ExtensionsSlice{
                         auth: Some(auth_slice.clone()),
+                        esp: None,
+                        esp_trailer: None,
                     }
                 );
             }
@@ -0,0 +1,262 @@
+use super::super::*;
+
+use arrayvec::ArrayVec;
+
+fn route_addresses(addresses: &[[u8;4]]) -> ArrayVec<[u8;4], { ipv4_option::MAX_ROUTE_ADDRESSES }> {
+    addresses.iter().copied().collect()
+}
+
+#[test]
+fn set_option_elements_and_iterate() {
+    use Ipv4OptionElement::*;
+    use Ipv4Timestamps::*;
+
+    let options = [
+        Noop,
+        RouterAlert(0),
+        RecordRoute{ pointer: 8, addresses: route_addresses(&[[1,2,3,4], [0;4]]) },
+        Timestamp{
+            pointer: 5,
+            overflow: 2,
+            timestamps: TimestampsOnly([0x1234_5678].iter().copied().collect())
+        },
+    ];
+
+    let mut header: Ipv4Header = Default::default();
+    header.set_option_elements(&options).unwrap();
+
+    // 1 + 4 + 11 + 8 = 24 bytes (already aligned)
+    assert_eq!(
+        &[
+            1,
+            148, 4, 0, 0,
+            7, 11, 8, 1, 2, 3, 4, 0, 0, 0, 0,
+            68, 8, 5, 0x20, 0x12, 0x34, 0x56, 0x78
+        ],
+        header.options()
+    );
+    assert_eq!(11, header.ihl());
+
+    // iterate header
+    assert_eq!(
+        &options[..],
+        &header.options_iterator().collect::<Result<Vec<_>,_>>().unwrap()[..]
+    );
+
+    // iterate slice
+    let mut buffer = Vec::new();
+    header.write(&mut buffer).unwrap();
+    let slice = Ipv4HeaderSlice::from_slice(&buffer).unwrap();
+    assert_eq!(
+        &options[..],
+        &slice.options_iterator().collect::<Result<Vec<_>,_>>().unwrap()[..]
+    );
+}
+
+#[test]
+fn set_option_elements_padding() {
+    use Ipv4OptionElement::*;
+    use Ipv4Timestamps::*;
+
+    let options = [
+        LooseSourceRoute{ pointer: 4, addresses: route_addresses(&[[1,2,3,4]]) },
+        StrictSourceRoute{ pointer: 4, addresses: route_addresses(&[[5,6,7,8]]) },
+        Security{
+            classification_level: 0x3d,
+            protection_authority: [0x81, 0x02].iter().copied().collect()
+        },
+        Timestamp{
+            pointer: 13,
+            overflow: 0,
+            timestamps: Prespecified([([9,9,9,9], 1)].iter().copied().collect())
+        },
+    ];
+
+    let mut header: Ipv4Header = Default::default();
+    header.set_option_elements(&options).unwrap();
+    assert_eq!(
+        &[
+            131, 7, 4, 1, 2, 3, 4,
+            137, 7, 4, 5, 6, 7, 8,
+            130, 5, 0x3d, 0x81, 0x02,
+            68, 12, 13, 3, 9, 9, 9, 9, 0, 0, 0, 1,
+            // padding
+            0,
+        ],
+        header.options()
+    );
+
+    // the iterator stops at the end of options list
+    let mut iter = header.options_iterator();
+    for option in options.iter() {
+        assert_eq!(option, &iter.next().unwrap().unwrap());
+    }
+    assert_eq!(None, iter.next());
+    assert!(iter.rest().is_empty());
+
+    // no options
+    header.set_option_elements(&[]).unwrap();
+    assert!(header.options().is_empty());
+    assert_eq!(None, header.options_iterator().next());
+
+    // with addresses flag
+    header.set_option_elements(&[
+        Timestamp{
+            pointer: 5,
+            overflow: 0xf,
+            timestamps: WithAddresses(ArrayVec::new())
+        }
+    ]).unwrap();
+    assert_eq!(&[68, 4, 5, 0xf1], header.options());
+}
+
+#[test]
+fn set_option_elements_errors() {
+    use Ipv4OptionElement::*;
+    use Ipv4Timestamps::*;
+    use ValueError::*;
+
+    let mut header: Ipv4Header = Default::default();
+    header.set_options(&[1,1,1,1]).unwrap();
+
+    // too big (39 + 1 + padding)
+    let route = RecordRoute{ pointer: 4, addresses: route_addresses(&[[0;4];9]) };
+    assert_eq!(
+        Err(Ipv4OptionsLengthBad(44)),
+        header.set_option_elements(&[route.clone(), Noop, Noop])
+    );
+    assert_eq!(&[1,1,1,1], header.options());
+
+    // ok with exactly 40 bytes
+    header.set_option_elements(&[route, Noop]).unwrap();
+    assert_eq!(40, header.options().len());
+
+    // overflow too big
+    assert_eq!(
+        Err(U8TooLarge{
+            value: 0x10,
+            max: 0xf,
+            field: ErrorField::Ipv4OptionTimestampOverflow
+        }),
+        header.set_option_elements(&[
+            Timestamp{ pointer: 5, overflow: 0x10, timestamps: TimestampsOnly(ArrayVec::new()) }
+        ])
+    );
+}
+
+#[test]
+fn iterator_errors() {
+    use Ipv4OptionReadError::*;
+
+    struct Test {
+        data: &'static [u8],
+        error: Ipv4OptionReadError,
+    }
+
+    let tests = [
+        // length missing
+        Test{ data: &[7], error: UnexpectedEndOfSlice{ option_id: 7, expected_len: 2, actual_len: 1 } },
+        // route length unaligned
+        Test{ data: &[131, 6, 4, 0, 0, 0], error: UnexpectedSize{ option_id: 131, size: 6 } },
+        // route too short
+        Test{ data: &[137, 2], error: UnexpectedSize{ option_id: 137, size: 2 } },
+        // route data missing
+        Test{ data: &[7, 7, 4, 0, 0, 0], error: UnexpectedEndOfSlice{ option_id: 7, expected_len: 7, actual_len: 6 } },
+        // unaligned timestamps with addresses
+        Test{ data: &[68, 8, 5, 1, 0, 0, 0, 0], error: UnexpectedSize{ option_id: 68, size: 8 } },
+        // unknown timestamp flag
+        Test{ data: &[68, 4, 5, 2], error: UnknownTimestampFlag(2) },
+        // router alert bad length
+        Test{ data: &[148, 5, 0, 0, 0], error: UnexpectedSize{ option_id: 148, size: 5 } },
+        // security too short
+        Test{ data: &[130, 2], error: UnexpectedSize{ option_id: 130, size: 2 } },
+        // unknown
+        Test{ data: &[25, 4, 0, 0], error: UnknownId(25) },
+    ];
+
+    for test in tests.iter() {
+        let mut iter = Ipv4OptionsIterator::from_slice(test.data);
+        assert_eq!(Some(Err(test.error.clone())), iter.next());
+        // iterator is moved to the end after an error
+        assert!(iter.rest().is_empty());
+        assert_eq!(None, iter.next());
+    }
+}
+
+#[test]
+fn iterator_debug() {
+    let iter = Ipv4OptionsIterator::from_slice(&[1, 148, 4, 0, 0, 25]);
+    assert_eq!(
+        format!(
+            "[{:?}, {:?}, {:?}]",
+            Ipv4OptionElement::Noop,
+            Ipv4OptionElement::RouterAlert(0),
+            Result::<(), Ipv4OptionReadError>::Err(Ipv4OptionReadError::UnknownId(25))
+        ),
+        format!("{:?}", iter)
+    );
+}
+
+#[test]
+fn read_error_display() {
+    use Ipv4OptionReadError::*;
+    use std::error::Error;
+
+    assert_eq!(
+        "Ipv4OptionReadError: Not enough memory left in slice to read option of type 1 (expected at least 2 bytes, only 3 bytes available).",
+        &format!("{}", UnexpectedEndOfSlice{ option_id: 1, expected_len: 2, actual_len: 3 })
+    );
+    assert_eq!(
+        "Ipv4OptionReadError: Length value of the option of type 1 had unexpected value 2.",
+        &format!("{}", UnexpectedSize{ option_id: 1, size: 2 })
+    );
+    assert_eq!(
+        "Ipv4OptionReadError: Unknown timestamp option flag value 2.",
+        &format!("{}", UnknownTimestampFlag(2))
+    );
+    assert_eq!(
+        "Ipv4OptionReadError: Unknown ipv4 option type value 3.",
+        &format!("{}", UnknownId(3))
+    );
+    assert!(UnknownId(3).source().is_none());
+}
+
+#[test]
+fn set_router_alert() {
+    use Ipv4OptionElement::*;
+
+    // no options
+    {
+        let mut header: Ipv4Header = Default::default();
+        assert_eq!(None, header.router_alert());
+        header.set_router_alert(0).unwrap();
+        assert_eq!(&[148, 4, 0, 0], header.options());
+        assert_eq!(Some(0), header.router_alert());
+
+        // overwrite the existing value
+        header.set_router_alert(0x1234).unwrap();
+        assert_eq!(&[148, 4, 0x12, 0x34], header.options());
+    }
+    // prepended in front of existing options
+    {
+        let mut header: Ipv4Header = Default::default();
+        header.set_option_elements(&[Noop, RouterAlert(1)]).unwrap();
+        header.set_router_alert(2).unwrap();
+        assert_eq!(&[1, 148, 4, 0, 2, 0, 0, 0], header.options());
+
+        let mut header: Ipv4Header = Default::default();
+        header.set_option_elements(&[RecordRoute{ pointer: 4, addresses: route_addresses(&[[0;4]]) }]).unwrap();
+        header.set_router_alert(3).unwrap();
+        assert_eq!(
+            vec![RouterAlert(3), RecordRoute{ pointer: 4, addresses: route_addresses(&[[0;4]]) }],
+            header.options_iterator().collect::<Result<Vec<_>,_>>().unwrap()
+        );
+    }
+    // no space left
+    {
+        let mut header: Ipv4Header = Default::default();
+        header.set_options(&[1;40]).unwrap();
+        assert_eq!(Err(ValueError::Ipv4OptionsLengthBad(44)), header.set_router_alert(0));
+        assert_eq!(&[1;40], header.options());
+    }
+}
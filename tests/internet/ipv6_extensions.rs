@@ -12,6 +12,15 @@ const EXTESION_KNOWN_IP_NUMBERS : [u8;5] = [
     IPV6_ROUTE,
 ];
 
+// IP numbers of the mobility, HIP & Shim6 headers (not generated by
+// `ExtensionTestPayload`, but decoded by `Ipv6Extensions` & skipped by
+// `Ipv6ExtensionsSlice`).
+const SLICE_SKIPPED_IP_NUMBERS : [u8;3] = [
+    MOBILITY,
+    HIP,
+    SHIM6,
+];
+
 /// Helper struct that generates test data with dummy
 /// extension header data.
 struct ExtensionTestPayload {
@@ -454,7 +463,7 @@ pub mod header {
             header_size in any::<u8>(),
             post_header in any::<u8>()
                 .prop_filter("Must be a non ipv6 header relevant ip number".to_owned(),
-                    |v| !EXTESION_KNOWN_IP_NUMBERS.iter().any(|&x| v == &x)
+                    |v| !EXTESION_KNOWN_IP_NUMBERS.iter().chain(SLICE_SKIPPED_IP_NUMBERS.iter()).any(|&x| v == &x)
                 )
         ) {
             // no extension headers filled
@@ -530,7 +539,7 @@ pub mod header {
             header_size in any::<u8>(),
             post_header in any::<u8>()
                 .prop_filter("Must be a non ipv6 header relevant ip number".to_owned(),
-                    |v| !EXTESION_KNOWN_IP_NUMBERS.iter().any(|&x| v == &x)
+                    |v| !EXTESION_KNOWN_IP_NUMBERS.iter().chain(SLICE_SKIPPED_IP_NUMBERS.iter()).any(|&x| v == &x)
                 )
         ) {
             // no extension headers filled
@@ -610,7 +619,7 @@ pub mod header {
             header_size in any::<u8>(),
             post_header in any::<u8>()
                 .prop_filter("Must be a non ipv6 header relevant ip number".to_owned(),
-                    |v| !EXTESION_KNOWN_IP_NUMBERS.iter().any(|&x| v == &x)
+                    |v| !EXTESION_KNOWN_IP_NUMBERS.iter().chain(SLICE_SKIPPED_IP_NUMBERS.iter()).any(|&x| v == &x)
                 )
         ) {
             // no extension headers filled
@@ -752,6 +761,9 @@ pub mod header {
                     ),
                     fragment: Some(fragment.clone()),
                     auth: Some(auth.clone()),
+                    mobility: None,
+                    hip: None,
+                    shim6: None,
                 };
                 assert_eq!(
                     exts.header_len(),
@@ -779,6 +791,9 @@ pub mod header {
                     ),
                     fragment: Some(fragment.clone()),
                     auth: Some(auth.clone()),
+                    mobility: None,
+                    hip: None,
+                    shim6: None,
                 };
                 assert_eq!(
                     exts.header_len(),
@@ -805,7 +820,7 @@ pub mod header {
             final_destination_options in ipv6_raw_extension_any(),
             post_header in any::<u8>()
                 .prop_filter("Must be a non ipv6 header relevant ip number".to_owned(),
-                    |v| !EXTESION_KNOWN_IP_NUMBERS.iter().any(|&x| v == &x)
+                    |v| !EXTESION_KNOWN_IP_NUMBERS.iter().chain(SLICE_SKIPPED_IP_NUMBERS.iter()).any(|&x| v == &x)
                 ),
         ) {
             // none filled
@@ -832,6 +847,9 @@ pub mod header {
                     ),
                     fragment: Some(fragment.clone()),
                     auth: Some(auth.clone()),
+                    mobility: None,
+                    hip: None,
+                    shim6: None,
                 };
                 assert_eq!(IPV6_HOP_BY_HOP, exts.set_next_headers(post_header));
 
@@ -851,7 +869,7 @@ pub mod header {
             header_size in any::<u8>(),
             post_header in any::<u8>()
                 .prop_filter("Must be a non ipv6 header relevant ip number".to_owned(),
-                    |v| !EXTESION_KNOWN_IP_NUMBERS.iter().any(|&x| v == &x)
+                    |v| !EXTESION_KNOWN_IP_NUMBERS.iter().chain(SLICE_SKIPPED_IP_NUMBERS.iter()).any(|&x| v == &x)
                 ),)
         {
             // test empty
@@ -938,6 +956,9 @@ pub mod header {
                 routing: None,
                 fragment: None,
                 auth: None,
+                mobility: None,
+                hip: None,
+                shim6: None,
             }.is_fragmenting_payload()
         );
 
@@ -950,6 +971,9 @@ pub mod header {
                 routing: None,
                 fragment: Some(Ipv6FragmentHeader::new(ip_number::UDP, 0, false, 0)),
                 auth: None,
+                mobility: None,
+                hip: None,
+                shim6: None,
             }.is_fragmenting_payload()
         );
 
@@ -961,6 +985,9 @@ pub mod header {
                 routing: None,
                 fragment: Some(Ipv6FragmentHeader::new(ip_number::UDP, 0, true, 0)),
                 auth: None,
+                mobility: None,
+                hip: None,
+                shim6: None,
             }.is_fragmenting_payload()
         );
     }
@@ -975,6 +1002,9 @@ pub mod header {
                 routing: None,
                 fragment: None,
                 auth: None,
+                mobility: None,
+                hip: None,
+                shim6: None,
             }.is_empty()
         );
 
@@ -989,6 +1019,9 @@ pub mod header {
                 routing: None,
                 fragment: None,
                 auth: None,
+                mobility: None,
+                hip: None,
+                shim6: None,
             }.is_empty()
         );
 
@@ -1003,6 +1036,9 @@ pub mod header {
                 routing: None,
                 fragment: None,
                 auth: None,
+                mobility: None,
+                hip: None,
+                shim6: None,
             }.is_empty()
         );
 
@@ -1020,6 +1056,9 @@ pub mod header {
                 ),
                 fragment: None,
                 auth: None,
+                mobility: None,
+                hip: None,
+                shim6: None,
             }.is_empty()
         );
 
@@ -1032,6 +1071,9 @@ pub mod header {
                 routing: None,
                 fragment: Some(Ipv6FragmentHeader::new(ip_number::UDP, 0, true, 0)),
                 auth: None,
+                mobility: None,
+                hip: None,
+                shim6: None,
             }.is_empty()
         );
 
@@ -1044,6 +1086,9 @@ pub mod header {
                 routing: None,
                 fragment: None,
                 auth: Some(IpAuthenticationHeader::new(ip_number::UDP, 0, 0, &[]).unwrap()),
+                mobility: None,
+                hip: None,
+                shim6: None,
             }.is_empty()
         );
     }
@@ -1053,12 +1098,15 @@ pub mod header {
         let a : Ipv6Extensions = Default::default();
         assert_eq!(
             &format!(
-                "Ipv6Extensions {{ hop_by_hop_options: {:?}, destination_options: {:?}, routing: {:?}, fragment: {:?}, auth: {:?} }}",
+                "Ipv6Extensions {{ hop_by_hop_options: {:?}, destination_options: {:?}, routing: {:?}, fragment: {:?}, auth: {:?}, mobility: {:?}, hip: {:?}, shim6: {:?} }}",
                 a.hop_by_hop_options,
                 a.destination_options,
                 a.routing,
                 a.fragment,
                 a.auth,
+                a.mobility,
+                a.hip,
+                a.shim6,
             ),
             &format!("{:?}", a)
         );
@@ -1078,6 +1126,103 @@ pub mod header {
         assert_eq!(a.routing, None);
         assert_eq!(a.fragment, None);
         assert_eq!(a.auth, None);
+        assert_eq!(a.mobility, None);
+        assert_eq!(a.hip, None);
+        assert_eq!(a.shim6, None);
+    }
+
+    #[test]
+    fn mobility_hip_shim6() {
+        let mut exts = Ipv6Extensions{
+            destination_options: Some(Ipv6RawExtensionHeader::new_raw(0, &[1;6]).unwrap()),
+            fragment: Some(Ipv6FragmentHeader::new(0, 0, false, 1)),
+            shim6: Some(Shim6Header::Payload(Shim6PayloadHeader{ next_header: 0, receiver_context_tag: 1 })),
+            hip: Some(HipHeader::new(0, hip_packet_type::UPDATE, [2;16], [3;16])),
+            mobility: Some(Ipv6MobilityHeader::new(
+                0,
+                &Ipv6MobilityMessage::BindingRefreshRequest{ options: &[] }
+            ).unwrap()),
+            ..Default::default()
+        };
+        assert!(!exts.is_empty());
+        assert_eq!(8 + 8 + 8 + 40 + 8, exts.header_len());
+
+        // shim6 is placed before the fragment header, hip & mobility last
+        assert_eq!(IPV6_DEST_OPTIONS, exts.set_next_headers(IPV6_NO_NEXT_HEADER));
+        assert_eq!(SHIM6, exts.destination_options.as_ref().unwrap().next_header);
+        assert_eq!(IPV6_FRAG, exts.shim6.as_ref().unwrap().next_header());
+        assert_eq!(HIP, exts.fragment.as_ref().unwrap().next_header);
+        assert_eq!(MOBILITY, exts.hip.as_ref().unwrap().next_header);
+        assert_eq!(IPV6_NO_NEXT_HEADER, exts.mobility.as_ref().unwrap().payload_proto);
+        assert_eq!(Ok(IPV6_NO_NEXT_HEADER), exts.next_header(IPV6_DEST_OPTIONS));
+
+        // write, from_slice & read
+        let mut buffer = Vec::new();
+        exts.write(&mut buffer, IPV6_DEST_OPTIONS).unwrap();
+        assert_eq!(exts.header_len(), buffer.len());
+        buffer.extend_from_slice(&[1,2,3,4]);
+        {
+            let (decoded, next_header, rest) = Ipv6Extensions::from_slice(IPV6_DEST_OPTIONS, &buffer).unwrap();
+            assert_eq!(exts, decoded);
+            assert_eq!(IPV6_NO_NEXT_HEADER, next_header);
+            assert_eq!(&[1,2,3,4], rest);
+        }
+        {
+            let mut cursor = Cursor::new(&buffer);
+            let (decoded, next_header) = Ipv6Extensions::read(&mut cursor, IPV6_DEST_OPTIONS).unwrap();
+            assert_eq!(exts, decoded);
+            assert_eq!(IPV6_NO_NEXT_HEADER, next_header);
+            assert_eq!(exts.header_len() as u64, cursor.position());
+        }
+        // unexpected end
+        for len in &[8 + 7, 8 + 8 + 8 + 39, exts.header_len() - 1] {
+            assert_matches!(
+                Ipv6Extensions::from_slice(IPV6_DEST_OPTIONS, &buffer[..*len]),
+                Err(ReadError::UnexpectedEndOfSlice(_))
+            );
+            assert_matches!(
+                Ipv6Extensions::read(&mut Cursor::new(&buffer[..*len]), IPV6_DEST_OPTIONS),
+                Err(ReadError::IoError(_))
+            );
+        }
+
+        // a second header of the same type stops the parsing
+        {
+            let mut data = Vec::new();
+            let mut shim6 = Shim6Header::Payload(Shim6PayloadHeader{ next_header: SHIM6, receiver_context_tag: 1 });
+            shim6.write(&mut data).unwrap();
+            shim6.set_next_header(UDP);
+            shim6.write(&mut data).unwrap();
+            let (decoded, next_header, rest) = Ipv6Extensions::from_slice(SHIM6, &data).unwrap();
+            assert!(decoded.shim6.is_some());
+            assert_eq!(SHIM6, next_header);
+            assert_eq!(&data[8..], rest);
+        }
+
+        // not referenced headers
+        {
+            let mut exts = exts.clone();
+            exts.fragment.as_mut().unwrap().next_header = IPV6_NO_NEXT_HEADER;
+            assert_eq!(
+                Err(ValueError::Ipv6ExtensionNotReferenced(IpNumber::MobilityHeader)),
+                exts.next_header(IPV6_DEST_OPTIONS)
+            );
+            assert_matches!(
+                exts.write(&mut Vec::new(), IPV6_DEST_OPTIONS),
+                Err(WriteError::ValueError(ValueError::Ipv6ExtensionNotReferenced(IpNumber::MobilityHeader)))
+            );
+            exts.mobility = None;
+            assert_eq!(
+                Err(ValueError::Ipv6ExtensionNotReferenced(IpNumber::Hip)),
+                exts.next_header(IPV6_DEST_OPTIONS)
+            );
+            exts.hip = None;
+            exts.destination_options.as_mut().unwrap().next_header = IPV6_FRAG;
+            assert_eq!(
+                Err(ValueError::Ipv6ExtensionNotReferenced(IpNumber::Shim6)),
+                exts.next_header(IPV6_DEST_OPTIONS)
+            );
+        }
     }
 }
 
@@ -1090,7 +1235,7 @@ pub mod slice {
             header_size in any::<u8>(),
             post_header in any::<u8>()
                 .prop_filter("Must be a non ipv6 header relevant ip number".to_owned(),
-                    |v| !EXTESION_KNOWN_IP_NUMBERS.iter().any(|&x| v == &x)
+                    |v| !EXTESION_KNOWN_IP_NUMBERS.iter().chain(SLICE_SKIPPED_IP_NUMBERS.iter()).any(|&x| v == &x)
                 )
         ) {
             // no extension headers filled
@@ -1185,6 +1330,9 @@ pub mod slice {
                     ),
                     fragment: None,
                     auth: Some(auth),
+                    mobility: None,
+                    hip: None,
+                    shim6: None,
                 };
                 let first_ip_number = exts.set_next_headers(UDP);
 
@@ -1254,11 +1402,82 @@ pub mod slice {
         }
     }
 
+    #[test]
+    fn from_slice_esp() {
+        // fragment header followed by esp
+        let mut data = Vec::new();
+        data.extend_from_slice(&Ipv6FragmentHeader::new(ENCAP_SEC, 0, false, 0).to_bytes().unwrap());
+        data.extend_from_slice(&EspHeader{ spi: 1, sequence_number: 2 }.to_bytes());
+        data.extend_from_slice(&[
+            1, 2, 3, 4, // payload
+            1, 2, // padding
+            2, TCP, // trailer
+            9, 9, 9, 9, // icv
+        ]);
+
+        // without decoding
+        {
+            let (slice, next, rest) = Ipv6ExtensionsSlice::from_slice(IPV6_FRAG, &data).unwrap();
+            assert_eq!(&data[..8], slice.slice());
+            assert_eq!(Some(IPV6_FRAG), slice.first_header());
+            assert_eq!(EspHeader{ spi: 1, sequence_number: 2 }, slice.esp().unwrap().to_header());
+            assert!(slice.esp_trailer().is_none());
+            assert_eq!(ENCAP_SEC, next);
+            assert_eq!(&data[8..], rest);
+            assert!(!slice.is_empty());
+        }
+
+        // null decoding
+        {
+            let (slice, next, rest) = Ipv6ExtensionsSlice::from_slice_esp_null(IPV6_FRAG, &data, 4).unwrap();
+            assert_eq!(&data[..8], slice.slice());
+            assert_eq!(EspHeader{ spi: 1, sequence_number: 2 }, slice.esp().unwrap().to_header());
+            let trailer = slice.esp_trailer().unwrap();
+            assert_eq!(2, trailer.pad_length());
+            assert_eq!(&[9, 9, 9, 9], trailer.icv());
+            assert_eq!(TCP, next);
+            assert_eq!(&[1, 2, 3, 4], rest);
+        }
+
+        // esp directly after the ip header
+        {
+            let (slice, next, rest) = Ipv6ExtensionsSlice::from_slice_esp_null(ENCAP_SEC, &data[8..], 4).unwrap();
+            assert!(slice.slice().is_empty());
+            assert_eq!(None, slice.first_header());
+            assert!(slice.esp().is_some());
+            assert!(!slice.is_empty());
+            assert_eq!(TCP, next);
+            assert_eq!(&[1, 2, 3, 4], rest);
+        }
+
+        // no esp present
+        assert_eq!(
+            Ipv6ExtensionsSlice::from_slice(UDP, &data).unwrap(),
+            Ipv6ExtensionsSlice::from_slice_esp_null(UDP, &data, 4).unwrap()
+        );
+
+        // errors (offsets are relative to the start of the extensions)
+        {
+            let (slice, next, rest) = Ipv6ExtensionsSlice::from_slice(IPV6_FRAG, &data[..8 + 7]).unwrap();
+            assert!(slice.esp().is_none());
+            assert_eq!(ENCAP_SEC, next);
+            assert_eq!(&data[8..8 + 7], rest);
+        }
+        assert_matches!(
+            Ipv6ExtensionsSlice::from_slice_esp_null(IPV6_FRAG, &data[..8 + 7], 0),
+            Err(ReadError::UnexpectedEndOfSlice(16))
+        );
+        assert_matches!(
+            Ipv6ExtensionsSlice::from_slice_esp_null(IPV6_FRAG, &data, 12),
+            Err(ReadError::UnexpectedEndOfSlice(30))
+        );
+    }
+
     #[test]
     fn debug() {
         let a : Ipv6ExtensionsSlice = Default::default();
         assert_eq!(
-            "Ipv6ExtensionsSlice { first_header: None, fragmented: false, slice: [] }",
+            "Ipv6ExtensionsSlice { first_header: None, fragmented: false, slice: [], esp: None, esp_trailer: None }",
             &format!("{:?}", a)
         );
     }
@@ -1394,7 +1613,7 @@ pub mod slice_iter {
             header_size in any::<u8>(),
             post_header in any::<u8>()
                 .prop_filter("Must be a non ipv6 header relevant ip number".to_owned(),
-                    |v| !EXTESION_KNOWN_IP_NUMBERS.iter().any(|&x| v == &x)
+                    |v| !EXTESION_KNOWN_IP_NUMBERS.iter().chain(SLICE_SKIPPED_IP_NUMBERS.iter()).any(|&x| v == &x)
                 )
         ) {
             /// Run a test with the given ip numbers
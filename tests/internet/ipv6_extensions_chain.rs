@@ -0,0 +1,173 @@
+use super::super::*;
+
+/// Chain containing three destination options headers followed by udp.
+fn repeated_chain() -> Ipv6ExtensionsChain {
+    use Ipv6ExtensionHeader::*;
+    let mut chain = Ipv6ExtensionsChain{
+        headers: vec![
+            HopByHop(Ipv6RawExtensionHeader::new_raw(0, &[1;6]).unwrap()),
+            DestinationOptions(Ipv6RawExtensionHeader::new_raw(0, &[2;6]).unwrap()),
+            Routing(Ipv6RawExtensionHeader::new_raw(0, &[3;6]).unwrap()),
+            DestinationOptions(Ipv6RawExtensionHeader::new_raw(0, &[4;14]).unwrap()),
+            Fragment(Ipv6FragmentHeader::new(0, 0, false, 1234)),
+            Authentication(IpAuthenticationHeader::new(0, 1, 2, &[5;4]).unwrap()),
+            DestinationOptions(Ipv6RawExtensionHeader::new_raw(0, &[6;6]).unwrap()),
+        ]
+    };
+    assert_eq!(ip_number::IPV6_HOP_BY_HOP, chain.set_next_headers(ip_number::UDP));
+    chain
+}
+
+#[test]
+fn from_slice() {
+    let chain = repeated_chain();
+    let mut buffer = Vec::new();
+    chain.write(&mut buffer).unwrap();
+    assert_eq!(chain.header_len(), buffer.len());
+    buffer.extend_from_slice(&[1,2,3,4]);
+
+    // owned chain
+    {
+        let (decoded, next_header, rest) = Ipv6ExtensionsChain::from_slice(ip_number::IPV6_HOP_BY_HOP, &buffer).unwrap();
+        assert_eq!(chain, decoded);
+        assert_eq!(ip_number::UDP, next_header);
+        assert_eq!(&[1,2,3,4], rest);
+        assert!(!decoded.is_fragmenting_payload());
+    }
+
+    // the struct based decoding stops at the third destination options header
+    {
+        let (_, next_header, rest) = Ipv6Extensions::from_slice(ip_number::IPV6_HOP_BY_HOP, &buffer).unwrap();
+        assert_eq!(ip_number::IPV6_DEST_OPTIONS, next_header);
+        assert_eq!(8 + 4, rest.len());
+    }
+
+    // iterator
+    {
+        let mut iter = Ipv6ExtensionsChainIter::new(ip_number::IPV6_HOP_BY_HOP, &buffer);
+        let headers = (&mut iter).collect::<Result<Vec<_>,_>>().unwrap();
+        assert_eq!(
+            chain.headers.iter().map(|h| h.ip_number()).collect::<Vec<_>>(),
+            headers.iter().map(|h| h.ip_number()).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            chain.headers,
+            headers.iter().map(|h| h.to_header()).collect::<Vec<_>>()
+        );
+        assert_eq!(ip_number::UDP, iter.next_header());
+        assert_eq!(&[1,2,3,4], iter.rest());
+        assert!(iter.next().is_none());
+    }
+
+    // slice conversion
+    {
+        let (slice, _, _) = Ipv6ExtensionsSlice::from_slice(ip_number::IPV6_HOP_BY_HOP, &buffer).unwrap();
+        assert_eq!(chain, Ipv6ExtensionsChain::from(slice));
+    }
+}
+
+#[test]
+fn iter_errors() {
+    use ReadError::*;
+
+    let chain = repeated_chain();
+    let mut buffer = Vec::new();
+    chain.write(&mut buffer).unwrap();
+
+    // header limit
+    {
+        let mut iter = Ipv6ExtensionsChainIter::with_max_headers(ip_number::IPV6_HOP_BY_HOP, &buffer, 2);
+        assert_matches!(iter.next(), Some(Ok(Ipv6ExtensionSlice::HopByHop(_))));
+        assert_matches!(iter.next(), Some(Ok(Ipv6ExtensionSlice::DestinationOptions(_))));
+        assert_matches!(iter.next(), Some(Err(Ipv6TooManyHeaderExtensions)));
+        assert!(iter.next().is_none());
+        assert_eq!(ip_number::IPV6_ROUTE, iter.next_header());
+        assert_eq!(&buffer[16..], iter.rest());
+    }
+
+    // hop by hop not at start
+    {
+        let mut iter = Ipv6ExtensionsChainIter::new(ip_number::IPV6_DEST_OPTIONS, &[ip_number::IPV6_HOP_BY_HOP, 0, 0, 0, 0, 0, 0, 0]);
+        assert_matches!(iter.next(), Some(Ok(Ipv6ExtensionSlice::DestinationOptions(_))));
+        assert_matches!(iter.next(), Some(Err(Ipv6HopByHopHeaderNotAtStart)));
+        assert!(iter.next().is_none());
+        assert_matches!(
+            Ipv6ExtensionsChain::from_slice(ip_number::IPV6_DEST_OPTIONS, &[ip_number::IPV6_HOP_BY_HOP, 0, 0, 0, 0, 0, 0, 0]),
+            Err(Ipv6HopByHopHeaderNotAtStart)
+        );
+    }
+
+    // length error
+    {
+        let mut iter = Ipv6ExtensionsChainIter::new(ip_number::IPV6_HOP_BY_HOP, &buffer[..buffer.len() - 1]);
+        let result = (&mut iter).collect::<Result<Vec<_>,_>>();
+        assert_matches!(result, Err(UnexpectedEndOfSlice(_)));
+        assert_eq!(ip_number::IPV6_DEST_OPTIONS, iter.next_header());
+        assert_eq!(7, iter.rest().len());
+    }
+}
+
+#[test]
+fn set_next_headers() {
+    let mut chain = Ipv6ExtensionsChain::default();
+    assert_eq!(ip_number::TCP, chain.set_next_headers(ip_number::TCP));
+    assert_eq!(0, chain.header_len());
+
+    chain.headers.push(Ipv6ExtensionHeader::Fragment(Ipv6FragmentHeader::new(0, 1, true, 2)));
+    chain.headers.push(Ipv6ExtensionHeader::Fragment(Ipv6FragmentHeader::new(0, 0, false, 2)));
+    assert_eq!(ip_number::IPV6_FRAG, chain.set_next_headers(ip_number::TCP));
+    assert_eq!(ip_number::IPV6_FRAG, chain.headers[0].next_header());
+    assert_eq!(ip_number::TCP, chain.headers[1].next_header());
+    assert!(chain.is_fragmenting_payload());
+}
+
+#[test]
+fn mobility_hip_shim6() {
+    use Ipv6ExtensionHeader::*;
+    let mut chain = Ipv6ExtensionsChain{
+        headers: vec![
+            DestinationOptions(Ipv6RawExtensionHeader::new_raw(0, &[1;6]).unwrap()),
+            Shim6(Shim6Header::Payload(Shim6PayloadHeader{ next_header: 0, receiver_context_tag: 1 })),
+            Hip(HipHeader::new(0, hip_packet_type::UPDATE, [2;16], [3;16])),
+            Mobility(Ipv6MobilityHeader::new(
+                0,
+                &Ipv6MobilityMessage::BindingRefreshRequest{ options: &[] }
+            ).unwrap()),
+        ]
+    };
+    assert_eq!(ip_number::IPV6_DEST_OPTIONS, chain.set_next_headers(ip_number::IPV6_NO_NEXT_HEADER));
+    assert_eq!(
+        [ip_number::SHIM6, ip_number::HIP, ip_number::MOBILITY, ip_number::IPV6_NO_NEXT_HEADER],
+        [
+            chain.headers[0].next_header(),
+            chain.headers[1].next_header(),
+            chain.headers[2].next_header(),
+            chain.headers[3].next_header(),
+        ]
+    );
+
+    let mut buffer = Vec::new();
+    chain.write(&mut buffer).unwrap();
+    assert_eq!(8 + 8 + 40 + 8, buffer.len());
+    assert_eq!(chain.header_len(), buffer.len());
+
+    let (decoded, next_header, rest) = Ipv6ExtensionsChain::from_slice(ip_number::IPV6_DEST_OPTIONS, &buffer).unwrap();
+    assert_eq!(chain, decoded);
+    assert_eq!(ip_number::IPV6_NO_NEXT_HEADER, next_header);
+    assert!(rest.is_empty());
+
+    // extensions slice skips all the headers
+    let (slice, next_header, rest) = Ipv6ExtensionsSlice::from_slice(ip_number::IPV6_DEST_OPTIONS, &buffer).unwrap();
+    assert_eq!(ip_number::IPV6_NO_NEXT_HEADER, next_header);
+    assert!(rest.is_empty());
+    assert_eq!(
+        vec![ip_number::IPV6_DEST_OPTIONS, ip_number::SHIM6, ip_number::HIP, ip_number::MOBILITY],
+        slice.into_iter().map(|h| h.ip_number()).collect::<Vec<_>>()
+    );
+
+    // truncated hip header
+    assert_matches!(
+        Ipv6ExtensionsSlice::from_slice(ip_number::IPV6_DEST_OPTIONS, &buffer[..8 + 8 + 39]),
+        Err(ReadError::UnexpectedEndOfSlice(40))
+    );
+}
@@ -0,0 +1,159 @@
+use super::super::*;
+
+#[test]
+fn binding_update() {
+    use Ipv6MobilityMessage::*;
+
+    let message = BindingUpdate{
+        sequence_number: 0x1234,
+        acknowledge: true,
+        home_registration: true,
+        link_local_address_compatibility: false,
+        key_management_mobility_capability: true,
+        lifetime: 0x5678,
+        options: &[],
+    };
+    let header = Ipv6MobilityHeader::new(ip_number::IPV6_NO_NEXT_HEADER, &message).unwrap();
+    assert_eq!(ipv6_mobility_type::BINDING_UPDATE, header.mh_type);
+    assert_eq!(16, header.header_len());
+    assert_eq!(
+        &[
+            0x12, 0x34, 0b1101_0000, 0, 0x56, 0x78,
+            // padding
+            ipv6_option::TYPE_PADN, 2, 0, 0
+        ],
+        header.message_data()
+    );
+    assert_eq!(
+        BindingUpdate{
+            sequence_number: 0x1234,
+            acknowledge: true,
+            home_registration: true,
+            link_local_address_compatibility: false,
+            key_management_mobility_capability: true,
+            lifetime: 0x5678,
+            options: &[ipv6_option::TYPE_PADN, 2, 0, 0],
+        },
+        header.message().unwrap()
+    );
+
+    // write & read
+    let mut buffer = Vec::new();
+    header.write(&mut buffer).unwrap();
+    buffer.extend_from_slice(&[1, 2]);
+    assert_eq!(&[ip_number::IPV6_NO_NEXT_HEADER, 1, ipv6_mobility_type::BINDING_UPDATE, 0, 0, 0], &buffer[..6]);
+    let (decoded, rest) = Ipv6MobilityHeader::from_slice(&buffer).unwrap();
+    assert_eq!(header, decoded);
+    assert_eq!(&[1, 2], rest);
+
+    let slice = Ipv6MobilityHeaderSlice::from_slice(&buffer).unwrap();
+    assert_eq!(&buffer[..16], slice.slice());
+    assert_eq!(ip_number::IPV6_NO_NEXT_HEADER, slice.payload_proto());
+    assert_eq!(ip_number::IPV6_NO_NEXT_HEADER, slice.next_header());
+    assert_eq!(ipv6_mobility_type::BINDING_UPDATE, slice.mh_type());
+    assert_eq!(header.message().unwrap(), slice.message().unwrap());
+    assert_eq!(header, slice.to_header());
+}
+
+#[test]
+fn messages() {
+    use Ipv6MobilityMessage::*;
+
+    let messages = [
+        BindingRefreshRequest{ options: &[] },
+        HomeTestInit{ home_init_cookie: [1;8], options: &[] },
+        CareOfTestInit{ care_of_init_cookie: [2;8], options: &[] },
+        HomeTest{ home_nonce_index: 3, home_init_cookie: [4;8], home_keygen_token: [5;8], options: &[] },
+        CareOfTest{ care_of_nonce_index: 6, care_of_init_cookie: [7;8], care_of_keygen_token: [8;8], options: &[] },
+        BindingAcknowledgement{ status: 128, key_management_mobility_capability: true, sequence_number: 9, lifetime: 10, options: &[] },
+        BindingError{ status: 1, home_address: [11;16], options: &[] },
+        Unknown{ mh_type: 200, data: &[1, 2] },
+    ];
+    for message in &messages {
+        let header = Ipv6MobilityHeader::new(ip_number::IPV6_NO_NEXT_HEADER, message).unwrap();
+        assert_eq!(message.mh_type(), header.mh_type);
+        assert_eq!(0, header.header_len() % 8);
+
+        // the decoded message only differs by the padding in the options
+        let len = message.serialized_len();
+        let padded = Ipv6MobilityMessage::from_message_data(
+            message.mh_type(),
+            &header.message_data()[..len]
+        ).unwrap();
+        assert_eq!(message, &padded);
+    }
+
+    // too short message data
+    assert_matches!(
+        Ipv6MobilityMessage::from_message_data(ipv6_mobility_type::BINDING_ERROR, &[0;17]),
+        Err(ReadError::UnexpectedEndOfSlice(18))
+    );
+}
+
+#[test]
+fn new_raw_errors() {
+    use ValueError::*;
+
+    assert_eq!(
+        Err(Ipv6ExtensionPayloadTooSmall(5)),
+        Ipv6MobilityHeader::new_raw(0, 0, &[0])
+    );
+    assert_eq!(
+        Err(Ipv6ExtensionPayloadLengthUnaligned(7)),
+        Ipv6MobilityHeader::new_raw(0, 0, &[0;3])
+    );
+    assert_eq!(
+        Err(Ipv6ExtensionPayloadTooLarge(Ipv6MobilityHeader::MAX_MESSAGE_DATA_LEN + 8 + 4)),
+        Ipv6MobilityHeader::new_raw(0, 0, &[0;Ipv6MobilityHeader::MAX_MESSAGE_DATA_LEN + 8])
+    );
+    assert!(Ipv6MobilityHeader::new_raw(0, 0, &[0;Ipv6MobilityHeader::MAX_MESSAGE_DATA_LEN]).is_ok());
+}
+
+#[test]
+fn from_slice_errors() {
+    assert_matches!(
+        Ipv6MobilityHeaderSlice::from_slice(&[0;7]),
+        Err(ReadError::UnexpectedEndOfSlice(8))
+    );
+    assert_matches!(
+        Ipv6MobilityHeaderSlice::from_slice(&[0, 1, 0, 0, 0, 0, 0, 0]),
+        Err(ReadError::UnexpectedEndOfSlice(16))
+    );
+}
+
+#[test]
+fn checksum() {
+    let source = [1;16];
+    let destination = [2;16];
+    let mut header = Ipv6MobilityHeader::new(
+        ip_number::IPV6_NO_NEXT_HEADER,
+        &Ipv6MobilityMessage::BindingRefreshRequest{ options: &[] }
+    ).unwrap();
+    header.checksum = header.calc_checksum(source, destination);
+
+    // the sum over the pseudo header & the header including the checksum
+    // must result in 0xffff
+    let bytes = header.to_bytes();
+    let sum = etherparse::checksum::Sum16BitWords::new()
+        .add_16bytes(source)
+        .add_16bytes(destination)
+        .add_4bytes((bytes.len() as u32).to_be_bytes())
+        .add_2bytes([0, ip_number::MOBILITY])
+        .add_slice(&bytes)
+        .ones_complement();
+    assert_eq!(0, sum);
+
+    // slice
+    let slice = Ipv6MobilityHeaderSlice::from_slice(&bytes).unwrap();
+    assert_eq!(header.checksum, slice.checksum());
+    assert_eq!(header.checksum, slice.calc_checksum(source, destination));
+}
+
+#[test]
+fn debug() {
+    let header = Ipv6MobilityHeader::new_raw(1, 2, &[3, 4]).unwrap();
+    assert_eq!(
+        "Ipv6MobilityHeader { payload_proto: 1, mh_type: 2, checksum: 0, message_data: [3, 4] }",
+        &format!("{:?}", header)
+    );
+}
@@ -0,0 +1,291 @@
+use super::super::*;
+
+#[test]
+fn unknown_action() {
+    use Ipv6OptionUnknownAction::*;
+    assert_eq!(Skip, Ipv6OptionUnknownAction::from_option_type(0b0011_1111));
+    assert_eq!(Discard, Ipv6OptionUnknownAction::from_option_type(0b0100_0000));
+    assert_eq!(DiscardSendIcmp, Ipv6OptionUnknownAction::from_option_type(0b1000_0000));
+    assert_eq!(DiscardSendIcmpNotMulticast, Ipv6OptionUnknownAction::from_option_type(0b1100_0000));
+
+    assert_eq!(DiscardSendIcmpNotMulticast, Ipv6OptionElement::JumboPayload(0).unknown_action());
+    assert_eq!(Skip, Ipv6OptionElement::RouterAlert(0).unknown_action());
+    assert!(!Ipv6OptionElement::RouterAlert(0).may_change_en_route());
+    assert!(Ipv6OptionElement::Unknown{ option_type: 0x3e, data: &[] }.may_change_en_route());
+}
+
+#[test]
+fn new_with_options() {
+    use Ipv6OptionElement::*;
+
+    // router alert (2n) & tunnel encapsulation limit
+    {
+        let options = [RouterAlert(0), TunnelEncapsulationLimit(4)];
+        let header = Ipv6RawExtensionHeader::new_with_options(ip_number::UDP, &options).unwrap();
+        assert_eq!(
+            &[
+                ipv6_option::TYPE_ROUTER_ALERT, 2, 0, 0,
+                ipv6_option::TYPE_TUNNEL_ENCAPSULATION_LIMIT, 1, 4,
+                // padding to 16 octets
+                ipv6_option::TYPE_PADN, 5, 0, 0, 0, 0, 0,
+            ],
+            header.payload()
+        );
+        assert_eq!(ip_number::UDP, header.next_header);
+        assert_eq!(
+            vec![RouterAlert(0), TunnelEncapsulationLimit(4), PadN(5)],
+            header.options_iterator().collect::<Result<Vec<_>,_>>().unwrap()
+        );
+    }
+
+    // jumbo payload (4n+2) after a 1 byte aligned option
+    {
+        let options = [TunnelEncapsulationLimit(1), JumboPayload(0x12345678)];
+        let header = Ipv6RawExtensionHeader::new_with_options(ip_number::TCP, &options).unwrap();
+        assert_eq!(
+            &[
+                ipv6_option::TYPE_TUNNEL_ENCAPSULATION_LIMIT, 1, 1,
+                // padding so the jumbo payload starts at offset 4n+2
+                ipv6_option::TYPE_PAD1,
+                ipv6_option::TYPE_JUMBO_PAYLOAD, 4, 0x12, 0x34, 0x56, 0x78,
+                // padding to 16 octets
+                ipv6_option::TYPE_PADN, 2, 0, 0,
+            ],
+            header.payload()
+        );
+        assert_eq!(
+            vec![TunnelEncapsulationLimit(1), Pad1, JumboPayload(0x12345678), PadN(2)],
+            header.options_iterator().collect::<Result<Vec<_>,_>>().unwrap()
+        );
+    }
+
+    // home address (8n+6) & calipso (4n+2)
+    {
+        let options = [
+            HomeAddress([1;16]),
+            Calipso{
+                domain_of_interpretation: 1,
+                sensitivity_level: 2,
+                checksum: 3,
+                compartment_bitmap: &[4, 5, 6, 7],
+            }
+        ];
+        let header = Ipv6RawExtensionHeader::new_with_options(ip_number::UDP, &options).unwrap();
+        assert_eq!(40, header.header_len());
+        let mut buffer = Vec::new();
+        header.write(&mut buffer).unwrap();
+        let slice = Ipv6RawExtensionHeaderSlice::from_slice(&buffer).unwrap();
+        let decoded: Vec<_> = slice.options_iterator()
+            .filter(|o| !matches!(o, Ok(Pad1) | Ok(PadN(_))))
+            .collect::<Result<Vec<_>,_>>()
+            .unwrap();
+        assert_eq!(&options[..], &decoded[..]);
+        // check the alignment
+        assert_eq!(ipv6_option::TYPE_HOME_ADDRESS, buffer[6]);
+        assert_eq!(ipv6_option::TYPE_CALIPSO, buffer[24 + 2]);
+    }
+
+    // no options
+    {
+        let header = Ipv6RawExtensionHeader::new_with_options(ip_number::UDP, &[]).unwrap();
+        assert_eq!(&[ipv6_option::TYPE_PADN, 4, 0, 0, 0, 0], header.payload());
+    }
+}
+
+#[test]
+fn new_with_options_errors() {
+    use Ipv6OptionElement::*;
+    use ValueError::*;
+
+    // data too big
+    assert_eq!(
+        Err(Ipv6OptionDataLengthBad(256)),
+        Ipv6RawExtensionHeader::new_with_options(
+            ip_number::UDP,
+            &[Unknown{ option_type: 0x1e, data: &[0;256] }]
+        )
+    );
+
+    // unaligned calipso bitmap
+    assert_eq!(
+        Err(Ipv6OptionDataLengthBad(3)),
+        Ipv6RawExtensionHeader::new_with_options(
+            ip_number::UDP,
+            &[Calipso{ domain_of_interpretation: 0, sensitivity_level: 0, checksum: 0, compartment_bitmap: &[0;3] }]
+        )
+    );
+
+    // too many options
+    let big = Unknown{ option_type: 0x1e, data: &[0;255] };
+    let options = [big.clone(), big.clone(), big.clone(), big.clone(), big.clone(), big.clone(), big.clone(), big.clone()];
+    assert_matches!(
+        Ipv6RawExtensionHeader::new_with_options(ip_number::UDP, &options),
+        Err(Ipv6ExtensionPayloadTooLarge(_))
+    );
+    assert!(Ipv6RawExtensionHeader::new_with_options(ip_number::UDP, &options[..7]).is_ok());
+}
+
+#[test]
+fn iterator() {
+    use Ipv6OptionElement::*;
+    use Ipv6OptionReadError::*;
+
+    // known types with non standard length are returned as unknown
+    assert_eq!(
+        vec![
+            Ok(Unknown{ option_type: ipv6_option::TYPE_ROUTER_ALERT, data: &[1, 2, 3] }),
+            Ok(Unknown{ option_type: 0x1e, data: &[] }),
+        ],
+        Ipv6OptionsIterator::from_slice(&[ipv6_option::TYPE_ROUTER_ALERT, 3, 1, 2, 3, 0x1e, 0]).collect::<Vec<_>>()
+    );
+
+    // length missing
+    {
+        let mut iter = Ipv6OptionsIterator::from_slice(&[0x1e]);
+        assert_eq!(
+            Some(Err(UnexpectedEndOfSlice{ option_type: 0x1e, expected_len: 2, actual_len: 1 })),
+            iter.next()
+        );
+        assert!(iter.rest().is_empty());
+        assert_eq!(None, iter.next());
+    }
+
+    // data missing
+    {
+        let mut iter = Ipv6OptionsIterator::from_slice(&[ipv6_option::TYPE_PADN, 2, 0]);
+        assert_eq!(
+            Some(Err(UnexpectedEndOfSlice{ option_type: ipv6_option::TYPE_PADN, expected_len: 4, actual_len: 3 })),
+            iter.next()
+        );
+        assert_eq!(None, iter.next());
+    }
+
+    // calipso with a bad length
+    {
+        let mut iter = Ipv6OptionsIterator::from_slice(&[ipv6_option::TYPE_CALIPSO, 8, 0, 0, 0, 0, 1, 0, 0, 0, 0]);
+        assert_eq!(
+            Some(Err(UnexpectedSize{ option_type: ipv6_option::TYPE_CALIPSO, size: 8 })),
+            iter.next()
+        );
+        assert_eq!(None, iter.next());
+    }
+
+    // debug
+    assert_eq!(
+        format!(
+            "[{:?}, {:?}]",
+            Pad1,
+            Result::<(), Ipv6OptionReadError>::Err(UnexpectedEndOfSlice{ option_type: 1, expected_len: 2, actual_len: 1 })
+        ),
+        format!("{:?}", Ipv6OptionsIterator::from_slice(&[0, 1]))
+    );
+}
+
+#[test]
+fn read_error_display() {
+    use Ipv6OptionReadError::*;
+    use std::error::Error;
+
+    assert_eq!(
+        "Ipv6OptionReadError: Not enough memory left in slice to read option of type 1 (expected at least 2 bytes, only 3 bytes available).",
+        &format!("{}", UnexpectedEndOfSlice{ option_type: 1, expected_len: 2, actual_len: 3 })
+    );
+    assert_eq!(
+        "Ipv6OptionReadError: Length value of the option of type 1 had unexpected value 2.",
+        &format!("{}", UnexpectedSize{ option_type: 1, size: 2 })
+    );
+    assert!(UnexpectedSize{ option_type: 1, size: 2 }.source().is_none());
+}
+
+#[test]
+fn set_payload_length_with_jumbo() {
+    use Ipv6OptionElement::*;
+
+    let mut header = Ipv6Header{
+        traffic_class: 0,
+        flow_label: 0,
+        payload_length: 0,
+        next_header: ip_number::UDP,
+        hop_limit: 1,
+        source: [1;16],
+        destination: [2;16],
+    };
+    let mut ext: Ipv6Extensions = Default::default();
+
+    // normal size
+    header.set_payload_length_with_jumbo(&mut ext, 0xffff).unwrap();
+    assert_eq!(0xffff, header.payload_length);
+    assert_eq!(None, ext.hop_by_hop_options);
+
+    // jumbogram adds a hop by hop header
+    header.set_payload_length_with_jumbo(&mut ext, 0xffff + 1).unwrap();
+    assert_eq!(0, header.payload_length);
+    assert_eq!(ip_number::IPV6_HOP_BY_HOP, header.next_header);
+    assert_eq!(ip_number::UDP, ext.hop_by_hop_options.as_ref().unwrap().next_header);
+    assert_eq!(Some(0xffff + 1 + 8), ext.jumbo_payload_length());
+
+    // update of an existing jumbo payload option
+    header.set_payload_length_with_jumbo(&mut ext, 0x20000).unwrap();
+    assert_eq!(8, ext.header_len());
+    assert_eq!(Some(0x20000 + 8), ext.jumbo_payload_length());
+
+    // back to a normal size removes the jumbo option
+    header.set_payload_length_with_jumbo(&mut ext, 100).unwrap();
+    assert_eq!(100 + 8, usize::from(header.payload_length));
+    assert_eq!(None, ext.jumbo_payload_length());
+    assert_eq!(
+        vec![PadN(4)],
+        ext.hop_by_hop_options.as_ref().unwrap().options_iterator().collect::<Result<Vec<_>,_>>().unwrap()
+    );
+
+    // too big
+    if std::mem::size_of::<usize>() > 4 {
+        assert_eq!(
+            Err(ValueError::Ipv6PayloadLengthTooLarge(0x1_0000_0000 + 8)),
+            header.set_payload_length_with_jumbo(&mut ext, 0x1_0000_0000)
+        );
+    }
+}
+
+#[test]
+fn set_router_alert() {
+    use Ipv6OptionElement::*;
+
+    // new hop by hop header
+    let mut ext: Ipv6Extensions = Default::default();
+    assert_eq!(None, ext.router_alert());
+    ext.set_router_alert(ipv6_option::ROUTER_ALERT_MLD).unwrap();
+    assert_eq!(Some(ipv6_option::ROUTER_ALERT_MLD), ext.router_alert());
+    assert_eq!(8, ext.header_len());
+
+    // overwrite the existing value
+    ext.set_router_alert(ipv6_option::ROUTER_ALERT_RSVP).unwrap();
+    assert_eq!(Some(ipv6_option::ROUTER_ALERT_RSVP), ext.router_alert());
+    assert_eq!(8, ext.header_len());
+
+    // insert in front of existing options (alignment is kept)
+    let mut ext = Ipv6Extensions{
+        hop_by_hop_options: Some(
+            Ipv6RawExtensionHeader::new_with_options(ip_number::UDP, &[HomeAddress([1;16])]).unwrap()
+        ),
+        ..Default::default()
+    };
+    ext.set_router_alert(ipv6_option::ROUTER_ALERT_MLD).unwrap();
+    assert_eq!(
+        vec![RouterAlert(0), PadN(2), PadN(2), HomeAddress([1;16])],
+        ext.hop_by_hop_options.as_ref().unwrap().options_iterator().collect::<Result<Vec<_>,_>>().unwrap()
+    );
+    assert_eq!(ip_number::UDP, ext.hop_by_hop_options.as_ref().unwrap().next_header);
+
+    // too big
+    let mut ext = Ipv6Extensions{
+        hop_by_hop_options: Some(
+            Ipv6RawExtensionHeader::new_raw(ip_number::UDP, &[0;Ipv6RawExtensionHeader::MAX_PAYLOAD_LEN]).unwrap()
+        ),
+        ..Default::default()
+    };
+    assert_eq!(
+        Err(ValueError::Ipv6ExtensionPayloadTooLarge(Ipv6RawExtensionHeader::MAX_PAYLOAD_LEN + 8)),
+        ext.set_router_alert(0)
+    );
+}
@@ -0,0 +1,264 @@
+use super::super::*;
+
+#[test]
+fn source_route() {
+    use Ipv6RoutingHeader::*;
+
+    let addresses = [[1;16], [2;16]];
+    let routing = SourceRoute{ segments_left: 2, addresses: &addresses };
+    let header = Ipv6RawExtensionHeader::new_with_routing(ip_number::UDP, &routing).unwrap();
+    assert_eq!(6 + 32, header.payload().len());
+    assert_eq!(&[ipv6_routing_type::SOURCE_ROUTE, 2, 0, 0, 0, 0], &header.payload()[..6]);
+    assert_eq!(Ok(routing.clone()), header.routing_header());
+    assert_eq!(Some(2), routing.address_count());
+
+    // the final destination is the last address as long as segments are left
+    assert_eq!([2;16], routing.effective_destination([9;16]));
+    assert_eq!(
+        [9;16],
+        SourceRoute{ segments_left: 0, addresses: &addresses }.effective_destination([9;16])
+    );
+    // segments left bigger then the address count
+    assert_eq!(
+        [9;16],
+        SourceRoute{ segments_left: 3, addresses: &addresses }.effective_destination([9;16])
+    );
+}
+
+#[test]
+fn type2() {
+    use Ipv6RoutingHeader::*;
+
+    let routing = Type2{ segments_left: 1, home_address: [3;16] };
+    let header = Ipv6RawExtensionHeader::new_with_routing(ip_number::TCP, &routing).unwrap();
+    assert_eq!(
+        Ipv6RawExtensionHeaderSlice::from_slice(&{
+            let mut buffer = Vec::new();
+            header.write(&mut buffer).unwrap();
+            buffer
+        }).unwrap().to_header(),
+        header
+    );
+    assert_eq!(Ok(routing.clone()), header.routing_header());
+    assert_eq!(ipv6_routing_type::TYPE2, routing.routing_type());
+    assert_eq!([3;16], routing.effective_destination([9;16]));
+
+    // type 2 headers must contain exactly one address
+    let raw = Ipv6RawExtensionHeader::new_raw(
+        ip_number::TCP,
+        &[ipv6_routing_type::TYPE2, 1, 0, 0, 0, 0]
+    ).unwrap();
+    assert_eq!(
+        Err(Ipv6RoutingReadError::UnexpectedSize{ routing_type: ipv6_routing_type::TYPE2, size: 8 }),
+        raw.routing_header()
+    );
+}
+
+#[test]
+fn rpl() {
+    use Ipv6RoutingHeader::*;
+
+    // one address with 14 elided octets & the last address with 12 elided octets
+    let addresses = [0x11, 0x12, 0x31, 0x32, 0x33, 0x34];
+    let routing = Rpl{ segments_left: 2, cmpr_i: 14, cmpr_e: 12, addresses: &addresses };
+    let header = Ipv6RawExtensionHeader::new_with_routing(ip_number::UDP, &routing).unwrap();
+    assert_eq!(
+        &[
+            ipv6_routing_type::RPL_SOURCE_ROUTE, 2, 0xec, 0x20, 0, 0,
+            0x11, 0x12, 0x31, 0x32, 0x33, 0x34,
+            // padding
+            0, 0,
+        ],
+        header.payload()
+    );
+    assert_eq!(Ok(routing.clone()), header.routing_header());
+    assert_eq!(Some(2), routing.address_count());
+
+    // the elided octets are taken from the destination
+    let mut expected = [9;16];
+    expected[12..].copy_from_slice(&[0x31, 0x32, 0x33, 0x34]);
+    assert_eq!(expected, routing.effective_destination([9;16]));
+
+    // errors
+    assert_eq!(
+        Err(ValueError::U8TooLarge{ value: 16, max: 15, field: ErrorField::Ipv6RplCmprI }),
+        Ipv6RawExtensionHeader::new_with_routing(
+            ip_number::UDP,
+            &Rpl{ segments_left: 0, cmpr_i: 16, cmpr_e: 0, addresses: &[0;16] }
+        )
+    );
+    assert_eq!(
+        Err(ValueError::U8TooLarge{ value: 16, max: 15, field: ErrorField::Ipv6RplCmprE }),
+        Ipv6RawExtensionHeader::new_with_routing(
+            ip_number::UDP,
+            &Rpl{ segments_left: 0, cmpr_i: 0, cmpr_e: 16, addresses: &[0;16] }
+        )
+    );
+    assert_eq!(
+        Err(ValueError::Ipv6RoutingAddressesLengthBad(5)),
+        Ipv6RawExtensionHeader::new_with_routing(
+            ip_number::UDP,
+            &Rpl{ segments_left: 0, cmpr_i: 14, cmpr_e: 12, addresses: &addresses[..5] }
+        )
+    );
+
+    // padding bigger then the header
+    let raw = Ipv6RawExtensionHeader::new_raw(
+        ip_number::UDP,
+        &[ipv6_routing_type::RPL_SOURCE_ROUTE, 0, 0xff, 0xf0, 0, 0]
+    ).unwrap();
+    assert_eq!(
+        Err(Ipv6RoutingReadError::UnexpectedSize{ routing_type: ipv6_routing_type::RPL_SOURCE_ROUTE, size: 8 }),
+        raw.routing_header()
+    );
+}
+
+#[test]
+fn segment_routing() {
+    use Ipv6RoutingHeader::*;
+
+    let segments = [[1;16], [2;16], [3;16]];
+
+    // without tlvs
+    {
+        let routing = SegmentRouting{ segments_left: 2, flags: 0x80, tag: 0x1234, segments: &segments, tlvs: &[] };
+        let header = Ipv6RawExtensionHeader::new_with_routing(ip_number::IPV6, &routing).unwrap();
+        assert_eq!(
+            &[ipv6_routing_type::SEGMENT_ROUTING, 2, 2, 0x80, 0x12, 0x34],
+            &header.payload()[..6]
+        );
+        assert_eq!(6 + 48, header.payload().len());
+        assert_eq!(Ok(routing.clone()), header.routing_header());
+        assert_eq!(Some(3), routing.address_count());
+
+        // segment list is in reverse order
+        assert_eq!([1;16], routing.effective_destination([3;16]));
+    }
+
+    // tlvs get padded with a padn tlv
+    {
+        let tlvs = [ipv6_srh_tlv::TYPE_PAD1];
+        let routing = SegmentRouting{ segments_left: 0, flags: 0, tag: 0, segments: &segments[..1], tlvs: &tlvs };
+        let header = Ipv6RawExtensionHeader::new_with_routing(ip_number::UDP, &routing).unwrap();
+        assert_eq!(
+            &[ipv6_srh_tlv::TYPE_PAD1, ipv6_srh_tlv::TYPE_PADN, 5, 0, 0, 0, 0, 0],
+            &header.payload()[22..]
+        );
+        assert_eq!(
+            Ok(SegmentRouting{
+                segments_left: 0,
+                flags: 0,
+                tag: 0,
+                segments: &segments[..1],
+                tlvs: &[ipv6_srh_tlv::TYPE_PAD1, ipv6_srh_tlv::TYPE_PADN, 5, 0, 0, 0, 0, 0],
+            }),
+            header.routing_header()
+        );
+    }
+
+    // tlvs get padded with a pad1 tlv
+    {
+        let tlvs = [ipv6_srh_tlv::TYPE_PADN, 5, 0, 0, 0, 0, 0];
+        let routing = SegmentRouting{ segments_left: 0, flags: 0, tag: 0, segments: &segments[..1], tlvs: &tlvs };
+        let header = Ipv6RawExtensionHeader::new_with_routing(ip_number::UDP, &routing).unwrap();
+        assert_eq!(ipv6_srh_tlv::TYPE_PAD1, header.payload()[29]);
+        assert_eq!(30, header.payload().len());
+    }
+
+    // errors
+    assert_eq!(
+        Err(ValueError::Ipv6RoutingAddressesLengthBad(0)),
+        Ipv6RawExtensionHeader::new_with_routing(
+            ip_number::UDP,
+            &SegmentRouting{ segments_left: 0, flags: 0, tag: 0, segments: &[], tlvs: &[] }
+        )
+    );
+    {
+        let segments = [[0;16];128];
+        assert_eq!(
+            Err(ValueError::Ipv6ExtensionPayloadTooLarge(6 + 128*16)),
+            Ipv6RawExtensionHeader::new_with_routing(
+                ip_number::UDP,
+                &SegmentRouting{ segments_left: 0, flags: 0, tag: 0, segments: &segments, tlvs: &[] }
+            )
+        );
+    }
+    {
+        // last entry pointing outside of the header
+        let raw = Ipv6RawExtensionHeader::new_raw(
+            ip_number::UDP,
+            &[ipv6_routing_type::SEGMENT_ROUTING, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]
+        ).unwrap();
+        assert_eq!(
+            Err(Ipv6RoutingReadError::UnexpectedSize{ routing_type: ipv6_routing_type::SEGMENT_ROUTING, size: 24 }),
+            raw.routing_header()
+        );
+    }
+}
+
+#[test]
+fn unknown() {
+    use Ipv6RoutingHeader::*;
+
+    let routing = Unknown{ routing_type: 253, segments_left: 1, data: &[1, 2, 3, 4] };
+    let header = Ipv6RawExtensionHeader::new_with_routing(ip_number::UDP, &routing).unwrap();
+    assert_eq!(Ok(routing.clone()), header.routing_header());
+    assert_eq!(None, routing.address_count());
+    assert_eq!([9;16], routing.effective_destination([9;16]));
+
+    assert_eq!(
+        Err(ValueError::Ipv6ExtensionPayloadLengthUnaligned(7)),
+        Ipv6RawExtensionHeader::new_with_routing(
+            ip_number::UDP,
+            &Unknown{ routing_type: 253, segments_left: 1, data: &[1, 2, 3, 4, 5] }
+        )
+    );
+    assert_eq!(
+        Err(Ipv6RoutingReadError::UnexpectedSize{ routing_type: 1, size: 7 }),
+        Ipv6RoutingHeader::from_payload(&[1, 2, 3, 4, 5])
+    );
+}
+
+#[test]
+fn effective_destination_extensions() {
+    let segments = [[1;16], [2;16]];
+    let routing = Ipv6RawExtensionHeader::new_with_routing(
+        ip_number::UDP,
+        &Ipv6RoutingHeader::SegmentRouting{ segments_left: 1, flags: 0, tag: 0, segments: &segments, tlvs: &[] }
+    ).unwrap();
+
+    // no routing header
+    assert_eq!([9;16], Ipv6Extensions::default().effective_destination([9;16]));
+
+    let extensions = Ipv6Extensions{
+        hop_by_hop_options: Some(
+            Ipv6RawExtensionHeader::new_with_options(ip_number::IPV6_ROUTE, &[]).unwrap()
+        ),
+        routing: Some(Ipv6RoutingExtensions{
+            routing,
+            final_destination_options: None,
+        }),
+        ..Default::default()
+    };
+    assert_eq!([1;16], extensions.effective_destination([2;16]));
+
+    // slice
+    let mut buffer = Vec::new();
+    extensions.write(&mut buffer, ip_number::IPV6_HOP_BY_HOP).unwrap();
+    let (slice, next, _) = Ipv6ExtensionsSlice::from_slice(ip_number::IPV6_HOP_BY_HOP, &buffer).unwrap();
+    assert_eq!(ip_number::UDP, next);
+    assert_eq!([1;16], slice.effective_destination([2;16]));
+
+    let (slice, _, _) = Ipv6ExtensionsSlice::from_slice(ip_number::UDP, &[]).unwrap();
+    assert_eq!([2;16], slice.effective_destination([2;16]));
+}
+
+#[test]
+fn read_error_display() {
+    assert_eq!(
+        "Ipv6RoutingReadError: Routing header of type 4 has an unexpected length of 8 bytes.",
+        &format!("{}", Ipv6RoutingReadError::UnexpectedSize{ routing_type: 4, size: 8 })
+    );
+    use std::error::Error;
+    assert!(Ipv6RoutingReadError::UnexpectedSize{ routing_type: 4, size: 8 }.source().is_none());
+}
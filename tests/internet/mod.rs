@@ -1,8 +1,17 @@
+pub mod esp;
 pub mod ip;
 pub mod ip_authentication;
+pub mod ip_authentication_icv;
 pub mod ipv4;
+pub mod ipv4_options;
 pub mod ipv4_extensions;
 pub mod ipv6;
 pub mod ipv6_extensions;
+pub mod ipv6_extensions_chain;
 pub mod ipv6_raw_extension;
+pub mod ipv6_options;
+pub mod ipv6_routing;
+pub mod ipv6_mobility;
+pub mod hip;
+pub mod shim6;
 pub mod ipv6_fragment;
\ No newline at end of file
@@ -0,0 +1,103 @@
+use super::super::*;
+
+#[test]
+fn payload_extension() {
+    let header = Shim6Header::Payload(Shim6PayloadHeader{
+        next_header: ip_number::TCP,
+        receiver_context_tag: 0x7fff_0102_0304,
+    });
+    assert_eq!(8, header.header_len());
+    assert_eq!(ip_number::TCP, header.next_header());
+
+    let mut buffer = Vec::new();
+    header.write(&mut buffer).unwrap();
+    buffer.extend_from_slice(&[1, 2]);
+    assert_eq!(
+        &[ip_number::TCP, 0, 0xff, 0xff, 1, 2, 3, 4],
+        &buffer[..8]
+    );
+    let (decoded, rest) = Shim6Header::from_slice(&buffer).unwrap();
+    assert_eq!(header, decoded);
+    assert_eq!(&[1, 2], rest);
+
+    let slice = Shim6HeaderSlice::from_slice(&buffer).unwrap();
+    assert!(slice.is_payload_extension());
+    assert_eq!(Some(0x7fff_0102_0304), slice.receiver_context_tag());
+    assert_eq!(None, slice.message_type());
+    assert_eq!(None, slice.type_specific());
+    assert_eq!(None, slice.checksum());
+    assert_eq!(None, slice.message_data());
+    assert_eq!(None, slice.calc_checksum());
+
+    // context tag too big
+    assert_eq!(
+        Err(ValueError::U64TooLarge{
+            value: 0x8000_0000_0000,
+            max: Shim6PayloadHeader::MAX_RECEIVER_CONTEXT_TAG,
+            field: ErrorField::Shim6ReceiverContextTag
+        }),
+        Shim6PayloadHeader{ next_header: 0, receiver_context_tag: 0x8000_0000_0000 }.to_bytes()
+    );
+}
+
+#[test]
+fn control() {
+    let mut control = Shim6ControlHeader::new(
+        ip_number::IPV6_NO_NEXT_HEADER,
+        shim6_message_type::I1,
+        0x7f,
+        &[1, 2, 3, 4, 5, 6, 7, 8, 9, 10]
+    ).unwrap();
+    control.checksum = control.calc_checksum().unwrap();
+    assert_eq!(16, control.header_len());
+
+    // the checksum is the ones complement over the whole message
+    let bytes = control.to_bytes().unwrap();
+    assert_eq!(0, etherparse::checksum::Sum16BitWords::new().add_slice(&bytes).ones_complement());
+    assert_eq!(
+        &[ip_number::IPV6_NO_NEXT_HEADER, 1, shim6_message_type::I1, 0xfe],
+        &bytes[..4]
+    );
+
+    let header = Shim6Header::Control(control.clone());
+    let mut buffer = Vec::new();
+    header.write(&mut buffer).unwrap();
+    let slice = Shim6HeaderSlice::from_slice(&buffer).unwrap();
+    assert!(!slice.is_payload_extension());
+    assert_eq!(None, slice.receiver_context_tag());
+    assert_eq!(Some(shim6_message_type::I1), slice.message_type());
+    assert_eq!(Some(0x7f), slice.type_specific());
+    assert_eq!(Some(control.checksum), slice.checksum());
+    assert_eq!(Some(control.checksum), slice.calc_checksum());
+    assert_eq!(Some(&[1, 2, 3, 4, 5, 6, 7, 8, 9, 10][..]), slice.message_data());
+    assert_eq!(header, slice.to_header());
+
+    // errors
+    control.message_type = 0x80;
+    assert_eq!(
+        Err(ValueError::U8TooLarge{ value: 0x80, max: 0x7f, field: ErrorField::Shim6MessageType }),
+        control.to_bytes()
+    );
+    control.message_type = 0;
+    control.type_specific = 0x80;
+    assert_eq!(
+        Err(ValueError::U8TooLarge{ value: 0x80, max: 0x7f, field: ErrorField::Shim6TypeSpecific }),
+        control.to_bytes()
+    );
+    assert_eq!(
+        Err(ValueError::Ipv6ExtensionPayloadLengthUnaligned(7)),
+        Shim6ControlHeader::new(0, 0, 0, &[0;3])
+    );
+}
+
+#[test]
+fn from_slice_errors() {
+    assert_matches!(
+        Shim6HeaderSlice::from_slice(&[0;7]),
+        Err(ReadError::UnexpectedEndOfSlice(8))
+    );
+    assert_matches!(
+        Shim6HeaderSlice::from_slice(&[0, 1, 0, 0, 0, 0, 0, 0]),
+        Err(ReadError::UnexpectedEndOfSlice(16))
+    );
+}
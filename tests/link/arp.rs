@@ -0,0 +1,109 @@
+use super::super::*;
+
+fn request() -> ArpEthIpv4Packet {
+    ArpEthIpv4Packet {
+        operation: arp_operation::REQUEST,
+        sender_hw_addr: [1,2,3,4,5,6],
+        sender_protocol_addr: [192,168,1,1],
+        target_hw_addr: [0;6],
+        target_protocol_addr: [192,168,1,2],
+    }
+}
+
+#[test]
+fn to_bytes_from_slice() {
+    let packet = request();
+    let bytes = packet.to_bytes();
+    assert_eq!(
+        [
+            0, 1, 0x08, 0x00, 6, 4, 0, 1,
+            1, 2, 3, 4, 5, 6, 192, 168, 1, 1,
+            0, 0, 0, 0, 0, 0, 192, 168, 1, 2,
+        ],
+        bytes
+    );
+    assert_eq!(ArpEthIpv4Packet::SERIALIZED_SIZE, packet.header_len());
+
+    // write
+    let mut buffer = Vec::new();
+    packet.write(&mut buffer).unwrap();
+    assert_eq!(&bytes[..], &buffer[..]);
+
+    // from_slice (ethernet padding is returned as rest)
+    buffer.extend_from_slice(&[0;18]);
+    let (decoded, rest) = ArpEthIpv4Packet::from_slice(&buffer).unwrap();
+    assert_eq!(packet, decoded);
+    assert_eq!(&[0;18], rest);
+    assert_eq!(packet.clone(), decoded);
+    assert!(!format!("{:?}", packet).is_empty());
+}
+
+#[test]
+fn from_slice_errors() {
+    use ReadError::*;
+    let bytes = request().to_bytes();
+    for len in 0..8 {
+        assert_matches!(ArpEthIpv4Packet::from_slice(&bytes[..len]), Err(UnexpectedEndOfSlice(8)));
+    }
+    for len in 8..28 {
+        assert_matches!(ArpEthIpv4Packet::from_slice(&bytes[..len]), Err(UnexpectedEndOfSlice(28)));
+    }
+
+    // unsupported address types
+    let mut bytes = bytes;
+    bytes[2] = 0x86;
+    bytes[3] = 0xdd;
+    assert_matches!(
+        ArpEthIpv4Packet::from_slice(&bytes),
+        Err(ArpUnsupportedAddressType{ hardware_type: 1, protocol_type: 0x86dd })
+    );
+}
+
+#[test]
+fn slice() {
+    // ethernet & ipv4
+    {
+        let bytes = request().to_bytes();
+        let slice = ArpPacketSlice::from_slice(&bytes).unwrap();
+        assert_eq!(&bytes[..], slice.slice());
+        assert_eq!(arp_hardware_type::ETHERNET, slice.hardware_type());
+        assert_eq!(ether_type::IPV4, slice.protocol_type());
+        assert_eq!(6, slice.hw_addr_len());
+        assert_eq!(4, slice.protocol_addr_len());
+        assert_eq!(arp_operation::REQUEST, slice.operation());
+        assert_eq!(&[1,2,3,4,5,6], slice.sender_hw_addr());
+        assert_eq!(&[192,168,1,1], slice.sender_protocol_addr());
+        assert_eq!(&[0;6], slice.target_hw_addr());
+        assert_eq!(&[192,168,1,2], slice.target_protocol_addr());
+        assert_eq!(Some(request()), slice.to_eth_ipv4());
+        assert_eq!(slice.clone(), slice);
+    }
+
+    // other address types
+    {
+        let bytes = [
+            0, 2, 0x12, 0x34, 2, 3, 0, 2,
+            1, 2, 3, 4, 5,
+            6, 7, 8, 9, 10,
+            0xff,
+        ];
+        let slice = ArpPacketSlice::from_slice(&bytes).unwrap();
+        assert_eq!(&bytes[..18], slice.slice());
+        assert_eq!(2, slice.hardware_type());
+        assert_eq!(0x1234, slice.protocol_type());
+        assert_eq!(arp_operation::REPLY, slice.operation());
+        assert_eq!(&[1,2], slice.sender_hw_addr());
+        assert_eq!(&[3,4,5], slice.sender_protocol_addr());
+        assert_eq!(&[6,7], slice.target_hw_addr());
+        assert_eq!(&[8,9,10], slice.target_protocol_addr());
+        assert_eq!(None, slice.to_eth_ipv4());
+    }
+
+    // ethernet & ipv4 types with other lengths
+    {
+        let mut bytes = request().to_bytes().to_vec();
+        bytes[5] = 5;
+        bytes.extend_from_slice(&[0, 0]);
+        assert_eq!(None, ArpPacketSlice::from_slice(&bytes).unwrap().to_eth_ipv4());
+    }
+}
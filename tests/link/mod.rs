@@ -1,3 +1,4 @@
+pub mod arp;
 pub mod ethernet;
 pub mod vlan_tagging;
 
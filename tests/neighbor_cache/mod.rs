@@ -0,0 +1,577 @@
+use super::*;
+
+const LOCAL_MAC: [u8;6] = [2,0,0,0,0,1];
+const REMOTE_MAC: [u8;6] = [2,0,0,0,0,2];
+const LOCAL_IPV4: [u8;4] = [192,168,1,1];
+const REMOTE_IPV4: [u8;4] = [192,168,1,2];
+const LOCAL_IPV6: [u8;16] = [0xfe,0x80,0,0,0,0,0,0,0,0,0,0,0,0,0,1];
+const REMOTE_IPV6: [u8;16] = [0xfe,0x80,0,0,0,0,0,0,0,0,0,0,0x12,0x34,0x56,0x78];
+
+fn cache() -> NeighborCache {
+    let mut result = NeighborCache::new(LOCAL_MAC, NeighborCacheConfig::default());
+    result.add_ipv4_address(LOCAL_IPV4);
+    result.add_ipv6_address(LOCAL_IPV6);
+    result
+}
+
+fn arp_frame(destination_mac: [u8;6], operation: u16, sender_mac: [u8;6], sender_ip: [u8;4], target_ip: [u8;4]) -> Vec<u8> {
+    let mut result = Vec::new();
+    Ethernet2Header{
+        source: sender_mac,
+        destination: destination_mac,
+        ether_type: ether_type::ARP,
+    }.write(&mut result).unwrap();
+    ArpEthIpv4Packet{
+        operation,
+        sender_hw_addr: sender_mac,
+        sender_protocol_addr: sender_ip,
+        target_hw_addr: if arp_operation::REPLY == operation { LOCAL_MAC } else { [0;6] },
+        target_protocol_addr: target_ip,
+    }.write(&mut result).unwrap();
+    result
+}
+
+fn ndp_frame(source: [u8;16], destination: [u8;16], hop_limit: u8, icmp_type: Icmpv6Type, options: &[u8]) -> Vec<u8> {
+    let builder = PacketBuilder::ethernet2(REMOTE_MAC, [0x33,0x33,0,0,0,1])
+        .ipv6(source, destination, hop_limit)
+        .icmpv6(icmp_type);
+    let mut result = Vec::new();
+    builder.write(&mut result, options).unwrap();
+    result
+}
+
+fn neighbor_solicitation(source: [u8;16], target: [u8;16], options: &[u8]) -> Vec<u8> {
+    ndp_frame(
+        source,
+        [0xff,0x02,0,0,0,0,0,0,0,0,0,0x01,0xff,target[13],target[14],target[15]],
+        255,
+        Icmpv6Type::NeighborSolicitation(icmpv6::NeighborSolicitationHeader{ target_address: target }),
+        options
+    )
+}
+
+fn neighbor_advertisement(solicited: bool, override_flag: bool, link_address: Option<[u8;6]>) -> Vec<u8> {
+    let mut options = Vec::new();
+    if let Some(value) = link_address {
+        options.extend_from_slice(&[ndp_option::TYPE_TARGET_LINK_LAYER_ADDRESS, 1]);
+        options.extend_from_slice(&value);
+    }
+    ndp_frame(
+        REMOTE_IPV6,
+        LOCAL_IPV6,
+        255,
+        Icmpv6Type::NeighborAdvertisement(icmpv6::NeighborAdvertisementHeader{
+            router: false,
+            solicited,
+            override_flag,
+            target_address: REMOTE_IPV6,
+        }),
+        &options
+    )
+}
+
+/// Checks the ipv6 & icmpv6 part of a frame sent by the cache and returns
+/// the ICMPv6 type.
+fn decode_ndp(frame: &[u8], source: [u8;16], destination: [u8;16], destination_mac: [u8;6]) -> (Icmpv6Type, Vec<u8>) {
+    let sliced = SlicedPacket::from_ethernet(frame).unwrap();
+    match sliced.link.unwrap() {
+        LinkSlice::Ethernet2(eth) => {
+            assert_eq!(LOCAL_MAC, eth.source());
+            assert_eq!(destination_mac, eth.destination());
+        }
+    }
+    match sliced.ip.unwrap() {
+        InternetSlice::Ipv6(ip, _) => {
+            assert_eq!(255, ip.hop_limit());
+            assert_eq!(source, ip.source());
+            assert_eq!(destination, ip.destination());
+        },
+        _ => panic!("expected ipv6 header"),
+    }
+    match sliced.transport.unwrap() {
+        TransportSlice::Icmpv6(icmp) => {
+            assert!(icmp.is_checksum_valid(source, destination));
+            (icmp.icmp_type(), icmp.payload().to_vec())
+        },
+        _ => panic!("expected icmpv6 message"),
+    }
+}
+
+#[test]
+fn config_default() {
+    let config = NeighborCacheConfig::default();
+    assert_eq!(30_000, config.reachable_time);
+    assert_eq!(1_000, config.retrans_timer);
+    assert_eq!(5_000, config.delay_first_probe_time);
+    assert_eq!(3, config.max_multicast_solicit);
+    assert_eq!(3, config.max_unicast_solicit);
+    assert_eq!(1024, config.max_entries);
+    assert_eq!(config.clone(), config);
+    assert!(!format!("{:?}", config).is_empty());
+}
+
+#[test]
+fn addresses() {
+    let mut cache = cache();
+    cache.add_ipv4_address(LOCAL_IPV4);
+    cache.add_ipv4_address([10,0,0,1]);
+    cache.add_ipv6_address(LOCAL_IPV6);
+    assert_eq!(LOCAL_MAC, cache.link_address());
+    assert_eq!(&NeighborCacheConfig::default(), cache.config());
+    assert_eq!(&[LOCAL_IPV4, [10,0,0,1]], cache.ipv4_addresses());
+    assert_eq!(&[LOCAL_IPV6], cache.ipv6_addresses());
+
+    // multicast & broadcast addresses are mapped directly
+    assert_eq!(Some([0xff;6]), cache.resolve_ipv4([0xff;4], 0));
+    assert_eq!(Some([0x01,0x00,0x5e,0x7f,0x00,0x01]), cache.resolve_ipv4([239,255,0,1], 0));
+    assert_eq!(
+        Some([0x33,0x33,0,0,0,1]),
+        cache.resolve_ipv6([0xff,0x02,0,0,0,0,0,0,0,0,0,0,0,0,0,1], 0)
+    );
+    assert_eq!(None, cache.next_timer());
+}
+
+#[test]
+fn max_entries() {
+    let mut cache = NeighborCache::new(
+        LOCAL_MAC,
+        NeighborCacheConfig{
+            max_entries: 2,
+            ..Default::default()
+        }
+    );
+    cache.add_ipv4_address(LOCAL_IPV4);
+
+    // the least recently used entry gets removed
+    assert_eq!(None, cache.resolve_ipv4([10,0,0,1], 0));
+    assert_eq!(None, cache.resolve_ipv4([10,0,0,2], 1));
+    assert_eq!(None, cache.resolve_ipv4([10,0,0,1], 2));
+    assert_eq!(None, cache.resolve_ipv4([10,0,0,3], 3));
+    assert!(cache.ipv4_neighbor([10,0,0,1]).is_some());
+    assert_eq!(None, cache.ipv4_neighbor([10,0,0,2]));
+    assert!(cache.ipv4_neighbor([10,0,0,3]).is_some());
+
+    // unsolicited requests can not grow the cache beyond the limit
+    for i in 0..10u8 {
+        let request = arp_frame([0xff;6], arp_operation::REQUEST, [2,0,0,0,1,i], [10,0,1,i], LOCAL_IPV4);
+        assert!(cache.receive(&SlicedPacket::from_ethernet(&request).unwrap(), 10 + u64::from(i)).is_some());
+    }
+    assert_eq!(None, cache.ipv4_neighbor([10,0,0,1]));
+    assert_eq!(None, cache.ipv4_neighbor([10,0,0,3]));
+    assert_eq!(None, cache.ipv4_neighbor([10,0,1,7]));
+    assert_eq!(Some([2,0,0,0,1,8]), cache.ipv4_neighbor([10,0,1,8]).unwrap().link_address);
+    assert_eq!(Some([2,0,0,0,1,9]), cache.ipv4_neighbor([10,0,1,9]).unwrap().link_address);
+
+    // ipv6 entries are limited separately
+    for i in 0..3u8 {
+        let mut address = REMOTE_IPV6;
+        address[15] = i;
+        assert_eq!(None, cache.resolve_ipv6(address, 20));
+    }
+    assert!(cache.ipv4_neighbor([10,0,1,9]).is_some());
+    assert_eq!(
+        2,
+        (0..3u8).filter(|i| {
+            let mut address = REMOTE_IPV6;
+            address[15] = *i;
+            cache.ipv6_neighbor(address).is_some()
+        }).count()
+    );
+
+    // a maximum of 0 is treated like 1
+    let mut cache = NeighborCache::new(
+        LOCAL_MAC,
+        NeighborCacheConfig{
+            max_entries: 0,
+            ..Default::default()
+        }
+    );
+    assert_eq!(None, cache.resolve_ipv4([10,0,0,1], 0));
+    assert!(cache.ipv4_neighbor([10,0,0,1]).is_some());
+    assert_eq!(None, cache.resolve_ipv4([10,0,0,2], 0));
+    assert_eq!(None, cache.ipv4_neighbor([10,0,0,1]));
+    assert!(cache.ipv4_neighbor([10,0,0,2]).is_some());
+}
+
+#[test]
+fn arp_reply_to_request() {
+    let mut cache = cache();
+
+    // request for the local address
+    {
+        let request = arp_frame([0xff;6], arp_operation::REQUEST, REMOTE_MAC, REMOTE_IPV4, LOCAL_IPV4);
+        let reply = cache.receive(&SlicedPacket::from_ethernet(&request).unwrap(), 0).unwrap();
+        assert_eq!(arp_frame(REMOTE_MAC, arp_operation::REPLY, LOCAL_MAC, LOCAL_IPV4, REMOTE_IPV4)[..14], reply[..14]);
+        let (arp, rest) = ArpEthIpv4Packet::from_slice(&reply[14..]).unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(
+            ArpEthIpv4Packet{
+                operation: arp_operation::REPLY,
+                sender_hw_addr: LOCAL_MAC,
+                sender_protocol_addr: LOCAL_IPV4,
+                target_hw_addr: REMOTE_MAC,
+                target_protocol_addr: REMOTE_IPV4,
+            },
+            arp
+        );
+
+        // the sender was learned
+        assert_eq!(
+            Some(&NeighborEntry{
+                link_address: Some(REMOTE_MAC),
+                state: NeighborState::Stale,
+                timer: None,
+                solicitations_sent: 0,
+            }),
+            cache.ipv4_neighbor(REMOTE_IPV4)
+        );
+    }
+
+    // request with a vlan tag
+    {
+        let arp = ArpEthIpv4Packet{
+            operation: arp_operation::REQUEST,
+            sender_hw_addr: REMOTE_MAC,
+            sender_protocol_addr: REMOTE_IPV4,
+            target_hw_addr: [0;6],
+            target_protocol_addr: LOCAL_IPV4,
+        };
+        let mut request = Vec::new();
+        Ethernet2Header{
+            source: REMOTE_MAC,
+            destination: [0xff;6],
+            ether_type: ether_type::VLAN_TAGGED_FRAME,
+        }.write(&mut request).unwrap();
+        SingleVlanHeader{
+            priority_code_point: 0,
+            drop_eligible_indicator: false,
+            vlan_identifier: 12,
+            ether_type: ether_type::ARP,
+        }.write(&mut request).unwrap();
+        arp.write(&mut request).unwrap();
+
+        let reply = cache.receive(&SlicedPacket::from_ethernet(&request).unwrap(), 0).unwrap();
+        let sliced = SlicedPacket::from_ethernet(&reply).unwrap();
+        assert_eq!(request[12..18], reply[12..18]);
+        assert_eq!(Some(ether_type::ARP), sliced.payload_ether_type());
+        assert_eq!(
+            arp_operation::REPLY,
+            ArpEthIpv4Packet::from_slice(sliced.payload).unwrap().0.operation
+        );
+    }
+
+    // request for a different address is not answered & not learned
+    {
+        let request = arp_frame([0xff;6], arp_operation::REQUEST, [2,0,0,0,0,3], [192,168,1,3], [192,168,1,4]);
+        assert_eq!(None, cache.receive(&SlicedPacket::from_ethernet(&request).unwrap(), 0));
+        assert_eq!(None, cache.ipv4_neighbor([192,168,1,3]));
+    }
+
+    // but known entries are updated
+    {
+        let request = arp_frame([0xff;6], arp_operation::REQUEST, [2,0,0,0,0,4], REMOTE_IPV4, [192,168,1,4]);
+        assert_eq!(None, cache.receive(&SlicedPacket::from_ethernet(&request).unwrap(), 0));
+        assert_eq!(Some([2,0,0,0,0,4]), cache.ipv4_neighbor(REMOTE_IPV4).unwrap().link_address);
+    }
+
+    // own packets, probes & non arp packets are ignored
+    {
+        let request = arp_frame([0xff;6], arp_operation::REQUEST, LOCAL_MAC, LOCAL_IPV4, LOCAL_IPV4);
+        assert_eq!(None, cache.receive(&SlicedPacket::from_ethernet(&request).unwrap(), 0));
+
+        let probe = arp_frame([0xff;6], arp_operation::REQUEST, [2,0,0,0,0,5], [0;4], LOCAL_IPV4);
+        assert!(cache.receive(&SlicedPacket::from_ethernet(&probe).unwrap(), 0).is_some());
+        assert_eq!(None, cache.ipv4_neighbor([0;4]));
+
+        let mut truncated = arp_frame([0xff;6], arp_operation::REQUEST, REMOTE_MAC, REMOTE_IPV4, LOCAL_IPV4);
+        truncated.truncate(20);
+        assert_eq!(None, cache.receive(&SlicedPacket::from_ethernet(&truncated).unwrap(), 0));
+
+        let builder = PacketBuilder::ethernet2(REMOTE_MAC, LOCAL_MAC)
+            .ipv4(REMOTE_IPV4, LOCAL_IPV4, 20)
+            .udp(1, 2);
+        let mut udp = Vec::new();
+        builder.write(&mut udp, &[]).unwrap();
+        assert_eq!(None, cache.receive(&SlicedPacket::from_ethernet(&udp).unwrap(), 0));
+    }
+}
+
+#[test]
+fn arp_resolve() {
+    let mut cache = cache();
+    let broadcast_request = arp_frame([0xff;6], arp_operation::REQUEST, LOCAL_MAC, LOCAL_IPV4, REMOTE_IPV4);
+
+    // failed resolution
+    {
+        assert_eq!(None, cache.resolve_ipv4(REMOTE_IPV4, 100));
+        assert_eq!(Some(100), cache.next_timer());
+        assert_eq!(vec![broadcast_request.clone()], cache.poll(100));
+        assert_eq!(Some(1100), cache.next_timer());
+        assert!(cache.poll(1099).is_empty());
+        assert_eq!(vec![broadcast_request.clone()], cache.poll(1100));
+        assert_eq!(vec![broadcast_request.clone()], cache.poll(2100));
+        assert_eq!(NeighborState::Incomplete, cache.ipv4_neighbor(REMOTE_IPV4).unwrap().state);
+        assert_eq!(3, cache.ipv4_neighbor(REMOTE_IPV4).unwrap().solicitations_sent);
+        assert!(cache.poll(3100).is_empty());
+        assert_eq!(None, cache.ipv4_neighbor(REMOTE_IPV4));
+        assert_eq!(None, cache.next_timer());
+    }
+
+    // successful resolution
+    {
+        assert_eq!(None, cache.resolve_ipv4(REMOTE_IPV4, 0));
+        assert_eq!(1, cache.poll(0).len());
+        // repeated lookups do not trigger additional requests
+        assert_eq!(None, cache.resolve_ipv4(REMOTE_IPV4, 10));
+        assert!(cache.poll(10).is_empty());
+
+        let reply = arp_frame(LOCAL_MAC, arp_operation::REPLY, REMOTE_MAC, REMOTE_IPV4, LOCAL_IPV4);
+        assert_eq!(None, cache.receive(&SlicedPacket::from_ethernet(&reply).unwrap(), 20));
+        assert_eq!(
+            Some(&NeighborEntry{
+                link_address: Some(REMOTE_MAC),
+                state: NeighborState::Reachable,
+                timer: Some(30_020),
+                solicitations_sent: 0,
+            }),
+            cache.ipv4_neighbor(REMOTE_IPV4)
+        );
+        assert_eq!(Some(REMOTE_MAC), cache.resolve_ipv4(REMOTE_IPV4, 30));
+    }
+
+    // reachable -> stale -> delay -> probe -> reachable
+    {
+        assert!(cache.poll(30_020).is_empty());
+        assert_eq!(NeighborState::Stale, cache.ipv4_neighbor(REMOTE_IPV4).unwrap().state);
+        assert_eq!(None, cache.next_timer());
+
+        assert_eq!(Some(REMOTE_MAC), cache.resolve_ipv4(REMOTE_IPV4, 40_000));
+        assert_eq!(NeighborState::Delay, cache.ipv4_neighbor(REMOTE_IPV4).unwrap().state);
+        assert_eq!(Some(45_000), cache.next_timer());
+
+        let unicast_request = arp_frame(REMOTE_MAC, arp_operation::REQUEST, LOCAL_MAC, LOCAL_IPV4, REMOTE_IPV4);
+        assert_eq!(vec![unicast_request.clone()], cache.poll(45_000));
+        assert_eq!(NeighborState::Probe, cache.ipv4_neighbor(REMOTE_IPV4).unwrap().state);
+        assert_eq!(Some(REMOTE_MAC), cache.resolve_ipv4(REMOTE_IPV4, 45_500));
+
+        let reply = arp_frame(LOCAL_MAC, arp_operation::REPLY, REMOTE_MAC, REMOTE_IPV4, LOCAL_IPV4);
+        assert_eq!(None, cache.receive(&SlicedPacket::from_ethernet(&reply).unwrap(), 45_600));
+        assert_eq!(NeighborState::Reachable, cache.ipv4_neighbor(REMOTE_IPV4).unwrap().state);
+    }
+
+    // failed probing
+    {
+        assert!(cache.poll(75_600).is_empty());
+        assert_eq!(Some(REMOTE_MAC), cache.resolve_ipv4(REMOTE_IPV4, 80_000));
+        let unicast_request = arp_frame(REMOTE_MAC, arp_operation::REQUEST, LOCAL_MAC, LOCAL_IPV4, REMOTE_IPV4);
+        assert_eq!(vec![unicast_request.clone()], cache.poll(85_000));
+        assert_eq!(vec![unicast_request.clone()], cache.poll(86_000));
+        assert_eq!(vec![unicast_request.clone()], cache.poll(87_000));
+        assert!(cache.poll(88_000).is_empty());
+        assert_eq!(None, cache.ipv4_neighbor(REMOTE_IPV4));
+    }
+
+    // requests without a local address
+    {
+        let mut cache = NeighborCache::new(LOCAL_MAC, NeighborCacheConfig::default());
+        assert_eq!(None, cache.resolve_ipv4(REMOTE_IPV4, 0));
+        assert_eq!(
+            vec![arp_frame([0xff;6], arp_operation::REQUEST, LOCAL_MAC, [0;4], REMOTE_IPV4)],
+            cache.poll(0)
+        );
+    }
+}
+
+#[test]
+fn ndp_reply_to_solicitation() {
+    let mut cache = cache();
+    let source_link_address_option = [ndp_option::TYPE_SOURCE_LINK_LAYER_ADDRESS, 1, 2, 0, 0, 0, 0, 9];
+    let target_link_address_option = [ndp_option::TYPE_TARGET_LINK_LAYER_ADDRESS, 1, 2, 0, 0, 0, 0, 1];
+
+    // solicitation with source link-layer address option
+    {
+        let request = neighbor_solicitation(REMOTE_IPV6, LOCAL_IPV6, &source_link_address_option);
+        let reply = cache.receive(&SlicedPacket::from_ethernet(&request).unwrap(), 0).unwrap();
+        let (icmp_type, payload) = decode_ndp(&reply, LOCAL_IPV6, REMOTE_IPV6, [2,0,0,0,0,9]);
+        assert_eq!(
+            Icmpv6Type::NeighborAdvertisement(icmpv6::NeighborAdvertisementHeader{
+                router: false,
+                solicited: true,
+                override_flag: true,
+                target_address: LOCAL_IPV6,
+            }),
+            icmp_type
+        );
+        assert_eq!(&target_link_address_option[..], &payload[..]);
+        assert_eq!(
+            Some(&NeighborEntry{
+                link_address: Some([2,0,0,0,0,9]),
+                state: NeighborState::Stale,
+                timer: None,
+                solicitations_sent: 0,
+            }),
+            cache.ipv6_neighbor(REMOTE_IPV6)
+        );
+    }
+
+    // solicitation without option is answered to the ethernet source
+    {
+        let mut cache = self::cache();
+        let request = neighbor_solicitation(REMOTE_IPV6, LOCAL_IPV6, &[]);
+        let reply = cache.receive(&SlicedPacket::from_ethernet(&request).unwrap(), 0).unwrap();
+        decode_ndp(&reply, LOCAL_IPV6, REMOTE_IPV6, REMOTE_MAC);
+        assert_eq!(None, cache.ipv6_neighbor(REMOTE_IPV6));
+
+        // not answered if there is no link-layer address at all
+        let sliced = SlicedPacket::from_ethernet(&request).unwrap();
+        let without_link = SlicedPacket{
+            link: None,
+            ..sliced
+        };
+        assert_eq!(None, cache.receive(&without_link, 0));
+    }
+
+    // duplicate address detection
+    {
+        let request = neighbor_solicitation([0;16], LOCAL_IPV6, &[]);
+        let reply = cache.receive(&SlicedPacket::from_ethernet(&request).unwrap(), 0).unwrap();
+        let all_nodes = [0xff,0x02,0,0,0,0,0,0,0,0,0,0,0,0,0,1];
+        let (icmp_type, _) = decode_ndp(&reply, LOCAL_IPV6, all_nodes, [0x33,0x33,0,0,0,1]);
+        assert_eq!(
+            Icmpv6Type::NeighborAdvertisement(icmpv6::NeighborAdvertisementHeader{
+                router: false,
+                solicited: false,
+                override_flag: true,
+                target_address: LOCAL_IPV6,
+            }),
+            icmp_type
+        );
+    }
+
+    // ignored solicitations
+    {
+        let mut cache = self::cache();
+        // different target
+        let request = neighbor_solicitation(REMOTE_IPV6, REMOTE_IPV6, &source_link_address_option);
+        assert_eq!(None, cache.receive(&SlicedPacket::from_ethernet(&request).unwrap(), 0));
+        // bad hop limit
+        let request = ndp_frame(
+            REMOTE_IPV6,
+            LOCAL_IPV6,
+            64,
+            Icmpv6Type::NeighborSolicitation(icmpv6::NeighborSolicitationHeader{ target_address: LOCAL_IPV6 }),
+            &source_link_address_option
+        );
+        assert_eq!(None, cache.receive(&SlicedPacket::from_ethernet(&request).unwrap(), 0));
+        // bad checksum
+        let mut request = neighbor_solicitation(REMOTE_IPV6, LOCAL_IPV6, &source_link_address_option);
+        let len = request.len();
+        request[len - 1] ^= 0xff;
+        assert_eq!(None, cache.receive(&SlicedPacket::from_ethernet(&request).unwrap(), 0));
+        // other icmpv6 messages
+        let request = ndp_frame(
+            REMOTE_IPV6,
+            LOCAL_IPV6,
+            255,
+            Icmpv6Type::EchoRequest(IcmpEchoHeader{ id: 1, seq: 2 }),
+            &[]
+        );
+        assert_eq!(None, cache.receive(&SlicedPacket::from_ethernet(&request).unwrap(), 0));
+        assert_eq!(None, cache.ipv6_neighbor(REMOTE_IPV6));
+    }
+}
+
+#[test]
+fn ndp_resolve() {
+    let mut cache = cache();
+    let solicited_node = [0xff,0x02,0,0,0,0,0,0,0,0,0,0x01,0xff,0x34,0x56,0x78];
+
+    // multicast solicitation
+    {
+        assert_eq!(None, cache.resolve_ipv6(REMOTE_IPV6, 0));
+        let frames = cache.poll(0);
+        assert_eq!(1, frames.len());
+        let (icmp_type, payload) = decode_ndp(&frames[0], LOCAL_IPV6, solicited_node, [0x33,0x33,0xff,0x34,0x56,0x78]);
+        assert_eq!(
+            Icmpv6Type::NeighborSolicitation(icmpv6::NeighborSolicitationHeader{ target_address: REMOTE_IPV6 }),
+            icmp_type
+        );
+        assert_eq!(&[ndp_option::TYPE_SOURCE_LINK_LAYER_ADDRESS, 1, 2, 0, 0, 0, 0, 1], &payload[..]);
+    }
+
+    // advertisements without link-layer address are ignored while incomplete
+    {
+        let advertisement = neighbor_advertisement(true, true, None);
+        assert_eq!(None, cache.receive(&SlicedPacket::from_ethernet(&advertisement).unwrap(), 10));
+        assert_eq!(NeighborState::Incomplete, cache.ipv6_neighbor(REMOTE_IPV6).unwrap().state);
+    }
+
+    // solicited advertisement
+    {
+        let advertisement = neighbor_advertisement(true, false, Some(REMOTE_MAC));
+        assert_eq!(None, cache.receive(&SlicedPacket::from_ethernet(&advertisement).unwrap(), 10));
+        assert_eq!(
+            Some(&NeighborEntry{
+                link_address: Some(REMOTE_MAC),
+                state: NeighborState::Reachable,
+                timer: Some(30_010),
+                solicitations_sent: 0,
+            }),
+            cache.ipv6_neighbor(REMOTE_IPV6)
+        );
+        assert_eq!(Some(REMOTE_MAC), cache.resolve_ipv6(REMOTE_IPV6, 20));
+    }
+
+    // advertisement with a different address without override flag
+    {
+        let advertisement = neighbor_advertisement(true, false, Some([2,0,0,0,0,7]));
+        cache.receive(&SlicedPacket::from_ethernet(&advertisement).unwrap(), 30);
+        let entry = cache.ipv6_neighbor(REMOTE_IPV6).unwrap();
+        assert_eq!(NeighborState::Stale, entry.state);
+        assert_eq!(Some(REMOTE_MAC), entry.link_address);
+    }
+
+    // probing a stale entry (unicast solicitation)
+    {
+        assert_eq!(Some(REMOTE_MAC), cache.resolve_ipv6(REMOTE_IPV6, 100));
+        let frames = cache.poll(5_100);
+        assert_eq!(1, frames.len());
+        let (icmp_type, _) = decode_ndp(&frames[0], LOCAL_IPV6, REMOTE_IPV6, REMOTE_MAC);
+        assert_eq!(
+            Icmpv6Type::NeighborSolicitation(icmpv6::NeighborSolicitationHeader{ target_address: REMOTE_IPV6 }),
+            icmp_type
+        );
+        assert_eq!(NeighborState::Probe, cache.ipv6_neighbor(REMOTE_IPV6).unwrap().state);
+
+        // solicited advertisement without link-layer address confirms the entry
+        let advertisement = neighbor_advertisement(true, false, None);
+        cache.receive(&SlicedPacket::from_ethernet(&advertisement).unwrap(), 5_200);
+        assert_eq!(NeighborState::Reachable, cache.ipv6_neighbor(REMOTE_IPV6).unwrap().state);
+    }
+
+    // unsolicited advertisement with override flag
+    {
+        let advertisement = neighbor_advertisement(false, true, Some([2,0,0,0,0,7]));
+        cache.receive(&SlicedPacket::from_ethernet(&advertisement).unwrap(), 5_300);
+        let entry = cache.ipv6_neighbor(REMOTE_IPV6).unwrap();
+        assert_eq!(NeighborState::Stale, entry.state);
+        assert_eq!(Some([2,0,0,0,0,7]), entry.link_address);
+    }
+
+    // advertisements for unknown addresses are ignored
+    {
+        let mut cache = self::cache();
+        let advertisement = neighbor_advertisement(false, true, Some(REMOTE_MAC));
+        cache.receive(&SlicedPacket::from_ethernet(&advertisement).unwrap(), 0);
+        assert_eq!(None, cache.ipv6_neighbor(REMOTE_IPV6));
+    }
+
+    // solicitation without a local address
+    {
+        let mut cache = NeighborCache::new(LOCAL_MAC, NeighborCacheConfig::default());
+        assert_eq!(None, cache.resolve_ipv6(REMOTE_IPV6, 0));
+        let frames = cache.poll(0);
+        let (_, payload) = decode_ndp(&frames[0], [0;16], solicited_node, [0x33,0x33,0xff,0x34,0x56,0x78]);
+        assert!(payload.is_empty());
+    }
+}
@@ -174,6 +174,9 @@ fn ipv6() {
                     routing: None,
                     fragment: None,
                     auth: Some(auth_ext.clone()),
+                    mobility: None,
+                    hip: None,
+                    shim6: None,
                 }
             )
         );
@@ -1088,7 +1091,10 @@ proptest! {
         ipv4_source in any::<[u8;4]>(),
         ipv4_dest in any::<[u8;4]>(),
         ipv4_time_to_live in any::<u8>(),
-        icmpv4_type_u8 in 15u8..u8::MAX,
+        icmpv4_type_u8 in (19u8..u8::MAX).prop_filter(
+            "type must be unknown",
+            |v| !(icmpv4::TYPE_EXT_ECHO_REQUEST..=icmpv4::TYPE_EXT_ECHO_REPLY).contains(v)
+        ),
         icmpv4_code_u8 in any::<u8>(),
         icmpv4_bytes5to8 in any::<[u8;4]>(),
         icmpv4 in icmpv4_type_any(),
@@ -1311,7 +1317,10 @@ proptest! {
         ipv6_source in any::<[u8;16]>(),
         ipv6_dest in any::<[u8;16]>(),
         ipv6_hop_limit in any::<u8>(),
-        icmpv4_type_u8 in 15u8..u8::MAX,
+        icmpv4_type_u8 in (19u8..u8::MAX).prop_filter(
+            "type must be unknown",
+            |v| !(icmpv4::TYPE_EXT_ECHO_REQUEST..=icmpv4::TYPE_EXT_ECHO_REPLY).contains(v)
+        ),
         icmpv4_code_u8 in any::<u8>(),
         icmpv4_bytes5to8 in any::<[u8;4]>(),
         icmpv4 in icmpv4_type_any(),
@@ -1558,5 +1567,350 @@ proptest! {
                 })
             );
         }
+
+        // icmpv6_router_advertisement
+        {
+            let header = icmpv6::RouterAdvertisementHeader{
+                cur_hop_limit: ipv6_hop_limit,
+                managed_address_config: true,
+                other_config: false,
+                router_lifetime: echo_id,
+                reachable_time: u32::from_be_bytes(icmpv6_bytes5to8),
+                retrans_timer: u32::from(echo_seq),
+            };
+            let builder = PacketBuilder::ethernet2([1,2,3,4,5,6],[7,8,9,10,11,12])
+                .ipv6(ipv6_source, ipv6_dest, ipv6_hop_limit)
+                .icmpv6_router_advertisement(header);
+
+            test_builder(
+                builder,
+                Icmpv6Type::RouterAdvertisement(header)
+            );
+        }
+
+        // icmpv6_neighbor_solicitation
+        {
+            let builder = PacketBuilder::ethernet2([1,2,3,4,5,6],[7,8,9,10,11,12])
+                .ipv6(ipv6_source, ipv6_dest, ipv6_hop_limit)
+                .icmpv6_neighbor_solicitation(ipv6_dest);
+
+            test_builder(
+                builder,
+                Icmpv6Type::NeighborSolicitation(
+                    icmpv6::NeighborSolicitationHeader{ target_address: ipv6_dest }
+                )
+            );
+        }
+
+        // icmpv6_neighbor_advertisement
+        {
+            let header = icmpv6::NeighborAdvertisementHeader{
+                router: false,
+                solicited: true,
+                override_flag: true,
+                target_address: ipv6_source,
+            };
+            let builder = PacketBuilder::ethernet2([1,2,3,4,5,6],[7,8,9,10,11,12])
+                .ipv6(ipv6_source, ipv6_dest, ipv6_hop_limit)
+                .icmpv6_neighbor_advertisement(header);
+
+            test_builder(
+                builder,
+                Icmpv6Type::NeighborAdvertisement(header)
+            );
+        }
     }
 }
+
+#[test]
+fn ipv6_udp_jumbogram() {
+    let payload = vec![0xab; 70000];
+    let builder = PacketBuilder::
+        ipv6(
+            [11,12,13,14,15,16,17,18,19,10,21,22,23,24,25,26],
+            [31,32,33,34,35,36,37,38,39,40,41,42,43,44,45,46],
+            47,
+        )
+        .udp(22,23);
+    let expected_size = builder.size(payload.len());
+    let mut serialized = Vec::new();
+    builder.write(&mut serialized, &payload).unwrap();
+    assert_eq!(expected_size, serialized.len());
+    // ipv6 header + hop by hop header (8) + udp header + payload
+    assert_eq!(40 + 8 + 8 + payload.len(), serialized.len());
+
+    let sliced = SlicedPacket::from_ip(&serialized).unwrap();
+    let (ip, ext) = match sliced.ip.unwrap() {
+        InternetSlice::Ipv6(ip, ext) => (ip, ext),
+        _ => panic!("expected ipv6"),
+    };
+    assert_eq!(0, ip.payload_length());
+    assert_eq!(ip_number::IPV6_HOP_BY_HOP, ip.next_header());
+    assert_eq!(Some((8 + 8 + payload.len()) as u32), ext.jumbo_payload_length());
+    assert_eq!(&payload[..], sliced.payload);
+
+    // udp length is zero & the checksum is calculated with the real length
+    let udp = match sliced.transport.unwrap() {
+        TransportSlice::Udp(udp) => udp,
+        _ => panic!("expected udp"),
+    };
+    assert_eq!(0, udp.length());
+    assert_eq!(
+        udp.checksum(),
+        udp.to_header().calc_checksum_ipv6_raw(ip.source(), ip.destination(), &payload).unwrap()
+    );
+}
+
+#[test]
+fn ipv6_tcp_jumbogram() {
+    let payload = vec![0xcd; 0x10000];
+    let mut serialized = Vec::new();
+    PacketBuilder::
+        ip(IpHeader::Version6(
+            Ipv6Header{
+                traffic_class: 0,
+                flow_label: 0,
+                payload_length: 0,
+                next_header: ip_number::IPV6_HOP_BY_HOP,
+                hop_limit: 47,
+                source: [1;16],
+                destination: [2;16],
+            },
+            Ipv6Extensions{
+                hop_by_hop_options: Some(
+                    Ipv6RawExtensionHeader::new_with_options(0, &[Ipv6OptionElement::RouterAlert(0)]).unwrap()
+                ),
+                ..Default::default()
+            }
+        ))
+        .tcp(22, 23, 1, 2)
+        .write(&mut serialized, &payload)
+        .unwrap();
+
+    let sliced = SlicedPacket::from_ip(&serialized).unwrap();
+    let (ip, ext) = match sliced.ip.unwrap() {
+        InternetSlice::Ipv6(ip, ext) => (ip, ext),
+        _ => panic!("expected ipv6"),
+    };
+    assert_eq!(0, ip.payload_length());
+    let hop_by_hop = Ipv6RawExtensionHeaderSlice::from_slice(ext.slice()).unwrap();
+    let options: Vec<_> = hop_by_hop.options_iterator()
+        .filter(|o| !matches!(o, Ok(Ipv6OptionElement::Pad1) | Ok(Ipv6OptionElement::PadN(_))))
+        .collect::<Result<_,_>>()
+        .unwrap();
+    let jumbo_len = (hop_by_hop.slice().len() + 20 + payload.len()) as u32;
+    assert_eq!(
+        vec![Ipv6OptionElement::RouterAlert(0), Ipv6OptionElement::JumboPayload(jumbo_len)],
+        options
+    );
+    let tcp = match sliced.transport.unwrap() {
+        TransportSlice::Tcp(tcp) => tcp,
+        _ => panic!("expected tcp"),
+    };
+    assert_eq!(
+        tcp.checksum(),
+        tcp.calc_checksum_ipv6(&ip, &payload).unwrap()
+    );
+}
+
+#[test]
+fn ipv6_jumbogram_fragmented() {
+    let payload = vec![0; 0x10000];
+    let mut serialized = Vec::new();
+    assert_matches!(
+        PacketBuilder::
+            ip(IpHeader::Version6(
+                Ipv6Header{
+                    traffic_class: 0,
+                    flow_label: 0,
+                    payload_length: 0,
+                    next_header: ip_number::IPV6_FRAG,
+                    hop_limit: 47,
+                    source: [1;16],
+                    destination: [2;16],
+                },
+                Ipv6Extensions{
+                    fragment: Some(Ipv6FragmentHeader::new(0, 0, false, 1)),
+                    ..Default::default()
+                }
+            ))
+            .udp(22, 23)
+            .write(&mut serialized, &payload),
+        Err(WriteError::ValueError(ValueError::Ipv6PayloadLengthTooLarge(_)))
+    );
+}
+
+#[test]
+fn ipv6_udp_segment_routing_checksum() {
+    let segments = [[1;16], [2;16]];
+    let payload = [1,2,3,4,5,6,7,8];
+    let mut serialized = Vec::new();
+    PacketBuilder::
+        ip(IpHeader::Version6(
+            Ipv6Header{
+                traffic_class: 0,
+                flow_label: 0,
+                payload_length: 0,
+                next_header: ip_number::IPV6_ROUTE,
+                hop_limit: 47,
+                source: [3;16],
+                destination: [2;16],
+            },
+            Ipv6Extensions{
+                routing: Some(Ipv6RoutingExtensions{
+                    routing: Ipv6RawExtensionHeader::new_with_routing(
+                        0,
+                        &Ipv6RoutingHeader::SegmentRouting{ segments_left: 1, flags: 0, tag: 0, segments: &segments, tlvs: &[] }
+                    ).unwrap(),
+                    final_destination_options: None,
+                }),
+                ..Default::default()
+            }
+        ))
+        .udp(22,23)
+        .write(&mut serialized, &payload)
+        .unwrap();
+
+    let sliced = SlicedPacket::from_ip(&serialized).unwrap();
+    let (ip, ext) = match sliced.ip.unwrap() {
+        InternetSlice::Ipv6(ip, ext) => (ip, ext),
+        _ => panic!("expected ipv6"),
+    };
+    assert_eq!([1;16], ext.effective_destination(ip.destination()));
+
+    // the checksum is calculated with the final destination
+    let udp = match sliced.transport.unwrap() {
+        TransportSlice::Udp(udp) => udp,
+        _ => panic!("expected udp"),
+    };
+    assert_eq!(
+        udp.checksum(),
+        udp.to_header().calc_checksum_ipv6_raw(ip.source(), [1;16], &payload).unwrap()
+    );
+}
+
+#[test]
+fn ipv6_icmpv6_mld_router_alert() {
+    let mut serialized = Vec::new();
+    PacketBuilder::
+        ipv6([1;16], [0xff;16], 1)
+        .icmpv6_multicast_listener_report([0xff;16])
+        .write(&mut serialized, &[])
+        .unwrap();
+
+    let decoded = PacketHeaders::from_ip_slice(&serialized).unwrap();
+    let (ip, ext) = match decoded.ip.unwrap() {
+        IpHeader::Version6(ip, ext) => (ip, ext),
+        _ => panic!("expected ipv6"),
+    };
+    assert_eq!(ip_number::IPV6_HOP_BY_HOP, ip.next_header);
+    assert_eq!(Some(ipv6_option::ROUTER_ALERT_MLD), ext.router_alert());
+    assert_eq!(
+        Icmpv6Type::MulticastListenerReport(icmpv6::MulticastListenerHeader{
+            maximum_response_delay: 0,
+            multicast_address: [0xff;16],
+        }),
+        decoded.transport.unwrap().icmpv6().unwrap().icmp_type
+    );
+    assert!(decoded.payload.is_empty());
+}
+
+#[test]
+fn ipv6_icmpv6_mld_router_alert_error() {
+    // hop by hop header without space left for the router alert option
+    let ext = Ipv6Extensions{
+        hop_by_hop_options: Some(Ipv6RawExtensionHeader::new_raw(
+            ip_number::IPV6_NO_NEXT_HEADER,
+            &[0;Ipv6RawExtensionHeader::MAX_PAYLOAD_LEN]
+        ).unwrap()),
+        ..Default::default()
+    };
+    let ip = Ipv6Header{
+        traffic_class: 0,
+        flow_label: 0,
+        payload_length: 0,
+        next_header: 0,
+        hop_limit: 1,
+        source: [1;16],
+        destination: [0xff;16],
+    };
+    let builder = PacketBuilder::
+        ip(IpHeader::Version6(ip, ext))
+        .icmpv6_multicast_listener_report([0xff;16]);
+    assert_matches!(
+        builder.write(&mut Vec::new(), &[]),
+        Err(WriteError::ValueError(_))
+    );
+}
+
+#[test]
+fn ipv4_igmp_router_alert() {
+    let mut serialized = Vec::new();
+    let builder = PacketBuilder::
+        ipv4([192,168,1,1], [224,0,0,2], 1)
+        .igmp(IgmpType::LeaveGroup{ group_address: [239,1,2,3] });
+    let size = builder.size(0);
+    builder.write(&mut serialized, &[]).unwrap();
+    assert_eq!(size, serialized.len());
+    assert_eq!(Ipv4Header::SERIALIZED_SIZE + 4 + 8, serialized.len());
+
+    let sliced = SlicedPacket::from_ip(&serialized).unwrap();
+    let ip = match sliced.ip.unwrap() {
+        InternetSlice::Ipv4(ip, _) => ip.to_header(),
+        _ => panic!("expected ipv4"),
+    };
+    assert_eq!(ip_number::IGMP, ip.protocol);
+    assert_eq!(Some(0), ip.router_alert());
+    let igmp = match sliced.transport.unwrap() {
+        TransportSlice::Igmp(igmp) => igmp,
+        _ => panic!("expected igmp"),
+    };
+    assert_eq!(IgmpType::LeaveGroup{ group_address: [239,1,2,3] }, igmp.igmp_type());
+    assert!(igmp.is_checksum_valid());
+}
+
+#[test]
+fn ipv4_igmp_router_alert_error() {
+    // options without space left for the router alert option
+    let mut ip = Ipv4Header::new(0, 1, ip_number::IGMP, [192,168,1,1], [224,0,0,1]);
+    ip.set_options(&[1;40]).unwrap();
+    let builder = PacketBuilder::
+        ip(IpHeader::Version4(ip, Default::default()))
+        .igmp(IgmpType::LeaveGroup{ group_address: [239,1,2,3] });
+    assert_matches!(
+        builder.write(&mut Vec::new(), &[]),
+        Err(WriteError::ValueError(_))
+    );
+}
+
+#[test]
+fn ipv4_igmp_padded_ethernet_frame() {
+    // IGMPv2 query padded to the minimum ethernet frame size
+    let mut serialized = Vec::new();
+    PacketBuilder::
+        ethernet2([1,2,3,4,5,6], [0x01,0,0x5e,0,0,1])
+        .ipv4([192,168,1,1], [224,0,0,1], 1)
+        .igmp(IgmpType::MembershipQuery(igmp::MembershipQueryHeader{
+            max_response_time: 100,
+            group_address: [0;4],
+        }))
+        .write(&mut serialized, &[])
+        .unwrap();
+    assert!(serialized.len() < 60);
+    serialized.resize(60, 0xee);
+
+    let sliced = SlicedPacket::from_ethernet(&serialized).unwrap();
+    let igmp = match sliced.transport.unwrap() {
+        TransportSlice::Igmp(igmp) => igmp,
+        _ => panic!("expected igmp"),
+    };
+    assert_eq!(
+        IgmpType::MembershipQuery(igmp::MembershipQueryHeader{
+            max_response_time: 100,
+            group_address: [0;4],
+        }),
+        igmp.igmp_type()
+    );
+    assert_eq!(IgmpHeader::MIN_SERIALIZED_SIZE, igmp.slice().len());
+    assert!(igmp.is_checksum_valid());
+}
@@ -59,6 +59,7 @@ impl ComponentTest {
         match &self.transport {
             Some(TransportHeader::Icmpv6(header)) => header.write(&mut buffer).unwrap(),
             Some(TransportHeader::Icmpv4(header)) => header.write(&mut buffer).unwrap(),
+            Some(TransportHeader::Igmp(header)) => header.write(&mut buffer).unwrap(),
             Some(TransportHeader::Udp(header)) => header.write(&mut buffer).unwrap(),
             Some(TransportHeader::Tcp(header)) => header.write(&mut buffer).unwrap(),
             None => {}
@@ -267,7 +268,14 @@ impl ComponentTest {
             }
         }
         if let Some(transport) = self.transport.as_ref() {
-            builder.add(transport.header_len());
+            match transport {
+                // slices too short for the extended part of a neighbor discovery
+                // or MLD header get decoded as an unknown 8 byte header
+                TransportHeader::Icmpv6(_) => builder.add(Icmpv6Header::MIN_SERIALIZED_SIZE),
+                // the same is true for IGMPv3 queries
+                TransportHeader::Igmp(_) => builder.add(IgmpHeader::MIN_SERIALIZED_SIZE),
+                _ => builder.add(transport.header_len()),
+            }
         }
 
         builder.result
@@ -325,6 +333,7 @@ impl ComponentTest {
             match result.transport.as_ref() {
                 Some(TransportSlice::Icmpv4(actual)) => Some(TransportHeader::Icmpv4(actual.header())),
                 Some(TransportSlice::Icmpv6(actual)) => Some(TransportHeader::Icmpv6(actual.header())),
+                Some(TransportSlice::Igmp(actual)) => Some(TransportHeader::Igmp(actual.header())),
                 Some(TransportSlice::Udp(actual)) => Some(TransportHeader::Udp(actual.to_header())),
                 Some(TransportSlice::Tcp(actual)) => Some(TransportHeader::Tcp(actual.to_header())),
                 Some(TransportSlice::Unknown(_)) => None,
@@ -351,6 +360,10 @@ impl ComponentTest {
                 assert_eq!(&self.payload[..], icmpv6.payload());
                 assert_eq!(0, result.payload.len());
             }
+            Some(TransportSlice::Igmp(igmp)) => {
+                assert_eq!(&self.payload[..], igmp.payload());
+                assert_eq!(0, result.payload.len());
+            }
             // for other cases
             _ => assert_eq!(&self.payload[..], &result.payload[..])
         }
@@ -368,6 +381,7 @@ impl ComponentTest {
         tcp: &TcpHeader,
         icmpv4: &Icmpv4Header,
         icmpv6: &Icmpv6Header,
+        igmp: &IgmpHeader,
     ) {
         let setup_single = | ether_type: u16| -> ComponentTest {
             let mut result = self.clone();
@@ -396,17 +410,18 @@ impl ComponentTest {
 
         //single
         setup_single(inner_vlan.ether_type).run();
-        setup_single(ether_type::IPV4).run_ipv4(ipv4, ipv4_ext, udp, tcp, icmpv4, icmpv6);
+        setup_single(ether_type::IPV4).run_ipv4(ipv4, ipv4_ext, udp, tcp, icmpv4, icmpv6, igmp);
         setup_single(ether_type::IPV6).run_ipv6(ipv6, ipv6_ext, udp, tcp, icmpv4, icmpv6);
 
         //double 
         for ether_type in VLAN_ETHER_TYPES {
             setup_double(*ether_type, inner_vlan.ether_type).run();
-            setup_double(*ether_type, ether_type::IPV4).run_ipv4(ipv4, ipv4_ext, udp, tcp, icmpv4, icmpv6);
+            setup_double(*ether_type, ether_type::IPV4).run_ipv4(ipv4, ipv4_ext, udp, tcp, icmpv4, icmpv6, igmp);
             setup_double(*ether_type, ether_type::IPV6).run_ipv6(ipv6, ipv6_ext, udp, tcp, icmpv4, icmpv6);
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn run_ipv4(
         &self,
         ip: &Ipv4Header,
@@ -415,6 +430,7 @@ impl ComponentTest {
         tcp: &TcpHeader,
         icmpv4: &Icmpv4Header,
         icmpv6: &Icmpv6Header,
+        igmp: &IgmpHeader,
     ) {
 
         // fragmenting
@@ -446,6 +462,8 @@ impl ComponentTest {
                 header
             });
             test.run_transport(udp, tcp, icmpv4, icmpv6);
+            // igmp is only defined for ipv4
+            test.run_igmp(igmp);
         }
     }
 
@@ -548,6 +566,21 @@ impl ComponentTest {
             test.run()
         }
     }
+
+    fn run_igmp(&self, igmp: &IgmpHeader) {
+        let mut test = self.clone();
+        test.ip.as_mut().unwrap().set_next_headers(ip_number::IGMP);
+        test.transport = Some(TransportHeader::Igmp(igmp.clone()));
+        if let Some(payload_size) = igmp.fixed_payload_size() {
+            // resize the payload in case it does not have to be as big
+            test.payload.resize(payload_size, 0);
+        }
+        // the igmp message is limited to the ip payload (the query
+        // version is determined based on the message length)
+        let payload_len = igmp.header_len() + test.payload.len();
+        test.ip.as_mut().unwrap().set_payload_len(payload_len).unwrap();
+        test.run()
+    }
 }
 
 proptest! {
@@ -564,6 +597,7 @@ proptest! {
                          ref tcp in tcp_any(),
                          ref icmpv4 in icmpv4_header_any(),
                          ref icmpv6 in icmpv6_header_any(),
+                         ref igmp in igmp_header_any(),
                          ref payload in proptest::collection::vec(any::<u8>(), 0..1024))
     {
         let setup_eth = | ether_type: u16 | -> ComponentTest {
@@ -582,12 +616,12 @@ proptest! {
 
         //ethernet 2: standalone, ipv4, ipv6
         setup_eth(eth.ether_type).run();
-        setup_eth(EtherType::Ipv4 as u16).run_ipv4(ipv4, ipv4_exts, udp, tcp, icmpv4, icmpv6);
+        setup_eth(EtherType::Ipv4 as u16).run_ipv4(ipv4, ipv4_exts, udp, tcp, icmpv4, icmpv6, igmp);
         setup_eth(EtherType::Ipv6 as u16).run_ipv6(ipv6, ipv6_exts, udp, tcp, icmpv4, icmpv6);
 
         //vlans
         for ether_type in VLAN_ETHER_TYPES {
-            setup_eth(*ether_type).run_vlan(vlan_outer, vlan_inner, ipv4, ipv4_exts, ipv6, ipv6_exts, udp, tcp, icmpv4, icmpv6);
+            setup_eth(*ether_type).run_vlan(vlan_outer, vlan_inner, ipv4, ipv4_exts, ipv6, ipv6_exts, udp, tcp, icmpv4, icmpv6, igmp);
         }
     }
 }
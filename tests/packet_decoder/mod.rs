@@ -25,6 +25,70 @@ mod packet_headers {
         );
     }
 
+    #[test]
+    fn from_ip_slice_mobility_hip_shim6() {
+        use IpHeader::*;
+
+        let udp_builder = PacketBuilder::ipv6([1;16], [2;16], 64).udp(1234, 5678);
+        let mut udp_packet = Vec::with_capacity(udp_builder.size(4));
+        udp_builder.write(&mut udp_packet, &[1,2,3,4]).unwrap();
+
+        // shim6 payload extension header in front of the udp header
+        {
+            let mut packet = udp_packet.clone();
+            packet[6] = ip_number::SHIM6;
+            packet.splice(
+                Ipv6Header::SERIALIZED_SIZE..Ipv6Header::SERIALIZED_SIZE,
+                Shim6PayloadHeader{ next_header: ip_number::UDP, receiver_context_tag: 1 }.to_bytes().unwrap().iter().cloned()
+            );
+            let payload_len = (packet.len() - Ipv6Header::SERIALIZED_SIZE) as u16;
+            packet[4..6].copy_from_slice(&payload_len.to_be_bytes());
+
+            let headers = PacketHeaders::from_ip_slice(&packet).unwrap();
+            match &headers.ip {
+                Some(Version6(_, exts)) => {
+                    assert_eq!(
+                        Some(Shim6Header::Payload(Shim6PayloadHeader{ next_header: ip_number::UDP, receiver_context_tag: 1 })),
+                        exts.shim6
+                    );
+                },
+                value => panic!("unexpected ip header {:?}", value),
+            }
+            assert_matches!(headers.transport, Some(TransportHeader::Udp(_)));
+            assert_eq!(&[1,2,3,4], headers.payload);
+        }
+
+        // hip & mobility headers followed by no next header
+        {
+            let mut exts = Ipv6Extensions{
+                hip: Some(HipHeader::new(0, hip_packet_type::UPDATE, [2;16], [3;16])),
+                mobility: Some(Ipv6MobilityHeader::new(
+                    0,
+                    &Ipv6MobilityMessage::BindingRefreshRequest{ options: &[] }
+                ).unwrap()),
+                ..Default::default()
+            };
+            let next_header = exts.set_next_headers(ip_number::IPV6_NO_NEXT_HEADER);
+            let ip = Ipv6Header{
+                traffic_class: 0,
+                flow_label: 0,
+                payload_length: exts.header_len() as u16,
+                next_header,
+                hop_limit: 64,
+                source: [1;16],
+                destination: [2;16],
+            };
+            let mut packet = Vec::new();
+            ip.write(&mut packet).unwrap();
+            exts.write(&mut packet, next_header).unwrap();
+
+            let headers = PacketHeaders::from_ip_slice(&packet).unwrap();
+            assert_eq!(Some(Version6(ip, exts)), headers.ip);
+            assert_eq!(None, headers.transport);
+            assert!(headers.payload.is_empty());
+        }
+    }
+
     #[test]
     fn clone_eq() {
         let header = PacketHeaders{
@@ -367,6 +367,10 @@ impl PacketFilterTest {
                     header.write(&mut transport_data).unwrap();
                     Some(TransportSlice::Icmpv6(Icmpv6Slice::from_slice(&transport_data[..]).unwrap()))
                 },
+                Some(TransportHeader::Igmp(header)) => {
+                    header.write(&mut transport_data).unwrap();
+                    Some(TransportSlice::Igmp(IgmpSlice::from_slice(&transport_data[..]).unwrap()))
+                },
                 Some(TransportHeader::Udp(header)) => {
                     header.write(&mut transport_data).unwrap();
                     Some(TransportSlice::Udp(UdpHeaderSlice::from_slice(&transport_data[..]).unwrap()))
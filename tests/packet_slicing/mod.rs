@@ -16,7 +16,9 @@ mod internet_slice {
             };
             let ipv4 = Ipv4HeaderSlice::from_slice(&buffer).unwrap();
             let exts = Ipv4ExtensionsSlice {
-                auth: None
+                auth: None,
+                esp: None,
+                esp_trailer: None,
             };
             let slice = InternetSlice::Ipv4(ipv4.clone(), exts.clone());
 
@@ -69,7 +71,9 @@ mod internet_slice {
                 };
                 let ipv4 = Ipv4HeaderSlice::from_slice(&buffer).unwrap();
                 let exts = Ipv4ExtensionsSlice {
-                    auth: None
+                    auth: None,
+                    esp: None,
+                    esp_trailer: None,
                 };
                 let slice = InternetSlice::Ipv4(ipv4.clone(), exts.clone());
                 assert_eq!(is_frag, slice.is_fragmenting_payload());
@@ -187,6 +191,236 @@ mod sliced_packet {
         }
     }
 
+    #[test]
+    fn from_ip_esp_null() {
+        use crate::ReadError::*;
+
+        let esp = {
+            let mut esp = Vec::new();
+            esp.extend_from_slice(&EspHeader{ spi: 10, sequence_number: 20 }.to_bytes());
+            UdpHeader{
+                source_port: 1,
+                destination_port: 2,
+                length: 8 + 4,
+                checksum: 0,
+            }.write(&mut esp).unwrap();
+            esp.extend_from_slice(&[
+                1, 2, 3, 4, // udp payload
+                1, 2, // padding
+                2, ip_number::UDP, // trailer
+                9, 9, 9, 9, 9, 9, 9, 9, 9, 9, 9, 9, // icv
+            ]);
+            esp
+        };
+
+        let ipv4 = {
+            let ip = Ipv4Header::new(esp.len() as u16, 20, ip_number::ENCAP_SEC, [1,2,3,4], [5,6,7,8]);
+            let mut packet = Vec::new();
+            ip.write(&mut packet).unwrap();
+            packet.extend_from_slice(&esp);
+            packet
+        };
+        let ipv6 = {
+            let ip = Ipv6Header{
+                traffic_class: 0,
+                flow_label: 0,
+                payload_length: esp.len() as u16,
+                next_header: ip_number::ENCAP_SEC,
+                hop_limit: 20,
+                source: [0;16],
+                destination: [1;16],
+            };
+            let mut packet = Vec::new();
+            ip.write(&mut packet).unwrap();
+            packet.extend_from_slice(&esp);
+            packet
+        };
+
+        for (packet, ip_header_len) in [(&ipv4, Ipv4Header::SERIALIZED_SIZE), (&ipv6, Ipv6Header::SERIALIZED_SIZE)] {
+            // add some padding after the ip packet (e.g. ethernet padding)
+            let mut padded = packet.clone();
+            padded.extend_from_slice(&[0, 0, 0]);
+
+            // without decoding
+            {
+                let sliced = SlicedPacket::from_ip(&padded).unwrap();
+                assert_eq!(Some(TransportSlice::Unknown(ip_number::ENCAP_SEC)), sliced.transport);
+                assert_eq!(&padded[ip_header_len..], sliced.payload);
+            }
+
+            // with decoding
+            {
+                let sliced = SlicedPacket::from_ip_esp_null(&padded, 12).unwrap();
+                let (esp_header, esp_trailer) = match sliced.ip.as_ref().unwrap() {
+                    InternetSlice::Ipv4(_, ext) => (ext.esp.clone(), ext.esp_trailer.clone()),
+                    InternetSlice::Ipv6(_, ext) => (ext.esp().cloned(), ext.esp_trailer().cloned()),
+                };
+                assert_eq!(10, esp_header.unwrap().spi());
+                assert_eq!(&[9;12], esp_trailer.unwrap().icv());
+                match sliced.transport.unwrap() {
+                    TransportSlice::Udp(udp) => {
+                        assert_eq!(1, udp.source_port());
+                        assert_eq!(2, udp.destination_port());
+                    },
+                    _ => panic!("expected udp"),
+                }
+                assert_eq!(&[1, 2, 3, 4], sliced.payload);
+            }
+
+            // ethernet
+            {
+                let mut eth = Vec::new();
+                Ethernet2Header{
+                    source: [0;6],
+                    destination: [0;6],
+                    ether_type: if ip_header_len == Ipv4Header::SERIALIZED_SIZE {
+                        ether_type::IPV4
+                    } else {
+                        ether_type::IPV6
+                    },
+                }.write(&mut eth).unwrap();
+                eth.extend_from_slice(&padded);
+                let sliced = SlicedPacket::from_ethernet_esp_null(&eth, 12).unwrap();
+                assert_matches!(sliced.transport, Some(TransportSlice::Udp(_)));
+                assert_eq!(&[1, 2, 3, 4], sliced.payload);
+            }
+
+            // icv length too big
+            assert_matches!(
+                SlicedPacket::from_ip_esp_null(&padded, esp.len()),
+                Err(UnexpectedEndOfSlice(_))
+            );
+
+            // ip payload length bigger then the data
+            assert_matches!(
+                SlicedPacket::from_ip_esp_null(&packet[..packet.len() - 1], 12),
+                Err(UnexpectedEndOfSlice(_))
+            );
+        }
+    }
+
+    #[test]
+    fn from_ip_repeated_ipv6_extensions() {
+        let mut chain = Ipv6ExtensionsChain{
+            headers: vec![
+                Ipv6ExtensionHeader::DestinationOptions(Ipv6RawExtensionHeader::new_raw(0, &[1;6]).unwrap()),
+                Ipv6ExtensionHeader::Routing(Ipv6RawExtensionHeader::new_raw(0, &[2;6]).unwrap()),
+                Ipv6ExtensionHeader::DestinationOptions(Ipv6RawExtensionHeader::new_raw(0, &[3;6]).unwrap()),
+                Ipv6ExtensionHeader::DestinationOptions(Ipv6RawExtensionHeader::new_raw(0, &[4;6]).unwrap()),
+            ]
+        };
+        let mut ip = Ipv6Header{
+            traffic_class: 0,
+            flow_label: 0,
+            payload_length: (chain.header_len() + 8 + 4) as u16,
+            next_header: chain.set_next_headers(ip_number::UDP),
+            hop_limit: 20,
+            source: [0;16],
+            destination: [1;16],
+        };
+        let udp = UdpHeader::with_ipv6_checksum(1, 2, &ip, &[1,2,3,4]).unwrap();
+        ip.payload_length = (chain.header_len() + udp.header_len() + 4) as u16;
+
+        let mut packet = Vec::new();
+        ip.write(&mut packet).unwrap();
+        chain.write(&mut packet).unwrap();
+        udp.write(&mut packet).unwrap();
+        packet.extend_from_slice(&[1,2,3,4]);
+
+        let sliced = SlicedPacket::from_ip(&packet).unwrap();
+        match sliced.ip.as_ref().unwrap() {
+            InternetSlice::Ipv6(_, ext) => {
+                assert_eq!(chain, Ipv6ExtensionsChain::from(ext.clone()));
+            },
+            _ => panic!("expected ipv6"),
+        }
+        assert_matches!(sliced.transport, Some(TransportSlice::Udp(_)));
+        assert_eq!(&[1,2,3,4], sliced.payload);
+    }
+
+    #[test]
+    fn from_ip_shim6_payload_extension() {
+        let mut chain = Ipv6ExtensionsChain{
+            headers: vec![
+                Ipv6ExtensionHeader::DestinationOptions(Ipv6RawExtensionHeader::new_raw(0, &[1;6]).unwrap()),
+                Ipv6ExtensionHeader::Shim6(Shim6Header::Payload(Shim6PayloadHeader{
+                    next_header: 0,
+                    receiver_context_tag: 0x1234_5678_9abc,
+                })),
+            ]
+        };
+        let mut ip = Ipv6Header{
+            traffic_class: 0,
+            flow_label: 0,
+            payload_length: 0,
+            next_header: chain.set_next_headers(ip_number::UDP),
+            hop_limit: 20,
+            source: [0;16],
+            destination: [1;16],
+        };
+        let udp = UdpHeader::with_ipv6_checksum(1, 2, &ip, &[1,2,3,4]).unwrap();
+        ip.payload_length = (chain.header_len() + udp.header_len() + 4) as u16;
+
+        let mut packet = Vec::new();
+        ip.write(&mut packet).unwrap();
+        chain.write(&mut packet).unwrap();
+        udp.write(&mut packet).unwrap();
+        packet.extend_from_slice(&[1,2,3,4]);
+
+        let sliced = SlicedPacket::from_ip(&packet).unwrap();
+        match sliced.ip.as_ref().unwrap() {
+            InternetSlice::Ipv6(_, ext) => {
+                assert_eq!(chain, Ipv6ExtensionsChain::from(ext.clone()));
+            },
+            _ => panic!("expected ipv6"),
+        }
+        assert_matches!(sliced.transport, Some(TransportSlice::Udp(_)));
+        assert_eq!(&[1,2,3,4], sliced.payload);
+    }
+
+    #[test]
+    fn limit_to_ip_payload() {
+        // ipv4 with ethernet padding
+        {
+            let builder = PacketBuilder::
+                ethernet2([1;6], [2;6])
+                .ipv4([192,168,1,1], [192,168,1,2], 20)
+                .udp(1, 2);
+            let mut packet = Vec::new();
+            builder.write(&mut packet, &[1,2,3,4]).unwrap();
+            packet.resize(60, 0);
+
+            let sliced = SlicedPacket::from_ethernet(&packet).unwrap();
+            assert_eq!(4 + 14, sliced.payload.len());
+            assert_eq!(&[1,2,3,4], sliced.limit_to_ip_payload(sliced.payload));
+        }
+        // ipv6 with ethernet padding
+        {
+            let builder = PacketBuilder::
+                ethernet2([1;6], [2;6])
+                .ipv6([1;16], [2;16], 20)
+                .udp(1, 2);
+            let mut packet = Vec::new();
+            builder.write(&mut packet, &[1,2,3,4]).unwrap();
+            let len = packet.len();
+            packet.resize(len + 6, 0);
+
+            let sliced = SlicedPacket::from_ethernet(&packet).unwrap();
+            assert_eq!(&[1,2,3,4], sliced.limit_to_ip_payload(sliced.payload));
+        }
+        // without ip header the slice stays unchanged
+        {
+            let sliced = SlicedPacket{
+                link: None,
+                vlan: None,
+                ip: None,
+                transport: None,
+                payload: &[1,2,3,4]
+            };
+            assert_eq!(&[1,2,3,4], sliced.limit_to_ip_payload(sliced.payload));
+        }
+    }
+
     #[test]
     fn debug() {
         let header = SlicedPacket{
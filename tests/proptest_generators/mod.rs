@@ -211,6 +211,7 @@ prop_compose! {
 
 static IPV4_KNOWN_PROTOCOLS: &'static [u8] = &[
     ip_number::ICMP,
+    ip_number::IGMP,
     ip_number::UDP,
     ip_number::TCP,
     ip_number::AUTH,
@@ -432,6 +433,9 @@ prop_compose! {
             } else {
                 None
             },
+            mobility: None,
+            hip: None,
+            shim6: None,
         };
         result.set_next_headers(next_header);
         result
@@ -756,7 +760,7 @@ prop_compose! {
 prop_compose! {
     pub fn icmpv6_type_any()
         (
-            bytes in any::<[u8;8]>(),
+            bytes in proptest::collection::vec(any::<u8>(), Icmpv6Header::MAX_SERIALIZED_SIZE),
         ) -> Icmpv6Type
     {
         Icmpv6Header::from_slice(&bytes).unwrap().0.icmp_type
@@ -766,9 +770,23 @@ prop_compose! {
 prop_compose! {
     pub fn icmpv6_header_any()
         (
-            bytes in any::<[u8;8]>(),
+            bytes in proptest::collection::vec(any::<u8>(), Icmpv6Header::MAX_SERIALIZED_SIZE),
         ) -> Icmpv6Header
     {
         Icmpv6Header::from_slice(&bytes).unwrap().0
     }
 }
+
+prop_compose! {
+    pub fn igmp_header_any()
+        (
+            // queries are decoded based on their length (8 bytes IGMPv1 & IGMPv2, 12 bytes IGMPv3)
+            bytes in prop_oneof![
+                proptest::collection::vec(any::<u8>(), IgmpHeader::MIN_SERIALIZED_SIZE),
+                proptest::collection::vec(any::<u8>(), IgmpHeader::MAX_SERIALIZED_SIZE),
+            ],
+        ) -> IgmpHeader
+    {
+        IgmpHeader::from_slice(&bytes).unwrap().0
+    }
+}
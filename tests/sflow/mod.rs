@@ -0,0 +1,496 @@
+use super::*;
+
+/// Serializes the values as XDR unsigned integers.
+fn xdr(values: &[u32]) -> Vec<u8> {
+    values.iter().flat_map(|v| v.to_be_bytes().to_vec()).collect()
+}
+
+/// Serializes the values as XDR opaque data (padded to a multiple of 4).
+fn opaque(data: &[u8]) -> Vec<u8> {
+    let mut result = data.to_vec();
+    result.resize(data.len() + (4 - data.len() % 4) % 4, 0);
+    result
+}
+
+/// Serializes a sample or record (data format, length & data).
+fn element(enterprise: u32, format: u32, data: &[u8]) -> Vec<u8> {
+    let mut result = xdr(&[(enterprise << 12) | format, data.len() as u32]);
+    result.extend_from_slice(&opaque(data));
+    result
+}
+
+/// Serializes a datagram with an IPv4 agent address & the given samples.
+fn datagram(samples: &[Vec<u8>]) -> Vec<u8> {
+    let mut result = xdr(&[5, 1, 0x0a00_0001, 3, 4, 5, samples.len() as u32]);
+    for sample in samples {
+        result.extend_from_slice(sample);
+    }
+    result
+}
+
+fn ethernet_frame(payload_len: usize) -> Vec<u8> {
+    let builder = PacketBuilder::ethernet2([2,3,4,5,6,7], [8,9,10,11,12,13])
+        .ipv4([192,168,1,1], [192,168,1,2], 20)
+        .tcp(1234, 80, 1, 1024);
+    let payload: Vec<u8> = (0..payload_len).map(|i| i as u8).collect();
+    let mut result = Vec::with_capacity(builder.size(payload.len()));
+    builder.write(&mut result, &payload).unwrap();
+    result
+}
+
+fn sampled_header_record(protocol: u32, frame_length: u32, header: &[u8]) -> Vec<u8> {
+    let mut data = xdr(&[protocol, frame_length, 4, header.len() as u32]);
+    data.extend_from_slice(&opaque(header));
+    element(0, 1, &data)
+}
+
+#[test]
+fn datagram_header() {
+    // ipv4 agent without samples
+    {
+        let data = datagram(&[]);
+        assert_eq!(
+            SflowDatagram {
+                agent_address: SflowAddress::Ipv4([10,0,0,1]),
+                sub_agent_id: 3,
+                sequence_number: 4,
+                uptime: 5,
+                samples: Vec::new(),
+            },
+            SflowDatagram::from_slice(&data).unwrap()
+        );
+    }
+    // ipv6 & unknown agent address
+    {
+        let mut data = xdr(&[5, 2]);
+        data.extend_from_slice(&[1;16]);
+        data.extend_from_slice(&xdr(&[3, 4, 5, 0]));
+        assert_eq!(
+            SflowAddress::Ipv6([1;16]),
+            SflowDatagram::from_slice(&data).unwrap().agent_address
+        );
+
+        let data = xdr(&[5, 0, 3, 4, 5, 0]);
+        let result = SflowDatagram::from_slice(&data).unwrap();
+        assert_eq!(SflowAddress::Unknown, result.agent_address);
+        assert_eq!(3, result.sub_agent_id);
+    }
+    // data after the last sample is ignored
+    {
+        let mut data = datagram(&[]);
+        data.extend_from_slice(&[1,2,3]);
+        assert!(SflowDatagram::from_slice(&data).unwrap().samples.is_empty());
+    }
+}
+
+#[test]
+fn flow_sample() {
+    let frame = ethernet_frame(4);
+    let mut sample = xdr(&[
+        7, // sequence number
+        (1 << 24) | 12, // source id
+        100, // sampling rate
+        2000, // sample pool
+        3, // drops
+        (1 << 30) | 5, // input
+        (2 << 30) | 0x3fff_ffff, // output
+        8, // record count
+    ]);
+    sample.extend_from_slice(&sampled_header_record(
+        sflow_header_protocol::ETHERNET_ISO88023,
+        frame.len() as u32 + 4,
+        &frame
+    ));
+    sample.extend_from_slice(&element(0, 2, &{
+        let mut data = xdr(&[64]);
+        data.extend_from_slice(&opaque(&[2,3,4,5,6,7]));
+        data.extend_from_slice(&opaque(&[8,9,10,11,12,13]));
+        data.extend_from_slice(&xdr(&[0x0800]));
+        data
+    }));
+    sample.extend_from_slice(&element(0, 3, &{
+        let mut data = xdr(&[60, 6]);
+        data.extend_from_slice(&[192,168,1,1,192,168,1,2]);
+        data.extend_from_slice(&xdr(&[1234, 80, 0x12, 0x28]));
+        data
+    }));
+    sample.extend_from_slice(&element(0, 4, &{
+        let mut data = xdr(&[80, 17]);
+        data.extend_from_slice(&[1;16]);
+        data.extend_from_slice(&[2;16]);
+        data.extend_from_slice(&xdr(&[53, 5353, 0, 3]));
+        data
+    }));
+    sample.extend_from_slice(&element(0, 1001, &xdr(&[10, 1, 20, 2])));
+    sample.extend_from_slice(&element(0, 1002, &xdr(&[1, 0x0a00_00fe, 24, 16])));
+    sample.extend_from_slice(&element(0, 1003, &[1,2,3]));
+    sample.extend_from_slice(&element(9, 1, &[4,5,6,7]));
+
+    let data = datagram(&[element(0, 1, &sample)]);
+    let result = SflowDatagram::from_slice(&data).unwrap();
+    assert_eq!(1, result.samples.len());
+
+    use SflowFlowRecord::*;
+    assert_eq!(
+        SflowSample::Flow(SflowFlowSample {
+            expanded: false,
+            sequence_number: 7,
+            source_id_type: 1,
+            source_id_index: 12,
+            sampling_rate: 100,
+            sample_pool: 2000,
+            drops: 3,
+            input: SflowInterface{ format: 1, value: 5 },
+            output: SflowInterface{ format: 2, value: 0x3fff_ffff },
+            records: vec![
+                SampledHeader(SflowSampledHeader {
+                    header_protocol: sflow_header_protocol::ETHERNET_ISO88023,
+                    frame_length: frame.len() as u32 + 4,
+                    stripped: 4,
+                    header: &frame,
+                }),
+                SampledEthernet {
+                    length: 64,
+                    source: [2,3,4,5,6,7],
+                    destination: [8,9,10,11,12,13],
+                    ether_type: 0x0800,
+                },
+                SampledIpv4 {
+                    length: 60,
+                    protocol: 6,
+                    source: [192,168,1,1],
+                    destination: [192,168,1,2],
+                    source_port: 1234,
+                    destination_port: 80,
+                    tcp_flags: 0x12,
+                    tos: 0x28,
+                },
+                SampledIpv6 {
+                    length: 80,
+                    protocol: 17,
+                    source: [1;16],
+                    destination: [2;16],
+                    source_port: 53,
+                    destination_port: 5353,
+                    tcp_flags: 0,
+                    priority: 3,
+                },
+                ExtendedSwitch {
+                    source_vlan: 10,
+                    source_priority: 1,
+                    destination_vlan: 20,
+                    destination_priority: 2,
+                },
+                ExtendedRouter {
+                    next_hop: SflowAddress::Ipv4([10,0,0,254]),
+                    source_mask_len: 24,
+                    destination_mask_len: 16,
+                },
+                Unknown{ enterprise: 0, format: 1003, data: &[1,2,3] },
+                Unknown{ enterprise: 9, format: 1, data: &[4,5,6,7] },
+            ],
+        }),
+        result.samples[0]
+    );
+}
+
+#[test]
+fn flow_sample_expanded() {
+    let frame = ethernet_frame(0);
+    let mut sample = xdr(&[7, 2, 0x0100_0000, 10, 20, 0, 0, 5, 1, 0x4000_0000, 1]);
+    sample.extend_from_slice(&sampled_header_record(
+        sflow_header_protocol::ETHERNET_ISO88023,
+        frame.len() as u32,
+        &frame
+    ));
+    let data = datagram(&[element(0, 3, &sample)]);
+    let result = SflowDatagram::from_slice(&data).unwrap();
+    match &result.samples[0] {
+        SflowSample::Flow(flow) => {
+            assert!(flow.expanded);
+            assert_eq!(7, flow.sequence_number);
+            assert_eq!(2, flow.source_id_type);
+            assert_eq!(0x0100_0000, flow.source_id_index);
+            assert_eq!(10, flow.sampling_rate);
+            assert_eq!(20, flow.sample_pool);
+            assert_eq!(0, flow.drops);
+            assert_eq!(SflowInterface{ format: 0, value: 5 }, flow.input);
+            // expanded interfaces are not split into format & value
+            assert_eq!(SflowInterface{ format: 1, value: 0x4000_0000 }, flow.output);
+            assert_eq!(1, flow.records.len());
+        },
+        value => panic!("unexpected sample {:?}", value),
+    }
+}
+
+#[test]
+fn sampled_header_sliced_packet() {
+    use sflow_header_protocol::*;
+
+    // complete ethernet frame
+    {
+        let frame = ethernet_frame(4);
+        let header = SflowSampledHeader {
+            header_protocol: ETHERNET_ISO88023,
+            frame_length: frame.len() as u32,
+            stripped: 0,
+            header: &frame,
+        };
+        let packet = header.sliced_packet().unwrap().unwrap();
+        assert_eq!(
+            SlicedPacket::from_ethernet(&frame).unwrap(),
+            packet
+        );
+        assert_eq!(&[0,1,2,3], packet.payload);
+    }
+    // truncated ethernet frame (the payload is cut)
+    {
+        let frame = ethernet_frame(200);
+        let header = SflowSampledHeader {
+            header_protocol: ETHERNET_ISO88023,
+            frame_length: frame.len() as u32,
+            stripped: 0,
+            header: &frame[..128],
+        };
+        let packet = header.sliced_packet().unwrap().unwrap();
+        assert!(packet.ip.is_some());
+        assert!(packet.transport.is_some());
+        assert_eq!(128 - 14 - 20 - 20, packet.payload.len());
+    }
+    // ip packet
+    {
+        let frame = ethernet_frame(4);
+        let header = SflowSampledHeader {
+            header_protocol: IPV4,
+            frame_length: frame.len() as u32 - 14,
+            stripped: 0,
+            header: &frame[14..],
+        };
+        assert_eq!(
+            SlicedPacket::from_ip(&frame[14..]).unwrap(),
+            header.sliced_packet().unwrap().unwrap()
+        );
+    }
+    // frame too short
+    {
+        let frame = ethernet_frame(4);
+        let header = SflowSampledHeader {
+            header_protocol: ETHERNET_ISO88023,
+            frame_length: frame.len() as u32,
+            stripped: 0,
+            header: &frame[..20],
+        };
+        assert_matches!(
+            header.sliced_packet(),
+            Some(Err(ReadError::UnexpectedEndOfSlice(_)))
+        );
+    }
+    // unsupported protocols
+    for protocol in &[ISO88024_TOKENBUS, PPP, MPLS, POS] {
+        let header = SflowSampledHeader {
+            header_protocol: *protocol,
+            frame_length: 4,
+            stripped: 0,
+            header: &[1,2,3,4],
+        };
+        assert!(header.sliced_packet().is_none());
+    }
+}
+
+#[test]
+fn counters_sample() {
+    let generic = SflowInterfaceCounters {
+        if_index: 1,
+        if_type: 6,
+        if_speed: 10_000_000_000,
+        if_direction: 1,
+        if_status: 3,
+        if_in_octets: 0x1_0000_0001,
+        if_in_ucast_pkts: 2,
+        if_in_multicast_pkts: 3,
+        if_in_broadcast_pkts: 4,
+        if_in_discards: 5,
+        if_in_errors: 6,
+        if_in_unknown_protos: 7,
+        if_out_octets: 0x2_0000_0002,
+        if_out_ucast_pkts: 8,
+        if_out_multicast_pkts: 9,
+        if_out_broadcast_pkts: 10,
+        if_out_discards: 11,
+        if_out_errors: 12,
+        if_promiscuous_mode: 1,
+    };
+    let generic_data = xdr(&[
+        1, 6, 2, 0x540b_e400, 1, 3, 1, 1, 2, 3, 4, 5, 6, 7,
+        2, 2, 8, 9, 10, 11, 12, 1
+    ]);
+    let ethernet = SflowEthernetCounters {
+        alignment_errors: 1,
+        fcs_errors: 2,
+        single_collision_frames: 3,
+        multiple_collision_frames: 4,
+        sqe_test_errors: 5,
+        deferred_transmissions: 6,
+        late_collisions: 7,
+        excessive_collisions: 8,
+        internal_mac_transmit_errors: 9,
+        carrier_sense_errors: 10,
+        frame_too_longs: 11,
+        internal_mac_receive_errors: 12,
+        symbol_errors: 13,
+    };
+    let ethernet_data = xdr(&[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13]);
+
+    // compact
+    {
+        let mut sample = xdr(&[9, (2 << 24) | 3, 3]);
+        sample.extend_from_slice(&element(0, 1, &generic_data));
+        sample.extend_from_slice(&element(0, 2, &ethernet_data));
+        sample.extend_from_slice(&element(0, 1005, &[1,2,3,4]));
+        let data = datagram(&[element(0, 2, &sample)]);
+        assert_eq!(
+            vec![
+                SflowSample::Counters(SflowCountersSample {
+                    expanded: false,
+                    sequence_number: 9,
+                    source_id_type: 2,
+                    source_id_index: 3,
+                    records: vec![
+                        SflowCounterRecord::GenericInterface(generic),
+                        SflowCounterRecord::EthernetInterface(ethernet),
+                        SflowCounterRecord::Unknown{ enterprise: 0, format: 1005, data: &[1,2,3,4] },
+                    ],
+                })
+            ],
+            SflowDatagram::from_slice(&data).unwrap().samples
+        );
+    }
+    // expanded
+    {
+        let mut sample = xdr(&[9, 0, 0x0100_0000, 1]);
+        sample.extend_from_slice(&element(0, 2, &ethernet_data));
+        let data = datagram(&[element(0, 4, &sample)]);
+        assert_eq!(
+            vec![
+                SflowSample::Counters(SflowCountersSample {
+                    expanded: true,
+                    sequence_number: 9,
+                    source_id_type: 0,
+                    source_id_index: 0x0100_0000,
+                    records: vec![
+                        SflowCounterRecord::EthernetInterface(ethernet),
+                    ],
+                })
+            ],
+            SflowDatagram::from_slice(&data).unwrap().samples
+        );
+    }
+}
+
+#[test]
+fn unknown_sample() {
+    let data = datagram(&[
+        element(0, 5, &[1,2,3,4,5]),
+        element(4413, 1, &[]),
+        element(0, 2, &xdr(&[1, 2, 0])),
+    ]);
+    let result = SflowDatagram::from_slice(&data).unwrap();
+    assert_eq!(
+        vec![
+            SflowSample::Unknown{ enterprise: 0, format: 5, data: &[1,2,3,4,5] },
+            SflowSample::Unknown{ enterprise: 4413, format: 1, data: &[] },
+            SflowSample::Counters(SflowCountersSample {
+                expanded: false,
+                sequence_number: 1,
+                source_id_type: 0,
+                source_id_index: 2,
+                records: Vec::new(),
+            }),
+        ],
+        result.samples
+    );
+}
+
+#[test]
+fn from_slice_errors() {
+    use ReadError::*;
+
+    // version
+    {
+        let mut data = datagram(&[]);
+        data[3] = 4;
+        assert_matches!(
+            SflowDatagram::from_slice(&data),
+            Err(SflowUnsupportedVersion(4))
+        );
+    }
+    // agent address type
+    {
+        let data = xdr(&[5, 3, 0, 0, 0, 0]);
+        assert_matches!(
+            SflowDatagram::from_slice(&data),
+            Err(SflowUnsupportedAddressType(3))
+        );
+    }
+    // next hop address type
+    {
+        let sample = {
+            let mut result = xdr(&[1, 0, 1, 1, 0, 0, 0, 1]);
+            result.extend_from_slice(&element(0, 1002, &xdr(&[7, 0, 0])));
+            result
+        };
+        let data = datagram(&[element(0, 1, &sample)]);
+        assert_matches!(
+            SflowDatagram::from_slice(&data),
+            Err(SflowUnsupportedAddressType(7))
+        );
+    }
+    // truncated datagram header
+    {
+        let data = datagram(&[]);
+        for len in 0..data.len() {
+            assert_matches!(
+                SflowDatagram::from_slice(&data[..len]),
+                Err(UnexpectedEndOfSlice(_))
+            );
+        }
+        assert_matches!(
+            SflowDatagram::from_slice(&data[..2]),
+            Err(UnexpectedEndOfSlice(4))
+        );
+        assert_matches!(
+            SflowDatagram::from_slice(&data[..10]),
+            Err(UnexpectedEndOfSlice(12))
+        );
+    }
+    // sample length bigger then the datagram
+    {
+        let mut data = datagram(&[element(0, 2, &xdr(&[1, 2, 0]))]);
+        data.truncate(data.len() - 4);
+        assert_matches!(
+            SflowDatagram::from_slice(&data),
+            Err(UnexpectedEndOfSlice(48))
+        );
+    }
+    // sample count bigger then the number of samples
+    {
+        let mut data = datagram(&[element(0, 2, &xdr(&[1, 2, 0]))]);
+        data[27] = 2;
+        assert_matches!(
+            SflowDatagram::from_slice(&data),
+            Err(UnexpectedEndOfSlice(52))
+        );
+    }
+    // record shorter then the known format requires
+    {
+        let mut sample = xdr(&[1, 2, 1]);
+        sample.extend_from_slice(&element(0, 2, &xdr(&[1, 2, 3])));
+        let data = datagram(&[element(0, 2, &sample)]);
+        // datagram header (28) + sample header (8) + sample fields (12)
+        // + record header (8) + 3 values of the record (12) + 4
+        assert_matches!(
+            SflowDatagram::from_slice(&data),
+            Err(UnexpectedEndOfSlice(72))
+        );
+    }
+}
@@ -0,0 +1,426 @@
+use super::*;
+
+const CLIENT: [u8;4] = [192,168,1,1];
+const SERVER: [u8;4] = [192,168,1,2];
+
+/// Owned version of the reassembler events.
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Event {
+    Data(TcpStreamKey, u64, Vec<u8>),
+    Gap(TcpStreamKey, u64, u64),
+    Fin(TcpStreamKey),
+    Reset(TcpStreamKey),
+    Evicted(TcpStreamKey),
+}
+
+impl From<TcpStreamEvent<'_>> for Event {
+    fn from(event: TcpStreamEvent) -> Event {
+        match event {
+            TcpStreamEvent::Data{ key, offset, data } => Event::Data(key, offset, data.to_vec()),
+            TcpStreamEvent::Gap{ key, offset, len } => Event::Gap(key, offset, len),
+            TcpStreamEvent::Fin{ key } => Event::Fin(key),
+            TcpStreamEvent::Reset{ key } => Event::Reset(key),
+            TcpStreamEvent::Evicted{ key } => Event::Evicted(key),
+        }
+    }
+}
+
+fn client_key(client_port: u16) -> TcpStreamKey {
+    TcpStreamKey {
+        source: TcpStreamAddress::Ipv4(CLIENT),
+        source_port: client_port,
+        destination: TcpStreamAddress::Ipv4(SERVER),
+        destination_port: 80,
+    }
+}
+
+#[derive(Clone, Copy, Default)]
+struct Flags {
+    syn: bool,
+    fin: bool,
+    rst: bool,
+}
+
+const NONE: Flags = Flags{ syn: false, fin: false, rst: false };
+const SYN: Flags = Flags{ syn: true, fin: false, rst: false };
+const FIN: Flags = Flags{ syn: false, fin: true, rst: false };
+const RST: Flags = Flags{ syn: false, fin: false, rst: true };
+
+/// Builds a segment of the connection with the given client port.
+fn segment(from_client: bool, client_port: u16, sequence_number: u32, flags: Flags, payload: &[u8]) -> Vec<u8> {
+    let builder = if from_client {
+        PacketBuilder::ipv4(CLIENT, SERVER, 20).tcp(client_port, 80, sequence_number, 1024)
+    } else {
+        PacketBuilder::ipv4(SERVER, CLIENT, 20).tcp(80, client_port, sequence_number, 1024)
+    };
+    let builder = if flags.syn { builder.syn() } else { builder };
+    let builder = if flags.fin { builder.fin() } else { builder };
+    let builder = if flags.rst { builder.rst() } else { builder };
+    let mut result = Vec::with_capacity(builder.size(payload.len()));
+    builder.write(&mut result, payload).unwrap();
+    result
+}
+
+/// Processes the packet & returns the generated events.
+fn process(reassembler: &mut TcpReassembler, packet: &[u8], now: u64) -> Vec<Event> {
+    let mut events = Vec::new();
+    assert!(reassembler.process(&SlicedPacket::from_ip(packet).unwrap(), now, |e| events.push(e.into())));
+    events
+}
+
+#[test]
+fn config_default() {
+    let config = TcpReassemblerConfig::default();
+    assert_eq!(256*1024, config.max_buffered_bytes_per_stream);
+    assert_eq!(64*1024*1024, config.max_buffered_bytes);
+    assert_eq!(65536, config.max_connections);
+    assert_eq!(300_000, config.idle_timeout);
+    assert_eq!(&config, TcpReassembler::new(config.clone()).config());
+}
+
+#[test]
+fn stream_key() {
+    // ipv4
+    let packet = segment(true, 1234, 0, NONE, &[]);
+    let key = TcpStreamKey::from_sliced_packet(&SlicedPacket::from_ip(&packet).unwrap()).unwrap();
+    assert_eq!(client_key(1234), key);
+    assert_eq!(
+        TcpStreamKey {
+            source: TcpStreamAddress::Ipv4(SERVER),
+            source_port: 80,
+            destination: TcpStreamAddress::Ipv4(CLIENT),
+            destination_port: 1234,
+        },
+        key.reversed()
+    );
+    assert_eq!(key, key.reversed().reversed());
+
+    // ipv6
+    let builder = PacketBuilder::ipv6([1;16], [2;16], 20).tcp(1, 2, 0, 0);
+    let mut packet = Vec::new();
+    builder.write(&mut packet, &[]).unwrap();
+    assert_eq!(
+        Some(TcpStreamKey {
+            source: TcpStreamAddress::Ipv6([1;16]),
+            source_port: 1,
+            destination: TcpStreamAddress::Ipv6([2;16]),
+            destination_port: 2,
+        }),
+        TcpStreamKey::from_sliced_packet(&SlicedPacket::from_ip(&packet).unwrap())
+    );
+
+    // udp
+    let builder = PacketBuilder::ipv4(CLIENT, SERVER, 20).udp(1, 2);
+    let mut packet = Vec::new();
+    builder.write(&mut packet, &[]).unwrap();
+    let sliced = SlicedPacket::from_ip(&packet).unwrap();
+    assert_eq!(None, TcpStreamKey::from_sliced_packet(&sliced));
+    let mut reassembler = TcpReassembler::new(Default::default());
+    assert!(!reassembler.process(&sliced, 0, |_| panic!("unexpected event")));
+}
+
+#[test]
+fn handshake_data_and_fin() {
+    let client = client_key(1234);
+    let server = client.reversed();
+    let mut r = TcpReassembler::new(Default::default());
+
+    // pure acks of unknown connections are ignored
+    assert_eq!(Vec::<Event>::new(), process(&mut r, &segment(true, 1234, 1000, NONE, &[]), 0));
+    assert_eq!(0, r.connection_count());
+
+    assert_eq!(Vec::<Event>::new(), process(&mut r, &segment(true, 1234, 999, SYN, &[]), 0));
+    assert_eq!(1, r.connection_count());
+    assert_eq!(Vec::<Event>::new(), process(&mut r, &segment(false, 1234, 4999, SYN, &[]), 1));
+    assert_eq!(
+        vec![Event::Data(client, 0, vec![1,2,3])],
+        process(&mut r, &segment(true, 1234, 1000, NONE, &[1,2,3]), 2)
+    );
+    assert_eq!(
+        vec![Event::Data(server, 0, vec![4,5])],
+        process(&mut r, &segment(false, 1234, 5000, NONE, &[4,5]), 3)
+    );
+    // retransmitted syn is ignored
+    assert_eq!(Vec::<Event>::new(), process(&mut r, &segment(true, 1234, 999, SYN, &[]), 4));
+
+    // fin with data
+    assert_eq!(
+        vec![Event::Data(client, 3, vec![6]), Event::Fin(client)],
+        process(&mut r, &segment(true, 1234, 1003, FIN, &[6]), 5)
+    );
+    // data after the fin is ignored
+    assert_eq!(Vec::<Event>::new(), process(&mut r, &segment(true, 1234, 1004, NONE, &[7]), 6));
+
+    // out of order fin only gets reported after the missing data
+    assert_eq!(Vec::<Event>::new(), process(&mut r, &segment(false, 1234, 5004, FIN, &[]), 7));
+    assert_eq!(1, r.connection_count());
+    assert_eq!(
+        vec![Event::Data(server, 2, vec![8,9]), Event::Fin(server)],
+        process(&mut r, &segment(false, 1234, 5002, NONE, &[8,9]), 8)
+    );
+    // both directions finished -> connection removed
+    assert_eq!(0, r.connection_count());
+    assert_eq!(0, r.buffered_bytes());
+}
+
+#[test]
+fn padded_ethernet_frames() {
+    let client = client_key(1234);
+    let mut r = TcpReassembler::new(Default::default());
+
+    // segments in ethernet frames padded to the minimum frame size
+    let process_padded = |r: &mut TcpReassembler, packet: &[u8], now: u64| -> Vec<Event> {
+        let mut frame = Vec::new();
+        Ethernet2Header{
+            source: [1,2,3,4,5,6],
+            destination: [7,8,9,10,11,12],
+            ether_type: ether_type::IPV4,
+        }.write(&mut frame).unwrap();
+        frame.extend_from_slice(packet);
+        assert!(frame.len() < 60);
+        frame.resize(60, 0xee);
+
+        let mut events = Vec::new();
+        assert!(r.process(&SlicedPacket::from_ethernet(&frame).unwrap(), now, |e| events.push(e.into())));
+        events
+    };
+
+    // the padding is not treated as data
+    assert_eq!(Vec::<Event>::new(), process_padded(&mut r, &segment(true, 1234, 999, SYN, &[]), 0));
+    assert_eq!(Vec::<Event>::new(), process_padded(&mut r, &segment(false, 1234, 4999, SYN, &[]), 1));
+    assert_eq!(Vec::<Event>::new(), process_padded(&mut r, &segment(true, 1234, 1000, NONE, &[]), 2));
+    assert_eq!(
+        vec![Event::Data(client, 0, b"hi".to_vec())],
+        process_padded(&mut r, &segment(true, 1234, 1000, NONE, b"hi"), 3)
+    );
+    assert_eq!(
+        vec![Event::Fin(client)],
+        process_padded(&mut r, &segment(true, 1234, 1002, FIN, &[]), 4)
+    );
+    assert_eq!(0, r.buffered_bytes());
+}
+
+#[test]
+fn out_of_order_retransmission_and_overlap() {
+    let client = client_key(1234);
+    let mut r = TcpReassembler::new(Default::default());
+
+    // connection picked up mid stream
+    assert_eq!(
+        vec![Event::Data(client, 0, vec![1,2])],
+        process(&mut r, &segment(true, 1234, 1000, NONE, &[1,2]), 0)
+    );
+    // out of order
+    assert_eq!(Vec::<Event>::new(), process(&mut r, &segment(true, 1234, 1006, NONE, &[7,8,9,10]), 0));
+    assert_eq!(4, r.buffered_bytes());
+    // overlapping the buffered data (the first received copy is used)
+    assert_eq!(Vec::<Event>::new(), process(&mut r, &segment(true, 1234, 1005, NONE, &[6,0,0,0,0,11]), 0));
+    assert_eq!(6, r.buffered_bytes());
+    // filling the gap, overlapping delivered & buffered bytes
+    assert_eq!(
+        vec![
+            Event::Data(client, 2, vec![3,4,5]),
+            Event::Data(client, 5, vec![6]),
+            Event::Data(client, 6, vec![7,8,9,10]),
+            Event::Data(client, 10, vec![11]),
+        ],
+        process(&mut r, &segment(true, 1234, 1000, NONE, &[0,0,3,4,5,0,0]), 0)
+    );
+    assert_eq!(0, r.buffered_bytes());
+    // retransmission
+    assert_eq!(Vec::<Event>::new(), process(&mut r, &segment(true, 1234, 1004, NONE, &[0,0,0]), 0));
+    // data before the start of the stream
+    assert_eq!(
+        vec![Event::Data(client, 11, vec![12])],
+        process(&mut r, &segment(true, 1234, 990, NONE, &{
+            let mut payload = [0;22];
+            payload[21] = 12;
+            payload
+        }), 0)
+    );
+}
+
+#[test]
+fn sequence_number_wraparound() {
+    let client = client_key(1234);
+    let mut r = TcpReassembler::new(Default::default());
+    assert_eq!(Vec::<Event>::new(), process(&mut r, &segment(true, 1234, u32::MAX - 1, SYN, &[]), 0));
+    assert_eq!(Vec::<Event>::new(), process(&mut r, &segment(true, 1234, 2, NONE, &[4,5]), 0));
+    assert_eq!(
+        vec![
+            Event::Data(client, 0, vec![1,2,3]),
+            Event::Data(client, 3, vec![4,5]),
+        ],
+        process(&mut r, &segment(true, 1234, u32::MAX, NONE, &[1,2,3]), 0)
+    );
+    assert_eq!(
+        vec![Event::Data(client, 5, vec![6]), Event::Fin(client)],
+        process(&mut r, &segment(true, 1234, 4, FIN, &[6]), 0)
+    );
+}
+
+#[test]
+fn memory_limits() {
+    let client = client_key(1234);
+
+    // per stream limit
+    {
+        let mut r = TcpReassembler::new(TcpReassemblerConfig{
+            max_buffered_bytes_per_stream: 4,
+            ..Default::default()
+        });
+        process(&mut r, &segment(true, 1234, 1000, NONE, &[1,2]), 0);
+        assert_eq!(Vec::<Event>::new(), process(&mut r, &segment(true, 1234, 1010, NONE, &[3,4,5,6]), 0));
+        assert_eq!(
+            vec![
+                Event::Gap(client, 2, 8),
+                Event::Data(client, 10, vec![3,4,5,6]),
+            ],
+            process(&mut r, &segment(true, 1234, 1020, NONE, &[7]), 0)
+        );
+        assert_eq!(1, r.buffered_bytes());
+        // segments bigger then the limit
+        assert_eq!(
+            vec![
+                Event::Gap(client, 14, 6),
+                Event::Data(client, 20, vec![7]),
+                Event::Gap(client, 21, 9),
+                Event::Data(client, 30, vec![8,9,10,11,12]),
+            ],
+            process(&mut r, &segment(true, 1234, 1030, NONE, &[8,9,10,11,12]), 0)
+        );
+        assert_eq!(0, r.buffered_bytes());
+    }
+
+    // limit over all connections
+    {
+        let mut r = TcpReassembler::new(TcpReassemblerConfig{
+            max_buffered_bytes: 4,
+            ..Default::default()
+        });
+        process(&mut r, &segment(true, 1, 1000, NONE, &[1]), 0);
+        process(&mut r, &segment(true, 2, 1000, NONE, &[1]), 0);
+        assert_eq!(Vec::<Event>::new(), process(&mut r, &segment(true, 1, 1010, NONE, &[2,3,4]), 0));
+        assert_eq!(
+            vec![
+                Event::Gap(client_key(2), 1, 9),
+                Event::Data(client_key(2), 10, vec![5,6]),
+            ],
+            process(&mut r, &segment(true, 2, 1010, NONE, &[5,6]), 0)
+        );
+        assert_eq!(3, r.buffered_bytes());
+    }
+}
+
+#[test]
+fn reset() {
+    let client = client_key(1234);
+    let server = client.reversed();
+    let mut r = TcpReassembler::new(Default::default());
+    process(&mut r, &segment(true, 1234, 1000, NONE, &[1]), 0);
+    process(&mut r, &segment(true, 1234, 1002, NONE, &[3]), 0);
+    assert_eq!(1, r.buffered_bytes());
+    assert_eq!(
+        vec![
+            Event::Gap(client, 1, 1),
+            Event::Data(client, 2, vec![3]),
+            Event::Reset(server),
+        ],
+        process(&mut r, &segment(false, 1234, 5000, RST, &[]), 0)
+    );
+    assert_eq!(0, r.connection_count());
+    assert_eq!(0, r.buffered_bytes());
+
+    // resets of unknown connections are ignored
+    assert_eq!(Vec::<Event>::new(), process(&mut r, &segment(false, 1234, 5000, RST, &[]), 0));
+    assert_eq!(0, r.connection_count());
+
+    // reset by the endpoint with the greater key & buffered data in both directions
+    assert!(client < server);
+    process(&mut r, &segment(true, 1234, 1000, NONE, &[1]), 0);
+    process(&mut r, &segment(true, 1234, 1002, NONE, &[3]), 0);
+    process(&mut r, &segment(false, 1234, 5000, NONE, &[4]), 0);
+    process(&mut r, &segment(false, 1234, 5003, NONE, &[7]), 0);
+    assert_eq!(2, r.buffered_bytes());
+    assert_eq!(
+        vec![
+            Event::Gap(client, 1, 1),
+            Event::Data(client, 2, vec![3]),
+            Event::Gap(server, 1, 2),
+            Event::Data(server, 3, vec![7]),
+            Event::Reset(server),
+        ],
+        process(&mut r, &segment(false, 1234, 5004, RST, &[]), 0)
+    );
+    assert_eq!(0, r.connection_count());
+    assert_eq!(0, r.buffered_bytes());
+}
+
+#[test]
+fn eviction() {
+    // idle timeout
+    {
+        let mut r = TcpReassembler::new(TcpReassemblerConfig{
+            idle_timeout: 100,
+            ..Default::default()
+        });
+        process(&mut r, &segment(true, 1, 1000, NONE, &[1]), 0);
+        process(&mut r, &segment(true, 1, 1002, NONE, &[3]), 0);
+        process(&mut r, &segment(true, 2, 1000, NONE, &[1]), 50);
+
+        let mut events = Vec::new();
+        r.evict_idle(99, |e| events.push(Event::from(e)));
+        assert_eq!(Vec::<Event>::new(), events);
+
+        r.evict_idle(100, |e| events.push(Event::from(e)));
+        assert_eq!(
+            vec![
+                Event::Gap(client_key(1), 1, 1),
+                Event::Data(client_key(1), 2, vec![3]),
+                Event::Evicted(client_key(1)),
+                Event::Evicted(client_key(1).reversed()),
+            ],
+            events
+        );
+        assert_eq!(1, r.connection_count());
+        assert_eq!(0, r.buffered_bytes());
+    }
+
+    // connection limit (least recently active connection is evicted)
+    {
+        let mut r = TcpReassembler::new(TcpReassemblerConfig{
+            max_connections: 2,
+            ..Default::default()
+        });
+        process(&mut r, &segment(true, 1, 1000, NONE, &[1]), 0);
+        process(&mut r, &segment(true, 2, 1000, NONE, &[1]), 1);
+        process(&mut r, &segment(true, 1, 1001, NONE, &[2]), 2);
+        assert_eq!(
+            vec![
+                Event::Evicted(client_key(2)),
+                Event::Evicted(client_key(2).reversed()),
+                Event::Data(client_key(3), 0, vec![1]),
+            ],
+            process(&mut r, &segment(true, 3, 1000, NONE, &[1]), 3)
+        );
+        assert_eq!(2, r.connection_count());
+    }
+
+    // flush (finished directions are not reported as evicted)
+    {
+        let mut r = TcpReassembler::new(Default::default());
+        process(&mut r, &segment(true, 1, 1000, FIN, &[1]), 0);
+        process(&mut r, &segment(true, 2, 1000, NONE, &[1]), 0);
+        let mut events = Vec::new();
+        r.flush(|e| events.push(Event::from(e)));
+        assert_eq!(
+            vec![
+                Event::Evicted(client_key(1).reversed()),
+                Event::Evicted(client_key(2)),
+                Event::Evicted(client_key(2).reversed()),
+            ],
+            events
+        );
+        assert_eq!(0, r.connection_count());
+    }
+}
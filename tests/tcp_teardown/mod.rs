@@ -0,0 +1,245 @@
+use super::*;
+
+fn ipv4_tcp(syn: bool, ack: Option<u32>, payload: &[u8]) -> Vec<u8> {
+    let builder = PacketBuilder::ethernet2([2,3,4,5,6,7], [8,9,10,11,12,13])
+        .single_vlan(12)
+        .ipv4([192,168,1,1], [192,168,1,2], 20)
+        .tcp(1234, 80, 1000, 4000);
+    let builder = if syn { builder.syn() } else { builder };
+    let builder = match ack {
+        Some(value) => builder.ack(value),
+        None => builder,
+    };
+    let mut result = Vec::with_capacity(builder.size(payload.len()));
+    builder.write(&mut result, payload).unwrap();
+    result
+}
+
+fn decode(frame: &[u8]) -> PacketHeaders<'_> {
+    PacketHeaders::from_ethernet_slice(frame).unwrap()
+}
+
+fn tcp_header(headers: &PacketHeaders) -> TcpHeader {
+    match &headers.transport {
+        Some(TransportHeader::Tcp(tcp)) => tcp.clone(),
+        _ => panic!("expected tcp header"),
+    }
+}
+
+fn ipv4_header(headers: &PacketHeaders) -> Ipv4Header {
+    match &headers.ip {
+        Some(IpHeader::Version4(ip, _)) => ip.clone(),
+        _ => panic!("expected ipv4 header"),
+    }
+}
+
+#[test]
+fn rst_ack() {
+    let packet = ipv4_tcp(false, Some(2000), &[1,2,3,4]);
+    let observed = SlicedPacket::from_ethernet(&packet).unwrap();
+    let builder = TcpTeardownBuilder::new(TcpTeardownKind::RstAck);
+
+    // toward the sender
+    {
+        let mut buffer = Vec::new();
+        builder.write_to_sender(&mut buffer, &observed).unwrap();
+        let headers = decode(&buffer);
+        assert_eq!(
+            Some(Ethernet2Header{
+                source: [8,9,10,11,12,13],
+                destination: [2,3,4,5,6,7],
+                ether_type: ether_type::VLAN_TAGGED_FRAME,
+            }),
+            headers.link
+        );
+        assert_eq!(observed.vlan.as_ref().map(|v| v.to_header()), headers.vlan);
+        let ip = ipv4_header(&headers);
+        assert_eq!([192,168,1,2], ip.source);
+        assert_eq!([192,168,1,1], ip.destination);
+        assert_eq!(64, ip.time_to_live);
+        assert_eq!(ip.calc_header_checksum().unwrap(), ip.header_checksum);
+        let tcp = tcp_header(&headers);
+        assert_eq!(80, tcp.source_port);
+        assert_eq!(1234, tcp.destination_port);
+        assert_eq!(2000, tcp.sequence_number);
+        assert_eq!(1004, tcp.acknowledgment_number);
+        assert!(tcp.rst && tcp.ack && !tcp.fin && !tcp.syn);
+        assert_eq!(0, tcp.window_size);
+        assert_eq!(tcp.calc_checksum_ipv4(&ip, &[]).unwrap(), tcp.checksum);
+        assert!(headers.payload.is_empty());
+    }
+
+    // toward the receiver
+    {
+        let mut buffer = Vec::new();
+        builder.write_to_receiver(&mut buffer, &observed).unwrap();
+        let headers = decode(&buffer);
+        assert_eq!(observed.link.as_ref().map(|l| l.to_header()), headers.link);
+        let ip = ipv4_header(&headers);
+        assert_eq!([192,168,1,1], ip.source);
+        assert_eq!([192,168,1,2], ip.destination);
+        let tcp = tcp_header(&headers);
+        assert_eq!(1234, tcp.source_port);
+        assert_eq!(80, tcp.destination_port);
+        assert_eq!(1004, tcp.sequence_number);
+        assert_eq!(2000, tcp.acknowledgment_number);
+        assert!(tcp.rst && tcp.ack);
+        assert_eq!(4000, tcp.window_size);
+        assert_eq!(tcp.calc_checksum_ipv4(&ip, &[]).unwrap(), tcp.checksum);
+    }
+}
+
+#[test]
+fn rst() {
+    let builder = TcpTeardownBuilder::new(TcpTeardownKind::Rst).time_to_live(10);
+
+    // observed segment with ack flag
+    {
+        let packet = ipv4_tcp(false, Some(2000), &[1,2,3,4]);
+        let observed = SlicedPacket::from_ethernet(&packet).unwrap();
+
+        let mut buffer = Vec::new();
+        builder.write_to_sender(&mut buffer, &observed).unwrap();
+        let headers = decode(&buffer);
+        assert_eq!(10, ipv4_header(&headers).time_to_live);
+        let tcp = tcp_header(&headers);
+        assert_eq!(2000, tcp.sequence_number);
+        assert!(tcp.rst && !tcp.ack);
+        assert_eq!(0, tcp.acknowledgment_number);
+
+        let mut buffer = Vec::new();
+        builder.write_to_receiver(&mut buffer, &observed).unwrap();
+        let tcp = tcp_header(&decode(&buffer));
+        assert_eq!(1004, tcp.sequence_number);
+        assert!(tcp.rst && !tcp.ack);
+    }
+
+    // observed syn without ack flag (rfc793 reset generation)
+    {
+        let packet = ipv4_tcp(true, None, &[]);
+        let observed = SlicedPacket::from_ethernet(&packet).unwrap();
+
+        let mut buffer = Vec::new();
+        builder.write_to_sender(&mut buffer, &observed).unwrap();
+        let tcp = tcp_header(&decode(&buffer));
+        assert_eq!(0, tcp.sequence_number);
+        assert_eq!(1001, tcp.acknowledgment_number);
+        assert!(tcp.rst && tcp.ack);
+
+        let mut buffer = Vec::new();
+        builder.write_to_receiver(&mut buffer, &observed).unwrap();
+        let tcp = tcp_header(&decode(&buffer));
+        assert_eq!(1001, tcp.sequence_number);
+        assert!(tcp.rst && !tcp.ack);
+    }
+}
+
+#[test]
+fn fin_ack() {
+    // sequence numbers wrap around
+    let builder = PacketBuilder::ipv6([1;16], [2;16], 20)
+        .tcp(1234, 80, u32::MAX, 4000)
+        .ack(2000)
+        .fin();
+    let mut packet = Vec::new();
+    builder.write(&mut packet, &[1,2]).unwrap();
+    let observed = SlicedPacket::from_ip(&packet).unwrap();
+    let builder = TcpTeardownBuilder::new(TcpTeardownKind::FinAck);
+
+    let mut buffer = Vec::new();
+    builder.write_to_sender(&mut buffer, &observed).unwrap();
+    let headers = PacketHeaders::from_ip_slice(&buffer).unwrap();
+    assert_eq!(None, headers.link);
+    let ip = match &headers.ip {
+        Some(IpHeader::Version6(ip, _)) => ip.clone(),
+        _ => panic!("expected ipv6 header"),
+    };
+    assert_eq!([2;16], ip.source);
+    assert_eq!([1;16], ip.destination);
+    assert_eq!(64, ip.hop_limit);
+    let tcp = tcp_header(&headers);
+    assert!(tcp.fin && tcp.ack && !tcp.rst);
+    assert_eq!(2000, tcp.sequence_number);
+    assert_eq!(2, tcp.acknowledgment_number);
+    assert_eq!(tcp.calc_checksum_ipv6(&ip, &[]).unwrap(), tcp.checksum);
+
+    let mut buffer = Vec::new();
+    builder.write_to_receiver(&mut buffer, &observed).unwrap();
+    let headers = PacketHeaders::from_ip_slice(&buffer).unwrap();
+    let tcp = tcp_header(&headers);
+    assert!(tcp.fin && tcp.ack);
+    assert_eq!(2, tcp.sequence_number);
+    assert_eq!(2000, tcp.acknowledgment_number);
+}
+
+#[test]
+fn padded_ethernet_frame() {
+    // pure ack padded to the minimum ethernet frame size (the
+    // padding is not part of the segment length)
+    let mut packet = ipv4_tcp(false, Some(2000), &[]);
+    assert!(packet.len() < 64);
+    packet.resize(64, 0xee);
+    let observed = SlicedPacket::from_ethernet(&packet).unwrap();
+    let builder = TcpTeardownBuilder::new(TcpTeardownKind::Rst);
+
+    let mut buffer = Vec::new();
+    builder.write_to_receiver(&mut buffer, &observed).unwrap();
+    let tcp = tcp_header(&decode(&buffer));
+    assert!(tcp.rst);
+    assert_eq!(1000, tcp.sequence_number);
+
+    let mut buffer = Vec::new();
+    TcpTeardownBuilder::new(TcpTeardownKind::RstAck).write_to_sender(&mut buffer, &observed).unwrap();
+    let tcp = tcp_header(&decode(&buffer));
+    assert!(tcp.rst && tcp.ack);
+    assert_eq!(2000, tcp.sequence_number);
+    assert_eq!(1000, tcp.acknowledgment_number);
+}
+
+#[test]
+fn errors() {
+    use TcpTeardownError::*;
+    let builder = TcpTeardownBuilder::new(TcpTeardownKind::RstAck);
+
+    // no tcp header
+    {
+        let udp_builder = PacketBuilder::ipv4([192,168,1,1], [192,168,1,2], 20).udp(1, 2);
+        let mut packet = Vec::new();
+        udp_builder.write(&mut packet, &[]).unwrap();
+        let observed = SlicedPacket::from_ip(&packet).unwrap();
+        let mut buffer = Vec::new();
+        assert_matches!(builder.write_to_sender(&mut buffer, &observed), Err(NoTcpHeader));
+        assert_matches!(builder.write_to_receiver(&mut buffer, &observed), Err(NoTcpHeader));
+        assert!(buffer.is_empty());
+    }
+
+    // write error
+    {
+        let packet = ipv4_tcp(false, Some(2000), &[]);
+        let observed = SlicedPacket::from_ethernet(&packet).unwrap();
+        let mut slice = [0u8;10];
+        let mut writer = std::io::Cursor::new(&mut slice[..]);
+        assert_matches!(
+            builder.write_to_sender(&mut writer, &observed),
+            Err(WriteError(etherparse::WriteError::IoError(_)))
+        );
+    }
+
+    // display, source & debug
+    {
+        use std::error::Error;
+        assert_eq!(
+            "TcpTeardownError: The observed packet does not contain an IP header & TCP header.",
+            &format!("{}", NoTcpHeader)
+        );
+        assert!(NoTcpHeader.source().is_none());
+        assert!(!format!("{:?}", NoTcpHeader).is_empty());
+
+        let err = TcpTeardownError::from(std::io::Error::new(std::io::ErrorKind::UnexpectedEof, "some error"));
+        assert_eq!("some error", &format!("{}", err));
+        assert!(err.source().is_some());
+
+        let err = TcpTeardownError::from(ValueError::TcpLengthTooLarge(0));
+        assert_matches!(err, WriteError(etherparse::WriteError::ValueError(_)));
+    }
+}
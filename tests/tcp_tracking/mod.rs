@@ -0,0 +1,486 @@
+use super::*;
+
+const CLIENT: [u8;4] = [192,168,1,1];
+const SERVER: [u8;4] = [192,168,1,2];
+
+/// Description of a segment between the client & server.
+#[derive(Clone, Copy)]
+struct Seg {
+    from_client: bool,
+    seq: u32,
+    ack: Option<u32>,
+    syn: bool,
+    fin: bool,
+    rst: bool,
+    window: u16,
+    window_scale: Option<u8>,
+    payload_len: usize,
+}
+
+const SEG: Seg = Seg {
+    from_client: true,
+    seq: 0,
+    ack: None,
+    syn: false,
+    fin: false,
+    rst: false,
+    window: 1000,
+    window_scale: None,
+    payload_len: 0,
+};
+
+fn packet(seg: Seg) -> Vec<u8> {
+    let builder = if seg.from_client {
+        PacketBuilder::ipv4(CLIENT, SERVER, 20).tcp(1234, 80, seg.seq, seg.window)
+    } else {
+        PacketBuilder::ipv4(SERVER, CLIENT, 20).tcp(80, 1234, seg.seq, seg.window)
+    };
+    let builder = match seg.ack {
+        Some(ack) => builder.ack(ack),
+        None => builder,
+    };
+    let builder = if seg.syn { builder.syn() } else { builder };
+    let builder = if seg.fin { builder.fin() } else { builder };
+    let builder = if seg.rst { builder.rst() } else { builder };
+    let builder = match seg.window_scale {
+        Some(scale) => builder.options(&[TcpOptionElement::WindowScale(scale)]).unwrap(),
+        None => builder,
+    };
+    let payload = vec![0u8;seg.payload_len];
+    let mut result = Vec::with_capacity(builder.size(payload.len()));
+    builder.write(&mut result, &payload).unwrap();
+    result
+}
+
+fn process(tracker: &mut TcpConnectionTracker, seg: Seg, now: u64) -> Result<(TcpState, TcpState), TcpTrackingError> {
+    let packet = packet(seg);
+    tracker
+        .process(&SlicedPacket::from_ip(&packet).unwrap(), now)
+        .map(|info| (info.initiator_state, info.responder_state))
+}
+
+fn client_key() -> TcpStreamKey {
+    TcpStreamKey {
+        source: TcpStreamAddress::Ipv4(CLIENT),
+        source_port: 1234,
+        destination: TcpStreamAddress::Ipv4(SERVER),
+        destination_port: 80,
+    }
+}
+
+/// Creates a tracker with an established connection (client isn 999 & server isn 4999).
+fn established(config: TcpConnectionTrackerConfig) -> TcpConnectionTracker {
+    let mut t = TcpConnectionTracker::new(config);
+    process(&mut t, Seg{ seq: 999, syn: true, ..SEG }, 0).unwrap();
+    process(&mut t, Seg{ from_client: false, seq: 4999, ack: Some(1000), syn: true, ..SEG }, 0).unwrap();
+    process(&mut t, Seg{ seq: 1000, ack: Some(5000), ..SEG }, 0).unwrap();
+    t
+}
+
+#[test]
+fn config_default() {
+    let config = TcpConnectionTrackerConfig::default();
+    assert_eq!(65536, config.max_connections);
+    assert_eq!(120_000, config.handshake_timeout);
+    assert_eq!(432_000_000, config.established_timeout);
+    assert_eq!(120_000, config.closing_timeout);
+    assert_eq!(120_000, config.time_wait_timeout);
+    assert_eq!(10_000, config.closed_timeout);
+    assert_eq!(&config, TcpConnectionTracker::new(config.clone()).config());
+}
+
+#[test]
+fn lifecycle() {
+    use TcpState::*;
+    let mut t = TcpConnectionTracker::new(Default::default());
+
+    // handshake
+    assert_eq!(Ok((SynSent, Listen)), process(&mut t, Seg{ seq: 999, syn: true, ..SEG }, 1));
+    assert_eq!(Ok((SynSent, Listen)), process(&mut t, Seg{ seq: 999, syn: true, ..SEG }, 2));
+    assert_eq!(
+        Ok((Established, SynReceived)),
+        process(&mut t, Seg{ from_client: false, seq: 4999, ack: Some(1000), syn: true, ..SEG }, 3)
+    );
+    assert_eq!(
+        Ok((Established, SynReceived)),
+        process(&mut t, Seg{ from_client: false, seq: 4999, ack: Some(1000), syn: true, ..SEG }, 4)
+    );
+    assert_eq!(
+        Ok((Established, Established)),
+        process(&mut t, Seg{ seq: 1000, ack: Some(5000), payload_len: 10, ..SEG }, 5)
+    );
+    assert_eq!(
+        Some(TcpConnectionInfo{
+            key: client_key(),
+            initiator_state: Established,
+            responder_state: Established,
+            last_activity: 5,
+        }),
+        t.connection(&client_key().reversed())
+    );
+
+    // data
+    assert_eq!(
+        Ok((Established, Established)),
+        process(&mut t, Seg{ from_client: false, seq: 5000, ack: Some(1010), payload_len: 100, ..SEG }, 6)
+    );
+
+    // close initiated by the client
+    assert_eq!(
+        Ok((FinWait1, CloseWait)),
+        process(&mut t, Seg{ seq: 1010, ack: Some(5100), fin: true, ..SEG }, 7)
+    );
+    assert_eq!(
+        Ok((FinWait2, CloseWait)),
+        process(&mut t, Seg{ from_client: false, seq: 5100, ack: Some(1011), payload_len: 10, ..SEG }, 8)
+    );
+    assert_eq!(
+        Ok((TimeWait, LastAck)),
+        process(&mut t, Seg{ from_client: false, seq: 5110, ack: Some(1011), fin: true, ..SEG }, 9)
+    );
+    assert_eq!(
+        Ok((TimeWait, Closed)),
+        process(&mut t, Seg{ seq: 1011, ack: Some(5111), ..SEG }, 10)
+    );
+
+    // time wait timeout
+    assert_eq!(Vec::<TcpConnectionInfo>::new(), t.evict_idle(10 + 120_000 - 1));
+    assert_eq!(
+        vec![TcpConnectionInfo{
+            key: client_key(),
+            initiator_state: TimeWait,
+            responder_state: Closed,
+            last_activity: 10,
+        }],
+        t.evict_idle(10 + 120_000)
+    );
+    assert_eq!(0, t.connection_count());
+}
+
+#[test]
+fn padded_ethernet_frames() {
+    use TcpState::*;
+    let mut t = TcpConnectionTracker::new(Default::default());
+
+    // segments in ethernet frames padded to the minimum frame size
+    let mut process_padded = |seg: Seg| -> Result<(TcpState, TcpState), TcpTrackingError> {
+        let mut frame = Vec::new();
+        Ethernet2Header{
+            source: [1,2,3,4,5,6],
+            destination: [7,8,9,10,11,12],
+            ether_type: ether_type::IPV4,
+        }.write(&mut frame).unwrap();
+        frame.extend_from_slice(&packet(seg));
+        assert!(frame.len() < 60);
+        frame.resize(60, 0xee);
+        t.process(&SlicedPacket::from_ethernet(&frame).unwrap(), 1)
+            .map(|info| (info.initiator_state, info.responder_state))
+    };
+
+    // the padding is not counted as part of the segments
+    assert_eq!(Ok((SynSent, Listen)), process_padded(Seg{ seq: 999, syn: true, ..SEG }));
+    assert_eq!(
+        Ok((Established, SynReceived)),
+        process_padded(Seg{ from_client: false, seq: 4999, ack: Some(1000), syn: true, ..SEG })
+    );
+    assert_eq!(
+        Ok((Established, Established)),
+        process_padded(Seg{ seq: 1000, ack: Some(5000), ..SEG })
+    );
+    assert_eq!(
+        Ok((Established, Established)),
+        process_padded(Seg{ seq: 1000, ack: Some(5000), payload_len: 2, ..SEG })
+    );
+    assert_eq!(
+        Ok((Established, Established)),
+        process_padded(Seg{ from_client: false, seq: 5000, ack: Some(1002), ..SEG })
+    );
+}
+
+#[test]
+fn simultaneous_close() {
+    use TcpState::*;
+    let mut t = established(Default::default());
+    assert_eq!(
+        Ok((FinWait1, CloseWait)),
+        process(&mut t, Seg{ seq: 1000, ack: Some(5000), fin: true, ..SEG }, 1)
+    );
+    // fin of the server does not acknowledge the fin of the client
+    assert_eq!(
+        Ok((Closing, LastAck)),
+        process(&mut t, Seg{ from_client: false, seq: 5000, ack: Some(1000), fin: true, ..SEG }, 2)
+    );
+    assert_eq!(
+        Ok((TimeWait, LastAck)),
+        process(&mut t, Seg{ from_client: false, seq: 5001, ack: Some(1001), ..SEG }, 3)
+    );
+    assert_eq!(
+        Ok((TimeWait, Closed)),
+        process(&mut t, Seg{ seq: 1001, ack: Some(5001), ..SEG }, 4)
+    );
+}
+
+#[test]
+fn reset() {
+    use TcpState::*;
+    use TcpTrackingError::*;
+
+    // reset as answer to a syn
+    {
+        let mut t = TcpConnectionTracker::new(Default::default());
+        process(&mut t, Seg{ seq: 999, syn: true, ..SEG }, 0).unwrap();
+        assert_eq!(
+            Err(OutOfWindow),
+            process(&mut t, Seg{ from_client: false, ack: Some(999), rst: true, ..SEG }, 0)
+        );
+        assert_eq!(
+            Ok((Closed, Closed)),
+            process(&mut t, Seg{ from_client: false, ack: Some(1000), rst: true, ..SEG }, 0)
+        );
+        // closed connection time out
+        assert_eq!(1, t.evict_idle(10_000).len());
+    }
+
+    // reset of an established connection
+    {
+        let mut t = established(Default::default());
+        assert_eq!(
+            Err(OutOfWindow),
+            process(&mut t, Seg{ from_client: false, seq: 5000 + 2000, rst: true, ..SEG }, 0)
+        );
+        assert_eq!(
+            Ok((Established, Established)),
+            t.connection(&client_key()).map(|info| (info.initiator_state, info.responder_state)).ok_or(UnknownConnection)
+        );
+        assert_eq!(
+            Ok((Closed, Closed)),
+            process(&mut t, Seg{ from_client: false, seq: 5000, rst: true, ..SEG }, 0)
+        );
+        // a new syn reopens the connection
+        assert_eq!(Ok((SynSent, Listen)), process(&mut t, Seg{ seq: 3000, syn: true, ..SEG }, 0));
+    }
+}
+
+#[test]
+fn reopen() {
+    use TcpState::*;
+    let mut t = TcpConnectionTracker::new(Default::default());
+    process(&mut t, Seg{ seq: 999, syn: true, ..SEG }, 0).unwrap();
+    // syn with a new initial sequence number
+    assert_eq!(Ok((SynSent, Listen)), process(&mut t, Seg{ seq: 2999, syn: true, ..SEG }, 0));
+    assert_eq!(
+        Err(TcpTrackingError::InvalidAcknowledgment),
+        process(&mut t, Seg{ from_client: false, seq: 4999, ack: Some(1000), syn: true, ..SEG }, 0)
+    );
+    assert_eq!(
+        Ok((Established, SynReceived)),
+        process(&mut t, Seg{ from_client: false, seq: 4999, ack: Some(3000), syn: true, ..SEG }, 0)
+    );
+    assert_eq!(1, t.connection_count());
+}
+
+#[test]
+fn invalid_segments() {
+    use TcpState::*;
+    use TcpTrackingError::*;
+
+    // no tcp header
+    {
+        let mut t = TcpConnectionTracker::new(Default::default());
+        let builder = PacketBuilder::ipv4(CLIENT, SERVER, 20).udp(1, 2);
+        let mut packet = Vec::new();
+        builder.write(&mut packet, &[]).unwrap();
+        assert_eq!(Err(NoTcpHeader), t.process(&SlicedPacket::from_ip(&packet).unwrap(), 0));
+    }
+
+    // flags & unknown connections
+    {
+        let mut t = TcpConnectionTracker::new(Default::default());
+        assert_eq!(Err(InvalidFlags), process(&mut t, Seg{ syn: true, fin: true, ..SEG }, 0));
+        assert_eq!(Err(InvalidFlags), process(&mut t, Seg{ syn: true, rst: true, ..SEG }, 0));
+        assert_eq!(Err(InvalidFlags), process(&mut t, Seg{ fin: true, ..SEG }, 0));
+        assert_eq!(Err(InvalidFlags), process(&mut t, SEG, 0));
+        assert_eq!(Err(UnknownConnection), process(&mut t, Seg{ ack: Some(1), ..SEG }, 0));
+        assert_eq!(Err(UnknownConnection), process(&mut t, Seg{ rst: true, ..SEG }, 0));
+        assert_eq!(Err(UnknownConnection), process(&mut t, Seg{ syn: true, ack: Some(1), ..SEG }, 0));
+        assert_eq!(0, t.connection_count());
+    }
+
+    // handshake violations
+    {
+        let mut t = TcpConnectionTracker::new(Default::default());
+        process(&mut t, Seg{ seq: 999, syn: true, ..SEG }, 0).unwrap();
+        assert_eq!(
+            Err(UnexpectedSegment{ state: Listen }),
+            process(&mut t, Seg{ from_client: false, seq: 4999, ack: Some(1000), ..SEG }, 0)
+        );
+        assert_eq!(
+            Err(UnexpectedSegment{ state: Listen }),
+            process(&mut t, Seg{ from_client: false, seq: 4999, syn: true, ..SEG }, 0)
+        );
+        assert_eq!(
+            Err(UnexpectedSegment{ state: SynSent }),
+            process(&mut t, Seg{ seq: 1000, ack: Some(5000), ..SEG }, 0)
+        );
+        process(&mut t, Seg{ from_client: false, seq: 4999, ack: Some(1000), syn: true, ..SEG }, 0).unwrap();
+        assert_eq!(
+            Err(DataBeforeHandshake),
+            process(&mut t, Seg{ from_client: false, seq: 5000, ack: Some(1000), payload_len: 1, ..SEG }, 0)
+        );
+        assert_eq!(
+            Err(UnexpectedSegment{ state: SynReceived }),
+            process(&mut t, Seg{ from_client: false, seq: 5999, ack: Some(1000), syn: true, ..SEG }, 0)
+        );
+        assert_eq!(
+            Err(UnexpectedSegment{ state: Established }),
+            process(&mut t, Seg{ seq: 999, ack: Some(5000), syn: true, ..SEG }, 0)
+        );
+    }
+
+    // window violations
+    {
+        let mut t = established(Default::default());
+        // beyond the window of the server
+        assert_eq!(Err(OutOfWindow), process(&mut t, Seg{ seq: 1000 + 1001, ack: Some(5000), ..SEG }, 0));
+        assert_eq!(
+            Ok((Established, Established)),
+            process(&mut t, Seg{ seq: 1000 + 1000, ack: Some(5000), payload_len: 1, ..SEG }, 0)
+        );
+        // too old
+        assert_eq!(Err(OutOfWindow), process(&mut t, Seg{ seq: 1000 - 1000, ack: Some(5000), ..SEG }, 0));
+        // acknowledgment of unsent data
+        assert_eq!(Err(InvalidAcknowledgment), process(&mut t, Seg{ seq: 2000, ack: Some(5001), ..SEG }, 0));
+        // acknowledgment too old
+        assert_eq!(Err(InvalidAcknowledgment), process(&mut t, Seg{ seq: 2000, ack: Some(5000u32.wrapping_sub(66001)), ..SEG }, 0));
+    }
+
+    // data after fin
+    {
+        let mut t = established(Default::default());
+        process(&mut t, Seg{ seq: 1000, ack: Some(5000), fin: true, ..SEG }, 0).unwrap();
+        assert_eq!(
+            Err(UnexpectedSegment{ state: FinWait1 }),
+            process(&mut t, Seg{ seq: 1000, ack: Some(5000), payload_len: 1, ..SEG }, 0)
+        );
+        // retransmitted fin
+        assert_eq!(
+            Ok((FinWait1, CloseWait)),
+            process(&mut t, Seg{ seq: 1000, ack: Some(5000), fin: true, ..SEG }, 0)
+        );
+    }
+}
+
+#[test]
+fn window_scaling() {
+    use TcpTrackingError::*;
+    fn connect(client_scale: Option<u8>, server_scale: Option<u8>) -> TcpConnectionTracker {
+        let mut t = TcpConnectionTracker::new(Default::default());
+        process(&mut t, Seg{ seq: 999, syn: true, window_scale: client_scale, ..SEG }, 0).unwrap();
+        process(&mut t, Seg{ from_client: false, seq: 4999, ack: Some(1000), syn: true, window_scale: server_scale, ..SEG }, 0).unwrap();
+        process(&mut t, Seg{ seq: 1000, ack: Some(5000), ..SEG }, 0).unwrap();
+        // server advertises a window of 1000 (scaled if both sides support scaling)
+        process(&mut t, Seg{ from_client: false, seq: 5000, ack: Some(1000), ..SEG }, 0).unwrap();
+        t
+    }
+
+    // both sides use window scaling
+    let mut t = connect(Some(2), Some(4));
+    assert!(process(&mut t, Seg{ seq: 1000 + 16000, ack: Some(5000), ..SEG }, 0).is_ok());
+    assert_eq!(Err(OutOfWindow), process(&mut t, Seg{ seq: 1000 + 16001, ack: Some(5000), ..SEG }, 0));
+
+    // window scaling only used if both sides support it
+    let mut t = connect(Some(2), None);
+    assert_eq!(Err(OutOfWindow), process(&mut t, Seg{ seq: 1000 + 1001, ack: Some(5000), ..SEG }, 0));
+}
+
+#[test]
+fn timeouts_and_limits() {
+    use TcpState::*;
+    use TcpTrackingError::*;
+
+    // handshake timeout
+    {
+        let mut t = TcpConnectionTracker::new(Default::default());
+        process(&mut t, Seg{ seq: 999, syn: true, ..SEG }, 0).unwrap();
+        assert_eq!(
+            Err(UnknownConnection),
+            process(&mut t, Seg{ from_client: false, seq: 4999, ack: Some(1000), syn: true, ..SEG }, 120_000)
+        );
+        assert_eq!(0, t.connection_count());
+    }
+
+    // established & closing timeouts
+    {
+        let mut t = established(Default::default());
+        assert_eq!(0, t.evict_idle(432_000_000 - 1).len());
+        assert_eq!(
+            Ok((Established, Established)),
+            process(&mut t, Seg{ seq: 1000, ack: Some(5000), ..SEG }, 432_000_000 - 1)
+        );
+        process(&mut t, Seg{ seq: 1000, ack: Some(5000), fin: true, ..SEG }, 432_000_000).unwrap();
+        assert_eq!(0, t.evict_idle(432_000_000 + 120_000 - 1).len());
+        assert_eq!(1, t.evict_idle(432_000_000 + 120_000).len());
+    }
+
+    // connection limit
+    {
+        let mut t = TcpConnectionTracker::new(TcpConnectionTrackerConfig{
+            max_connections: 1,
+            ..Default::default()
+        });
+        process(&mut t, Seg{ seq: 999, syn: true, ..SEG }, 0).unwrap();
+        let builder = PacketBuilder::ipv4(CLIENT, SERVER, 20).tcp(4321, 80, 0, 1000).syn();
+        let mut packet = Vec::new();
+        builder.write(&mut packet, &[]).unwrap();
+        t.process(&SlicedPacket::from_ip(&packet).unwrap(), 1).unwrap();
+        assert_eq!(1, t.connection_count());
+        assert_eq!(None, t.connection(&client_key()));
+    }
+
+    // the least recently active connection gets removed
+    {
+        let mut t = TcpConnectionTracker::new(TcpConnectionTrackerConfig{
+            max_connections: 2,
+            ..Default::default()
+        });
+        let syn = |t: &mut TcpConnectionTracker, port: u16, now: u64| {
+            let builder = PacketBuilder::ipv4(CLIENT, SERVER, 20).tcp(port, 80, 0, 1000).syn();
+            let mut packet = Vec::new();
+            builder.write(&mut packet, &[]).unwrap();
+            t.process(&SlicedPacket::from_ip(&packet).unwrap(), now).unwrap();
+        };
+        let key = |port: u16| TcpStreamKey{ source_port: port, ..client_key() };
+        process(&mut t, Seg{ seq: 999, syn: true, ..SEG }, 0).unwrap();
+        syn(&mut t, 4321, 1);
+        process(&mut t, Seg{ from_client: false, seq: 4999, ack: Some(1000), syn: true, ..SEG }, 2).unwrap();
+        syn(&mut t, 5678, 3);
+        assert_eq!(2, t.connection_count());
+        assert!(t.connection(&client_key()).is_some());
+        assert_eq!(None, t.connection(&key(4321)));
+        assert!(t.connection(&key(5678)).is_some());
+        syn(&mut t, 4321, 4);
+        assert_eq!(None, t.connection(&client_key()));
+        assert!(t.connection(&key(4321)).is_some());
+        assert!(t.connection(&key(5678)).is_some());
+    }
+}
+
+#[test]
+fn error_display_source() {
+    use std::error::Error;
+    use TcpTrackingError::*;
+    let tests = [
+        (NoTcpHeader, "TcpTrackingError: The packet does not contain an IP header & TCP header."),
+        (InvalidFlags, "TcpTrackingError: Invalid combination of TCP flags (SYN together with FIN or RST, or none of SYN, ACK & RST set)."),
+        (UnknownConnection, "TcpTrackingError: Segment (other then an initial SYN) of a connection that is not tracked."),
+        (UnexpectedSegment{ state: TcpState::Listen }, "TcpTrackingError: Unexpected segment from an endpoint in the state Listen."),
+        (DataBeforeHandshake, "TcpTrackingError: Data sent before the three way handshake was completed."),
+        (OutOfWindow, "TcpTrackingError: Sequence numbers of the segment are outside of the receive window."),
+        (InvalidAcknowledgment, "TcpTrackingError: Acknowledgment number acknowledges unsent data or is too old."),
+    ];
+    for (error, expected) in &tests {
+        assert_eq!(*expected, &format!("{}", error));
+        assert!(error.source().is_none());
+        assert!(!format!("{:?}", error).is_empty());
+    }
+}
@@ -54,4 +54,111 @@ mod icmp_echo_header {
             );
         }
     }
-}
\ No newline at end of file
+}
+mod extended_echo_request_header {
+    use super::*;
+
+    proptest!{
+        #[test]
+        fn to_bytes(
+            id in any::<u16>(),
+            seq in any::<u8>(),
+            local in any::<bool>()
+        ) {
+            let id_bytes = id.to_be_bytes();
+            assert_eq!(
+                ExtendedEchoRequestHeader{ id, seq, local }.to_bytes(),
+                [id_bytes[0], id_bytes[1], seq, if local { 1 } else { 0 }]
+            );
+        }
+
+        #[test]
+        fn from_bytes(
+            bytes in any::<[u8;4]>()
+        ) {
+            let value = ExtendedEchoRequestHeader::from_bytes(bytes);
+            assert_eq!(
+                value,
+                ExtendedEchoRequestHeader {
+                    id: u16::from_be_bytes([bytes[0], bytes[1]]),
+                    seq: bytes[2],
+                    local: 0 != bytes[3] & 1,
+                }
+            );
+            // reserved bits are dropped
+            assert_eq!(
+                value.to_bytes(),
+                [bytes[0], bytes[1], bytes[2], bytes[3] & 1]
+            );
+        }
+    }
+}
+
+mod extended_echo_reply_code {
+    use super::*;
+    use ExtendedEchoReplyCode::*;
+
+    #[test]
+    fn from_u8_code_u8() {
+        let values = [
+            (0, NoError),
+            (1, MalformedQuery),
+            (2, NoSuchInterface),
+            (3, NoSuchTableEntry),
+            (4, MultipleInterfacesSatisfyQuery),
+        ];
+        for (code_u8, code) in values {
+            assert_eq!(Some(code), ExtendedEchoReplyCode::from_u8(code_u8));
+            assert_eq!(code_u8, code.code_u8());
+        }
+        for code_u8 in 5..=u8::MAX {
+            assert_eq!(None, ExtendedEchoReplyCode::from_u8(code_u8));
+        }
+    }
+}
+
+mod extended_echo_reply_header {
+    use super::*;
+
+    proptest!{
+        #[test]
+        fn to_bytes_from_bytes(
+            code_u8 in 0u8..=4,
+            bytes in any::<[u8;4]>()
+        ) {
+            let code = ExtendedEchoReplyCode::from_u8(code_u8).unwrap();
+            let value = ExtendedEchoReplyHeader::from_bytes(code, bytes);
+            assert_eq!(
+                value,
+                ExtendedEchoReplyHeader {
+                    code,
+                    id: u16::from_be_bytes([bytes[0], bytes[1]]),
+                    seq: bytes[2],
+                    state: bytes[3] >> 5,
+                    active: 0 != bytes[3] & 0b100,
+                    ipv4: 0 != bytes[3] & 0b10,
+                    ipv6: 0 != bytes[3] & 0b1,
+                }
+            );
+            // reserved bits are dropped
+            assert_eq!(
+                value.to_bytes(),
+                [bytes[0], bytes[1], bytes[2], bytes[3] & 0b1110_0111]
+            );
+        }
+    }
+
+    #[test]
+    fn to_bytes_state_masked() {
+        let value = ExtendedEchoReplyHeader {
+            code: ExtendedEchoReplyCode::NoError,
+            id: 0x1234,
+            seq: 5,
+            state: 0b1111_1010,
+            active: true,
+            ipv4: false,
+            ipv6: true,
+        };
+        assert_eq!(value.to_bytes(), [0x12, 0x34, 5, 0b0100_0101]);
+    }
+}
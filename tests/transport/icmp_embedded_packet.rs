@@ -0,0 +1,196 @@
+use super::super::*;
+
+fn ipv4_udp(payload: &[u8]) -> Vec<u8> {
+    let builder = PacketBuilder::ipv4([192, 168, 1, 1], [192, 168, 1, 2], 20)
+        .udp(1234, 53);
+    let mut result = Vec::with_capacity(builder.size(payload.len()));
+    builder.write(&mut result, payload).unwrap();
+    result
+}
+
+fn ipv4_tcp(payload: &[u8]) -> Vec<u8> {
+    let builder = PacketBuilder::ipv4([192, 168, 1, 1], [192, 168, 1, 2], 20)
+        .tcp(4321, 80, 0x1234_5678, 1024);
+    let mut result = Vec::with_capacity(builder.size(payload.len()));
+    builder.write(&mut result, payload).unwrap();
+    result
+}
+
+fn ipv6_udp(payload: &[u8]) -> Vec<u8> {
+    let builder = PacketBuilder::ipv6([1; 16], [2; 16], 20)
+        .udp(1234, 53);
+    let mut result = Vec::with_capacity(builder.size(payload.len()));
+    builder.write(&mut result, payload).unwrap();
+    result
+}
+
+#[test]
+fn from_slice() {
+    // complete udp packet
+    {
+        let packet = ipv4_udp(&[1, 2, 3, 4]);
+        let slice = IcmpEmbeddedPacketSlice::from_slice(&packet).unwrap();
+        assert_matches!(slice.ip, InternetSlice::Ipv4(_, _));
+        assert_eq!(ip_number::UDP, slice.ip_number);
+        assert_matches!(slice.transport, Some(TransportSlice::Udp(_)));
+        assert_eq!(&[1, 2, 3, 4], slice.payload);
+        assert_eq!(Some(1234), slice.source_port());
+        assert_eq!(Some(53), slice.destination_port());
+    }
+
+    // truncated tcp header (only the first 8 bytes quoted)
+    {
+        let packet = ipv4_tcp(&[1, 2, 3, 4]);
+        let slice = IcmpEmbeddedPacketSlice::from_slice(&packet[..20 + 8]).unwrap();
+        assert_eq!(ip_number::TCP, slice.ip_number);
+        assert_eq!(None, slice.transport);
+        assert_eq!(&packet[20..28], slice.payload);
+        assert_eq!(Some(4321), slice.source_port());
+        assert_eq!(Some(80), slice.destination_port());
+    }
+
+    // total length smaller then the quoted data
+    {
+        let mut packet = ipv4_udp(&[1, 2, 3, 4]);
+        packet.extend_from_slice(&[5, 6, 7, 8]);
+        let slice = IcmpEmbeddedPacketSlice::from_slice(&packet).unwrap();
+        assert_eq!(&[1, 2, 3, 4], slice.payload);
+    }
+
+    // ipv6 with a truncated udp header
+    {
+        let packet = ipv6_udp(&[1, 2, 3, 4]);
+        let slice = IcmpEmbeddedPacketSlice::from_slice(&packet[..40 + 4]).unwrap();
+        assert_matches!(slice.ip, InternetSlice::Ipv6(_, _));
+        assert_eq!(ip_number::UDP, slice.ip_number);
+        assert_eq!(None, slice.transport);
+        assert_eq!(Some(1234), slice.source_port());
+        assert_eq!(Some(53), slice.destination_port());
+
+        // not enough data left for the ports
+        let slice = IcmpEmbeddedPacketSlice::from_slice(&packet[..40 + 3]).unwrap();
+        assert_eq!(None, slice.source_port());
+        assert_eq!(None, slice.destination_port());
+    }
+
+    // fragmented payload (no transport decoding)
+    {
+        let mut packet = ipv4_udp(&[1, 2, 3, 4]);
+        // set the "more fragments" flag
+        packet[6] = 0b0010_0000;
+        let slice = IcmpEmbeddedPacketSlice::from_slice(&packet).unwrap();
+        assert_eq!(None, slice.transport);
+        assert_eq!(&packet[20..], slice.payload);
+        assert_eq!(None, slice.source_port());
+    }
+
+    // quoted icmp message
+    {
+        let builder = PacketBuilder::ipv4([192, 168, 1, 1], [192, 168, 1, 2], 20)
+            .icmpv4_echo_request(1, 2);
+        let mut packet = Vec::with_capacity(builder.size(4));
+        builder.write(&mut packet, &[1, 2, 3, 4]).unwrap();
+        let slice = IcmpEmbeddedPacketSlice::from_slice(&packet).unwrap();
+        assert_matches!(slice.transport, Some(TransportSlice::Icmpv4(_)));
+        assert_eq!(&[1, 2, 3, 4], slice.payload);
+        assert_eq!(None, slice.source_port());
+    }
+
+    // errors
+    {
+        use ReadError::*;
+        assert_matches!(IcmpEmbeddedPacketSlice::from_slice(&[]), Err(UnexpectedEndOfSlice(1)));
+        assert_matches!(IcmpEmbeddedPacketSlice::from_slice(&[0x50]), Err(IpUnsupportedVersion(5)));
+        let packet = ipv4_udp(&[]);
+        assert_matches!(IcmpEmbeddedPacketSlice::from_slice(&packet[..19]), Err(UnexpectedEndOfSlice(20)));
+    }
+}
+
+#[test]
+fn icmpv4_embedded_packet() {
+    let quoted = ipv4_tcp(&[]);
+    let quoted = &quoted[..28];
+
+    // error messages
+    {
+        let error_types = [
+            Icmpv4Type::DestinationUnreachable(icmpv4::DestUnreachableHeader::Port),
+            Icmpv4Type::Redirect(icmpv4::RedirectHeader{ code: icmpv4::RedirectCode::RedirectForNetwork, gateway_internet_address: [0;4] }),
+            Icmpv4Type::TimeExceeded(icmpv4::TimeExceededCode::TtlExceededInTransit),
+            Icmpv4Type::ParameterProblem(icmpv4::ParameterProblemHeader::PointerIndicatesError(0)),
+        ];
+        for t in &error_types {
+            let mut bytes = Vec::new();
+            Icmpv4Header::with_checksum(t.clone(), quoted).write(&mut bytes).unwrap();
+            bytes.extend_from_slice(quoted);
+            let slice = Icmpv4Slice::from_slice(&bytes).unwrap();
+            let embedded = slice.embedded_packet().unwrap().unwrap();
+            assert_eq!(ip_number::TCP, embedded.ip_number);
+            assert_eq!(Some(4321), embedded.source_port());
+        }
+    }
+
+    // extension structure is not part of the embedded packet
+    {
+        let mut quoted = ipv4_udp(&[0; 100]);
+        quoted.resize(128, 0);
+        let mut bytes = vec![icmpv4::TYPE_TIME_EXCEEDED, 0, 0, 0, 0, 32, 0, 0];
+        bytes.extend_from_slice(&quoted);
+        bytes.extend_from_slice(&[0x20, 0, 0, 0, 0, 4, 1, 1]);
+        let slice = Icmpv4Slice::from_slice(&bytes).unwrap();
+        let embedded = slice.embedded_packet().unwrap().unwrap();
+        assert_eq!(&quoted[28..], embedded.payload);
+    }
+
+    // non error messages
+    {
+        let mut bytes = Vec::new();
+        Icmpv4Header::new(Icmpv4Type::EchoRequest(IcmpEchoHeader{ id: 1, seq: 2 })).write(&mut bytes).unwrap();
+        bytes.extend_from_slice(quoted);
+        assert!(Icmpv4Slice::from_slice(&bytes).unwrap().embedded_packet().is_none());
+    }
+
+    // error while decoding the quoted packet
+    {
+        let mut bytes = Vec::new();
+        Icmpv4Header::new(Icmpv4Type::DestinationUnreachable(icmpv4::DestUnreachableHeader::Port)).write(&mut bytes).unwrap();
+        bytes.extend_from_slice(&quoted[..10]);
+        assert_matches!(
+            Icmpv4Slice::from_slice(&bytes).unwrap().embedded_packet(),
+            Some(Err(ReadError::UnexpectedEndOfSlice(20)))
+        );
+    }
+}
+
+#[test]
+fn icmpv6_embedded_packet() {
+    let quoted = ipv6_udp(&[1, 2, 3, 4]);
+
+    // error messages
+    {
+        let error_types = [
+            Icmpv6Type::DestinationUnreachable(icmpv6::DestUnreachableCode::Port),
+            Icmpv6Type::PacketTooBig{ mtu: 1280 },
+            Icmpv6Type::TimeExceeded(icmpv6::TimeExceededCode::HopLimitExceeded),
+            Icmpv6Type::ParameterProblem(icmpv6::ParameterProblemHeader{ code: icmpv6::ParameterProblemCode::ErroneousHeaderField, pointer: 0 }),
+        ];
+        for t in &error_types {
+            let mut bytes = Vec::new();
+            Icmpv6Header::new(*t).write(&mut bytes).unwrap();
+            bytes.extend_from_slice(&quoted);
+            let slice = Icmpv6Slice::from_slice(&bytes).unwrap();
+            let embedded = slice.embedded_packet().unwrap().unwrap();
+            assert_matches!(embedded.transport, Some(TransportSlice::Udp(_)));
+            assert_eq!(&[1, 2, 3, 4], embedded.payload);
+            assert_eq!(Some(53), embedded.destination_port());
+        }
+    }
+
+    // non error messages
+    {
+        let mut bytes = Vec::new();
+        Icmpv6Header::new(Icmpv6Type::EchoReply(IcmpEchoHeader{ id: 1, seq: 2 })).write(&mut bytes).unwrap();
+        bytes.extend_from_slice(&quoted);
+        assert!(Icmpv6Slice::from_slice(&bytes).unwrap().embedded_packet().is_none());
+    }
+}
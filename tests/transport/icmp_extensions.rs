@@ -0,0 +1,207 @@
+use super::super::*;
+
+/// Prepends an extension header (with calculated checksum) to the given objects.
+fn extension_structure(objects: &[u8]) -> Vec<u8> {
+    let mut result = vec![0x20, 0, 0, 0];
+    result.extend_from_slice(objects);
+    let checksum = etherparse::checksum::Sum16BitWords::new()
+        .add_slice(&result)
+        .ones_complement()
+        .to_be();
+    result[2..4].copy_from_slice(&checksum.to_be_bytes());
+    result
+}
+
+#[test]
+fn from_slice() {
+    use ReadError::*;
+
+    // ok
+    {
+        let bytes = extension_structure(&[0, 8, 1, 1, 0x12, 0x34, 0x51, 0x40]);
+        let slice = IcmpExtensionsSlice::from_slice(&bytes).unwrap();
+        assert_eq!(2, slice.version());
+        assert_eq!(u16::from_be_bytes([bytes[2], bytes[3]]), slice.checksum());
+        assert_eq!(&bytes[..], slice.slice());
+        assert!(slice.is_checksum_valid());
+        assert_eq!(
+            "IcmpExtensionsSlice { slice: [32, 0, 123, 130, 0, 8, 1, 1, 18, 52, 81, 64] }",
+            format!("{:?}", slice)
+        );
+        assert_eq!(slice.clone(), slice);
+    }
+
+    // bad checksum
+    {
+        let mut bytes = extension_structure(&[0, 8, 1, 1, 0x12, 0x34, 0x51, 0x40]);
+        bytes[11] = 0x41;
+        assert!(!IcmpExtensionsSlice::from_slice(&bytes).unwrap().is_checksum_valid());
+    }
+
+    // no checksum transmitted
+    {
+        let bytes = [0x20, 0, 0, 0, 0, 4, 9, 9];
+        assert!(IcmpExtensionsSlice::from_slice(&bytes).unwrap().is_checksum_valid());
+    }
+
+    // too short
+    for len in 0..4 {
+        assert_matches!(
+            IcmpExtensionsSlice::from_slice(&[0x20, 0, 0, 0][..len]),
+            Err(UnexpectedEndOfSlice(4))
+        );
+    }
+
+    // unsupported version
+    assert_matches!(
+        IcmpExtensionsSlice::from_slice(&[0x10, 0, 0, 0]),
+        Err(IcmpExtensionUnsupportedVersion(1))
+    );
+}
+
+#[test]
+fn mpls_label_stack_entry() {
+    let entry = IcmpMplsLabelStackEntry {
+        label: 0x12345,
+        traffic_class: 0b101,
+        bottom_of_stack: true,
+        ttl: 0x40,
+    };
+    assert_eq!([0x12, 0x34, 0x5b, 0x40], entry.to_bytes());
+    assert_eq!(entry, IcmpMplsLabelStackEntry::from_bytes(entry.to_bytes()));
+    assert_eq!(
+        IcmpMplsLabelStackEntry { label: 0xfffff, traffic_class: 0b111, bottom_of_stack: false, ttl: 0xff },
+        IcmpMplsLabelStackEntry::from_bytes([0xff, 0xff, 0xfe, 0xff])
+    );
+}
+
+#[test]
+fn objects() {
+    use icmp_extension::*;
+    use IcmpExtensionObject::*;
+
+    let bytes = extension_structure(&[
+        // mpls label stack
+        0, 12, CLASS_NUM_MPLS_LABEL_STACK, C_TYPE_MPLS_INCOMING_LABEL_STACK,
+        0x12, 0x34, 0x50, 0x01,
+        0x67, 0x89, 0xa1, 0x01,
+        // interface information (incoming interface with all fields)
+        0, 28, CLASS_NUM_INTERFACE_INFORMATION, 0b0000_1111,
+        0, 0, 0, 7,
+        0, 1, 0, 0, 192, 0, 2, 1,
+        8, b'e', b't', b'h', b'0', 0, 0, 0,
+        0, 0, 0x05, 0xdc,
+        // interface information (outgoing interface with ipv6 address only)
+        0, 24, CLASS_NUM_INTERFACE_INFORMATION, 0b1000_0100,
+        0, 2, 0, 0, 0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1,
+        // interface identification
+        0, 8, CLASS_NUM_INTERFACE_IDENTIFICATION, C_TYPE_INTERFACE_IDENTIFICATION_NAME,
+        b'l', b'o', 0, 0,
+        0, 8, CLASS_NUM_INTERFACE_IDENTIFICATION, C_TYPE_INTERFACE_IDENTIFICATION_INDEX,
+        0, 0, 0, 3,
+        0, 12, CLASS_NUM_INTERFACE_IDENTIFICATION, C_TYPE_INTERFACE_IDENTIFICATION_ADDRESS,
+        0, 1, 4, 0, 10, 0, 0, 1,
+        // unknown
+        0, 6, 99, 1, 1, 2,
+    ]);
+    let slice = IcmpExtensionsSlice::from_slice(&bytes).unwrap();
+    assert!(slice.is_checksum_valid());
+
+    let expected = [
+        MplsLabelStack(&[[0x12, 0x34, 0x50, 0x01], [0x67, 0x89, 0xa1, 0x01]]),
+        InterfaceInformation(IcmpInterfaceInformation {
+            role: 0,
+            if_index: Some(7),
+            address: Some(IcmpInterfaceAddress::Ipv4([192, 0, 2, 1])),
+            name: Some(b"eth0\0\0\0"),
+            mtu: Some(1500),
+        }),
+        InterfaceInformation(IcmpInterfaceInformation {
+            role: 2,
+            if_index: None,
+            address: Some(IcmpInterfaceAddress::Ipv6([0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1])),
+            name: None,
+            mtu: None,
+        }),
+        InterfaceIdentification(IcmpInterfaceIdentification::Name(b"lo\0\0")),
+        InterfaceIdentification(IcmpInterfaceIdentification::Index(3)),
+        InterfaceIdentification(IcmpInterfaceIdentification::Address { afi: AFI_IPV4, address: &[10, 0, 0, 1] }),
+        Unknown { class_num: 99, c_type: 1, payload: &[1, 2] },
+    ];
+    assert_eq!(
+        expected.iter().map(|o| Ok(o.clone())).collect::<Vec<_>>(),
+        slice.objects().collect::<Vec<_>>()
+    );
+    assert_eq!(
+        [1, 2, 2, 3, 3, 3, 99],
+        {
+            let mut class_nums = [0u8; 7];
+            for (i, o) in expected.iter().enumerate() {
+                class_nums[i] = o.class_num();
+            }
+            class_nums
+        }
+    );
+
+    // decoding the label stack entries
+    if let Some(Ok(MplsLabelStack(entries))) = slice.objects().next() {
+        assert_eq!(
+            IcmpMplsLabelStackEntry { label: 0x12345, traffic_class: 0, bottom_of_stack: false, ttl: 1 },
+            IcmpMplsLabelStackEntry::from_bytes(entries[0])
+        );
+    } else {
+        panic!("expected a mpls label stack");
+    }
+}
+
+#[test]
+fn read_errors() {
+    use icmp_extension::*;
+    use IcmpExtensionObjectReadError::*;
+
+    let errors = [
+        (&[0u8, 4, 1][..], UnexpectedEndOfSlice { expected_len: 4, actual_len: 3 }),
+        (&[0, 3, 1, 1], UnexpectedLength { class_num: 1, c_type: 1, length: 3 }),
+        (&[0, 12, 1, 1, 0, 0, 0, 0], UnexpectedEndOfSlice { expected_len: 12, actual_len: 8 }),
+        (&[0, 6, CLASS_NUM_MPLS_LABEL_STACK, 1, 0, 0], UnexpectedLength { class_num: 1, c_type: 1, length: 6 }),
+        (&[0, 4, CLASS_NUM_INTERFACE_INFORMATION, 0b1000], UnexpectedLength { class_num: 2, c_type: 0b1000, length: 4 }),
+        (&[0, 12, CLASS_NUM_INTERFACE_INFORMATION, 0b1, 0, 0, 0, 0, 0, 0, 0, 0], UnexpectedLength { class_num: 2, c_type: 0b1, length: 12 }),
+        (&[0, 8, CLASS_NUM_INTERFACE_INFORMATION, 0b10, 9, 0, 0, 0], UnexpectedLength { class_num: 2, c_type: 0b10, length: 8 }),
+        (&[0, 12, CLASS_NUM_INTERFACE_INFORMATION, 0b100, 0, 2, 0, 0, 0, 0, 0, 0], UnexpectedLength { class_num: 2, c_type: 0b100, length: 12 }),
+        (&[0, 12, CLASS_NUM_INTERFACE_INFORMATION, 0b100, 0, 3, 0, 0, 0, 0, 0, 0], UnsupportedAddressFamily(3)),
+        (&[0, 6, CLASS_NUM_INTERFACE_IDENTIFICATION, C_TYPE_INTERFACE_IDENTIFICATION_INDEX, 0, 0], UnexpectedLength { class_num: 3, c_type: 2, length: 6 }),
+        (&[0, 8, CLASS_NUM_INTERFACE_IDENTIFICATION, C_TYPE_INTERFACE_IDENTIFICATION_ADDRESS, 0, 1, 4, 0], UnexpectedLength { class_num: 3, c_type: 3, length: 8 }),
+    ];
+    for (bytes, expected) in &errors {
+        let mut iter = IcmpExtensionObjectsIterator::from_slice(bytes);
+        assert_eq!(Some(Err(expected.clone())), iter.next());
+        assert_eq!(0, iter.rest().len());
+        assert_eq!(None, iter.next());
+    }
+
+    assert_eq!(
+        "IcmpExtensionObjectReadError: Not enough memory left in slice to read extension object (expected at least 12 bytes, only 8 bytes available).",
+        &format!("{}", UnexpectedEndOfSlice { expected_len: 12, actual_len: 8 })
+    );
+    assert_eq!(
+        "IcmpExtensionObjectReadError: Length value 6 of the extension object with class-num 1 and c-type 1 does not match the content of the object.",
+        &format!("{}", UnexpectedLength { class_num: 1, c_type: 1, length: 6 })
+    );
+    assert_eq!(
+        "IcmpExtensionObjectReadError: Address family 3 in interface information object is not supported.",
+        &format!("{}", UnsupportedAddressFamily(3))
+    );
+    {
+        use std::error::Error;
+        assert!(UnsupportedAddressFamily(3).source().is_none());
+    }
+}
+
+#[test]
+fn debug() {
+    let iter = IcmpExtensionObjectsIterator::from_slice(&[0, 8, 1, 1, 0, 0, 0x11, 0xff, 0, 4, 1]);
+    assert_eq!(
+        "[MplsLabelStack([[0, 0, 17, 255]]), Err(UnexpectedEndOfSlice { expected_len: 4, actual_len: 3 })]",
+        &format!("{:?}", iter)
+    );
+}
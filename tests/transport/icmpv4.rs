@@ -23,6 +23,8 @@ fn constants() {
     assert_eq!(TYPE_INFO_REPLY, 16);
     assert_eq!(TYPE_ADDRESS, 17);
     assert_eq!(TYPE_ADDRESSREPLY, 18);
+    assert_eq!(TYPE_EXT_ECHO_REQUEST, 42);
+    assert_eq!(TYPE_EXT_ECHO_REPLY, 43);
 
     // destination unreachable code numbers according to
     // https://www.iana.org/assignments/icmp-parameters/icmp-parameters.xhtml#icmp-parameters-codes-3
@@ -440,6 +442,30 @@ mod icmpv4_type {
             code: RedirectCode::RedirectForNetwork,
             gateway_internet_address: [0;4],
         };
+        let dummy_router_adv = RouterAdvertisementHeader{
+            num_addrs: 1,
+            addr_entry_size: 2,
+            lifetime: 1800,
+        };
+        let dummy_mask = AddressMaskMessage{
+            id: 1,
+            seq: 2,
+            address_mask: [255, 255, 255, 0],
+        };
+        let dummy_ext_echo_req = ExtendedEchoRequestHeader{
+            id: 1,
+            seq: 2,
+            local: true,
+        };
+        let dummy_ext_echo_reply = ExtendedEchoReplyHeader{
+            code: ExtendedEchoReplyCode::NoError,
+            id: 1,
+            seq: 2,
+            state: 2,
+            active: true,
+            ipv4: true,
+            ipv6: false,
+        };
         let tests = [
             (8, Unknown{type_u8: 0, code_u8: 0, bytes5to8: [0;4]}),
             (8, EchoReply(dummy_echo)),
@@ -450,6 +476,14 @@ mod icmpv4_type {
             (8, ParameterProblem(ParameterProblemHeader::BadLength)),
             (20, TimestampRequest(dummy_ts.clone())),
             (20, TimestampReply(dummy_ts)),
+            (8, RouterAdvertisement(dummy_router_adv)),
+            (8, RouterSolicitation),
+            (8, InfoRequest(dummy_echo)),
+            (8, InfoReply(dummy_echo)),
+            (12, AddressMaskRequest(dummy_mask.clone())),
+            (12, AddressMaskReply(dummy_mask)),
+            (8, ExtendedEchoRequest(dummy_ext_echo_req)),
+            (8, ExtendedEchoReply(dummy_ext_echo_reply)),
         ];
         for t in tests {
             assert_eq!(t.0, t.1.header_len());
@@ -475,6 +509,30 @@ mod icmpv4_type {
             code: RedirectCode::RedirectForNetwork,
             gateway_internet_address: [0;4],
         };
+        let dummy_router_adv = RouterAdvertisementHeader{
+            num_addrs: 1,
+            addr_entry_size: 2,
+            lifetime: 1800,
+        };
+        let dummy_mask = AddressMaskMessage{
+            id: 1,
+            seq: 2,
+            address_mask: [255, 255, 255, 0],
+        };
+        let dummy_ext_echo_req = ExtendedEchoRequestHeader{
+            id: 1,
+            seq: 2,
+            local: true,
+        };
+        let dummy_ext_echo_reply = ExtendedEchoReplyHeader{
+            code: ExtendedEchoReplyCode::NoError,
+            id: 1,
+            seq: 2,
+            state: 2,
+            active: true,
+            ipv4: true,
+            ipv6: false,
+        };
         let tests = [
             (None, Unknown{type_u8: 0, code_u8: 0, bytes5to8: [0;4]}),
             (None, EchoReply(dummy_echo)),
@@ -485,6 +543,14 @@ mod icmpv4_type {
             (None, ParameterProblem(ParameterProblemHeader::BadLength)),
             (Some(0), TimestampRequest(dummy_ts.clone())),
             (Some(0), TimestampReply(dummy_ts)),
+            (None, RouterAdvertisement(dummy_router_adv)),
+            (Some(0), RouterSolicitation),
+            (Some(0), InfoRequest(dummy_echo)),
+            (Some(0), InfoReply(dummy_echo)),
+            (Some(0), AddressMaskRequest(dummy_mask.clone())),
+            (Some(0), AddressMaskReply(dummy_mask)),
+            (None, ExtendedEchoRequest(dummy_ext_echo_req)),
+            (Some(0), ExtendedEchoReply(dummy_ext_echo_reply)),
         ];
         for t in tests {
             assert_eq!(t.0, t.1.fixed_payload_size());
@@ -509,6 +575,7 @@ mod icmpv4_type {
             unknown_type_u8 in any::<u8>(),
             unknown_code_u8 in any::<u8>(),
             bytes5to8 in any::<[u8;4]>(),
+            ext_echo_reply_code_u8 in 0u8..=4,
             payload in proptest::collection::vec(any::<u8>(), 0..1024)
         ) {
             let ts = TimestampMessage{
@@ -542,6 +609,17 @@ mod icmpv4_type {
                 ParameterProblem(param_prob),
                 TimestampRequest(ts.clone()),
                 TimestampReply(ts),
+                RouterAdvertisement(RouterAdvertisementHeader::from_bytes(bytes5to8)),
+                RouterSolicitation,
+                InfoRequest(echo),
+                InfoReply(echo),
+                AddressMaskRequest(AddressMaskMessage{ id, seq, address_mask: bytes5to8 }),
+                AddressMaskReply(AddressMaskMessage{ id, seq, address_mask: bytes5to8 }),
+                ExtendedEchoRequest(ExtendedEchoRequestHeader::from_bytes(bytes5to8)),
+                ExtendedEchoReply(ExtendedEchoReplyHeader::from_bytes(
+                    ExtendedEchoReplyCode::from_u8(ext_echo_reply_code_u8).unwrap(),
+                    bytes5to8
+                )),
             ];
             
             for t in values {
@@ -576,6 +654,30 @@ mod icmpv4_type {
             code: RedirectCode::RedirectForNetwork,
             gateway_internet_address: [0;4],
         };
+        let dummy_router_adv = RouterAdvertisementHeader{
+            num_addrs: 1,
+            addr_entry_size: 2,
+            lifetime: 1800,
+        };
+        let dummy_mask = AddressMaskMessage{
+            id: 1,
+            seq: 2,
+            address_mask: [255, 255, 255, 0],
+        };
+        let dummy_ext_echo_req = ExtendedEchoRequestHeader{
+            id: 1,
+            seq: 2,
+            local: true,
+        };
+        let dummy_ext_echo_reply = ExtendedEchoReplyHeader{
+            code: ExtendedEchoReplyCode::NoError,
+            id: 1,
+            seq: 2,
+            state: 2,
+            active: true,
+            ipv4: true,
+            ipv6: false,
+        };
         let tests = [
             Unknown{type_u8: 0, code_u8: 0, bytes5to8: [0;4]},
             EchoReply(dummy_echo),
@@ -586,6 +688,14 @@ mod icmpv4_type {
             ParameterProblem(ParameterProblemHeader::BadLength),
             TimestampRequest(dummy_ts.clone()),
             TimestampReply(dummy_ts),
+            RouterAdvertisement(dummy_router_adv),
+            RouterSolicitation,
+            InfoRequest(dummy_echo),
+            InfoReply(dummy_echo),
+            AddressMaskRequest(dummy_mask.clone()),
+            AddressMaskReply(dummy_mask),
+            ExtendedEchoRequest(dummy_ext_echo_req),
+            ExtendedEchoReply(dummy_ext_echo_reply),
         ];
         for t in tests {
             assert_eq!(t.clone(), t);
@@ -754,6 +864,12 @@ mod icmpv4_header {
                 // timestamp with non-zero code
                 (TYPE_TIMESTAMP_REPLY, non_zero_code),
                 (TYPE_TIMESTAMP, non_zero_code),
+                // address mask with zero code
+                (TYPE_ADDRESS, 0u8),
+                (TYPE_ADDRESSREPLY, 0u8),
+                // address mask with non-zero code
+                (TYPE_ADDRESS, non_zero_code),
+                (TYPE_ADDRESSREPLY, non_zero_code),
             ] {
                 let b = {
                     let mut b = bytes.clone();
@@ -1178,6 +1294,37 @@ mod icmpv4_slice {
                 assert_eq!(slice.slice(), &bytes[..8]);
             }
         }
+
+        // address mask tests
+        for mask_type_u8 in [TYPE_ADDRESS, TYPE_ADDRESSREPLY] {
+            let bytes = {
+                let mut bytes = [0u8;16];
+                bytes[0] = mask_type_u8;
+                bytes
+            };
+
+            // valid address mask messages (with & without trailing bytes)
+            for len in 12..16 {
+                let slice = Icmpv4Slice::from_slice(&bytes[..len]).unwrap();
+                assert_eq!(slice.slice(), &bytes[..len]);
+            }
+
+            // too short address mask messages
+            for bad_len in 8..12 {
+                assert_matches!(
+                    Icmpv4Slice::from_slice(&bytes[..bad_len]),
+                    Err(UnexpectedEndOfSlice(AddressMaskMessage::SERIALIZED_SIZE))
+                );
+            }
+
+            // address mask with a non zero code
+            {
+                let mut bytes = bytes;
+                bytes[1] = 1;
+                let slice = Icmpv4Slice::from_slice(&bytes[..8]).unwrap();
+                assert_eq!(slice.slice(), &bytes[..8]);
+            }
+        }
     }
 
     proptest!{
@@ -1212,6 +1359,30 @@ mod icmpv4_slice {
             code: RedirectCode::RedirectForNetwork,
             gateway_internet_address: [0;4],
         };
+        let dummy_router_adv = RouterAdvertisementHeader{
+            num_addrs: 1,
+            addr_entry_size: 2,
+            lifetime: 1800,
+        };
+        let dummy_mask = AddressMaskMessage{
+            id: 1,
+            seq: 2,
+            address_mask: [255, 255, 255, 0],
+        };
+        let dummy_ext_echo_req = ExtendedEchoRequestHeader{
+            id: 1,
+            seq: 2,
+            local: true,
+        };
+        let dummy_ext_echo_reply = ExtendedEchoReplyHeader{
+            code: ExtendedEchoReplyCode::NoError,
+            id: 1,
+            seq: 2,
+            state: 2,
+            active: true,
+            ipv4: true,
+            ipv6: false,
+        };
         let tests = [
             (Unknown{type_u8: u8::MAX, code_u8: 0, bytes5to8: [0;4]}),
             (EchoReply(dummy_echo)),
@@ -1227,6 +1398,18 @@ mod icmpv4_slice {
             (TimestampReply(dummy_ts)),
             // check that a non zero code value return 8
             (Unknown{type_u8: TYPE_TIMESTAMP_REPLY, code_u8: 1, bytes5to8: [0;4]}),
+            (RouterAdvertisement(dummy_router_adv)),
+            (RouterSolicitation),
+            (InfoRequest(dummy_echo)),
+            (InfoReply(dummy_echo)),
+            (AddressMaskRequest(dummy_mask.clone())),
+            // check that a non zero code value return 8
+            (Unknown{type_u8: TYPE_ADDRESS, code_u8: 1, bytes5to8: [0;4]}),
+            (AddressMaskReply(dummy_mask)),
+            // check that a non zero code value return 8
+            (Unknown{type_u8: TYPE_ADDRESSREPLY, code_u8: 1, bytes5to8: [0;4]}),
+            (ExtendedEchoRequest(dummy_ext_echo_req)),
+            (ExtendedEchoReply(dummy_ext_echo_reply)),
         ];
         for t in tests {
             assert_eq!(
@@ -1269,7 +1452,10 @@ mod icmpv4_slice {
                 match type_u8 {
                     TYPE_ECHO_REPLY | TYPE_DEST_UNREACH | TYPE_REDIRECT |
                     TYPE_ECHO_REQUEST | TYPE_TIME_EXCEEDED | TYPE_PARAMETER_PROBLEM |
-                    TYPE_TIMESTAMP | TYPE_TIMESTAMP_REPLY => {},
+                    TYPE_TIMESTAMP | TYPE_TIMESTAMP_REPLY | TYPE_ROUTER_ADVERTISEMENT |
+                    TYPE_ROUTER_SOLICITATION | TYPE_INFO_REQUEST | TYPE_INFO_REPLY |
+                    TYPE_ADDRESS | TYPE_ADDRESSREPLY | TYPE_EXT_ECHO_REQUEST |
+                    TYPE_EXT_ECHO_REPLY => {},
                     type_u8 => {
                         assert_unknown(type_u8, base_bytes[1]);
                     }
@@ -1502,6 +1688,80 @@ mod icmpv4_slice {
                     assert_unknown(TYPE_TIMESTAMP_REPLY, unknow_code);
                 }
             }
+
+            // router advertisement, router solicitation, info request/reply
+            // & extended echo request (only code 0 is known)
+            {
+                let known = [
+                    (TYPE_ROUTER_ADVERTISEMENT, RouterAdvertisement(RouterAdvertisementHeader::from_bytes([
+                        base_bytes[4], base_bytes[5], base_bytes[6], base_bytes[7]
+                    ]))),
+                    (TYPE_ROUTER_SOLICITATION, RouterSolicitation),
+                    (TYPE_INFO_REQUEST, InfoRequest(IcmpEchoHeader::from_bytes([
+                        base_bytes[4], base_bytes[5], base_bytes[6], base_bytes[7]
+                    ]))),
+                    (TYPE_INFO_REPLY, InfoReply(IcmpEchoHeader::from_bytes([
+                        base_bytes[4], base_bytes[5], base_bytes[6], base_bytes[7]
+                    ]))),
+                    (TYPE_EXT_ECHO_REQUEST, ExtendedEchoRequest(ExtendedEchoRequestHeader::from_bytes([
+                        base_bytes[4], base_bytes[5], base_bytes[6], base_bytes[7]
+                    ]))),
+                ];
+                for (type_u8, expected) in known {
+                    let bytes = gen_bytes(type_u8, 0);
+                    let slice = Icmpv4Slice::from_slice(&bytes).unwrap();
+                    assert_eq!(slice.icmp_type(), expected);
+                    assert_eq!(slice.header_len(), 8);
+
+                    // unknown code
+                    for unknow_code in 1..=u8::MAX {
+                        assert_unknown(type_u8, unknow_code);
+                    }
+                }
+            }
+
+            // address mask request & reply
+            {
+                let msg = AddressMaskMessage::from_bytes([
+                    base_bytes[4], base_bytes[5], base_bytes[6], base_bytes[7],
+                    base_bytes[8], base_bytes[9], base_bytes[10], base_bytes[11],
+                ]);
+                for (type_u8, expected) in [
+                    (TYPE_ADDRESS, AddressMaskRequest(msg.clone())),
+                    (TYPE_ADDRESSREPLY, AddressMaskReply(msg.clone())),
+                ] {
+                    let bytes = gen_bytes(type_u8, 0);
+                    let slice = Icmpv4Slice::from_slice(&bytes).unwrap();
+                    assert_eq!(slice.icmp_type(), expected);
+                    assert_eq!(slice.header_len(), 12);
+                    assert_eq!(slice.payload(), &bytes[12..]);
+
+                    // unknown code
+                    for unknow_code in 1..=u8::MAX {
+                        assert_unknown(type_u8, unknow_code);
+                    }
+                }
+            }
+
+            // extended echo reply
+            {
+                for code_u8 in 0..=4u8 {
+                    let bytes = gen_bytes(TYPE_EXT_ECHO_REPLY, code_u8);
+                    let slice = Icmpv4Slice::from_slice(&bytes).unwrap();
+                    assert_eq!(
+                        slice.icmp_type(),
+                        ExtendedEchoReply(ExtendedEchoReplyHeader::from_bytes(
+                            ExtendedEchoReplyCode::from_u8(code_u8).unwrap(),
+                            slice.bytes5to8()
+                        ))
+                    );
+                }
+
+                // unknown code
+                for unknow_code in 5..=u8::MAX {
+                    assert_unknown(TYPE_EXT_ECHO_REPLY, unknow_code);
+                }
+            }
         }
     }
 
@@ -1566,6 +1826,11 @@ mod icmpv4_slice {
                 code: RedirectCode::RedirectForNetwork,
                 gateway_internet_address: [0;4],
             };
+            let dummy_mask = AddressMaskMessage{
+                id: 0,
+                seq: 0,
+                address_mask: [0;4],
+            };
             // tests with variable payloads
             {
                 let var_tests = [
@@ -1579,6 +1844,18 @@ mod icmpv4_slice {
                     // timestamps with non-zero code values
                     Unknown{type_u8: TYPE_TIMESTAMP, code_u8: 1, bytes5to8: [0;4]},
                     Unknown{type_u8: TYPE_TIMESTAMP_REPLY, code_u8: 1, bytes5to8: [0;4]},
+                    RouterAdvertisement(RouterAdvertisementHeader{
+                        num_addrs: 0,
+                        addr_entry_size: 2,
+                        lifetime: 0,
+                    }),
+                    RouterSolicitation,
+                    InfoRequest(dummy_echo),
+                    InfoReply(dummy_echo),
+                    // trailing bytes after an address mask are part of the payload
+                    AddressMaskRequest(dummy_mask.clone()),
+                    AddressMaskReply(dummy_mask.clone()),
+                    ExtendedEchoRequest(ExtendedEchoRequestHeader{ id: 0, seq: 0, local: false }),
                 ];
                 for t in var_tests {
 
@@ -1612,6 +1889,142 @@ mod icmpv4_slice {
         }
     }
 
+    #[test]
+    fn router_advertisement_entries() {
+        let entries = [
+            RouterAdvertisementEntry{
+                router_address: [192, 168, 1, 1],
+                preference_level: 0,
+            },
+            RouterAdvertisementEntry{
+                router_address: [192, 168, 1, 2],
+                preference_level: -1,
+            },
+        ];
+        let build = |num_addrs: u8, addr_entry_size: u8, payload: &[u8]| -> Vec<u8> {
+            let header = Icmpv4Header::with_checksum(
+                Icmpv4Type::RouterAdvertisement(RouterAdvertisementHeader{
+                    num_addrs,
+                    addr_entry_size,
+                    lifetime: 1800,
+                }),
+                payload
+            );
+            let mut bytes = Vec::new();
+            header.write(&mut bytes).unwrap();
+            bytes.extend_from_slice(payload);
+            bytes
+        };
+
+        // normal entries
+        {
+            let mut payload = Vec::new();
+            for e in &entries {
+                payload.extend_from_slice(&e.to_bytes());
+            }
+            let bytes = build(2, 2, &payload);
+            let slice = Icmpv4Slice::from_slice(&bytes).unwrap();
+            assert_eq!(
+                &entries[..],
+                &slice.router_advertisement_entries().unwrap().collect::<Vec<_>>()[..]
+            );
+        }
+
+        // bigger entries (additional words get skipped)
+        {
+            let mut payload = Vec::new();
+            for e in &entries {
+                payload.extend_from_slice(&e.to_bytes());
+                payload.extend_from_slice(&[0xff;4]);
+            }
+            let bytes = build(2, 3, &payload);
+            let slice = Icmpv4Slice::from_slice(&bytes).unwrap();
+            assert_eq!(
+                &entries[..],
+                &slice.router_advertisement_entries().unwrap().collect::<Vec<_>>()[..]
+            );
+        }
+
+        // entry size too small
+        for addr_entry_size in 0..2 {
+            let bytes = build(2, addr_entry_size, &[0;16]);
+            let slice = Icmpv4Slice::from_slice(&bytes).unwrap();
+            assert!(slice.router_advertisement_entries().is_none());
+        }
+
+        // payload too short
+        {
+            let bytes = build(2, 2, &[0;15]);
+            let slice = Icmpv4Slice::from_slice(&bytes).unwrap();
+            assert!(slice.router_advertisement_entries().is_none());
+        }
+
+        // non router advertisement
+        {
+            let bytes = Icmpv4Header::new(Icmpv4Type::RouterSolicitation).to_bytes();
+            let slice = Icmpv4Slice::from_slice(&bytes).unwrap();
+            assert!(slice.router_advertisement_entries().is_none());
+        }
+    }
+
+    #[test]
+    fn original_datagram_extensions() {
+        let datagram = [0xabu8;128];
+        let extensions = [0x20, 0, 0x7b, 0x82, 0, 8, 1, 1, 0x12, 0x34, 0x51, 0x40];
+        let mut payload = datagram.to_vec();
+        payload.extend_from_slice(&extensions);
+
+        for type_u8 in [TYPE_DEST_UNREACH, TYPE_TIME_EXCEEDED, TYPE_PARAMETER_PROBLEM] {
+            let build = |length: u8, payload: &[u8]| -> Vec<u8> {
+                let mut bytes = vec![type_u8, 0, 0, 0, 0, length, 0, 0];
+                bytes.extend_from_slice(payload);
+                bytes
+            };
+
+            // with extensions (length in 32-bit words)
+            {
+                let bytes = build(32, &payload);
+                let slice = Icmpv4Slice::from_slice(&bytes).unwrap();
+                assert_eq!(&payload[..], slice.payload());
+                assert_eq!(&datagram[..], slice.original_datagram());
+                let ext = slice.extensions().unwrap();
+                assert_eq!(&extensions[..], ext.slice());
+                assert!(ext.is_checksum_valid());
+                assert_eq!(
+                    vec![Ok(IcmpExtensionObject::MplsLabelStack(&[[0x12, 0x34, 0x51, 0x40]]))],
+                    ext.objects().collect::<Vec<_>>()
+                );
+            }
+
+            // zero length or payload too short (no extensions)
+            for (length, payload) in [(0, &payload[..]), (32, &datagram[..127])] {
+                let bytes = build(length, payload);
+                let slice = Icmpv4Slice::from_slice(&bytes).unwrap();
+                assert_eq!(payload, slice.original_datagram());
+                assert!(slice.extensions().is_none());
+            }
+
+            // no valid extension header
+            {
+                let mut bad_version = payload.clone();
+                bad_version[128] = 0x10;
+                let bytes = build(32, &bad_version);
+                let slice = Icmpv4Slice::from_slice(&bytes).unwrap();
+                assert_eq!(&datagram[..], slice.original_datagram());
+                assert!(slice.extensions().is_none());
+            }
+        }
+
+        // types without extension support
+        {
+            let mut bytes = vec![TYPE_ECHO_REQUEST, 0, 0, 0, 0, 32, 0, 0];
+            bytes.extend_from_slice(&payload);
+            let slice = Icmpv4Slice::from_slice(&bytes).unwrap();
+            assert_eq!(&payload[..], slice.original_datagram());
+            assert!(slice.extensions().is_none());
+        }
+    }
+
     proptest!{
         #[test]
         fn slice(bytes in proptest::collection::vec(any::<u8>(), 20..1024)) {
@@ -1753,7 +2166,7 @@ mod icmpv4_regression {
         use TransportSlice::*;
         let icmp4 = match echo.transport.unwrap() {
             Icmpv4(icmp4) => icmp4,
-            Icmpv6(_) | Udp(_) | Tcp(_) | Unknown(_) => panic!("Misparsed header!"),
+            Icmpv6(_) | Igmp(_) | Udp(_) | Tcp(_) | Unknown(_) => panic!("Misparsed header!"),
         };
         assert!(matches!(icmp4.icmp_type(), Icmpv4Type::EchoRequest(_)));
     }
@@ -25,6 +25,7 @@ fn constants() {
     assert_eq!(138, TYPE_ROUTER_RENUMBERING);
     assert_eq!(141, TYPE_INVERSE_NEIGHBOR_DISCOVERY_SOLICITATION);
     assert_eq!(142, TYPE_INVERSE_NEIGHBOR_DISCOVERY_ADVERTISEMENT);
+    assert_eq!(143, TYPE_MULTICAST_LISTENER_REPORT_V2);
     assert_eq!(160, TYPE_EXT_ECHO_REQUEST);
     assert_eq!(161, TYPE_EXT_ECHO_REPLY);
 
@@ -293,6 +294,8 @@ mod icmpv6_type {
                     (TYPE_PARAMETER_PROBLEM, ParameterProblem(ParameterProblemHeader{ code: ParameterProblemCode::UnrecognizedNextHeader, pointer: u32::from_be_bytes(bytes5to8)})),
                     (TYPE_ECHO_REQUEST, EchoRequest(IcmpEchoHeader::from_bytes(bytes5to8))),
                     (TYPE_ECHO_REPLY, EchoReply(IcmpEchoHeader::from_bytes(bytes5to8))),
+                    (TYPE_EXT_ECHO_REQUEST, ExtendedEchoRequest(ExtendedEchoRequestHeader::from_bytes(bytes5to8))),
+                    (TYPE_EXT_ECHO_REPLY, ExtendedEchoReply(ExtendedEchoReplyHeader::from_bytes(ExtendedEchoReplyCode::NoSuchInterface, bytes5to8))),
                 ];
                 for test in type_u8_type_pair {
                     assert_eq!(test.0, test.1.type_u8());
@@ -327,12 +330,22 @@ mod icmpv6_type {
                     (0, PacketTooBig{ mtu: u32::from_be_bytes(bytes5to8), }),
                     (0, EchoRequest(IcmpEchoHeader::from_bytes(bytes5to8))),
                     (0, EchoReply(IcmpEchoHeader::from_bytes(bytes5to8))),
+                    (0, ExtendedEchoRequest(ExtendedEchoRequestHeader::from_bytes(bytes5to8))),
                 ];
                 for test in code_type_pair {
                     assert_eq!(test.0, test.1.code_u8());
                 }
             }
 
+            // extended echo reply
+            for code_u8 in 0..=4u8 {
+                let code = ExtendedEchoReplyCode::from_u8(code_u8).unwrap();
+                assert_eq!(
+                    code_u8,
+                    ExtendedEchoReply(ExtendedEchoReplyHeader::from_bytes(code, bytes5to8)).code_u8()
+                );
+            }
+
             // destination unreachable
             for (code, code_u8) in dest_unreachable_code::VALID_VALUES {
                 assert_eq!(code_u8, DestinationUnreachable(code).code_u8());
@@ -413,7 +426,7 @@ mod icmpv6_type {
                         .add_16bytes(ip_header.destination)
                         .add_2bytes([0, ip_number::IPV6_ICMP])
                         .add_4bytes((
-                            payload.len() as u32 + icmpv6_type.header_len() as u32
+                            payload.len() as u32 + icmp_type.header_len() as u32
                         ).to_be_bytes())
                         .add_slice(&Icmpv6Header {
                             icmp_type: icmp_type.clone(),
@@ -474,6 +487,21 @@ mod icmpv6_type {
                 test_checksum_calc(EchoReply(
                     IcmpEchoHeader::from_bytes(bytes5to8)
                 ));
+
+                // extended echo request
+                test_checksum_calc(ExtendedEchoRequest(
+                    ExtendedEchoRequestHeader::from_bytes(bytes5to8)
+                ));
+
+                // extended echo reply
+                for code_u8 in 0..=4u8 {
+                    test_checksum_calc(ExtendedEchoReply(
+                        ExtendedEchoReplyHeader::from_bytes(
+                            ExtendedEchoReplyCode::from_u8(code_u8).unwrap(),
+                            bytes5to8
+                        )
+                    ));
+                }
             }
         }
     }
@@ -536,6 +564,8 @@ mod icmpv6_type {
                 }),
                 EchoRequest(IcmpEchoHeader::from_bytes(bytes5to8)),
                 EchoReply(IcmpEchoHeader::from_bytes(bytes5to8)),
+                ExtendedEchoRequest(ExtendedEchoRequestHeader::from_bytes(bytes5to8)),
+                ExtendedEchoReply(ExtendedEchoReplyHeader::from_bytes(ExtendedEchoReplyCode::NoError, bytes5to8)),
             ];
 
             for hdr in len_8_hdrs {
@@ -574,12 +604,18 @@ mod icmpv6_type {
                 }),
                 EchoRequest(IcmpEchoHeader::from_bytes(bytes5to8)),
                 EchoReply(IcmpEchoHeader::from_bytes(bytes5to8)),
+                ExtendedEchoRequest(ExtendedEchoRequestHeader::from_bytes(bytes5to8)),
             ];
 
             for hdr in variable_payload_headers {
                 assert_eq!(None, hdr.fixed_payload_size());
             }
 
+            assert_eq!(
+                Some(0),
+                ExtendedEchoReply(ExtendedEchoReplyHeader::from_bytes(ExtendedEchoReplyCode::NoError, bytes5to8)).fixed_payload_size()
+            );
+
             for t in 0..=u8::MAX {
                 assert_eq!(
                     None,
@@ -722,6 +758,10 @@ mod icmpv6_header {
             };
             let bytes = header.to_bytes();
 
+            // MLDv2 queries can not be distinguished from MLDv1
+            // queries when reading from a reader
+            prop_assume!(!matches!(icmp_type, Icmpv6Type::MulticastListenerQueryV2(_)));
+
             // ok case
             {
                 let mut cursor = std::io::Cursor::new(&bytes);
@@ -1177,6 +1217,39 @@ mod icmpv6_slice {
                     assert_unknown(TYPE_ECHO_REPLY, code_u8);
                 }
             }
+
+            // extended echo request
+            {
+                // known code
+                assert_eq!(
+                    Icmpv6Slice::from_slice(&gen_bytes(TYPE_EXT_ECHO_REQUEST, 0)).unwrap().icmp_type(),
+                    ExtendedEchoRequest(ExtendedEchoRequestHeader::from_bytes(bytes5to8))
+                );
+
+                // unknown codes
+                for code_u8 in 1..=u8::MAX {
+                    assert_unknown(TYPE_EXT_ECHO_REQUEST, code_u8);
+                }
+            }
+
+            // extended echo reply
+            {
+                // known codes
+                for code_u8 in 0..=4u8 {
+                    assert_eq!(
+                        Icmpv6Slice::from_slice(&gen_bytes(TYPE_EXT_ECHO_REPLY, code_u8)).unwrap().icmp_type(),
+                        ExtendedEchoReply(ExtendedEchoReplyHeader::from_bytes(
+                            ExtendedEchoReplyCode::from_u8(code_u8).unwrap(),
+                            bytes5to8
+                        ))
+                    );
+                }
+
+                // unknown codes
+                for code_u8 in 5..=u8::MAX {
+                    assert_unknown(TYPE_EXT_ECHO_REPLY, code_u8);
+                }
+            }
         }
     }
 
@@ -1364,6 +1437,16 @@ mod icmpv6_slice {
             use etherparse::Icmpv6Type::*;
             use etherparse::{IcmpEchoHeader, icmpv6::*};
 
+            // neighbor discovery & MLD messages with code 0 would be decoded
+            // with a bigger header (the payload is long enough)
+            let extended = (TYPE_MULTICAST_LISTENER_QUERY..=TYPE_REDIRECT_MESSAGE).contains(&type_u8) ||
+                           TYPE_MULTICAST_LISTENER_REPORT_V2 == type_u8;
+            let code_u8 = if extended && 0 == code_u8 {
+                1
+            } else {
+                code_u8
+            };
+
             let len_8_types = [
                 Unknown{
                     type_u8,
@@ -1396,6 +1479,52 @@ mod icmpv6_slice {
         }
     }
 
+    #[test]
+    fn original_datagram_extensions() {
+        use etherparse::icmpv6::*;
+
+        let datagram = [0xabu8;128];
+        let extensions = [0x20, 0, 0x7b, 0x82, 0, 8, 1, 1, 0x12, 0x34, 0x51, 0x40];
+        let mut payload = datagram.to_vec();
+        payload.extend_from_slice(&extensions);
+
+        for type_u8 in [TYPE_DST_UNREACH, TYPE_TIME_EXCEEDED] {
+            let build = |length: u8, payload: &[u8]| -> Vec<u8> {
+                let mut bytes = vec![type_u8, 0, 0, 0, length, 0, 0, 0];
+                bytes.extend_from_slice(payload);
+                bytes
+            };
+
+            // with extensions (length in 64-bit words)
+            {
+                let bytes = build(16, &payload);
+                let slice = Icmpv6Slice::from_slice(&bytes).unwrap();
+                assert_eq!(&payload[..], slice.payload());
+                assert_eq!(&datagram[..], slice.original_datagram());
+                let ext = slice.extensions().unwrap();
+                assert_eq!(&extensions[..], ext.slice());
+                assert!(ext.is_checksum_valid());
+            }
+
+            // zero length or payload too short (no extensions)
+            for (length, payload) in [(0, &payload[..]), (16, &datagram[..127])] {
+                let bytes = build(length, payload);
+                let slice = Icmpv6Slice::from_slice(&bytes).unwrap();
+                assert_eq!(payload, slice.original_datagram());
+                assert!(slice.extensions().is_none());
+            }
+        }
+
+        // types without extension support
+        {
+            let mut bytes = vec![TYPE_PARAMETER_PROBLEM, 0, 0, 0, 16, 0, 0, 0];
+            bytes.extend_from_slice(&payload);
+            let slice = Icmpv6Slice::from_slice(&bytes).unwrap();
+            assert_eq!(&payload[..], slice.original_datagram());
+            assert!(slice.extensions().is_none());
+        }
+    }
+
     #[test]
     fn debug() {
         let data = [0u8; 8];
@@ -1416,6 +1545,253 @@ mod icmpv6_slice {
     }
 }
 
+mod neighbor_discovery {
+    use super::*;
+
+    #[test]
+    fn to_bytes_from_slice() {
+        use Icmpv6Type::*;
+
+        let types = [
+            (RouterSolicitation, 8),
+            (
+                RouterAdvertisement(RouterAdvertisementHeader{
+                    cur_hop_limit: 64,
+                    managed_address_config: true,
+                    other_config: false,
+                    router_lifetime: 0x1234,
+                    reachable_time: 0x5678_9abc,
+                    retrans_timer: 0xdef0_1234,
+                }),
+                16
+            ),
+            (NeighborSolicitation(NeighborSolicitationHeader{ target_address: [1;16] }), 24),
+            (
+                NeighborAdvertisement(NeighborAdvertisementHeader{
+                    router: true,
+                    solicited: false,
+                    override_flag: true,
+                    target_address: [2;16]
+                }),
+                24
+            ),
+            (
+                Redirect(RedirectHeader{
+                    target_address: [3;16],
+                    destination_address: [4;16],
+                }),
+                40
+            ),
+        ];
+        for (t, len) in types {
+            assert_eq!(len, t.header_len());
+            assert_eq!(None, t.fixed_payload_size());
+            assert_eq!(0, t.code_u8());
+
+            let header = t.to_header([5;16], [6;16], &[7, 8]).unwrap();
+            let mut bytes = header.to_bytes().to_vec();
+            assert_eq!(len, bytes.len());
+            assert_eq!(t.type_u8(), bytes[0]);
+            bytes.extend_from_slice(&[7, 8]);
+
+            // slice
+            let slice = Icmpv6Slice::from_slice(&bytes).unwrap();
+            assert_eq!(t, slice.icmp_type());
+            assert_eq!(len, slice.header_len());
+            assert_eq!(&[7, 8], slice.payload());
+            assert!(slice.is_checksum_valid([5;16], [6;16]));
+            assert!(slice.ndp_options().is_some());
+
+            // header from slice & read
+            assert_eq!((header.clone(), &[7u8, 8][..]), Icmpv6Header::from_slice(&bytes).unwrap());
+            let mut cursor = std::io::Cursor::new(&bytes);
+            assert_eq!(header, Icmpv6Header::read(&mut cursor).unwrap());
+            assert_eq!(len as u64, cursor.position());
+
+            // too short & unknown codes are decoded as unknown
+            let bytes5to8 = [bytes[4], bytes[5], bytes[6], bytes[7]];
+            if len > 8 {
+                let too_short = Icmpv6Slice::from_slice(&bytes[..len - 1]).unwrap();
+                assert_eq!(
+                    Unknown{ type_u8: t.type_u8(), code_u8: 0, bytes5to8 },
+                    too_short.icmp_type()
+                );
+                assert_eq!(8, too_short.header_len());
+                assert_eq!(None, too_short.ndp_options());
+            }
+            bytes[1] = 1;
+            assert_eq!(
+                Unknown{ type_u8: t.type_u8(), code_u8: 1, bytes5to8 },
+                Icmpv6Slice::from_slice(&bytes).unwrap().icmp_type()
+            );
+        }
+    }
+
+    #[test]
+    fn flags() {
+        let mut bytes = Icmpv6Header::new(Icmpv6Type::NeighborAdvertisement(
+            NeighborAdvertisementHeader{
+                router: true,
+                solicited: true,
+                override_flag: true,
+                target_address: [0;16],
+            }
+        )).to_bytes();
+        assert_eq!(0b1110_0000, bytes[4]);
+
+        // reserved bits are ignored
+        bytes[4] = 0b0101_1111;
+        assert_eq!(
+            Icmpv6Type::NeighborAdvertisement(NeighborAdvertisementHeader{
+                router: false,
+                solicited: true,
+                override_flag: false,
+                target_address: [0;16],
+            }),
+            Icmpv6Slice::from_slice(&bytes).unwrap().icmp_type()
+        );
+
+        let ra = RouterAdvertisementHeader::from_bytes([0, 0b0111_1111, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        assert!(!ra.managed_address_config);
+        assert!(ra.other_config);
+        assert_eq!([0, 0b0100_0000, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0], ra.to_bytes());
+    }
+
+    #[test]
+    fn ndp_options() {
+        let mut bytes = Icmpv6Header::new(Icmpv6Type::NeighborSolicitation(
+            NeighborSolicitationHeader{ target_address: [1;16] }
+        )).to_bytes().to_vec();
+        NdpOption::SourceLinkLayerAddress(&[1, 2, 3, 4, 5, 6]).write(&mut bytes).unwrap();
+        NdpOption::Nonce(&[9;6]).write(&mut bytes).unwrap();
+
+        let slice = Icmpv6Slice::from_slice(&bytes).unwrap();
+        assert_eq!(
+            vec![
+                Ok(NdpOption::SourceLinkLayerAddress(&[1, 2, 3, 4, 5, 6])),
+                Ok(NdpOption::Nonce(&[9;6])),
+            ],
+            slice.ndp_options().unwrap().collect::<Vec<_>>()
+        );
+
+        // non neighbor discovery messages have no options
+        let echo = Icmpv6Header::new(Icmpv6Type::EchoRequest(IcmpEchoHeader{ id: 1, seq: 2 })).to_bytes();
+        assert_eq!(None, Icmpv6Slice::from_slice(&echo).unwrap().ndp_options());
+    }
+}
+
+mod multicast_listener_discovery {
+    use super::*;
+
+    #[test]
+    fn v1() {
+        use Icmpv6Type::*;
+
+        let header = MulticastListenerHeader{
+            maximum_response_delay: 0x1234,
+            multicast_address: [0xff;16],
+        };
+        for t in [MulticastListenerQuery(header), MulticastListenerReport(header), MulticastListenerDone(header)] {
+            assert_eq!(24, t.header_len());
+            assert_eq!(Some(0), t.fixed_payload_size());
+
+            let header = t.to_header([1;16], [2;16], &[]).unwrap();
+            let bytes = header.to_bytes();
+            assert_eq!(&[t.type_u8(), 0], &bytes[..2]);
+            assert_eq!(&[0x12, 0x34, 0, 0], &bytes[4..8]);
+            assert_eq!(&[0xff;16], &bytes[8..24]);
+
+            let slice = Icmpv6Slice::from_slice(&bytes).unwrap();
+            assert_eq!(t, slice.icmp_type());
+            assert!(slice.is_checksum_valid([1;16], [2;16]));
+            assert_eq!(header, Icmpv6Header::read(&mut std::io::Cursor::new(&bytes)).unwrap());
+        }
+        assert_eq!(TYPE_MULTICAST_LISTENER_QUERY, MulticastListenerQuery(header).type_u8());
+        assert_eq!(TYPE_MULTICAST_LISTENER_REPORT, MulticastListenerReport(header).type_u8());
+        assert_eq!(TYPE_MULTICAST_LISTENER_REDUCTION, MulticastListenerDone(header).type_u8());
+    }
+
+    #[test]
+    fn query_v2() {
+        let query = MulticastListenerQueryV2Header{
+            maximum_response_code: 0x8123,
+            multicast_address: [3;16],
+            suppress_router_side_processing: true,
+            querier_robustness_variable: 2,
+            querier_query_interval_code: 0x91,
+            number_of_sources: 2,
+        };
+        let t = Icmpv6Type::MulticastListenerQueryV2(query);
+        assert_eq!(28, t.header_len());
+        assert_eq!(None, t.fixed_payload_size());
+        assert_eq!(TYPE_MULTICAST_LISTENER_QUERY, t.type_u8());
+
+        let sources = [[4u8;16], [5u8;16]];
+        let payload = sources.concat();
+        let header = t.to_header([1;16], [2;16], &payload).unwrap();
+        let mut bytes = header.to_bytes().to_vec();
+        assert_eq!(&[0b1010, 0x91, 0, 2], &bytes[24..28]);
+        bytes.extend_from_slice(&payload);
+
+        let slice = Icmpv6Slice::from_slice(&bytes).unwrap();
+        assert_eq!(t, slice.icmp_type());
+        assert_eq!(28, slice.header_len());
+        assert!(slice.is_checksum_valid([1;16], [2;16]));
+        assert_eq!(Some(&sources[..]), slice.mldv2_query_sources());
+        assert_eq!(None, slice.mldv2_records());
+
+        // not enough sources
+        assert_eq!(None, Icmpv6Slice::from_slice(&bytes[..bytes.len() - 1]).unwrap().mldv2_query_sources());
+
+        // lengths between v1 & v2 are not decoded
+        for len in 25..28 {
+            assert_matches!(
+                Icmpv6Slice::from_slice(&bytes[..len]).unwrap().icmp_type(),
+                Icmpv6Type::Unknown{ .. }
+            );
+        }
+
+        // decoding of the exponential values
+        assert_eq!((0x123 | 0x1000) << 3, query.max_response_delay());
+        assert_eq!((0x1 | 0x10) << 4, query.querier_query_interval());
+        let linear = MulticastListenerQueryV2Header{
+            maximum_response_code: 0x7fff,
+            querier_query_interval_code: 0x7f,
+            ..query
+        };
+        assert_eq!(0x7fff, linear.max_response_delay());
+        assert_eq!(0x7f, linear.querier_query_interval());
+    }
+
+    #[test]
+    fn report_v2() {
+        let t = Icmpv6Type::MulticastListenerReportV2{ number_of_records: 1 };
+        assert_eq!(8, t.header_len());
+        assert_eq!(TYPE_MULTICAST_LISTENER_REPORT_V2, t.type_u8());
+
+        let record = MulticastAddressRecord{
+            record_type: mld_record_type::CHANGE_TO_EXCLUDE_MODE,
+            multicast_address: [6;16],
+            sources: &[],
+            aux_data: &[],
+        };
+        let mut payload = Vec::new();
+        record.write(&mut payload).unwrap();
+
+        let header = t.to_header([1;16], [2;16], &payload).unwrap();
+        let mut bytes = header.to_bytes().to_vec();
+        assert_eq!(&[TYPE_MULTICAST_LISTENER_REPORT_V2, 0], &bytes[..2]);
+        assert_eq!(&[0, 0, 0, 1], &bytes[4..8]);
+        bytes.extend_from_slice(&payload);
+
+        let slice = Icmpv6Slice::from_slice(&bytes).unwrap();
+        assert_eq!(t, slice.icmp_type());
+        assert!(slice.is_checksum_valid([1;16], [2;16]));
+        assert_eq!(vec![Ok(record)], slice.mldv2_records().unwrap().collect::<Vec<_>>());
+        assert_eq!(None, slice.mldv2_query_sources());
+    }
+}
+
 mod regression {
     use super::*;
 
@@ -1513,7 +1889,7 @@ mod regression {
         use TransportSlice::*;
         let icmp6 = match echo.transport.unwrap() {
             Icmpv6(icmp6) => icmp6,
-            Icmpv4(_) | Udp(_) | Tcp(_) | Unknown(_) => panic!("Misparsed header!"),
+            Icmpv4(_) | Igmp(_) | Udp(_) | Tcp(_) | Unknown(_) => panic!("Misparsed header!"),
         };
         assert!(matches!(
             icmp6.header().icmp_type,
@@ -0,0 +1,328 @@
+use super::super::*;
+
+use etherparse::igmp::*;
+use std::io::Cursor;
+
+#[test]
+fn constants() {
+    assert_eq!(0x11, TYPE_MEMBERSHIP_QUERY);
+    assert_eq!(0x12, TYPE_MEMBERSHIP_REPORT_V1);
+    assert_eq!(0x16, TYPE_MEMBERSHIP_REPORT_V2);
+    assert_eq!(0x17, TYPE_LEAVE_GROUP);
+    assert_eq!(0x22, TYPE_MEMBERSHIP_REPORT_V3);
+    assert_eq!(8, HEADER_LEN);
+    assert_eq!(12, MEMBERSHIP_QUERY_V3_HEADER_LEN);
+}
+
+mod igmp_type {
+    use super::*;
+
+    #[test]
+    fn fixed_size_messages() {
+        use IgmpType::*;
+        let values = [
+            (
+                MembershipQuery(MembershipQueryHeader{ max_response_time: 100, group_address: [224,0,0,1] }),
+                [TYPE_MEMBERSHIP_QUERY, 100, 224, 0, 0, 1],
+            ),
+            (MembershipReportV1{ group_address: [239,1,2,3] }, [TYPE_MEMBERSHIP_REPORT_V1, 0, 239, 1, 2, 3]),
+            (MembershipReportV2{ group_address: [239,1,2,3] }, [TYPE_MEMBERSHIP_REPORT_V2, 0, 239, 1, 2, 3]),
+            (LeaveGroup{ group_address: [239,1,2,3] }, [TYPE_LEAVE_GROUP, 0, 239, 1, 2, 3]),
+        ];
+        for (t, expected) in values {
+            assert_eq!(expected[0], t.type_u8());
+            assert_eq!(8, t.header_len());
+            assert_eq!(Some(0), t.fixed_payload_size());
+
+            let header = IgmpHeader::with_checksum(t, &[]);
+            let bytes = header.to_bytes();
+            assert_eq!(8, bytes.len());
+            assert_eq!(&expected[..2], &bytes[..2]);
+            assert_eq!(&expected[2..], &bytes[4..]);
+
+            // decode
+            let slice = IgmpSlice::from_slice(&bytes).unwrap();
+            assert_eq!(header, slice.header());
+            assert_eq!(8, slice.header_len());
+            assert!(slice.is_checksum_valid());
+            assert!(slice.payload().is_empty());
+            assert_eq!(header, IgmpHeader::read(&mut Cursor::new(&bytes)).unwrap());
+            assert_eq!((header.clone(), &[][..]), IgmpHeader::from_slice(&bytes).unwrap());
+        }
+    }
+
+    #[test]
+    fn query_v3() {
+        let query = MembershipQueryV3Header{
+            max_response_code: 0x8a,
+            group_address: [239,1,2,3],
+            suppress_router_side_processing: true,
+            querier_robustness_variable: 2,
+            querier_query_interval_code: 0x7d,
+            number_of_sources: 2,
+        };
+        let t = IgmpType::MembershipQueryV3(query);
+        assert_eq!(TYPE_MEMBERSHIP_QUERY, t.type_u8());
+        assert_eq!(12, t.header_len());
+        assert_eq!(None, t.fixed_payload_size());
+
+        let sources = [[10,0,0,1], [10,0,0,2]];
+        let payload = sources.concat();
+        let header = IgmpHeader::with_checksum(t, &payload);
+        let mut bytes = header.to_bytes().to_vec();
+        assert_eq!(
+            &[TYPE_MEMBERSHIP_QUERY, 0x8a, 239, 1, 2, 3, 0b1010, 0x7d, 0, 2],
+            &[&bytes[..2], &bytes[4..]].concat()[..]
+        );
+        bytes.extend_from_slice(&payload);
+
+        let slice = IgmpSlice::from_slice(&bytes).unwrap();
+        assert_eq!(header, slice.header());
+        assert_eq!(12, slice.header_len());
+        assert!(slice.is_checksum_valid());
+        assert_eq!(&payload[..], slice.payload());
+        assert_eq!(Some(&sources[..]), slice.v3_query_sources());
+        assert!(slice.v3_group_records().is_none());
+
+        // not enough sources
+        assert_eq!(None, IgmpSlice::from_slice(&bytes[..bytes.len() - 1]).unwrap().v3_query_sources());
+
+        // queries with a length of 8 are IGMPv1 or IGMPv2 queries
+        assert_eq!(
+            IgmpType::MembershipQuery(MembershipQueryHeader{ max_response_time: 0x8a, group_address: [239,1,2,3] }),
+            IgmpSlice::from_slice(&bytes[..8]).unwrap().igmp_type()
+        );
+        // lengths between v2 & v3 are not decoded
+        for len in 9..12 {
+            assert_eq!(
+                IgmpType::Unknown{ type_u8: TYPE_MEMBERSHIP_QUERY, max_response_code: 0x8a, bytes5to8: [239,1,2,3] },
+                IgmpSlice::from_slice(&bytes[..len]).unwrap().igmp_type()
+            );
+        }
+        // reading from a reader decodes the query as IGMPv1 or IGMPv2 query
+        assert_eq!(
+            IgmpType::MembershipQuery(MembershipQueryHeader{ max_response_time: 0x8a, group_address: [239,1,2,3] }),
+            IgmpHeader::read(&mut Cursor::new(&bytes)).unwrap().igmp_type
+        );
+
+        // decoding of the exponential values
+        assert_eq!((0xa | 0x10) << 3, query.max_response_time());
+        assert_eq!(0x7d, query.querier_query_interval());
+        let exp = MembershipQueryV3Header{
+            max_response_code: 0x7f,
+            querier_query_interval_code: 0xff,
+            ..query
+        };
+        assert_eq!(0x7f, exp.max_response_time());
+        assert_eq!(0x1f << 10, exp.querier_query_interval());
+    }
+
+    #[test]
+    fn report_v3() {
+        let t = IgmpType::MembershipReportV3{ number_of_records: 2 };
+        assert_eq!(TYPE_MEMBERSHIP_REPORT_V3, t.type_u8());
+        assert_eq!(8, t.header_len());
+        assert_eq!(None, t.fixed_payload_size());
+
+        let sources = [[10,0,0,1]];
+        let records = [
+            IgmpGroupRecord{
+                record_type: igmp_record_type::MODE_IS_EXCLUDE,
+                multicast_address: [239,1,2,3],
+                sources: &sources,
+                aux_data: &[1,2,3,4],
+            },
+            IgmpGroupRecord{
+                record_type: igmp_record_type::ALLOW_NEW_SOURCES,
+                multicast_address: [239,1,2,4],
+                sources: &[],
+                aux_data: &[],
+            },
+        ];
+        let mut payload = Vec::new();
+        for record in &records {
+            record.write(&mut payload).unwrap();
+        }
+        assert_eq!(records[0].serialized_len() + records[1].serialized_len(), payload.len());
+        assert_eq!(&[igmp_record_type::MODE_IS_EXCLUDE, 1, 0, 1, 239, 1, 2, 3, 10, 0, 0, 1, 1, 2, 3, 4], &payload[..16]);
+
+        let header = IgmpHeader::with_checksum(t, &payload);
+        let mut bytes = header.to_bytes().to_vec();
+        assert_eq!(&[TYPE_MEMBERSHIP_REPORT_V3, 0], &bytes[..2]);
+        assert_eq!(&[0, 0, 0, 2], &bytes[4..8]);
+        bytes.extend_from_slice(&payload);
+
+        let slice = IgmpSlice::from_slice(&bytes).unwrap();
+        assert_eq!(header, slice.header());
+        assert!(slice.is_checksum_valid());
+        assert!(slice.v3_query_sources().is_none());
+        assert_eq!(
+            records.iter().cloned().map(Ok).collect::<Vec<_>>(),
+            slice.v3_group_records().unwrap().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn unknown() {
+        // unknown type
+        let t = IgmpType::Unknown{ type_u8: 0x13, max_response_code: 1, bytes5to8: [2,3,4,5] };
+        assert_eq!(0x13, t.type_u8());
+        assert_eq!(8, t.header_len());
+        assert_eq!(None, t.fixed_payload_size());
+        let bytes = IgmpHeader::new(t).to_bytes();
+        assert_eq!(&[0x13, 1, 0, 0, 2, 3, 4, 5], &bytes[..]);
+        assert_eq!(t, IgmpSlice::from_slice(&bytes).unwrap().igmp_type());
+
+        // reports & leave messages with non zero unused fields
+        for type_u8 in [TYPE_MEMBERSHIP_REPORT_V1, TYPE_MEMBERSHIP_REPORT_V2, TYPE_LEAVE_GROUP, TYPE_MEMBERSHIP_REPORT_V3] {
+            let bytes = [type_u8, 1, 0, 0, 0, 0, 0, 0];
+            assert_eq!(
+                IgmpType::Unknown{ type_u8, max_response_code: 1, bytes5to8: [0;4] },
+                IgmpSlice::from_slice(&bytes).unwrap().igmp_type()
+            );
+        }
+        let bytes = [TYPE_MEMBERSHIP_REPORT_V3, 0, 0, 0, 1, 0, 0, 0];
+        assert_eq!(
+            IgmpType::Unknown{ type_u8: TYPE_MEMBERSHIP_REPORT_V3, max_response_code: 0, bytes5to8: [1, 0, 0, 0] },
+            IgmpSlice::from_slice(&bytes).unwrap().igmp_type()
+        );
+    }
+
+    #[test]
+    fn calc_checksum() {
+        // IGMPv2 report for 224.0.0.251 captured from a network
+        let t = IgmpType::MembershipReportV2{ group_address: [224,0,0,251] };
+        assert_eq!(0x0904, t.calc_checksum(&[]));
+    }
+}
+
+mod igmp_header {
+    use super::*;
+
+    proptest! {
+        #[test]
+        fn write_read(header in igmp_header_any()) {
+            let mut buffer = Vec::new();
+            header.write(&mut buffer).unwrap();
+            assert_eq!(header.header_len(), buffer.len());
+            assert_eq!(&header.to_bytes()[..], &buffer[..]);
+            assert_eq!((header.clone(), &[][..]), IgmpHeader::from_slice(&buffer).unwrap());
+
+            // read error
+            assert_matches!(
+                IgmpHeader::read(&mut Cursor::new(&buffer[..7])),
+                Err(ReadError::IoError(_))
+            );
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn write_error(header in igmp_header_any()) {
+            let mut a: [u8;0] = [];
+            assert_matches!(
+                header.write(&mut Cursor::new(&mut a[..])),
+                Err(WriteError::IoError(_))
+            );
+        }
+    }
+
+    #[test]
+    fn update_checksum() {
+        let mut header = IgmpHeader::new(IgmpType::MembershipReportV3{ number_of_records: 0 });
+        header.update_checksum(&[1,2,3,4]);
+        assert_eq!(IgmpHeader::with_checksum(header.igmp_type, &[1,2,3,4]), header);
+        let mut bytes = header.to_bytes().to_vec();
+        bytes.extend_from_slice(&[1,2,3,4]);
+        assert!(IgmpSlice::from_slice(&bytes).unwrap().is_checksum_valid());
+        bytes[8] = 0;
+        assert!(!IgmpSlice::from_slice(&bytes).unwrap().is_checksum_valid());
+    }
+}
+
+mod igmp_slice {
+    use super::*;
+
+    #[test]
+    fn from_slice_error() {
+        assert_matches!(
+            IgmpSlice::from_slice(&[0;7]),
+            Err(ReadError::UnexpectedEndOfSlice(8))
+        );
+    }
+
+    #[test]
+    fn accessors() {
+        let bytes = [0x13, 1, 0x12, 0x34, 2, 3, 4, 5, 6];
+        let slice = IgmpSlice::from_slice(&bytes).unwrap();
+        assert_eq!(0x13, slice.type_u8());
+        assert_eq!(1, slice.max_response_code());
+        assert_eq!(0x1234, slice.checksum());
+        assert_eq!(&[6], slice.payload());
+        assert_eq!(&bytes[..], slice.slice());
+        assert_eq!(slice.clone(), slice);
+    }
+}
+
+mod igmp_group_record {
+    use super::*;
+
+    #[test]
+    fn write_errors() {
+        let too_many = vec![[0u8;4]; usize::from(u16::MAX) + 1];
+        let bad_records = [
+            IgmpGroupRecord{ record_type: 1, multicast_address: [0;4], sources: &too_many, aux_data: &[] },
+            IgmpGroupRecord{ record_type: 1, multicast_address: [0;4], sources: &[], aux_data: &[0;3] },
+            IgmpGroupRecord{ record_type: 1, multicast_address: [0;4], sources: &[], aux_data: &[0;1024] },
+        ];
+        for record in &bad_records {
+            assert_matches!(
+                record.write(&mut Vec::new()),
+                Err(WriteError::ValueError(ValueError::IgmpGroupRecordLengthBad(_)))
+            );
+        }
+    }
+
+    #[test]
+    fn read_errors() {
+        use IgmpGroupRecordReadError::*;
+
+        // too short for the fixed part
+        {
+            let mut iter = IgmpGroupRecordsIterator::from_slice(1, &[0;7]);
+            assert_eq!(Some(Err(UnexpectedEndOfSlice{ expected_len: 8, actual_len: 7 })), iter.next());
+            assert_eq!(None, iter.next());
+        }
+        // too short for the sources & aux data
+        {
+            let bytes = [1, 1, 0, 1, 0, 0, 0, 0, 0];
+            let mut iter = IgmpGroupRecordsIterator::from_slice(2, &bytes);
+            assert_eq!(Some(Err(UnexpectedEndOfSlice{ expected_len: 16, actual_len: 9 })), iter.next());
+            assert_eq!(0, iter.rest().len());
+            assert_eq!(None, iter.next());
+        }
+        assert_eq!(
+            "IgmpGroupRecordReadError: Not enough memory left in slice to read group record (expected at least 16 bytes, only 9 bytes available).",
+            &format!("{}", UnexpectedEndOfSlice{ expected_len: 16, actual_len: 9 })
+        );
+    }
+
+    #[test]
+    fn iterator_rest_debug() {
+        let bytes = [3, 0, 0, 0, 239, 1, 2, 3, 9];
+        let mut iter = IgmpGroupRecordsIterator::from_slice(1, &bytes);
+        assert_eq!(
+            "[IgmpGroupRecord { record_type: 3, multicast_address: [239, 1, 2, 3], sources: [], aux_data: [] }]",
+            &format!("{:?}", iter)
+        );
+        assert!(iter.next().unwrap().is_ok());
+        assert_eq!(None, iter.next());
+        assert_eq!(&[9], iter.rest());
+
+        let iter = IgmpGroupRecordsIterator::from_slice(1, &bytes[..5]);
+        assert_eq!(
+            "[Err(UnexpectedEndOfSlice { expected_len: 8, actual_len: 5 })]",
+            &format!("{:?}", iter)
+        );
+    }
+}
@@ -0,0 +1,86 @@
+use super::super::*;
+
+#[test]
+fn write_read() {
+    let sources = [[1;16], [2;16]];
+    let records = [
+        MulticastAddressRecord{
+            record_type: mld_record_type::MODE_IS_INCLUDE,
+            multicast_address: [3;16],
+            sources: &sources,
+            aux_data: &[4, 5, 6, 7],
+        },
+        MulticastAddressRecord{
+            record_type: mld_record_type::BLOCK_OLD_SOURCES,
+            multicast_address: [8;16],
+            sources: &[],
+            aux_data: &[],
+        },
+    ];
+    let mut buffer = Vec::new();
+    for record in &records {
+        record.write(&mut buffer).unwrap();
+    }
+    assert_eq!(20 + 32 + 4 + 20, buffer.len());
+    assert_eq!(records[0].serialized_len() + records[1].serialized_len(), buffer.len());
+    assert_eq!(&[mld_record_type::MODE_IS_INCLUDE, 1, 0, 2], &buffer[..4]);
+
+    // the number of records limits the iterator
+    buffer.extend_from_slice(&[9, 9]);
+    let mut iter = MulticastAddressRecordsIterator::from_slice(2, &buffer);
+    assert_eq!(Some(Ok(records[0].clone())), iter.next());
+    assert_eq!(Some(Ok(records[1].clone())), iter.next());
+    assert_eq!(None, iter.next());
+    assert_eq!(&[9, 9], iter.rest());
+}
+
+#[test]
+fn write_errors() {
+    let too_many = vec![[0u8;16]; usize::from(u16::MAX) + 1];
+    let bad_records = [
+        MulticastAddressRecord{ record_type: 1, multicast_address: [0;16], sources: &too_many, aux_data: &[] },
+        MulticastAddressRecord{ record_type: 1, multicast_address: [0;16], sources: &[], aux_data: &[0;3] },
+        MulticastAddressRecord{ record_type: 1, multicast_address: [0;16], sources: &[], aux_data: &[0;1024] },
+    ];
+    for record in &bad_records {
+        assert_matches!(
+            record.write(&mut Vec::new()),
+            Err(WriteError::ValueError(ValueError::MulticastAddressRecordLengthBad(_)))
+        );
+    }
+}
+
+#[test]
+fn read_errors() {
+    use MulticastAddressRecordReadError::*;
+
+    // too short for the fixed part
+    {
+        let mut iter = MulticastAddressRecordsIterator::from_slice(1, &[0;19]);
+        assert_eq!(Some(Err(UnexpectedEndOfSlice{ expected_len: 20, actual_len: 19 })), iter.next());
+        assert_eq!(None, iter.next());
+    }
+    // too short for the sources & aux data
+    {
+        let mut bytes = [0u8;20];
+        bytes[1] = 1;
+        bytes[3] = 1;
+        let mut iter = MulticastAddressRecordsIterator::from_slice(2, &bytes);
+        assert_eq!(Some(Err(UnexpectedEndOfSlice{ expected_len: 40, actual_len: 20 })), iter.next());
+        assert_eq!(0, iter.rest().len());
+        assert_eq!(None, iter.next());
+    }
+    assert_eq!(
+        "MulticastAddressRecordReadError: Not enough memory left in slice to read multicast address record (expected at least 40 bytes, only 20 bytes available).",
+        &format!("{}", UnexpectedEndOfSlice{ expected_len: 40, actual_len: 20 })
+    );
+}
+
+#[test]
+fn debug() {
+    let iter = MulticastAddressRecordsIterator::from_slice(1, &[0;5]);
+    assert_eq!(
+        "[Err(UnexpectedEndOfSlice { expected_len: 20, actual_len: 5 })]",
+        &format!("{:?}", iter)
+    );
+}
@@ -1,6 +1,11 @@
 pub mod icmp;
+pub mod icmp_embedded_packet;
+pub mod icmp_extensions;
 pub mod icmpv4;
 pub mod icmpv6;
+pub mod igmp;
+pub mod mld;
+pub mod ndp_options;
 pub mod udp;
 pub mod tcp;
 
@@ -16,6 +21,7 @@ mod transport_header {
             udp in udp_any(),
             icmpv4 in icmpv4_header_any(),
             icmpv6 in icmpv6_header_any(),
+            igmp in igmp_header_any(),
         ) {
             use TransportHeader::*;
             assert_eq!(
@@ -34,6 +40,10 @@ mod transport_header {
                 format!("Icmpv6({:?})", icmpv6),
                 format!("{:?}", Icmpv6(icmpv6.clone())),
             );
+            assert_eq!(
+                format!("Igmp({:?})", igmp),
+                format!("{:?}", Igmp(igmp.clone())),
+            );
         }
     }
 
@@ -44,6 +54,7 @@ mod transport_header {
             udp in udp_any(),
             icmpv4 in icmpv4_header_any(),
             icmpv6 in icmpv6_header_any(),
+            igmp in igmp_header_any(),
         ) {
             use TransportHeader::*;
             let values = [
@@ -51,6 +62,7 @@ mod transport_header {
                 Tcp(tcp),
                 Icmpv4(icmpv4),
                 Icmpv6(icmpv6),
+                Igmp(igmp),
             ];
             for value in values {
                 assert_eq!(value.clone(), value);
@@ -110,6 +122,20 @@ mod transport_header {
             assert_eq!(None, TransportHeader::Udp(Default::default()).mut_icmpv6());
         }
     }
+    proptest! {
+        #[test]
+        fn igmp(igmp in igmp_header_any()) {
+            assert_eq!(Some(igmp.clone()), TransportHeader::Igmp(igmp).igmp());
+            assert_eq!(None, TransportHeader::Udp(Default::default()).igmp());
+        }
+    }
+    proptest! {
+        #[test]
+        fn mut_igmp(igmp in igmp_header_any()) {
+            assert_eq!(Some(&mut igmp.clone()), TransportHeader::Igmp(igmp).mut_igmp());
+            assert_eq!(None, TransportHeader::Udp(Default::default()).mut_igmp());
+        }
+    }
     proptest! {
         #[test]
         fn header_size(
@@ -117,6 +143,7 @@ mod transport_header {
             tcp in tcp_any(),
             icmpv4 in icmpv4_header_any(),
             icmpv6 in icmpv6_header_any(),
+            igmp in igmp_header_any(),
         ) {
             assert_eq!(
                 TransportHeader::Udp(udp).header_len(), 
@@ -134,6 +161,10 @@ mod transport_header {
                 TransportHeader::Icmpv6(icmpv6.clone()).header_len(), 
                 icmpv6.header_len()
             );
+            assert_eq!(
+                TransportHeader::Igmp(igmp.clone()).header_len(), 
+                igmp.header_len()
+            );
         }
     }
     proptest! {
@@ -144,6 +175,7 @@ mod transport_header {
             tcp in tcp_any(),
             icmpv4 in icmpv4_header_any(),
             icmpv6 in icmpv6_header_any(),
+            igmp in igmp_header_any(),
         ) {
             use TransportHeader::*;
 
@@ -213,6 +245,17 @@ mod transport_header {
                 );
             }
 
+            // igmp
+            {
+                let mut transport = Igmp(igmp.clone());
+                let payload = [1, 2, 3, 4];
+                transport.update_checksum_ipv4(&ipv4, &payload).unwrap();
+                assert_eq!(
+                    transport.igmp().unwrap().checksum, 
+                    igmp.igmp_type.calc_checksum(&payload)
+                );
+            }
+
             // icmpv6 (error)
             assert_eq!(
                 Icmpv6(icmpv6).update_checksum_ipv4(&ipv4, &[]),
@@ -230,6 +273,7 @@ mod transport_header {
             tcp in tcp_any(),
             icmpv4 in icmpv4_header_any(),
             icmpv6 in icmpv6_header_any(),
+            igmp in igmp_header_any(),
         ) {
             use TransportHeader::*;
 
@@ -302,6 +346,17 @@ mod transport_header {
                 );
             }
 
+            // igmp
+            {
+                let mut transport = Igmp(igmp.clone());
+                let payload = [1, 2, 3, 4];
+                transport.update_checksum_ipv6(&ipv6, &payload).unwrap();
+                assert_eq!(
+                    transport.igmp().unwrap().checksum, 
+                    igmp.igmp_type.calc_checksum(&payload)
+                );
+            }
+
             // icmpv6
             {
                 // normal case
@@ -346,6 +401,7 @@ mod transport_header {
             tcp in tcp_any(),
             icmpv4 in icmpv4_header_any(),
             icmpv6 in icmpv6_header_any(),
+            igmp in igmp_header_any(),
         ) {
             // udp
             {
@@ -447,6 +503,33 @@ mod transport_header {
                     );
                 }
             }
+
+            // igmp
+            {
+                // normal write
+                {
+                    let result_input = {
+                        let mut buffer = Vec::new();
+                        igmp.write(&mut buffer).unwrap();
+                        buffer
+                    };
+                    let result_transport = {
+                        let mut buffer = Vec::new();
+                        TransportHeader::Igmp(igmp.clone()).write(&mut buffer).unwrap();
+                        buffer
+                    };
+                    assert_eq!(result_input, result_transport);
+                }
+
+                // error during write
+                {
+                    let mut a: [u8;0] = [];
+                    assert_matches!(
+                        TransportHeader::Igmp(igmp.clone()).write(&mut Cursor::new(&mut a[..])),
+                        Err(WriteError::IoError(_))
+                    );
+                }
+            }
         }
     }
-}
\ No newline at end of file
+}
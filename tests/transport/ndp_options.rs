@@ -0,0 +1,110 @@
+use super::super::*;
+
+#[test]
+fn write_read() {
+    use NdpOption::*;
+
+    let prefix = NdpPrefixInformation{
+        prefix_length: 64,
+        on_link: true,
+        autonomous: false,
+        valid_lifetime: 0x1234_5678,
+        preferred_lifetime: 0x9abc_def0,
+        prefix: [0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+    };
+    let addresses = [[1;16], [2;16]];
+    let options = [
+        (SourceLinkLayerAddress(&[1, 2, 3, 4, 5, 6]), 8),
+        (TargetLinkLayerAddress(&[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14]), 16),
+        (PrefixInformation(prefix), 32),
+        (RedirectedHeader(&[3;16]), 24),
+        (Mtu(1500), 8),
+        (Nonce(&[4;6]), 8),
+        (RecursiveDnsServer{ lifetime: 0x1122_3344, addresses: &addresses }, 40),
+        (DnsSearchList{ lifetime: 0x5566_7788, domain_names: &[7, b'e', b'x', b'a', b'm', b'p', b'l', b'e', 0, 0, 0, 0, 0, 0, 0, 0] }, 24),
+        (Unknown{ option_type: 200, data: &[5;14] }, 16),
+    ];
+
+    let mut buffer = Vec::new();
+    for (option, len) in &options {
+        assert_eq!(*len, option.serialized_len());
+        let bytes = option.to_bytes().unwrap();
+        assert_eq!(*len, bytes.len());
+        assert_eq!(option.option_type(), bytes[0]);
+        assert_eq!((*len / 8) as u8, bytes[1]);
+        option.write(&mut buffer).unwrap();
+    }
+    assert_eq!(
+        options.iter().map(|(o, _)| Ok(o.clone())).collect::<Vec<_>>(),
+        NdpOptionsIterator::from_slice(&buffer).collect::<Vec<_>>()
+    );
+
+    // prefix information layout
+    assert_eq!(
+        &[
+            ndp_option::TYPE_PREFIX_INFORMATION, 4, 64, 0b1000_0000,
+            0x12, 0x34, 0x56, 0x78,
+            0x9a, 0xbc, 0xde, 0xf0,
+            0, 0, 0, 0,
+        ],
+        &PrefixInformation(prefix).to_bytes().unwrap()[..16]
+    );
+
+    // padding is part of the decoded data
+    let padded = Nonce(&[1, 2, 3]).to_bytes().unwrap();
+    assert_eq!(
+        Some(Ok(Nonce(&[1, 2, 3, 0, 0, 0]))),
+        NdpOptionsIterator::from_slice(&padded).next()
+    );
+
+    // too big
+    assert_eq!(
+        Err(ValueError::NdpOptionDataLengthBad(2048)),
+        Unknown{ option_type: 1, data: &[0;2040] }.to_bytes()
+    );
+    assert_matches!(
+        Unknown{ option_type: 1, data: &[0;2040] }.write(&mut Vec::new()),
+        Err(WriteError::ValueError(ValueError::NdpOptionDataLengthBad(2048)))
+    );
+}
+
+#[test]
+fn read_errors() {
+    use NdpOptionReadError::*;
+    use ndp_option::*;
+
+    let errors = [
+        (&[1u8][..], UnexpectedEndOfSlice{ option_type: 1, expected_len: 2, actual_len: 1 }),
+        (&[1, 0, 0, 0, 0, 0, 0, 0], UnexpectedSize{ option_type: 1, size: 0 }),
+        (&[1, 2, 0, 0, 0, 0, 0, 0], UnexpectedEndOfSlice{ option_type: 1, expected_len: 16, actual_len: 8 }),
+        (&[TYPE_PREFIX_INFORMATION, 1, 0, 0, 0, 0, 0, 0], UnexpectedSize{ option_type: TYPE_PREFIX_INFORMATION, size: 1 }),
+        (&[TYPE_MTU, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0], UnexpectedSize{ option_type: TYPE_MTU, size: 2 }),
+        (&[TYPE_RECURSIVE_DNS_SERVER, 1, 0, 0, 0, 0, 0, 0], UnexpectedSize{ option_type: TYPE_RECURSIVE_DNS_SERVER, size: 1 }),
+        (&[TYPE_RECURSIVE_DNS_SERVER, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0], UnexpectedSize{ option_type: TYPE_RECURSIVE_DNS_SERVER, size: 2 }),
+        (&[TYPE_DNS_SEARCH_LIST, 1, 0, 0, 0, 0, 0, 0], UnexpectedSize{ option_type: TYPE_DNS_SEARCH_LIST, size: 1 }),
+    ];
+    for (bytes, expected) in &errors {
+        let mut iter = NdpOptionsIterator::from_slice(bytes);
+        assert_eq!(Some(Err(expected.clone())), iter.next());
+        assert_eq!(0, iter.rest().len());
+        assert_eq!(None, iter.next());
+    }
+
+    assert_eq!(
+        "NdpOptionReadError: Not enough memory left in slice to read option of type 1 (expected at least 16 bytes, only 8 bytes available).",
+        &format!("{}", UnexpectedEndOfSlice{ option_type: 1, expected_len: 16, actual_len: 8 })
+    );
+    assert_eq!(
+        "NdpOptionReadError: Length value of the option of type 3 had unexpected value 1.",
+        &format!("{}", UnexpectedSize{ option_type: 3, size: 1 })
+    );
+}
+
+#[test]
+fn debug() {
+    let iter = NdpOptionsIterator::from_slice(&[ndp_option::TYPE_MTU, 1, 0, 0, 0, 0, 0x05, 0xdc, 1]);
+    assert_eq!(
+        "[Mtu(1500), Err(UnexpectedEndOfSlice { option_type: 1, expected_len: 2, actual_len: 1 })]",
+        &format!("{:?}", iter)
+    );
+}
@@ -1,5 +1,6 @@
 use super::super::*;
 
+use arrayvec::ArrayVec;
 use std::io::Cursor;
 use proptest::prelude::*;
 use std::slice;
@@ -235,6 +236,8 @@ mod header {
             use TcpOptionWriteError::*;
             let value = NotEnoughSpace(123);
             assert_eq!(value, value.clone());
+            let value = UserTimeoutTooLarge(0x8000);
+            assert_eq!(value, value.clone());
         }
         // TcpOptionsIterator
         {
@@ -384,6 +387,10 @@ mod header {
                 "NotEnoughSpace(0)",
                 format!("{:?}", NotEnoughSpace(0))
             );
+            assert_eq!(
+                "UserTimeoutTooLarge(32768)",
+                format!("{:?}", UserTimeoutTooLarge(0x8000))
+            );
         }
         // TcpOptionsIterator
         {
@@ -628,6 +635,92 @@ mod header {
             );
         }
     }
+    #[test]
+    fn set_options_additional_kinds() {
+        use crate::TcpOptionElement::*;
+        use tcp_option::*;
+        let elements = [
+            Md5Signature([1,2,3,4,5,6,7,8,9,10,11,12,13,14,15,16]),
+            UserTimeout{ granularity_minutes: true, timeout: 0x1234 },
+            FastOpenCookie([17,18,19,20].iter().copied().collect()),
+            Noop,
+        ];
+        let header = write_options(&elements);
+        assert_eq!(
+            header.options(),
+            &[
+                KIND_MD5_SIGNATURE, 18, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16,
+                KIND_USER_TIMEOUT, 4, 0x92, 0x34,
+                KIND_FAST_OPEN_COOKIE, 6, 17, 18, 19, 20,
+                KIND_NOOP, KIND_END, 0, 0,
+            ]
+        );
+        assert_eq!(
+            &elements[..],
+            &header.options_iterator().collect::<Result<Vec<_>, _>>().unwrap()[..]
+        );
+
+        let elements = [
+            Authentication{ key_id: 1, rnext_key_id: 2, mac: [3,4,5,6,7,8,9,10,11,12,13,14].iter().copied().collect() },
+            MultipathTcp{ subtype: mptcp_subtype::DSS, flags: 0x1, data: [0x5, 1, 2, 3, 4].iter().copied().collect() },
+            FastOpenCookie(ArrayVec::new()),
+            Unknown{ kind: 253, data: [0x12, 0x34, 5, 6, 7].iter().copied().collect() },
+        ];
+        let header = write_options(&elements);
+        assert_eq!(
+            header.options(),
+            &[
+                KIND_AUTHENTICATION, 16, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14,
+                KIND_MULTIPATH_TCP, 8, 0x21, 0x5, 1, 2, 3, 4,
+                KIND_FAST_OPEN_COOKIE, 2,
+                253, 7, 0x12, 0x34, 5, 6, 7, KIND_END, 0, 0,
+            ]
+        );
+        assert_eq!(
+            &elements[..],
+            &header.options_iterator().collect::<Result<Vec<_>, _>>().unwrap()[..]
+        );
+
+        // out of range multipath subtypes get masked
+        assert_eq!(
+            write_options(&[
+                UserTimeout{ granularity_minutes: false, timeout: MAX_USER_TIMEOUT },
+                MultipathTcp{ subtype: 0x12, flags: 0x34, data: ArrayVec::new() },
+            ]).options(),
+            &[KIND_USER_TIMEOUT, 4, 0x7f, 0xff, KIND_MULTIPATH_TCP, 3, 0x24, KIND_END]
+        );
+
+        // user timeouts that do not fit into 15 bits get rejected
+        {
+            let mut header = write_options(&[Noop]);
+            for timeout in [MAX_USER_TIMEOUT + 1, 0xffff].iter().copied() {
+                assert_eq!(
+                    Err(TcpOptionWriteError::UserTimeoutTooLarge(timeout)),
+                    header.set_options(&[
+                        UserTimeout{ granularity_minutes: true, timeout },
+                    ])
+                );
+            }
+            // header is left unchanged
+            assert_eq!(write_options(&[Noop]), header);
+        }
+
+        // maximum sizes
+        assert_eq!(
+            Err(TcpOptionWriteError::NotEnoughSpace(41)),
+            TcpHeader::default().set_options(&[
+                Noop,
+                Unknown{ kind: 253, data: [0;MAX_UNKNOWN_DATA_LEN].iter().copied().collect() },
+            ])
+        );
+        assert_eq!(
+            40,
+            write_options(&[
+                Authentication{ key_id: 1, rnext_key_id: 2, mac: [0;MAX_AUTHENTICATION_MAC_LEN].iter().copied().collect() },
+            ]).options_len()
+        );
+    }
+
     #[test]
     fn set_option_padding() {
         use crate::TcpOptionElement::*;
@@ -1435,8 +1528,8 @@ fn options_iterator_unexpected_length() {
 }
 
 #[test]
-fn options_iterator_unexpected_id() {
-    let data = [255, 2, 0, 0, 0,
+fn options_iterator_unknown_id() {
+    let data = [255, 4, 1, 2, 0,
                 0, 0, 0, 0, 0, //10
                 0, 0, 0, 0, 0,
                 0, 0, 0, 0, 0, //20
@@ -1444,13 +1537,50 @@ fn options_iterator_unexpected_id() {
                 0, 0, 0, 0, 0, //30
                 0, 0, 0, 0];
     let mut it = TcpOptionsIterator::from_slice(&data);
-    assert_eq!(Some(Err(TcpOptionReadError::UnknownId(255))), it.next());
-    //expect the iterator slice to be moved to the end
-    assert_eq!(0, it.rest().len());
+    assert_eq!(
+        Some(Ok(TcpOptionElement::Unknown{ kind: 255, data: [1, 2].iter().copied().collect() })),
+        it.next()
+    );
+    assert_eq!(&data[4..], it.rest());
+    //expect the iterator slice to be moved to the end after the end option
     assert_eq!(None, it.next());
     assert_eq!(0, it.rest().len());
 }
 
+#[test]
+fn options_iterator_variable_size_errors() {
+    use tcp_option::*;
+    use TcpOptionReadError::*;
+    fn expect_error(data: &[u8], error: TcpOptionReadError) {
+        let mut it = TcpOptionsIterator::from_slice(data);
+        assert_eq!(Some(Err(error)), it.next());
+        //expect the iterator slice to be moved to the end
+        assert_eq!(0, it.rest().len());
+        assert_eq!(None, it.next());
+    }
+    for kind in &[KIND_AUTHENTICATION, KIND_MULTIPATH_TCP, KIND_FAST_OPEN_COOKIE, 255] {
+        // length field missing
+        expect_error(&[*kind], UnexpectedEndOfSlice{ option_id: *kind, expected_len: 2, actual_len: 1 });
+        // option longer then the slice
+        expect_error(&[*kind, 8, 0, 0, 0, 0, 0], UnexpectedEndOfSlice{ option_id: *kind, expected_len: 8, actual_len: 7 });
+        // option longer then the tcp options can be
+        expect_error(&[*kind, 41], UnexpectedSize{ option_id: *kind, size: 41 });
+    }
+    expect_error(&[255, 1], UnexpectedSize{ option_id: 255, size: 1 });
+    expect_error(&[KIND_AUTHENTICATION, 3, 0], UnexpectedSize{ option_id: KIND_AUTHENTICATION, size: 3 });
+    expect_error(&[KIND_MULTIPATH_TCP, 2], UnexpectedSize{ option_id: KIND_MULTIPATH_TCP, size: 2 });
+    for size in &[1, 3, 4, 5, 19] {
+        expect_error(
+            &[KIND_FAST_OPEN_COOKIE, *size, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+            UnexpectedSize{ option_id: KIND_FAST_OPEN_COOKIE, size: *size }
+        );
+    }
+    expect_error(&[KIND_MD5_SIGNATURE, 17, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0], UnexpectedSize{ option_id: KIND_MD5_SIGNATURE, size: 17 });
+    expect_error(&[KIND_MD5_SIGNATURE, 18, 0], UnexpectedEndOfSlice{ option_id: KIND_MD5_SIGNATURE, expected_len: 18, actual_len: 3 });
+    expect_error(&[KIND_USER_TIMEOUT, 5, 0, 0, 0], UnexpectedSize{ option_id: KIND_USER_TIMEOUT, size: 5 });
+    expect_error(&[KIND_USER_TIMEOUT, 4, 0], UnexpectedEndOfSlice{ option_id: KIND_USER_TIMEOUT, expected_len: 4, actual_len: 3 });
+}
+
 #[test]
 fn options_iterator_debug() {
     fn expect_elements(buffer: &[u8], expected: &[TcpOptionElement]) {
@@ -1568,7 +1698,8 @@ proptest! {
 proptest! {
     #[test]
     fn tcp_options_write_error_display(
-        arg_usize in any::<usize>()
+        arg_usize in any::<usize>(),
+        arg_u16 in any::<u16>()
     ) {
 
         use crate::TcpOptionWriteError::*;
@@ -1578,6 +1709,12 @@ proptest! {
             &format!("TcpOptionWriteError: Not enough memory to store all options in the options section of a tcp header (maximum 40 bytes can be stored, the options would have needed {} bytes).", arg_usize),
             &format!("{}", NotEnoughSpace(arg_usize))
         );
+
+        //UserTimeoutTooLarge
+        assert_eq!(
+            &format!("TcpOptionWriteError: The user timeout {} is bigger then the maximum allowed value of 32767.", arg_u16),
+            &format!("{}", UserTimeoutTooLarge(arg_u16))
+        );
     }
 }
 
@@ -1591,5 +1728,6 @@ proptest! {
 
         //NotEnoughSpace
         assert!(NotEnoughSpace(arg_usize).source().is_none());
+        assert!(UserTimeoutTooLarge(0x8000).source().is_none());
     }
 }
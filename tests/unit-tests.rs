@@ -17,6 +17,16 @@ mod packet_builder;
 mod packet_decoder;
 mod packet_filter;
 mod packet_slicing;
+mod icmp_reply;
+mod neighbor_cache;
+mod tcp_teardown;
+mod tcp_reassembly;
+mod tcp_tracking;
+mod flow_key;
+mod flow_table;
+mod flow_export;
+mod flow_decoder;
+mod sflow;
 mod proptest_generators;
 pub use crate::proptest_generators::*;
 use proptest::prelude::*;